@@ -30,6 +30,8 @@ pub struct App {
     pub resources: Resources,
     pub runner: Box<dyn Fn(App)>,
     pub schedule: Schedule,
+    /// Runs once when the app exits, after the last update. See [App::shutdown].
+    pub shutdown_schedule: Schedule,
 }
 
 impl Default for App {
@@ -38,6 +40,7 @@ impl Default for App {
             world: Default::default(),
             resources: Default::default(),
             schedule: Default::default(),
+            shutdown_schedule: Default::default(),
             runner: Box::new(run_once),
         }
     }
@@ -45,6 +48,7 @@ impl Default for App {
 
 fn run_once(mut app: App) {
     app.update();
+    app.shutdown();
 }
 
 impl App {
@@ -57,6 +61,20 @@ impl App {
             .initialize_and_run(&mut self.world, &mut self.resources);
     }
 
+    /// Runs the shutdown systems, which flush saves and release resources before the app is
+    /// dropped. Runners call this once, when the app exits.
+    pub fn shutdown(&mut self) {
+        self.shutdown_schedule
+            .initialize_and_run(&mut self.world, &mut self.resources);
+    }
+
+    /// The [AppExitCode] the process should exit with
+    pub fn exit_code(&self) -> i32 {
+        self.resources
+            .get::<AppExitCode>()
+            .map_or(0, |exit_code| exit_code.0)
+    }
+
     pub fn run(mut self) {
         #[cfg(feature = "trace")]
         let bevy_app_run_span = info_span!("bevy_app");
@@ -66,8 +84,55 @@ impl App {
         let runner = std::mem::replace(&mut self.runner, Box::new(run_once));
         (runner)(self);
     }
+
+    /// Returns the stages and systems of the app's schedule in the Graphviz DOT format. See
+    /// [Schedule::dot].
+    pub fn schedule_graphviz(&self) -> String {
+        self.schedule.dot()
+    }
 }
 
 /// An event that indicates the app should exit. This will fully exit the app process.
 #[derive(Debug, Clone)]
 pub struct AppExit;
+
+/// The code the process exits with when the app exits, so headless runs like tests in CI can
+/// report a failure. Set it before sending [AppExit]. Runners that own the process exit it with
+/// this code when it is not zero, after running the shutdown systems.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AppExitCode(pub i32);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shutdown_stage;
+    use bevy_ecs::{IntoSystem, ResMut};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn shutdown_systems_run_in_order() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let push = |log: &Arc<Mutex<Vec<&'static str>>>, name| {
+            let log = log.clone();
+            move || log.lock().unwrap().push(name)
+        };
+        let mut app = App::build();
+        app.add_system(push(&log, "update").system())
+            .add_shutdown_system_to_stage(
+                shutdown_stage::POST_SHUTDOWN,
+                push(&log, "release").system(),
+            )
+            .add_shutdown_system(push(&log, "flush").system())
+            .add_shutdown_system(push(&log, "join").system())
+            .add_system((|mut exit_code: ResMut<AppExitCode>| exit_code.0 = 3).system());
+        let mut app = app.app;
+        app.update();
+        assert_eq!(app.exit_code(), 3);
+        assert_eq!(*log.lock().unwrap(), vec!["update"]);
+        app.shutdown();
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["update", "flush", "join", "release"]
+        );
+    }
+}
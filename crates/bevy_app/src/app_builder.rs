@@ -1,12 +1,13 @@
 use crate::{
-    app::{App, AppExit},
-    event::Events,
+    app::{App, AppExit, AppExitCode},
+    entity_event::EntityEvents,
+    event::{EventOverflow, Events},
     plugin::Plugin,
-    stage, startup_stage, PluginGroup, PluginGroupBuilder,
+    shutdown_stage, stage, startup_stage, PluginGroup, PluginGroupBuilder,
 };
 use bevy_ecs::{
-    clear_trackers_system, FromResources, IntoSystem, Resource, Resources, RunOnce, Schedule,
-    Stage, StateStage, System, SystemStage, World,
+    clear_trackers_system, Component, FromResources, IntoSystem, Resource, Resources, RunOnce,
+    Schedule, SparseStorage, Stage, StateStage, System, SystemStage, World,
 };
 use bevy_utils::tracing::debug;
 
@@ -24,6 +25,7 @@ impl Default for AppBuilder {
         app_builder
             .add_default_stages()
             .add_event::<AppExit>()
+            .init_resource::<AppExitCode>()
             .add_system_to_stage(stage::LAST, clear_trackers_system.system());
         app_builder
     }
@@ -179,7 +181,29 @@ impl AppBuilder {
         self.add_startup_system_to_stage(startup_stage::STARTUP, system)
     }
 
+    /// Adds a system that runs once when the app exits, in [shutdown_stage::SHUTDOWN]
+    pub fn add_shutdown_system<S: System<In = (), Out = ()>>(&mut self, system: S) -> &mut Self {
+        self.add_shutdown_system_to_stage(shutdown_stage::SHUTDOWN, system)
+    }
+
+    pub fn add_shutdown_system_to_stage<S: System<In = (), Out = ()>>(
+        &mut self,
+        stage_name: &'static str,
+        system: S,
+    ) -> &mut Self {
+        self.app
+            .shutdown_schedule
+            .add_system_to_stage(stage_name, system);
+        self
+    }
+
     pub fn add_default_stages(&mut self) -> &mut Self {
+        // shutdown systems run in the order they were added, so resources are released in a
+        // predictable order
+        self.app
+            .shutdown_schedule
+            .add_stage(shutdown_stage::SHUTDOWN, SystemStage::serial())
+            .add_stage(shutdown_stage::POST_SHUTDOWN, SystemStage::serial());
         self.add_stage(
             stage::STARTUP,
             Schedule::default()
@@ -214,6 +238,39 @@ impl AppBuilder {
             .add_system_to_stage(stage::EVENT, Events::<T>::update_system.system())
     }
 
+    /// Like [AppBuilder::add_event], except at most `capacity` events of type `T` are kept per
+    /// frame. See [Events::bounded].
+    pub fn add_bounded_event<T>(&mut self, capacity: usize, overflow: EventOverflow<T>) -> &mut Self
+    where
+        T: Send + Sync + 'static,
+    {
+        self.add_resource(Events::<T>::bounded(capacity, overflow))
+            .add_system_to_stage(stage::EVENT, Events::<T>::update_system.system())
+    }
+
+    /// Adds [EntityEvents] of type `T`, which are updated once per frame like events added with
+    /// [AppBuilder::add_event].
+    pub fn add_entity_event<T>(&mut self) -> &mut Self
+    where
+        T: Send + Sync + 'static,
+    {
+        self.add_resource(EntityEvents::<T>::default())
+            .add_system_to_stage(stage::EVENT, EntityEvents::<T>::update_system.system())
+    }
+
+    /// Stores `T` components in a [SparseStorage] resource, and removes the components of despawned
+    /// entities at the end of each frame. See [Commands::insert_sparse](bevy_ecs::Commands::insert_sparse).
+    pub fn add_sparse_component<T>(&mut self) -> &mut Self
+    where
+        T: Component,
+    {
+        self.add_resource(SparseStorage::<T>::default())
+            .add_system_to_stage(
+                stage::LAST,
+                SparseStorage::<T>::remove_despawned_system.system(),
+            )
+    }
+
     /// Adds a resource to the current [App] and overwrites any resource previously added of the same type.
     pub fn add_resource<T>(&mut self, resource: T) -> &mut Self
     where
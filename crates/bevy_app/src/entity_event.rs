@@ -0,0 +1,200 @@
+use crate::event::{EventReader, Events};
+use bevy_ecs::{Entity, Fetch, Query, QueryFilter, ReadOnlyFetch, ResMut, WorldQuery};
+
+/// Events that are addressed to a specific entity, such as `TileDamaged` for a tile entity.
+///
+/// Like [Events], this is double buffered and should be updated once per frame, which
+/// [AppBuilder::add_entity_event](crate::AppBuilder::add_entity_event) takes care of. Instead of
+/// scanning every event and looking for the entities it cares about, a system reads the events
+/// addressed to the entities matched by one of its queries with an [EntityEventReader].
+///
+/// # Example
+/// ```
+/// use bevy_app::{EntityEventReader, EntityEvents};
+/// use bevy_ecs::{Local, Mut, Query, Res, With};
+///
+/// struct TileDamaged {
+///     amount: u32,
+/// }
+///
+/// struct Tile;
+/// struct Health(u32);
+///
+/// fn damage_tiles(
+///     mut reader: Local<EntityEventReader<TileDamaged>>,
+///     events: Res<EntityEvents<TileDamaged>>,
+///     mut tiles: Query<&mut Health, With<Tile>>,
+/// ) {
+///     reader.for_each_matching_mut(&events, &mut tiles, |mut health: Mut<Health>, event| {
+///         health.0 = health.0.saturating_sub(event.amount);
+///     });
+/// }
+/// ```
+#[derive(Debug)]
+pub struct EntityEvents<T> {
+    events: Events<(Entity, T)>,
+}
+
+impl<T> Default for EntityEvents<T> {
+    fn default() -> Self {
+        EntityEvents {
+            events: Events::default(),
+        }
+    }
+}
+
+impl<T: bevy_ecs::Resource> EntityEvents<T> {
+    /// Sends `event` to `entity`
+    pub fn send(&mut self, entity: Entity, event: T) {
+        self.events.send((entity, event));
+    }
+
+    /// Swaps the event buffers and clears the oldest event buffer. In general, this should be
+    /// called once per frame/update.
+    pub fn update(&mut self) {
+        self.events.update();
+    }
+
+    /// A system that calls [EntityEvents::update] once per frame.
+    pub fn update_system(mut events: ResMut<Self>) {
+        events.update();
+    }
+
+    /// Removes all events.
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    /// Gets a new [EntityEventReader]. This will ignore all events already in the event buffers.
+    pub fn get_reader_current(&self) -> EntityEventReader<T> {
+        EntityEventReader {
+            reader: self.events.get_reader_current(),
+        }
+    }
+}
+
+/// Reads [EntityEvents] in order and tracks which events have already been read.
+pub struct EntityEventReader<T> {
+    reader: EventReader<(Entity, T)>,
+}
+
+impl<T> Default for EntityEventReader<T> {
+    fn default() -> Self {
+        EntityEventReader {
+            reader: Default::default(),
+        }
+    }
+}
+
+impl<T> EntityEventReader<T> {
+    /// Iterates over the events this reader has not seen yet, and the entities they were sent to.
+    pub fn iter<'a>(
+        &mut self,
+        events: &'a EntityEvents<T>,
+    ) -> impl DoubleEndedIterator<Item = (Entity, &'a T)> {
+        self.reader
+            .iter(&events.events)
+            .map(|(entity, event)| (*entity, event))
+    }
+
+    /// Iterates over the events this reader has not seen yet that were sent to entities matched by
+    /// `query`, along with the query result for the entity. Events sent to other entities are
+    /// skipped.
+    pub fn iter_matching<'a, 'q, Q, F>(
+        &mut self,
+        events: &'a EntityEvents<T>,
+        query: &'q Query<'_, Q, F>,
+    ) -> impl Iterator<Item = (<Q::Fetch as Fetch<'q>>::Item, &'a T)> + 'q
+    where
+        'a: 'q,
+        Q: WorldQuery,
+        Q::Fetch: ReadOnlyFetch,
+        F: QueryFilter,
+    {
+        self.iter(events)
+            .filter_map(move |(entity, event)| Some((query.get(entity).ok()?, event)))
+    }
+
+    /// Calls `f` with each event this reader has not seen yet that was sent to an entity matched
+    /// by `query`, and the query result for that entity. This works with queries that mutate
+    /// components, unlike [EntityEventReader::iter_matching].
+    pub fn for_each_matching_mut<Q, F>(
+        &mut self,
+        events: &EntityEvents<T>,
+        query: &mut Query<'_, Q, F>,
+        mut f: impl FnMut(<Q::Fetch as Fetch<'_>>::Item, &T),
+    ) where
+        Q: WorldQuery,
+        F: QueryFilter,
+    {
+        for (entity, event) in self.iter(events) {
+            if let Ok(item) = query.get_mut(entity) {
+                f(item, event);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::{
+        IntoSystem, Local, Mut, Res, ResMut, Resources, Stage, SystemStage, With, World,
+    };
+
+    struct Damage(u32);
+    struct Health(u32);
+    struct Tile;
+
+    fn damage_tiles(
+        mut reader: Local<EntityEventReader<Damage>>,
+        events: Res<EntityEvents<Damage>>,
+        mut tiles: Query<&mut Health, With<Tile>>,
+    ) {
+        reader.for_each_matching_mut(&events, &mut tiles, |mut health: Mut<Health>, damage| {
+            health.0 -= damage.0;
+        });
+    }
+
+    fn record_damaged_tiles(
+        mut reader: Local<EntityEventReader<Damage>>,
+        events: Res<EntityEvents<Damage>>,
+        tiles: Query<Entity, With<Tile>>,
+        mut damaged: ResMut<Vec<(Entity, u32)>>,
+    ) {
+        for (entity, damage) in reader.iter_matching(&events, &tiles) {
+            damaged.push((entity, damage.0));
+        }
+    }
+
+    #[test]
+    fn read_matching_events() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        let tile = world.spawn((Health(10), Tile));
+        let other = world.spawn((Health(10),));
+
+        let mut events = EntityEvents::<Damage>::default();
+        events.send(tile, Damage(3));
+        events.send(other, Damage(4));
+        events.send(tile, Damage(1));
+        resources.insert(events);
+        resources.insert(Vec::<(Entity, u32)>::new());
+
+        let mut stage = SystemStage::serial();
+        stage
+            .add_system(damage_tiles.system())
+            .add_system(record_damaged_tiles.system());
+        stage.initialize(&mut world, &mut resources);
+        stage.run(&mut world, &mut resources);
+        // events are only read once per reader
+        stage.run(&mut world, &mut resources);
+
+        assert_eq!(world.get::<Health>(tile).unwrap().0, 6);
+        assert_eq!(world.get::<Health>(other).unwrap().0, 10);
+        assert_eq!(
+            *resources.get::<Vec<(Entity, u32)>>().unwrap(),
+            vec![(tile, 3), (tile, 1)]
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use bevy_ecs::ResMut;
 use bevy_utils::tracing::trace;
-use std::{fmt, marker::PhantomData};
+use std::{collections::VecDeque, fmt, marker::PhantomData};
 
 /// An `EventId` uniquely identifies an event.
 ///
@@ -27,12 +27,8 @@ impl<T> fmt::Display for EventId<T> {
 
 impl<T> fmt::Debug for EventId<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "event<{}>#{}",
-            std::any::type_name::<T>().split("::").last().unwrap(),
-            self.id,
-        )
+        // the full type name, as the last path segment of generic types is only a type argument
+        write!(f, "event<{}>#{}", std::any::type_name::<T>(), self.id)
     }
 }
 
@@ -48,6 +44,49 @@ enum State {
     B,
 }
 
+/// What [Events::send] does when a bounded [Events] collection already holds as many events sent
+/// since the last [Events::update] as it may
+pub enum EventOverflow<T> {
+    /// Drops the oldest event sent since the last update. Readers that didn't read it yet never will.
+    DropOldest,
+    /// Replaces the newest event sent since the last update that the function considers to be the
+    /// same as the new event, keeping its place and id. Readers that already read the replaced
+    /// event don't read the new one. Drops the oldest event when nothing matches.
+    Coalesce(fn(&T, &T) -> bool),
+    /// Panics in debug builds, to find producers that outpace their consumers. Drops the oldest
+    /// event in release builds.
+    PanicInDebug,
+}
+
+impl<T> Clone for EventOverflow<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for EventOverflow<T> {}
+
+impl<T> fmt::Debug for EventOverflow<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EventOverflow::DropOldest => f.write_str("DropOldest"),
+            EventOverflow::Coalesce(_) => f.write_str("Coalesce"),
+            EventOverflow::PanicInDebug => f.write_str("PanicInDebug"),
+        }
+    }
+}
+
+/// The number of events sent to an [Events] collection between two [Events::update] calls
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EventStats {
+    /// The events sent, including the ones that were dropped or coalesced
+    pub sent: usize,
+    /// The events dropped because the collection was full
+    pub dropped: usize,
+    /// The events that replaced an earlier event because the collection was full
+    pub coalesced: usize,
+}
+
 /// An event collection that represents the events that occurred within the last two [Events::update] calls. Events can be cheaply read using
 /// an [EventReader]. This collection is meant to be paired with a system that calls [Events::update] exactly once per update/frame. [Events::update_system]
 /// is a system that does this. [EventReader]s are expected to read events from this collection at least once per update/frame. If events are not handled
@@ -86,18 +125,23 @@ enum State {
 /// [EventReader]s that read at least once per update will never drop events. [EventReader]s that read once within two updates might
 /// still receive some events. [EventReader]s that read after two updates are guaranteed to drop all events that occurred before those updates.
 ///
-/// The buffers in [Events] will grow indefinitely if [Events::update] is never called.
+/// The buffers in [Events] will grow indefinitely if [Events::update] is never called, or if events are sent faster than
+/// they are read. [Events::bounded] limits the number of events sent between two updates, see [EventOverflow].
 ///
 /// An alternative call pattern would be to call [Events::update] manually across frames to control when events are cleared. However
 /// this complicates consumption
 #[derive(Debug)]
 pub struct Events<T> {
-    events_a: Vec<EventInstance<T>>,
-    events_b: Vec<EventInstance<T>>,
+    events_a: VecDeque<EventInstance<T>>,
+    events_b: VecDeque<EventInstance<T>>,
     a_start_event_count: usize,
     b_start_event_count: usize,
     event_count: usize,
     state: State,
+    capacity: Option<usize>,
+    overflow: EventOverflow<T>,
+    stats: EventStats,
+    last_update_stats: EventStats,
 }
 
 impl<T> Default for Events<T> {
@@ -106,11 +150,38 @@ impl<T> Default for Events<T> {
             a_start_event_count: 0,
             b_start_event_count: 0,
             event_count: 0,
-            events_a: Vec::new(),
-            events_b: Vec::new(),
+            events_a: VecDeque::new(),
+            events_b: VecDeque::new(),
             state: State::A,
+            capacity: None,
+            overflow: EventOverflow::DropOldest,
+            stats: EventStats::default(),
+            last_update_stats: EventStats::default(),
+        }
+    }
+}
+
+impl<T> Events<T> {
+    /// Creates an [Events] collection that holds at most `capacity` events sent since the last
+    /// [Events::update], and handles more events according to `overflow`
+    pub fn bounded(capacity: usize, overflow: EventOverflow<T>) -> Self {
+        assert!(capacity > 0, "bounded events need a capacity of at least 1");
+        Events {
+            capacity: Some(capacity),
+            overflow,
+            ..Default::default()
         }
     }
+
+    /// The maximum number of events sent since the last [Events::update], if the collection is bounded
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    /// The number of events sent between the last two [Events::update] calls
+    pub fn last_update_stats(&self) -> EventStats {
+        self.last_update_stats
+    }
 }
 
 fn map_instance_event_with_id<T>(event_instance: &EventInstance<T>) -> (&T, EventId<T>) {
@@ -175,30 +246,26 @@ impl<T> EventReader<T> {
         match events.state {
             State::A => events
                 .events_b
-                .get(b_index..)
-                .unwrap_or_else(|| &[])
                 .iter()
+                .skip(b_index)
                 .map(map_instance_event_with_id)
                 .chain(
                     events
                         .events_a
-                        .get(a_index..)
-                        .unwrap_or_else(|| &[])
                         .iter()
+                        .skip(a_index)
                         .map(map_instance_event_with_id),
                 ),
             State::B => events
                 .events_a
-                .get(a_index..)
-                .unwrap_or_else(|| &[])
                 .iter()
+                .skip(a_index)
                 .map(map_instance_event_with_id)
                 .chain(
                     events
                         .events_b
-                        .get(b_index..)
-                        .unwrap_or_else(|| &[])
                         .iter()
+                        .skip(b_index)
                         .map(map_instance_event_with_id),
                 ),
         }
@@ -266,15 +333,45 @@ impl<T: bevy_ecs::Resource> Events<T> {
             id: self.event_count,
             _marker: PhantomData,
         };
-        trace!("Events::send() -> {}", event_id);
-
-        let event_instance = EventInstance { event, event_id };
-
-        match self.state {
-            State::A => self.events_a.push(event_instance),
-            State::B => self.events_b.push(event_instance),
+        self.stats.sent += 1;
+        let (events, start_event_count) = match self.state {
+            State::A => (&mut self.events_a, &mut self.a_start_event_count),
+            State::B => (&mut self.events_b, &mut self.b_start_event_count),
+        };
+        if let Some(capacity) = self.capacity {
+            if events.len() >= capacity {
+                match self.overflow {
+                    EventOverflow::Coalesce(same) => {
+                        if let Some(instance) = events
+                            .iter_mut()
+                            .rev()
+                            .find(|instance| same(&instance.event, &event))
+                        {
+                            trace!("Events::send() coalesced into {}", instance.event_id);
+                            instance.event = event;
+                            self.stats.coalesced += 1;
+                            return;
+                        }
+                    }
+                    EventOverflow::PanicInDebug => debug_assert!(
+                        false,
+                        "more than {} {} events were sent in one update",
+                        capacity,
+                        std::any::type_name::<T>()
+                    ),
+                    EventOverflow::DropOldest => {}
+                }
+                // the buffer always holds the events with consecutive ids starting at its start count
+                if let Some(dropped) = events.pop_front() {
+                    trace!("Events::send() dropped {}", dropped.event_id);
+                    *start_event_count += 1;
+                    self.stats.dropped += 1;
+                }
+            }
         }
 
+        trace!("Events::send() -> {}", event_id);
+        events.push_back(EventInstance { event, event_id });
         self.event_count += 1;
     }
 
@@ -296,14 +393,15 @@ impl<T: bevy_ecs::Resource> Events<T> {
 
     /// Swaps the event buffers and clears the oldest event buffer. In general, this should be called once per frame/update.
     pub fn update(&mut self) {
+        self.last_update_stats = std::mem::take(&mut self.stats);
         match self.state {
             State::A => {
-                self.events_b = Vec::new();
+                self.events_b = VecDeque::new();
                 self.state = State::B;
                 self.b_start_event_count = self.event_count;
             }
             State::B => {
-                self.events_a = Vec::new();
+                self.events_a = VecDeque::new();
                 self.state = State::A;
                 self.a_start_event_count = self.event_count;
             }
@@ -459,6 +557,79 @@ mod tests {
         );
     }
 
+    #[test]
+    fn bounded_events_drop_oldest() {
+        let mut events = Events::<TestEvent>::bounded(2, EventOverflow::DropOldest);
+        let mut reader_a = events.get_reader();
+        let mut reader_b = events.get_reader();
+        events.send(TestEvent { i: 0 });
+        assert_eq!(get_events(&events, &mut reader_a), vec![TestEvent { i: 0 }]);
+
+        events.send(TestEvent { i: 1 });
+        events.send(TestEvent { i: 2 });
+        assert_eq!(
+            get_events(&events, &mut reader_a),
+            vec![TestEvent { i: 1 }, TestEvent { i: 2 }]
+        );
+        assert_eq!(
+            get_events(&events, &mut reader_b),
+            vec![TestEvent { i: 1 }, TestEvent { i: 2 }],
+            "reader_b never reads the dropped event"
+        );
+
+        // the events of the previous update don't count towards the capacity
+        events.update();
+        events.send(TestEvent { i: 3 });
+        events.send(TestEvent { i: 4 });
+        assert_eq!(
+            get_events(&events, &mut reader_a),
+            vec![TestEvent { i: 3 }, TestEvent { i: 4 }]
+        );
+        assert_eq!(
+            events.last_update_stats(),
+            EventStats {
+                sent: 3,
+                dropped: 1,
+                coalesced: 0
+            }
+        );
+        events.update();
+        assert_eq!(events.last_update_stats().sent, 2);
+    }
+
+    #[test]
+    fn bounded_events_coalesce() {
+        let mut events =
+            Events::<TestEvent>::bounded(2, EventOverflow::Coalesce(|a, b| a.i % 2 == b.i % 2));
+        let mut reader = events.get_reader();
+        events.send(TestEvent { i: 0 });
+        events.send(TestEvent { i: 1 });
+        events.send(TestEvent { i: 2 });
+        events.send(TestEvent { i: 3 });
+        assert_eq!(
+            get_events(&events, &mut reader),
+            vec![TestEvent { i: 2 }, TestEvent { i: 3 }]
+        );
+        events.update();
+        assert_eq!(
+            events.last_update_stats(),
+            EventStats {
+                sent: 4,
+                dropped: 0,
+                coalesced: 2
+            }
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "more than 1")]
+    fn bounded_events_panic_in_debug() {
+        let mut events = Events::<TestEvent>::bounded(1, EventOverflow::PanicInDebug);
+        events.send(TestEvent { i: 0 });
+        events.send(TestEvent { i: 1 });
+    }
+
     fn get_events(
         events: &Events<TestEvent>,
         reader: &mut EventReader<TestEvent>,
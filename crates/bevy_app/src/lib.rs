@@ -1,3 +1,5 @@
+/// The names of the default App shutdown stages
+pub mod shutdown_stage;
 /// The names of the default App stages
 pub mod stage;
 /// The names of the default App startup stages
@@ -5,6 +7,7 @@ pub mod startup_stage;
 
 mod app;
 mod app_builder;
+mod entity_event;
 mod event;
 mod plugin;
 mod plugin_group;
@@ -13,6 +16,7 @@ mod schedule_runner;
 pub use app::*;
 pub use app_builder::*;
 pub use bevy_derive::DynamicPlugin;
+pub use entity_event::*;
 pub use event::*;
 pub use plugin::*;
 pub use plugin_group::*;
@@ -22,7 +26,8 @@ pub mod prelude {
     pub use crate::{
         app::App,
         app_builder::AppBuilder,
-        event::{EventReader, Events},
-        stage, DynamicPlugin, Plugin, PluginGroup,
+        entity_event::{EntityEventReader, EntityEvents},
+        event::{EventOverflow, EventReader, Events},
+        shutdown_stage, stage, DynamicPlugin, Plugin, PluginGroup,
     };
 }
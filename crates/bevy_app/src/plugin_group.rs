@@ -18,32 +18,28 @@ pub struct PluginGroupBuilder {
 }
 
 impl PluginGroupBuilder {
-    pub fn add<T: Plugin>(&mut self, plugin: T) -> &mut Self {
-        self.order.push(TypeId::of::<T>());
-        self.plugins.insert(
-            TypeId::of::<T>(),
-            PluginEntry {
-                plugin: Box::new(plugin),
-                enabled: true,
-            },
-        );
-        self
-    }
-
-    pub fn add_before<Target: Plugin, T: Plugin>(&mut self, plugin: T) -> &mut Self {
-        let target_index = self
-            .order
+    fn index_of<Target: Plugin>(&self) -> usize {
+        self.order
             .iter()
-            .enumerate()
-            .find(|(_i, ty)| **ty == TypeId::of::<Target>())
-            .map(|(i, _)| i)
+            .position(|ty| *ty == TypeId::of::<Target>())
             .unwrap_or_else(|| {
                 panic!(
                     "Plugin does not exist: {}.",
                     std::any::type_name::<Target>()
                 )
-            });
-        self.order.insert(target_index, TypeId::of::<T>());
+            })
+    }
+
+    /// Inserts `plugin` at `index`, moving it there if a plugin of the same type was already added
+    fn insert<T: Plugin>(&mut self, index: usize, plugin: T) {
+        let mut index = index;
+        if let Some(old_index) = self.order.iter().position(|ty| *ty == TypeId::of::<T>()) {
+            self.order.remove(old_index);
+            if old_index < index {
+                index -= 1;
+            }
+        }
+        self.order.insert(index, TypeId::of::<T>());
         self.plugins.insert(
             TypeId::of::<T>(),
             PluginEntry {
@@ -51,33 +47,53 @@ impl PluginGroupBuilder {
                 enabled: true,
             },
         );
+    }
+
+    /// Adds a plugin to the end of the group. If a plugin of the same type was already added, it
+    /// is replaced and moved to the end.
+    pub fn add<T: Plugin>(&mut self, plugin: T) -> &mut Self {
+        self.insert(self.order.len(), plugin);
+        self
+    }
+
+    pub fn add_before<Target: Plugin, T: Plugin>(&mut self, plugin: T) -> &mut Self {
+        let target_index = self.index_of::<Target>();
+        self.insert(target_index, plugin);
         self
     }
 
     pub fn add_after<Target: Plugin, T: Plugin>(&mut self, plugin: T) -> &mut Self {
-        let target_index = self
-            .order
-            .iter()
-            .enumerate()
-            .find(|(_i, ty)| **ty == TypeId::of::<Target>())
-            .map(|(i, _)| i)
-            .unwrap_or_else(|| {
-                panic!(
-                    "Plugin does not exist: {}.",
-                    std::any::type_name::<Target>()
-                )
-            });
-        self.order.insert(target_index + 1, TypeId::of::<T>());
-        self.plugins.insert(
-            TypeId::of::<T>(),
-            PluginEntry {
-                plugin: Box::new(plugin),
-                enabled: true,
-            },
-        );
+        let target_index = self.index_of::<Target>();
+        self.insert(target_index + 1, plugin);
+        self
+    }
+
+    /// Replaces the `Target` plugin with `plugin`, which is built in the same position. This is
+    /// useful for swapping out a default plugin for a custom implementation.
+    pub fn replace<Target: Plugin, T: Plugin>(&mut self, plugin: T) -> &mut Self {
+        let target_index = self.index_of::<Target>();
+        if TypeId::of::<Target>() != TypeId::of::<T>() {
+            self.order.remove(target_index);
+            self.plugins.remove(&TypeId::of::<Target>());
+        }
+        self.insert(target_index, plugin);
+        self
+    }
+
+    /// Removes a plugin from the group. Unlike [PluginGroupBuilder::disable], the plugin cannot be
+    /// enabled again, but another plugin can be added in its place.
+    pub fn remove<T: Plugin>(&mut self) -> &mut Self {
+        let index = self.index_of::<T>();
+        self.order.remove(index);
+        self.plugins.remove(&TypeId::of::<T>());
         self
     }
 
+    /// Returns true if the group contains a plugin of type `T`, even if it is disabled
+    pub fn contains<T: Plugin>(&self) -> bool {
+        self.plugins.contains_key(&TypeId::of::<T>())
+    }
+
     pub fn enable<T: Plugin>(&mut self) -> &mut Self {
         let mut plugin_entry = self
             .plugins
@@ -107,3 +123,94 @@ impl PluginGroupBuilder {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::App;
+
+    struct Log(Vec<&'static str>);
+
+    macro_rules! log_plugin {
+        ($name:ident) => {
+            struct $name;
+
+            impl Plugin for $name {
+                fn build(&self, app: &mut AppBuilder) {
+                    app.resources_mut()
+                        .get_mut::<Log>()
+                        .unwrap()
+                        .0
+                        .push(stringify!($name));
+                }
+            }
+        };
+    }
+
+    log_plugin!(A);
+    log_plugin!(B);
+    log_plugin!(C);
+    log_plugin!(D);
+
+    fn build(func: impl FnOnce(&mut PluginGroupBuilder)) -> Vec<&'static str> {
+        let mut group = PluginGroupBuilder::default();
+        group.add(A).add(B).add(C);
+        func(&mut group);
+        let mut app = App::build();
+        app.add_resource(Log(Vec::new()));
+        group.finish(&mut app);
+        let mut log = app.resources_mut().get_mut::<Log>().unwrap();
+        std::mem::take(&mut log.0)
+    }
+
+    #[test]
+    fn order() {
+        assert_eq!(build(|_| {}), vec!["A", "B", "C"]);
+        assert_eq!(
+            build(|group| {
+                group.add_before::<B, _>(D);
+            }),
+            vec!["A", "D", "B", "C"]
+        );
+        assert_eq!(
+            build(|group| {
+                group.add_after::<C, _>(D);
+            }),
+            vec!["A", "B", "C", "D"]
+        );
+        assert_eq!(
+            build(|group| {
+                group.add(A);
+            }),
+            vec!["B", "C", "A"]
+        );
+        assert_eq!(
+            build(|group| {
+                group.add_after::<B, _>(A);
+            }),
+            vec!["B", "A", "C"]
+        );
+    }
+
+    #[test]
+    fn disable_replace_remove() {
+        assert_eq!(
+            build(|group| {
+                group.disable::<B>();
+            }),
+            vec!["A", "C"]
+        );
+        assert_eq!(
+            build(|group| {
+                group.replace::<B, _>(D);
+            }),
+            vec!["A", "D", "C"]
+        );
+        assert_eq!(
+            build(|group| {
+                group.remove::<A>().add_after::<C, _>(D);
+            }),
+            vec!["B", "C", "D"]
+        );
+    }
+}
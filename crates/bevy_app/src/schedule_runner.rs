@@ -60,6 +60,7 @@ impl Plugin for ScheduleRunnerPlugin {
             match settings.run_mode {
                 RunMode::Once => {
                     app.update();
+                    exit(app);
                 }
                 RunMode::Loop { wait } => {
                     let mut tick = move |app: &mut App,
@@ -100,6 +101,7 @@ impl Plugin for ScheduleRunnerPlugin {
                                 std::thread::sleep(delay);
                             }
                         }
+                        exit(app);
                     }
 
                     #[cfg(target_arch = "wasm32")]
@@ -126,7 +128,7 @@ impl Plugin for ScheduleRunnerPlugin {
                                 Ok(delay) => {
                                     set_timeout(f.borrow().as_ref().unwrap(), delay.unwrap_or(asap))
                                 }
-                                Err(_) => {}
+                                Err(_) => app.shutdown(),
                             }
                         };
                         *g.borrow_mut() = Some(Closure::wrap(Box::new(c) as Box<dyn FnMut()>));
@@ -137,3 +139,14 @@ impl Plugin for ScheduleRunnerPlugin {
         });
     }
 }
+
+/// Runs the shutdown systems of `app` and drops it, then exits the process if the app set an
+/// [AppExitCode](crate::AppExitCode)
+fn exit(mut app: App) {
+    app.shutdown();
+    let exit_code = app.exit_code();
+    drop(app);
+    if exit_code != 0 {
+        std::process::exit(exit_code);
+    }
+}
@@ -0,0 +1,6 @@
+/// Name of app stage that runs once when an app exits
+pub const SHUTDOWN: &str = "shutdown";
+
+/// Name of app stage that runs once after the shutdown stage, to release what the shutdown
+/// systems still used
+pub const POST_SHUTDOWN: &str = "post_shutdown";
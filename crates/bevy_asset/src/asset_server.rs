@@ -106,11 +106,13 @@ impl AssetServer {
         Ok(())
     }
 
+    #[track_caller]
     pub fn get_handle<T: Asset, I: Into<HandleId>>(&self, id: I) -> Handle<T> {
         let sender = self.server.asset_ref_counter.channel.sender.clone();
         Handle::strong(id.into(), sender)
     }
 
+    #[track_caller]
     pub fn get_handle_untyped<I: Into<HandleId>>(&self, id: I) -> HandleUntyped {
         let sender = self.server.asset_ref_counter.channel.sender.clone();
         HandleUntyped::strong(id.into(), sender)
@@ -178,6 +180,7 @@ impl AssetServer {
         load_state
     }
 
+    #[track_caller]
     pub fn load<'a, T: Asset, P: Into<AssetPath<'a>>>(&self, path: P) -> Handle<T> {
         self.load_untyped(path).typed()
     }
@@ -279,6 +282,13 @@ impl AssetServer {
         Ok(asset_path_id)
     }
 
+    /// Loads the asset at `path` again, even if it is loaded already, like when its file changes
+    /// while watching for changes
+    pub fn reload_asset<'a, P: Into<AssetPath<'a>>>(&self, path: P) {
+        let _ = self.load_untracked(path, true);
+    }
+
+    #[track_caller]
     pub fn load_untyped<'a, P: Into<AssetPath<'a>>>(&self, path: P) -> HandleUntyped {
         let handle_id = self.load_untracked(path, false);
         self.get_handle_untyped(handle_id)
@@ -292,6 +302,11 @@ impl AssetServer {
         let asset_path: AssetPath<'a> = path.into();
         let server = self.clone();
         let owned_path = asset_path.to_owned();
+        self.server
+            .handle_to_path
+            .write()
+            .entry(asset_path.get_id().into())
+            .or_insert_with(|| owned_path.clone());
         self.server
             .task_pool
             .spawn(async move {
@@ -301,6 +316,7 @@ impl AssetServer {
         asset_path.into()
     }
 
+    #[track_caller]
     pub fn load_folder<P: AsRef<Path>>(
         &self,
         path: P,
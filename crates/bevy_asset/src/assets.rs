@@ -4,7 +4,7 @@ use crate::{
 use bevy_app::{prelude::Events, AppBuilder};
 use bevy_ecs::{FromResources, IntoSystem, ResMut};
 use bevy_reflect::RegisterTypeBuilder;
-use bevy_utils::HashMap;
+use bevy_utils::{HashMap, HashSet};
 use crossbeam_channel::Sender;
 use std::fmt::Debug;
 
@@ -48,6 +48,8 @@ impl<T: Asset> Debug for AssetEvent<T> {
 pub struct Assets<T: Asset> {
     assets: HashMap<HandleId, T>,
     events: Events<AssetEvent<T>>,
+    /// The assets with a [AssetEvent::Modified] event that wasn't sent to the App yet
+    modified: HashSet<HandleId>,
     pub(crate) ref_change_sender: Sender<RefChange>,
 }
 
@@ -56,27 +58,30 @@ impl<T: Asset> Assets<T> {
         Assets {
             assets: HashMap::default(),
             events: Events::default(),
+            modified: HashSet::default(),
             ref_change_sender,
         }
     }
 
+    #[track_caller]
     pub fn add(&mut self, asset: T) -> Handle<T> {
         let id = HandleId::random::<T>();
         self.assets.insert(id, asset);
-        self.events.send(AssetEvent::Created {
+        self.send_event(AssetEvent::Created {
             handle: Handle::weak(id),
         });
         self.get_handle(id)
     }
 
+    #[track_caller]
     pub fn set<H: Into<HandleId>>(&mut self, handle: H, asset: T) -> Handle<T> {
         let id: HandleId = handle.into();
         if self.assets.insert(id, asset).is_some() {
-            self.events.send(AssetEvent::Modified {
+            self.send_event(AssetEvent::Modified {
                 handle: Handle::weak(id),
             });
         } else {
-            self.events.send(AssetEvent::Created {
+            self.send_event(AssetEvent::Created {
                 handle: Handle::weak(id),
             });
         }
@@ -87,11 +92,11 @@ impl<T: Asset> Assets<T> {
     pub fn set_untracked<H: Into<HandleId>>(&mut self, handle: H, asset: T) {
         let id: HandleId = handle.into();
         if self.assets.insert(id, asset).is_some() {
-            self.events.send(AssetEvent::Modified {
+            self.send_event(AssetEvent::Modified {
                 handle: Handle::weak(id),
             });
         } else {
-            self.events.send(AssetEvent::Created {
+            self.send_event(AssetEvent::Created {
                 handle: Handle::weak(id),
             });
         }
@@ -107,12 +112,19 @@ impl<T: Asset> Assets<T> {
 
     pub fn get_mut<H: Into<HandleId>>(&mut self, handle: H) -> Option<&mut T> {
         let id: HandleId = handle.into();
-        self.events.send(AssetEvent::Modified {
+        self.send_event(AssetEvent::Modified {
             handle: Handle::weak(id),
         });
         self.assets.get_mut(&id)
     }
 
+    /// Gets a mutable reference to an asset without sending an [AssetEvent::Modified], for
+    /// bookkeeping that systems reacting to the asset's events shouldn't see as a change
+    pub fn get_mut_untracked<H: Into<HandleId>>(&mut self, handle: H) -> Option<&mut T> {
+        self.assets.get_mut(&handle.into())
+    }
+
+    #[track_caller]
     pub fn get_handle<H: Into<HandleId>>(&self, handle: H) -> Handle<T> {
         Handle::strong(handle.into(), self.ref_change_sender.clone())
     }
@@ -132,11 +144,28 @@ impl<T: Asset> Assets<T> {
         });
 
         if let Some(event) = event {
+            self.modified.remove(&id);
             self.events.send(event);
         }
         borrowed
     }
 
+    /// Queues an event to be sent to the App. An asset modified several times in a frame only gets
+    /// one [AssetEvent::Modified] event, unless it was also created or removed in between.
+    fn send_event(&mut self, event: AssetEvent<T>) {
+        match &event {
+            AssetEvent::Modified { handle } => {
+                if !self.modified.insert(handle.id) {
+                    return;
+                }
+            }
+            AssetEvent::Created { handle } | AssetEvent::Removed { handle } => {
+                self.modified.remove(&handle.id);
+            }
+        }
+        self.events.send(event);
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = (HandleId, &T)> {
         self.assets.iter().map(|(k, v)| (*k, v))
     }
@@ -149,7 +178,7 @@ impl<T: Asset> Assets<T> {
         let id: HandleId = handle.into();
         let asset = self.assets.remove(&id);
         if asset.is_some() {
-            self.events.send(AssetEvent::Removed {
+            self.send_event(AssetEvent::Removed {
                 handle: Handle::weak(id),
             });
         }
@@ -182,7 +211,8 @@ impl<T: Asset> Assets<T> {
         mut events: ResMut<Events<AssetEvent<T>>>,
         mut assets: ResMut<Assets<T>>,
     ) {
-        events.extend(assets.events.drain())
+        events.extend(assets.events.drain());
+        assets.modified.clear();
     }
 
     pub fn len(&self) -> usize {
@@ -248,3 +278,52 @@ impl AddAsset for AppBuilder {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RefChangeChannel;
+    use bevy_reflect::TypeUuid;
+
+    #[derive(Debug, TypeUuid)]
+    #[uuid = "7b7c9a7e-2c36-4b38-8f6c-6fd8f7d0a8a1"]
+    struct Counter(usize);
+
+    /// Takes the events like [Assets::asset_event_system] does
+    fn drain_events(assets: &mut Assets<Counter>) -> Vec<String> {
+        assets.modified.clear();
+        assets
+            .events
+            .drain()
+            .map(|event| match event {
+                AssetEvent::Created { .. } => "created",
+                AssetEvent::Modified { .. } => "modified",
+                AssetEvent::Removed { .. } => "removed",
+            })
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn coalesce_modified_events() {
+        let channel = RefChangeChannel::default();
+        let mut assets = Assets::<Counter>::new(channel.sender.clone());
+        let handle = assets.add(Counter(0));
+        for _ in 0..1000 {
+            assets.get_mut(&handle).unwrap().0 += 1;
+        }
+        assert_eq!(drain_events(&mut assets), vec!["created", "modified"]);
+
+        for _ in 0..3 {
+            assets.get_mut(&handle).unwrap().0 += 1;
+        }
+        assets.remove(&handle);
+        assets.set(&handle, Counter(0));
+        assets.get_mut(&handle);
+        assets.get_mut(&handle);
+        assert_eq!(
+            drain_events(&mut assets),
+            vec!["modified", "removed", "created", "modified"]
+        );
+    }
+}
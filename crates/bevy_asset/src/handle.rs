@@ -7,6 +7,7 @@ use std::{
 
 use crate::{
     path::{AssetPath, AssetPathId},
+    provenance::{self, HandleSite},
     Asset, Assets,
 };
 use bevy_reflect::{Reflect, ReflectComponent, ReflectDeserialize};
@@ -71,24 +72,25 @@ where
 
 enum HandleType {
     Weak,
-    Strong(Sender<RefChange>),
+    Strong(Sender<RefChange>, HandleSite),
 }
 
 impl Debug for HandleType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             HandleType::Weak => f.write_str("Weak"),
-            HandleType::Strong(_) => f.write_str("Strong"),
+            HandleType::Strong(..) => f.write_str("Strong"),
         }
     }
 }
 
 impl<T: Asset> Handle<T> {
+    #[track_caller]
     pub(crate) fn strong(id: HandleId, ref_change_sender: Sender<RefChange>) -> Self {
         ref_change_sender.send(RefChange::Increment(id)).unwrap();
         Self {
             id,
-            handle_type: HandleType::Strong(ref_change_sender),
+            handle_type: HandleType::Strong(ref_change_sender, provenance::track(id)),
             marker: PhantomData,
         }
     }
@@ -114,25 +116,27 @@ impl<T: Asset> Handle<T> {
     }
 
     pub fn is_strong(&self) -> bool {
-        matches!(self.handle_type, HandleType::Strong(_))
+        matches!(self.handle_type, HandleType::Strong(..))
     }
 
+    #[track_caller]
     pub fn make_strong(&mut self, assets: &mut Assets<T>) {
         if self.is_strong() {
             return;
         }
         let sender = assets.ref_change_sender.clone();
         sender.send(RefChange::Increment(self.id)).unwrap();
-        self.handle_type = HandleType::Strong(sender);
+        self.handle_type = HandleType::Strong(sender, provenance::track(self.id));
     }
 
     pub fn clone_weak(&self) -> Self {
         Handle::weak(self.id)
     }
 
+    #[track_caller]
     pub fn clone_untyped(&self) -> HandleUntyped {
         match &self.handle_type {
-            HandleType::Strong(sender) => HandleUntyped::strong(self.id, sender.clone()),
+            HandleType::Strong(sender, _) => HandleUntyped::strong(self.id, sender.clone()),
             HandleType::Weak => HandleUntyped::weak(self.id),
         }
     }
@@ -145,9 +149,10 @@ impl<T: Asset> Handle<T> {
 impl<T: Asset> Drop for Handle<T> {
     fn drop(&mut self) {
         match self.handle_type {
-            HandleType::Strong(ref sender) => {
+            HandleType::Strong(ref sender, site) => {
                 // ignore send errors because this means the channel is shut down / the game has stopped
                 let _ = sender.send(RefChange::Decrement(self.id));
+                provenance::untrack(self.id, site);
             }
             HandleType::Weak => {}
         }
@@ -218,9 +223,10 @@ impl<T: Asset> Debug for Handle<T> {
 }
 
 impl<T: Asset> Clone for Handle<T> {
+    #[track_caller]
     fn clone(&self) -> Self {
         match self.handle_type {
-            HandleType::Strong(ref sender) => Handle::strong(self.id, sender.clone()),
+            HandleType::Strong(ref sender, _) => Handle::strong(self.id, sender.clone()),
             HandleType::Weak => Handle::weak(self.id),
         }
     }
@@ -247,11 +253,12 @@ impl HandleUntyped {
         }
     }
 
+    #[track_caller]
     pub(crate) fn strong(id: HandleId, ref_change_sender: Sender<RefChange>) -> Self {
         ref_change_sender.send(RefChange::Increment(id)).unwrap();
         Self {
             id,
-            handle_type: HandleType::Strong(ref_change_sender),
+            handle_type: HandleType::Strong(ref_change_sender, provenance::track(id)),
         }
     }
 
@@ -271,7 +278,7 @@ impl HandleUntyped {
     }
 
     pub fn is_strong(&self) -> bool {
-        matches!(self.handle_type, HandleType::Strong(_))
+        matches!(self.handle_type, HandleType::Strong(..))
     }
 
     pub fn typed<T: Asset>(mut self) -> Handle<T> {
@@ -281,7 +288,7 @@ impl HandleUntyped {
             }
         }
         let handle_type = match &self.handle_type {
-            HandleType::Strong(sender) => HandleType::Strong(sender.clone()),
+            HandleType::Strong(sender, site) => HandleType::Strong(sender.clone(), *site),
             HandleType::Weak => HandleType::Weak,
         };
         // ensure we don't send the RefChange event when "self" is dropped
@@ -297,9 +304,10 @@ impl HandleUntyped {
 impl Drop for HandleUntyped {
     fn drop(&mut self) {
         match self.handle_type {
-            HandleType::Strong(ref sender) => {
+            HandleType::Strong(ref sender, site) => {
                 // ignore send errors because this means the channel is shut down / the game has stopped
                 let _ = sender.send(RefChange::Decrement(self.id));
+                provenance::untrack(self.id, site);
             }
             HandleType::Weak => {}
         }
@@ -327,9 +335,10 @@ impl PartialEq for HandleUntyped {
 impl Eq for HandleUntyped {}
 
 impl Clone for HandleUntyped {
+    #[track_caller]
     fn clone(&self) -> Self {
         match self.handle_type {
-            HandleType::Strong(ref sender) => HandleUntyped::strong(self.id, sender.clone()),
+            HandleType::Strong(ref sender, _) => HandleUntyped::strong(self.id, sender.clone()),
             HandleType::Weak => HandleUntyped::weak(self.id),
         }
     }
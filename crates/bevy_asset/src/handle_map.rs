@@ -0,0 +1,218 @@
+use crate::{Asset, AssetPath, AssetServer, Handle, HandleId};
+use bevy_utils::HashMap;
+use serde::{Deserialize, Serialize};
+use std::hash::Hash;
+
+/// Whether a [HandleMap] keeps the assets it refers to loaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandlePolicy {
+    /// The map holds strong handles, so its assets stay loaded until they are removed from the map
+    Strong,
+    /// The map holds weak handles, so its assets are unloaded when nothing else uses them
+    Weak,
+}
+
+impl Default for HandlePolicy {
+    fn default() -> Self {
+        HandlePolicy::Strong
+    }
+}
+
+/// Maps keys, like tile kinds or item names, to the handles of their assets. Handles are stored
+/// strong or weak depending on the map's [HandlePolicy].
+///
+/// Maps of loaded assets can be saved with [HandleMap::to_paths] and restored with
+/// [HandleMap::load_paths], which loads each asset again from its path.
+#[derive(Debug)]
+pub struct HandleMap<K, T: Asset> {
+    handles: HashMap<K, Handle<T>>,
+    policy: HandlePolicy,
+}
+
+impl<K: Eq + Hash, T: Asset> Default for HandleMap<K, T> {
+    fn default() -> Self {
+        HandleMap::new(HandlePolicy::default())
+    }
+}
+
+impl<K: Eq + Hash, T: Asset> HandleMap<K, T> {
+    pub fn new(policy: HandlePolicy) -> Self {
+        HandleMap {
+            handles: HashMap::default(),
+            policy,
+        }
+    }
+
+    pub fn policy(&self) -> HandlePolicy {
+        self.policy
+    }
+
+    fn store(&self, handle: &Handle<T>) -> Handle<T> {
+        match self.policy {
+            HandlePolicy::Strong => handle.clone(),
+            HandlePolicy::Weak => handle.clone_weak(),
+        }
+    }
+
+    /// Maps `key` to `handle`, returning the handle it was mapped to before
+    pub fn insert(&mut self, key: K, handle: &Handle<T>) -> Option<Handle<T>> {
+        let handle = self.store(handle);
+        self.handles.insert(key, handle)
+    }
+
+    /// Returns the handle `key` is mapped to, mapping it to the handle returned by `f` first if it
+    /// isn't mapped yet
+    pub fn get_or_insert_with(&mut self, key: K, f: impl FnOnce() -> Handle<T>) -> &Handle<T> {
+        let policy = self.policy;
+        self.handles.entry(key).or_insert_with(|| match policy {
+            HandlePolicy::Strong => f(),
+            HandlePolicy::Weak => f().clone_weak(),
+        })
+    }
+
+    /// Returns the handle `key` is mapped to, mapping it to the asset at `path` first if it isn't
+    /// mapped yet
+    pub fn get_or_load<'a, P: Into<AssetPath<'a>>>(
+        &mut self,
+        key: K,
+        path: P,
+        asset_server: &AssetServer,
+    ) -> &Handle<T> {
+        self.get_or_insert_with(key, || asset_server.load(path))
+    }
+
+    pub fn get(&self, key: &K) -> Option<&Handle<T>> {
+        self.handles.get(key)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.handles.contains_key(key)
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<Handle<T>> {
+        self.handles.remove(key)
+    }
+
+    /// Finds a key that is mapped to the asset `id`
+    pub fn find_key<H: Into<HandleId>>(&self, id: H) -> Option<&K> {
+        let id = id.into();
+        self.handles
+            .iter()
+            .find(|(_, handle)| handle.id == id)
+            .map(|(key, _)| key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &Handle<T>)> {
+        self.handles.iter()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.handles.keys()
+    }
+
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.handles.clear()
+    }
+}
+
+impl<K: Eq + Hash + Ord + Clone, T: Asset> HandleMap<K, T> {
+    /// Returns the asset paths of the mapped handles, sorted by key. Handles of assets that weren't
+    /// loaded from a path, like assets added with [Assets::add](crate::Assets::add), are skipped.
+    pub fn to_paths(&self, asset_server: &AssetServer) -> HandleMapPaths<K> {
+        let mut entries = self
+            .handles
+            .iter()
+            .filter_map(|(key, handle)| {
+                asset_server
+                    .get_handle_path(handle)
+                    .map(|path| (key.clone(), path.to_owned()))
+            })
+            .collect::<Vec<_>>();
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        HandleMapPaths { entries }
+    }
+
+    /// Creates a map from saved asset paths, loading each asset
+    pub fn load_paths(
+        paths: &HandleMapPaths<K>,
+        policy: HandlePolicy,
+        asset_server: &AssetServer,
+    ) -> Self {
+        let mut map = HandleMap::new(policy);
+        for (key, path) in paths.entries.iter() {
+            map.insert(key.clone(), &asset_server.load(path.clone()));
+        }
+        map
+    }
+}
+
+/// The asset paths of the handles in a [HandleMap], which can be stored in save files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandleMapPaths<K> {
+    pub entries: Vec<(K, AssetPath<'static>)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Assets, FileAssetIo, RefChangeChannel};
+    use bevy_reflect::TypeUuid;
+    use bevy_tasks::TaskPool;
+
+    #[derive(Debug, TypeUuid)]
+    #[uuid = "b5d2c9a3-8d2e-4f51-9a3e-2f0f4f8a6c11"]
+    struct Tile;
+
+    #[test]
+    fn weak_and_strong_policies() {
+        let channel = RefChangeChannel::default();
+        let mut assets = Assets::<Tile>::new(channel.sender.clone());
+        let grass = assets.add(Tile);
+        let mut strong = HandleMap::new(HandlePolicy::Strong);
+        let mut weak = HandleMap::new(HandlePolicy::Weak);
+        strong.insert("grass", &grass);
+        weak.insert("grass", &grass);
+        assert!(strong.get(&"grass").unwrap().is_strong());
+        assert!(weak.get(&"grass").unwrap().is_weak());
+        assert_eq!(weak.find_key(&grass), Some(&"grass"));
+
+        let dirt = weak.get_or_insert_with("dirt", || assets.add(Tile)).clone();
+        assert!(dirt.is_weak());
+        assert_eq!(weak.get_or_insert_with("dirt", || assets.add(Tile)), &dirt);
+        assert_eq!(weak.len(), 2);
+    }
+
+    #[test]
+    fn save_and_load_paths() {
+        let asset_server = AssetServer::new(FileAssetIo::new(""), TaskPool::new());
+        let mut map = HandleMap::<u32, Tile>::default();
+        map.get_or_load(2, "tiles/water.tile", &asset_server);
+        map.get_or_load(1, "tiles/grass.tile", &asset_server);
+        map.insert(3, &Handle::weak(HandleId::random::<Tile>()));
+
+        let paths = map.to_paths(&asset_server);
+        let saved = ron::ser::to_string(&paths).unwrap();
+        let paths: HandleMapPaths<u32> = ron::de::from_str(&saved).unwrap();
+        assert_eq!(
+            paths
+                .entries
+                .iter()
+                .map(|(key, path)| (*key, path.path().to_str().unwrap()))
+                .collect::<Vec<_>>(),
+            vec![(1, "tiles/grass.tile"), (2, "tiles/water.tile")]
+        );
+
+        let loaded = HandleMap::<u32, Tile>::load_paths(&paths, HandlePolicy::Weak, &asset_server);
+        assert_eq!(loaded.get(&1), map.get(&1));
+        assert_eq!(loaded.get(&2), map.get(&2));
+        assert!(!loaded.contains_key(&3));
+    }
+}
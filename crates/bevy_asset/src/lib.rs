@@ -6,10 +6,12 @@ mod assets;
 ))]
 mod filesystem_watcher;
 mod handle;
+mod handle_map;
 mod info;
 mod io;
 mod loader;
 mod path;
+mod provenance;
 
 pub use asset_server::*;
 pub use assets::*;
@@ -17,10 +19,12 @@ use bevy_ecs::{IntoSystem, SystemStage};
 use bevy_reflect::RegisterTypeBuilder;
 use bevy_tasks::IoTaskPool;
 pub use handle::*;
+pub use handle_map::*;
 pub use info::*;
 pub use io::*;
 pub use loader::*;
 pub use path::*;
+pub use provenance::{HandleHolder, HandleProvenance};
 
 /// The names of asset stages in an App Schedule
 pub mod stage {
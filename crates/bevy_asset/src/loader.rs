@@ -94,6 +94,7 @@ impl<'a> LoadContext<'a> {
         self.labeled_assets.insert(Some(label.to_string()), asset);
     }
 
+    #[track_caller]
     pub fn get_handle<I: Into<HandleId>, T: Asset>(&self, id: I) -> Handle<T> {
         Handle::strong(id.into(), self.ref_change_channel.sender.clone())
     }
@@ -0,0 +1,200 @@
+//! Tracks where the live strong handles of each asset were created, to find the code that keeps
+//! assets alive. Only records anything with the `handle_provenance` feature, which makes creating
+//! and dropping strong handles noticeably slower.
+
+use crate::HandleId;
+use std::panic::Location;
+
+/// The location a strong handle was created at. Only tracked with the `handle_provenance` feature.
+#[cfg(feature = "handle_provenance")]
+pub(crate) type HandleSite = &'static Location<'static>;
+#[cfg(not(feature = "handle_provenance"))]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct HandleSite;
+
+#[cfg(feature = "handle_provenance")]
+mod sites {
+    use super::HandleSite;
+    use crate::HandleId;
+    use bevy_utils::HashMap;
+    use parking_lot::{const_mutex, Mutex};
+
+    /// The number of live strong handles per asset and creation site. Handles are created and
+    /// dropped wherever they are cloned, so this can't be a resource.
+    pub(super) static LIVE_HANDLES: Mutex<Option<HashMap<HandleId, HashMap<HandleSite, usize>>>> =
+        const_mutex(None);
+
+    pub(super) fn add(id: HandleId, site: HandleSite) {
+        let mut live_handles = LIVE_HANDLES.lock();
+        *live_handles
+            .get_or_insert_with(Default::default)
+            .entry(id)
+            .or_default()
+            .entry(site)
+            .or_insert(0) += 1;
+    }
+
+    pub(super) fn remove(id: HandleId, site: HandleSite) {
+        let mut live_handles = LIVE_HANDLES.lock();
+        let live_handles = match live_handles.as_mut() {
+            Some(live_handles) => live_handles,
+            None => return,
+        };
+        if let Some(sites) = live_handles.get_mut(&id) {
+            if let Some(count) = sites.get_mut(&site) {
+                *count -= 1;
+                if *count == 0 {
+                    sites.remove(&site);
+                }
+            }
+            if sites.is_empty() {
+                live_handles.remove(&id);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "handle_provenance")]
+#[track_caller]
+pub(crate) fn track(id: HandleId) -> HandleSite {
+    let site = Location::caller();
+    sites::add(id, site);
+    site
+}
+
+#[cfg(not(feature = "handle_provenance"))]
+#[inline]
+pub(crate) fn track(_id: HandleId) -> HandleSite {
+    HandleSite
+}
+
+#[cfg(feature = "handle_provenance")]
+pub(crate) fn untrack(id: HandleId, site: HandleSite) {
+    sites::remove(id, site);
+}
+
+#[cfg(not(feature = "handle_provenance"))]
+#[inline]
+pub(crate) fn untrack(_id: HandleId, _site: HandleSite) {}
+
+/// The live strong handles of an asset that were created at the same location
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HandleHolder {
+    pub site: &'static Location<'static>,
+    pub handles: usize,
+}
+
+/// Reports which code holds the strong handles that keep assets alive. Every strong handle,
+/// including clones, is tagged with the location it was created at. Handles cloned from generic
+/// code (like `Vec::clone`) are tagged with a location in that code.
+///
+/// Everything is empty unless the `handle_provenance` feature is enabled.
+pub struct HandleProvenance;
+
+impl HandleProvenance {
+    /// Whether handles are tracked, which depends on the `handle_provenance` feature
+    pub const ENABLED: bool = cfg!(feature = "handle_provenance");
+
+    /// The number of live strong handles of the asset `id`
+    pub fn live_handles<H: Into<HandleId>>(id: H) -> usize {
+        Self::holders(id).iter().map(|holder| holder.handles).sum()
+    }
+
+    /// Where the live strong handles of the asset `id` were created, the most handles first
+    #[allow(unused_variables)]
+    pub fn holders<H: Into<HandleId>>(id: H) -> Vec<HandleHolder> {
+        #[allow(unused_mut)]
+        let mut holders: Vec<HandleHolder> = Vec::new();
+        #[cfg(feature = "handle_provenance")]
+        if let Some(sites) = sites::LIVE_HANDLES
+            .lock()
+            .as_ref()
+            .and_then(|live_handles| live_handles.get(&id.into()))
+        {
+            holders.extend(sites.iter().map(|(site, handles)| HandleHolder {
+                site,
+                handles: *handles,
+            }));
+        }
+        holders.sort_by(|a, b| {
+            b.handles
+                .cmp(&a.handles)
+                .then_with(|| (a.site.file(), a.site.line()).cmp(&(b.site.file(), b.site.line())))
+        });
+        holders
+    }
+
+    /// The `count` assets with the most live strong handles, the most handles first
+    pub fn top_assets(count: usize) -> Vec<(HandleId, usize)> {
+        #[allow(unused_mut)]
+        let mut assets: Vec<(HandleId, usize)> = Vec::new();
+        #[cfg(feature = "handle_provenance")]
+        if let Some(live_handles) = sites::LIVE_HANDLES.lock().as_ref() {
+            assets.extend(
+                live_handles
+                    .iter()
+                    .map(|(id, sites)| (*id, sites.values().sum::<usize>())),
+            );
+        }
+        assets.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        assets.truncate(count);
+        assets
+    }
+
+    /// Formats the `count` assets with the most live strong handles, each followed by the
+    /// `count` locations holding the most of them
+    pub fn report(count: usize) -> String {
+        let mut report = String::new();
+        for (id, handles) in Self::top_assets(count) {
+            report.push_str(&format!("{:?}: {} handles\n", id, handles));
+            for holder in Self::holders(id).iter().take(count) {
+                report.push_str(&format!("    {}: {}\n", holder.site, holder.handles));
+            }
+        }
+        report
+    }
+}
+
+#[cfg(all(test, feature = "handle_provenance"))]
+mod tests {
+    use super::*;
+    use crate::{Assets, Handle, RefChangeChannel};
+    use bevy_reflect::TypeUuid;
+
+    #[derive(Debug, TypeUuid)]
+    #[uuid = "44f4c71c-54b9-4d6a-9f9f-7b4a7d0b3f4e"]
+    struct Leaky;
+
+    #[test]
+    fn track_handle_sites() {
+        let channel = RefChangeChannel::default();
+        let mut assets = Assets::<Leaky>::new(channel.sender.clone());
+        let handle = assets.add(Leaky);
+        let add_line = line!() - 1;
+        let clones = (0..3).map(|_| handle.clone()).collect::<Vec<_>>();
+        let clone_line = line!() - 1;
+        let weak = handle.clone_weak();
+
+        assert_eq!(HandleProvenance::live_handles(&handle), 4);
+        let holders = HandleProvenance::holders(&handle);
+        assert_eq!(holders.len(), 2);
+        assert_eq!(holders[0].handles, 3);
+        assert_eq!(holders[0].site.line(), clone_line);
+        assert_eq!(holders[1].handles, 1);
+        assert_eq!(holders[1].site.line(), add_line);
+        assert!(holders[0].site.file().ends_with("provenance.rs"));
+
+        // converting a handle moves its site along with it
+        let untyped = clones[0].clone_untyped().typed::<Leaky>();
+        assert_eq!(HandleProvenance::live_handles(&handle), 5);
+        drop(untyped);
+
+        drop(clones);
+        drop(weak);
+        assert_eq!(HandleProvenance::live_handles(&handle), 1);
+        let id = handle.id;
+        drop(handle);
+        assert_eq!(HandleProvenance::live_handles(id), 0);
+        assert!(HandleProvenance::holders(id).is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use bevy_math::{Mat4, Vec2, Vec3, Vec4};
+use bevy_math::{Bounds, Mat4, Vec2, Vec3, Vec4};
 
 pub use bevy_derive::Bytes;
 
@@ -101,6 +101,7 @@ unsafe impl Byteable for isize {}
 unsafe impl Byteable for f32 {}
 unsafe impl Byteable for f64 {}
 unsafe impl Byteable for Vec2 {}
+unsafe impl Byteable for Bounds {}
 // NOTE: Vec3 actually takes up the size of 4 floats / 16 bytes due to SIMD. This is actually convenient because GLSL
 // uniform buffer objects pad Vec3s to be 16 bytes.
 unsafe impl Byteable for Vec3 {}
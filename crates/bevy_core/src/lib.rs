@@ -27,10 +27,17 @@ pub struct CorePlugin;
 impl Plugin for CorePlugin {
     fn build(&self, app: &mut AppBuilder) {
         // Setup the default bevy task pools
-        app.resources_mut()
+        let task_pool_options = app
+            .resources_mut()
             .get_cloned::<DefaultTaskPoolOptions>()
-            .unwrap_or_else(DefaultTaskPoolOptions::default)
-            .create_default_pools(app.resources_mut());
+            .unwrap_or_else(DefaultTaskPoolOptions::default);
+        task_pool_options.create_default_pools(app.resources_mut());
+        if task_pool_options.single_threaded {
+            app.add_system_to_stage(
+                stage::FIRST,
+                task_pool_options::tick_single_threaded_task_pools_system.system(),
+            );
+        }
 
         app.init_resource::<Time>()
             .init_resource::<EntityLabels>()
@@ -1,4 +1,4 @@
-use bevy_ecs::Resources;
+use bevy_ecs::{Res, Resources};
 use bevy_tasks::{AsyncComputeTaskPool, ComputeTaskPool, IoTaskPool, TaskPoolBuilder};
 use bevy_utils::tracing::trace;
 
@@ -47,6 +47,16 @@ pub struct DefaultTaskPoolOptions {
     pub async_compute: TaskPoolThreadAssignmentPolicy,
     /// Used to determine number of compute threads to allocate
     pub compute: TaskPoolThreadAssignmentPolicy,
+
+    /// If true, each pool's threads are pinned to their own range of logical cores, in the order
+    /// IO, async compute, compute. This keeps the pools from competing for the same cores. Pinning
+    /// is only supported on Linux and Android, and is ignored on other platforms.
+    pub pin_threads: bool,
+
+    /// If true, the default pools are created without threads. Tasks and parallel system stages
+    /// then run on the main thread in a deterministic order, which helps with debugging
+    /// nondeterministic behavior. The other thread options are ignored.
+    pub single_threaded: bool,
 }
 
 impl Default for DefaultTaskPoolOptions {
@@ -76,6 +86,9 @@ impl Default for DefaultTaskPoolOptions {
                 max_threads: std::usize::MAX,
                 percent: 1.0, // This 1.0 here means "whatever is left over"
             },
+
+            pin_threads: false,
+            single_threaded: false,
         }
     }
 }
@@ -90,8 +103,34 @@ impl DefaultTaskPoolOptions {
         }
     }
 
+    /// Create a configuration that runs every task and system on the main thread
+    pub fn single_threaded() -> Self {
+        DefaultTaskPoolOptions {
+            single_threaded: true,
+            ..Default::default()
+        }
+    }
+
     /// Inserts the default thread pools into the given resource map based on the configured values
     pub fn create_default_pools(&self, resources: &mut Resources) {
+        if self.single_threaded {
+            trace!("Creating single threaded task pools");
+            if !resources.contains::<IoTaskPool>() {
+                resources.insert(IoTaskPool(TaskPoolBuilder::new().num_threads(0).build()));
+            }
+            if !resources.contains::<AsyncComputeTaskPool>() {
+                resources.insert(AsyncComputeTaskPool(
+                    TaskPoolBuilder::new().num_threads(0).build(),
+                ));
+            }
+            if !resources.contains::<ComputeTaskPool>() {
+                resources.insert(ComputeTaskPool(
+                    TaskPoolBuilder::new().num_threads(0).build(),
+                ));
+            }
+            return;
+        }
+
         let total_threads = bevy_math::clamp(
             bevy_tasks::logical_core_count(),
             self.min_total_threads,
@@ -100,6 +139,21 @@ impl DefaultTaskPoolOptions {
         trace!("Assigning {} cores to default task pools", total_threads);
 
         let mut remaining_threads = total_threads;
+        let logical_cores = bevy_tasks::logical_core_count().max(1);
+        let mut next_core = 0;
+        // assigns the next `threads` cores to a pool, wrapping around if there are more threads
+        // than cores
+        let mut assign_cores = |builder: TaskPoolBuilder, threads: usize| {
+            if !self.pin_threads {
+                return builder;
+            }
+            let core_ids = (next_core..next_core + threads)
+                .map(|core| core % logical_cores)
+                .collect::<Vec<_>>();
+            trace!("Pinning threads to cores {:?}", core_ids);
+            next_core += threads;
+            builder.core_ids(core_ids)
+        };
 
         if !resources.contains::<IoTaskPool>() {
             // Determine the number of IO threads we will use
@@ -111,7 +165,7 @@ impl DefaultTaskPoolOptions {
             remaining_threads = remaining_threads.saturating_sub(io_threads);
 
             resources.insert(IoTaskPool(
-                TaskPoolBuilder::default()
+                assign_cores(TaskPoolBuilder::default(), io_threads)
                     .num_threads(io_threads)
                     .thread_name("IO Task Pool".to_string())
                     .build(),
@@ -128,7 +182,7 @@ impl DefaultTaskPoolOptions {
             remaining_threads = remaining_threads.saturating_sub(async_compute_threads);
 
             resources.insert(AsyncComputeTaskPool(
-                TaskPoolBuilder::default()
+                assign_cores(TaskPoolBuilder::default(), async_compute_threads)
                     .num_threads(async_compute_threads)
                     .thread_name("Async Compute Task Pool".to_string())
                     .build(),
@@ -144,7 +198,7 @@ impl DefaultTaskPoolOptions {
 
             trace!("Compute Threads: {}", compute_threads);
             resources.insert(ComputeTaskPool(
-                TaskPoolBuilder::default()
+                assign_cores(TaskPoolBuilder::default(), compute_threads)
                     .num_threads(compute_threads)
                     .thread_name("Compute Task Pool".to_string())
                     .build(),
@@ -152,3 +206,17 @@ impl DefaultTaskPoolOptions {
         }
     }
 }
+
+/// Runs the tasks of pools that have no threads of their own, which is the case when the app is
+/// [single threaded](DefaultTaskPoolOptions::single_threaded)
+pub(crate) fn tick_single_threaded_task_pools_system(
+    compute: Res<ComputeTaskPool>,
+    async_compute: Res<AsyncComputeTaskPool>,
+    io: Res<IoTaskPool>,
+) {
+    for pool in [&compute.0, &async_compute.0, &io.0].iter() {
+        if pool.thread_num() == 0 {
+            while pool.try_tick() {}
+        }
+    }
+}
@@ -25,7 +25,9 @@ pub fn derive_bytes(input: TokenStream) -> TokenStream {
 }
 
 /// Derives the RenderResources trait. Each field must implement RenderResource or this will fail.
-/// You can ignore fields using `#[render_resources(ignore)]`.
+/// You can ignore fields using `#[render_resources(ignore)]`. A field of type
+/// `Option<SamplerDescriptor>` marked with `#[render_resources(sampler)]` overrides the sampler of
+/// every texture in the struct when it is `Some`.
 #[proc_macro_derive(RenderResources, attributes(render_resources, as_crate))]
 pub fn derive_render_resources(input: TokenStream) -> TokenStream {
     render_resources::derive_render_resources(input)
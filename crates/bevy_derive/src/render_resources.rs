@@ -9,6 +9,7 @@ use syn::{
 struct RenderResourceFieldAttributes {
     pub ignore: bool,
     pub buffer: bool,
+    pub sampler: bool,
 }
 
 #[derive(Default)]
@@ -93,12 +94,15 @@ pub fn derive_render_resources(input: TokenStream) -> TokenStream {
                         .map_or_else(RenderResourceFieldAttributes::default, |a| {
                             syn::custom_keyword!(ignore);
                             syn::custom_keyword!(buffer);
+                            syn::custom_keyword!(sampler);
                             let mut attributes = RenderResourceFieldAttributes::default();
                             a.parse_args_with(|input: ParseStream| {
                                 if input.parse::<Option<ignore>>()?.is_some() {
                                     attributes.ignore = true;
                                 } else if input.parse::<Option<buffer>>()?.is_some() {
                                     attributes.buffer = true;
+                                } else if input.parse::<Option<sampler>>()?.is_some() {
+                                    attributes.sampler = true;
                                 }
                                 Ok(())
                             })
@@ -112,7 +116,12 @@ pub fn derive_render_resources(input: TokenStream) -> TokenStream {
         let mut render_resource_names = Vec::new();
         let mut render_resource_fields = Vec::new();
         let mut render_resource_hints = Vec::new();
+        let mut sampler_field = None;
         for (field, attrs) in field_attributes.iter() {
+            if attrs.sampler {
+                sampler_field = Some(field.ident.as_ref().unwrap());
+                continue;
+            }
             if attrs.ignore {
                 continue;
             }
@@ -129,6 +138,14 @@ pub fn derive_render_resources(input: TokenStream) -> TokenStream {
             }
         }
 
+        let get_sampler = sampler_field.map(|sampler_field| {
+            quote! {
+                fn get_sampler(&self, _index: usize) -> Option<&#bevy_render_path::texture::SamplerDescriptor> {
+                    self.#sampler_field.as_ref()
+                }
+            }
+        });
+
         let render_resource_count = render_resource_names.len();
         let render_resource_indices = 0..render_resource_count;
 
@@ -167,6 +184,8 @@ pub fn derive_render_resources(input: TokenStream) -> TokenStream {
                     #render_resource_hints_ident.get(index).and_then(|o| *o)
                 }
 
+                #get_sampler
+
                 fn iter(&self) -> #bevy_render_path::renderer::RenderResourceIterator {
                     #bevy_render_path::renderer::RenderResourceIterator::new(self)
                 }
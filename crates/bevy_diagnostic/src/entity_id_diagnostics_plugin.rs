@@ -0,0 +1,78 @@
+use crate::{Diagnostic, DiagnosticId, Diagnostics};
+use bevy_app::prelude::*;
+use bevy_ecs::{EntityStats, IntoSystem, ResMut, Resources, World};
+
+/// Adds diagnostics about the reuse of entity ids to an App: the number of entities allocated and
+/// recycled each frame, the number of distinct ids in use, and how close ids are to running out of
+/// generations
+#[derive(Default)]
+pub struct EntityIdDiagnosticsPlugin;
+
+#[derive(Default)]
+pub struct EntityIdDiagnosticsState {
+    last_stats: EntityStats,
+}
+
+impl Plugin for EntityIdDiagnosticsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_startup_system(Self::setup_system.system())
+            .init_resource::<EntityIdDiagnosticsState>()
+            .add_system(Self::diagnostic_system.system());
+    }
+}
+
+impl EntityIdDiagnosticsPlugin {
+    pub const ALLOCATED_ENTITIES: DiagnosticId =
+        DiagnosticId::from_u128(140243553713838307474384941604338915561);
+    pub const RECYCLED_ENTITIES: DiagnosticId =
+        DiagnosticId::from_u128(276342458716093498167419637049915305113);
+    pub const ENTITY_IDS: DiagnosticId =
+        DiagnosticId::from_u128(62745712335284390712367470920113802671);
+    pub const RETIRED_ENTITY_IDS: DiagnosticId =
+        DiagnosticId::from_u128(194380255372836103916530883245813466457);
+    pub const MAX_ENTITY_GENERATION: DiagnosticId =
+        DiagnosticId::from_u128(316735489637419820133466208754393541802);
+
+    pub fn setup_system(mut diagnostics: ResMut<Diagnostics>) {
+        diagnostics.add(Diagnostic::new(
+            Self::ALLOCATED_ENTITIES,
+            "allocated_entities",
+            20,
+        ));
+        diagnostics.add(Diagnostic::new(
+            Self::RECYCLED_ENTITIES,
+            "recycled_entities",
+            20,
+        ));
+        diagnostics.add(Diagnostic::new(Self::ENTITY_IDS, "entity_ids", 1));
+        diagnostics.add(Diagnostic::new(
+            Self::RETIRED_ENTITY_IDS,
+            "retired_entity_ids",
+            1,
+        ));
+        diagnostics.add(Diagnostic::new(
+            Self::MAX_ENTITY_GENERATION,
+            "max_entity_generation",
+            1,
+        ));
+    }
+
+    pub fn diagnostic_system(world: &mut World, resources: &mut Resources) {
+        let stats = world.entity_stats();
+        let mut state = resources.get_mut::<EntityIdDiagnosticsState>().unwrap();
+        if let Some(mut diagnostics) = resources.get_mut::<Diagnostics>() {
+            diagnostics.add_measurement(
+                Self::ALLOCATED_ENTITIES,
+                (stats.allocated - state.last_stats.allocated) as f64,
+            );
+            diagnostics.add_measurement(
+                Self::RECYCLED_ENTITIES,
+                (stats.recycled - state.last_stats.recycled) as f64,
+            );
+            diagnostics.add_measurement(Self::ENTITY_IDS, stats.ids as f64);
+            diagnostics.add_measurement(Self::RETIRED_ENTITY_IDS, stats.retired as f64);
+            diagnostics.add_measurement(Self::MAX_ENTITY_GENERATION, stats.max_generation as f64);
+        }
+        state.last_stats = stats;
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{Diagnostic, DiagnosticId, Diagnostics};
+use bevy_app::prelude::*;
+use bevy_ecs::{IntoSystem, Res, ResMut};
+use std::marker::PhantomData;
+
+/// Adds diagnostics about the events of type `T` to an App: the number of events sent each frame,
+/// and the number that were dropped or coalesced because the [Events] collection is bounded. The
+/// diagnostics are named after the full type name of `T`, like `events/my_game::MyEvent`, unless
+/// the plugin is given a label.
+pub struct EventDiagnosticsPlugin<T> {
+    label: Option<String>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for EventDiagnosticsPlugin<T> {
+    fn default() -> Self {
+        EventDiagnosticsPlugin {
+            label: None,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> EventDiagnosticsPlugin<T> {
+    /// Names the diagnostics after `label` instead of the type name of `T`, like `events/{label}`
+    pub fn with_label(label: &str) -> Self {
+        EventDiagnosticsPlugin {
+            label: Some(label.to_string()),
+            marker: PhantomData,
+        }
+    }
+}
+
+/// The ids of the diagnostics added by [EventDiagnosticsPlugin]
+pub struct EventDiagnosticIds<T> {
+    pub sent: DiagnosticId,
+    pub dropped: DiagnosticId,
+    pub coalesced: DiagnosticId,
+    /// What the diagnostics are named after
+    pub label: String,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for EventDiagnosticIds<T> {
+    fn default() -> Self {
+        EventDiagnosticIds {
+            sent: DiagnosticId::default(),
+            dropped: DiagnosticId::default(),
+            coalesced: DiagnosticId::default(),
+            label: std::any::type_name::<T>().to_string(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Send + Sync + 'static> Plugin for EventDiagnosticsPlugin<T> {
+    fn build(&self, app: &mut AppBuilder) {
+        let mut ids = EventDiagnosticIds::<T>::default();
+        if let Some(label) = &self.label {
+            ids.label = label.clone();
+        }
+        app.add_startup_system(Self::setup_system.system())
+            .add_resource(ids)
+            .add_system(Self::diagnostic_system.system());
+    }
+}
+
+impl<T: Send + Sync + 'static> EventDiagnosticsPlugin<T> {
+    pub fn setup_system(ids: Res<EventDiagnosticIds<T>>, mut diagnostics: ResMut<Diagnostics>) {
+        let name = &ids.label;
+        diagnostics.add(Diagnostic::new(ids.sent, &format!("events/{}", name), 20));
+        diagnostics.add(Diagnostic::new(
+            ids.dropped,
+            &format!("dropped_events/{}", name),
+            20,
+        ));
+        diagnostics.add(Diagnostic::new(
+            ids.coalesced,
+            &format!("coalesced_events/{}", name),
+            20,
+        ));
+    }
+
+    pub fn diagnostic_system(
+        ids: Res<EventDiagnosticIds<T>>,
+        mut diagnostics: ResMut<Diagnostics>,
+        events: Res<Events<T>>,
+    ) {
+        let stats = events.last_update_stats();
+        diagnostics.add_measurement(ids.sent, stats.sent as f64);
+        diagnostics.add_measurement(ids.dropped, stats.dropped as f64);
+        diagnostics.add_measurement(ids.coalesced, stats.coalesced as f64);
+    }
+}
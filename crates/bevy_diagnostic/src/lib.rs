@@ -1,9 +1,17 @@
 mod diagnostic;
+mod entity_id_diagnostics_plugin;
+mod event_diagnostics_plugin;
 mod frame_time_diagnostics_plugin;
 mod print_diagnostics_plugin;
+mod task_pool_diagnostics_plugin;
 pub use diagnostic::*;
+pub use entity_id_diagnostics_plugin::{EntityIdDiagnosticsPlugin, EntityIdDiagnosticsState};
+pub use event_diagnostics_plugin::{EventDiagnosticIds, EventDiagnosticsPlugin};
 pub use frame_time_diagnostics_plugin::FrameTimeDiagnosticsPlugin;
 pub use print_diagnostics_plugin::PrintDiagnosticsPlugin;
+pub use task_pool_diagnostics_plugin::{
+    TaskPoolDiagnosticIds, TaskPoolDiagnosticsPlugin, TaskPoolDiagnosticsState,
+};
 
 use bevy_app::prelude::*;
 
@@ -0,0 +1,128 @@
+use crate::{Diagnostic, DiagnosticId, Diagnostics};
+use bevy_app::prelude::*;
+use bevy_ecs::{IntoSystem, ResMut, Resources, World};
+use bevy_tasks::{AsyncComputeTaskPool, ComputeTaskPool, IoTaskPool, TaskPool, TaskPoolStats};
+
+/// Adds diagnostics about the occupancy of the default task pools to an App: the number of tasks
+/// queued and running at the end of each frame, the number of tasks completed during the frame,
+/// and their average duration in seconds. The full duration histogram of a pool is available from
+/// [TaskPool::stats].
+#[derive(Default)]
+pub struct TaskPoolDiagnosticsPlugin;
+
+/// The ids of the diagnostics of one task pool
+#[derive(Debug, Clone, Copy)]
+pub struct TaskPoolDiagnosticIds {
+    pub queued: DiagnosticId,
+    pub running: DiagnosticId,
+    pub completed: DiagnosticId,
+    pub task_duration: DiagnosticId,
+}
+
+#[derive(Default)]
+pub struct TaskPoolDiagnosticsState {
+    compute: TaskPoolStats,
+    async_compute: TaskPoolStats,
+    io: TaskPoolStats,
+}
+
+impl Plugin for TaskPoolDiagnosticsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_startup_system(Self::setup_system.system())
+            .init_resource::<TaskPoolDiagnosticsState>()
+            .add_system(Self::diagnostic_system.system());
+    }
+}
+
+impl TaskPoolDiagnosticsPlugin {
+    pub const COMPUTE: TaskPoolDiagnosticIds = TaskPoolDiagnosticIds {
+        queued: DiagnosticId::from_u128(204571396845132765412739866532870814327),
+        running: DiagnosticId::from_u128(38457211937684205163482039756120394751),
+        completed: DiagnosticId::from_u128(261309487512376098412357690821346590184),
+        task_duration: DiagnosticId::from_u128(117650923487162309845761230984576120398),
+    };
+    pub const ASYNC_COMPUTE: TaskPoolDiagnosticIds = TaskPoolDiagnosticIds {
+        queued: DiagnosticId::from_u128(298761234509871623409875612309487561230),
+        running: DiagnosticId::from_u128(76512390487561230984756123098475612309),
+        completed: DiagnosticId::from_u128(153409876512309847561239087456123098745),
+        task_duration: DiagnosticId::from_u128(223098475612309847561230984756120398476),
+    };
+    pub const IO: TaskPoolDiagnosticIds = TaskPoolDiagnosticIds {
+        queued: DiagnosticId::from_u128(19283746501928374650192837465019283746),
+        running: DiagnosticId::from_u128(310928374650192837465019283746501928374),
+        completed: DiagnosticId::from_u128(88273645509182736455091827364550918273),
+        task_duration: DiagnosticId::from_u128(176354829101928374655647382910192837465),
+    };
+
+    pub fn setup_system(mut diagnostics: ResMut<Diagnostics>) {
+        for (ids, pool) in [
+            (Self::COMPUTE, "compute"),
+            (Self::ASYNC_COMPUTE, "async_compute"),
+            (Self::IO, "io"),
+        ]
+        .iter()
+        {
+            diagnostics.add(Diagnostic::new(
+                ids.queued,
+                &format!("{}_tasks_queued", pool),
+                20,
+            ));
+            diagnostics.add(Diagnostic::new(
+                ids.running,
+                &format!("{}_tasks_running", pool),
+                20,
+            ));
+            diagnostics.add(Diagnostic::new(
+                ids.completed,
+                &format!("{}_tasks_completed", pool),
+                20,
+            ));
+            diagnostics.add(Diagnostic::new(
+                ids.task_duration,
+                &format!("{}_task_duration", pool),
+                20,
+            ));
+        }
+    }
+
+    pub fn diagnostic_system(_world: &mut World, resources: &mut Resources) {
+        let mut state = resources.get_mut::<TaskPoolDiagnosticsState>().unwrap();
+        let mut diagnostics = match resources.get_mut::<Diagnostics>() {
+            Some(diagnostics) => diagnostics,
+            None => return,
+        };
+        if let Some(pool) = resources.get::<ComputeTaskPool>() {
+            Self::measure(&mut diagnostics, &Self::COMPUTE, &pool, &mut state.compute);
+        }
+        if let Some(pool) = resources.get::<AsyncComputeTaskPool>() {
+            Self::measure(
+                &mut diagnostics,
+                &Self::ASYNC_COMPUTE,
+                &pool,
+                &mut state.async_compute,
+            );
+        }
+        if let Some(pool) = resources.get::<IoTaskPool>() {
+            Self::measure(&mut diagnostics, &Self::IO, &pool, &mut state.io);
+        }
+    }
+
+    fn measure(
+        diagnostics: &mut Diagnostics,
+        ids: &TaskPoolDiagnosticIds,
+        pool: &TaskPool,
+        last_stats: &mut TaskPoolStats,
+    ) {
+        let stats = pool.stats();
+        diagnostics.add_measurement(ids.queued, stats.queued as f64);
+        diagnostics.add_measurement(ids.running, stats.running as f64);
+        diagnostics.add_measurement(
+            ids.completed,
+            (stats.completed - last_stats.completed) as f64,
+        );
+        if let Some(duration) = stats.average_duration_since(last_stats) {
+            diagnostics.add_measurement(ids.task_duration, duration.as_secs_f64());
+        }
+        *last_stats = stats;
+    }
+}
@@ -0,0 +1,180 @@
+use bevy_ecs::{Resources, Stage, SystemStage, World};
+use bevy_utils::{
+    tracing::{error, info},
+    Duration, Instant,
+};
+use libloading::{Library, Symbol};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use thiserror::Error;
+
+/// Registers the systems of a hot-reloadable library. Libraries loaded with
+/// [DynamicPluginExt::load_plugin_dynamic](crate::DynamicPluginExt::load_plugin_dynamic) must
+/// export a function with this signature, named [REGISTER_SYSTEMS_SYMBOL]:
+///
+/// ```ignore
+/// #[no_mangle]
+/// pub fn _bevy_register_systems(stage: &mut SystemStage) {
+///     stage.add_system(player_movement.system());
+/// }
+/// ```
+pub type RegisterSystems = unsafe fn(stage: &mut SystemStage);
+
+/// The name of the [RegisterSystems] function exported by hot-reloadable libraries
+pub const REGISTER_SYSTEMS_SYMBOL: &[u8] = b"_bevy_register_systems";
+
+#[derive(Error, Debug)]
+pub enum HotReloadError {
+    #[error("failed to copy library")]
+    Copy(#[from] io::Error),
+    #[error("failed to load library")]
+    Load(#[from] libloading::Error),
+}
+
+/// A stage that runs the systems registered by a dynamically loaded library, and reloads them
+/// whenever the library file changes. The file is checked for changes once every
+/// `check_interval`, rather than every frame.
+///
+/// The library is copied to a temporary file before it is loaded, so it can be rebuilt while the
+/// app is running. Only systems are reloaded: the world and resources are left untouched. Component
+/// and resource types must be defined in a crate that both the app and the library link to
+/// dynamically, otherwise their type ids will not match.
+///
+/// Libraries are never unloaded, not even when the stage is dropped: the world and resources keep
+/// code from every version of the library, like the drop functions of the components its systems
+/// spawned and the storage of their [Local](bevy_ecs::Local) resources. Every reload keeps
+/// another copy of the library in memory until the app exits.
+pub struct HotReloadStage {
+    path: PathBuf,
+    check_interval: Duration,
+    last_check: Option<Instant>,
+    modified: Option<SystemTime>,
+    reloads: usize,
+    stage: Option<SystemStage>,
+    /// Every library that was loaded, and the copy it was loaded from
+    libraries: Vec<(Library, PathBuf)>,
+}
+
+impl HotReloadStage {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        HotReloadStage {
+            path: path.as_ref().to_path_buf(),
+            check_interval: Duration::from_millis(500),
+            last_check: None,
+            modified: None,
+            reloads: 0,
+            stage: None,
+            libraries: Vec::new(),
+        }
+    }
+
+    /// Sets how often the library file is checked for changes. Defaults to twice per second.
+    pub fn with_check_interval(mut self, check_interval: Duration) -> Self {
+        self.check_interval = check_interval;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The number of times the library has been loaded
+    pub fn reloads(&self) -> usize {
+        self.reloads
+    }
+
+    fn reload(&mut self) -> Result<(), HotReloadError> {
+        let file_name = self
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        // dlopen returns the already loaded library when a path is opened twice, so each reload
+        // needs a new file name
+        let copy_path = std::env::temp_dir().join(format!(
+            "{}-{}-{}",
+            std::process::id(),
+            self.reloads,
+            file_name
+        ));
+        fs::copy(&self.path, &copy_path)?;
+
+        let result = unsafe { load_systems(&copy_path) };
+        let (library, stage) = match result {
+            Ok(loaded) => loaded,
+            Err(err) => {
+                let _ = fs::remove_file(&copy_path);
+                return Err(err);
+            }
+        };
+
+        // the old library stays loaded, as the world and resources can still hold its code
+        self.stage = Some(stage);
+        self.libraries.push((library, copy_path));
+        self.reloads += 1;
+        Ok(())
+    }
+}
+
+unsafe fn load_systems(path: &Path) -> Result<(Library, SystemStage), HotReloadError> {
+    let library = Library::new(path)?;
+    let mut stage = SystemStage::serial();
+    {
+        let register: Symbol<RegisterSystems> = library.get(REGISTER_SYSTEMS_SYMBOL)?;
+        register(&mut stage);
+    }
+    Ok((library, stage))
+}
+
+impl Stage for HotReloadStage {
+    fn initialize(&mut self, world: &mut World, resources: &mut Resources) {
+        let now = Instant::now();
+        let check_interval = self.check_interval;
+        let check_due = self.last_check.map_or(true, |last_check| {
+            now.duration_since(last_check) >= check_interval
+        });
+        if check_due {
+            self.last_check = Some(now);
+            let modified = fs::metadata(&self.path)
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            if modified.is_some() && modified != self.modified {
+                self.modified = modified;
+                match self.reload() {
+                    Ok(()) => info!("Loaded systems from {}", self.path.display()),
+                    // the previous systems keep running, and loading is retried on the next change
+                    Err(err) => error!(
+                        "Failed to load systems from {}: {:?}",
+                        self.path.display(),
+                        err
+                    ),
+                }
+            }
+        }
+
+        if let Some(stage) = self.stage.as_mut() {
+            stage.initialize(world, resources);
+        }
+    }
+
+    fn run(&mut self, world: &mut World, resources: &mut Resources) {
+        if let Some(stage) = self.stage.as_mut() {
+            stage.run(world, resources);
+        }
+    }
+}
+
+impl Drop for HotReloadStage {
+    fn drop(&mut self) {
+        self.stage = None;
+        for (library, path) in self.libraries.drain(..) {
+            // the world and resources may outlive the stage, so the code they hold is never
+            // unloaded. The copy is removed where the platform allows removing loaded files.
+            std::mem::forget(library);
+            let _ = fs::remove_file(path);
+        }
+    }
+}
@@ -1,3 +1,5 @@
+mod hot_reload;
 mod loader;
 
+pub use hot_reload::*;
 pub use loader::*;
@@ -1,6 +1,9 @@
 use libloading::{Library, Symbol};
 
 use bevy_app::{AppBuilder, CreatePlugin, Plugin};
+use std::path::Path;
+
+use crate::HotReloadStage;
 
 /// Dynamically links a plugin a the given path. The plugin must export the [CreatePlugin] function.
 pub fn dynamically_load_plugin(path: &str) -> (Library, Box<dyn Plugin>) {
@@ -15,6 +18,12 @@ pub fn dynamically_load_plugin(path: &str) -> (Library, Box<dyn Plugin>) {
 
 pub trait DynamicPluginExt {
     fn load_plugin(&mut self, path: &str) -> &mut Self;
+
+    /// Runs the systems registered by the library at the given path in a new stage after UPDATE,
+    /// and reloads them whenever the library is rebuilt. The library must export a
+    /// [RegisterSystems](crate::RegisterSystems) function. See [HotReloadStage] for the limitations
+    /// of hot reloading.
+    fn load_plugin_dynamic(&mut self, path: impl AsRef<Path>) -> &mut Self;
 }
 
 impl DynamicPluginExt for AppBuilder {
@@ -23,4 +32,14 @@ impl DynamicPluginExt for AppBuilder {
         plugin.build(self);
         self
     }
+
+    fn load_plugin_dynamic(&mut self, path: impl AsRef<Path>) -> &mut Self {
+        let stage = HotReloadStage::new(path);
+        // the schedule stores stage names itself, so the name doesn't need to be static
+        let name = format!("hot_reload {}", stage.path().display());
+        self.app
+            .schedule
+            .add_stage_after(bevy_app::stage::UPDATE, &name, stage);
+        self
+    }
 }
@@ -31,7 +31,19 @@ use syn::{
 ///
 /// Using derived `Bundle` impls improves spawn performance and can be convenient when combined with
 /// other derives like `serde::Deserialize`.
-#[proc_macro_derive(Bundle)]
+///
+/// Fields marked with `#[bundle]` are bundles themselves, and their components are added to the
+/// entity instead of the field:
+///
+/// ```ignore
+/// #[derive(Bundle)]
+/// struct ChunkBundle {
+///     chunk: Chunk,
+///     #[bundle]
+///     sprite: SpriteBundle,
+/// }
+/// ```
+#[proc_macro_derive(Bundle, attributes(bundle))]
 pub fn derive_bundle(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     match derive_bundle_(input) {
@@ -54,6 +66,7 @@ fn derive_bundle_(input: DeriveInput) -> Result<TokenStream2> {
         }
     };
     let (tys, field_members) = struct_fields(&data.fields);
+    let bundle_fields = bundle_fields(&data.fields);
     let manifest = Manifest::new().unwrap();
     let path_str = if let Some(package) = manifest.find(|name| name == "bevy") {
         format!("{}::ecs", package.name)
@@ -68,10 +81,26 @@ fn derive_bundle_(input: DeriveInput) -> Result<TokenStream2> {
     let field_idents = member_as_idents(&field_members);
     let generics = add_additional_bounds_to_generic_params(&crate_path, input.generics);
 
-    let dyn_bundle_code =
-        gen_dynamic_bundle_impl(&crate_path, &ident, &generics, &field_members, &tys);
+    let dyn_bundle_code = gen_dynamic_bundle_impl(
+        &crate_path,
+        &ident,
+        &generics,
+        &field_members,
+        &tys,
+        &bundle_fields,
+    );
     let bundle_code = if tys.is_empty() {
         gen_unit_struct_bundle_impl(&crate_path, ident, &generics)
+    } else if bundle_fields.iter().any(|is_bundle| *is_bundle) {
+        gen_nested_bundle_impl(
+            &crate_path,
+            &ident,
+            &generics,
+            &field_members,
+            &field_idents,
+            &tys,
+            &bundle_fields,
+        )
     } else {
         gen_bundle_impl(
             &crate_path,
@@ -93,8 +122,27 @@ fn gen_dynamic_bundle_impl(
     generics: &syn::Generics,
     field_members: &[syn::Member],
     tys: &[&syn::Type],
+    bundle_fields: &[bool],
 ) -> TokenStream2 {
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let put = field_members
+        .iter()
+        .zip(tys.iter())
+        .zip(bundle_fields.iter())
+        .map(|((field_member, ty), is_bundle)| {
+            if *is_bundle {
+                quote! {
+                    ::#crate_path::DynamicBundle::put(self.#field_member, &mut f);
+                }
+            } else {
+                quote! {
+                    if f((&mut self.#field_member as *mut #ty).cast::<u8>(), ::std::any::TypeId::of::<#ty>(), ::std::mem::size_of::<#ty>()) {
+                        #[allow(clippy::forget_copy)]
+                        ::std::mem::forget(self.#field_member);
+                    }
+                }
+            }
+        });
     quote! {
         impl #impl_generics ::#crate_path::DynamicBundle for #ident #ty_generics #where_clause {
             fn with_ids<__hecs__T>(&self, f: impl ::std::ops::FnOnce(&[::std::any::TypeId]) -> __hecs__T) -> __hecs__T {
@@ -105,14 +153,9 @@ fn gen_dynamic_bundle_impl(
                 <Self as ::#crate_path::Bundle>::static_type_info()
             }
 
-            #[allow(clippy::forget_copy)]
+            #[allow(clippy::forget_copy, unused_mut)]
             unsafe fn put(mut self, mut f: impl ::std::ops::FnMut(*mut u8, ::std::any::TypeId, usize) -> bool) {
-                #(
-                    if f((&mut self.#field_members as *mut #tys).cast::<u8>(), ::std::any::TypeId::of::<#tys>(), ::std::mem::size_of::<#tys>()) {
-                        #[allow(clippy::forget_copy)]
-                        ::std::mem::forget(self.#field_members);
-                    }
-                )*
+                #(#put)*
             }
         }
     }
@@ -185,6 +228,96 @@ fn gen_bundle_impl(
     }
 }
 
+// nested bundles only know their components' TypeInfo, so the ids are collected from that
+fn gen_nested_bundle_impl(
+    crate_path: &syn::Path,
+    ident: &syn::Ident,
+    generics: &syn::Generics,
+    field_members: &[syn::Member],
+    field_idents: &[Cow<syn::Ident>],
+    tys: &[&syn::Type],
+    bundle_fields: &[bool],
+) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let mut type_info = Vec::new();
+    let mut get = Vec::new();
+    let mut read = Vec::new();
+    for (((field_member, field_ident), ty), is_bundle) in field_members
+        .iter()
+        .zip(field_idents.iter())
+        .zip(tys.iter())
+        .zip(bundle_fields.iter())
+    {
+        if *is_bundle {
+            type_info.push(quote! {
+                info.extend(<#ty as ::#crate_path::Bundle>::static_type_info());
+            });
+            // the world keeps ownership of the components if a later field is missing, so the
+            // copies read by nested bundles must not be dropped
+            get.push(quote! {
+                let #field_ident = ::std::mem::ManuallyDrop::new(<#ty as ::#crate_path::Bundle>::get(&mut f)?);
+            });
+            read.push(quote! {
+                #field_member: ::std::mem::ManuallyDrop::into_inner(#field_ident)
+            });
+        } else {
+            type_info.push(quote! {
+                info.push(::#crate_path::TypeInfo::of::<#ty>());
+            });
+            get.push(quote! {
+                let #field_ident = f(::std::any::TypeId::of::<#ty>(), ::std::mem::size_of::<#ty>())
+                        .ok_or_else(::#crate_path::MissingComponent::new::<#ty>)?
+                        .cast::<#ty>()
+                        .as_ptr();
+            });
+            read.push(quote! {
+                #field_member: #field_ident.read()
+            });
+        }
+    }
+    // statics can't refer to `Self`
+    let with_static_ids_inner = quote! {
+        <#ident #ty_generics as ::#crate_path::Bundle>::static_type_info()
+            .iter()
+            .map(|info| info.id())
+            .collect::<::std::vec::Vec<_>>()
+    };
+    let with_static_ids_body = if generics.params.is_empty() {
+        quote! {
+            ::#crate_path::lazy_static::lazy_static! {
+                static ref ELEMENTS: ::std::vec::Vec<::std::any::TypeId> = #with_static_ids_inner;
+            }
+            f(&*ELEMENTS)
+        }
+    } else {
+        quote! {
+            f(&#with_static_ids_inner)
+        }
+    };
+    quote! {
+        impl #impl_generics ::#crate_path::Bundle for #ident #ty_generics #where_clause {
+            #[allow(non_camel_case_types)]
+            fn with_static_ids<__hecs__T>(f: impl ::std::ops::FnOnce(&[::std::any::TypeId]) -> __hecs__T) -> __hecs__T {
+                #with_static_ids_body
+            }
+
+            fn static_type_info() -> ::std::vec::Vec<::#crate_path::TypeInfo> {
+                let mut info = ::std::vec::Vec::new();
+                #(#type_info)*
+                info.sort_unstable();
+                info
+            }
+
+            unsafe fn get(
+                mut f: impl ::std::ops::FnMut(::std::any::TypeId, usize) -> ::std::option::Option<::std::ptr::NonNull<u8>>,
+            ) -> ::std::result::Result<Self, ::#crate_path::MissingComponent> {
+                #(#get)*
+                ::std::result::Result::Ok(Self { #(#read,)* })
+            }
+        }
+    }
+}
+
 // no reason to generate a static for unit structs
 fn gen_unit_struct_bundle_impl(
     crate_path: &syn::Path,
@@ -254,6 +387,13 @@ fn struct_fields(fields: &syn::Fields) -> (Vec<&syn::Type>, Vec<syn::Member>) {
     }
 }
 
+fn bundle_fields(fields: &syn::Fields) -> Vec<bool> {
+    fields
+        .iter()
+        .map(|field| field.attrs.iter().any(|attr| attr.path.is_ident("bundle")))
+        .collect()
+}
+
 fn member_as_idents(members: &[syn::Member]) -> Vec<Cow<'_, syn::Ident>> {
     members
         .iter()
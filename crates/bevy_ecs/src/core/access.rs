@@ -240,6 +240,16 @@ impl<T: Hash + Eq + PartialEq + Copy> TypeAccess<T> {
         self.reads_and_writes.intersection(&other.writes).next()
     }
 
+    /// Returns every type accessed by both `self` and `other`, where at least one of them has
+    /// mutable access
+    pub fn get_conflicts<'a>(&'a self, other: &'a TypeAccess<T>) -> impl Iterator<Item = &'a T> {
+        self.writes.intersection(&other.reads_and_writes).chain(
+            self.reads_and_writes
+                .intersection(&other.writes)
+                .filter(move |ty| !self.writes.contains(ty)),
+        )
+    }
+
     pub fn union(&mut self, other: &TypeAccess<T>) {
         self.writes.extend(&other.writes);
         self.reads.extend(&other.reads);
@@ -25,6 +25,7 @@ use std::{
     hash::{BuildHasherDefault, Hasher},
     mem,
     ptr::{self, NonNull},
+    sync::atomic::{AtomicU8, Ordering},
 };
 
 /// A collection of entities having the same component types
@@ -415,7 +416,7 @@ impl Archetype {
         flags: ComponentFlags,
     ) {
         let state = self.state.get_mut(&ty).unwrap();
-        state.component_flags[index] = flags;
+        state.set_component_flags(index, flags);
         let ptr = (*self.data.get())
             .as_ptr()
             .add(state.offset + size * index)
@@ -447,6 +448,7 @@ pub struct TypeState {
     offset: usize,
     borrow: AtomicBorrow,
     component_flags: Vec<ComponentFlags>,
+    archetype_flags: ArchetypeFlags,
 }
 
 bitflags! {
@@ -462,6 +464,7 @@ impl TypeState {
             offset: 0,
             borrow: AtomicBorrow::new(),
             component_flags: Vec::new(),
+            archetype_flags: ArchetypeFlags::default(),
         }
     }
 
@@ -469,6 +472,7 @@ impl TypeState {
         for flags in self.component_flags.iter_mut() {
             *flags = ComponentFlags::empty();
         }
+        self.archetype_flags.clear();
     }
 
     #[allow(missing_docs)]
@@ -476,6 +480,58 @@ impl TypeState {
     pub fn component_flags(&self) -> NonNull<ComponentFlags> {
         unsafe { NonNull::new_unchecked(self.component_flags.as_ptr() as *mut ComponentFlags) }
     }
+
+    /// Sets the flags of the component at `index`
+    pub fn set_component_flags(&mut self, index: usize, flags: ComponentFlags) {
+        self.component_flags[index] = flags;
+        self.archetype_flags.insert(flags);
+    }
+
+    /// The flags of every component of this type in the archetype, combined
+    #[inline]
+    pub fn archetype_flags(&self) -> &ArchetypeFlags {
+        &self.archetype_flags
+    }
+}
+
+/// The [ComponentFlags] of every component of a type in an archetype, combined. These are cleared
+/// along with the flags of each component, so systems can skip whole archetypes in which nothing
+/// was added or mutated since the start of the frame.
+#[derive(Debug, Default)]
+pub struct ArchetypeFlags(AtomicU8);
+
+impl ArchetypeFlags {
+    #[allow(missing_docs)]
+    #[inline]
+    pub fn get(&self) -> ComponentFlags {
+        ComponentFlags::from_bits_truncate(self.0.load(Ordering::Relaxed))
+    }
+
+    /// Returns `true` if all of the given flags are set
+    #[inline]
+    pub fn contains(&self, flags: ComponentFlags) -> bool {
+        self.get().contains(flags)
+    }
+
+    /// Returns `true` if any of the given flags are set
+    #[inline]
+    pub fn intersects(&self, flags: ComponentFlags) -> bool {
+        self.get().intersects(flags)
+    }
+
+    #[allow(missing_docs)]
+    #[inline]
+    pub fn insert(&self, flags: ComponentFlags) {
+        // flags are usually already set, so only write to them when needed to avoid contention
+        // between threads mutating the same archetype
+        if !self.contains(flags) {
+            self.0.fetch_or(flags.bits(), Ordering::Relaxed);
+        }
+    }
+
+    fn clear(&mut self) {
+        *self.0.get_mut() = 0;
+    }
 }
 
 /// Metadata required to store a component
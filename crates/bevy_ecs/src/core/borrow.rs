@@ -14,7 +14,7 @@
 
 // modified by Bevy contributors
 
-use crate::{Archetype, Component, ComponentFlags, MissingComponent};
+use crate::{Archetype, ArchetypeFlags, Component, ComponentFlags, MissingComponent};
 use core::{
     fmt::Debug,
     ops::{Deref, DerefMut},
@@ -125,6 +125,7 @@ pub struct RefMut<'a, T: Component> {
     archetype: &'a Archetype,
     target: &'a mut T,
     flags: &'a mut ComponentFlags,
+    archetype_flags: &'a ArchetypeFlags,
 }
 
 impl<'a, T: Component> RefMut<'a, T> {
@@ -142,6 +143,7 @@ impl<'a, T: Component> RefMut<'a, T> {
             archetype,
             target: &mut *target.as_ptr().add(index),
             flags: &mut *type_state.component_flags().as_ptr().add(index),
+            archetype_flags: type_state.archetype_flags(),
         })
     }
 }
@@ -166,6 +168,7 @@ impl<'a, T: Component> Deref for RefMut<'a, T> {
 impl<'a, T: Component> DerefMut for RefMut<'a, T> {
     fn deref_mut(&mut self) -> &mut T {
         self.flags.insert(ComponentFlags::MUTATED);
+        self.archetype_flags.insert(ComponentFlags::MUTATED);
         self.target
     }
 }
@@ -41,6 +41,45 @@ pub trait DynamicBundle {
 
 /// A statically typed collection of components
 ///
+/// Bundles are implemented for tuples of components, and can be derived for structs. Fields of a
+/// derived bundle marked with `#[bundle]` are bundles themselves, whose components are added
+/// alongside the others:
+///
+/// ```
+/// use bevy_ecs::prelude::*;
+///
+/// struct Position(f32, f32);
+/// struct Velocity(f32, f32);
+/// struct Player;
+///
+/// #[derive(Bundle)]
+/// struct BodyBundle {
+///     position: Position,
+///     velocity: Velocity,
+/// }
+///
+/// #[derive(Bundle)]
+/// struct PlayerBundle {
+///     player: Player,
+///     #[bundle]
+///     body: BodyBundle,
+/// }
+///
+/// let mut world = World::default();
+/// let entity = world.spawn(PlayerBundle {
+///     player: Player,
+///     body: BodyBundle {
+///         position: Position(0.0, 0.0),
+///         velocity: Velocity(1.0, 0.0),
+///     },
+/// });
+/// assert!(world.get::<Velocity>(entity).is_ok());
+///
+/// let player = world.remove::<PlayerBundle>(entity).unwrap();
+/// assert_eq!(player.body.velocity.0, 1.0);
+/// assert!(world.get::<Position>(entity).is_err());
+/// ```
+///
 /// See [DynamicBundle]
 pub trait Bundle: DynamicBundle {
     #[doc(hidden)]
@@ -47,6 +47,12 @@ impl Entity {
     pub fn id(self) -> u32 {
         self.id
     }
+
+    /// The number of times the ID of this entity was used by an entity that has since been
+    /// despawned
+    pub fn generation(self) -> u32 {
+        self.generation
+    }
 }
 
 impl fmt::Debug for Entity {
@@ -55,6 +61,27 @@ impl fmt::Debug for Entity {
     }
 }
 
+/// The generation given to IDs that have been used by so many entities that their generation can't
+/// be incremented anymore. Retired IDs are never reused, so that a stale [Entity] can't refer to a
+/// new entity after the generation wraps around.
+const RETIRED_GENERATION: u32 = u32::MAX;
+
+/// Statistics about the allocation of entity IDs, returned by
+/// [World::entity_stats](crate::World::entity_stats)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EntityStats {
+    /// The number of entities that have been allocated
+    pub allocated: u64,
+    /// The number of allocated entities that reused the ID of a despawned entity
+    pub recycled: u64,
+    /// The number of distinct IDs that have been used
+    pub ids: u32,
+    /// The number of IDs that can't be reused because their generation would overflow
+    pub retired: u32,
+    /// The highest generation of any ID
+    pub max_generation: u32,
+}
+
 #[derive(Debug, Default)]
 pub(crate) struct Entities {
     pub meta: Vec<EntityMeta>,
@@ -69,6 +96,7 @@ pub(crate) struct Entities {
     // consumed and used to initialize locations to produce real entities after calling `flush`.
     reserved: Box<[AtomicU32]>,
     reserved_cursor: AtomicU32,
+    stats: EntityStats,
 }
 
 impl Entities {
@@ -132,6 +160,7 @@ impl Entities {
                 self.grow(0);
                 let cursor = self.free_cursor.fetch_sub(1, Ordering::Relaxed);
                 let id = self.free[(cursor - 1) as usize];
+                self.record_allocation(id);
                 Entity {
                     generation: self.meta[id as usize].generation,
                     id,
@@ -141,6 +170,7 @@ impl Entities {
                 // Not racey due to &mut self
                 self.free_cursor.store(next, Ordering::Relaxed);
                 let id = self.free[next as usize];
+                self.record_allocation(id);
                 Entity {
                     generation: self.meta[id as usize].generation,
                     id,
@@ -149,15 +179,30 @@ impl Entities {
         }
     }
 
+    fn record_allocation(&mut self, id: u32) {
+        self.stats.allocated += 1;
+        if id < self.stats.ids {
+            self.stats.recycled += 1;
+        } else {
+            self.stats.ids = id + 1;
+        }
+    }
+
+    pub fn stats(&self) -> EntityStats {
+        self.stats
+    }
+
     /// Destroy an entity, allowing it to be reused
     ///
     /// Must not be called on reserved entities prior to `flush`.
     pub fn free(&mut self, entity: Entity) -> Result<Location, NoSuchEntity> {
         let meta = &mut self.meta[entity.id as usize];
-        if meta.generation != entity.generation {
+        if !meta.is(entity) {
             return Err(NoSuchEntity);
         }
+        // live entities never have the retired generation, so this can't overflow
         meta.generation += 1;
+        let retired = meta.generation == RETIRED_GENERATION;
         let loc = mem::replace(
             &mut meta.location,
             Location {
@@ -166,8 +211,13 @@ impl Entities {
                 index: usize::max_value(),
             },
         );
-        let index = self.free_cursor.fetch_add(1, Ordering::Relaxed); // Not racey due to &mut self
-        self.free[index as usize] = entity.id;
+        self.stats.max_generation = self.stats.max_generation.max(entity.generation + 1);
+        if retired {
+            self.stats.retired += 1;
+        } else {
+            let index = self.free_cursor.fetch_add(1, Ordering::Relaxed); // Not racey due to &mut self
+            self.free[index as usize] = entity.id;
+        }
         debug_assert!(
             loc.index != usize::max_value(),
             "free called on reserved entity without flush"
@@ -192,16 +242,38 @@ impl Entities {
         if entity.id >= self.meta.len() as u32 {
             return true;
         }
-        self.meta[entity.id as usize].generation == entity.generation
+        self.meta[entity.id as usize].is(entity)
     }
 
+    /// Destroy all entities
+    ///
+    /// Every generation is incremented, so entities that existed before clearing are not confused
+    /// with the entities that reuse their IDs.
     pub fn clear(&mut self) {
+        let mut free_cursor = 0;
+        for (id, meta) in self.meta.iter_mut().enumerate() {
+            if meta.generation == RETIRED_GENERATION {
+                continue;
+            }
+            // IDs that have never been used can't have any entities referring to them
+            if (id as u32) < self.stats.ids {
+                meta.generation += 1;
+                self.stats.max_generation = self.stats.max_generation.max(meta.generation);
+            }
+            meta.location = Location {
+                archetype: 0,
+                index: usize::max_value(),
+            };
+            if meta.generation == RETIRED_GENERATION {
+                self.stats.retired += 1;
+            } else {
+                self.free[free_cursor] = id as u32;
+                free_cursor += 1;
+            }
+        }
         // Not racey due to &mut self
         self.free_cursor
-            .store(self.meta.len() as u32, Ordering::Relaxed);
-        for (i, x) in self.free.iter_mut().enumerate() {
-            *x = i as u32;
-        }
+            .store(free_cursor as u32, Ordering::Relaxed);
         self.pending.store(0, Ordering::Relaxed);
         self.reserved_cursor.store(0, Ordering::Relaxed);
     }
@@ -211,7 +283,7 @@ impl Entities {
     /// Must not be called on pending entities.
     pub fn get_mut(&mut self, entity: Entity) -> Result<&mut Location, NoSuchEntity> {
         let meta = &mut self.meta[entity.id as usize];
-        if meta.generation == entity.generation {
+        if meta.is(entity) {
             Ok(&mut meta.location)
         } else {
             Err(NoSuchEntity)
@@ -227,7 +299,7 @@ impl Entities {
             });
         }
         let meta = &self.meta[entity.id as usize];
-        if meta.generation != entity.generation {
+        if !meta.is(entity) {
             return Err(NoSuchEntity);
         }
         if meta.location.archetype == 0 {
@@ -246,6 +318,8 @@ impl Entities {
         if pending != 0 {
             let first = self.meta.len() as u32;
             self.grow(0);
+            self.stats.allocated += pending as u64;
+            self.stats.ids = first + pending;
             first..(first + pending)
         } else {
             0..0
@@ -265,6 +339,10 @@ impl Entities {
     }
 
     pub fn clear_reserved(&mut self) {
+        for i in 0..self.reserved_len() {
+            let id = self.reserved(i);
+            self.record_allocation(id);
+        }
         self.reserved_cursor.store(0, Ordering::Relaxed);
     }
 
@@ -337,6 +415,13 @@ pub(crate) struct EntityMeta {
     pub location: Location,
 }
 
+impl EntityMeta {
+    /// Whether this is the metadata of `entity`, rather than of another entity with the same ID
+    fn is(&self, entity: Entity) -> bool {
+        self.generation == entity.generation && entity.generation != RETIRED_GENERATION
+    }
+}
+
 /// A location of an entity in an archetype
 #[derive(Copy, Clone, Debug)]
 pub struct Location {
@@ -372,4 +457,100 @@ mod tests {
         };
         assert_eq!(Entity::from_bits(e.to_bits()), e);
     }
+
+    /// Allocates an entity and gives it a location, like `World::spawn` does
+    fn alloc(entities: &mut Entities) -> Entity {
+        let entity = entities.alloc();
+        entities.meta[entity.id as usize].location.index = 0;
+        entity
+    }
+
+    #[test]
+    fn recycle_ids() {
+        let mut entities = Entities::default();
+        let mut live = Vec::new();
+        for cycle in 0..1_000_000u32 {
+            live.push(alloc(&mut entities));
+            if cycle % 8 == 7 {
+                for entity in live.drain(..) {
+                    entities.free(entity).unwrap();
+                    assert!(!entities.contains(entity));
+                }
+            }
+        }
+
+        let stats = entities.stats();
+        assert_eq!(stats.allocated, 1_000_000);
+        assert_eq!(stats.ids, 8);
+        assert_eq!(stats.recycled, 1_000_000 - 8);
+        assert_eq!(stats.retired, 0);
+        assert_eq!(stats.max_generation, 1_000_000 / 8);
+    }
+
+    #[test]
+    fn clear_increments_generations() {
+        let mut entities = Entities::default();
+        let a = alloc(&mut entities);
+        let b = alloc(&mut entities);
+        entities.free(b).unwrap();
+        entities.clear();
+        assert!(!entities.contains(a));
+        assert!(!entities.contains(b));
+
+        let reused = (0..2).map(|_| alloc(&mut entities)).collect::<Vec<_>>();
+        for entity in reused {
+            assert!(entity != a && entity != b);
+            assert!(entities.contains(entity));
+        }
+    }
+
+    #[test]
+    fn retire_ids_before_generation_overflow() {
+        let mut entities = Entities::default();
+        let entity = alloc(&mut entities);
+        entities.meta[entity.id as usize].generation = RETIRED_GENERATION - 3;
+        let mut stale = Vec::new();
+        for _ in 0..2 {
+            let entity = Entity {
+                generation: entities.meta[entity.id as usize].generation,
+                id: entity.id,
+            };
+            entities.free(entity).unwrap();
+            stale.push(entity);
+            let reused = alloc(&mut entities);
+            assert_eq!(reused.id, entity.id);
+        }
+
+        // the last generation is freed without being reused
+        let last = Entity {
+            generation: RETIRED_GENERATION - 1,
+            id: entity.id,
+        };
+        entities.free(last).unwrap();
+        stale.push(last);
+        for _ in 0..1_000_000 {
+            let entity = alloc(&mut entities);
+            assert_ne!(entity.id, last.id);
+            entities.free(entity).unwrap();
+        }
+
+        for entity in stale {
+            assert!(!entities.contains(entity));
+            assert!(entities.get(entity).is_err());
+        }
+        let retired = Entity {
+            generation: RETIRED_GENERATION,
+            id: last.id,
+        };
+        assert!(!entities.contains(retired));
+        assert!(entities.free(retired).is_err());
+        assert_eq!(entities.stats().retired, 1);
+        assert_eq!(entities.stats().max_generation, RETIRED_GENERATION);
+
+        // clearing doesn't make retired IDs available again
+        entities.clear();
+        for _ in 0..2048 {
+            assert_ne!(alloc(&mut entities).id, last.id);
+        }
+    }
 }
@@ -63,6 +63,8 @@ impl<T: Component> QueryFilter for Added<T> {
     fn get_entity_filter(archetype: &Archetype) -> Option<Self::EntityFilter> {
         archetype
             .get_type_state(TypeId::of::<T>())
+            // skip the whole archetype if none of its components match
+            .filter(|state| state.archetype_flags().contains(ComponentFlags::ADDED))
             .map(|state| Added(state.component_flags(), Default::default()))
     }
 }
@@ -87,6 +89,8 @@ impl<T: Component> QueryFilter for Mutated<T> {
     fn get_entity_filter(archetype: &Archetype) -> Option<Self::EntityFilter> {
         archetype
             .get_type_state(TypeId::of::<T>())
+            // skip the whole archetype if none of its components match
+            .filter(|state| state.archetype_flags().contains(ComponentFlags::MUTATED))
             .map(|state| Mutated(state.component_flags(), Default::default()))
     }
 }
@@ -110,6 +114,12 @@ impl<T: Component> QueryFilter for Changed<T> {
     fn get_entity_filter(archetype: &Archetype) -> Option<Self::EntityFilter> {
         archetype
             .get_type_state(TypeId::of::<T>())
+            // skip the whole archetype if none of its components match
+            .filter(|state| {
+                state
+                    .archetype_flags()
+                    .intersects(ComponentFlags::ADDED | ComponentFlags::MUTATED)
+            })
             .map(|state| Changed(state.component_flags(), Default::default()))
     }
 }
@@ -40,18 +40,20 @@ mod entity_map;
 mod filter;
 mod query;
 mod serde;
+mod sparse_storage;
 mod world;
 mod world_builder;
 
 pub use access::{ArchetypeComponent, QueryAccess, TypeAccess};
-pub use archetype::{Archetype, ComponentFlags, TypeState};
+pub use archetype::{Archetype, ArchetypeFlags, ComponentFlags, TypeState};
 pub use borrow::{AtomicBorrow, Ref, RefMut};
 pub use bundle::{Bundle, DynamicBundle, MissingComponent};
-pub use entities::{Entity, EntityReserver, Location, NoSuchEntity};
+pub use entities::{Entity, EntityReserver, EntityStats, Location, NoSuchEntity};
 pub use entity_builder::{BuiltEntity, EntityBuilder};
 pub use entity_map::*;
 pub use filter::{Added, Changed, EntityFilter, Mutated, Or, QueryFilter, With, Without};
 pub use query::{Batch, BatchedIter, Mut, QueryIter, ReadOnlyFetch, WorldQuery};
+pub use sparse_storage::SparseStorage;
 pub use world::{ArchetypesGeneration, Component, ComponentError, SpawnBatchIter, World};
 pub use world_builder::*;
 
@@ -15,7 +15,7 @@
 // modified by Bevy contributors
 
 use super::{Archetype, Component, Entity, MissingComponent, QueryAccess, QueryFilter};
-use crate::{ComponentFlags, EntityFilter};
+use crate::{ArchetypeFlags, ComponentFlags, EntityFilter};
 use std::{
     marker::PhantomData,
     ops::{Deref, DerefMut},
@@ -134,6 +134,7 @@ impl<T: WorldQuery> WorldQuery for Option<T> {
 pub struct Mut<'a, T: Component> {
     pub(crate) value: &'a mut T,
     pub(crate) flags: &'a mut ComponentFlags,
+    pub(crate) archetype_flags: &'a ArchetypeFlags,
 }
 
 impl<'a, T: Component> Mut<'a, T> {
@@ -148,6 +149,7 @@ impl<'a, T: Component> Mut<'a, T> {
         Ok(Self {
             value: &mut *target.as_ptr().add(index),
             flags: &mut *type_state.component_flags().as_ptr().add(index),
+            archetype_flags: type_state.archetype_flags(),
         })
     }
 }
@@ -168,6 +170,7 @@ impl<'a, T: Component> DerefMut for Mut<'a, T> {
     #[inline]
     fn deref_mut(&mut self) -> &mut T {
         self.flags.insert(ComponentFlags::MUTATED);
+        self.archetype_flags.insert(ComponentFlags::MUTATED);
         self.value
     }
 }
@@ -182,12 +185,16 @@ impl<'a, T: Component> WorldQuery for Mut<'a, T> {
     type Fetch = FetchMut<T>;
 }
 #[doc(hidden)]
-pub struct FetchMut<T>(NonNull<T>, NonNull<ComponentFlags>);
+pub struct FetchMut<T>(NonNull<T>, NonNull<ComponentFlags>, NonNull<ArchetypeFlags>);
 
 impl<'a, T: Component> Fetch<'a> for FetchMut<T> {
     type Item = Mut<'a, T>;
 
-    const DANGLING: Self = Self(NonNull::dangling(), NonNull::dangling());
+    const DANGLING: Self = Self(
+        NonNull::dangling(),
+        NonNull::dangling(),
+        NonNull::dangling(),
+    );
 
     unsafe fn get(archetype: &'a Archetype, offset: usize) -> Option<Self> {
         archetype
@@ -196,6 +203,7 @@ impl<'a, T: Component> Fetch<'a> for FetchMut<T> {
                 Self(
                     NonNull::new_unchecked(components.as_ptr().add(offset)),
                     NonNull::new_unchecked(type_state.component_flags().as_ptr().add(offset)),
+                    NonNull::from(type_state.archetype_flags()),
                 )
             })
     }
@@ -205,6 +213,7 @@ impl<'a, T: Component> Fetch<'a> for FetchMut<T> {
         Mut {
             value: &mut *self.0.as_ptr().add(n),
             flags: &mut *self.1.as_ptr().add(n),
+            archetype_flags: &*self.2.as_ptr(),
         }
     }
 
@@ -466,8 +475,10 @@ smaller_tuples_too!(tuple_impl, O, N, M, L, K, J, I, H, G, F, E, D, C, B, A);
 
 #[cfg(test)]
 mod tests {
-    use crate::core::{Added, Changed, Component, Entity, Mutated, Or, QueryFilter, World};
-    use std::{vec, vec::Vec};
+    use crate::core::{
+        Added, Changed, Component, ComponentFlags, Entity, Mutated, Or, QueryFilter, World,
+    };
+    use std::{any::TypeId, vec, vec::Vec};
 
     use super::Mut;
 
@@ -599,6 +610,42 @@ mod tests {
         assert_eq!(a_b_mutated, vec![e2]);
     }
 
+    #[test]
+    fn archetype_flags() {
+        let mut world = World::default();
+        let e1 = world.spawn((A(0),));
+        let e2 = world.spawn((A(0), B(0)));
+
+        fn archetype_flags<T: Component>(world: &World, entity: Entity) -> ComponentFlags {
+            let location = world.get_entity_location(entity).unwrap();
+            let archetype = world.archetypes().nth(location.archetype as usize).unwrap();
+            archetype
+                .get_type_state(TypeId::of::<T>())
+                .unwrap()
+                .archetype_flags()
+                .get()
+        }
+
+        assert_eq!(archetype_flags::<A>(&world, e1), ComponentFlags::ADDED);
+        world.clear_trackers();
+        assert_eq!(archetype_flags::<A>(&world, e1), ComponentFlags::empty());
+
+        world.get_mut::<A>(e2).unwrap().0 += 1;
+        assert_eq!(archetype_flags::<A>(&world, e1), ComponentFlags::empty());
+        assert_eq!(archetype_flags::<A>(&world, e2), ComponentFlags::MUTATED);
+        assert_eq!(archetype_flags::<B>(&world, e2), ComponentFlags::empty());
+
+        // moving to another archetype carries the flags along
+        world.insert_one(e2, C).unwrap();
+        assert_eq!(archetype_flags::<A>(&world, e2), ComponentFlags::MUTATED);
+        assert_eq!(archetype_flags::<C>(&world, e2), ComponentFlags::ADDED);
+
+        let changed = world
+            .query_filtered::<Entity, Changed<A>>()
+            .collect::<Vec<Entity>>();
+        assert_eq!(changed, vec![e2]);
+    }
+
     #[test]
     fn or_mutated_query() {
         let mut world = World::default();
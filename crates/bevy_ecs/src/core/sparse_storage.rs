@@ -0,0 +1,206 @@
+use crate::{Component, Entity, Resources, World};
+
+/// Stores components of type `T` outside of archetypes, indexed by entity id.
+///
+/// Adding a component to an entity or removing one normally moves the entity and all of its other
+/// components to a different archetype. Marker components that are added and removed every frame
+/// are cheaper to keep in a [SparseStorage] resource instead, where inserting and removing only
+/// touches the marker itself. The tradeoff is that sparse components can't be used in queries:
+/// systems access them through `Res<SparseStorage<T>>` and look up entities from there.
+///
+/// Components are not removed automatically when their entity is despawned. Call
+/// [SparseStorage::remove_despawned], or add [SparseStorage::remove_despawned_system] to the
+/// schedule.
+#[derive(Debug)]
+pub struct SparseStorage<T> {
+    // one more than the index in `dense` of each entity id's component, or 0 if it has none
+    sparse: Vec<u32>,
+    entities: Vec<Entity>,
+    dense: Vec<T>,
+}
+
+impl<T> Default for SparseStorage<T> {
+    fn default() -> Self {
+        SparseStorage {
+            sparse: Vec::new(),
+            entities: Vec::new(),
+            dense: Vec::new(),
+        }
+    }
+}
+
+impl<T> SparseStorage<T> {
+    fn index(&self, entity: Entity) -> Option<usize> {
+        let index = *self.sparse.get(entity.id as usize)? as usize;
+        if index == 0 || self.entities[index - 1] != entity {
+            return None;
+        }
+
+        Some(index - 1)
+    }
+
+    /// Adds a component to `entity`, returning the previous one if there was one
+    pub fn insert(&mut self, entity: Entity, value: T) -> Option<T> {
+        if let Some(index) = self.index(entity) {
+            return Some(std::mem::replace(&mut self.dense[index], value));
+        }
+
+        let id = entity.id as usize;
+        if id >= self.sparse.len() {
+            self.sparse.resize(id + 1, 0);
+        }
+
+        // the id may still point to the component of a despawned entity with an older generation
+        let previous = self.sparse[id];
+        if previous != 0 {
+            self.swap_remove(previous as usize - 1);
+        }
+
+        self.entities.push(entity);
+        self.dense.push(value);
+        self.sparse[id] = self.dense.len() as u32;
+        None
+    }
+
+    /// Removes the component of `entity`
+    pub fn remove(&mut self, entity: Entity) -> Option<T> {
+        let index = self.index(entity)?;
+        Some(self.swap_remove(index))
+    }
+
+    fn swap_remove(&mut self, index: usize) -> T {
+        let entity = self.entities.swap_remove(index);
+        self.sparse[entity.id as usize] = 0;
+        if let Some(moved) = self.entities.get(index) {
+            self.sparse[moved.id as usize] = index as u32 + 1;
+        }
+
+        self.dense.swap_remove(index)
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<&T> {
+        let index = self.index(entity)?;
+        Some(&self.dense[index])
+    }
+
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        let index = self.index(entity)?;
+        Some(&mut self.dense[index])
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.index(entity).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.dense.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dense.is_empty()
+    }
+
+    /// The entities that have a component, in no particular order
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.entities.iter().copied().zip(self.dense.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.entities.iter().copied().zip(self.dense.iter_mut())
+    }
+
+    /// Removes every component for which `f` returns `false`
+    pub fn retain(&mut self, mut f: impl FnMut(Entity, &mut T) -> bool) {
+        let mut index = 0;
+        while index < self.dense.len() {
+            if f(self.entities[index], &mut self.dense[index]) {
+                index += 1;
+            } else {
+                self.swap_remove(index);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.sparse.clear();
+        self.entities.clear();
+        self.dense.clear();
+    }
+
+    /// Removes the components of entities that no longer exist in `world`
+    pub fn remove_despawned(&mut self, world: &World) {
+        self.retain(|entity, _| world.contains(entity));
+    }
+}
+
+impl<T: Component> SparseStorage<T> {
+    /// A system that calls [SparseStorage::remove_despawned] on the `SparseStorage<T>` resource
+    pub fn remove_despawned_system(world: &mut World, resources: &mut Resources) {
+        if let Some(mut storage) = resources.get_mut::<Self>() {
+            storage.remove_despawned(world);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SparseStorage;
+    use crate::{Entity, World};
+
+    #[test]
+    fn insert_remove() {
+        let mut storage = SparseStorage::default();
+        let a = Entity::new(3);
+        let b = Entity::new(0);
+        let c = Entity::new(7);
+        assert_eq!(storage.insert(a, 'a'), None);
+        assert_eq!(storage.insert(b, 'b'), None);
+        assert_eq!(storage.insert(c, 'c'), None);
+        assert_eq!(storage.insert(b, 'B'), Some('b'));
+        assert_eq!(storage.len(), 3);
+
+        assert_eq!(storage.remove(a), Some('a'));
+        assert_eq!(storage.remove(a), None);
+        assert!(!storage.contains(a));
+        assert_eq!(storage.get(b), Some(&'B'));
+        assert_eq!(storage.get(c), Some(&'c'));
+
+        *storage.get_mut(c).unwrap() = 'C';
+        let mut values = storage.iter().collect::<Vec<_>>();
+        values.sort();
+        assert_eq!(values, vec![(b, &'B'), (c, &'C')]);
+
+        storage.retain(|entity, _| entity != b);
+        assert_eq!(storage.entities(), &[c]);
+    }
+
+    #[test]
+    fn remove_despawned() {
+        let mut world = World::default();
+        let mut storage = SparseStorage::default();
+        let a = world.spawn(());
+        let b = world.spawn(());
+        storage.insert(a, 1);
+        storage.insert(b, 2);
+
+        world.despawn(a).unwrap();
+        let reused = world.spawn(());
+        assert_eq!(reused.id(), a.id());
+        // the component of the despawned entity doesn't belong to the new one
+        assert!(!storage.contains(reused));
+        assert!(storage.contains(a));
+
+        storage.remove_despawned(&world);
+        assert!(!storage.contains(a));
+        assert_eq!(storage.get(b), Some(&2));
+
+        storage.insert(a, 3);
+        storage.insert(reused, 4);
+        assert_eq!(storage.len(), 2);
+        assert_eq!(storage.get(reused), Some(&4));
+    }
+}
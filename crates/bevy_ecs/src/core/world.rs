@@ -16,8 +16,8 @@
 
 use crate::{
     core::entities::Entities, Archetype, BatchedIter, Bundle, ComponentFlags, DynamicBundle,
-    Entity, EntityFilter, EntityReserver, Fetch, Location, MissingComponent, Mut, NoSuchEntity,
-    QueryFilter, QueryIter, ReadOnlyFetch, Ref, RefMut, WorldQuery,
+    Entity, EntityFilter, EntityReserver, EntityStats, Fetch, Location, MissingComponent, Mut,
+    NoSuchEntity, QueryFilter, QueryIter, ReadOnlyFetch, Ref, RefMut, WorldQuery,
 };
 use bevy_utils::{HashMap, HashSet};
 use std::{any::TypeId, fmt, mem, ptr};
@@ -206,6 +206,51 @@ impl World {
         self.entities.clear();
     }
 
+    /// Despawn all entities that match the filter `F`
+    ///
+    /// Resources are stored separately from the world, so they are not affected. This can be used
+    /// to reset the game while keeping entities that are marked as persistent.
+    ///
+    /// # Example
+    /// ```
+    /// # use bevy_ecs::*;
+    /// struct Persistent;
+    ///
+    /// let mut world = World::new();
+    /// let player = world.spawn((123,));
+    /// let settings = world.spawn((456, Persistent));
+    /// world.clear_filtered::<Without<Persistent>>();
+    /// assert!(!world.contains(player));
+    /// assert!(world.contains(settings));
+    /// ```
+    pub fn clear_filtered<F: QueryFilter>(&mut self) {
+        let entities = self.query_filtered::<Entity, F>().collect::<Vec<_>>();
+        for entity in entities {
+            self.despawn(entity).unwrap();
+        }
+    }
+
+    /// Statistics about how entity IDs have been allocated and reused
+    ///
+    /// # Example
+    /// ```
+    /// # use bevy_ecs::*;
+    /// let mut world = World::new();
+    /// let a = world.spawn((123,));
+    /// world.despawn(a).unwrap();
+    /// let b = world.spawn((456,));
+    /// assert_eq!(a.id(), b.id());
+    /// assert_eq!(b.generation(), a.generation() + 1);
+    ///
+    /// let stats = world.entity_stats();
+    /// assert_eq!(stats.allocated, 2);
+    /// assert_eq!(stats.recycled, 1);
+    /// assert_eq!(stats.ids, 1);
+    /// ```
+    pub fn entity_stats(&self) -> EntityStats {
+        self.entities.stats()
+    }
+
     /// Whether `entity` still exists
     pub fn contains(&self, entity: Entity) -> bool {
         self.entities.contains(entity)
@@ -620,7 +665,7 @@ impl World {
             if let Some(moved) = source_arch.move_to(old_index, |ptr, ty, size, flags| {
                 target_arch.put_dynamic(ptr, ty, size, target_index, ComponentFlags::empty());
                 let type_state = target_arch.get_type_state_mut(ty).unwrap();
-                *type_state.component_flags().as_ptr().add(target_index) = flags;
+                type_state.set_component_flags(target_index, flags);
             }) {
                 self.entities.get_mut(moved).unwrap().index = old_index;
             }
@@ -726,7 +771,7 @@ impl World {
                 if let Some(dst) = target_arch.get_dynamic(ty, size, target_index) {
                     ptr::copy_nonoverlapping(src, dst.as_ptr(), size);
                     let state = target_arch.get_type_state_mut(ty).unwrap();
-                    *state.component_flags().as_ptr().add(target_index) = flags;
+                    state.set_component_flags(target_index, flags);
                 } else {
                     let removed_entities = removed_components.entry(ty).or_insert_with(Vec::new);
                     removed_entities.push(entity);
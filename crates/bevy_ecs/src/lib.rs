@@ -15,8 +15,8 @@ pub mod prelude {
         core::WorldBuilderSource,
         resource::{ChangedRes, FromResources, Local, Res, ResMut, Resource, Resources},
         schedule::{Schedule, State, StateStage, SystemStage},
-        system::{Commands, IntoSystem, Query, System},
-        Added, Bundle, Changed, Component, Entity, In, IntoChainSystem, Mut, Mutated, Or, QuerySet,
-        Ref, RefMut, With, Without, World,
+        system::{despawn_all_with, Commands, IntoSystem, Query, System},
+        Added, Bundle, Changed, Component, Entity, In, IntoChainSystem, IntoResourceChangedSystem,
+        Mut, Mutated, Or, QuerySet, Ref, RefMut, ResourceChanged, With, Without, World,
     };
 }
@@ -142,6 +142,13 @@ impl Resources {
         self.get_resource::<T>(ResourceIndex::Global).is_some()
     }
 
+    /// Returns true if a global resource with the given [TypeId] has been inserted
+    pub fn contains_type_id(&self, type_id: TypeId) -> bool {
+        self.resource_data
+            .get(&type_id)
+            .map_or(false, |data| data.default_index.is_some())
+    }
+
     pub fn get<T: Resource>(&self) -> Option<ResourceRef<'_, T>> {
         self.get_resource(ResourceIndex::Global)
     }
@@ -271,6 +278,16 @@ impl Resources {
     #[inline]
     #[allow(clippy::missing_safety_doc)]
     pub unsafe fn get_unsafe_ref<T: Resource>(&self, resource_index: ResourceIndex) -> NonNull<T> {
+        self.try_get_unsafe_ref(resource_index)
+            .unwrap_or_else(|| panic!("Resource does not exist {}.", std::any::type_name::<T>()))
+    }
+
+    #[inline]
+    #[allow(clippy::missing_safety_doc)]
+    pub unsafe fn try_get_unsafe_ref<T: Resource>(
+        &self,
+        resource_index: ResourceIndex,
+    ) -> Option<NonNull<T>> {
         self.get_resource_data_index::<T>(resource_index)
             .map(|(data, index)| {
                 let resources = data
@@ -279,7 +296,6 @@ impl Resources {
                     .unwrap();
                 resources.get_unsafe_ref(index)
             })
-            .unwrap_or_else(|| panic!("Resource does not exist {}.", std::any::type_name::<T>()))
     }
 
     #[inline]
@@ -288,6 +304,16 @@ impl Resources {
         &self,
         resource_index: ResourceIndex,
     ) -> (NonNull<T>, NonNull<bool>, NonNull<bool>) {
+        self.try_get_unsafe_ref_with_added_and_mutated(resource_index)
+            .unwrap_or_else(|| panic!("Resource does not exist {}.", std::any::type_name::<T>()))
+    }
+
+    #[inline]
+    #[allow(clippy::missing_safety_doc)]
+    pub unsafe fn try_get_unsafe_ref_with_added_and_mutated<T: Resource>(
+        &self,
+        resource_index: ResourceIndex,
+    ) -> Option<(NonNull<T>, NonNull<bool>, NonNull<bool>)> {
         self.get_resource_data_index::<T>(resource_index)
             .map(|(data, index)| {
                 let resources = data
@@ -301,7 +327,6 @@ impl Resources {
                     NonNull::new_unchecked(resources.stored[index].mutated.get()),
                 )
             })
-            .unwrap_or_else(|| panic!("Resource does not exist {}.", std::any::type_name::<T>()))
     }
 
     #[inline]
@@ -0,0 +1,114 @@
+use super::{Schedule, SerialSystemStageExecutor, SystemStage};
+use std::fmt::Write;
+
+impl Schedule {
+    /// Returns the stages and systems of this schedule in the Graphviz DOT format. Each stage is
+    /// drawn as a cluster of its systems, and nested schedules are drawn inside their parent stage.
+    /// Edges connect stages in the order they run. Systems in serial stages are connected in the
+    /// order they run, while systems in parallel stages are listed in the order they were added.
+    pub fn dot(&self) -> String {
+        let mut dot = String::new();
+        writeln!(dot, "digraph schedule {{").unwrap();
+        writeln!(dot, "\tcompound=true;").unwrap();
+        writeln!(dot, "\tnode [shape=box];").unwrap();
+        write_schedule(&mut dot, self, "", 1);
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+}
+
+fn write_schedule(dot: &mut String, schedule: &Schedule, prefix: &str, depth: usize) {
+    let indent = "\t".repeat(depth);
+    let mut previous_stage: Option<String> = None;
+    for (name, stage) in schedule.iter_stages() {
+        let id = format!("{}{}", prefix, name);
+        writeln!(dot, "{}subgraph \"cluster_{}\" {{", indent, escape(&id)).unwrap();
+        writeln!(dot, "{}\tlabel=\"{}\";", indent, escape(name)).unwrap();
+        // edges between stages connect these invisible nodes, clipped to the stage clusters
+        writeln!(
+            dot,
+            "{}\t\"{}\" [shape=point, style=invis];",
+            indent,
+            escape(&id)
+        )
+        .unwrap();
+
+        if let Some(system_stage) = stage.downcast_ref::<SystemStage>() {
+            let serial = system_stage
+                .get_executor::<SerialSystemStageExecutor>()
+                .is_some();
+            for (index, system) in system_stage.systems().iter().enumerate() {
+                writeln!(
+                    dot,
+                    "{}\t\"{}/{}\" [label=\"{}\"];",
+                    indent,
+                    escape(&id),
+                    index,
+                    escape(&system.name())
+                )
+                .unwrap();
+                if serial && index > 0 {
+                    writeln!(
+                        dot,
+                        "{}\t\"{}/{}\" -> \"{}/{}\";",
+                        indent,
+                        escape(&id),
+                        index - 1,
+                        escape(&id),
+                        index
+                    )
+                    .unwrap();
+                }
+            }
+        } else if let Some(nested_schedule) = stage.downcast_ref::<Schedule>() {
+            write_schedule(dot, nested_schedule, &format!("{}/", id), depth + 1);
+        }
+        writeln!(dot, "{}}}", indent).unwrap();
+
+        if let Some(previous_stage) = previous_stage {
+            writeln!(
+                dot,
+                "{}\"{}\" -> \"{}\" [ltail=\"cluster_{}\", lhead=\"cluster_{}\"];",
+                indent,
+                escape(&previous_stage),
+                escape(&id),
+                escape(&previous_stage),
+                escape(&id)
+            )
+            .unwrap();
+        }
+        previous_stage = Some(id);
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{IntoSystem, Schedule, SystemStage};
+
+    #[test]
+    fn schedule_dot() {
+        fn a() {}
+        fn b() {}
+
+        let mut schedule = Schedule::default();
+        schedule
+            .add_stage(
+                "startup",
+                Schedule::default().with_stage("inner", SystemStage::serial()),
+            )
+            .add_stage("update", SystemStage::serial())
+            .add_system_to_stage("update", a.system())
+            .add_system_to_stage("update", b.system());
+
+        let dot = schedule.dot();
+        assert!(dot.contains("subgraph \"cluster_startup/inner\""));
+        assert!(dot.contains("\"update/0\" -> \"update/1\""));
+        assert!(dot.contains(
+            "\"startup\" -> \"update\" [ltail=\"cluster_startup\", lhead=\"cluster_update\"]"
+        ));
+    }
+}
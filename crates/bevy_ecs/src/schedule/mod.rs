@@ -1,3 +1,4 @@
+mod graphviz;
 mod stage;
 mod stage_executor;
 mod state;
@@ -139,6 +140,13 @@ impl Schedule {
             .and_then(|stage| stage.downcast_mut::<T>())
     }
 
+    /// Iterates over the stages of this schedule in the order they run
+    pub fn iter_stages(&self) -> impl Iterator<Item = (&str, &dyn Stage)> {
+        self.stage_order
+            .iter()
+            .map(move |name| (name.as_str(), &*self.stages[name]))
+    }
+
     pub fn run_once(&mut self, world: &mut World, resources: &mut Resources) {
         for name in self.stage_order.iter() {
             #[cfg(feature = "trace")]
@@ -206,11 +214,11 @@ pub fn clear_trackers_system(world: &mut World, resources: &mut Resources) {
 mod tests {
     use crate::{
         resource::{Res, ResMut, Resources},
-        schedule::{ParallelSystemStageExecutor, Schedule, SystemStage},
+        schedule::{ParallelSystemStageExecutor, Schedule, Stage, SystemStage},
         system::Query,
         Commands, Entity, IntoSystem, World,
     };
-    use bevy_tasks::{ComputeTaskPool, TaskPool};
+    use bevy_tasks::{ComputeTaskPool, TaskPool, TaskPoolBuilder};
     use fixedbitset::FixedBitSet;
     use parking_lot::Mutex;
     use std::{collections::HashSet, sync::Arc};
@@ -518,4 +526,72 @@ mod tests {
             run_and_validate(&mut schedule, &mut world, &mut resources);
         }
     }
+
+    #[test]
+    fn execution_order_ambiguities() {
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        resources.insert(ComputeTaskPool(TaskPool::default()));
+        resources.insert(0usize);
+        world.spawn((0u32, 0u64));
+
+        fn write_u32(_query: Query<&mut u32>) {}
+        fn read_u32(_query: Query<&u32>, _res: Res<usize>) {}
+        fn read_u64(_query: Query<&u64>, _res: Res<usize>) {}
+        fn write_usize(_res: ResMut<usize>) {}
+        fn thread_local_system(_world: &mut World, _resources: &mut Resources) {}
+
+        let mut stage = SystemStage::parallel();
+        stage
+            .add_system(write_u32.system())
+            .add_system(read_u32.system())
+            .add_system(read_u64.system())
+            .add_system(thread_local_system.system())
+            .add_system(write_usize.system());
+        stage.initialize(&mut world, &mut resources);
+        stage.run(&mut world, &mut resources);
+
+        let ambiguities = stage
+            .execution_order_ambiguities(&world)
+            .into_iter()
+            .map(|ambiguity| ambiguity.conflicts)
+            .collect::<Vec<_>>();
+        // write_usize runs after the thread local system, so it is not ambiguous with the earlier
+        // systems
+        assert_eq!(ambiguities, vec![vec!["u32"]]);
+    }
+
+    #[test]
+    fn single_threaded_order() {
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        resources.insert(ComputeTaskPool(
+            TaskPoolBuilder::new().num_threads(0).build(),
+        ));
+        resources.insert(Mutex::new(Vec::<usize>::new()));
+
+        // these systems don't conflict, so they only run in order when the pool has no threads
+        fn first(order: Res<Mutex<Vec<usize>>>) {
+            order.lock().push(0);
+        }
+        fn second(order: Res<Mutex<Vec<usize>>>) {
+            order.lock().push(1);
+        }
+        fn third(order: Res<Mutex<Vec<usize>>>) {
+            order.lock().push(2);
+        }
+
+        let mut stage = SystemStage::parallel();
+        stage
+            .add_system(first.system())
+            .add_system(second.system())
+            .add_system(third.system());
+        for _ in 0..10 {
+            stage.initialize(&mut world, &mut resources);
+            stage.run(&mut world, &mut resources);
+        }
+
+        let order = resources.get::<Mutex<Vec<usize>>>().unwrap();
+        assert_eq!(*order.lock(), [0, 1, 2].repeat(10));
+    }
 }
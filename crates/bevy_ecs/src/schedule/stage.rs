@@ -1,9 +1,13 @@
-use std::{any::TypeId, borrow::Cow};
+use std::{any::TypeId, borrow::Cow, marker::PhantomData};
 
 use crate::{
-    ArchetypeComponent, Resources, System, SystemId, ThreadLocalExecution, TypeAccess, World,
+    resource_changed, ArchetypeComponent, Resource, Resources, System, SystemId,
+    ThreadLocalExecution, TypeAccess, World,
+};
+use bevy_utils::{
+    tracing::{debug, error, info},
+    HashMap, HashSet,
 };
-use bevy_utils::HashSet;
 use downcast_rs::{impl_downcast, Downcast};
 
 use super::{ParallelSystemStageExecutor, SerialSystemStageExecutor, SystemStageExecutor};
@@ -23,6 +27,28 @@ pub trait Stage: Downcast + Send + Sync {
 
 impl_downcast!(Stage);
 
+/// Insert this resource to log the [ExecutionOrderAmbiguity]s of each [SystemStage] when its
+/// systems first run.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ReportExecutionOrderAmbiguities;
+
+/// Two systems in the same stage that access the same data, where at least one of them has mutable
+/// access. `first` runs before `second` only because it was added first.
+#[derive(Debug, Clone)]
+pub struct ExecutionOrderAmbiguity {
+    pub first: Cow<'static, str>,
+    pub second: Cow<'static, str>,
+    /// The type names of the conflicting components and resources
+    pub conflicts: Vec<&'static str>,
+}
+
+/// The systems of a stage that access a resource
+struct ResourceAccesses {
+    type_name: &'static str,
+    writers: Vec<Cow<'static, str>>,
+    readers: Vec<Cow<'static, str>>,
+}
+
 pub struct SystemStage {
     systems: Vec<Box<dyn System<In = (), Out = ()>>>,
     system_ids: HashSet<SystemId>,
@@ -89,6 +115,11 @@ impl SystemStage {
         self
     }
 
+    /// The systems in this stage, in the order they were added
+    pub fn systems(&self) -> &[Box<dyn System<In = (), Out = ()>>] {
+        &self.systems
+    }
+
     pub fn get_executor<T: SystemStageExecutor>(&self) -> Option<&T> {
         self.executor.downcast_ref()
     }
@@ -97,10 +128,145 @@ impl SystemStage {
         self.executor.downcast_mut()
     }
 
+    /// Reports problems with systems that have not run yet: systems that access resources which do
+    /// not exist are logged as errors before they panic. Resources that the new systems access
+    /// and that other systems in the stage write are logged at the debug level, as the systems
+    /// that access them cannot run in parallel.
+    pub fn validate_systems(&self, system_indices: &[usize], resources: &Resources) {
+        let mut new_resources = HashSet::default();
+        for &index in system_indices.iter() {
+            let system = &self.systems[index];
+            for (type_id, type_name) in system.resource_types() {
+                if !resources.contains_type_id(type_id) {
+                    error!(
+                        "System `{}` accesses the resource `{}`, which does not exist. Insert the \
+                        resource before the system runs, for example with \
+                        `AppBuilder::add_resource` or `AppBuilder::init_resource`.",
+                        system.name(),
+                        type_name
+                    );
+                }
+                new_resources.insert(type_id);
+            }
+        }
+
+        // the access of every system is only visited once, instead of comparing each pair
+        let mut accesses = HashMap::<TypeId, ResourceAccesses>::default();
+        for system in self.systems.iter() {
+            let resource_access = system.resource_access();
+            for (type_id, type_name) in system.resource_types() {
+                if !new_resources.contains(&type_id) {
+                    continue;
+                }
+                let access = accesses.entry(type_id).or_insert_with(|| ResourceAccesses {
+                    type_name,
+                    writers: Vec::new(),
+                    readers: Vec::new(),
+                });
+                let name = system.name();
+                if resource_access.is_write(&type_id) {
+                    if !access.writers.contains(&name) {
+                        access.writers.push(name);
+                    }
+                } else if !access.readers.contains(&name) {
+                    access.readers.push(name);
+                }
+            }
+        }
+        for access in accesses.values() {
+            if !access.writers.is_empty() && access.writers.len() + access.readers.len() > 1 {
+                debug!(
+                    "The resource `{}` is written by [{}] and read by [{}], so these systems \
+                    cannot run in parallel",
+                    access.type_name,
+                    access.writers.join(", "),
+                    access.readers.join(", ")
+                );
+            }
+        }
+    }
+
+    /// Returns the pairs of systems in this stage that have conflicting access to the same data,
+    /// and are only ordered by the order they were added in. Reordering these systems, for example
+    /// by adding plugins in a different order, can change the behavior of the app.
+    ///
+    /// Component access depends on the archetypes in the world, so this is only accurate after the
+    /// stage has run.
+    pub fn execution_order_ambiguities(&self, world: &World) -> Vec<ExecutionOrderAmbiguity> {
+        let mut component_names = HashMap::default();
+        for archetype in world.archetypes() {
+            for type_info in archetype.types() {
+                component_names.insert(type_info.id(), type_info.type_name());
+            }
+        }
+
+        let mut ambiguities = Vec::new();
+        // thread local systems run exclusively, so they split the stage into independently
+        // ordered batches
+        for batch in self
+            .systems
+            .split(|system| system.thread_local_execution() == ThreadLocalExecution::Immediate)
+        {
+            for (index, first) in batch.iter().enumerate() {
+                let resource_types = first.resource_types();
+                for second in batch[index + 1..].iter() {
+                    let mut conflicts = Vec::new();
+                    for archetype_component in first
+                        .archetype_component_access()
+                        .get_conflicts(second.archetype_component_access())
+                    {
+                        let name = component_names
+                            .get(&archetype_component.component)
+                            .copied()
+                            .unwrap_or("unknown");
+                        if !conflicts.contains(&name) {
+                            conflicts.push(name);
+                        }
+                    }
+                    for type_id in first
+                        .resource_access()
+                        .get_conflicts(second.resource_access())
+                    {
+                        let name = resource_types
+                            .iter()
+                            .find(|(id, _)| id == type_id)
+                            .map_or("unknown", |(_, name)| name);
+                        conflicts.push(name);
+                    }
+
+                    if !conflicts.is_empty() {
+                        conflicts.sort_unstable();
+                        ambiguities.push(ExecutionOrderAmbiguity {
+                            first: first.name(),
+                            second: second.name(),
+                            conflicts,
+                        });
+                    }
+                }
+            }
+        }
+        ambiguities
+    }
+
     pub fn run_once(&mut self, world: &mut World, resources: &mut Resources) {
         let unexecuted_systems = std::mem::take(&mut self.unexecuted_systems);
+        if !unexecuted_systems.is_empty() {
+            self.validate_systems(&unexecuted_systems, resources);
+        }
         self.executor
             .execute_stage(&mut self.systems, &unexecuted_systems, world, resources);
+
+        if !unexecuted_systems.is_empty() && resources.contains::<ReportExecutionOrderAmbiguities>()
+        {
+            for ambiguity in self.execution_order_ambiguities(world) {
+                info!(
+                    "Execution order ambiguity: `{}` and `{}` conflict on [{}]",
+                    ambiguity.first,
+                    ambiguity.second,
+                    ambiguity.conflicts.join(", ")
+                );
+            }
+        }
     }
 }
 
@@ -221,3 +387,71 @@ impl System for RunOnce {
 
     fn initialize(&mut self, _world: &mut World, _resources: &mut Resources) {}
 }
+
+/// A run criteria that only runs a stage in frames the resource `T` was inserted or mutated in,
+/// like recomputing what is visible when the camera moved, instead of every frame. Systems can be
+/// run on changes too, with
+/// [IntoResourceChangedSystem::on_resource_changed](crate::IntoResourceChangedSystem::on_resource_changed).
+pub struct ResourceChanged<T> {
+    system_id: SystemId,
+    resource_access: TypeAccess<TypeId>,
+    archetype_access: TypeAccess<ArchetypeComponent>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Resource> Default for ResourceChanged<T> {
+    fn default() -> Self {
+        let mut resource_access = TypeAccess::default();
+        resource_access.add_read(TypeId::of::<T>());
+        Self {
+            system_id: SystemId::new(),
+            resource_access,
+            archetype_access: Default::default(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Resource> System for ResourceChanged<T> {
+    type In = ();
+    type Out = ShouldRun;
+
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed(std::any::type_name::<ResourceChanged<T>>())
+    }
+
+    fn id(&self) -> SystemId {
+        self.system_id
+    }
+
+    fn update(&mut self, _world: &World) {}
+
+    fn archetype_component_access(&self) -> &TypeAccess<ArchetypeComponent> {
+        &self.archetype_access
+    }
+
+    fn resource_access(&self) -> &TypeAccess<TypeId> {
+        &self.resource_access
+    }
+
+    fn thread_local_execution(&self) -> ThreadLocalExecution {
+        ThreadLocalExecution::Immediate
+    }
+
+    unsafe fn run_unsafe(
+        &mut self,
+        _input: Self::In,
+        _world: &World,
+        resources: &Resources,
+    ) -> Option<Self::Out> {
+        Some(if resource_changed::<T>(resources) {
+            ShouldRun::Yes
+        } else {
+            ShouldRun::No
+        })
+    }
+
+    fn run_thread_local(&mut self, _world: &mut World, _resources: &mut Resources) {}
+
+    fn initialize(&mut self, _world: &mut World, _resources: &mut Resources) {}
+}
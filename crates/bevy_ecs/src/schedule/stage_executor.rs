@@ -295,6 +295,25 @@ impl ParallelSystemStageExecutor {
         prepared_system_range: Range<usize>,
         compute_pool: &TaskPool,
     ) {
+        // A compute pool without threads means the app runs in single threaded mode. Dependencies
+        // always point to earlier systems, so running the systems in order satisfies them.
+        if compute_pool.thread_num() == 0 {
+            trace!("running systems {:?} in order", prepared_system_range);
+            for system in &mut systems[prepared_system_range] {
+                #[cfg(feature = "trace")]
+                let system_span =
+                    bevy_utils::tracing::info_span!("system", name = system.name().as_ref());
+                #[cfg(feature = "trace")]
+                let _system_guard = system_span.enter();
+
+                // SAFETY: systems run one at a time, so world / resource access can't conflict
+                unsafe {
+                    system.run_unsafe((), world, resources);
+                }
+            }
+            return;
+        }
+
         // Generate tasks for systems in the given range and block until they are complete
         trace!("running systems {:?}", prepared_system_range);
         compute_pool.scope(|scope| {
@@ -1,10 +1,14 @@
 use super::SystemId;
 use crate::{
     resource::{Resource, Resources},
-    Bundle, Component, ComponentError, DynamicBundle, Entity, EntityReserver, World,
+    Bundle, Component, ComponentError, DynamicBundle, Entity, EntityReserver, QueryFilter,
+    SparseStorage, With, World,
 };
 use bevy_utils::tracing::{debug, warn};
-use std::marker::PhantomData;
+use std::{
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
 
 /// A [World] mutation
 pub trait Command: Send + Sync {
@@ -59,6 +63,61 @@ impl Command for Despawn {
     }
 }
 
+#[derive(Debug)]
+pub(crate) struct ClearEntities<F>
+where
+    F: QueryFilter + Send + Sync + 'static,
+{
+    phantom: PhantomData<F>,
+}
+
+impl<F> Command for ClearEntities<F>
+where
+    F: QueryFilter + Send + Sync + 'static,
+{
+    fn write(self: Box<Self>, world: &mut World, _resources: &mut Resources) {
+        // entities reserved by spawn commands are flushed into the empty archetype before their
+        // components are inserted, so entities without components are kept to let later spawn
+        // commands in the same batch succeed
+        let entities = world
+            .query_filtered::<Entity, F>()
+            .filter(|entity| match world.get_entity_location(*entity) {
+                Some(location) => location.archetype != 0,
+                None => false,
+            })
+            .collect::<Vec<_>>();
+        for entity in entities {
+            if let Err(e) = world.despawn(entity) {
+                debug!("Failed to despawn entity {:?}: {}", entity, e);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct DespawnAllWith<T>
+where
+    T: Component,
+{
+    phantom: PhantomData<T>,
+}
+
+impl<T> Command for DespawnAllWith<T>
+where
+    T: Component,
+{
+    fn write(self: Box<Self>, world: &mut World, _resources: &mut Resources) {
+        let entities = world
+            .query_filtered::<Entity, With<T>>()
+            .collect::<Vec<_>>();
+        for entity in entities {
+            if let Err(e) = world.despawn(entity) {
+                debug!("Failed to despawn entity {:?}: {}", entity, e);
+            }
+        }
+    }
+}
+
 pub struct Insert<T>
 where
     T: DynamicBundle + Send + Sync + 'static,
@@ -114,6 +173,51 @@ where
     }
 }
 
+#[derive(Debug)]
+pub(crate) struct InsertSparse<T>
+where
+    T: Component,
+{
+    entity: Entity,
+    component: T,
+}
+
+impl<T> Command for InsertSparse<T>
+where
+    T: Component,
+{
+    fn write(self: Box<Self>, _world: &mut World, resources: &mut Resources) {
+        if let Some(mut storage) = resources.get_mut::<SparseStorage<T>>() {
+            storage.insert(self.entity, self.component);
+            return;
+        }
+
+        let mut storage = SparseStorage::default();
+        storage.insert(self.entity, self.component);
+        resources.insert(storage);
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct RemoveSparse<T>
+where
+    T: Component,
+{
+    entity: Entity,
+    phantom: PhantomData<T>,
+}
+
+impl<T> Command for RemoveSparse<T>
+where
+    T: Component,
+{
+    fn write(self: Box<Self>, _world: &mut World, resources: &mut Resources) {
+        if let Some(mut storage) = resources.get_mut::<SparseStorage<T>>() {
+            storage.remove(self.entity);
+        }
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct Remove<T>
 where
@@ -224,6 +328,35 @@ impl Commands {
     /// }
     /// ```
     pub fn spawn(&mut self, bundle: impl DynamicBundle + Send + Sync + 'static) -> &mut Self {
+        self.spawn_entity(bundle);
+        self
+    }
+
+    /// Creates a new entity with the components contained in `bundle`, like [Self::spawn], and
+    /// returns an [EntityCommands] for it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bevy_ecs::prelude::*;
+    ///
+    /// struct Component1;
+    /// struct Component2;
+    ///
+    /// fn example_system(mut commands: Commands) {
+    ///     // The id of the new entity is available right away, and components added through the
+    ///     // returned builder always go to that entity.
+    ///     let mut first = commands.spawn_entity((Component1,));
+    ///     let first_id = first.id();
+    ///     first.with(Component2);
+    ///     commands.spawn_entity((Component1,)).with(Component2);
+    ///     commands.entity(first_id).with(5u32);
+    /// }
+    /// ```
+    pub fn spawn_entity(
+        &mut self,
+        bundle: impl DynamicBundle + Send + Sync + 'static,
+    ) -> EntityCommands<'_> {
         let entity = self
             .entity_reserver
             .as_ref()
@@ -231,7 +364,20 @@ impl Commands {
             .reserve_entity();
         self.set_current_entity(entity);
         self.insert(entity, bundle);
-        self
+        EntityCommands {
+            entity,
+            commands: self,
+        }
+    }
+
+    /// Returns an [EntityCommands] for adding components to an existing `entity`. This also makes
+    /// `entity` the current entity.
+    pub fn entity(&mut self, entity: Entity) -> EntityCommands<'_> {
+        self.set_current_entity(entity);
+        EntityCommands {
+            entity,
+            commands: self,
+        }
     }
 
     /// Equivalent to iterating `bundles_iter` and calling [`Self::spawn`] on each bundle, but slightly more performant.
@@ -248,6 +394,37 @@ impl Commands {
         self.add_command(Despawn { entity })
     }
 
+    /// Despawns every entity that has a `T` component, not including their children.
+    ///
+    /// See [`despawn_all_with`] for a system that does this.
+    pub fn despawn_all_with<T>(&mut self) -> &mut Self
+    where
+        T: Component,
+    {
+        self.add_command(DespawnAllWith::<T> {
+            phantom: PhantomData,
+        })
+    }
+
+    /// Despawns every entity that has components. Resources are not affected, so this can be used
+    /// to start a new game.
+    pub fn clear_entities(&mut self) -> &mut Self {
+        self.clear_entities_filtered::<()>()
+    }
+
+    /// Despawns every entity with components that matches the filter `F`, for example
+    /// `Without<Persistent>` to keep entities marked as persistent.
+    ///
+    /// See [`World::clear_filtered`].
+    pub fn clear_entities_filtered<F>(&mut self) -> &mut Self
+    where
+        F: QueryFilter + Send + Sync + 'static,
+    {
+        self.add_command(ClearEntities::<F> {
+            phantom: PhantomData,
+        })
+    }
+
     /// Inserts a bundle of components into `entity`.
     ///
     /// See [`World::insert`].
@@ -295,6 +472,23 @@ impl Commands {
         })
     }
 
+    /// Adds `component` to the [SparseStorage] resource of its type, which is created if it doesn't
+    /// exist yet. Unlike [Commands::insert_one], this doesn't move the entity to another archetype.
+    pub fn insert_sparse(&mut self, entity: Entity, component: impl Component) -> &mut Self {
+        self.add_command(InsertSparse { entity, component })
+    }
+
+    /// Removes the `T` component of `entity` from the [SparseStorage] resource of its type
+    pub fn remove_sparse<T>(&mut self, entity: Entity) -> &mut Self
+    where
+        T: Component,
+    {
+        self.add_command(RemoveSparse::<T> {
+            entity,
+            phantom: PhantomData,
+        })
+    }
+
     /// See [`World::remove`].
     pub fn remove<T>(&mut self, entity: Entity) -> &mut Self
     where
@@ -408,6 +602,79 @@ impl Commands {
     }
 }
 
+/// Adds components to a single entity, returned by [Commands::spawn_entity] and [Commands::entity].
+///
+/// Unlike [Commands::with], components are always added to the entity this was created for, even
+/// if other entities were spawned in the meantime. Every other [Commands] method can be called
+/// directly on an [EntityCommands], which makes `spawn_entity` calls chainable.
+pub struct EntityCommands<'a> {
+    entity: Entity,
+    commands: &'a mut Commands,
+}
+
+impl<'a> EntityCommands<'a> {
+    /// The id of the entity
+    pub fn id(&self) -> Entity {
+        self.entity
+    }
+
+    /// Adds a single component to the entity.
+    ///
+    /// See [Commands::insert_one].
+    pub fn with(&mut self, component: impl Component) -> &mut Self {
+        self.commands.insert_one(self.entity, component);
+        self
+    }
+
+    /// Adds a bundle of components to the entity.
+    ///
+    /// See [Commands::insert].
+    pub fn with_bundle(&mut self, bundle: impl DynamicBundle + Send + Sync + 'static) -> &mut Self {
+        self.commands.insert(self.entity, bundle);
+        self
+    }
+
+    /// Returns the [Commands] this was created from
+    pub fn commands(&mut self) -> &mut Commands {
+        self.commands
+    }
+}
+
+impl<'a> Deref for EntityCommands<'a> {
+    type Target = Commands;
+
+    fn deref(&self) -> &Self::Target {
+        self.commands
+    }
+}
+
+impl<'a> DerefMut for EntityCommands<'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.commands
+    }
+}
+
+/// A system that despawns every entity with a `T` component. This is useful to clean up the
+/// entities of a state when leaving it:
+///
+/// ```
+/// use bevy_ecs::prelude::*;
+///
+/// #[derive(Clone)]
+/// enum GameState {
+///     Playing,
+///     GameOver,
+/// }
+///
+/// struct Food;
+///
+/// let mut stage = StateStage::<GameState>::default();
+/// stage.on_state_exit(GameState::Playing, despawn_all_with::<Food>.system());
+/// ```
+pub fn despawn_all_with<T: Component>(commands: &mut Commands) {
+    commands.despawn_all_with::<T>();
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{resource::Resources, Commands, World};
@@ -466,4 +733,94 @@ mod tests {
         let results_after_u64 = world.query::<&u64>().map(|a| *a).collect::<Vec<_>>();
         assert_eq!(results_after_u64, vec![]);
     }
+
+    #[test]
+    fn despawn_all_with() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        let mut command_buffer = Commands::default();
+        command_buffer.set_entity_reserver(world.get_entity_reserver());
+        command_buffer
+            .spawn((1u32, 'a'))
+            .spawn((2u32,))
+            .spawn((3u32, 'b'));
+        command_buffer.apply(&mut world, &mut resources);
+
+        command_buffer.despawn_all_with::<char>();
+        command_buffer.despawn_all_with::<char>(); // despawning twice shouldn't panic
+        command_buffer.apply(&mut world, &mut resources);
+        let results = world.query::<&u32>().copied().collect::<Vec<_>>();
+        assert_eq!(results, vec![2u32]);
+        assert_eq!(world.query::<&char>().count(), 0);
+    }
+
+    #[test]
+    fn clear_entities() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        let mut command_buffer = Commands::default();
+        command_buffer.set_entity_reserver(world.get_entity_reserver());
+        command_buffer
+            .spawn((1u32, 'a'))
+            .spawn((2u32,))
+            .insert_resource(3u64);
+        command_buffer.apply(&mut world, &mut resources);
+
+        command_buffer.clear_entities_filtered::<crate::Without<char>>();
+        command_buffer.apply(&mut world, &mut resources);
+        let results = world.query::<&u32>().copied().collect::<Vec<_>>();
+        assert_eq!(results, vec![1u32]);
+
+        command_buffer.clear_entities().spawn((4u32,));
+        command_buffer.apply(&mut world, &mut resources);
+        let results = world.query::<&u32>().copied().collect::<Vec<_>>();
+        assert_eq!(results, vec![4u32]);
+        assert_eq!(*resources.get::<u64>().unwrap(), 3);
+    }
+
+    #[test]
+    fn sparse_components() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        let mut command_buffer = Commands::default();
+        let entity = world.spawn((1u32,));
+        command_buffer.insert_sparse(entity, 'a');
+        command_buffer.apply(&mut world, &mut resources);
+        assert_eq!(
+            resources
+                .get::<crate::SparseStorage<char>>()
+                .unwrap()
+                .get(entity),
+            Some(&'a')
+        );
+        // the entity stays in its archetype
+        assert_eq!(world.query::<&char>().count(), 0);
+
+        command_buffer.remove_sparse::<char>(entity);
+        command_buffer.apply(&mut world, &mut resources);
+        assert!(resources
+            .get::<crate::SparseStorage<char>>()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn entity_commands() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        let mut command_buffer = Commands::default();
+        command_buffer.set_entity_reserver(world.get_entity_reserver());
+
+        let mut first = command_buffer.spawn_entity((1u32,));
+        let first_id = first.id();
+        let second_id = first.spawn_entity((2u32,)).with(2u64).id();
+        first.with(1u64);
+        command_buffer.entity(first_id).with_bundle(('a',));
+        command_buffer.apply(&mut world, &mut resources);
+
+        assert_eq!(*world.get::<u64>(first_id).unwrap(), 1u64);
+        assert_eq!(*world.get::<char>(first_id).unwrap(), 'a');
+        assert_eq!(*world.get::<u64>(second_id).unwrap(), 2u64);
+        assert!(world.get::<char>(second_id).is_err());
+    }
 }
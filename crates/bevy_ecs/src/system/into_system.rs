@@ -12,6 +12,7 @@ pub struct SystemState {
     pub(crate) archetype_component_access: TypeAccess<ArchetypeComponent>,
     pub(crate) resource_access: TypeAccess<TypeId>,
     pub(crate) local_resource_access: TypeAccess<TypeId>,
+    pub(crate) resource_types: Vec<(TypeId, &'static str)>,
     pub(crate) query_archetype_component_accesses: Vec<TypeAccess<ArchetypeComponent>>,
     pub(crate) query_accesses: Vec<Vec<QueryAccess>>,
     pub(crate) query_type_names: Vec<&'static str>,
@@ -112,6 +113,10 @@ impl<Out: 'static> System for FuncSystem<Out> {
         &self.state.resource_access
     }
 
+    fn resource_types(&self) -> Vec<(TypeId, &'static str)> {
+        self.state.resource_types.clone()
+    }
+
     fn thread_local_execution(&self) -> ThreadLocalExecution {
         ThreadLocalExecution::NextFlush
     }
@@ -168,6 +173,10 @@ impl<In: 'static, Out: 'static> System for InputFuncSystem<In, Out> {
         &self.state.resource_access
     }
 
+    fn resource_types(&self) -> Vec<(TypeId, &'static str)> {
+        self.state.resource_types.clone()
+    }
+
     fn thread_local_execution(&self) -> ThreadLocalExecution {
         ThreadLocalExecution::NextFlush
     }
@@ -221,6 +230,7 @@ macro_rules! impl_into_system {
                         archetype_component_access: TypeAccess::default(),
                         resource_access: TypeAccess::default(),
                         local_resource_access: TypeAccess::default(),
+                        resource_types: Vec::new(),
                         id: SystemId::new(),
                         commands: Default::default(),
                         arc_commands: Default::default(),
@@ -273,6 +283,7 @@ macro_rules! impl_into_system {
                         archetype_component_access: TypeAccess::default(),
                         resource_access: TypeAccess::default(),
                         local_resource_access: TypeAccess::default(),
+                        resource_types: Vec::new(),
                         id: SystemId::new(),
                         commands: Default::default(),
                         arc_commands: Default::default(),
@@ -336,7 +347,8 @@ mod tests {
         clear_trackers_system,
         resource::{Res, ResMut, Resources},
         schedule::Schedule,
-        ChangedRes, Entity, Local, Or, Query, QuerySet, System, SystemStage, With, World,
+        ChangedRes, Entity, Local, Or, Query, QueryError, QuerySet, System, SystemStage, With,
+        World,
     };
 
     #[derive(Debug, Eq, PartialEq, Default)]
@@ -549,6 +561,62 @@ mod tests {
         run_system(&mut world, &mut resources, sys.system());
     }
 
+    #[test]
+    fn query_lens_system() {
+        fn sys(mut ran: ResMut<bool>, mut query: Query<(&mut A, &B)>) {
+            let mut lens = query.lens::<&mut A, (With<B>, With<C>)>().unwrap();
+            assert_eq!(lens.iter_mut().count(), 1);
+            assert_eq!(query.lens::<&B, ()>().unwrap().iter().count(), 2);
+            assert!(matches!(
+                query.lens::<&mut B, ()>(),
+                Err(QueryError::CannotWriteArchetype)
+            ));
+            // (A, C) is not matched by the query
+            assert!(matches!(
+                query.lens::<&A, ()>(),
+                Err(QueryError::CannotReadArchetype)
+            ));
+
+            *ran = true;
+        }
+
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(false);
+        world.spawn((A, B));
+        world.spawn((A, B, C));
+        world.spawn((A, C));
+
+        run_system(&mut world, &mut resources, sys.system());
+
+        assert!(*resources.get::<bool>().unwrap(), "system ran");
+    }
+
+    #[test]
+    fn query_join_system() {
+        fn sys(mut ran: ResMut<bool>, mut positions: Query<&mut i32>, mut heads: Query<&A>) {
+            for (mut position, _head) in positions.join(&mut heads) {
+                *position += 1;
+            }
+            let mut lens = heads.lens::<Entity, With<A>>().unwrap();
+            assert_eq!(positions.join(&mut lens).count(), 1);
+
+            *ran = true;
+        }
+
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(false);
+        let head = world.spawn((A, 1));
+        let segment = world.spawn((B, 1));
+
+        run_system(&mut world, &mut resources, sys.system());
+
+        assert!(*resources.get::<bool>().unwrap(), "system ran");
+        assert_eq!(*world.get::<i32>(head).unwrap(), 2);
+        assert_eq!(*world.get::<i32>(segment).unwrap(), 1);
+    }
+
     #[test]
     #[should_panic]
     fn conflicting_query_with_query_set_system() {
@@ -639,4 +707,21 @@ mod tests {
         fn sys(_: Local<BufferRes>, _: ResMut<BufferRes>, _: Local<A>, _: ResMut<A>) {}
         test_for_conflicting_resources(sys.system())
     }
+
+    #[test]
+    #[should_panic(expected = "Resource does not exist")]
+    fn missing_resource_system() {
+        fn sys(mut count: ResMut<u32>, _: Res<BufferRes>) {
+            *count += 1;
+        }
+
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(0u32);
+        let mut schedule = Schedule::default();
+        schedule.add_stage("update", SystemStage::serial().with_system(sys.system()));
+
+        // the missing resource is reported when the system is added, then fetching it panics
+        schedule.initialize_and_run(&mut world, &mut resources);
+    }
 }
@@ -2,6 +2,7 @@ mod commands;
 mod into_system;
 mod into_thread_local;
 mod query;
+mod resource_changed;
 #[allow(clippy::module_inception)]
 mod system;
 mod system_chaining;
@@ -11,6 +12,7 @@ pub use commands::*;
 pub use into_system::*;
 pub use into_thread_local::*;
 pub use query::*;
+pub use resource_changed::*;
 pub use system::*;
 pub use system_chaining::*;
 pub use system_param::*;
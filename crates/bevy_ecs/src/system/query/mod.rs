@@ -3,7 +3,7 @@ pub use query_set::*;
 
 use crate::{
     ArchetypeComponent, Batch, BatchedIter, Component, ComponentError, Entity, Fetch, Mut,
-    QueryFilter, QueryIter, ReadOnlyFetch, TypeAccess, World, WorldQuery,
+    QueryAccess, QueryFilter, QueryIter, ReadOnlyFetch, TypeAccess, World, WorldQuery,
 };
 use bevy_tasks::ParallelIterator;
 use std::marker::PhantomData;
@@ -200,6 +200,73 @@ impl<'a, Q: WorldQuery, F: QueryFilter> Query<'a, Q, F> {
         self.world.removed::<C>()
     }
 
+    /// Returns a view of this query as a query for `NQ` filtered by `NF`, for passing a subset of a
+    /// query to code that expects a narrower one. This will fail if `NQ` and `NF` match archetypes
+    /// this query doesn't, access components this query doesn't, or write components this query
+    /// only reads. Per-entity filters of this query, like [Changed](crate::Changed), don't apply
+    /// to the lens.
+    pub fn lens<NQ: WorldQuery, NF: QueryFilter>(
+        &mut self,
+    ) -> Result<Query<'_, NQ, NF>, QueryError> {
+        let access = QueryAccess::union(vec![Q::Fetch::access(), F::access()]);
+        let lens_access = QueryAccess::union(vec![NQ::Fetch::access(), NF::access()]);
+        let mut lens_type_access = TypeAccess::default();
+        for (index, archetype) in self.world.archetypes().enumerate() {
+            let index = index as u32;
+            if lens_access
+                .get_access(archetype, index, Some(&mut lens_type_access))
+                .is_some()
+                && access.get_access(archetype, index, None).is_none()
+            {
+                return Err(QueryError::CannotReadArchetype);
+            }
+        }
+
+        if let Some(write) = lens_type_access
+            .iter_writes()
+            .find(|write| !self.component_access.is_write(write))
+        {
+            return Err(if self.component_access.is_read_or_write(write) {
+                QueryError::CannotWriteArchetype
+            } else {
+                QueryError::CannotReadArchetype
+            });
+        }
+        if lens_type_access
+            .iter_reads()
+            .any(|read| !self.component_access.is_read_or_write(read))
+        {
+            return Err(QueryError::CannotReadArchetype);
+        }
+
+        // SAFE: the lens only accesses components this query has access to, and borrowing self
+        // mutably prevents this query from being used at the same time
+        Ok(unsafe { Query::new(self.world, self.component_access) })
+    }
+
+    /// Iterates over the entities matched by both this query and `other`, returning the results
+    /// of both queries for each of them. This is useful to combine queries for different sets of
+    /// components, or a query with a [lens](Self::lens) of another one.
+    pub fn join<'s, OQ: WorldQuery, OF: QueryFilter>(
+        &'s mut self,
+        other: &'s mut Query<'_, OQ, OF>,
+    ) -> impl Iterator<
+        Item = (
+            <Q::Fetch as Fetch<'s>>::Item,
+            <OQ::Fetch as Fetch<'s>>::Item,
+        ),
+    > + 's {
+        let other_world: &'s World = other.world;
+        // SAFE: queries that can be borrowed at the same time are checked not to conflict when
+        // their system is created, and both are borrowed mutably for as long as the results live
+        let iter = unsafe { self.world.query_unchecked::<(Entity, Q), F>() };
+        iter.filter_map(move |(entity, item)| {
+            // SAFE: see above
+            let other_item = unsafe { other_world.query_one_unchecked::<OQ, OF>(entity) };
+            other_item.ok().map(|other_item| (item, other_item))
+        })
+    }
+
     /// Sets the entity's component to the given value. This will fail if the entity does not already have
     /// the given component type or if the given component type does not match this query.
     pub fn set<T: Component>(&mut self, entity: Entity, component: T) -> Result<(), QueryError> {
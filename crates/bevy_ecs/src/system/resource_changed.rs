@@ -0,0 +1,188 @@
+use crate::{
+    ArchetypeComponent, Resource, ResourceIndex, Resources, System, SystemId, ThreadLocalExecution,
+    TypeAccess, World,
+};
+use std::{any::TypeId, borrow::Cow, marker::PhantomData};
+
+/// Whether the global resource `T` was inserted or mutated since trackers were last cleared
+///
+/// # Safety
+/// The caller must have read access to `T`
+pub(crate) unsafe fn resource_changed<T: Resource>(resources: &Resources) -> bool {
+    match resources.try_get_unsafe_ref_with_added_and_mutated::<T>(ResourceIndex::Global) {
+        Some((_, added, mutated)) => *added.as_ptr() || *mutated.as_ptr(),
+        None => false,
+    }
+}
+
+/// A [System] that only runs when the resource `T` was inserted or mutated, see
+/// [IntoResourceChangedSystem::on_resource_changed]
+pub struct ResourceChangedSystem<T, S> {
+    system: S,
+    ran: bool,
+    name: Cow<'static, str>,
+    resource_access: TypeAccess<TypeId>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Resource, S: System> System for ResourceChangedSystem<T, S> {
+    type In = S::In;
+    type Out = S::Out;
+
+    fn name(&self) -> Cow<'static, str> {
+        self.name.clone()
+    }
+
+    fn id(&self) -> SystemId {
+        self.system.id()
+    }
+
+    fn update(&mut self, world: &World) {
+        self.system.update(world);
+        self.resource_access.clear();
+        self.resource_access.union(self.system.resource_access());
+        self.resource_access.add_read(TypeId::of::<T>());
+    }
+
+    fn archetype_component_access(&self) -> &TypeAccess<ArchetypeComponent> {
+        self.system.archetype_component_access()
+    }
+
+    fn resource_access(&self) -> &TypeAccess<TypeId> {
+        &self.resource_access
+    }
+
+    fn resource_types(&self) -> Vec<(TypeId, &'static str)> {
+        let mut resource_types = self.system.resource_types();
+        resource_types.push((TypeId::of::<T>(), std::any::type_name::<T>()));
+        resource_types
+    }
+
+    fn thread_local_execution(&self) -> ThreadLocalExecution {
+        self.system.thread_local_execution()
+    }
+
+    unsafe fn run_unsafe(
+        &mut self,
+        input: Self::In,
+        world: &World,
+        resources: &Resources,
+    ) -> Option<Self::Out> {
+        self.ran = resource_changed::<T>(resources);
+        if self.ran {
+            self.system.run_unsafe(input, world, resources)
+        } else {
+            None
+        }
+    }
+
+    fn run_thread_local(&mut self, world: &mut World, resources: &mut Resources) {
+        // thread local systems do their work here, right after `run_unsafe` decided whether to
+        // run them
+        if self.ran {
+            self.system.run_thread_local(world, resources);
+        }
+    }
+
+    fn initialize(&mut self, world: &mut World, resources: &mut Resources) {
+        self.system.initialize(world, resources);
+    }
+}
+
+pub trait IntoResourceChangedSystem: System + Sized {
+    /// Only runs the system in frames the resource `T` was inserted or mutated in, like
+    /// rebuilding pipelines when `Msaa` changes, instead of every frame. The system doesn't run
+    /// while `T` does not exist.
+    ///
+    /// Trackers are cleared at the end of each frame, so like [ChangedRes](crate::ChangedRes),
+    /// the system only sees changes made before it runs in the same frame. Stages can also be
+    /// run only when a resource changed with the [ResourceChanged](crate::ResourceChanged) run
+    /// criteria.
+    fn on_resource_changed<T: Resource>(self) -> ResourceChangedSystem<T, Self>;
+}
+
+impl<S: System> IntoResourceChangedSystem for S {
+    fn on_resource_changed<T: Resource>(self) -> ResourceChangedSystem<T, S> {
+        ResourceChangedSystem {
+            name: Cow::Owned(format!(
+                "OnResourceChanged<{}>({})",
+                std::any::type_name::<T>(),
+                self.name()
+            )),
+            system: self,
+            ran: false,
+            resource_access: Default::default(),
+            marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IntoResourceChangedSystem;
+    use crate::{IntoSystem, Res, ResMut, ResourceChanged, Resources, Stage, SystemStage, World};
+
+    struct Center(f32);
+
+    #[derive(Default)]
+    struct Runs(u32);
+
+    fn count(center: Res<Center>, mut runs: ResMut<Runs>) {
+        assert!(center.0 >= 0.0);
+        runs.0 += 1;
+    }
+
+    fn count_thread_local(_world: &mut World, resources: &mut Resources) {
+        resources.get_mut::<Runs>().unwrap().0 += 10;
+    }
+
+    #[test]
+    fn run_on_resource_change() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(Runs::default());
+        let mut stage = SystemStage::serial();
+        stage
+            .add_system(count.system().on_resource_changed::<Center>())
+            .add_system(count_thread_local.system().on_resource_changed::<Center>());
+        stage.initialize(&mut world, &mut resources);
+        let mut run = |world: &mut World, resources: &mut Resources| {
+            stage.run(world, resources);
+            resources.clear_trackers();
+            resources.get::<Runs>().unwrap().0
+        };
+
+        // the resource doesn't exist yet
+        assert_eq!(run(&mut world, &mut resources), 0);
+        resources.insert(Center(0.0));
+        assert_eq!(run(&mut world, &mut resources), 11);
+        assert_eq!(run(&mut world, &mut resources), 11);
+        // reading the resource isn't a change
+        assert_eq!(resources.get::<Center>().unwrap().0, 0.0);
+        assert_eq!(run(&mut world, &mut resources), 11);
+        resources.get_mut::<Center>().unwrap().0 = 1.0;
+        assert_eq!(run(&mut world, &mut resources), 22);
+        assert_eq!(run(&mut world, &mut resources), 22);
+    }
+
+    #[test]
+    fn run_stage_on_resource_change() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(Runs::default());
+        resources.insert(Center(0.0));
+        let mut stage =
+            SystemStage::serial().with_run_criteria(ResourceChanged::<Center>::default());
+        stage.add_system(count.system());
+        stage.initialize(&mut world, &mut resources);
+
+        stage.run(&mut world, &mut resources);
+        resources.clear_trackers();
+        stage.run(&mut world, &mut resources);
+        assert_eq!(resources.get::<Runs>().unwrap().0, 1);
+
+        resources.get_mut::<Center>().unwrap().0 = 2.0;
+        stage.run(&mut world, &mut resources);
+        assert_eq!(resources.get::<Runs>().unwrap().0, 2);
+    }
+}
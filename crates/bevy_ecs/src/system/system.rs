@@ -27,6 +27,11 @@ pub trait System: Send + Sync + 'static {
     fn update(&mut self, world: &World);
     fn archetype_component_access(&self) -> &TypeAccess<ArchetypeComponent>;
     fn resource_access(&self) -> &TypeAccess<TypeId>;
+    /// The ids and names of the global resources this system accesses. These are used to report
+    /// missing resources and conflicting access.
+    fn resource_types(&self) -> Vec<(TypeId, &'static str)> {
+        Vec::new()
+    }
     fn thread_local_execution(&self) -> ThreadLocalExecution;
     /// # Safety
     /// This might access World and Resources in an unsafe manner. This should only be called in one of the following contexts:
@@ -160,6 +160,9 @@ impl<'a, T: Resource> FetchSystemParam<'a> for FetchRes<T> {
             );
         }
         system_state.resource_access.add_read(TypeId::of::<T>());
+        system_state
+            .resource_types
+            .push((TypeId::of::<T>(), std::any::type_name::<T>()));
     }
 
     #[inline]
@@ -197,6 +200,9 @@ impl<'a, T: Resource> FetchSystemParam<'a> for FetchResMut<T> {
             );
         }
         system_state.resource_access.add_write(TypeId::of::<T>());
+        system_state
+            .resource_types
+            .push((TypeId::of::<T>(), std::any::type_name::<T>()));
     }
 
     #[inline]
@@ -230,6 +236,9 @@ impl<'a, T: Resource> FetchSystemParam<'a> for FetchChangedRes<T> {
             );
         }
         system_state.resource_access.add_read(TypeId::of::<T>());
+        system_state
+            .resource_types
+            .push((TypeId::of::<T>(), std::any::type_name::<T>()));
     }
 
     #[inline]
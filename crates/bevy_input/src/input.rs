@@ -1,4 +1,4 @@
-use bevy_utils::HashSet;
+use bevy_utils::{Duration, HashMap, HashSet, Instant};
 use std::hash::Hash;
 
 /// A "press-able" input of type `T`
@@ -7,6 +7,10 @@ pub struct Input<T> {
     pressed: HashSet<T>,
     just_pressed: HashSet<T>,
     just_released: HashSet<T>,
+    /// The update count and the start of the frame when each pressed input was pressed
+    pressed_since: HashMap<T, (u64, Instant)>,
+    updates: u64,
+    frame_start: Option<Instant>,
 }
 
 impl<T> Default for Input<T> {
@@ -15,6 +19,9 @@ impl<T> Default for Input<T> {
             pressed: Default::default(),
             just_pressed: Default::default(),
             just_released: Default::default(),
+            pressed_since: Default::default(),
+            updates: 0,
+            frame_start: None,
         }
     }
 }
@@ -26,6 +33,9 @@ where
     pub fn press(&mut self, input: T) {
         if !self.pressed(input) {
             self.just_pressed.insert(input);
+            let frame_start = *self.frame_start.get_or_insert_with(Instant::now);
+            self.pressed_since
+                .insert(input, (self.updates, frame_start));
         }
 
         self.pressed.insert(input);
@@ -37,6 +47,7 @@ where
 
     pub fn release(&mut self, input: T) {
         self.pressed.remove(&input);
+        self.pressed_since.remove(&input);
         self.just_released.insert(input);
     }
 
@@ -48,15 +59,68 @@ where
         self.just_released.contains(&input)
     }
 
+    /// Whether every input in `inputs` is pressed, in whatever order they were pressed
+    pub fn all_pressed(&self, inputs: &[T]) -> bool {
+        inputs.iter().all(|input| self.pressed(*input))
+    }
+
+    pub fn any_pressed(&self, inputs: &[T]) -> bool {
+        inputs.iter().any(|input| self.pressed(*input))
+    }
+
+    pub fn any_just_pressed(&self, inputs: &[T]) -> bool {
+        inputs.iter().any(|input| self.just_pressed(*input))
+    }
+
+    pub fn any_just_released(&self, inputs: &[T]) -> bool {
+        inputs.iter().any(|input| self.just_released(*input))
+    }
+
+    /// Whether the chord of `inputs` was completed on this update: all of them are pressed, and
+    /// the last of them was just pressed. The inputs can be pressed in any order, or at once.
+    pub fn chord_just_pressed(&self, inputs: &[T]) -> bool {
+        self.all_pressed(inputs) && self.any_just_pressed(inputs)
+    }
+
+    /// Whether the chord of `inputs` was broken on this update: all of them were pressed, and
+    /// some were just released
+    pub fn chord_just_released(&self, inputs: &[T]) -> bool {
+        inputs
+            .iter()
+            .all(|input| self.pressed(*input) || self.just_released(*input))
+            && self.any_just_released(inputs)
+    }
+
+    /// How long `input` has been pressed, from the start of the frame it was pressed in to the
+    /// start of the current frame, so all systems see the same duration during a frame. Zero on
+    /// the frame it is pressed, and `None` if it is not pressed.
+    pub fn pressed_duration(&self, input: T) -> Option<Duration> {
+        let (_, pressed_at) = self.pressed_since.get(&input)?;
+        Some(self.frame_start.map_or(Duration::default(), |frame_start| {
+            frame_start.duration_since(*pressed_at)
+        }))
+    }
+
+    /// For how many updates `input` has been pressed. Zero on the frame it is pressed, and `None`
+    /// if it is not pressed.
+    pub fn pressed_updates(&self, input: T) -> Option<u64> {
+        let (pressed_at, _) = self.pressed_since.get(&input)?;
+        Some(self.updates - pressed_at)
+    }
+
     pub fn reset(&mut self, input: T) {
         self.pressed.remove(&input);
+        self.pressed_since.remove(&input);
         self.just_pressed.remove(&input);
         self.just_released.remove(&input);
     }
 
+    /// Starts a new frame, clearing the inputs that were just pressed or released
     pub fn update(&mut self) {
         self.just_pressed.clear();
         self.just_released.clear();
+        self.updates += 1;
+        self.frame_start = Some(Instant::now());
     }
 
     pub fn get_pressed(&self) -> impl ExactSizeIterator<Item = &T> {
@@ -147,4 +211,50 @@ mod test {
 
         assert!(!input.just_released(DummyInput::Input2));
     }
+
+    #[test]
+    fn chord_test() {
+        use crate::Input;
+
+        #[derive(Copy, Clone, Eq, PartialEq, Hash)]
+        enum DummyInput {
+            Control,
+            Shift,
+            S,
+        }
+        use DummyInput::*;
+        let save_as = [Control, Shift, S];
+
+        let mut input = Input::default();
+        input.update();
+        input.press(Shift);
+        assert!(!input.chord_just_pressed(&save_as));
+        assert_eq!(input.pressed_updates(Shift), Some(0));
+        assert_eq!(
+            input.pressed_duration(Shift),
+            Some(std::time::Duration::default())
+        );
+
+        // the order the keys are pressed in doesn't matter
+        input.update();
+        input.press(S);
+        input.press(Control);
+        assert!(input.chord_just_pressed(&save_as));
+        assert!(input.all_pressed(&save_as));
+        assert_eq!(input.pressed_updates(Shift), Some(1));
+
+        input.update();
+        assert!(!input.chord_just_pressed(&save_as));
+        assert!(input.all_pressed(&save_as));
+
+        // releasing any key breaks the chord once
+        input.release(S);
+        assert!(input.chord_just_released(&save_as));
+        assert_eq!(input.pressed_updates(S), None);
+        input.update();
+        input.release(Control);
+        assert!(!input.chord_just_released(&save_as));
+        assert!(input.any_pressed(&save_as));
+        assert!(input.any_just_released(&save_as));
+    }
 }
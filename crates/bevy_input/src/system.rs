@@ -3,10 +3,20 @@ use crate::{
     ElementState,
 };
 use bevy_app::{
-    prelude::{EventReader, Events},
+    prelude::{AppBuilder, EventReader, Events, Plugin},
     AppExit,
 };
-use bevy_ecs::{Local, Res, ResMut};
+use bevy_ecs::{IntoSystem, Local, Res, ResMut};
+
+/// Exits the app when the "esc" key is pressed, with [exit_on_esc_system]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ExitOnEscPlugin;
+
+impl Plugin for ExitOnEscPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system(exit_on_esc_system.system());
+    }
+}
 
 /// Local "exit on escape" system state
 #[derive(Default)]
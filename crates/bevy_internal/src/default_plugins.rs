@@ -14,6 +14,9 @@ impl PluginGroup for DefaultPlugins {
         group.add(bevy_asset::AssetPlugin::default());
         group.add(bevy_scene::ScenePlugin::default());
 
+        #[cfg(feature = "bevy_tween")]
+        group.add(bevy_tween::TweenPlugin::default());
+
         #[cfg(feature = "bevy_render")]
         group.add(bevy_render::RenderPlugin::default());
 
@@ -88,6 +88,24 @@ pub mod gltf {
     pub use bevy_gltf::*;
 }
 
+#[cfg(feature = "bevy_net")]
+pub mod net {
+    //! Replicate entities and components between apps over the network.
+    pub use bevy_net::*;
+}
+
+#[cfg(feature = "bevy_noise")]
+pub mod noise {
+    //! Seeded procedural noise functions (Perlin, simplex, Worley) and fractal combinators.
+    pub use bevy_noise::*;
+}
+
+#[cfg(feature = "bevy_script")]
+pub mod script {
+    //! Gameplay systems written in Rhai scripts.
+    pub use bevy_script::*;
+}
+
 #[cfg(feature = "bevy_pbr")]
 pub mod pbr {
     //! Physically based rendering.
@@ -113,6 +131,18 @@ pub mod text {
     pub use bevy_text::*;
 }
 
+#[cfg(feature = "bevy_tilemap")]
+pub mod tilemap {
+    //! Tile worlds.
+    pub use bevy_tilemap::*;
+}
+
+#[cfg(feature = "bevy_tween")]
+pub mod tween {
+    //! Tweening and keyframe animation of component fields.
+    pub use bevy_tween::*;
+}
+
 #[cfg(feature = "bevy_ui")]
 pub mod ui {
     //! User interface components and widgets.
@@ -9,9 +9,15 @@ pub use bevy_derive::bevy_main;
 #[cfg(feature = "bevy_audio")]
 pub use crate::audio::prelude::*;
 
+#[cfg(feature = "bevy_net")]
+pub use crate::net::prelude::*;
+
 #[cfg(feature = "bevy_pbr")]
 pub use crate::pbr::prelude::*;
 
+#[cfg(feature = "bevy_script")]
+pub use crate::script::prelude::*;
+
 #[cfg(feature = "bevy_render")]
 pub use crate::render::prelude::*;
 
@@ -21,6 +27,12 @@ pub use crate::sprite::prelude::*;
 #[cfg(feature = "bevy_text")]
 pub use crate::text::prelude::*;
 
+#[cfg(feature = "bevy_tilemap")]
+pub use crate::tilemap::prelude::*;
+
+#[cfg(feature = "bevy_tween")]
+pub use crate::tween::prelude::*;
+
 #[cfg(feature = "bevy_ui")]
 pub use crate::ui::prelude::*;
 
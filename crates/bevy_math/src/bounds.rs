@@ -0,0 +1,110 @@
+use bevy_reflect::Reflect;
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+
+/// A rectangle defined by two points. There is no defined origin, so 0,0 could be anywhere (top-left, bottom-left, etc)
+#[repr(C)]
+#[derive(Default, Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Reflect)]
+#[reflect(PartialEq)]
+pub struct Bounds {
+    /// The beginning point of the rect
+    pub min: Vec2,
+    /// The ending point of the rect
+    pub max: Vec2,
+}
+
+impl Bounds {
+    pub fn new(min: Vec2, max: Vec2) -> Self {
+        Bounds { min, max }
+    }
+
+    /// The rectangle of `size` centered on `center`
+    pub fn from_center_size(center: Vec2, size: Vec2) -> Self {
+        let half_size = size / 2.0;
+        Bounds::new(center - half_size, center + half_size)
+    }
+
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+
+    pub fn center(&self) -> Vec2 {
+        (self.min + self.max) / 2.0
+    }
+
+    /// Whether the rect has no area, which is the case when `max` is not above and to the right
+    /// of `min`
+    pub fn is_empty(&self) -> bool {
+        self.min.x >= self.max.x || self.min.y >= self.max.y
+    }
+
+    /// Whether `point` is inside the rect or on its edges
+    pub fn contains(&self, point: Vec2) -> bool {
+        point.x >= self.min.x
+            && point.x <= self.max.x
+            && point.y >= self.min.y
+            && point.y <= self.max.y
+    }
+
+    /// The area covered by both `self` and `other`, which is empty when they don't overlap
+    pub fn intersect(&self, other: &Bounds) -> Bounds {
+        Bounds::new(self.min.max(other.min), self.max.min(other.max))
+    }
+
+    pub fn intersects(&self, other: &Bounds) -> bool {
+        !self.intersect(other).is_empty()
+    }
+
+    /// The smallest rect that covers both `self` and `other`
+    pub fn union(&self, other: &Bounds) -> Bounds {
+        if self.is_empty() {
+            *other
+        } else if other.is_empty() {
+            *self
+        } else {
+            Bounds::new(self.min.min(other.min), self.max.max(other.max))
+        }
+    }
+
+    /// Grows the rect by `amount` on every side, or shrinks it if `amount` is negative
+    pub fn expand(&self, amount: f32) -> Bounds {
+        Bounds::new(
+            self.min - Vec2::splat(amount),
+            self.max + Vec2::splat(amount),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rect_operations() {
+        let a = Bounds::new(Vec2::new(0.0, 0.0), Vec2::new(4.0, 3.0));
+        let b = Bounds::from_center_size(Vec2::new(4.0, 0.5), Vec2::new(4.0, 3.0));
+        assert_eq!(
+            a.intersect(&b),
+            Bounds::new(Vec2::new(2.0, 0.0), Vec2::new(4.0, 2.0))
+        );
+        assert_eq!(
+            a.union(&b),
+            Bounds::new(Vec2::new(0.0, -1.0), Vec2::new(6.0, 3.0))
+        );
+        assert!(a.contains(Vec2::new(4.0, 1.5)));
+        assert!(!a.contains(Vec2::new(4.1, 1.5)));
+
+        let far = Bounds::from_center_size(Vec2::new(10.0, 10.0), Vec2::one());
+        assert!(!a.intersects(&far));
+        assert!(a.expand(7.0).intersects(&far));
+        assert_eq!(a.expand(-1.0).size(), Vec2::new(2.0, 1.0));
+    }
+}
@@ -24,7 +24,8 @@ impl<T: Default + Reflect> Default for Size<T> {
     }
 }
 
-/// A rect, as defined by its "side" locations
+/// A rect, as defined by its "side" locations, like the margins, padding, borders and positions
+/// of UI nodes. See [Bounds](crate::Bounds) for a rect defined by its corners.
 #[derive(Copy, Clone, PartialEq, Debug, Reflect)]
 pub struct Rect<T: Reflect> {
     pub left: T,
@@ -0,0 +1,115 @@
+use crate::{IVec2, UVec2};
+use bevy_reflect::Reflect;
+use serde::{Deserialize, Serialize};
+
+/// A rectangle of grid cells, from `min` up to but not including `max`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Reflect)]
+#[reflect(PartialEq, Hash)]
+pub struct IRect {
+    pub min: IVec2,
+    pub max: IVec2,
+}
+
+impl IRect {
+    pub fn new(min: IVec2, max: IVec2) -> Self {
+        IRect { min, max }
+    }
+
+    /// The rectangle of `size` cells with `min` in its bottom left corner
+    pub fn from_size(min: IVec2, size: UVec2) -> Self {
+        IRect::new(min, min + size.as_ivec2())
+    }
+
+    /// The rectangle that holds just the cell at `point`
+    pub fn from_point(point: IVec2) -> Self {
+        IRect::new(point, point + IVec2::one())
+    }
+
+    /// The number of cells along each side. Empty rectangles have a size of zero.
+    pub fn size(&self) -> UVec2 {
+        (self.max - self.min).max(IVec2::zero()).as_uvec2().unwrap()
+    }
+
+    /// The number of cells in the rectangle
+    pub fn area(&self) -> usize {
+        let size = self.size();
+        size.x as usize * size.y as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x >= self.max.x || self.min.y >= self.max.y
+    }
+
+    pub fn contains(&self, point: IVec2) -> bool {
+        point.x >= self.min.x
+            && point.x < self.max.x
+            && point.y >= self.min.y
+            && point.y < self.max.y
+    }
+
+    /// The cells in both `self` and `other`, which may be empty
+    pub fn intersect(&self, other: &IRect) -> IRect {
+        IRect::new(self.min.max(other.min), self.max.min(other.max))
+    }
+
+    pub fn intersects(&self, other: &IRect) -> bool {
+        !self.intersect(other).is_empty()
+    }
+
+    /// The smallest rectangle that holds both `self` and `other`
+    pub fn union(&self, other: &IRect) -> IRect {
+        if self.is_empty() {
+            *other
+        } else if other.is_empty() {
+            *self
+        } else {
+            IRect::new(self.min.min(other.min), self.max.max(other.max))
+        }
+    }
+
+    /// Grows the rectangle by `amount` cells on every side, or shrinks it if `amount` is negative
+    pub fn expand(&self, amount: i32) -> IRect {
+        IRect::new(
+            self.min - IVec2::splat(amount),
+            self.max + IVec2::splat(amount),
+        )
+    }
+
+    /// The cells in the rectangle, the bottom row first, each row from left to right
+    pub fn iter(&self) -> impl Iterator<Item = IVec2> {
+        let IRect { min, max } = *self;
+        (min.y..max.y).flat_map(move |y| (min.x..max.x).map(move |x| IVec2::new(x, y)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rect_operations() {
+        let a = IRect::new(IVec2::new(0, 0), IVec2::new(4, 3));
+        let b = IRect::new(IVec2::new(2, -1), IVec2::new(6, 2));
+        assert_eq!(
+            a.intersect(&b),
+            IRect::new(IVec2::new(2, 0), IVec2::new(4, 2))
+        );
+        assert_eq!(a.union(&b), IRect::new(IVec2::new(0, -1), IVec2::new(6, 3)));
+        assert!(a.contains(IVec2::new(3, 2)));
+        assert!(!a.contains(IVec2::new(4, 2)));
+        assert_eq!(a.area(), 12);
+
+        let far = IRect::from_point(IVec2::new(10, 10));
+        assert!(!a.intersects(&far));
+        assert_eq!(a.intersect(&far).size(), UVec2::zero());
+
+        let cells = IRect::from_point(IVec2::zero())
+            .expand(1)
+            .iter()
+            .collect::<Vec<_>>();
+        assert_eq!(cells.len(), 9);
+        assert_eq!(cells[0], IVec2::new(-1, -1));
+        assert_eq!(cells[1], IVec2::new(0, -1));
+        assert_eq!(cells[8], IVec2::new(1, 1));
+    }
+}
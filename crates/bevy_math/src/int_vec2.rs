@@ -0,0 +1,243 @@
+use bevy_reflect::Reflect;
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+macro_rules! impl_int_vec2 {
+    ($vec:ident, $scalar:ty) => {
+        impl $vec {
+            pub const fn new(x: $scalar, y: $scalar) -> Self {
+                $vec { x, y }
+            }
+
+            pub const fn splat(value: $scalar) -> Self {
+                $vec { x: value, y: value }
+            }
+
+            pub const fn zero() -> Self {
+                $vec::splat(0)
+            }
+
+            pub const fn one() -> Self {
+                $vec::splat(1)
+            }
+
+            pub const fn unit_x() -> Self {
+                $vec::new(1, 0)
+            }
+
+            pub const fn unit_y() -> Self {
+                $vec::new(0, 1)
+            }
+
+            /// The smaller of each component of `self` and `other`
+            pub fn min(self, other: Self) -> Self {
+                $vec::new(self.x.min(other.x), self.y.min(other.y))
+            }
+
+            /// The larger of each component of `self` and `other`
+            pub fn max(self, other: Self) -> Self {
+                $vec::new(self.x.max(other.x), self.y.max(other.y))
+            }
+
+            pub fn as_vec2(self) -> Vec2 {
+                Vec2::new(self.x as f32, self.y as f32)
+            }
+        }
+
+        impl From<($scalar, $scalar)> for $vec {
+            fn from((x, y): ($scalar, $scalar)) -> Self {
+                $vec::new(x, y)
+            }
+        }
+
+        impl From<$vec> for ($scalar, $scalar) {
+            fn from(vec: $vec) -> Self {
+                (vec.x, vec.y)
+            }
+        }
+
+        impl From<[$scalar; 2]> for $vec {
+            fn from([x, y]: [$scalar; 2]) -> Self {
+                $vec::new(x, y)
+            }
+        }
+
+        impl From<$vec> for [$scalar; 2] {
+            fn from(vec: $vec) -> Self {
+                [vec.x, vec.y]
+            }
+        }
+
+        impl Add for $vec {
+            type Output = $vec;
+
+            fn add(self, rhs: $vec) -> $vec {
+                $vec::new(self.x + rhs.x, self.y + rhs.y)
+            }
+        }
+
+        impl AddAssign for $vec {
+            fn add_assign(&mut self, rhs: $vec) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl Sub for $vec {
+            type Output = $vec;
+
+            fn sub(self, rhs: $vec) -> $vec {
+                $vec::new(self.x - rhs.x, self.y - rhs.y)
+            }
+        }
+
+        impl SubAssign for $vec {
+            fn sub_assign(&mut self, rhs: $vec) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl Mul for $vec {
+            type Output = $vec;
+
+            fn mul(self, rhs: $vec) -> $vec {
+                $vec::new(self.x * rhs.x, self.y * rhs.y)
+            }
+        }
+
+        impl Mul<$scalar> for $vec {
+            type Output = $vec;
+
+            fn mul(self, rhs: $scalar) -> $vec {
+                $vec::new(self.x * rhs, self.y * rhs)
+            }
+        }
+
+        impl Div<$scalar> for $vec {
+            type Output = $vec;
+
+            fn div(self, rhs: $scalar) -> $vec {
+                $vec::new(self.x / rhs, self.y / rhs)
+            }
+        }
+    };
+}
+
+/// A 2-dimensional vector of `i32`s, for positions on grids like tiles or chunks
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    Reflect,
+)]
+#[reflect(PartialEq, Hash)]
+pub struct IVec2 {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl_int_vec2!(IVec2, i32);
+
+impl IVec2 {
+    /// The position of the grid cell of `cell_size` that contains `position`
+    pub fn from_grid_position(position: Vec2, cell_size: f32) -> Self {
+        let cell = position / cell_size;
+        IVec2::new(cell.x.floor() as i32, cell.y.floor() as i32)
+    }
+
+    pub fn abs(self) -> Self {
+        IVec2::new(self.x.abs(), self.y.abs())
+    }
+
+    /// Divides each component by `rhs`, rounding towards negative infinity, so that cells keep
+    /// the same size on both sides of zero
+    pub fn div_euclid(self, rhs: i32) -> Self {
+        IVec2::new(self.x.div_euclid(rhs), self.y.div_euclid(rhs))
+    }
+
+    /// The remainder of [IVec2::div_euclid], which is never negative
+    pub fn rem_euclid(self, rhs: i32) -> Self {
+        IVec2::new(self.x.rem_euclid(rhs), self.y.rem_euclid(rhs))
+    }
+
+    /// The squared distance to `other`, which is exact where the distance itself would not be
+    pub fn distance_squared(self, other: IVec2) -> i64 {
+        let x = (self.x - other.x) as i64;
+        let y = (self.y - other.y) as i64;
+        x * x + y * y
+    }
+
+    /// Converts to a [UVec2], or returns `None` if a component is negative
+    pub fn as_uvec2(self) -> Option<UVec2> {
+        if self.x >= 0 && self.y >= 0 {
+            Some(UVec2::new(self.x as u32, self.y as u32))
+        } else {
+            None
+        }
+    }
+}
+
+impl Neg for IVec2 {
+    type Output = IVec2;
+
+    fn neg(self) -> IVec2 {
+        IVec2::new(-self.x, -self.y)
+    }
+}
+
+/// A 2-dimensional vector of `u32`s, for sizes of and positions within grids
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    Reflect,
+)]
+#[reflect(PartialEq, Hash)]
+pub struct UVec2 {
+    pub x: u32,
+    pub y: u32,
+}
+
+impl_int_vec2!(UVec2, u32);
+
+impl UVec2 {
+    pub fn as_ivec2(self) -> IVec2 {
+        IVec2::new(self.x as i32, self.y as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_math() {
+        assert_eq!(IVec2::new(-1, 7).div_euclid(4), IVec2::new(-1, 1));
+        assert_eq!(IVec2::new(-1, 7).rem_euclid(4), IVec2::new(3, 3));
+        assert_eq!(
+            IVec2::from_grid_position(Vec2::new(-0.5, 31.9), 16.0),
+            IVec2::new(-1, 1)
+        );
+        assert_eq!(IVec2::new(2, -3).as_uvec2(), None);
+        assert_eq!(
+            UVec2::new(2, 3).as_ivec2() * 2 - IVec2::one(),
+            IVec2::new(3, 5)
+        );
+    }
+}
@@ -1,12 +1,22 @@
+mod bounds;
 mod clamp;
 mod face_toward;
 mod geometry;
+mod int_rect;
+mod int_vec2;
+mod morton;
 
+pub use bounds::*;
 pub use clamp::*;
 pub use face_toward::*;
 pub use geometry::*;
 pub use glam::*;
+pub use int_rect::*;
+pub use int_vec2::*;
+pub use morton::*;
 
 pub mod prelude {
-    pub use crate::{FaceToward, Mat3, Mat4, Quat, Rect, Size, Vec2, Vec3, Vec4};
+    pub use crate::{
+        Bounds, FaceToward, IRect, IVec2, Mat3, Mat4, Quat, Rect, Size, UVec2, Vec2, Vec3, Vec4,
+    };
 }
@@ -0,0 +1,192 @@
+use crate::{IRect, IVec2};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, ops::Bound};
+
+/// A grid position encoded as a Morton code (Z-order curve), which interleaves the bits of its
+/// coordinates.
+///
+/// Sorting by Morton code keeps positions that are close on the grid close in memory, so maps
+/// keyed by it, like a [BTreeMap], are cache friendly and can find everything in an [IRect]
+/// with a few range lookups, see [MortonCode::range].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct MortonCode(pub u64);
+
+// the bits of x are at the even positions of a code and the bits of y at the odd positions
+const X_BITS: u64 = 0x5555_5555_5555_5555;
+const Y_BITS: u64 = 0xaaaa_aaaa_aaaa_aaaa;
+
+impl MortonCode {
+    pub fn encode(position: IVec2) -> Self {
+        // flipping the sign bit maps i32s to u32s in the same order, so that every position in
+        // a rect has a code between the codes of its corners
+        let x = spread(position.x as u32 ^ 0x8000_0000);
+        let y = spread(position.y as u32 ^ 0x8000_0000);
+        MortonCode(x | y << 1)
+    }
+
+    pub fn decode(self) -> IVec2 {
+        let x = compact(self.0) ^ 0x8000_0000;
+        let y = compact(self.0 >> 1) ^ 0x8000_0000;
+        IVec2::new(x as i32, y as i32)
+    }
+
+    /// The smallest code that is at least `self` and is inside `rect`, if there is one
+    pub fn next_in(self, rect: &IRect) -> Option<MortonCode> {
+        if rect.is_empty() {
+            return None;
+        }
+        let min = MortonCode::encode(rect.min);
+        let max = MortonCode::encode(rect.max - IVec2::one());
+        if self > max {
+            None
+        } else if self <= min {
+            Some(min)
+        } else if rect.contains(self.decode()) {
+            Some(self)
+        } else {
+            Some(MortonCode(big_min(self.0, min.0, max.0)))
+        }
+    }
+
+    /// The codes of the positions in `rect`, in Z-order
+    pub fn iter_rect(rect: IRect) -> impl Iterator<Item = MortonCode> {
+        let mut next = MortonCode(0).next_in(&rect);
+        std::iter::from_fn(move || {
+            let code = next?;
+            next = code
+                .0
+                .checked_add(1)
+                .and_then(|successor| MortonCode(successor).next_in(&rect));
+            Some(code)
+        })
+    }
+
+    /// The entries of `map` with positions in `rect`, in Z-order. Skips over runs of codes
+    /// outside the rect instead of visiting every entry between the corners of the rect.
+    pub fn range<V>(
+        map: &BTreeMap<MortonCode, V>,
+        rect: IRect,
+    ) -> impl Iterator<Item = (IVec2, &V)> + '_ {
+        let mut next = MortonCode(0).next_in(&rect);
+        std::iter::from_fn(move || loop {
+            let code = next?;
+            let (&found, value) = map
+                .range((Bound::Included(code), Bound::Unbounded))
+                .next()?;
+            let position = found.decode();
+            if rect.contains(position) {
+                next = found.0.checked_add(1).map(MortonCode);
+                return Some((position, value));
+            }
+            next = found.next_in(&rect);
+        })
+    }
+}
+
+impl From<IVec2> for MortonCode {
+    fn from(position: IVec2) -> Self {
+        MortonCode::encode(position)
+    }
+}
+
+impl From<MortonCode> for IVec2 {
+    fn from(code: MortonCode) -> Self {
+        code.decode()
+    }
+}
+
+/// Moves the bits of `value` to the even bit positions
+fn spread(value: u32) -> u64 {
+    let mut value = value as u64;
+    value = (value | value << 16) & 0x0000_ffff_0000_ffff;
+    value = (value | value << 8) & 0x00ff_00ff_00ff_00ff;
+    value = (value | value << 4) & 0x0f0f_0f0f_0f0f_0f0f;
+    value = (value | value << 2) & 0x3333_3333_3333_3333;
+    value = (value | value << 1) & X_BITS;
+    value
+}
+
+/// Moves the even bits of `value` together, the opposite of [spread]
+fn compact(value: u64) -> u32 {
+    let mut value = value & X_BITS;
+    value = (value | value >> 1) & 0x3333_3333_3333_3333;
+    value = (value | value >> 2) & 0x0f0f_0f0f_0f0f_0f0f;
+    value = (value | value >> 4) & 0x00ff_00ff_00ff_00ff;
+    value = (value | value >> 8) & 0x0000_ffff_0000_ffff;
+    value = (value | value >> 16) & 0x0000_0000_ffff_ffff;
+    value as u32
+}
+
+/// The smallest code above `code` inside the rect with the corner codes `min` and `max`, for a
+/// `code` between them but outside the rect. This is the BIGMIN of Tropf and Herzog's
+/// "Multidimensional Range Search in Dynamically Balanced Trees".
+fn big_min(code: u64, mut min: u64, mut max: u64) -> u64 {
+    let mut big_min = min;
+    for bit in (0..64).rev() {
+        let mask = 1 << bit;
+        let dimension = if mask & X_BITS != 0 { X_BITS } else { Y_BITS };
+        let below = dimension & (mask - 1);
+        // sets the bit, and clears the lower bits of the same coordinate
+        let load_min = |value: u64| (value | mask) & !below;
+        // clears the bit, and sets the lower bits of the same coordinate
+        let load_max = |value: u64| (value & !mask) | below;
+        match (code & mask != 0, min & mask != 0, max & mask != 0) {
+            (false, false, true) => {
+                big_min = load_min(min);
+                max = load_max(max);
+            }
+            (false, true, true) => return min,
+            (true, false, false) => return big_min,
+            (true, false, true) => min = load_min(min),
+            // equal bits, or min above max, which the caller rules out
+            _ => {}
+        }
+    }
+    big_min
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_decode() {
+        for &(x, y) in &[(0, 0), (1, 2), (-1, -1), (i32::MIN, i32::MAX), (-7, 300)] {
+            let position = IVec2::new(x, y);
+            assert_eq!(MortonCode::encode(position).decode(), position);
+        }
+        // the codes of a 2x2 square follow a Z
+        let codes = [(0, 0), (1, 0), (0, 1), (1, 1)]
+            .iter()
+            .map(|&position| MortonCode::encode(position.into()))
+            .collect::<Vec<_>>();
+        assert!(codes.windows(2).all(|pair| pair[1].0 == pair[0].0 + 1));
+        assert!(MortonCode::encode(IVec2::new(-1, 0)) < MortonCode::encode(IVec2::new(0, 0)));
+    }
+
+    #[test]
+    fn rect_queries() {
+        let rect = IRect::new(IVec2::new(-2, 1), IVec2::new(3, 4));
+        let mut codes = MortonCode::iter_rect(rect)
+            .map(MortonCode::decode)
+            .collect::<Vec<_>>();
+        codes.sort_by_key(|position| (position.y, position.x));
+        assert_eq!(codes, rect.iter().collect::<Vec<_>>());
+
+        let mut map = BTreeMap::new();
+        for position in IRect::new(IVec2::new(-8, -8), IVec2::new(8, 8)).iter() {
+            map.insert(MortonCode::encode(position), position);
+        }
+        let found = MortonCode::range(&map, rect)
+            .map(|(position, value)| {
+                assert_eq!(position, *value);
+                position
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(found.len(), rect.area());
+        assert!(found.iter().all(|position| rect.contains(*position)));
+        assert!(found
+            .windows(2)
+            .all(|pair| MortonCode::encode(pair[0]) < MortonCode::encode(pair[1])));
+    }
+}
@@ -0,0 +1,208 @@
+use crate::{
+    protocol::{Packet, Snapshot},
+    ClientTransport, ReplicationSettings, UdpClientTransport,
+};
+use bevy_ecs::{Commands, Component, Entity, Res, ResMut};
+use bevy_utils::{tracing::warn, HashMap, HashSet, Instant};
+use serde::de::DeserializeOwned;
+use std::{io, net::ToSocketAddrs};
+
+/// The id of the server entity a client entity is replicated from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NetworkId(pub u64);
+
+/// Receives replicated entities from a [ReplicationServer](crate::ReplicationServer). Insert this
+/// resource before adding [ReplicationPlugin](crate::ReplicationPlugin) to run the app as a client.
+pub struct ReplicationClient {
+    transport: Box<dyn ClientTransport>,
+    last_heartbeat: Option<Instant>,
+    tick: u64,
+    entities: HashMap<u64, ReplicatedEntity>,
+    full_sync: Option<FullSync>,
+    pub(crate) received: Vec<Snapshot>,
+}
+
+/// A local entity replicated from the server
+struct ReplicatedEntity {
+    entity: Entity,
+    /// The last tick a snapshot mentioned the entity in
+    last_seen: u64,
+}
+
+/// The parts of a full snapshot received so far
+struct FullSync {
+    tick: u64,
+    parts: u32,
+    received: HashSet<u32>,
+}
+
+impl ReplicationClient {
+    pub fn new(transport: impl ClientTransport) -> Self {
+        ReplicationClient {
+            transport: Box::new(transport),
+            last_heartbeat: None,
+            tick: 0,
+            entities: Default::default(),
+            full_sync: None,
+            received: Vec::new(),
+        }
+    }
+
+    /// Creates a client that connects to a server on the given UDP address
+    pub fn connect(server: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self::new(UdpClientTransport::connect(server)?))
+    }
+
+    /// The tick of the most recent snapshot a part of which was applied to the world. This is 0
+    /// until the first snapshot arrives.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Returns the local entity replicated from the given server entity
+    pub fn entity(&self, id: NetworkId) -> Option<Entity> {
+        self.entities.get(&id.0).map(|entity| entity.entity)
+    }
+}
+
+pub(crate) fn client_receive_system(
+    mut client: ResMut<ReplicationClient>,
+    settings: Res<ReplicationSettings>,
+) {
+    let now = Instant::now();
+    let heartbeat_due = match client.last_heartbeat {
+        Some(last_heartbeat) => now.duration_since(last_heartbeat) >= settings.heartbeat_interval,
+        None => true,
+    };
+    if heartbeat_due {
+        let bytes = Packet::Heartbeat.encode().unwrap();
+        match client.transport.send(&bytes) {
+            Ok(_) => client.last_heartbeat = Some(now),
+            Err(err) => warn!("Failed to send heartbeat: {}", err),
+        }
+    }
+
+    client.received.clear();
+    loop {
+        match client.transport.receive() {
+            Ok(Some(packet)) => match Packet::decode(&packet) {
+                Ok(Packet::Snapshot(snapshot)) => {
+                    // snapshots can arrive out of order, so drop any that are older than the
+                    // ones we already applied. Parts of the last one may still arrive.
+                    if snapshot.tick >= client.tick {
+                        client.received.push(snapshot);
+                    }
+                }
+                Ok(packet) => warn!("Unexpected packet from server: {:?}", packet),
+                Err(err) => warn!("Invalid packet from server: {}", err),
+            },
+            Ok(None) => break,
+            Err(err) => {
+                warn!("Failed to receive packet: {}", err);
+                break;
+            }
+        }
+    }
+
+    client
+        .received
+        .sort_by_key(|snapshot| (snapshot.tick, snapshot.part));
+    if let Some(snapshot) = client.received.last() {
+        client.tick = snapshot.tick;
+    }
+}
+
+/// Spawns and despawns entities as the received snapshots say. Entities are spawned as soon as a
+/// snapshot mentions them, so they are spawned even if the part that spawned them was lost. Once
+/// every part of a full snapshot arrived, the entities it didn't list are despawned, which recovers
+/// from despawns that were lost.
+pub(crate) fn client_sync_entities_system(
+    commands: &mut Commands,
+    mut client: ResMut<ReplicationClient>,
+) {
+    let ReplicationClient {
+        entities,
+        full_sync,
+        received,
+        ..
+    } = &mut *client;
+    for snapshot in received.iter() {
+        let tick = snapshot.tick;
+        for id in snapshot.despawned.iter() {
+            if let Some(entity) = entities.remove(id) {
+                commands.despawn(entity.entity);
+            }
+        }
+
+        let mentioned = snapshot
+            .spawned
+            .iter()
+            .chain(snapshot.entities.iter())
+            .chain(snapshot.components.iter().map(|update| &update.entity));
+        for id in mentioned {
+            let entity = entities.entry(*id).or_insert_with(|| ReplicatedEntity {
+                entity: commands.spawn_entity((NetworkId(*id),)).id(),
+                last_seen: tick,
+            });
+            entity.last_seen = entity.last_seen.max(tick);
+        }
+
+        if !snapshot.full {
+            continue;
+        }
+        if full_sync
+            .as_ref()
+            .map_or(true, |full_sync| full_sync.tick < tick)
+        {
+            *full_sync = Some(FullSync {
+                tick,
+                parts: snapshot.parts,
+                received: HashSet::default(),
+            });
+        }
+        let complete = match full_sync {
+            Some(full_sync) if full_sync.tick == tick => {
+                full_sync.received.insert(snapshot.part);
+                full_sync.received.len() == full_sync.parts as usize
+            }
+            _ => false,
+        };
+        if complete {
+            *full_sync = None;
+            // every entity that is still replicated was listed by a part of the full snapshot
+            entities.retain(|_, entity| {
+                let keep = entity.last_seen >= tick;
+                if !keep {
+                    commands.despawn(entity.entity);
+                }
+                keep
+            });
+        }
+    }
+}
+
+pub(crate) fn client_apply_component_system<T: Component + DeserializeOwned>(
+    commands: &mut Commands,
+    client: Res<ReplicationClient>,
+) {
+    let name = std::any::type_name::<T>();
+    for snapshot in client.received.iter() {
+        for update in snapshot.components.iter() {
+            if update.component != name {
+                continue;
+            }
+
+            // entities that were despawned by a later snapshot are skipped
+            let entity = match client.entities.get(&update.entity) {
+                Some(entity) => entity.entity,
+                None => continue,
+            };
+            match ron::from_str::<T>(&update.value) {
+                Ok(component) => {
+                    commands.insert_one(entity, component);
+                }
+                Err(err) => warn!("Failed to deserialize {}: {}", name, err),
+            }
+        }
+    }
+}
@@ -0,0 +1,358 @@
+mod client;
+mod protocol;
+mod server;
+mod transport;
+
+pub use client::*;
+pub use server::*;
+pub use transport::*;
+
+pub mod prelude {
+    pub use crate::{
+        AddReplicatedComponent, NetworkId, Replicated, ReplicationClient, ReplicationPlugin,
+        ReplicationServer, ReplicationSettings,
+    };
+}
+
+use bevy_app::prelude::*;
+use bevy_core::FixedTimestep;
+use bevy_ecs::{Component, IntoSystem, SystemStage};
+use serde::{de::DeserializeOwned, Serialize};
+use std::time::Duration;
+
+pub mod stage {
+    /// Name of the app stage where servers receive heartbeats and clients apply received snapshots.
+    /// Runs before UPDATE.
+    pub const NET_RECEIVE: &str = "net_receive";
+    /// Name of the app stage where servers collect replicated state. Runs after POST_UPDATE on the
+    /// replication fixed timestep.
+    pub const NET_REPLICATE: &str = "net_replicate";
+    /// Name of the app stage where servers send collected snapshots. Runs after NET_REPLICATE.
+    pub const NET_SEND: &str = "net_send";
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplicationSettings {
+    /// How many snapshots the server sends per second
+    pub ticks_per_second: f64,
+    /// Every `full_sync_interval` ticks, the server sends every replicated component instead of
+    /// only the changed ones, so clients recover from lost packets
+    pub full_sync_interval: u64,
+    /// How often clients tell the server they are still connected
+    pub heartbeat_interval: Duration,
+    /// How long the server waits for a heartbeat before dropping a client
+    pub client_timeout: Duration,
+    /// The largest packet the server sends. Snapshots are split into parts of at most this size,
+    /// which should fit in the MTU of the network to avoid IP fragmentation.
+    pub max_packet_size: usize,
+}
+
+impl Default for ReplicationSettings {
+    fn default() -> Self {
+        ReplicationSettings {
+            ticks_per_second: 20.0,
+            full_sync_interval: 20,
+            heartbeat_interval: Duration::from_secs(1),
+            client_timeout: Duration::from_secs(5),
+            max_packet_size: 1200,
+        }
+    }
+}
+
+/// Replicates entities from a server to its clients. The app runs as a server if a
+/// [ReplicationServer] resource is present, and as a client if a [ReplicationClient] resource is
+/// present.
+///
+/// Each server tick sends a snapshot containing the [Replicated] entities that were spawned or
+/// despawned and the registered components that changed since the last tick, split into packets
+/// of at most [ReplicationSettings::max_packet_size]. Every
+/// [full_sync_interval](ReplicationSettings::full_sync_interval) ticks, the snapshot lists every
+/// replicated entity and component instead. Clients spawn and despawn entities to match the
+/// snapshots, and tag them with their server [NetworkId].
+///
+/// Packets are sent over UDP by default. Other protocols can be used by implementing
+/// [ServerTransport] and [ClientTransport].
+#[derive(Default)]
+pub struct ReplicationPlugin;
+
+impl Plugin for ReplicationPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<ReplicationSettings>();
+        let settings = app.resources().get_cloned::<ReplicationSettings>().unwrap();
+        app.add_stage_before(
+            bevy_app::stage::UPDATE,
+            stage::NET_RECEIVE,
+            SystemStage::parallel(),
+        )
+        .add_stage_after(
+            bevy_app::stage::POST_UPDATE,
+            stage::NET_REPLICATE,
+            SystemStage::parallel()
+                .with_run_criteria(FixedTimestep::steps_per_second(settings.ticks_per_second)),
+        )
+        .add_stage_after(
+            stage::NET_REPLICATE,
+            stage::NET_SEND,
+            SystemStage::parallel(),
+        );
+
+        if app.resources().contains::<ReplicationServer>() {
+            app.add_system_to_stage(stage::NET_RECEIVE, server_receive_system.system())
+                .add_system_to_stage(stage::NET_REPLICATE, server_begin_tick_system.system())
+                .add_system_to_stage(stage::NET_SEND, server_send_system.system());
+        }
+
+        if app.resources().contains::<ReplicationClient>() {
+            app.add_system_to_stage(stage::NET_RECEIVE, client_receive_system.system())
+                .add_system_to_stage(stage::NET_RECEIVE, client_sync_entities_system.system());
+        }
+    }
+}
+
+pub trait AddReplicatedComponent {
+    /// Replicates `T` components of [Replicated] entities. Components are matched between server
+    /// and client by type name, so both sides must be built from the same code. This must be
+    /// called after adding [ReplicationPlugin].
+    fn add_replicated_component<T>(&mut self) -> &mut Self
+    where
+        T: Component + Serialize + DeserializeOwned;
+}
+
+impl AddReplicatedComponent for AppBuilder {
+    fn add_replicated_component<T>(&mut self) -> &mut Self
+    where
+        T: Component + Serialize + DeserializeOwned,
+    {
+        if self.resources().contains::<ReplicationServer>() {
+            self.add_system_to_stage(
+                stage::NET_REPLICATE,
+                server_collect_component_system::<T>.system(),
+            );
+        }
+
+        if self.resources().contains::<ReplicationClient>() {
+            self.add_system_to_stage(
+                stage::NET_RECEIVE,
+                client_apply_component_system::<T>.system(),
+            );
+        }
+
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::{Entity, Resources, Stage, World};
+    use serde::Deserialize;
+    use std::{
+        collections::VecDeque,
+        io,
+        sync::{Arc, Mutex},
+    };
+
+    #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+    struct Position(i32, i32);
+
+    /// Packets sent from one side of a [MemoryTransport] to the other
+    type Queue = Arc<Mutex<VecDeque<Vec<u8>>>>;
+
+    /// Delivers every packet in order on the next receive, so tests don't depend on timing
+    struct MemoryTransport {
+        incoming: Queue,
+        outgoing: Queue,
+    }
+
+    impl MemoryTransport {
+        fn pair() -> (Self, Self) {
+            let (a, b) = (Queue::default(), Queue::default());
+            (
+                MemoryTransport {
+                    incoming: a.clone(),
+                    outgoing: b.clone(),
+                },
+                MemoryTransport {
+                    incoming: b,
+                    outgoing: a,
+                },
+            )
+        }
+    }
+
+    impl ServerTransport for MemoryTransport {
+        fn send(&mut self, _client: ClientId, packet: &[u8]) -> io::Result<()> {
+            ClientTransport::send(self, packet)
+        }
+
+        fn receive(&mut self) -> io::Result<Option<(ClientId, Vec<u8>)>> {
+            let packet = ClientTransport::receive(self)?;
+            Ok(packet.map(|packet| (ClientId(0), packet)))
+        }
+
+        fn disconnect(&mut self, _client: ClientId) {}
+
+        fn max_packet_size(&self) -> usize {
+            usize::MAX
+        }
+    }
+
+    impl ClientTransport for MemoryTransport {
+        fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+            self.outgoing.lock().unwrap().push_back(packet.to_vec());
+            Ok(())
+        }
+
+        fn receive(&mut self) -> io::Result<Option<Vec<u8>>> {
+            Ok(self.incoming.lock().unwrap().pop_front())
+        }
+
+        fn max_packet_size(&self) -> usize {
+            usize::MAX
+        }
+    }
+
+    /// Runs the systems the plugin adds, in order, without waiting for the replication timestep
+    struct Peer {
+        world: World,
+        resources: Resources,
+        stage: SystemStage,
+    }
+
+    impl Peer {
+        fn new(resource: impl Component, stage: SystemStage) -> Self {
+            let mut resources = Resources::default();
+            resources.insert(resource);
+            resources.insert(ReplicationSettings::default());
+            Peer {
+                world: World::default(),
+                resources,
+                stage,
+            }
+        }
+
+        fn server(transport: MemoryTransport) -> Self {
+            let mut stage = SystemStage::serial();
+            stage
+                .add_system(server_receive_system.system())
+                .add_system(server_begin_tick_system.system())
+                .add_system(server_collect_component_system::<Position>.system())
+                .add_system(server_send_system.system());
+            Peer::new(ReplicationServer::new(transport), stage)
+        }
+
+        fn client(transport: MemoryTransport) -> Self {
+            let mut stage = SystemStage::serial();
+            stage
+                .add_system(client_receive_system.system())
+                .add_system(client_sync_entities_system.system())
+                .add_system(client_apply_component_system::<Position>.system());
+            Peer::new(ReplicationClient::new(transport), stage)
+        }
+
+        fn update(&mut self) {
+            // systems are initialized here rather than in `new`, as commands point into the world
+            // they were initialized with, which moves when the peer is returned
+            self.stage.initialize(&mut self.world, &mut self.resources);
+            self.stage.run(&mut self.world, &mut self.resources);
+        }
+
+        fn positions(&mut self) -> Vec<(NetworkId, Position)> {
+            self.world
+                .query::<(&NetworkId, &Position)>()
+                .map(|(id, position)| (*id, *position))
+                .collect()
+        }
+    }
+
+    /// Sends one snapshot from the server to the client
+    fn tick(server: &mut Peer, client: &mut Peer) {
+        server.update();
+        client.update();
+    }
+
+    #[test]
+    fn replicate() {
+        let (server_transport, client_transport) = MemoryTransport::pair();
+        let mut server = Peer::server(server_transport);
+        let mut client = Peer::client(client_transport);
+        // connects by sending a heartbeat
+        client.update();
+
+        let entity = server.world.spawn((Replicated, Position(1, 2)));
+        server.world.spawn((Position(3, 4),));
+        let id = NetworkId(entity.to_bits());
+        tick(&mut server, &mut client);
+        assert_eq!(client.positions(), vec![(id, Position(1, 2))]);
+
+        *server.world.get_mut::<Position>(entity).unwrap() = Position(5, 6);
+        tick(&mut server, &mut client);
+        assert_eq!(client.positions(), vec![(id, Position(5, 6))]);
+
+        // entities that stop being replicated are sent again in full when they come back
+        server.world.remove_one::<Replicated>(entity).unwrap();
+        tick(&mut server, &mut client);
+        assert_eq!(client.world.query::<Entity>().count(), 0);
+        server.world.insert_one(entity, Replicated).unwrap();
+        tick(&mut server, &mut client);
+        assert_eq!(client.positions(), vec![(id, Position(5, 6))]);
+
+        server.world.despawn(entity).unwrap();
+        tick(&mut server, &mut client);
+        assert_eq!(client.world.query::<Entity>().count(), 0);
+        assert_eq!(
+            client.resources.get::<ReplicationClient>().unwrap().tick(),
+            5
+        );
+    }
+
+    #[test]
+    fn replicate_in_parts() {
+        let (server_transport, client_transport) = MemoryTransport::pair();
+        let to_client = client_transport.incoming.clone();
+        let mut server = Peer::server(server_transport);
+        server.resources.insert(ReplicationSettings {
+            max_packet_size: 256,
+            full_sync_interval: 4,
+            ..Default::default()
+        });
+        let mut client = Peer::client(client_transport);
+        client.update();
+
+        let entities = (0..100)
+            .map(|i| server.world.spawn((Replicated, Position(i, -i))))
+            .collect::<Vec<_>>();
+        server.update();
+        // the snapshot is split into packets that all fit
+        let sizes = to_client
+            .lock()
+            .unwrap()
+            .iter()
+            .map(Vec::len)
+            .collect::<Vec<_>>();
+        assert!(sizes.len() > 1);
+        assert!(sizes.iter().all(|size| *size <= 256));
+        client.update();
+        assert_eq!(client.positions().len(), 100);
+
+        // despawns that were lost are caught up with on the next full snapshot
+        for entity in entities[..50].iter() {
+            server.world.despawn(*entity).unwrap();
+        }
+        server.update();
+        to_client.lock().unwrap().clear();
+        client.update();
+        for _ in 0..2 {
+            tick(&mut server, &mut client);
+            assert_eq!(client.positions().len(), 100);
+        }
+        tick(&mut server, &mut client);
+        let mut positions = client
+            .positions()
+            .into_iter()
+            .map(|(_, position)| position.0)
+            .collect::<Vec<_>>();
+        positions.sort_unstable();
+        assert_eq!(positions, (50..100).collect::<Vec<_>>());
+    }
+}
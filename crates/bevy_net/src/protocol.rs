@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) enum Packet {
+    /// Sent by clients to connect, and periodically to stay connected
+    Heartbeat,
+    Snapshot(Snapshot),
+}
+
+/// The replicated state of the server after a replication tick. Snapshots are sent split into
+/// parts that each fit in a packet and can be applied on their own, so a lost packet only loses
+/// the part it carried.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct Snapshot {
+    pub tick: u64,
+    /// The index of this part among the parts of the snapshot of this tick
+    pub part: u32,
+    /// The number of parts the snapshot of this tick was split into
+    pub parts: u32,
+    /// Whether the parts of this tick list every replicated entity in `entities` and contain every
+    /// replicated component, rather than only the ones that changed since the last snapshot
+    pub full: bool,
+    /// The network ids of the entities that started being replicated since the last snapshot
+    pub spawned: Vec<u64>,
+    /// The network ids of the entities that stopped being replicated since the last snapshot
+    pub despawned: Vec<u64>,
+    /// The network ids of every replicated entity, in full snapshots only
+    pub entities: Vec<u64>,
+    pub components: Vec<ComponentUpdate>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct ComponentUpdate {
+    pub entity: u64,
+    pub component: String,
+    /// The component value, serialized as RON
+    pub value: String,
+}
+
+impl Packet {
+    pub fn encode(&self) -> Result<Vec<u8>, ron::Error> {
+        ron::to_string(self).map(String::into_bytes)
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, ron::Error> {
+        ron::de::from_bytes(bytes)
+    }
+}
+
+impl Snapshot {
+    /// Splits the snapshot into parts that encode to packets of at most `max_packet_size` bytes.
+    /// Component updates that don't fit in a packet on their own are left out and returned.
+    pub fn split(self, max_packet_size: usize) -> (Vec<Snapshot>, Vec<ComponentUpdate>) {
+        let mut parts = SnapshotParts::new(self.tick, self.full, max_packet_size);
+        for id in self.spawned {
+            if let Some(part) = parts.reserve(id_size(id)) {
+                part.spawned.push(id);
+            }
+        }
+        for id in self.despawned {
+            if let Some(part) = parts.reserve(id_size(id)) {
+                part.despawned.push(id);
+            }
+        }
+        for id in self.entities {
+            if let Some(part) = parts.reserve(id_size(id)) {
+                part.entities.push(id);
+            }
+        }
+        let mut too_large = Vec::new();
+        for update in self.components {
+            // the separator after the update is counted too
+            let size = ron::to_string(&update).map_or(usize::MAX, |value| value.len() + 1);
+            match parts.reserve(size) {
+                Some(part) => part.components.push(update),
+                None => too_large.push(update),
+            }
+        }
+        (parts.finish(), too_large)
+    }
+}
+
+/// The size of an encoded network id, and the separator after it
+fn id_size(id: u64) -> usize {
+    id.to_string().len() + 1
+}
+
+/// The parts a [Snapshot] is split into, filled one after the other
+struct SnapshotParts {
+    tick: u64,
+    full: bool,
+    parts: Vec<Snapshot>,
+    /// The bytes left for lists in a part, after the fields every part has
+    budget: usize,
+    /// The bytes of lists in the last part
+    size: usize,
+}
+
+impl SnapshotParts {
+    fn new(tick: u64, full: bool, max_packet_size: usize) -> Self {
+        // the fields every part has, at their largest
+        let header = Packet::Snapshot(Snapshot {
+            tick: u64::MAX,
+            part: u32::MAX,
+            parts: u32::MAX,
+            full: false,
+            ..Default::default()
+        })
+        .encode()
+        .map_or(0, |bytes| bytes.len());
+        SnapshotParts {
+            tick,
+            full,
+            parts: vec![Snapshot {
+                tick,
+                full,
+                ..Default::default()
+            }],
+            budget: max_packet_size.saturating_sub(header),
+            size: 0,
+        }
+    }
+
+    /// The part with room for an item of `size` bytes, or `None` if the item doesn't fit in any
+    fn reserve(&mut self, size: usize) -> Option<&mut Snapshot> {
+        if size > self.budget {
+            return None;
+        }
+        if self.size + size > self.budget {
+            self.parts.push(Snapshot {
+                tick: self.tick,
+                full: self.full,
+                ..Default::default()
+            });
+            self.size = 0;
+        }
+        self.size += size;
+        self.parts.last_mut()
+    }
+
+    fn finish(mut self) -> Vec<Snapshot> {
+        let count = self.parts.len() as u32;
+        for (index, part) in self.parts.iter_mut().enumerate() {
+            part.part = index as u32;
+            part.parts = count;
+        }
+        self.parts
+    }
+}
@@ -0,0 +1,218 @@
+use crate::{
+    protocol::{ComponentUpdate, Packet, Snapshot},
+    ClientId, ReplicationSettings, ServerTransport, UdpServerTransport,
+};
+use bevy_ecs::{Component, Entity, Local, Query, Res, ResMut, With};
+use bevy_utils::{tracing::warn, HashMap, HashSet, Instant};
+use serde::Serialize;
+use std::{io, net::ToSocketAddrs};
+
+/// Marks an entity for replication to connected clients. Only components registered with
+/// [AddReplicatedComponent](crate::AddReplicatedComponent) are sent.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Replicated;
+
+/// Sends the state of [Replicated] entities to connected clients. Insert this resource before
+/// adding [ReplicationPlugin](crate::ReplicationPlugin) to run the app as a server.
+pub struct ReplicationServer {
+    transport: Box<dyn ServerTransport>,
+    clients: HashMap<ClientId, Instant>,
+    tick: u64,
+    ticks_since_full_sync: u64,
+    needs_full_sync: bool,
+    /// The entities that were replicated on the last tick
+    replicated: HashSet<u64>,
+    pub(crate) snapshot: Option<Snapshot>,
+}
+
+impl ReplicationServer {
+    pub fn new(transport: impl ServerTransport) -> Self {
+        ReplicationServer {
+            transport: Box::new(transport),
+            clients: Default::default(),
+            tick: 0,
+            ticks_since_full_sync: 0,
+            needs_full_sync: false,
+            replicated: Default::default(),
+            snapshot: None,
+        }
+    }
+
+    /// Creates a server that listens for clients on the given UDP address
+    pub fn bind(address: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self::new(UdpServerTransport::bind(address)?))
+    }
+
+    /// The currently connected clients
+    pub fn clients(&self) -> impl Iterator<Item = &ClientId> {
+        self.clients.keys()
+    }
+
+    /// The number of replication ticks that have run
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+}
+
+pub(crate) fn server_receive_system(
+    mut server: ResMut<ReplicationServer>,
+    settings: Res<ReplicationSettings>,
+) {
+    let server = &mut *server;
+    let now = Instant::now();
+    loop {
+        match server.transport.receive() {
+            Ok(Some((client, packet))) => match Packet::decode(&packet) {
+                Ok(Packet::Heartbeat) => {
+                    if server.clients.insert(client, now).is_none() {
+                        server.needs_full_sync = true;
+                    }
+                }
+                Ok(packet) => warn!("Unexpected packet from client {:?}: {:?}", client, packet),
+                Err(err) => warn!("Invalid packet from client {:?}: {}", client, err),
+            },
+            Ok(None) => break,
+            Err(err) => {
+                warn!("Failed to receive packet: {}", err);
+                break;
+            }
+        }
+    }
+
+    let timeout = settings.client_timeout;
+    let transport = &mut server.transport;
+    server.clients.retain(|client, last_seen| {
+        let connected = now.duration_since(*last_seen) < timeout;
+        if !connected {
+            transport.disconnect(*client);
+        }
+        connected
+    });
+}
+
+pub(crate) fn server_begin_tick_system(
+    mut server: ResMut<ReplicationServer>,
+    settings: Res<ReplicationSettings>,
+    query: Query<Entity, With<Replicated>>,
+) {
+    server.tick += 1;
+    server.ticks_since_full_sync += 1;
+    let full =
+        server.needs_full_sync || server.ticks_since_full_sync >= settings.full_sync_interval;
+    if full {
+        server.needs_full_sync = false;
+        server.ticks_since_full_sync = 0;
+    }
+
+    // only the entities that started or stopped being replicated are sent, except in full
+    // snapshots, which list every entity so clients recover from lost packets
+    let replicated = query.iter().map(Entity::to_bits).collect::<HashSet<_>>();
+    let spawned = replicated.difference(&server.replicated).copied().collect();
+    let despawned = server.replicated.difference(&replicated).copied().collect();
+    let entities = if full {
+        replicated.iter().copied().collect()
+    } else {
+        Vec::new()
+    };
+    server.replicated = replicated;
+    server.snapshot = Some(Snapshot {
+        tick: server.tick,
+        full,
+        spawned,
+        despawned,
+        entities,
+        ..Default::default()
+    });
+}
+
+/// Adds the `T` components that changed since they were last sent to the current snapshot. Changes
+/// are detected by comparing serialized values, so they are picked up even when they happen on
+/// frames without a replication tick.
+///
+/// Entities that were despawned or stopped being replicated are forgotten, so their component is
+/// sent again if they are replicated again.
+pub(crate) fn server_collect_component_system<T: Component + Serialize>(
+    mut server: ResMut<ReplicationServer>,
+    mut sent: Local<HashMap<u64, String>>,
+    query: Query<(Entity, &T), With<Replicated>>,
+) {
+    let snapshot = match server.snapshot.as_mut() {
+        Some(snapshot) => snapshot,
+        None => return,
+    };
+
+    if snapshot.full {
+        sent.clear();
+    }
+
+    let mut replicated = HashSet::default();
+    for (entity, component) in query.iter() {
+        let entity = entity.to_bits();
+        replicated.insert(entity);
+        let value = match ron::to_string(component) {
+            Ok(value) => value,
+            Err(err) => {
+                warn!(
+                    "Failed to serialize {}: {}",
+                    std::any::type_name::<T>(),
+                    err
+                );
+                continue;
+            }
+        };
+
+        if sent.get(&entity) != Some(&value) {
+            snapshot.components.push(ComponentUpdate {
+                entity,
+                component: std::any::type_name::<T>().to_string(),
+                value: value.clone(),
+            });
+            sent.insert(entity, value);
+        }
+    }
+    sent.retain(|entity, _| replicated.contains(entity));
+}
+
+/// Sends the snapshot of the current tick, split into parts that fit in
+/// [ReplicationSettings::max_packet_size]
+pub(crate) fn server_send_system(
+    mut server: ResMut<ReplicationServer>,
+    settings: Res<ReplicationSettings>,
+) {
+    let snapshot = match server.snapshot.take() {
+        Some(snapshot) => snapshot,
+        None => return,
+    };
+
+    let tick = snapshot.tick;
+    let max_packet_size = settings
+        .max_packet_size
+        .min(server.transport.max_packet_size());
+    let (parts, too_large) = snapshot.split(max_packet_size);
+    for update in too_large {
+        warn!(
+            "{} of entity {} is too large to send in a packet of {} bytes",
+            update.component, update.entity, max_packet_size
+        );
+    }
+
+    let server = &mut *server;
+    for part in parts {
+        let index = part.part;
+        let bytes = match Packet::Snapshot(part).encode() {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                warn!(
+                    "Failed to encode part {} of snapshot {}: {}",
+                    index, tick, err
+                );
+                continue;
+            }
+        };
+        for client in server.clients.keys() {
+            if let Err(err) = server.transport.send(*client, &bytes) {
+                warn!("Failed to send snapshot to client {:?}: {}", client, err);
+            }
+        }
+    }
+}
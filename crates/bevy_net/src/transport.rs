@@ -0,0 +1,152 @@
+use bevy_utils::HashMap;
+use std::{
+    io,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+};
+
+/// Identifies a client connected to a [ServerTransport]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ClientId(pub u64);
+
+/// The server side of a packet transport. Packets may be dropped or arrive out of order, so
+/// transports do not need to be reliable. All methods must be non-blocking.
+pub trait ServerTransport: Send + Sync + 'static {
+    /// Sends a packet to a client
+    fn send(&mut self, client: ClientId, packet: &[u8]) -> io::Result<()>;
+    /// Returns the next received packet and the client that sent it, or `None` if no packets
+    /// are waiting
+    fn receive(&mut self) -> io::Result<Option<(ClientId, Vec<u8>)>>;
+    /// Forgets a client that timed out. Later packets from the same client may be assigned a new
+    /// [ClientId].
+    fn disconnect(&mut self, client: ClientId);
+    /// The largest packet that can be sent in one call to [ServerTransport::send]
+    fn max_packet_size(&self) -> usize;
+}
+
+/// The client side of a packet transport, connected to a single server. Like [ServerTransport],
+/// this does not need to be reliable and must be non-blocking.
+pub trait ClientTransport: Send + Sync + 'static {
+    /// Sends a packet to the server
+    fn send(&mut self, packet: &[u8]) -> io::Result<()>;
+    /// Returns the next packet received from the server, or `None` if no packets are waiting
+    fn receive(&mut self) -> io::Result<Option<Vec<u8>>>;
+    /// The largest packet that can be sent in one call to [ClientTransport::send]
+    fn max_packet_size(&self) -> usize;
+}
+
+/// The largest payload that fits in a single UDP datagram
+const MAX_UDP_PACKET_SIZE: usize = 65507;
+
+/// A [ServerTransport] that sends each packet as a UDP datagram
+#[derive(Debug)]
+pub struct UdpServerTransport {
+    socket: UdpSocket,
+    clients: HashMap<SocketAddr, ClientId>,
+    addresses: HashMap<ClientId, SocketAddr>,
+    next_client_id: u64,
+    buffer: Vec<u8>,
+}
+
+impl UdpServerTransport {
+    pub fn bind(address: impl ToSocketAddrs) -> io::Result<Self> {
+        let socket = UdpSocket::bind(address)?;
+        socket.set_nonblocking(true)?;
+        Ok(UdpServerTransport {
+            socket,
+            clients: Default::default(),
+            addresses: Default::default(),
+            next_client_id: 0,
+            buffer: vec![0; MAX_UDP_PACKET_SIZE],
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Returns the address of a connected client
+    pub fn client_addr(&self, client: ClientId) -> Option<SocketAddr> {
+        self.addresses.get(&client).copied()
+    }
+}
+
+impl ServerTransport for UdpServerTransport {
+    fn send(&mut self, client: ClientId, packet: &[u8]) -> io::Result<()> {
+        let address = self
+            .addresses
+            .get(&client)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotConnected, "unknown client"))?;
+        self.socket.send_to(packet, address)?;
+        Ok(())
+    }
+
+    fn receive(&mut self) -> io::Result<Option<(ClientId, Vec<u8>)>> {
+        let (len, address) = match self.socket.recv_from(&mut self.buffer) {
+            Ok(received) => received,
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(None),
+            Err(err) => return Err(err),
+        };
+
+        let next_client_id = &mut self.next_client_id;
+        let addresses = &mut self.addresses;
+        let client = *self.clients.entry(address).or_insert_with(|| {
+            let client = ClientId(*next_client_id);
+            *next_client_id += 1;
+            addresses.insert(client, address);
+            client
+        });
+        Ok(Some((client, self.buffer[..len].to_vec())))
+    }
+
+    fn disconnect(&mut self, client: ClientId) {
+        if let Some(address) = self.addresses.remove(&client) {
+            self.clients.remove(&address);
+        }
+    }
+
+    fn max_packet_size(&self) -> usize {
+        MAX_UDP_PACKET_SIZE
+    }
+}
+
+/// A [ClientTransport] that sends each packet as a UDP datagram
+#[derive(Debug)]
+pub struct UdpClientTransport {
+    socket: UdpSocket,
+    buffer: Vec<u8>,
+}
+
+impl UdpClientTransport {
+    pub fn connect(server: impl ToSocketAddrs) -> io::Result<Self> {
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        socket.connect(server)?;
+        socket.set_nonblocking(true)?;
+        Ok(UdpClientTransport {
+            socket,
+            buffer: vec![0; MAX_UDP_PACKET_SIZE],
+        })
+    }
+
+    pub fn server_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.peer_addr()
+    }
+}
+
+impl ClientTransport for UdpClientTransport {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+        self.socket.send(packet)?;
+        Ok(())
+    }
+
+    fn receive(&mut self) -> io::Result<Option<Vec<u8>>> {
+        match self.socket.recv(&mut self.buffer) {
+            Ok(len) => Ok(Some(self.buffer[..len].to_vec())),
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn max_packet_size(&self) -> usize {
+        MAX_UDP_PACKET_SIZE
+    }
+}
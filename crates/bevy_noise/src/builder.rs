@@ -0,0 +1,59 @@
+use crate::{permutation::Lattice, Perlin, Simplex, Worley};
+
+/// Configures and builds the [Perlin], [Simplex] and [Worley] noise functions, so they share
+/// their seed and tiling options.
+///
+/// ```
+/// # use bevy_noise::{NoiseBuilder, NoiseFn2d};
+/// // a 64x64 texture that wraps around seamlessly
+/// let noise = NoiseBuilder::new(42).with_period(8, 8).simplex();
+/// let texture: Vec<f32> = (0..64 * 64)
+///     .map(|i| noise.get((i % 64) as f32 / 8.0, (i / 64) as f32 / 8.0))
+///     .collect();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NoiseBuilder {
+    /// Noise functions built with the same seed return the same values
+    pub seed: u64,
+    /// How many units the noise repeats after horizontally and vertically, if it tiles
+    pub period: Option<(u32, u32)>,
+}
+
+impl NoiseBuilder {
+    pub fn new(seed: u64) -> Self {
+        NoiseBuilder { seed, period: None }
+    }
+
+    /// Makes the noise repeat every `x` units horizontally and `y` units vertically, which is
+    /// useful for seamless textures and wrapping worlds. [Simplex] noise needs an even `y`.
+    pub fn with_period(mut self, x: u32, y: u32) -> Self {
+        assert!(x > 0 && y > 0, "noise period must be greater than zero");
+        self.period = Some((x, y));
+        self
+    }
+
+    pub fn perlin(&self) -> Perlin {
+        Perlin::from_lattice(self.lattice())
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the noise tiles with an odd vertical period
+    pub fn simplex(&self) -> Simplex {
+        if let Some((_, y)) = self.period {
+            assert!(
+                y % 2 == 0,
+                "simplex noise can only tile with an even vertical period"
+            );
+        }
+        Simplex::from_lattice(self.lattice())
+    }
+
+    pub fn worley(&self) -> Worley {
+        Worley::from_lattice(self.lattice())
+    }
+
+    fn lattice(&self) -> Lattice {
+        Lattice::new(self.seed, self.period)
+    }
+}
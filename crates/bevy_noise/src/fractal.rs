@@ -0,0 +1,150 @@
+use crate::NoiseFn2d;
+
+/// Fractal brownian motion: sums several octaves of a noise function at increasing frequencies
+/// and decreasing amplitudes. The result is normalized, so it has the same range as the source.
+///
+/// If the source noise tiles, the result tiles with the same period as long as `frequency` and
+/// `lacunarity` are whole numbers.
+#[derive(Debug, Clone)]
+pub struct Fbm<N> {
+    pub source: N,
+    /// The number of octaves to sum
+    pub octaves: u32,
+    /// The frequency of the first octave
+    pub frequency: f32,
+    /// How much the frequency is multiplied by for each octave
+    pub lacunarity: f32,
+    /// How much the amplitude is multiplied by for each octave
+    pub persistence: f32,
+}
+
+impl<N: NoiseFn2d> Fbm<N> {
+    pub fn new(source: N) -> Self {
+        Fbm {
+            source,
+            octaves: 4,
+            frequency: 1.0,
+            lacunarity: 2.0,
+            persistence: 0.5,
+        }
+    }
+
+    pub fn with_octaves(mut self, octaves: u32) -> Self {
+        self.octaves = octaves;
+        self
+    }
+
+    pub fn with_frequency(mut self, frequency: f32) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    pub fn with_lacunarity(mut self, lacunarity: f32) -> Self {
+        self.lacunarity = lacunarity;
+        self
+    }
+
+    pub fn with_persistence(mut self, persistence: f32) -> Self {
+        self.persistence = persistence;
+        self
+    }
+}
+
+impl<N: NoiseFn2d> NoiseFn2d for Fbm<N> {
+    fn get(&self, x: f32, y: f32) -> f32 {
+        sum_octaves(
+            self.octaves,
+            self.frequency,
+            self.lacunarity,
+            self.persistence,
+            |frequency| self.source.get(x * frequency, y * frequency),
+        )
+    }
+}
+
+/// Ridged multifractal noise: like [Fbm], but each octave is folded around zero, which produces
+/// sharp ridges that work well for mountains and rivers. Values are in the range `[0.0, 1.0]`
+/// when the source is in the range `[-1.0, 1.0]`.
+#[derive(Debug, Clone)]
+pub struct Ridged<N> {
+    pub source: N,
+    /// The number of octaves to sum
+    pub octaves: u32,
+    /// The frequency of the first octave
+    pub frequency: f32,
+    /// How much the frequency is multiplied by for each octave
+    pub lacunarity: f32,
+    /// How much the amplitude is multiplied by for each octave
+    pub persistence: f32,
+}
+
+impl<N: NoiseFn2d> Ridged<N> {
+    pub fn new(source: N) -> Self {
+        Ridged {
+            source,
+            octaves: 4,
+            frequency: 1.0,
+            lacunarity: 2.0,
+            persistence: 0.5,
+        }
+    }
+
+    pub fn with_octaves(mut self, octaves: u32) -> Self {
+        self.octaves = octaves;
+        self
+    }
+
+    pub fn with_frequency(mut self, frequency: f32) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    pub fn with_lacunarity(mut self, lacunarity: f32) -> Self {
+        self.lacunarity = lacunarity;
+        self
+    }
+
+    pub fn with_persistence(mut self, persistence: f32) -> Self {
+        self.persistence = persistence;
+        self
+    }
+}
+
+impl<N: NoiseFn2d> NoiseFn2d for Ridged<N> {
+    fn get(&self, x: f32, y: f32) -> f32 {
+        sum_octaves(
+            self.octaves,
+            self.frequency,
+            self.lacunarity,
+            self.persistence,
+            |frequency| {
+                let ridge = 1.0 - self.source.get(x * frequency, y * frequency).abs();
+                ridge * ridge
+            },
+        )
+    }
+}
+
+fn sum_octaves(
+    octaves: u32,
+    mut frequency: f32,
+    lacunarity: f32,
+    persistence: f32,
+    mut octave: impl FnMut(f32) -> f32,
+) -> f32 {
+    let mut sum = 0.0;
+    let mut amplitude = 1.0;
+    let mut total_amplitude = 0.0;
+    for _ in 0..octaves {
+        sum += octave(frequency) * amplitude;
+        total_amplitude += amplitude;
+        frequency *= lacunarity;
+        amplitude *= persistence;
+    }
+
+    if total_amplitude > 0.0 {
+        sum / total_amplitude
+    } else {
+        0.0
+    }
+}
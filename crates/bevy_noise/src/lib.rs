@@ -0,0 +1,89 @@
+mod builder;
+mod fractal;
+mod perlin;
+mod permutation;
+mod simplex;
+mod worley;
+
+pub use builder::*;
+pub use fractal::*;
+pub use perlin::*;
+pub use simplex::*;
+pub use worley::*;
+
+pub mod prelude {
+    pub use crate::{Fbm, NoiseBuilder, NoiseFn2d, Perlin, Ridged, Simplex, Worley};
+}
+
+/// A 2d noise function. Implementations are deterministic: sampling the same point on functions
+/// created with the same seed always returns the same value.
+pub trait NoiseFn2d {
+    /// Samples the noise at the given point. Unless documented otherwise, values are roughly in
+    /// the range `[-1.0, 1.0]`.
+    fn get(&self, x: f32, y: f32) -> f32;
+}
+
+impl<F> NoiseFn2d for F
+where
+    F: Fn(f32, f32) -> f32,
+{
+    fn get(&self, x: f32, y: f32) -> f32 {
+        self(x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(noise: &impl NoiseFn2d) -> Vec<f32> {
+        (0..64)
+            .map(|i| noise.get(i as f32 * 0.37 - 5.0, i as f32 * 0.71 + 2.0))
+            .collect()
+    }
+
+    #[test]
+    fn seeded() {
+        assert_eq!(sample(&Perlin::new(7)), sample(&Perlin::new(7)));
+        assert_ne!(sample(&Perlin::new(7)), sample(&Perlin::new(8)));
+        assert_eq!(sample(&Simplex::new(7)), sample(&Simplex::new(7)));
+        assert_ne!(sample(&Simplex::new(7)), sample(&Simplex::new(8)));
+        assert_eq!(sample(&Worley::new(7)), sample(&Worley::new(7)));
+        assert_ne!(sample(&Worley::new(7)), sample(&Worley::new(8)));
+    }
+
+    #[test]
+    fn range() {
+        let perlin = Perlin::new(1);
+        let simplex = Simplex::new(1);
+        let fbm = Fbm::new(Perlin::new(1));
+        let ridged = Ridged::new(Simplex::new(1));
+        for x in -50..50 {
+            for y in -50..50 {
+                let (x, y) = (x as f32 * 0.13, y as f32 * 0.17);
+                for value in [perlin.get(x, y), simplex.get(x, y), fbm.get(x, y)].iter() {
+                    assert!((-1.0..=1.0).contains(value), "{} out of range", value);
+                }
+                let ridged = ridged.get(x, y);
+                assert!((0.0..=1.0).contains(&ridged), "{} out of range", ridged);
+            }
+        }
+    }
+
+    #[test]
+    fn tiling() {
+        let builder = NoiseBuilder::new(3).with_period(4, 8);
+        let perlin = builder.perlin();
+        let simplex = builder.simplex();
+        let worley = builder.worley();
+        let fbm = Fbm::new(builder.perlin());
+        for i in 0..32 {
+            let (x, y) = (i as f32 * 0.29, i as f32 * 0.53);
+            assert!((perlin.get(x, y) - perlin.get(x + 4.0, y - 8.0)).abs() < 1e-4);
+            assert!((simplex.get(x, y) - simplex.get(x - 4.0, y + 8.0)).abs() < 1e-4);
+            assert!((simplex.get(x, y) - simplex.get(x + 8.0, y)).abs() < 1e-4);
+            assert!((worley.get(x, y) - worley.get(x - 4.0, y + 16.0)).abs() < 1e-4);
+            assert!((fbm.get(x, y) - fbm.get(x + 8.0, y + 8.0)).abs() < 1e-4);
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use crate::{
+    permutation::{gradient, Lattice},
+    NoiseBuilder, NoiseFn2d,
+};
+
+/// Seeded 2d gradient ("improved Perlin") noise. Values are in the range `[-1.0, 1.0]` and are
+/// zero at every integer coordinate.
+///
+/// Use a [NoiseBuilder] to make the noise tile.
+#[derive(Debug, Clone)]
+pub struct Perlin {
+    lattice: Lattice,
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        NoiseBuilder::new(seed).perlin()
+    }
+
+    pub(crate) fn from_lattice(lattice: Lattice) -> Self {
+        Perlin { lattice }
+    }
+}
+
+impl NoiseFn2d for Perlin {
+    fn get(&self, x: f32, y: f32) -> f32 {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (cell_x, cell_y) = (x0 as i32, y0 as i32);
+        let (x, y) = (x - x0, y - y0);
+        let lattice = &self.lattice;
+
+        let n00 = gradient(lattice.hash(cell_x, cell_y), x, y);
+        let n10 = gradient(lattice.hash(cell_x + 1, cell_y), x - 1.0, y);
+        let n01 = gradient(lattice.hash(cell_x, cell_y + 1), x, y - 1.0);
+        let n11 = gradient(lattice.hash(cell_x + 1, cell_y + 1), x - 1.0, y - 1.0);
+
+        let (u, v) = (fade(x), fade(y));
+        lerp(lerp(n00, n10, u), lerp(n01, n11, u), v)
+    }
+}
+
+#[inline]
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+#[inline]
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
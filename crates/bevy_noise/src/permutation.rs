@@ -0,0 +1,99 @@
+/// A seeded permutation of `0..256`, used to hash lattice coordinates
+#[derive(Debug, Clone)]
+pub(crate) struct PermutationTable {
+    // the permutation is stored twice so that `hash` never needs to wrap
+    values: [u8; 512],
+}
+
+impl PermutationTable {
+    pub fn new(seed: u64) -> Self {
+        let mut state = seed;
+        let mut permutation = [0u8; 256];
+        for (i, value) in permutation.iter_mut().enumerate() {
+            *value = i as u8;
+        }
+        for i in (1..permutation.len()).rev() {
+            let j = (split_mix_64(&mut state) % (i as u64 + 1)) as usize;
+            permutation.swap(i, j);
+        }
+
+        let mut values = [0u8; 512];
+        values[..256].copy_from_slice(&permutation);
+        values[256..].copy_from_slice(&permutation);
+        PermutationTable { values }
+    }
+
+    /// Hashes a pair of lattice coordinates to a value in `0..256`
+    #[inline]
+    pub fn hash(&self, x: i32, y: i32) -> usize {
+        let x = self.values[(x & 255) as usize] as usize;
+        self.values[x + (y & 255) as usize] as usize
+    }
+
+    #[inline]
+    pub fn get(&self, index: usize) -> u8 {
+        self.values[index & 511]
+    }
+}
+
+/// The lattice of integer points that noise functions place gradients and feature points on.
+/// Points are hashed with a [PermutationTable], wrapping around `period` when the noise tiles.
+#[derive(Debug, Clone)]
+pub(crate) struct Lattice {
+    permutation: PermutationTable,
+    period: Option<(i32, i32)>,
+}
+
+impl Lattice {
+    pub fn new(seed: u64, period: Option<(u32, u32)>) -> Self {
+        Lattice {
+            permutation: PermutationTable::new(seed),
+            period: period.map(|(x, y)| (x as i32, y as i32)),
+        }
+    }
+
+    /// Hashes a lattice point to a value in `0..256`. Points a whole number of periods apart
+    /// have the same hash.
+    #[inline]
+    pub fn hash(&self, x: i32, y: i32) -> usize {
+        match self.period {
+            Some((period_x, period_y)) => self
+                .permutation
+                .hash(x.rem_euclid(period_x), y.rem_euclid(period_y)),
+            None => self.permutation.hash(x, y),
+        }
+    }
+
+    #[inline]
+    pub fn period(&self) -> Option<(i32, i32)> {
+        self.period
+    }
+
+    #[inline]
+    pub fn permutation(&self) -> &PermutationTable {
+        &self.permutation
+    }
+}
+
+fn split_mix_64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Returns the dot product of `(x, y)` with one of eight gradient directions selected by `hash`
+#[inline]
+pub(crate) fn gradient(hash: usize, x: f32, y: f32) -> f32 {
+    match hash & 7 {
+        0 => x + y,
+        1 => -x + y,
+        2 => x - y,
+        3 => -x - y,
+        4 => x,
+        5 => -x,
+        6 => y,
+        _ => -y,
+    }
+}
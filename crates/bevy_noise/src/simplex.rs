@@ -0,0 +1,76 @@
+use crate::{
+    permutation::{gradient, Lattice},
+    NoiseBuilder, NoiseFn2d,
+};
+
+/// Seeded 2d simplex noise. This has fewer directional artifacts than [Perlin](crate::Perlin)
+/// noise and is cheaper to sample. Values are in the range `[-1.0, 1.0]`.
+///
+/// Use a [NoiseBuilder] to make the noise tile. The triangles of the simplex grid are laid out in
+/// rows that are offset by half a triangle from each other, so the noise can only repeat
+/// vertically after an even number of units.
+#[derive(Debug, Clone)]
+pub struct Simplex {
+    lattice: Lattice,
+}
+
+impl Simplex {
+    pub fn new(seed: u64) -> Self {
+        NoiseBuilder::new(seed).simplex()
+    }
+
+    pub(crate) fn from_lattice(lattice: Lattice) -> Self {
+        Simplex { lattice }
+    }
+
+    /// Hashes the corner at column `i` of row `j`, which is at `(i - j / 2, j)`
+    fn hash(&self, i: i32, j: i32) -> usize {
+        match self.lattice.period() {
+            Some((period_x, period_y)) => {
+                // the grid is sheared, so wrap the position of the corner instead of its
+                // column. Positions are doubled to keep them whole.
+                let y = j.rem_euclid(period_y);
+                let double_x = (2 * i - j).rem_euclid(2 * period_x);
+                self.lattice.permutation().hash((double_x + y) / 2, y)
+            }
+            None => self.lattice.hash(i, j),
+        }
+    }
+
+    fn corner(&self, i: i32, j: i32, x: f32, y: f32) -> f32 {
+        let t = 0.8 - x * x - y * y;
+        if t < 0.0 {
+            0.0
+        } else {
+            let t = t * t;
+            t * t * gradient(self.hash(i, j), x, y)
+        }
+    }
+}
+
+impl NoiseFn2d for Simplex {
+    fn get(&self, x: f32, y: f32) -> f32 {
+        // find the triangle containing the point. Shearing the grid turns it into unit squares
+        // that are split along their diagonal.
+        let (u, v) = (x + 0.5 * y, y);
+        let (i, j) = (u.floor(), v.floor());
+        let corner_x = |i: f32, j: f32| x - (i - 0.5 * j);
+        let (x0, y0) = (corner_x(i, j), y - j);
+
+        // the middle corner depends on which half of the square we are in
+        let (offset_i, offset_j) = if u - i > v - j { (1, 0) } else { (0, 1) };
+        let x1 = corner_x(i + offset_i as f32, j + offset_j as f32);
+        let y1 = y0 - offset_j as f32;
+        let (x2, y2) = (corner_x(i + 1.0, j + 1.0), y0 - 1.0);
+
+        let (i, j) = (i as i32, j as i32);
+        let n0 = self.corner(i, j, x0, y0);
+        let n1 = self.corner(i + offset_i, j + offset_j, x1, y1);
+        let n2 = self.corner(i + 1, j + 1, x2, y2);
+
+        (SCALE * (n0 + n1 + n2)).clamp(-1.0, 1.0)
+    }
+}
+
+/// Scales the sum of the corners to the range `[-1.0, 1.0]`
+const SCALE: f32 = 9.0;
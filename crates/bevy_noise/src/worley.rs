@@ -0,0 +1,47 @@
+use crate::{permutation::Lattice, NoiseBuilder, NoiseFn2d};
+
+/// Seeded 2d cellular ("Worley") noise. Every unit cell contains one randomly placed feature
+/// point, and sampling returns the distance to the closest one. Values are never larger than
+/// `sqrt(2)`, and are usually below `1.0`.
+///
+/// Use a [NoiseBuilder] to make the noise tile.
+#[derive(Debug, Clone)]
+pub struct Worley {
+    lattice: Lattice,
+}
+
+impl Worley {
+    pub fn new(seed: u64) -> Self {
+        NoiseBuilder::new(seed).worley()
+    }
+
+    pub(crate) fn from_lattice(lattice: Lattice) -> Self {
+        Worley { lattice }
+    }
+
+    /// Returns the position of the feature point in the given cell
+    fn feature_point(&self, cell_x: i32, cell_y: i32) -> (f32, f32) {
+        let hash = self.lattice.hash(cell_x, cell_y);
+        let permutation = self.lattice.permutation();
+        (
+            cell_x as f32 + permutation.get(hash) as f32 / 256.0,
+            cell_y as f32 + permutation.get(hash + 1) as f32 / 256.0,
+        )
+    }
+}
+
+impl NoiseFn2d for Worley {
+    fn get(&self, x: f32, y: f32) -> f32 {
+        let (cell_x, cell_y) = (x.floor() as i32, y.floor() as i32);
+        let mut closest = f32::MAX;
+        for offset_y in -1..=1 {
+            for offset_x in -1..=1 {
+                let (point_x, point_y) = self.feature_point(cell_x + offset_x, cell_y + offset_y);
+                let (dx, dy) = (point_x - x, point_y - y);
+                closest = closest.min(dx * dx + dy * dy);
+            }
+        }
+
+        closest.sqrt()
+    }
+}
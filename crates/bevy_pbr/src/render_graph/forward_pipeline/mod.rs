@@ -24,7 +24,7 @@ pub(crate) fn build_forward_pipeline(shaders: &mut Assets<Shader>) -> PipelineDe
             clamp_depth: false,
         }),
         depth_stencil_state: Some(DepthStencilStateDescriptor {
-            format: TextureFormat::Depth32Float,
+            format: TextureFormat::Depth24PlusStencil8,
             depth_write_enabled: true,
             depth_compare: CompareFunction::Less,
             stencil: StencilStateDescriptor {
@@ -8,7 +8,9 @@ use std::marker::PhantomData;
 #[derive(Clone)]
 pub struct ReflectComponent {
     add_component: fn(&mut World, resources: &Resources, Entity, &dyn Reflect),
+    add_default_component: fn(&mut World, resources: &Resources, Entity),
     apply_component: fn(&mut World, Entity, &dyn Reflect),
+    modify_component: fn(&mut World, Entity, &mut dyn FnMut(&mut dyn Reflect)) -> bool,
     reflect_component: unsafe fn(&Archetype, usize) -> &dyn Reflect,
     copy_component: fn(&World, &mut World, &Resources, Entity, Entity),
 }
@@ -24,10 +26,26 @@ impl ReflectComponent {
         (self.add_component)(world, resources, entity, component);
     }
 
+    /// Adds the component to `entity`, created with [FromResources]
+    pub fn add_default_component(&self, world: &mut World, resources: &Resources, entity: Entity) {
+        (self.add_default_component)(world, resources, entity);
+    }
+
     pub fn apply_component(&self, world: &mut World, entity: Entity, component: &dyn Reflect) {
         (self.apply_component)(world, entity, component);
     }
 
+    /// Calls `f` with the component of `entity`, which is marked as mutated. Returns `false` if
+    /// the entity doesn't exist or doesn't have the component.
+    pub fn modify_component(
+        &self,
+        world: &mut World,
+        entity: Entity,
+        f: &mut dyn FnMut(&mut dyn Reflect),
+    ) -> bool {
+        (self.modify_component)(world, entity, f)
+    }
+
     /// # Safety
     /// This does not do bound checks on entity_index. You must make sure entity_index is within bounds before calling.
     pub unsafe fn reflect_component<'a>(
@@ -64,10 +82,22 @@ impl<C: Component + Reflect + FromResources> FromType<C> for ReflectComponent {
                 component.apply(reflected_component);
                 world.insert_one(entity, component).unwrap();
             },
+            add_default_component: |world, resources, entity| {
+                world
+                    .insert_one(entity, C::from_resources(resources))
+                    .unwrap();
+            },
             apply_component: |world, entity, reflected_component| {
                 let mut component = world.get_mut::<C>(entity).unwrap();
                 component.apply(reflected_component);
             },
+            modify_component: |world, entity, f| match world.get_mut::<C>(entity) {
+                Ok(mut component) => {
+                    f(&mut *component);
+                    true
+                }
+                Err(_) => false,
+            },
             copy_component: |source_world,
                              destination_world,
                              resources,
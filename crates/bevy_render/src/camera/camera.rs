@@ -1,6 +1,7 @@
 use super::CameraProjection;
+use crate::color::Color;
 use bevy_app::prelude::{EventReader, Events};
-use bevy_ecs::{Added, Component, Entity, Local, Query, QuerySet, Res};
+use bevy_ecs::{Added, Changed, Component, Entity, Local, Query, QuerySet, Res};
 use bevy_math::Mat4;
 use bevy_reflect::{Reflect, ReflectComponent};
 use bevy_window::{WindowCreated, WindowId, WindowResized, Windows};
@@ -14,9 +15,61 @@ pub struct Camera {
     pub window: WindowId,
     #[reflect(ignore)]
     pub depth_calculation: DepthCalculation,
+    /// The area of the window the camera draws to. The camera draws to the whole window if this
+    /// is `None`.
+    #[reflect(ignore)]
+    pub viewport: Option<Viewport>,
+    /// The color the camera's pass is cleared with, instead of the [ClearColor](crate::pass::ClearColor)
+    /// resource. The whole target of the pass is cleared, not only the viewport of the camera, so
+    /// when several cameras draw to the same pass, the clear color of the first of them that has
+    /// one is used.
+    #[reflect(ignore)]
+    pub clear_color: Option<Color>,
+}
+
+/// An area of a window, as fractions of the window size measured from its top left corner
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Viewport::new(0.0, 0.0, 1.0, 1.0)
+    }
+}
+
+impl Viewport {
+    pub const fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Viewport {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// The position and size of the viewport in pixels, within a target of `width` x `height`
+    /// pixels
+    pub fn physical_rect(&self, width: u32, height: u32) -> [f32; 4] {
+        let (width, height) = (width as f32, height as f32);
+        let x = bevy_math::clamp(self.x, 0.0, 1.0) * width;
+        let y = bevy_math::clamp(self.y, 0.0, 1.0) * height;
+        let physical_width = bevy_math::clamp(self.width * width, 0.0, width - x);
+        let physical_height = bevy_math::clamp(self.height * height, 0.0, height - y);
+        [
+            x.round(),
+            y.round(),
+            physical_width.round(),
+            physical_height.round(),
+        ]
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum DepthCalculation {
     Distance,
     ZDifference,
@@ -42,6 +95,7 @@ pub fn camera_system<T: CameraProjection + Component>(
     mut queries: QuerySet<(
         Query<(Entity, &mut Camera, &mut T)>,
         Query<Entity, Added<Camera>>,
+        Query<Entity, Changed<Camera>>,
     )>,
 ) {
     let mut changed_window_ids = Vec::new();
@@ -75,13 +129,98 @@ pub fn camera_system<T: CameraProjection + Component>(
     for entity in &mut queries.q1().iter() {
         added_cameras.push(entity);
     }
+    // cameras are also updated when their viewport may have changed
+    let changed_cameras = queries.q2().iter().collect::<Vec<_>>();
     for (entity, mut camera, mut camera_projection) in queries.q0_mut().iter_mut() {
         if let Some(window) = windows.get(camera.window) {
-            if changed_window_ids.contains(&window.id()) || added_cameras.contains(&entity) {
-                camera_projection.update(window.width(), window.height());
-                camera.projection_matrix = camera_projection.get_projection_matrix();
-                camera.depth_calculation = camera_projection.depth_calculation();
+            if changed_window_ids.contains(&window.id())
+                || added_cameras.contains(&entity)
+                || changed_cameras.contains(&entity)
+            {
+                let viewport = camera.viewport.unwrap_or_default();
+                camera_projection.update(
+                    window.width() * viewport.width,
+                    window.height() * viewport.height,
+                );
+                // only write changes, so updating the projection doesn't mark the camera as
+                // changed again
+                let projection_matrix = camera_projection.get_projection_matrix();
+                if camera.projection_matrix != projection_matrix {
+                    camera.projection_matrix = projection_matrix;
+                }
+                let depth_calculation = camera_projection.depth_calculation();
+                if camera.depth_calculation != depth_calculation {
+                    camera.depth_calculation = depth_calculation;
+                }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::PerspectiveProjection;
+    use bevy_ecs::{IntoSystem, Resources, Stage, SystemStage, World};
+    use bevy_window::{Window, WindowDescriptor};
+
+    #[test]
+    fn viewport_aspect_ratio() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        let mut windows = Windows::default();
+        windows.add(Window::new(
+            WindowId::primary(),
+            &WindowDescriptor::default(),
+            1280,
+            720,
+            1.0,
+        ));
+        resources.insert(windows);
+        resources.insert(Events::<WindowResized>::default());
+        resources.insert(Events::<WindowCreated>::default());
+        let camera = world.spawn((
+            Camera {
+                viewport: Some(Viewport::new(0.0, 0.0, 0.5, 1.0)),
+                ..Default::default()
+            },
+            PerspectiveProjection::default(),
+        ));
+
+        let mut stage = SystemStage::serial();
+        stage.add_system(camera_system::<PerspectiveProjection>.system());
+        stage.initialize(&mut world, &mut resources);
+        stage.run(&mut world, &mut resources);
+        let aspect_ratio = world
+            .get::<PerspectiveProjection>(camera)
+            .unwrap()
+            .aspect_ratio;
+        assert_eq!(aspect_ratio, 640.0 / 720.0);
+
+        // changing the viewport updates the projection
+        world.clear_trackers();
+        world.get_mut::<Camera>(camera).unwrap().viewport = Some(Viewport::new(0.0, 0.0, 1.0, 0.5));
+        stage.run(&mut world, &mut resources);
+        let aspect_ratio = world
+            .get::<PerspectiveProjection>(camera)
+            .unwrap()
+            .aspect_ratio;
+        assert_eq!(aspect_ratio, 1280.0 / 360.0);
+    }
+
+    #[test]
+    fn viewport_physical_rect() {
+        let right_half = Viewport::new(0.5, 0.0, 0.5, 1.0);
+        assert_eq!(
+            right_half.physical_rect(1280, 720),
+            [640.0, 0.0, 640.0, 720.0]
+        );
+        // viewports are clipped to the target
+        let inset = Viewport::new(0.75, 0.75, 0.5, 0.5);
+        assert_eq!(inset.physical_rect(100, 100), [75.0, 75.0, 25.0, 25.0]);
+        assert_eq!(
+            Viewport::default().physical_rect(800, 600),
+            [0.0, 0.0, 800.0, 600.0]
+        );
+    }
+}
@@ -51,6 +51,51 @@ pub enum WindowOrigin {
     BottomLeft,
 }
 
+/// How an [OrthographicProjection] maps the window to world units
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect_value(Serialize, Deserialize)]
+pub enum ScalingMode {
+    /// One world unit is one logical pixel, so resizing the window shows more or less of the world
+    WindowSize,
+    /// The view is always this many world units tall. Its width follows the aspect ratio of the
+    /// window.
+    FixedVertical(f32),
+    /// The view is always this many world units wide. Its height follows the aspect ratio of the
+    /// window.
+    FixedHorizontal(f32),
+    /// Each world unit is drawn as a whole number of logical pixels, so pixel art stays crisp.
+    /// The scale is the largest one that still shows at least `width` x `height` world units, and
+    /// never less than 1.
+    PixelPerfect { width: f32, height: f32 },
+}
+
+impl Default for ScalingMode {
+    fn default() -> Self {
+        ScalingMode::WindowSize
+    }
+}
+
+impl ScalingMode {
+    /// The size of the view in world units for a window of `width` x `height` logical pixels
+    pub fn view_size(&self, width: f32, height: f32) -> (f32, f32) {
+        match *self {
+            ScalingMode::WindowSize => (width, height),
+            ScalingMode::FixedVertical(view_height) => (width * view_height / height, view_height),
+            ScalingMode::FixedHorizontal(view_width) => (view_width, height * view_width / width),
+            ScalingMode::PixelPerfect {
+                width: min_width,
+                height: min_height,
+            } => {
+                let scale = (width / min_width)
+                    .min(height / min_height)
+                    .floor()
+                    .max(1.0);
+                (width / scale, height / scale)
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Reflect)]
 #[reflect(Component)]
 pub struct OrthographicProjection {
@@ -61,6 +106,7 @@ pub struct OrthographicProjection {
     pub near: f32,
     pub far: f32,
     pub window_origin: WindowOrigin,
+    pub scaling_mode: ScalingMode,
 }
 
 impl CameraProjection for OrthographicProjection {
@@ -76,6 +122,7 @@ impl CameraProjection for OrthographicProjection {
     }
 
     fn update(&mut self, width: f32, height: f32) {
+        let (width, height) = self.scaling_mode.view_size(width, height);
         match self.window_origin {
             WindowOrigin::Center => {
                 let half_width = width / 2.0;
@@ -109,6 +156,54 @@ impl Default for OrthographicProjection {
             near: 0.0,
             far: 1000.0,
             window_origin: WindowOrigin::Center,
+            scaling_mode: ScalingMode::WindowSize,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaling_modes() {
+        assert_eq!(
+            ScalingMode::WindowSize.view_size(1280.0, 720.0),
+            (1280.0, 720.0)
+        );
+        assert_eq!(
+            ScalingMode::FixedVertical(20.0).view_size(1280.0, 720.0),
+            (1280.0 * 20.0 / 720.0, 20.0)
+        );
+        assert_eq!(
+            ScalingMode::FixedHorizontal(32.0).view_size(1280.0, 720.0),
+            (32.0, 18.0)
+        );
+        // 1280x720 fits 320x180 exactly 4 times, 1300x730 still only fits it 4 times
+        let pixel_perfect = ScalingMode::PixelPerfect {
+            width: 320.0,
+            height: 180.0,
+        };
+        assert_eq!(pixel_perfect.view_size(1280.0, 720.0), (320.0, 180.0));
+        assert_eq!(pixel_perfect.view_size(1300.0, 730.0), (325.0, 182.5));
+        assert_eq!(pixel_perfect.view_size(200.0, 100.0), (200.0, 100.0));
+    }
+
+    #[test]
+    fn orthographic_scaling_mode() {
+        let mut projection = OrthographicProjection {
+            scaling_mode: ScalingMode::FixedVertical(10.0),
+            ..Default::default()
+        };
+        projection.update(400.0, 200.0);
+        assert_eq!(
+            (
+                projection.left,
+                projection.right,
+                projection.bottom,
+                projection.top
+            ),
+            (-10.0, 10.0, -5.0, 5.0)
+        );
+    }
+}
@@ -11,9 +11,10 @@ use bevy_reflect::Reflect;
 use serde::{Deserialize, Serialize};
 use std::ops::{Add, AddAssign, Mul, MulAssign};
 
-// TODO: Separate types for non-linear sRGB and linear sRGB, with conversions between
-// see comment on bevy issue #688 https://github.com/bevyengine/bevy/pull/688#issuecomment-711414011
 /// RGBA color in the Linear sRGB colorspace (often colloquially referred to as "linear", "RGB", or "linear RGB").
+///
+/// This is the color space shaders work in. Use [Srgba] and [LinearRgba] to say explicitly which
+/// color space the components of a color are in.
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Reflect)]
 pub struct Color {
@@ -65,6 +66,30 @@ impl Color {
     pub const YELLOW: Color = Color::rgb_linear(1.0, 1.0, 0.0);
     pub const YELLOW_GREEN: Color = Color::rgb_linear(0.6, 0.8, 0.2);
 
+    /// A gradient from cold to hot colors for heatmaps, such as chunk load times. Sample it with
+    /// [Color::gradient].
+    pub const HEATMAP: [Color; 5] = [
+        Color::BLUE,
+        Color::CYAN,
+        Color::GREEN,
+        Color::YELLOW,
+        Color::RED,
+    ];
+    /// A gradient from black to white
+    pub const GRAYSCALE: [Color; 2] = [Color::BLACK, Color::WHITE];
+    /// Distinct colors to tell things apart in debug visualizations, such as chunk borders or
+    /// regions
+    pub const DEBUG: [Color; 8] = [
+        Color::RED,
+        Color::LIME_GREEN,
+        Color::BLUE,
+        Color::GOLD,
+        Color::FUCHSIA,
+        Color::TURQUOISE,
+        Color::ORANGE,
+        Color::INDIGO,
+    ];
+
     // TODO: cant make rgb and rgba const due traits not allowed in const functions
     // see issue #57563 https://github.com/rust-lang/rust/issues/57563
     /// New ``Color`` from sRGB colorspace.
@@ -258,6 +283,289 @@ impl Color {
     }
 }
 
+impl Color {
+    /// New ``Color`` from hue in degrees, and saturation and lightness between 0 and 1, in the sRGB
+    /// colorspace.
+    pub fn hsl(hue: f32, saturation: f32, lightness: f32) -> Color {
+        Color::hsla(hue, saturation, lightness, 1.0)
+    }
+
+    /// New ``Color`` from hue in degrees, and saturation and lightness between 0 and 1, in the sRGB
+    /// colorspace.
+    pub fn hsla(hue: f32, saturation: f32, lightness: f32, alpha: f32) -> Color {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        let [r, g, b] = hue_to_rgb(hue, chroma, lightness - chroma / 2.0);
+        Color::rgba(r, g, b, alpha)
+    }
+
+    /// New ``Color`` from hue in degrees, and saturation and value between 0 and 1, in the sRGB
+    /// colorspace.
+    pub fn hsv(hue: f32, saturation: f32, value: f32) -> Color {
+        Color::hsva(hue, saturation, value, 1.0)
+    }
+
+    /// New ``Color`` from hue in degrees, and saturation and value between 0 and 1, in the sRGB
+    /// colorspace.
+    pub fn hsva(hue: f32, saturation: f32, value: f32, alpha: f32) -> Color {
+        let chroma = value * saturation;
+        let [r, g, b] = hue_to_rgb(hue, chroma, value - chroma);
+        Color::rgba(r, g, b, alpha)
+    }
+
+    /// The hue in degrees, saturation, lightness and alpha of this color, in the sRGB colorspace
+    pub fn as_hsla(&self) -> [f32; 4] {
+        let (hue, max, min) = rgb_to_hue([self.r(), self.g(), self.b()]);
+        let lightness = (max + min) / 2.0;
+        let saturation = if max == min {
+            0.0
+        } else {
+            (max - min) / (1.0 - (2.0 * lightness - 1.0).abs())
+        };
+        [hue, saturation, lightness, self.alpha]
+    }
+
+    /// The hue in degrees, saturation, value and alpha of this color, in the sRGB colorspace
+    pub fn as_hsva(&self) -> [f32; 4] {
+        let (hue, max, min) = rgb_to_hue([self.r(), self.g(), self.b()]);
+        let saturation = if max == 0.0 { 0.0 } else { (max - min) / max };
+        [hue, saturation, max, self.alpha]
+    }
+
+    /// Linearly interpolates from this color to `other` in the linear sRGB colorspace, which
+    /// blends colors the way light does. `t` is clamped between 0 and 1.
+    pub fn lerp(&self, other: Color, t: f32) -> Color {
+        let t = bevy_math::clamp(t, 0.0, 1.0);
+        Color::rgba_linear(
+            self.red + (other.red - self.red) * t,
+            self.green + (other.green - self.green) * t,
+            self.blue + (other.blue - self.blue) * t,
+            self.alpha + (other.alpha - self.alpha) * t,
+        )
+    }
+
+    /// Samples a gradient of evenly spaced `colors`, such as [Color::HEATMAP], at `t` between 0
+    /// and 1.
+    ///
+    /// # Panics
+    /// Panics if `colors` is empty.
+    pub fn gradient(colors: &[Color], t: f32) -> Color {
+        assert!(!colors.is_empty(), "a gradient needs at least one color");
+        let position = bevy_math::clamp(t, 0.0, 1.0) * (colors.len() - 1) as f32;
+        let index = (position as usize).min(colors.len() - 1);
+        match colors.get(index + 1) {
+            Some(next) => colors[index].lerp(*next, position - index as f32),
+            None => colors[index],
+        }
+    }
+
+    /// The components of this color in the linear sRGB colorspace, as sent to shaders
+    pub fn as_linear_rgba(&self) -> LinearRgba {
+        LinearRgba::new(self.red, self.green, self.blue, self.alpha)
+    }
+
+    /// The components of this color in the non-linear sRGB colorspace
+    pub fn as_srgba(&self) -> Srgba {
+        Srgba::new(self.r(), self.g(), self.b(), self.alpha)
+    }
+}
+
+/// RGBA color with components in the non-linear sRGB colorspace, the colorspace of image files,
+/// color pickers, hex codes and `Rgba8UnormSrgb` textures.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct Srgba {
+    pub red: f32,
+    pub green: f32,
+    pub blue: f32,
+    pub alpha: f32,
+}
+
+impl Srgba {
+    pub const fn new(red: f32, green: f32, blue: f32, alpha: f32) -> Self {
+        Srgba {
+            red,
+            green,
+            blue,
+            alpha,
+        }
+    }
+
+    pub const fn rgb(red: f32, green: f32, blue: f32) -> Self {
+        Srgba::new(red, green, blue, 1.0)
+    }
+
+    /// New ``Srgba`` from bytes, as stored in `Rgba8UnormSrgb` textures
+    pub fn from_u8([red, green, blue, alpha]: [u8; 4]) -> Self {
+        Srgba::new(
+            red as f32 / u8::MAX as f32,
+            green as f32 / u8::MAX as f32,
+            blue as f32 / u8::MAX as f32,
+            alpha as f32 / u8::MAX as f32,
+        )
+    }
+
+    /// The components as bytes, as stored in `Rgba8UnormSrgb` textures
+    pub fn to_u8(&self) -> [u8; 4] {
+        [
+            unorm_to_u8(self.red),
+            unorm_to_u8(self.green),
+            unorm_to_u8(self.blue),
+            unorm_to_u8(self.alpha),
+        ]
+    }
+
+    pub fn to_linear(&self) -> LinearRgba {
+        LinearRgba::new(
+            self.red.nonlinear_to_linear_srgb(),
+            self.green.nonlinear_to_linear_srgb(),
+            self.blue.nonlinear_to_linear_srgb(),
+            self.alpha, // alpha is always linear
+        )
+    }
+}
+
+impl Default for Srgba {
+    fn default() -> Self {
+        Srgba::rgb(1.0, 1.0, 1.0)
+    }
+}
+
+/// RGBA color with components in the linear sRGB colorspace, the colorspace of lighting and
+/// blending math, shader inputs and outputs, and `Rgba8Unorm` textures.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct LinearRgba {
+    pub red: f32,
+    pub green: f32,
+    pub blue: f32,
+    pub alpha: f32,
+}
+
+unsafe impl Byteable for LinearRgba {}
+
+impl LinearRgba {
+    pub const fn new(red: f32, green: f32, blue: f32, alpha: f32) -> Self {
+        LinearRgba {
+            red,
+            green,
+            blue,
+            alpha,
+        }
+    }
+
+    pub const fn rgb(red: f32, green: f32, blue: f32) -> Self {
+        LinearRgba::new(red, green, blue, 1.0)
+    }
+
+    /// New ``LinearRgba`` from bytes, as stored in `Rgba8Unorm` textures
+    pub fn from_u8([red, green, blue, alpha]: [u8; 4]) -> Self {
+        LinearRgba::new(
+            red as f32 / u8::MAX as f32,
+            green as f32 / u8::MAX as f32,
+            blue as f32 / u8::MAX as f32,
+            alpha as f32 / u8::MAX as f32,
+        )
+    }
+
+    /// The components as bytes, as stored in `Rgba8Unorm` textures
+    pub fn to_u8(&self) -> [u8; 4] {
+        [
+            unorm_to_u8(self.red),
+            unorm_to_u8(self.green),
+            unorm_to_u8(self.blue),
+            unorm_to_u8(self.alpha),
+        ]
+    }
+
+    pub fn to_srgb(&self) -> Srgba {
+        Srgba::new(
+            self.red.linear_to_nonlinear_srgb(),
+            self.green.linear_to_nonlinear_srgb(),
+            self.blue.linear_to_nonlinear_srgb(),
+            self.alpha,
+        )
+    }
+}
+
+impl Default for LinearRgba {
+    fn default() -> Self {
+        LinearRgba::rgb(1.0, 1.0, 1.0)
+    }
+}
+
+/// The red, green and blue components of a color from its hue in degrees, chroma, and the value
+/// added to each component
+fn hue_to_rgb(hue: f32, chroma: f32, offset: f32) -> [f32; 3] {
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let [r, g, b] = match sector as u32 {
+        0 => [chroma, x, 0.0],
+        1 => [x, chroma, 0.0],
+        2 => [0.0, chroma, x],
+        3 => [0.0, x, chroma],
+        4 => [x, 0.0, chroma],
+        _ => [chroma, 0.0, x],
+    };
+    [r + offset, g + offset, b + offset]
+}
+
+/// The hue in degrees, and the largest and smallest components of a color
+fn rgb_to_hue([r, g, b]: [f32; 3]) -> (f32, f32, f32) {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let chroma = max - min;
+    let hue = if chroma == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / chroma + 2.0)
+    } else {
+        60.0 * ((r - g) / chroma + 4.0)
+    };
+    (hue, max, min)
+}
+
+fn unorm_to_u8(value: f32) -> u8 {
+    (bevy_math::clamp(value, 0.0, 1.0) * u8::MAX as f32).round() as u8
+}
+
+impl From<Srgba> for LinearRgba {
+    fn from(color: Srgba) -> Self {
+        color.to_linear()
+    }
+}
+
+impl From<LinearRgba> for Srgba {
+    fn from(color: LinearRgba) -> Self {
+        color.to_srgb()
+    }
+}
+
+impl From<Srgba> for Color {
+    fn from(color: Srgba) -> Self {
+        Color::rgba(color.red, color.green, color.blue, color.alpha)
+    }
+}
+
+impl From<Color> for Srgba {
+    fn from(color: Color) -> Self {
+        color.as_srgba()
+    }
+}
+
+impl From<LinearRgba> for Color {
+    fn from(color: LinearRgba) -> Self {
+        Color::rgba_linear(color.red, color.green, color.blue, color.alpha)
+    }
+}
+
+impl From<Color> for LinearRgba {
+    fn from(color: Color) -> Self {
+        color.as_linear_rgba()
+    }
+}
+
 impl Default for Color {
     fn default() -> Self {
         Color::WHITE
@@ -464,6 +772,7 @@ impl From<Handle<Texture>> for ColorSource {
 }
 
 impl_render_resource_bytes!(Color);
+impl_render_resource_bytes!(LinearRgba);
 
 #[derive(Debug)]
 pub enum HexColorError {
@@ -630,3 +939,61 @@ fn test_mul_and_mulassign_vec4() {
 
     assert_eq!(starting_color * transformation, mutated_color,);
 }
+
+#[test]
+fn test_srgba_linear_rgba_roundtrip() {
+    for value in 0..=u8::MAX {
+        let srgba = Srgba::from_u8([value, value, value, value]);
+        assert_eq!(srgba.to_linear().to_srgb().to_u8(), srgba.to_u8());
+        assert_eq!(Srgba::from(Color::from(srgba)).to_u8(), srgba.to_u8());
+        assert_eq!(srgba.to_linear().alpha, srgba.alpha);
+    }
+}
+
+#[test]
+fn test_color_byte_components() {
+    // a color picked from an image stays the same when it goes through `Color`, which stores it
+    // in the linear colorspace shaders work in
+    let color = Color::rgba_u8(210, 105, 30, 255);
+    assert_eq!(color.as_srgba().to_u8(), [210, 105, 30, 255]);
+    assert_eq!(color, Srgba::from_u8([210, 105, 30, 255]).into());
+    assert_eq!(color.as_linear_rgba().to_u8(), [164, 36, 3, 255]);
+    assert_eq!(Color::from(color.as_linear_rgba()), color);
+}
+
+#[test]
+fn test_hsl_hsv() {
+    let color = Color::rgb_u8(210, 105, 30);
+    let [h, s, l, a] = color.as_hsla();
+    assert!((h - 25.0).abs() < 0.01);
+    assert!((s - 0.75).abs() < 0.01);
+    assert!((l - 0.47).abs() < 0.01);
+    assert_eq!(a, 1.0);
+    assert_eq!(Color::hsl(h, s, l).as_srgba().to_u8(), [210, 105, 30, 255]);
+
+    let [h, s, v, _] = color.as_hsva();
+    assert!((h - 25.0).abs() < 0.01);
+    assert!((s - 0.857).abs() < 0.01);
+    assert!((v - 0.824).abs() < 0.01);
+    assert_eq!(Color::hsv(h, s, v).as_srgba().to_u8(), [210, 105, 30, 255]);
+
+    assert_eq!(Color::hsv(-120.0, 1.0, 1.0), Color::BLUE);
+    assert_eq!(Color::hsl(0.0, 0.0, 1.0), Color::WHITE);
+    assert_eq!(Color::BLACK.as_hsla(), [0.0, 0.0, 0.0, 1.0]);
+}
+
+#[test]
+fn test_lerp_and_gradient() {
+    let half = Color::BLACK.lerp(Color::WHITE, 0.5);
+    assert_eq!(half, Color::rgb_linear(0.5, 0.5, 0.5));
+    assert_eq!(Color::BLACK.lerp(Color::WHITE, 2.0), Color::WHITE);
+
+    assert_eq!(Color::gradient(&Color::HEATMAP, 0.0), Color::BLUE);
+    assert_eq!(Color::gradient(&Color::HEATMAP, 0.5), Color::GREEN);
+    assert_eq!(Color::gradient(&Color::HEATMAP, 1.0), Color::RED);
+    assert_eq!(
+        Color::gradient(&Color::HEATMAP, 0.125),
+        Color::rgb_linear(0.0, 0.5, 1.0)
+    );
+    assert_eq!(Color::gradient(&[Color::RED], 0.7), Color::RED);
+}
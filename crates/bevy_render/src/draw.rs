@@ -1,5 +1,8 @@
 use crate::{
-    pipeline::{PipelineCompiler, PipelineDescriptor, PipelineLayout, PipelineSpecialization},
+    pipeline::{
+        PipelineCompiler, PipelineDescriptor, PipelineLayout, PipelineSpecialization,
+        VERTEX_FALLBACK_LAYOUT_NAME,
+    },
     renderer::{
         AssetRenderResourceBindings, BindGroup, BindGroupId, BufferId, RenderResource,
         RenderResourceBinding, RenderResourceBindings, RenderResourceContext, SharedBuffers,
@@ -41,6 +44,17 @@ pub enum RenderCommand {
         vertices: Range<u32>,
         instances: Range<u32>,
     },
+    /// Restricts the following draws of the entity to a rectangle of the render target
+    SetScissorRect {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    /// Sets the value the following draws of the entity compare to and write to the stencil buffer
+    SetStencilReference {
+        reference: u32,
+    },
 }
 
 #[derive(Debug, Clone, Reflect)]
@@ -60,6 +74,28 @@ impl Default for Visible {
     }
 }
 
+/// Restricts the drawing of an entity to a rectangle of its render target, in physical pixels from
+/// the top left corner, like the visible area of a scroll view
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub struct ScissorRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// The value draws of an entity compare to and write to the stencil buffer, for pipelines that use
+/// it, like the [StencilStateDescriptor::WRITE_REFERENCE] pipeline of a minimap mask and the
+/// [StencilStateDescriptor::EQUAL_REFERENCE] pipeline of the minimap drawn inside it.
+/// Entities without one draw with a reference of 0.
+///
+/// [StencilStateDescriptor::WRITE_REFERENCE]: crate::pipeline::StencilStateDescriptor::WRITE_REFERENCE
+/// [StencilStateDescriptor::EQUAL_REFERENCE]: crate::pipeline::StencilStateDescriptor::EQUAL_REFERENCE
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub struct StencilReference(pub u32);
+
 /// A component that indicates how to draw an entity.
 #[derive(Debug, Clone, Reflect)]
 #[reflect(Component)]
@@ -115,6 +151,23 @@ impl Draw {
         });
     }
 
+    /// Restricts the following draws of this entity to a rectangle of the render target. Draws of
+    /// other entities are not affected.
+    pub fn set_scissor_rect(&mut self, scissor_rect: &ScissorRect) {
+        self.render_command(RenderCommand::SetScissorRect {
+            x: scissor_rect.x,
+            y: scissor_rect.y,
+            width: scissor_rect.width,
+            height: scissor_rect.height,
+        });
+    }
+
+    /// Sets the stencil reference of the following draws of this entity. Draws of other entities
+    /// are not affected.
+    pub fn set_stencil_reference(&mut self, reference: u32) {
+        self.render_command(RenderCommand::SetStencilReference { reference });
+    }
+
     #[inline]
     pub fn render_command(&mut self, render_command: RenderCommand) {
         self.render_commands.push(render_command);
@@ -324,6 +377,26 @@ impl<'a> DrawContext<'a> {
             if let Some(main_vertex_buffer) = bindings.vertex_attribute_buffer {
                 draw.set_vertex_buffer(0, main_vertex_buffer, 0);
             }
+            if let Some(fallback_vertex_buffer) = bindings.vertex_fallback_buffer {
+                // only pipelines of meshes that miss attributes their shaders require have a slot for it
+                let pipeline = self
+                    .current_pipeline
+                    .as_ref()
+                    .ok_or(DrawError::NoPipelineSet)?;
+                let layout = self
+                    .pipelines
+                    .get(pipeline)
+                    .ok_or(DrawError::NonExistentPipeline)?
+                    .get_layout()
+                    .ok_or(DrawError::PipelineHasNoLayout)?;
+                if let Some(slot) = layout
+                    .vertex_buffer_descriptors
+                    .iter()
+                    .position(|descriptor| descriptor.name == VERTEX_FALLBACK_LAYOUT_NAME)
+                {
+                    draw.set_vertex_buffer(slot as u32, fallback_vertex_buffer, 0);
+                }
+            }
         }
         Ok(())
     }
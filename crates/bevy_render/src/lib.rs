@@ -20,7 +20,7 @@ pub mod prelude {
     pub use crate::{
         base::Msaa,
         color::Color,
-        draw::{Draw, Visible},
+        draw::{Draw, ScissorRect, StencilReference, Visible},
         entity::*,
         mesh::{shape, Mesh},
         pass::ClearColor,
@@ -38,20 +38,22 @@ use camera::{
     ActiveCameras, Camera, OrthographicProjection, PerspectiveProjection, VisibleEntities,
 };
 use pipeline::{
-    IndexFormat, PipelineCompiler, PipelineDescriptor, PipelineSpecialization, PrimitiveTopology,
-    ShaderSpecialization,
+    BlendMode, IndexFormat, PipelineCompiler, PipelineDescriptor, PipelineSpecialization,
+    PrimitiveTopology, ShaderSpecialization,
 };
 use render_graph::{
     base::{self, BaseRenderGraphBuilder, BaseRenderGraphConfig, MainPass},
     RenderGraph,
 };
-use renderer::{AssetRenderResourceBindings, RenderResourceBindings};
-use shader::ShaderLoader;
+use renderer::{
+    AssetRenderResourceBindings, RenderCapabilities, RenderResourceBindings, RenderResourceGc,
+};
+use shader::{ShaderIncludeLoader, ShaderLoader};
 #[cfg(feature = "hdr")]
 use texture::HdrTextureLoader;
 #[cfg(feature = "png")]
 use texture::ImageTextureLoader;
-use texture::TextureResourceSystemState;
+use texture::{TextureResidency, TextureResourceSystemState};
 
 /// The names of "render" App stages
 pub mod stage {
@@ -90,7 +92,8 @@ impl Plugin for RenderPlugin {
             app.init_asset_loader::<HdrTextureLoader>();
         }
 
-        app.init_asset_loader::<ShaderLoader>();
+        app.init_asset_loader::<ShaderLoader>()
+            .init_asset_loader::<ShaderIncludeLoader>();
 
         if app.resources().get::<ClearColor>().is_none() {
             app.resources_mut().insert(ClearColor::default());
@@ -116,10 +119,13 @@ impl Plugin for RenderPlugin {
         .add_asset::<Mesh>()
         .add_asset::<Texture>()
         .add_asset::<Shader>()
+        .add_asset::<shader::ShaderInclude>()
         .add_asset::<PipelineDescriptor>()
         .register_type::<Camera>()
         .register_type::<Draw>()
         .register_type::<Visible>()
+        .register_type::<ScissorRect>()
+        .register_type::<StencilReference>()
         .register_type::<RenderPipelines>()
         .register_type::<OrthographicProjection>()
         .register_type::<PerspectiveProjection>()
@@ -128,10 +134,12 @@ impl Plugin for RenderPlugin {
         .register_type::<Color>()
         .register_type::<ShaderSpecialization>()
         .register_type::<PrimitiveTopology>()
+        .register_type::<BlendMode>()
         .register_type::<IndexFormat>()
         .register_type::<PipelineSpecialization>()
         .init_resource::<RenderGraph>()
         .init_resource::<PipelineCompiler>()
+        .init_resource::<RenderCapabilities>()
         .init_resource::<RenderResourceBindings>()
         .init_resource::<TextureResourceSystemState>()
         .init_resource::<AssetRenderResourceBindings>()
@@ -161,6 +169,10 @@ impl Plugin for RenderPlugin {
             stage::RENDER_RESOURCE,
             shader::shader_update_system.system(),
         )
+        .add_system_to_stage(
+            stage::RENDER_RESOURCE,
+            shader::shader_include_update_system.system(),
+        )
         .add_system_to_stage(
             stage::RENDER_RESOURCE,
             mesh::mesh_resource_provider_system.system(),
@@ -169,6 +181,10 @@ impl Plugin for RenderPlugin {
             stage::RENDER_RESOURCE,
             Texture::texture_resource_system.system(),
         )
+        .add_system_to_stage(
+            stage::RENDER_RESOURCE,
+            TextureResidency::texture_residency_system.system(),
+        )
         .add_system_to_stage(
             stage::RENDER_GRAPH_SYSTEMS,
             render_graph::render_graph_schedule_executor_system.system(),
@@ -177,12 +193,24 @@ impl Plugin for RenderPlugin {
         .add_system_to_stage(
             stage::POST_RENDER,
             shader::clear_shader_defs_system.system(),
+        )
+        .add_system_to_stage(
+            stage::POST_RENDER,
+            RenderResourceGc::render_resource_gc_system.system(),
         );
 
         if app.resources().get::<Msaa>().is_none() {
             app.init_resource::<Msaa>();
         }
 
+        if app.resources().get::<TextureResidency>().is_none() {
+            app.init_resource::<TextureResidency>();
+        }
+
+        if app.resources().get::<RenderResourceGc>().is_none() {
+            app.init_resource::<RenderResourceGc>();
+        }
+
         if let Some(ref config) = self.base_render_graph_config {
             let resources = app.resources();
             let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
@@ -1,14 +1,17 @@
 use crate::{
     pipeline::{IndexFormat, PrimitiveTopology, RenderPipelines, VertexFormat},
-    renderer::{BufferInfo, BufferUsage, RenderResourceContext, RenderResourceId},
+    renderer::{
+        BufferId, BufferInfo, BufferUsage, RenderResourceBindings, RenderResourceContext,
+        RenderResourceId,
+    },
 };
 use bevy_app::prelude::{EventReader, Events};
 use bevy_asset::{AssetEvent, Assets, Handle};
 use bevy_core::AsBytes;
-use bevy_ecs::{Changed, Entity, Local, Mut, Query, QuerySet, Res, With};
+use bevy_ecs::{Changed, Entity, Local, Mut, Query, QuerySet, Res, ResMut, With};
 use bevy_math::*;
 use bevy_reflect::TypeUuid;
-use std::borrow::Cow;
+use std::{borrow::Cow, ops::Range};
 
 use crate::pipeline::{InputStepMode, VertexAttributeDescriptor, VertexBufferDescriptor};
 use bevy_utils::{HashMap, HashSet};
@@ -16,6 +19,20 @@ use bevy_utils::{HashMap, HashSet};
 pub const INDEX_BUFFER_ASSET_INDEX: u64 = 0;
 pub const VERTEX_ATTRIBUTE_BUFFER_ID: u64 = 10;
 
+/// The data of the vertex buffer that attributes required by a shader, but missing from a mesh,
+/// are read from: zeros, followed by the ones a missing [Mesh::ATTRIBUTE_COLOR] reads, so meshes
+/// without colors are drawn as if their vertices were white
+pub const VERTEX_FALLBACK_DATA: [f32; 8] = [0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0];
+
+/// Where the value of the attribute `name` is in [VERTEX_FALLBACK_DATA]
+pub fn vertex_fallback_offset(name: &str) -> u64 {
+    if name == Mesh::ATTRIBUTE_COLOR {
+        16
+    } else {
+        0
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum VertexAttributeValues {
     Float(Vec<f32>),
@@ -71,6 +88,53 @@ impl VertexAttributeValues {
             VertexAttributeValues::Uint4(values) => values.as_slice().as_bytes(),
         }
     }
+
+    /// Overwrites the values starting at `start` with `new_values`, which need the same format
+    fn write(&mut self, start: usize, new_values: &VertexAttributeValues) {
+        match (self, new_values) {
+            (VertexAttributeValues::Float(values), VertexAttributeValues::Float(new_values)) => {
+                values[start..start + new_values.len()].copy_from_slice(new_values)
+            }
+            (VertexAttributeValues::Int(values), VertexAttributeValues::Int(new_values)) => {
+                values[start..start + new_values.len()].copy_from_slice(new_values)
+            }
+            (VertexAttributeValues::Uint(values), VertexAttributeValues::Uint(new_values)) => {
+                values[start..start + new_values.len()].copy_from_slice(new_values)
+            }
+            (VertexAttributeValues::Float2(values), VertexAttributeValues::Float2(new_values)) => {
+                values[start..start + new_values.len()].copy_from_slice(new_values)
+            }
+            (VertexAttributeValues::Int2(values), VertexAttributeValues::Int2(new_values)) => {
+                values[start..start + new_values.len()].copy_from_slice(new_values)
+            }
+            (VertexAttributeValues::Uint2(values), VertexAttributeValues::Uint2(new_values)) => {
+                values[start..start + new_values.len()].copy_from_slice(new_values)
+            }
+            (VertexAttributeValues::Float3(values), VertexAttributeValues::Float3(new_values)) => {
+                values[start..start + new_values.len()].copy_from_slice(new_values)
+            }
+            (VertexAttributeValues::Int3(values), VertexAttributeValues::Int3(new_values)) => {
+                values[start..start + new_values.len()].copy_from_slice(new_values)
+            }
+            (VertexAttributeValues::Uint3(values), VertexAttributeValues::Uint3(new_values)) => {
+                values[start..start + new_values.len()].copy_from_slice(new_values)
+            }
+            (VertexAttributeValues::Float4(values), VertexAttributeValues::Float4(new_values)) => {
+                values[start..start + new_values.len()].copy_from_slice(new_values)
+            }
+            (VertexAttributeValues::Int4(values), VertexAttributeValues::Int4(new_values)) => {
+                values[start..start + new_values.len()].copy_from_slice(new_values)
+            }
+            (VertexAttributeValues::Uint4(values), VertexAttributeValues::Uint4(new_values)) => {
+                values[start..start + new_values.len()].copy_from_slice(new_values)
+            }
+            (values, new_values) => panic!(
+                "Can't write {:?} values to {:?} values",
+                VertexFormat::from(new_values),
+                VertexFormat::from(&*values)
+            ),
+        }
+    }
 }
 
 impl From<&VertexAttributeValues> for VertexFormat {
@@ -170,6 +234,26 @@ pub enum Indices {
     U32(Vec<u32>),
 }
 
+impl Indices {
+    pub fn len(&self) -> usize {
+        match self {
+            Indices::U16(indices) => indices.len(),
+            Indices::U32(indices) => indices.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn get_bytes(&self) -> &[u8] {
+        match self {
+            Indices::U16(indices) => indices.as_slice().as_bytes(),
+            Indices::U32(indices) => indices.as_slice().as_bytes(),
+        }
+    }
+}
+
 impl From<&Indices> for IndexFormat {
     fn from(indices: &Indices) -> Self {
         match indices {
@@ -179,6 +263,21 @@ impl From<&Indices> for IndexFormat {
     }
 }
 
+/// The vertices and indices of a [Mesh] that were updated with [Mesh::update_attribute] and
+/// [Mesh::update_indices] since its buffers were last written
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeshUpdates {
+    pub vertices: Option<Range<usize>>,
+    pub indices: Option<Range<usize>>,
+}
+
+fn include_range(range: &mut Option<Range<usize>>, other: Range<usize>) {
+    *range = Some(match range.take() {
+        Some(range) => range.start.min(other.start)..range.end.max(other.end),
+        None => other,
+    });
+}
+
 // TODO: allow values to be unloaded after been submitting to the GPU to conserve memory
 #[derive(Debug, TypeUuid)]
 #[uuid = "8ecbac0f-f545-4473-ad43-e1f4243af51e"]
@@ -187,18 +286,24 @@ pub struct Mesh {
     /// `bevy_utils::HashMap` with all defined vertex attributes (Positions, Normals, ...) for this mesh. Attribute name maps to attribute values.
     attributes: HashMap<Cow<'static, str>, VertexAttributeValues>,
     indices: Option<Indices>,
+    /// `None` until the buffers of the mesh are created, and after changes that need them to be
+    /// created again
+    pending_updates: Option<MeshUpdates>,
 }
 
 impl Mesh {
     pub const ATTRIBUTE_NORMAL: &'static str = "Vertex_Normal";
     pub const ATTRIBUTE_POSITION: &'static str = "Vertex_Position";
     pub const ATTRIBUTE_UV_0: &'static str = "Vertex_Uv";
+    /// A linear RGBA color per vertex, that the sprite shaders multiply the color of the sprite by
+    pub const ATTRIBUTE_COLOR: &'static str = "Vertex_Color";
 
     pub fn new(primitive_topology: PrimitiveTopology) -> Self {
         Mesh {
             primitive_topology,
             attributes: Default::default(),
             indices: None,
+            pending_updates: None,
         }
     }
 
@@ -213,6 +318,40 @@ impl Mesh {
     ) {
         let values: VertexAttributeValues = values.into();
         self.attributes.insert(name.into(), values);
+        self.pending_updates = None;
+    }
+
+    /// Overwrites the values of the attribute `name` starting at vertex `start`. Unlike
+    /// [Mesh::set_attribute], only the updated vertices are written to the existing vertex buffer
+    /// of the mesh, so parts of large dynamic meshes can be changed every frame cheaply.
+    ///
+    /// Panics if the mesh has no attribute `name`, if its values have a different format, or if
+    /// `values` don't fit in it.
+    pub fn update_attribute(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        start: usize,
+        values: impl Into<VertexAttributeValues>,
+    ) {
+        let name = name.into();
+        let values: VertexAttributeValues = values.into();
+        let attribute = self
+            .attributes
+            .get_mut(&name)
+            .unwrap_or_else(|| panic!("The mesh has no attribute {}.", name));
+        let end = start + values.len();
+        assert!(
+            end <= attribute.len(),
+            "Can't update vertices {}..{} of attribute {}, which has {} vertices.",
+            start,
+            end,
+            name,
+            attribute.len()
+        );
+        attribute.write(start, &values);
+        if let Some(updates) = self.pending_updates.as_mut() {
+            include_range(&mut updates.vertices, start..end);
+        }
     }
 
     pub fn attribute(&self, name: impl Into<Cow<'static, str>>) -> Option<&VertexAttributeValues> {
@@ -221,6 +360,49 @@ impl Mesh {
 
     pub fn set_indices(&mut self, indices: Option<Indices>) {
         self.indices = indices;
+        self.pending_updates = None;
+    }
+
+    /// Overwrites the indices starting at `start`, and only writes them to the existing index
+    /// buffer of the mesh, like [Mesh::update_attribute] does for vertices.
+    ///
+    /// Panics if the mesh has no indices, if they have a different format, or if `indices` don't
+    /// fit in them.
+    pub fn update_indices(&mut self, start: usize, indices: Indices) {
+        let current_indices = self
+            .indices
+            .as_mut()
+            .expect("The mesh has no indices to update.");
+        let end = start + indices.len();
+        assert!(
+            end <= current_indices.len(),
+            "Can't update indices {}..{} of a mesh with {} indices.",
+            start,
+            end,
+            current_indices.len()
+        );
+        match (current_indices, indices) {
+            (Indices::U16(current_indices), Indices::U16(indices)) => {
+                current_indices[start..end].copy_from_slice(&indices)
+            }
+            (Indices::U32(current_indices), Indices::U32(indices)) => {
+                current_indices[start..end].copy_from_slice(&indices)
+            }
+            (current_indices, indices) => panic!(
+                "Can't write {:?} indices to {:?} indices.",
+                IndexFormat::from(&indices),
+                IndexFormat::from(&*current_indices)
+            ),
+        }
+        if let Some(updates) = self.pending_updates.as_mut() {
+            include_range(&mut updates.indices, start..end);
+        }
+    }
+
+    /// The updates that haven't been written to the buffers of the mesh yet, or `None` if its
+    /// buffers will be created from scratch
+    pub fn pending_updates(&self) -> Option<&MeshUpdates> {
+        self.pending_updates.as_ref()
     }
 
     pub fn indices(&self) -> Option<&Indices> {
@@ -228,10 +410,9 @@ impl Mesh {
     }
 
     pub fn get_index_buffer_bytes(&self) -> Option<Vec<u8>> {
-        self.indices.as_ref().map(|indices| match &indices {
-            Indices::U16(indices) => indices.as_slice().as_bytes().to_vec(),
-            Indices::U32(indices) => indices.as_slice().as_bytes().to_vec(),
-        })
+        self.indices
+            .as_ref()
+            .map(|indices| indices.get_bytes().to_vec())
     }
 
     pub fn get_vertex_buffer_descriptor(&self) -> VertexBufferDescriptor {
@@ -270,21 +451,31 @@ impl Mesh {
         vertex_count.unwrap_or(0)
     }
 
-    pub fn get_vertex_buffer_data(&self) -> Vec<u8> {
+    fn vertex_size(&self) -> usize {
         let mut vertex_size = 0;
         for attribute_values in self.attributes.values() {
             let vertex_format = VertexFormat::from(attribute_values);
             vertex_size += vertex_format.get_size() as usize;
         }
+        vertex_size
+    }
+
+    pub fn get_vertex_buffer_data(&self) -> Vec<u8> {
+        self.get_vertex_buffer_data_range(0..self.count_vertices())
+    }
 
-        let vertex_count = self.count_vertices();
-        let mut attributes_interleaved_buffer = vec![0; vertex_count * vertex_size];
+    /// The interleaved data of the vertices in `vertices`, as it is laid out in the vertex buffer
+    /// from `vertices.start` times the stride of the vertex buffer
+    pub fn get_vertex_buffer_data_range(&self, vertices: Range<usize>) -> Vec<u8> {
+        let vertex_size = self.vertex_size();
+        let mut attributes_interleaved_buffer = vec![0; vertices.len() * vertex_size];
         // bundle into interleaved buffers
         let mut attribute_offset = 0;
         for attribute_values in self.attributes.values() {
             let vertex_format = VertexFormat::from(attribute_values);
             let attribute_size = vertex_format.get_size() as usize;
-            let attributes_bytes = attribute_values.get_bytes();
+            let attributes_bytes = &attribute_values.get_bytes()
+                [vertices.start * attribute_size..vertices.end * attribute_size];
             for (vertex_index, attribute_bytes) in
                 attributes_bytes.chunks_exact(attribute_size).enumerate()
             {
@@ -320,6 +511,64 @@ fn remove_current_mesh_resources(
     remove_resource_save(render_resource_context, handle, INDEX_BUFFER_ASSET_INDEX);
 }
 
+/// Writes the vertices and indices updated since the buffers of the mesh were last written to its
+/// existing buffers. Returns false if the buffers have to be created again instead.
+fn write_mesh_updates(
+    render_resource_context: &dyn RenderResourceContext,
+    mesh: &Mesh,
+    handle: &Handle<Mesh>,
+) -> bool {
+    let updates = match mesh.pending_updates() {
+        Some(updates) => updates,
+        None => return false,
+    };
+    let (vertex_buffer, index_buffer) = match (
+        render_resource_context.get_asset_resource(handle, VERTEX_ATTRIBUTE_BUFFER_ID),
+        render_resource_context.get_asset_resource(handle, INDEX_BUFFER_ASSET_INDEX),
+    ) {
+        (
+            Some(RenderResourceId::Buffer(vertex_buffer)),
+            Some(RenderResourceId::Buffer(index_buffer)),
+        ) => (vertex_buffer, index_buffer),
+        _ => return false,
+    };
+    let indices = match mesh.indices() {
+        Some(indices) => indices,
+        None => return false,
+    };
+
+    if let Some(vertices) = updates
+        .vertices
+        .clone()
+        .filter(|vertices| !vertices.is_empty())
+    {
+        // the vertex size is a multiple of 4, as every vertex format is
+        let offset = vertices.start * mesh.vertex_size();
+        render_resource_context.queue_write_buffer(
+            vertex_buffer,
+            offset as u64,
+            &mesh.get_vertex_buffer_data_range(vertices),
+        );
+    }
+    if let Some(updated_indices) = updates
+        .indices
+        .clone()
+        .filter(|indices| !indices.is_empty())
+    {
+        // writes have to start at a multiple of 4, so 16 bit indices may include their neighbors
+        let bytes = indices.get_bytes();
+        let index_size = bytes.len() / indices.len();
+        let start = (updated_indices.start * index_size) & !3;
+        let end = (updated_indices.end * index_size + 3) & !3;
+        render_resource_context.queue_write_buffer(
+            index_buffer,
+            start as u64,
+            &bytes[start..end.min(bytes.len())],
+        );
+    }
+    true
+}
+
 #[derive(Default)]
 pub struct MeshEntities {
     entities: HashSet<Entity>,
@@ -329,12 +578,14 @@ pub struct MeshEntities {
 pub struct MeshResourceProviderState {
     mesh_event_reader: EventReader<AssetEvent<Mesh>>,
     mesh_entities: HashMap<Handle<Mesh>, MeshEntities>,
+    vertex_fallback_buffer: Option<BufferId>,
 }
 
 pub fn mesh_resource_provider_system(
     mut state: Local<MeshResourceProviderState>,
     render_resource_context: Res<Box<dyn RenderResourceContext>>,
-    meshes: Res<Assets<Mesh>>,
+    mut render_resource_bindings: ResMut<RenderResourceBindings>,
+    mut meshes: ResMut<Assets<Mesh>>,
     mesh_events: Res<Events<AssetEvent<Mesh>>>,
     mut queries: QuerySet<(
         Query<&mut RenderPipelines, With<Handle<Mesh>>>,
@@ -343,6 +594,19 @@ pub fn mesh_resource_provider_system(
 ) {
     let mut changed_meshes = HashSet::default();
     let render_resource_context = &**render_resource_context;
+    let vertex_fallback_buffer = *state.vertex_fallback_buffer.get_or_insert_with(|| {
+        render_resource_context.create_buffer_with_data(
+            BufferInfo {
+                buffer_usage: BufferUsage::VERTEX,
+                ..Default::default()
+            },
+            VERTEX_FALLBACK_DATA.as_bytes(),
+        )
+    });
+    // drawables without a mesh entity, like text, bind it from the global bindings
+    if render_resource_bindings.vertex_fallback_buffer != Some(vertex_fallback_buffer) {
+        render_resource_bindings.vertex_fallback_buffer = Some(vertex_fallback_buffer);
+    }
     for event in state.mesh_event_reader.iter(&mesh_events) {
         match event {
             AssetEvent::Created { ref handle } => {
@@ -350,7 +614,6 @@ pub fn mesh_resource_provider_system(
             }
             AssetEvent::Modified { ref handle } => {
                 changed_meshes.insert(handle.clone_weak());
-                remove_current_mesh_resources(render_resource_context, handle);
             }
             AssetEvent::Removed { ref handle } => {
                 remove_current_mesh_resources(render_resource_context, handle);
@@ -364,10 +627,16 @@ pub fn mesh_resource_provider_system(
     // update changed mesh data
     for changed_mesh_handle in changed_meshes.iter() {
         if let Some(mesh) = meshes.get(changed_mesh_handle) {
+            // meshes that were only updated in place keep their buffers
+            if write_mesh_updates(render_resource_context, mesh, changed_mesh_handle) {
+                continue;
+            }
+            remove_current_mesh_resources(render_resource_context, changed_mesh_handle);
+
             // TODO: check for individual buffer changes in non-interleaved mode
             let index_buffer = render_resource_context.create_buffer_with_data(
                 BufferInfo {
-                    buffer_usage: BufferUsage::INDEX,
+                    buffer_usage: BufferUsage::INDEX | BufferUsage::COPY_DST,
                     ..Default::default()
                 },
                 &mesh.get_index_buffer_bytes().unwrap(),
@@ -385,7 +654,7 @@ pub fn mesh_resource_provider_system(
                 changed_mesh_handle,
                 RenderResourceId::Buffer(render_resource_context.create_buffer_with_data(
                     BufferInfo {
-                        buffer_usage: BufferUsage::VERTEX,
+                        buffer_usage: BufferUsage::VERTEX | BufferUsage::COPY_DST,
                         ..Default::default()
                     },
                     &interleaved_buffer,
@@ -400,6 +669,7 @@ pub fn mesh_resource_provider_system(
                             render_resource_context,
                             mesh,
                             changed_mesh_handle,
+                            vertex_fallback_buffer,
                             render_pipelines,
                         );
                     }
@@ -408,6 +678,13 @@ pub fn mesh_resource_provider_system(
         }
     }
 
+    // later updates are written to the buffers that were just written or created
+    for changed_mesh_handle in changed_meshes.iter() {
+        if let Some(mesh) = meshes.get_mut_untracked(changed_mesh_handle) {
+            mesh.pending_updates = Some(MeshUpdates::default());
+        }
+    }
+
     // handover buffers to pipeline
     for (entity, handle, render_pipelines) in queries.q1_mut().iter_mut() {
         let mesh_entities = state
@@ -416,7 +693,13 @@ pub fn mesh_resource_provider_system(
             .or_insert_with(MeshEntities::default);
         mesh_entities.entities.insert(entity);
         if let Some(mesh) = meshes.get(handle) {
-            update_entity_mesh(render_resource_context, mesh, handle, render_pipelines);
+            update_entity_mesh(
+                render_resource_context,
+                mesh,
+                handle,
+                vertex_fallback_buffer,
+                render_pipelines,
+            );
         }
     }
 }
@@ -425,6 +708,7 @@ fn update_entity_mesh(
     render_resource_context: &dyn RenderResourceContext,
     mesh: &Mesh,
     handle: &Handle<Mesh>,
+    vertex_fallback_buffer: BufferId,
     mut render_pipelines: Mut<RenderPipelines>,
 ) {
     for render_pipeline in render_pipelines.pipelines.iter_mut() {
@@ -453,4 +737,46 @@ fn update_entity_mesh(
         // set index buffer into binding
         render_pipelines.bindings.vertex_attribute_buffer = Some(vertex_attribute_buffer_resource);
     }
+    render_pipelines.bindings.vertex_fallback_buffer = Some(vertex_fallback_buffer);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_vertex_and_index_ranges() {
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, vec![[0.0f32; 3]; 4]);
+        mesh.set_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0f32; 2]; 4]);
+        mesh.set_indices(Some(Indices::U16(vec![0, 1, 2, 2, 3, 0])));
+        assert_eq!(mesh.pending_updates(), None);
+        // as after the buffers of the mesh are created
+        mesh.pending_updates = Some(MeshUpdates::default());
+
+        mesh.update_attribute(Mesh::ATTRIBUTE_UV_0, 2, vec![[1.0f32, 1.0]]);
+        mesh.update_attribute(Mesh::ATTRIBUTE_UV_0, 1, vec![[0.5f32, 0.5]]);
+        mesh.update_indices(3, Indices::U16(vec![1]));
+        assert_eq!(
+            mesh.pending_updates(),
+            Some(&MeshUpdates {
+                vertices: Some(1..3),
+                indices: Some(3..4),
+            })
+        );
+        match mesh.indices() {
+            Some(Indices::U16(indices)) => assert_eq!(indices, &[0, 1, 2, 1, 3, 0]),
+            _ => panic!("expected u16 indices"),
+        }
+
+        let vertex_size = mesh.vertex_size();
+        assert_eq!(
+            mesh.get_vertex_buffer_data_range(1..3),
+            &mesh.get_vertex_buffer_data()[vertex_size..3 * vertex_size]
+        );
+
+        // replacing an attribute changes the layout of the buffer, so it has to be created again
+        mesh.set_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0f32; 3]; 4]);
+        assert_eq!(mesh.pending_updates(), None);
+    }
 }
@@ -324,3 +324,203 @@ impl From<Icosphere> for Mesh {
         mesh
     }
 }
+
+/// A circle on the XY plane, centered on the origin.
+#[derive(Debug, Clone, Copy)]
+pub struct Circle {
+    pub radius: f32,
+    /// The number of vertices on the edge of the circle.
+    pub vertices: usize,
+}
+
+impl Circle {
+    pub fn new(radius: f32) -> Self {
+        Self {
+            radius,
+            vertices: 32,
+        }
+    }
+}
+
+impl Default for Circle {
+    fn default() -> Self {
+        Circle::new(0.5)
+    }
+}
+
+impl From<Circle> for Mesh {
+    fn from(circle: Circle) -> Self {
+        let vertices = circle.vertices.max(3);
+        let outline = (0..vertices)
+            .map(|i| {
+                let angle = i as f32 / vertices as f32 * 2.0 * std::f32::consts::PI;
+                vec2(angle.cos(), angle.sin()) * circle.radius
+            })
+            .collect::<Vec<_>>();
+        polygon_mesh(&outline)
+    }
+}
+
+/// A rectangle with rounded corners on the XY plane, centered on the origin.
+#[derive(Debug, Clone, Copy)]
+pub struct RoundedRect {
+    /// Full width and height of the rectangle.
+    pub size: Vec2,
+    /// The radius of the corners. It is clamped to half of the shorter side.
+    pub radius: f32,
+    /// The number of edges each corner is made of.
+    pub corner_segments: usize,
+}
+
+impl RoundedRect {
+    pub fn new(size: Vec2, radius: f32) -> Self {
+        Self {
+            size,
+            radius,
+            corner_segments: 8,
+        }
+    }
+}
+
+impl From<RoundedRect> for Mesh {
+    fn from(rect: RoundedRect) -> Self {
+        let half_size = rect.size / 2.0;
+        let radius = rect.radius.max(0.0).min(half_size.x).min(half_size.y);
+        let inner = half_size - Vec2::splat(radius);
+        let segments = rect.corner_segments.max(1);
+        // the corners counterclockwise from the top right one, with the angle their arc starts at
+        let corners = [
+            (vec2(inner.x, inner.y), 0.0),
+            (vec2(-inner.x, inner.y), 0.25),
+            (vec2(-inner.x, -inner.y), 0.5),
+            (vec2(inner.x, -inner.y), 0.75),
+        ];
+
+        let mut outline: Vec<Vec2> = Vec::with_capacity(4 * (segments + 1));
+        for (center, start) in corners.iter() {
+            for i in 0..=segments {
+                let angle =
+                    (start + 0.25 * i as f32 / segments as f32) * 2.0 * std::f32::consts::PI;
+                let point = *center + vec2(angle.cos(), angle.sin()) * radius;
+                // corners without straight edges between them, like the ones of capsules, share
+                // their end points
+                if outline
+                    .last()
+                    .filter(|last| last.distance_squared(point) <= 1e-8)
+                    .is_none()
+                {
+                    outline.push(point);
+                }
+            }
+        }
+        if outline.len() > 1 && outline[0].distance_squared(*outline.last().unwrap()) <= 1e-8 {
+            outline.pop();
+        }
+        polygon_mesh(&outline)
+    }
+}
+
+/// A capsule on the XY plane, centered on the origin: a vertical rectangle with half circles
+/// at its top and bottom.
+#[derive(Debug, Clone, Copy)]
+pub struct Capsule {
+    pub radius: f32,
+    /// The length of the straight part between the half circles.
+    pub length: f32,
+    /// The number of edges each half circle is made of.
+    pub segments: usize,
+}
+
+impl Capsule {
+    pub fn new(radius: f32, length: f32) -> Self {
+        Self {
+            radius,
+            length,
+            segments: 16,
+        }
+    }
+}
+
+impl From<Capsule> for Mesh {
+    fn from(capsule: Capsule) -> Self {
+        RoundedRect {
+            size: vec2(capsule.radius * 2.0, capsule.length + capsule.radius * 2.0),
+            radius: capsule.radius,
+            corner_segments: (capsule.segments / 2).max(1),
+        }
+        .into()
+    }
+}
+
+/// A mesh filling a convex outline, given counterclockwise, with a vertex at its center. The
+/// texture is stretched over the bounds of the outline, the same way it is over a [Quad].
+fn polygon_mesh(outline: &[Vec2]) -> Mesh {
+    let min = outline
+        .iter()
+        .fold(Vec2::zero(), |min, point| min.min(*point));
+    let max = outline
+        .iter()
+        .fold(Vec2::zero(), |max, point| max.max(*point));
+    let size = (max - min).max(Vec2::splat(std::f32::EPSILON));
+
+    let mut positions = Vec::with_capacity(outline.len() + 1);
+    let mut uvs = Vec::with_capacity(outline.len() + 1);
+    for point in std::iter::once(Vec2::zero()).chain(outline.iter().copied()) {
+        positions.push([point.x, point.y, 0.0]);
+        uvs.push([(point.x - min.x) / size.x, (max.y - point.y) / size.y]);
+    }
+    let normals = vec![[0.0, 0.0, 1.0]; positions.len()];
+
+    let count = outline.len() as u32;
+    let indices = (0..count)
+        .flat_map(|i| vec![0, i + 1, (i + 1) % count + 1])
+        .collect();
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.set_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.set_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::VertexAttributeValues;
+
+    fn positions(mesh: &Mesh) -> Vec<[f32; 3]> {
+        match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+            Some(VertexAttributeValues::Float3(positions)) => positions.clone(),
+            _ => panic!("the mesh has no positions"),
+        }
+    }
+
+    #[test]
+    fn shapes_2d() {
+        let circle = Mesh::from(Circle {
+            radius: 2.0,
+            vertices: 16,
+        });
+        assert_eq!(positions(&circle).len(), 17);
+        match circle.indices() {
+            Some(Indices::U32(indices)) => assert_eq!(indices.len(), 16 * 3),
+            _ => panic!("the circle has no indices"),
+        }
+
+        let rect = Mesh::from(RoundedRect::new(vec2(4.0, 2.0), 0.5));
+        let rect_positions = positions(&rect);
+        assert_eq!(rect_positions.len(), 1 + 4 * 9);
+        assert!(rect_positions
+            .iter()
+            .all(|p| p[0].abs() <= 2.0 + 1e-5 && p[1].abs() <= 1.0 + 1e-5));
+
+        // the half circles of a capsule meet without doubled vertices
+        let capsule = Mesh::from(Capsule {
+            radius: 1.0,
+            length: 0.0,
+            segments: 8,
+        });
+        assert_eq!(positions(&capsule).len(), 1 + 16);
+    }
+}
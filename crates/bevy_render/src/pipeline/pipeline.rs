@@ -78,7 +78,7 @@ impl PipelineDescriptor {
                 clamp_depth: false,
             }),
             depth_stencil_state: Some(DepthStencilStateDescriptor {
-                format: TextureFormat::Depth32Float,
+                format: TextureFormat::Depth24PlusStencil8,
                 depth_write_enabled: true,
                 depth_compare: CompareFunction::Less,
                 stencil: StencilStateDescriptor {
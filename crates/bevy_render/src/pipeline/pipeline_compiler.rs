@@ -1,8 +1,12 @@
-use super::{state_descriptors::PrimitiveTopology, IndexFormat, PipelineDescriptor};
+use super::{
+    state_descriptors::{BlendMode, PrimitiveTopology},
+    IndexFormat, PipelineDescriptor,
+};
 use crate::{
-    pipeline::{BindType, InputStepMode, VertexBufferDescriptor},
+    mesh::vertex_fallback_offset,
+    pipeline::{BindType, InputStepMode, VertexBufferDescriptor, VERTEX_FALLBACK_LAYOUT_NAME},
     renderer::RenderResourceContext,
-    shader::{Shader, ShaderError, ShaderSource},
+    shader::{Shader, ShaderCache, ShaderError, ShaderSource},
 };
 use bevy_asset::{Assets, Handle};
 use bevy_reflect::Reflect;
@@ -18,6 +22,8 @@ pub struct PipelineSpecialization {
     pub index_format: IndexFormat,
     pub vertex_buffer_descriptor: VertexBufferDescriptor,
     pub sample_count: u32,
+    /// Overrides the blending of all color states of the pipeline, if set
+    pub blend_mode: Option<BlendMode>,
 }
 
 impl Default for PipelineSpecialization {
@@ -29,6 +35,7 @@ impl Default for PipelineSpecialization {
             primitive_topology: Default::default(),
             dynamic_bindings: Default::default(),
             vertex_buffer_descriptor: Default::default(),
+            blend_mode: None,
         }
     }
 }
@@ -62,9 +69,15 @@ pub struct PipelineCompiler {
     specialized_shaders: HashMap<Handle<Shader>, Vec<SpecializedShader>>,
     specialized_shader_pipelines: HashMap<Handle<Shader>, Vec<Handle<PipelineDescriptor>>>,
     specialized_pipelines: HashMap<Handle<PipelineDescriptor>, Vec<SpecializedPipeline>>,
+    shader_cache: ShaderCache,
 }
 
 impl PipelineCompiler {
+    /// Uses the precompiled shaders of `shader_cache` instead of compiling shaders that are in it
+    pub fn set_shader_cache(&mut self, shader_cache: ShaderCache) {
+        self.shader_cache = shader_cache;
+    }
+
     fn compile_shader(
         &mut self,
         render_resource_context: &dyn RenderResourceContext,
@@ -100,8 +113,11 @@ impl PipelineCompiler {
                 .iter()
                 .cloned()
                 .collect::<Vec<String>>();
-            let compiled_shader =
-                render_resource_context.get_specialized_shader(shader, Some(&shader_def_vec))?;
+            let compiled_shader = self.shader_cache.get_specialized_shader(
+                render_resource_context,
+                shader,
+                &shader_def_vec,
+            )?;
             let specialized_handle = shaders.add(compiled_shader);
             let weak_specialized_handle = specialized_handle.clone_weak();
             specialized_shaders.push(SpecializedShader {
@@ -199,7 +215,7 @@ impl PipelineCompiler {
         }
         specialized_descriptor.layout = Some(layout);
 
-        // create a vertex layout that provides all attributes from either the specialized vertex buffers or a zero buffer
+        // create a vertex layout that provides all attributes from either the specialized vertex buffers or a fallback buffer
         let mut pipeline_layout = specialized_descriptor.layout.as_mut().unwrap();
         // the vertex buffer descriptor of the mesh
         let mesh_vertex_buffer_descriptor = &pipeline_specialization.vertex_buffer_descriptor;
@@ -210,6 +226,13 @@ impl PipelineCompiler {
             stride: mesh_vertex_buffer_descriptor.stride,
             ..Default::default()
         };
+        // attributes the mesh doesn't have are read from the same place for every vertex
+        let mut fallback_vertex_buffer_descriptor = VertexBufferDescriptor {
+            name: VERTEX_FALLBACK_LAYOUT_NAME.into(),
+            step_mode: InputStepMode::Instance,
+            stride: 0,
+            ..Default::default()
+        };
 
         for shader_vertex_attribute in pipeline_layout.vertex_buffer_descriptors.iter() {
             let shader_vertex_attribute = shader_vertex_attribute
@@ -229,22 +252,33 @@ impl PipelineCompiler {
                     .attributes
                     .push(compiled_vertex_attribute);
             } else {
-                panic!(
-                    "Attribute {} is required by shader, but not supplied by mesh. Either remove the attribute from the shader or supply the attribute ({}) to the mesh.",
-                    shader_vertex_attribute.name,
-                    shader_vertex_attribute.name,
-                );
+                let mut fallback_vertex_attribute = shader_vertex_attribute.clone();
+                fallback_vertex_attribute.offset =
+                    vertex_fallback_offset(&shader_vertex_attribute.name);
+                fallback_vertex_buffer_descriptor
+                    .attributes
+                    .push(fallback_vertex_attribute);
             }
         }
 
         //TODO: add other buffers (like instancing) here
         let mut vertex_buffer_descriptors = Vec::<VertexBufferDescriptor>::default();
         vertex_buffer_descriptors.push(compiled_vertex_buffer_descriptor);
+        if !fallback_vertex_buffer_descriptor.attributes.is_empty() {
+            vertex_buffer_descriptors.push(fallback_vertex_buffer_descriptor);
+        }
 
         pipeline_layout.vertex_buffer_descriptors = vertex_buffer_descriptors;
         specialized_descriptor.sample_count = pipeline_specialization.sample_count;
         specialized_descriptor.primitive_topology = pipeline_specialization.primitive_topology;
         specialized_descriptor.index_format = pipeline_specialization.index_format;
+        if let Some(blend_mode) = pipeline_specialization.blend_mode {
+            let (color_blend, alpha_blend) = blend_mode.blend_descriptors();
+            for color_state in specialized_descriptor.color_states.iter_mut() {
+                color_state.color_blend = color_blend.clone();
+                color_state.alpha_blend = alpha_blend.clone();
+            }
+        }
 
         let specialized_pipeline_handle = pipelines.add(specialized_descriptor);
         render_resource_context.create_render_pipeline(
@@ -322,11 +356,11 @@ impl PipelineCompiler {
                     .iter()
                     .cloned()
                     .collect::<Vec<String>>();
-                let new_handle =
-                    shaders.add(render_resource_context.get_specialized_shader(
-                        shaders.get(shader).unwrap(),
-                        Some(&shader_def_vec),
-                    )?);
+                let new_handle = shaders.add(self.shader_cache.get_specialized_shader(
+                    render_resource_context,
+                    shaders.get(shader).unwrap(),
+                    &shader_def_vec,
+                )?);
 
                 // Replace handle and remove old from assets.
                 let old_handle = std::mem::replace(&mut specialized_shader.shader, new_handle);
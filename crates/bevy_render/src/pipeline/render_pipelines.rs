@@ -1,6 +1,6 @@
 use super::{PipelineDescriptor, PipelineSpecialization};
 use crate::{
-    draw::{Draw, DrawContext},
+    draw::{Draw, DrawContext, ScissorRect, StencilReference},
     mesh::{Indices, Mesh},
     prelude::{Msaa, Visible},
     renderer::RenderResourceBindings,
@@ -82,9 +82,18 @@ pub fn draw_render_pipelines_system(
     mut render_resource_bindings: ResMut<RenderResourceBindings>,
     msaa: Res<Msaa>,
     meshes: Res<Assets<Mesh>>,
-    mut query: Query<(&mut Draw, &mut RenderPipelines, &Handle<Mesh>, &Visible)>,
+    mut query: Query<(
+        &mut Draw,
+        &mut RenderPipelines,
+        &Handle<Mesh>,
+        &Visible,
+        Option<&ScissorRect>,
+        Option<&StencilReference>,
+    )>,
 ) {
-    for (mut draw, mut render_pipelines, mesh_handle, visible) in query.iter_mut() {
+    for (mut draw, mut render_pipelines, mesh_handle, visible, scissor_rect, stencil_reference) in
+        query.iter_mut()
+    {
         if !visible.is_visible {
             continue;
         }
@@ -102,6 +111,13 @@ pub fn draw_render_pipelines_system(
             None => None,
         };
 
+        if let Some(scissor_rect) = scissor_rect {
+            draw.set_scissor_rect(scissor_rect);
+        }
+        if let Some(stencil_reference) = stencil_reference {
+            draw.set_stencil_reference(stencil_reference.0);
+        }
+
         let render_pipelines = &mut *render_pipelines;
         for pipeline in render_pipelines.pipelines.iter_mut() {
             pipeline.specialization.sample_count = msaa.samples;
@@ -18,6 +18,34 @@ pub struct StencilStateDescriptor {
     pub write_mask: u32,
 }
 
+impl StencilStateDescriptor {
+    /// Neither reads nor writes the stencil buffer
+    pub const IGNORE: Self = StencilStateDescriptor {
+        front: StencilStateFaceDescriptor::IGNORE,
+        back: StencilStateFaceDescriptor::IGNORE,
+        read_mask: 0,
+        write_mask: 0,
+    };
+
+    /// Writes the stencil reference of each draw to the pixels it covers, which marks the shape of
+    /// a mask, like the circle of a minimap
+    pub const WRITE_REFERENCE: Self = StencilStateDescriptor {
+        front: StencilStateFaceDescriptor::WRITE_REFERENCE,
+        back: StencilStateFaceDescriptor::WRITE_REFERENCE,
+        read_mask: 0,
+        write_mask: !0,
+    };
+
+    /// Only draws the pixels whose stencil value equals the stencil reference of the draw, which
+    /// clips draws to a mask written with [StencilStateDescriptor::WRITE_REFERENCE]
+    pub const EQUAL_REFERENCE: Self = StencilStateDescriptor {
+        front: StencilStateFaceDescriptor::EQUAL_REFERENCE,
+        back: StencilStateFaceDescriptor::EQUAL_REFERENCE,
+        read_mask: !0,
+        write_mask: 0,
+    };
+}
+
 #[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
 pub enum StencilOperation {
     Keep = 0,
@@ -45,6 +73,20 @@ impl StencilStateFaceDescriptor {
         depth_fail_op: StencilOperation::Keep,
         pass_op: StencilOperation::Keep,
     };
+
+    pub const WRITE_REFERENCE: Self = StencilStateFaceDescriptor {
+        compare: CompareFunction::Always,
+        fail_op: StencilOperation::Keep,
+        depth_fail_op: StencilOperation::Keep,
+        pass_op: StencilOperation::Replace,
+    };
+
+    pub const EQUAL_REFERENCE: Self = StencilStateFaceDescriptor {
+        compare: CompareFunction::Equal,
+        fail_op: StencilOperation::Keep,
+        depth_fail_op: StencilOperation::Keep,
+        pass_op: StencilOperation::Keep,
+    };
 }
 
 #[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
@@ -132,6 +174,71 @@ impl BlendDescriptor {
     };
 }
 
+/// Common ways of blending the colors a pipeline draws with the colors of its target. Pipelines
+/// specialized with a blend mode use it for all of their color states, see
+/// [PipelineSpecialization::blend_mode](super::PipelineSpecialization::blend_mode).
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize, Reflect)]
+pub enum BlendMode {
+    /// Replaces the color of the target, ignoring alpha
+    Opaque,
+    /// Mixes the color with the color of the target by its alpha
+    AlphaBlend,
+    /// Adds the color, scaled by its alpha, to the color of the target, which brightens it, like
+    /// light or fire particles do
+    Additive,
+    /// Multiplies the color of the target with the color, which darkens it, like shadows do
+    Multiply,
+}
+
+impl BlendMode {
+    /// The color and alpha blend descriptors of this mode
+    pub fn blend_descriptors(self) -> (BlendDescriptor, BlendDescriptor) {
+        // all but opaque keep the alpha of the target, except for alpha blending which adds to it
+        let keep_alpha = BlendDescriptor {
+            src_factor: BlendFactor::Zero,
+            dst_factor: BlendFactor::One,
+            operation: BlendOperation::Add,
+        };
+        match self {
+            BlendMode::Opaque => (BlendDescriptor::REPLACE, BlendDescriptor::REPLACE),
+            BlendMode::AlphaBlend => (
+                BlendDescriptor {
+                    src_factor: BlendFactor::SrcAlpha,
+                    dst_factor: BlendFactor::OneMinusSrcAlpha,
+                    operation: BlendOperation::Add,
+                },
+                BlendDescriptor {
+                    src_factor: BlendFactor::One,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+            ),
+            BlendMode::Additive => (
+                BlendDescriptor {
+                    src_factor: BlendFactor::SrcAlpha,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+                keep_alpha,
+            ),
+            BlendMode::Multiply => (
+                BlendDescriptor {
+                    src_factor: BlendFactor::DstColor,
+                    dst_factor: BlendFactor::Zero,
+                    operation: BlendOperation::Add,
+                },
+                keep_alpha,
+            ),
+        }
+    }
+
+    /// Whether drawing with this mode depends on what was drawn before, so entities using it
+    /// have to be drawn back to front, after opaque entities
+    pub fn is_transparent(self) -> bool {
+        self != BlendMode::Opaque
+    }
+}
+
 bitflags::bitflags! {
     #[repr(transparent)]
     pub struct ColorWrite: u32 {
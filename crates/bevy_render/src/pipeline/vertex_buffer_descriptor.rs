@@ -6,6 +6,10 @@ use std::{
     hash::{Hash, Hasher},
 };
 
+/// The name of the vertex buffer that attributes a shader requires, but a mesh doesn't have, are
+/// read from
+pub const VERTEX_FALLBACK_LAYOUT_NAME: &str = "Fallback";
+
 #[derive(Clone, Debug, Eq, PartialEq, Default, Reflect, Serialize, Deserialize)]
 #[reflect_value(Serialize, Deserialize, PartialEq)]
 pub struct VertexBufferDescriptor {
@@ -1,6 +1,6 @@
 use super::{
-    CameraNode, PassNode, RenderGraph, SharedBuffersNode, TextureCopyNode, WindowSwapChainNode,
-    WindowTextureNode,
+    CameraNode, GlobalsNode, PassNode, RenderGraph, SharedBuffersNode, TextureCopyNode,
+    WindowSwapChainNode, WindowTextureNode,
 };
 use crate::{
     pass::{
@@ -71,6 +71,7 @@ pub mod node {
     pub const MAIN_SAMPLED_COLOR_ATTACHMENT: &str = "main_pass_sampled_color_attachment";
     pub const MAIN_PASS: &str = "main_pass";
     pub const SHARED_BUFFERS: &str = "shared_buffers";
+    pub const GLOBALS: &str = "globals";
 }
 
 pub mod camera {
@@ -95,11 +96,31 @@ impl Default for BaseRenderGraphConfig {
 /// set of nodes. It can be customized using `BaseRenderGraphConfig`.
 pub trait BaseRenderGraphBuilder {
     fn add_base_graph(&mut self, config: &BaseRenderGraphConfig, msaa: &Msaa) -> &mut Self;
+
+    /// Adds a camera that draws in the main pass, such as the second camera of a split-screen
+    /// view. Its [Camera](crate::camera::Camera) must be named `camera_name`, and the name must
+    /// also be added to [ActiveCameras](crate::camera::ActiveCameras). Cameras draw in the order
+    /// they are added.
+    fn add_main_pass_camera(&mut self, camera_name: &str) -> &mut Self;
 }
 
 impl BaseRenderGraphBuilder for RenderGraph {
+    fn add_main_pass_camera(&mut self, camera_name: &str) -> &mut Self {
+        self.add_system_node(
+            camera_name.to_string(),
+            CameraNode::new(camera_name.to_string()),
+        );
+        self.get_node_mut::<PassNode<&MainPass>>(node::MAIN_PASS)
+            .unwrap()
+            .add_camera(camera_name);
+        self.add_node_edge(camera_name.to_string(), node::MAIN_PASS)
+            .unwrap();
+        self
+    }
+
     fn add_base_graph(&mut self, config: &BaseRenderGraphConfig, msaa: &Msaa) -> &mut Self {
         self.add_node(node::TEXTURE_COPY, TextureCopyNode::default());
+        self.add_system_node(node::GLOBALS, GlobalsNode::default());
         if config.add_3d_camera {
             self.add_system_node(node::CAMERA_3D, CameraNode::new(camera::CAMERA_3D));
         }
@@ -123,7 +144,7 @@ impl BaseRenderGraphBuilder for RenderGraph {
                         mip_level_count: 1,
                         sample_count: msaa.samples,
                         dimension: TextureDimension::D2,
-                        format: TextureFormat::Depth32Float, // PERF: vulkan docs recommend using 24 bit depth for better performance
+                        format: TextureFormat::Depth24PlusStencil8,
                         usage: TextureUsage::OUTPUT_ATTACHMENT,
                     },
                 ),
@@ -146,7 +167,10 @@ impl BaseRenderGraphBuilder for RenderGraph {
                         load: LoadOp::Clear(1.0),
                         store: true,
                     }),
-                    stencil_ops: None,
+                    stencil_ops: Some(Operations {
+                        load: LoadOp::Clear(0),
+                        store: true,
+                    }),
                 }),
                 sample_count: msaa.samples,
             });
@@ -167,6 +191,7 @@ impl BaseRenderGraphBuilder for RenderGraph {
                 .unwrap();
             self.add_node_edge(node::SHARED_BUFFERS, node::MAIN_PASS)
                 .unwrap();
+            self.add_node_edge(node::GLOBALS, node::MAIN_PASS).unwrap();
 
             if config.add_3d_camera {
                 self.add_node_edge(node::CAMERA_3D, node::MAIN_PASS)
@@ -1,7 +1,10 @@
-use super::{Edge, Node, NodeId, NodeLabel, NodeState, RenderGraphError, SlotLabel, SystemNode};
+use super::{
+    Edge, Node, NodeId, NodeLabel, NodeState, RenderGraphError, ResourceSlots, SlotLabel,
+    SystemNode,
+};
 use bevy_ecs::{Commands, Schedule, SystemStage};
 use bevy_utils::HashMap;
-use std::{borrow::Cow, fmt::Debug};
+use std::{borrow::Cow, fmt::Debug, fmt::Write};
 pub struct RenderGraph {
     nodes: HashMap<NodeId, NodeState>,
     node_names: HashMap<Cow<'static, str>, NodeId>,
@@ -277,6 +280,90 @@ impl RenderGraph {
     pub fn take_commands(&mut self) -> Commands {
         std::mem::take(&mut self.commands)
     }
+
+    /// Returns the nodes and edges of this graph in the Graphviz DOT format. Nodes are drawn with
+    /// their input slots on the left and output slots on the right. Slot edges are drawn between
+    /// slots, and node edges are drawn as dashed lines between nodes.
+    pub fn dot(&self) -> String {
+        let mut nodes = self.iter_nodes().collect::<Vec<_>>();
+        nodes.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+        let node_indices = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.id, index))
+            .collect::<HashMap<_, _>>();
+
+        let mut dot = String::new();
+        writeln!(dot, "digraph render_graph {{").unwrap();
+        writeln!(dot, "\trankdir=LR;").unwrap();
+        writeln!(dot, "\tnode [shape=record];").unwrap();
+        for (index, node) in nodes.iter().enumerate() {
+            let slots = |prefix: &str, slots: &ResourceSlots| {
+                slots
+                    .iter()
+                    .enumerate()
+                    .map(|(index, slot)| {
+                        format!("<{}{}> {}", prefix, index, escape_record(&slot.info.name))
+                    })
+                    .collect::<Vec<_>>()
+                    .join("|")
+            };
+            let mut label = Vec::new();
+            if !node.input_slots.is_empty() {
+                label.push(format!("{{{}}}", slots("i", &node.input_slots)));
+            }
+            label.push(escape_record(
+                node.name.as_ref().map_or("<unnamed>", |name| name.as_ref()),
+            ));
+            if !node.output_slots.is_empty() {
+                label.push(format!("{{{}}}", slots("o", &node.output_slots)));
+            }
+            writeln!(
+                dot,
+                "\tn{} [label=\"{}\"];",
+                index,
+                label.join("|").replace('"', "\\\"")
+            )
+            .unwrap();
+        }
+
+        for (index, node) in nodes.iter().enumerate() {
+            for edge in node.edges.output_edges.iter() {
+                match edge {
+                    Edge::SlotEdge {
+                        input_node,
+                        input_index,
+                        output_index,
+                        ..
+                    } => writeln!(
+                        dot,
+                        "\tn{}:o{} -> n{}:i{};",
+                        index, output_index, node_indices[input_node], input_index
+                    )
+                    .unwrap(),
+                    Edge::NodeEdge { input_node, .. } => writeln!(
+                        dot,
+                        "\tn{} -> n{} [style=dashed];",
+                        index, node_indices[input_node]
+                    )
+                    .unwrap(),
+                }
+            }
+        }
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+}
+
+fn escape_record(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '{' | '}' | '|' | '<' | '>' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
 }
 
 impl Debug for RenderGraph {
@@ -475,4 +562,28 @@ mod tests {
             "Adding to a duplicate edge should return an error"
         );
     }
+
+    #[test]
+    pub fn test_dot() {
+        let mut graph = RenderGraph::default();
+
+        graph.add_node("A", TestNode::new(0, 1));
+        graph.add_node("B", TestNode::new(1, 0));
+        graph.add_node("C", TestNode::new(0, 0));
+
+        graph.add_slot_edge("A", 0, "B", 0).unwrap();
+        graph.add_node_edge("C", "B").unwrap();
+        assert_eq!(
+            graph.dot(),
+            "digraph render_graph {\n\
+            \trankdir=LR;\n\
+            \tnode [shape=record];\n\
+            \tn0 [label=\"A|{<o0> out_0}\"];\n\
+            \tn1 [label=\"{<i0> in_0}|B\"];\n\
+            \tn2 [label=\"C\"];\n\
+            \tn0:o0 -> n1:i0;\n\
+            \tn2 -> n1 [style=dashed];\n\
+            }\n"
+        );
+    }
 }
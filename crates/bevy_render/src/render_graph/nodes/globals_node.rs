@@ -0,0 +1,184 @@
+use crate::{
+    render_graph::{Node, ResourceSlots, SystemNode},
+    renderer::{
+        BufferUsage, DynamicBuffer, RenderContext, RenderResourceBinding, RenderResourceBindings,
+        RenderResourceContext,
+    },
+};
+use bevy_core::{AsBytes, Byteable, Time};
+use bevy_ecs::{Commands, IntoSystem, Local, Res, ResMut, Resources, System, World};
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+/// The name of the uniform shaders read the [GlobalsUniform] from
+pub const GLOBALS_UNIFORM: &str = "Globals";
+
+/// Values for time based effects, like animated water or lava, that every pipeline can read
+/// without any per-entity data.
+///
+/// Render passes bind it next to the camera, so shaders read it by declaring a `Globals` uniform
+/// right after the `Camera` uniform:
+///
+/// ```glsl
+/// layout(set = 0, binding = 0) uniform Camera {
+///     mat4 ViewProj;
+/// };
+/// layout(set = 0, binding = 1) uniform Globals {
+///     float Time;
+///     float DeltaTime;
+///     uint Frame;
+/// };
+/// ```
+///
+/// It can also be declared in any other bind group that is made only of global bindings.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct GlobalsUniform {
+    /// Seconds since startup. It loses precision the longer the app runs, so effects that loop
+    /// should wrap it with `mod` first.
+    pub time: f32,
+    /// Seconds since the last frame
+    pub delta_time: f32,
+    /// The number of frames since startup
+    pub frame: u32,
+}
+
+unsafe impl Byteable for GlobalsUniform {}
+
+/// A Render Graph [Node] that writes the [GlobalsUniform] to a GPU buffer every frame. The buffer
+/// is reused between frames, and written through [RenderContext::write_buffer].
+#[derive(Debug)]
+pub struct GlobalsNode {
+    buffer: Arc<Mutex<DynamicBuffer>>,
+}
+
+impl Default for GlobalsNode {
+    fn default() -> Self {
+        GlobalsNode {
+            buffer: Arc::new(Mutex::new(DynamicBuffer::new(
+                BufferUsage::UNIFORM,
+                std::mem::size_of::<GlobalsUniform>(),
+            ))),
+        }
+    }
+}
+
+impl Node for GlobalsNode {
+    fn update(
+        &mut self,
+        _world: &World,
+        _resources: &Resources,
+        render_context: &mut dyn RenderContext,
+        _input: &ResourceSlots,
+        _output: &mut ResourceSlots,
+    ) {
+        self.buffer.lock().upload(render_context);
+    }
+}
+
+impl SystemNode for GlobalsNode {
+    fn get_system(&self, commands: &mut Commands) -> Box<dyn System<In = (), Out = ()>> {
+        let system = globals_node_system.system();
+        commands.insert_local_resource(
+            system.id(),
+            GlobalsNodeState {
+                buffer: self.buffer.clone(),
+                frame: 0,
+            },
+        );
+        Box::new(system)
+    }
+}
+
+/// Local "globals node system" state
+#[derive(Debug)]
+pub struct GlobalsNodeState {
+    buffer: Arc<Mutex<DynamicBuffer>>,
+    frame: u32,
+}
+
+impl Default for GlobalsNodeState {
+    fn default() -> Self {
+        GlobalsNodeState {
+            buffer: GlobalsNode::default().buffer,
+            frame: 0,
+        }
+    }
+}
+
+pub fn globals_node_system(
+    mut state: Local<GlobalsNodeState>,
+    render_resource_context: Res<Box<dyn RenderResourceContext>>,
+    time: Res<Time>,
+    mut render_resource_bindings: ResMut<RenderResourceBindings>,
+) {
+    let render_resource_context = &**render_resource_context;
+    let globals = GlobalsUniform {
+        time: time.seconds_since_startup() as f32,
+        delta_time: time.delta_seconds(),
+        frame: state.frame,
+    };
+    state.frame = state.frame.wrapping_add(1);
+
+    let mut buffer = state.buffer.lock();
+    buffer.clear(render_resource_context);
+    let (buffer, range) = buffer.push_bytes(render_resource_context, globals.as_bytes());
+    let binding = RenderResourceBinding::Buffer {
+        buffer,
+        range,
+        dynamic_index: None,
+    };
+    // the buffer only changes when it is first created, so bind groups are usually left alone
+    if render_resource_bindings.get(GLOBALS_UNIFORM) != Some(&binding) {
+        render_resource_bindings.set(GLOBALS_UNIFORM, binding);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::HeadlessRenderResourceContext;
+    use bevy_ecs::{Stage, SystemStage};
+
+    #[test]
+    fn reuse_globals_buffer() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert::<Box<dyn RenderResourceContext>>(Box::new(
+            HeadlessRenderResourceContext::default(),
+        ));
+        resources.insert(Time::default());
+        resources.insert(RenderResourceBindings::default());
+
+        let node = GlobalsNode::default();
+        let mut commands = Commands::default();
+        let mut stage = SystemStage::serial();
+        stage.add_system_boxed(node.get_system(&mut commands));
+        commands.apply(&mut world, &mut resources);
+        stage.initialize(&mut world, &mut resources);
+
+        stage.run(&mut world, &mut resources);
+        let binding = resources
+            .get::<RenderResourceBindings>()
+            .unwrap()
+            .get(GLOBALS_UNIFORM)
+            .cloned()
+            .unwrap();
+        stage.run(&mut world, &mut resources);
+
+        // every frame writes the same buffer, which only holds the globals of that frame
+        assert_eq!(
+            resources
+                .get::<RenderResourceBindings>()
+                .unwrap()
+                .get(GLOBALS_UNIFORM),
+            Some(&binding)
+        );
+        let render_resource_context = resources.get::<Box<dyn RenderResourceContext>>().unwrap();
+        assert_eq!(render_resource_context.resource_usage().buffers, 1);
+        assert_eq!(
+            node.buffer.lock().len(),
+            std::mem::size_of::<GlobalsUniform>()
+        );
+    }
+}
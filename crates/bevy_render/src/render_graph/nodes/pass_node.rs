@@ -1,13 +1,13 @@
 use crate::{
-    camera::{ActiveCameras, VisibleEntities},
+    camera::{ActiveCameras, Camera, VisibleEntities},
     draw::{Draw, RenderCommand},
-    pass::{ClearColor, LoadOp, PassDescriptor, TextureAttachment},
+    pass::{ClearColor, LoadOp, PassDescriptor, RenderPass, TextureAttachment},
     pipeline::{
         BindGroupDescriptor, BindType, BindingDescriptor, BindingShaderStage, PipelineDescriptor,
         UniformProperty,
     },
     prelude::Visible,
-    render_graph::{Node, ResourceSlotInfo, ResourceSlots},
+    render_graph::{Node, ResourceSlotInfo, ResourceSlots, GLOBALS_UNIFORM},
     renderer::{
         BindGroup, BindGroupId, BufferId, RenderContext, RenderResourceBindings, RenderResourceType,
     },
@@ -15,12 +15,15 @@ use crate::{
 use bevy_asset::{Assets, Handle};
 use bevy_ecs::{ReadOnlyFetch, Resources, World, WorldQuery};
 use bevy_utils::tracing::debug;
+use bevy_window::Windows;
 use std::{fmt, marker::PhantomData, ops::Deref};
 
 #[derive(Debug)]
 struct CameraInfo {
     name: String,
     bind_group_id: Option<BindGroupId>,
+    /// The bind group of pipelines that read the globals next to the camera
+    globals_bind_group_id: Option<BindGroupId>,
 }
 
 pub struct PassNode<Q: WorldQuery> {
@@ -32,6 +35,7 @@ pub struct PassNode<Q: WorldQuery> {
     depth_stencil_attachment_input_index: Option<usize>,
     default_clear_color_inputs: Vec<usize>,
     camera_bind_group_descriptor: BindGroupDescriptor,
+    camera_globals_bind_group_descriptor: BindGroupDescriptor,
     _marker: PhantomData<Q>,
 }
 
@@ -61,6 +65,10 @@ impl<Q: WorldQuery> fmt::Debug for PassNode<Q> {
                 "camera_bind_group_descriptor",
                 &self.camera_bind_group_descriptor,
             )
+            .field(
+                "camera_globals_bind_group_descriptor",
+                &self.camera_globals_bind_group_descriptor,
+            )
             .finish()
     }
 }
@@ -103,17 +111,36 @@ impl<Q: WorldQuery> PassNode<Q> {
             }
         }
 
-        let camera_bind_group_descriptor = BindGroupDescriptor::new(
+        let camera_binding_descriptor = BindingDescriptor {
+            name: "Camera".to_string(),
+            index: 0,
+            bind_type: BindType::Uniform {
+                dynamic: false,
+                property: UniformProperty::Struct(vec![UniformProperty::Mat4]),
+            },
+            shader_stage: BindingShaderStage::VERTEX | BindingShaderStage::FRAGMENT,
+        };
+        let camera_bind_group_descriptor =
+            BindGroupDescriptor::new(0, vec![camera_binding_descriptor.clone()]);
+        // pipelines that declare the globals uniform right after the camera
+        let camera_globals_bind_group_descriptor = BindGroupDescriptor::new(
             0,
-            vec![BindingDescriptor {
-                name: "Camera".to_string(),
-                index: 0,
-                bind_type: BindType::Uniform {
-                    dynamic: false,
-                    property: UniformProperty::Struct(vec![UniformProperty::Mat4]),
+            vec![
+                camera_binding_descriptor,
+                BindingDescriptor {
+                    name: GLOBALS_UNIFORM.to_string(),
+                    index: 1,
+                    bind_type: BindType::Uniform {
+                        dynamic: false,
+                        property: UniformProperty::Struct(vec![
+                            UniformProperty::Float,
+                            UniformProperty::Float,
+                            UniformProperty::UInt,
+                        ]),
+                    },
+                    shader_stage: BindingShaderStage::VERTEX | BindingShaderStage::FRAGMENT,
                 },
-                shader_stage: BindingShaderStage::VERTEX | BindingShaderStage::FRAGMENT,
-            }],
+            ],
         );
 
         PassNode {
@@ -125,6 +152,7 @@ impl<Q: WorldQuery> PassNode<Q> {
             depth_stencil_attachment_input_index,
             default_clear_color_inputs: Vec::new(),
             camera_bind_group_descriptor,
+            camera_globals_bind_group_descriptor,
             _marker: PhantomData::default(),
         }
     }
@@ -133,6 +161,7 @@ impl<Q: WorldQuery> PassNode<Q> {
         self.cameras.push(CameraInfo {
             name: camera_name.to_string(),
             bind_group_id: None,
+            globals_bind_group_id: None,
         });
     }
 
@@ -157,13 +186,25 @@ where
         input: &ResourceSlots,
         _output: &mut ResourceSlots,
     ) {
+        // the attachments of a window that can't be drawn to right now are unset
+        if (0..self.inputs.len()).any(|index| input.get(index).is_none()) {
+            return;
+        }
         let render_resource_bindings = resources.get::<RenderResourceBindings>().unwrap();
         let pipelines = resources.get::<Assets<PipelineDescriptor>>().unwrap();
         let active_cameras = resources.get::<ActiveCameras>().unwrap();
+        let windows = resources.get::<Windows>();
 
+        // the pass is cleared once, so the first camera with a clear color decides it
+        let camera_clear_color = self.cameras.iter().find_map(|camera_info| {
+            let camera_entity = active_cameras.get(&camera_info.name)?;
+            world.get::<Camera>(camera_entity).ok()?.clear_color
+        });
         for (i, color_attachment) in self.descriptor.color_attachments.iter_mut().enumerate() {
             if self.default_clear_color_inputs.contains(&i) {
-                if let Some(default_clear_color) = resources.get::<ClearColor>() {
+                if let Some(clear_color) = camera_clear_color {
+                    color_attachment.ops.load = LoadOp::Clear(clear_color);
+                } else if let Some(default_clear_color) = resources.get::<ClearColor>() {
                     color_attachment.ops.load = LoadOp::Clear(default_clear_color.0);
                 }
             }
@@ -197,18 +238,38 @@ where
                 .resources()
                 .bind_group_descriptor_exists(self.camera_bind_group_descriptor.id)
             {
-                let camera_bind_group = BindGroup::build().add_binding(0, camera_binding).finish();
+                let camera_bind_group = BindGroup::build()
+                    .add_binding(0, camera_binding.clone())
+                    .finish();
                 render_context
                     .resources()
                     .create_bind_group(self.camera_bind_group_descriptor.id, &camera_bind_group);
                 camera_info.bind_group_id = Some(camera_bind_group.id);
             }
+            if let Some(globals_binding) = render_resource_bindings.get(GLOBALS_UNIFORM) {
+                if render_context
+                    .resources()
+                    .bind_group_descriptor_exists(self.camera_globals_bind_group_descriptor.id)
+                {
+                    let camera_globals_bind_group = BindGroup::build()
+                        .add_binding(0, camera_binding)
+                        .add_binding(1, globals_binding.clone())
+                        .finish();
+                    render_context.resources().create_bind_group(
+                        self.camera_globals_bind_group_descriptor.id,
+                        &camera_globals_bind_group,
+                    );
+                    camera_info.globals_bind_group_id = Some(camera_globals_bind_group.id);
+                }
+            }
         }
 
         render_context.begin_pass(
             &self.descriptor,
             &render_resource_bindings,
             &mut |render_pass| {
+                let mut viewport_set = false;
+                let mut dynamic_state = DynamicState::default();
                 for camera_info in self.cameras.iter() {
                     let camera_bind_group_id= if let Some(bind_group_id) = camera_info.bind_group_id {
                         bind_group_id
@@ -217,11 +278,36 @@ where
                     };
 
                     // get an ordered list of entities visible to the camera
-                    let visible_entities = if let Some(camera_entity) = active_cameras.get(&camera_info.name) {
-                        world.get::<VisibleEntities>(camera_entity).unwrap()
+                    let camera_entity = if let Some(camera_entity) = active_cameras.get(&camera_info.name) {
+                        camera_entity
                     } else {
                         continue;
                     };
+                    let visible_entities = world.get::<VisibleEntities>(camera_entity).unwrap();
+                    let target_size = world
+                        .get::<Camera>(camera_entity)
+                        .ok()
+                        .and_then(|camera| windows.as_ref()?.get(camera.window))
+                        .map(|window| (window.physical_width(), window.physical_height()));
+
+                    // restrict drawing to the camera's viewport, and reset it for cameras that
+                    // draw to the whole window
+                    if let Ok(camera) = world.get::<Camera>(camera_entity) {
+                        if camera.viewport.is_some() || viewport_set {
+                            let window = windows.as_ref().and_then(|windows| windows.get(camera.window));
+                            if let Some(window) = window {
+                                let [x, y, width, height] = camera
+                                    .viewport
+                                    .unwrap_or_default()
+                                    .physical_rect(window.physical_width(), window.physical_height());
+                                if width == 0.0 || height == 0.0 {
+                                    continue;
+                                }
+                                render_pass.set_viewport(x, y, width, height, 0.0, 1.0);
+                                viewport_set = camera.viewport.is_some();
+                            }
+                        }
+                    }
 
                     // attempt to draw each visible entity
                     let mut draw_state = DrawState::default();
@@ -244,6 +330,8 @@ where
                         }
 
                         // each Draw component contains an ordered list of render commands. we turn those into actual render commands here
+                        let mut scissor_rect = None;
+                        let mut stencil_reference = 0;
                         for render_command in draw.render_commands.iter() {
                             match render_command {
                                 RenderCommand::SetPipeline { pipeline } => {
@@ -257,12 +345,19 @@ where
                                     // try to set current camera bind group
                                     let layout = descriptor.get_layout().unwrap();
                                     if let Some(descriptor) = layout.get_bind_group(0) {
-                                        if *descriptor == self.camera_bind_group_descriptor {
-                                            draw_state.set_bind_group(0, camera_bind_group_id);
+                                        let bind_group_id = if *descriptor == self.camera_bind_group_descriptor {
+                                            Some(camera_bind_group_id)
+                                        } else if *descriptor == self.camera_globals_bind_group_descriptor {
+                                            camera_info.globals_bind_group_id
+                                        } else {
+                                            None
+                                        };
+                                        if let Some(bind_group_id) = bind_group_id {
+                                            draw_state.set_bind_group(0, bind_group_id);
                                             render_pass.set_bind_group(
                                                 0,
                                                 descriptor.id,
-                                                camera_bind_group_id,
+                                                bind_group_id,
                                                 None
                                             );
                                         }
@@ -274,6 +369,9 @@ where
                                     instances,
                                 } => {
                                     if draw_state.can_draw_indexed() {
+                                        if !dynamic_state.set(render_pass, scissor_rect, stencil_reference, target_size) {
+                                            continue;
+                                        }
                                         render_pass.draw_indexed(
                                             indices.clone(),
                                             *base_vertex,
@@ -285,11 +383,25 @@ where
                                 }
                                 RenderCommand::Draw { vertices, instances } => {
                                     if draw_state.can_draw() {
+                                        if !dynamic_state.set(render_pass, scissor_rect, stencil_reference, target_size) {
+                                            continue;
+                                        }
                                         render_pass.draw(vertices.clone(), instances.clone());
                                     } else {
                                         debug!("Could not draw because the pipeline layout wasn't fully set for pipeline: {:?}", draw_state.pipeline);
                                     }
                                 }
+                                RenderCommand::SetScissorRect {
+                                    x,
+                                    y,
+                                    width,
+                                    height,
+                                } => {
+                                    scissor_rect = Some([*x, *y, *width, *height]);
+                                }
+                                RenderCommand::SetStencilReference { reference } => {
+                                    stencil_reference = *reference;
+                                }
                                 RenderCommand::SetVertexBuffer {
                                     buffer,
                                     offset,
@@ -401,3 +513,56 @@ impl DrawState {
             .resize(layout.vertex_buffer_descriptors.len(), None);
     }
 }
+
+/// Tracks the scissor rect and stencil reference of a pass. They outlive the draws that set them,
+/// so they are only set right before a draw, and reset for draws of entities that don't set them.
+#[derive(Debug, Default)]
+struct DynamicState {
+    /// `None` when draws cover the whole render target
+    scissor_rect: Option<[u32; 4]>,
+    stencil_reference: u32,
+}
+
+impl DynamicState {
+    /// Sets the scissor rect and stencil reference of a draw, if they changed since the last one.
+    /// Returns false if the scissor rect is outside of the render target, so nothing would be
+    /// drawn.
+    fn set(
+        &mut self,
+        render_pass: &mut dyn RenderPass,
+        scissor_rect: Option<[u32; 4]>,
+        stencil_reference: u32,
+        target_size: Option<(u32, u32)>,
+    ) -> bool {
+        if self.stencil_reference != stencil_reference {
+            render_pass.set_stencil_reference(stencil_reference);
+            self.stencil_reference = stencil_reference;
+        }
+
+        // scissor rects have to fit in the render target, so they are clipped to its size
+        let scissor_rect = match (scissor_rect, target_size) {
+            (Some([x, y, width, height]), Some((target_width, target_height))) => {
+                let x = x.min(target_width);
+                let y = y.min(target_height);
+                let width = width.min(target_width - x);
+                let height = height.min(target_height - y);
+                if width == 0 || height == 0 {
+                    return false;
+                }
+                Some([x, y, width, height])
+            }
+            (scissor_rect, _) => scissor_rect,
+        };
+        if self.scissor_rect != scissor_rect {
+            let rect = scissor_rect.or_else(|| {
+                target_size.map(|(target_width, target_height)| [0, 0, target_width, target_height])
+            });
+            // without the size of the render target, the scissor rect of the last draw is kept
+            if let Some([x, y, width, height]) = rect {
+                render_pass.set_scissor_rect(x, y, width, height);
+                self.scissor_rect = scissor_rect;
+            }
+        }
+        true
+    }
+}
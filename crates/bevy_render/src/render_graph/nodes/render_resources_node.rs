@@ -4,9 +4,10 @@ use crate::{
     render_graph::{CommandQueue, Node, ResourceSlots, SystemNode},
     renderer::{
         self, BufferInfo, BufferUsage, RenderContext, RenderResourceBinding,
-        RenderResourceBindings, RenderResourceContext, RenderResourceHints,
+        RenderResourceBindings, RenderResourceContext, RenderResourceGc, RenderResourceHints,
+        SamplerId,
     },
-    texture,
+    texture::{self, SamplerDescriptor},
 };
 
 use bevy_app::{EventReader, Events};
@@ -127,6 +128,8 @@ where
     required_staging_buffer_size: usize,
     current_staging_buffer_offset: usize,
     queued_buffer_writes: Vec<QueuedBufferWrite>,
+    /// The buffers created for each id when dynamic uniforms are disabled
+    owned_buffers: HashMap<I, Vec<BufferId>>,
     _marker: PhantomData<T>,
 }
 
@@ -142,6 +145,7 @@ where
             current_staging_buffer_offset: 0,
             queued_buffer_writes: Vec::new(),
             required_staging_buffer_size: 0,
+            owned_buffers: Default::default(),
             _marker: Default::default(),
         }
     }
@@ -242,12 +246,16 @@ where
         }
     }
 
-    fn remove_bindings(&mut self, id: I) {
+    /// Frees the buffer array slots of `id`, and queues the buffers created for it to be released
+    fn remove_bindings(&mut self, id: I, render_resource_gc: &RenderResourceGc) {
         for buffer_array in self.buffer_arrays.iter_mut() {
             if let Some(buffer_array) = buffer_array {
                 buffer_array.remove_binding(id);
             }
         }
+        for buffer in self.owned_buffers.remove(&id).unwrap_or_default() {
+            render_resource_gc.queue(buffer);
+        }
     }
 
     fn write_uniform_buffers(
@@ -291,6 +299,9 @@ where
                                 matching_buffer = Some(buffer_id);
                             } else {
                                 render_resource_context.remove_buffer(buffer_id);
+                                if let Some(owned_buffers) = self.owned_buffers.get_mut(&id) {
+                                    owned_buffers.retain(|buffer| *buffer != buffer_id);
+                                }
                             }
                         }
                     }
@@ -310,6 +321,7 @@ where
                             buffer_usage: BufferUsage::COPY_DST | usage,
                             ..Default::default()
                         });
+                        self.owned_buffers.entry(id).or_default().push(buffer);
 
                         render_resource_bindings.set(
                             render_resource_name,
@@ -409,6 +421,7 @@ where
                 command_queue: self.command_queue.clone(),
                 uniform_buffer_arrays: UniformBufferArrays::<Entity, T>::default(),
                 dynamic_uniforms: self.dynamic_uniforms,
+                samplers: Default::default(),
             },
         );
 
@@ -420,6 +433,7 @@ struct RenderResourcesNodeState<I, T: RenderResources> {
     command_queue: CommandQueue,
     uniform_buffer_arrays: UniformBufferArrays<I, T>,
     dynamic_uniforms: bool,
+    samplers: SamplerCache,
 }
 
 impl<I, T: RenderResources> Default for RenderResourcesNodeState<I, T> {
@@ -428,7 +442,35 @@ impl<I, T: RenderResources> Default for RenderResourcesNodeState<I, T> {
             command_queue: Default::default(),
             uniform_buffer_arrays: Default::default(),
             dynamic_uniforms: Default::default(),
+            samplers: Default::default(),
+        }
+    }
+}
+
+/// The samplers created for the sampler overrides of [RenderResources]. They are shared by
+/// everything that uses the same [SamplerDescriptor].
+#[derive(Default)]
+struct SamplerCache {
+    samplers: Vec<(SamplerDescriptor, SamplerId)>,
+}
+
+impl SamplerCache {
+    fn get_or_create(
+        &mut self,
+        sampler_descriptor: &SamplerDescriptor,
+        render_resource_context: &dyn RenderResourceContext,
+    ) -> SamplerId {
+        if let Some((_, sampler)) = self
+            .samplers
+            .iter()
+            .find(|(descriptor, _)| descriptor == sampler_descriptor)
+        {
+            return *sampler;
         }
+
+        let sampler = render_resource_context.create_sampler(sampler_descriptor);
+        self.samplers.push((*sampler_descriptor, sampler));
+        sampler
     }
 }
 
@@ -436,6 +478,7 @@ fn render_resources_node_system<T: RenderResources>(
     mut state: Local<RenderResourcesNodeState<Entity, T>>,
     mut entities_waiting_for_textures: Local<Vec<Entity>>,
     render_resource_context: Res<Box<dyn RenderResourceContext>>,
+    render_resource_gc: Res<RenderResourceGc>,
     mut queries: QuerySet<(
         Query<(Entity, &T, &Visible, &mut RenderPipelines), Or<(Changed<T>, Changed<Visible>)>>,
         Query<(Entity, &T, &Visible, &mut RenderPipelines)>,
@@ -451,7 +494,7 @@ fn render_resources_node_system<T: RenderResources>(
     }
 
     for entity in queries.q0().removed::<T>() {
-        uniform_buffer_arrays.remove_bindings(*entity);
+        uniform_buffer_arrays.remove_bindings(*entity, &render_resource_gc);
     }
 
     // handle entities that were waiting for texture loads on the last update
@@ -463,6 +506,7 @@ fn render_resources_node_system<T: RenderResources>(
                 &uniforms,
                 render_resource_context,
                 &mut render_pipelines.bindings,
+                &mut state.samplers,
             ) {
                 entities_waiting_for_textures.push(entity);
             }
@@ -478,6 +522,7 @@ fn render_resources_node_system<T: RenderResources>(
             &uniforms,
             render_resource_context,
             &mut render_pipelines.bindings,
+            &mut state.samplers,
         ) {
             entities_waiting_for_textures.push(entity);
         }
@@ -592,6 +637,7 @@ where
                 command_queue: self.command_queue.clone(),
                 uniform_buffer_arrays: UniformBufferArrays::<HandleId, T>::default(),
                 dynamic_uniforms: self.dynamic_uniforms,
+                samplers: Default::default(),
             },
         );
 
@@ -621,6 +667,7 @@ fn asset_render_resources_node_system<T: RenderResources + Asset>(
     asset_events: Res<Events<AssetEvent<T>>>,
     mut asset_render_resource_bindings: ResMut<AssetRenderResourceBindings>,
     render_resource_context: Res<Box<dyn RenderResourceContext>>,
+    render_resource_gc: Res<RenderResourceGc>,
     mut queries: QuerySet<(
         Query<(&Handle<T>, &mut RenderPipelines), Changed<Handle<T>>>,
         Query<&mut RenderPipelines, With<Handle<T>>>,
@@ -645,7 +692,8 @@ fn asset_render_resources_node_system<T: RenderResources + Asset>(
                 }
             }
             AssetEvent::Removed { ref handle } => {
-                uniform_buffer_arrays.remove_bindings(handle.id);
+                uniform_buffer_arrays.remove_bindings(handle.id, &render_resource_gc);
+                asset_render_resource_bindings.remove(handle);
                 // if asset was modified and removed in the same update, ignore the modification
                 // events are ordered so future modification events are ok
                 changed_assets.remove(&handle.id);
@@ -658,8 +706,12 @@ fn asset_render_resources_node_system<T: RenderResources + Asset>(
         if let Some(asset) = assets.get(asset_handle) {
             let mut bindings =
                 asset_render_resource_bindings.get_or_insert_mut(&Handle::<T>::weak(asset_handle));
-            if !setup_uniform_texture_resources::<T>(&asset, render_resource_context, &mut bindings)
-            {
+            if !setup_uniform_texture_resources::<T>(
+                &asset,
+                render_resource_context,
+                &mut bindings,
+                &mut state.samplers,
+            ) {
                 asset_state.assets_waiting_for_textures.push(asset_handle);
             }
         }
@@ -675,7 +727,12 @@ fn asset_render_resources_node_system<T: RenderResources + Asset>(
         uniform_buffer_arrays.prepare_uniform_buffers(*asset_handle, asset);
         let mut bindings =
             asset_render_resource_bindings.get_or_insert_mut(&Handle::<T>::weak(*asset_handle));
-        if !setup_uniform_texture_resources::<T>(&asset, render_resource_context, &mut bindings) {
+        if !setup_uniform_texture_resources::<T>(
+            &asset,
+            render_resource_context,
+            &mut bindings,
+            &mut state.samplers,
+        ) {
             asset_state.assets_waiting_for_textures.push(*asset_handle);
         }
     }
@@ -754,6 +811,7 @@ fn setup_uniform_texture_resources<T>(
     uniforms: &T,
     render_resource_context: &dyn RenderResourceContext,
     render_resource_bindings: &mut RenderResourceBindings,
+    samplers: &mut SamplerCache,
 ) -> bool
 where
     T: renderer::RenderResources,
@@ -767,18 +825,23 @@ where
                 if let Some(texture_resource) = render_resource_context
                     .get_asset_resource(texture_handle, texture::TEXTURE_ASSET_INDEX)
                 {
-                    let sampler_resource = render_resource_context
-                        .get_asset_resource(texture_handle, texture::SAMPLER_ASSET_INDEX)
-                        .unwrap();
+                    let sampler = match uniforms.get_sampler(i) {
+                        Some(sampler_descriptor) => {
+                            samplers.get_or_create(sampler_descriptor, render_resource_context)
+                        }
+                        None => render_resource_context
+                            .get_asset_resource(texture_handle, texture::SAMPLER_ASSET_INDEX)
+                            .unwrap()
+                            .get_sampler()
+                            .unwrap(),
+                    };
 
                     render_resource_bindings.set(
                         render_resource_name,
                         RenderResourceBinding::Texture(texture_resource.get_texture().unwrap()),
                     );
-                    render_resource_bindings.set(
-                        &sampler_name,
-                        RenderResourceBinding::Sampler(sampler_resource.get_sampler().unwrap()),
-                    );
+                    render_resource_bindings
+                        .set(&sampler_name, RenderResourceBinding::Sampler(sampler));
                     continue;
                 } else {
                     success = false;
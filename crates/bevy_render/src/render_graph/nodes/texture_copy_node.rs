@@ -1,6 +1,6 @@
 use crate::{
     render_graph::{Node, ResourceSlots},
-    renderer::{BufferInfo, BufferUsage, RenderContext},
+    renderer::RenderContext,
     texture::{Texture, TextureDescriptor, TEXTURE_ASSET_INDEX},
 };
 use bevy_app::prelude::{EventReader, Events};
@@ -54,29 +54,19 @@ impl Node for TextureCopyNode {
                                 aligned_data[offset..(offset + width * format_size)]
                                     .copy_from_slice(row);
                             });
-                        let texture_buffer = render_context.resources().create_buffer_with_data(
-                            BufferInfo {
-                                buffer_usage: BufferUsage::COPY_SRC,
-                                ..Default::default()
-                            },
-                            &aligned_data,
-                        );
-
                         let texture_resource = render_context
                             .resources()
                             .get_asset_resource(handle, TEXTURE_ASSET_INDEX)
                             .unwrap();
 
-                        render_context.copy_buffer_to_texture(
-                            texture_buffer,
-                            0,
+                        render_context.write_texture(
+                            &aligned_data,
                             (format_size * aligned_width) as u32,
                             texture_resource.get_texture().unwrap(),
                             [0, 0, 0],
                             0,
                             texture_descriptor.size,
                         );
-                        render_context.resources().remove_buffer(texture_buffer);
 
                         copied_textures.insert(&handle.id);
                     }
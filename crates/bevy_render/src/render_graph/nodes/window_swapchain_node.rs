@@ -66,10 +66,13 @@ impl Node for WindowSwapChainNode {
             render_resource_context.create_swap_chain(window);
         }
 
-        let swap_chain_texture = render_resource_context.next_swap_chain_texture(&window);
-        output.set(
-            WINDOW_TEXTURE,
-            RenderResourceId::Texture(swap_chain_texture),
-        );
+        match render_resource_context.next_swap_chain_texture(&window) {
+            Some(swap_chain_texture) => output.set(
+                WINDOW_TEXTURE,
+                RenderResourceId::Texture(swap_chain_texture),
+            ),
+            // the passes that draw to the window are skipped until it can be drawn to again
+            None => output.get_slot_mut(WINDOW_TEXTURE).unwrap().resource = None,
+        }
     }
 }
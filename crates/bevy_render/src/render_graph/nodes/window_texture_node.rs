@@ -63,6 +63,10 @@ impl Node for WindowTextureNode {
                 .find_latest(&window_resized_events, |e| e.id == window.id())
                 .is_some()
         {
+            if window.physical_width() == 0 || window.physical_height() == 0 {
+                // a minimized window is not drawn to, so the texture is created once it is restored
+                return;
+            }
             let render_resource_context = render_context.resources_mut();
             if let Some(RenderResourceId::Texture(old_texture)) = output.get(WINDOW_TEXTURE) {
                 render_resource_context.remove_texture(old_texture);
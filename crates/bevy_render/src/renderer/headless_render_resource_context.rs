@@ -1,4 +1,4 @@
-use super::RenderResourceContext;
+use super::{RenderResourceContext, RenderResourceUsage};
 use crate::{
     pipeline::{BindGroupDescriptorId, PipelineDescriptor},
     renderer::{BindGroup, BufferId, BufferInfo, RenderResourceId, SamplerId, TextureId},
@@ -31,8 +31,8 @@ impl HeadlessRenderResourceContext {
 impl RenderResourceContext for HeadlessRenderResourceContext {
     fn create_swap_chain(&self, _window: &Window) {}
 
-    fn next_swap_chain_texture(&self, _window: &Window) -> TextureId {
-        TextureId::new()
+    fn next_swap_chain_texture(&self, _window: &Window) -> Option<TextureId> {
+        Some(TextureId::new())
     }
 
     fn drop_swap_chain_texture(&self, _render_resource: TextureId) {}
@@ -70,12 +70,22 @@ impl RenderResourceContext for HeadlessRenderResourceContext {
 
     fn unmap_buffer(&self, _id: BufferId) {}
 
+    fn map_buffer_async(&self, _id: BufferId) {}
+
+    fn try_read_mapped_buffer(&self, id: BufferId, read: &mut dyn FnMut(&[u8])) -> bool {
+        let size = self.buffer_info.read().get(&id).unwrap().size;
+        read(&vec![0; size]);
+        true
+    }
+
     fn create_buffer_with_data(&self, buffer_info: BufferInfo, _data: &[u8]) -> BufferId {
         let buffer = BufferId::new();
         self.add_buffer_info(buffer, buffer_info);
         buffer
     }
 
+    fn queue_write_buffer(&self, _id: BufferId, _offset: u64, _data: &[u8]) {}
+
     fn create_shader_module(&self, _shader_handle: &Handle<Shader>, _shaders: &Assets<Shader>) {}
 
     fn remove_buffer(&self, buffer: BufferId) {
@@ -134,6 +144,15 @@ impl RenderResourceContext for HeadlessRenderResourceContext {
         self.buffer_info.read().get(&buffer).cloned()
     }
 
+    fn resource_usage(&self) -> RenderResourceUsage {
+        let buffer_info = self.buffer_info.read();
+        RenderResourceUsage {
+            buffers: buffer_info.len(),
+            buffer_memory: buffer_info.values().map(|info| info.size).sum(),
+            textures: self.texture_descriptors.read().len(),
+        }
+    }
+
     fn bind_group_descriptor_exists(
         &self,
         _bind_group_descriptor_id: BindGroupDescriptorId,
@@ -1,9 +1,13 @@
 mod headless_render_resource_context;
+mod render_capabilities;
 mod render_context;
 mod render_resource;
 mod render_resource_context;
+mod render_resource_gc;
 
 pub use headless_render_resource_context::*;
+pub use render_capabilities::*;
 pub use render_context::*;
 pub use render_resource::*;
 pub use render_resource_context::*;
+pub use render_resource_gc::*;
@@ -0,0 +1,133 @@
+use crate::texture::{Extent3d, TextureDimension};
+use thiserror::Error;
+
+/// What the render device supports, filled in by the render backend when it creates the device.
+/// The defaults are the limits every device supports.
+///
+/// Check resources against it before creating them, so an oversized texture or pipeline is
+/// reported with a clear error instead of crashing the backend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderCapabilities {
+    /// The name of the GPU, or `None` without a render backend
+    pub adapter_name: Option<String>,
+    pub max_texture_dimension_1d: u32,
+    pub max_texture_dimension_2d: u32,
+    pub max_texture_dimension_3d: u32,
+    pub max_texture_array_layers: u32,
+    pub max_bind_groups: u32,
+    pub max_sampled_textures_per_shader_stage: u32,
+    pub max_uniform_buffer_binding_size: u32,
+    /// Whether textures can use the BC compressed formats, which desktop GPUs support
+    pub texture_compression_bc: bool,
+}
+
+impl Default for RenderCapabilities {
+    fn default() -> Self {
+        RenderCapabilities {
+            adapter_name: None,
+            max_texture_dimension_1d: 8192,
+            max_texture_dimension_2d: 8192,
+            max_texture_dimension_3d: 2048,
+            max_texture_array_layers: 256,
+            max_bind_groups: 4,
+            max_sampled_textures_per_shader_stage: 16,
+            max_uniform_buffer_binding_size: 16384,
+            texture_compression_bc: false,
+        }
+    }
+}
+
+/// A resource that exceeds the [RenderCapabilities] of the render device
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum RenderCapabilityError {
+    #[error("a {dimension:?} texture of {width}x{height}x{depth} is larger than the {max} the GPU supports")]
+    TextureTooLarge {
+        dimension: TextureDimension,
+        width: u32,
+        height: u32,
+        depth: u32,
+        max: u32,
+    },
+    #[error("a texture with {layers} array layers has more than the {max} the GPU supports")]
+    TooManyArrayLayers { layers: u32, max: u32 },
+    #[error("a pipeline with {count} bind groups uses more than the {max} the GPU supports")]
+    TooManyBindGroups { count: u32, max: u32 },
+}
+
+impl RenderCapabilities {
+    /// Checks that a texture of `size` and `dimension` can be created. For 2d textures, the depth
+    /// of `size` is the number of array layers.
+    pub fn validate_texture(
+        &self,
+        size: Extent3d,
+        dimension: TextureDimension,
+    ) -> Result<(), RenderCapabilityError> {
+        let (largest, max) = match dimension {
+            TextureDimension::D1 => (size.width, self.max_texture_dimension_1d),
+            TextureDimension::D2 => (size.width.max(size.height), self.max_texture_dimension_2d),
+            TextureDimension::D3 => (
+                size.width.max(size.height).max(size.depth),
+                self.max_texture_dimension_3d,
+            ),
+        };
+        if largest > max {
+            return Err(RenderCapabilityError::TextureTooLarge {
+                dimension,
+                width: size.width,
+                height: size.height,
+                depth: size.depth,
+                max,
+            });
+        }
+        if dimension != TextureDimension::D3 && size.depth > self.max_texture_array_layers {
+            return Err(RenderCapabilityError::TooManyArrayLayers {
+                layers: size.depth,
+                max: self.max_texture_array_layers,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks that a pipeline can use `count` bind groups
+    pub fn validate_bind_groups(&self, count: u32) -> Result<(), RenderCapabilityError> {
+        if count > self.max_bind_groups {
+            Err(RenderCapabilityError::TooManyBindGroups {
+                count,
+                max: self.max_bind_groups,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_textures() {
+        let capabilities = RenderCapabilities::default();
+        assert!(capabilities
+            .validate_texture(Extent3d::new(8192, 16, 1), TextureDimension::D2)
+            .is_ok());
+        assert_eq!(
+            capabilities.validate_texture(Extent3d::new(16, 8193, 1), TextureDimension::D2),
+            Err(RenderCapabilityError::TextureTooLarge {
+                dimension: TextureDimension::D2,
+                width: 16,
+                height: 8193,
+                depth: 1,
+                max: 8192
+            })
+        );
+        assert_eq!(
+            capabilities.validate_texture(Extent3d::new(16, 16, 300), TextureDimension::D2),
+            Err(RenderCapabilityError::TooManyArrayLayers {
+                layers: 300,
+                max: 256
+            })
+        );
+        assert!(capabilities.validate_bind_groups(5).is_err());
+    }
+}
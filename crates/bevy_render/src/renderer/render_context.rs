@@ -1,7 +1,7 @@
 use super::RenderResourceContext;
 use crate::{
     pass::{PassDescriptor, RenderPass},
-    renderer::{BufferId, RenderResourceBindings, TextureId},
+    renderer::{BufferId, BufferInfo, BufferUsage, RenderResourceBindings, TextureId},
     texture::Extent3d,
 };
 
@@ -27,6 +27,70 @@ pub trait RenderContext {
         destination_mip_level: u32,
         size: Extent3d,
     );
+    /// Copies texels to a buffer. `destination_bytes_per_row` must be a multiple of 256, even when
+    /// a single row is copied.
+    #[allow(clippy::too_many_arguments)]
+    fn copy_texture_to_buffer(
+        &mut self,
+        source_texture: TextureId,
+        source_origin: [u32; 3],
+        source_mip_level: u32,
+        destination_buffer: BufferId,
+        destination_offset: u64,
+        destination_bytes_per_row: u32,
+        size: Extent3d,
+    );
+
+    /// Uploads `data` to a texture. `bytes_per_row` must be a multiple of 256, like for
+    /// [RenderContext::copy_buffer_to_texture].
+    fn write_texture(
+        &mut self,
+        data: &[u8],
+        bytes_per_row: u32,
+        destination_texture: TextureId,
+        destination_origin: [u32; 3],
+        destination_mip_level: u32,
+        size: Extent3d,
+    ) {
+        let buffer = self.resources().create_buffer_with_data(
+            BufferInfo {
+                buffer_usage: BufferUsage::COPY_SRC,
+                ..Default::default()
+            },
+            data,
+        );
+        self.copy_buffer_to_texture(
+            buffer,
+            0,
+            bytes_per_row,
+            destination_texture,
+            destination_origin,
+            destination_mip_level,
+            size,
+        );
+        self.resources().remove_buffer(buffer);
+    }
+
+    /// Uploads `data` to a buffer at `destination_offset`. The size of `data` must be a multiple
+    /// of 4.
+    fn write_buffer(&mut self, data: &[u8], destination_buffer: BufferId, destination_offset: u64) {
+        let buffer = self.resources().create_buffer_with_data(
+            BufferInfo {
+                buffer_usage: BufferUsage::COPY_SRC,
+                ..Default::default()
+            },
+            data,
+        );
+        self.copy_buffer_to_buffer(
+            buffer,
+            0,
+            destination_buffer,
+            destination_offset,
+            data.len() as u64,
+        );
+        self.resources().remove_buffer(buffer);
+    }
+
     fn begin_pass(
         &mut self,
         pass_descriptor: &PassDescriptor,
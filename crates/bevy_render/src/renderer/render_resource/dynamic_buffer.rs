@@ -0,0 +1,150 @@
+use super::{BufferId, BufferInfo, BufferUsage};
+use crate::renderer::{RenderContext, RenderResourceContext};
+use std::ops::Range;
+
+/// A GPU buffer for data that is written again every frame, like per-sprite instance data or
+/// per-draw uniforms.
+///
+/// The data of a frame is pushed into memory the buffer keeps between frames, and uploaded at once
+/// with [RenderContext::write_buffer]. The GPU buffer is reused every frame and only replaced when
+/// the data of a frame outgrows it, so nothing is allocated, freed or mapped in a typical frame.
+#[derive(Debug)]
+pub struct DynamicBuffer {
+    buffer_usage: BufferUsage,
+    initial_capacity: usize,
+    buffer: Option<BufferId>,
+    capacity: usize,
+    data: Vec<u8>,
+    /// Buffers replaced during the frame, and the data pushed to them before
+    replaced_buffers: Vec<(BufferId, Vec<u8>)>,
+}
+
+impl DynamicBuffer {
+    /// A buffer for `buffer_usage`, starting with room for `initial_capacity` bytes
+    pub fn new(buffer_usage: BufferUsage, initial_capacity: usize) -> Self {
+        DynamicBuffer {
+            buffer_usage: buffer_usage | BufferUsage::COPY_DST,
+            initial_capacity: initial_capacity.max(4),
+            buffer: None,
+            capacity: 0,
+            data: Vec::new(),
+            replaced_buffers: Vec::new(),
+        }
+    }
+
+    /// Appends `size` bytes, filled in by `write`, and returns the buffer and the range they will
+    /// be in once uploaded. The range is only valid for the current frame.
+    pub fn push(
+        &mut self,
+        render_resource_context: &dyn RenderResourceContext,
+        size: usize,
+        write: impl FnOnce(&mut [u8]),
+    ) -> (BufferId, Range<u64>) {
+        // copies need offsets and sizes that are a multiple of 4
+        let aligned_size = (size + 3) & !3;
+        if self.data.len() + aligned_size > self.capacity {
+            self.grow(render_resource_context, aligned_size);
+        }
+        let offset = self.data.len();
+        self.data.resize(offset + aligned_size, 0);
+        write(&mut self.data[offset..offset + size]);
+        (self.buffer.unwrap(), offset as u64..(offset + size) as u64)
+    }
+
+    pub fn push_bytes(
+        &mut self,
+        render_resource_context: &dyn RenderResourceContext,
+        bytes: &[u8],
+    ) -> (BufferId, Range<u64>) {
+        self.push(render_resource_context, bytes.len(), |data| {
+            data.copy_from_slice(bytes)
+        })
+    }
+
+    /// The buffer the data is pushed to
+    pub fn buffer(&self) -> Option<BufferId> {
+        self.buffer
+    }
+
+    /// The number of bytes pushed this frame
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Uploads the data pushed this frame
+    pub fn upload(&mut self, render_context: &mut dyn RenderContext) {
+        for (buffer, data) in self.replaced_buffers.iter() {
+            render_context.write_buffer(data, *buffer, 0);
+        }
+        if let (Some(buffer), false) = (self.buffer, self.data.is_empty()) {
+            render_context.write_buffer(&self.data, buffer, 0);
+        }
+    }
+
+    /// Starts a new frame, freeing the buffers replaced during the last one
+    pub fn clear(&mut self, render_resource_context: &dyn RenderResourceContext) {
+        for (buffer, _) in self.replaced_buffers.drain(..) {
+            render_resource_context.remove_buffer(buffer);
+        }
+        self.data.clear();
+    }
+
+    fn grow(&mut self, render_resource_context: &dyn RenderResourceContext, required_size: usize) {
+        // the data pushed before still goes to the old buffer, that the bindings of this frame use
+        if let Some(buffer) = self.buffer.take() {
+            self.replaced_buffers
+                .push((buffer, std::mem::take(&mut self.data)));
+        }
+        let mut capacity = self.capacity.max(self.initial_capacity);
+        while capacity < required_size {
+            capacity *= 2;
+        }
+        if self.capacity > 0 {
+            capacity = capacity.max(self.capacity * 2);
+        }
+        self.buffer = Some(render_resource_context.create_buffer(BufferInfo {
+            size: capacity,
+            buffer_usage: self.buffer_usage,
+            mapped_at_creation: false,
+        }));
+        self.capacity = capacity;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::HeadlessRenderResourceContext;
+
+    #[test]
+    fn reuse_buffer_between_frames() {
+        let context = HeadlessRenderResourceContext::default();
+        let mut dynamic_buffer = DynamicBuffer::new(BufferUsage::VERTEX, 16);
+        let (buffer, range) = dynamic_buffer.push_bytes(&context, &[1, 2, 3]);
+        assert_eq!(range, 0..3);
+        let (_, range) = dynamic_buffer.push_bytes(&context, &[4; 8]);
+        assert_eq!(range, 4..12);
+        dynamic_buffer.clear(&context);
+
+        // the next frame uses the same buffer
+        assert_eq!(
+            dynamic_buffer.push_bytes(&context, &[5; 16]),
+            (buffer, 0..16)
+        );
+        assert_eq!(context.resource_usage().buffers, 1);
+
+        // outgrowing the buffer replaces it, and the old one is freed once the frame is done
+        let (new_buffer, range) = dynamic_buffer.push_bytes(&context, &[6; 4]);
+        assert_ne!(new_buffer, buffer);
+        assert_eq!(range, 0..4);
+        assert_eq!(context.get_buffer_info(new_buffer).unwrap().size, 32);
+        assert_eq!(context.resource_usage().buffers, 2);
+        dynamic_buffer.clear(&context);
+        assert_eq!(context.resource_usage().buffers, 1);
+        assert!(dynamic_buffer.is_empty());
+    }
+}
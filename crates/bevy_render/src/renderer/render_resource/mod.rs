@@ -1,5 +1,6 @@
 mod bind_group;
 mod buffer;
+mod dynamic_buffer;
 #[allow(clippy::module_inception)]
 mod render_resource;
 mod render_resource_bindings;
@@ -8,6 +9,7 @@ mod texture;
 
 pub use bind_group::*;
 pub use buffer::*;
+pub use dynamic_buffer::*;
 pub use render_resource::*;
 pub use render_resource_bindings::*;
 pub use shared_buffers::*;
@@ -1,5 +1,5 @@
 use super::{BufferId, SamplerId, TextureId};
-use crate::texture::Texture;
+use crate::texture::{SamplerDescriptor, Texture};
 use bevy_asset::Handle;
 
 use bevy_core::{Byteable, Bytes};
@@ -87,6 +87,10 @@ pub trait RenderResources: Send + Sync + 'static {
     fn get_render_resource_hints(&self, _index: usize) -> Option<RenderResourceHints> {
         None
     }
+    /// The sampler to bind with the texture at `index` instead of the sampler of the texture
+    fn get_sampler(&self, _index: usize) -> Option<&SamplerDescriptor> {
+        None
+    }
     fn iter(&self) -> RenderResourceIterator;
 }
 
@@ -67,7 +67,7 @@ pub struct RenderResourceBindings {
     pub bindings: HashMap<String, RenderResourceBinding>,
     /// A Buffer that contains all attributes a mesh has defined
     pub vertex_attribute_buffer: Option<BufferId>,
-    /// A Buffer that is filled with zeros that will be used for attributes required by the shader, but undefined by the mesh.
+    /// A Buffer with the fallback values of attributes required by the shader, but undefined by the mesh. See [VERTEX_FALLBACK_DATA](crate::mesh::VERTEX_FALLBACK_DATA).
     pub vertex_fallback_buffer: Option<BufferId>,
     pub index_buffer: Option<BufferId>,
     assets: HashSet<(HandleUntyped, TypeId)>,
@@ -277,6 +277,10 @@ impl AssetRenderResourceBindings {
             .or_insert_with(RenderResourceBindings::default)
     }
 
+    pub fn remove<T: Asset>(&mut self, handle: &Handle<T>) -> Option<RenderResourceBindings> {
+        self.bindings.remove(&handle.clone_weak_untyped())
+    }
+
     pub fn get_mut<T: Asset>(&mut self, handle: &Handle<T>) -> Option<&mut RenderResourceBindings> {
         self.get_mut_untyped(&handle.clone_weak_untyped())
     }
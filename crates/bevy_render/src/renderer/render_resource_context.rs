@@ -9,9 +9,20 @@ use bevy_window::Window;
 use downcast_rs::{impl_downcast, Downcast};
 use std::ops::Range;
 
+/// The GPU resources a [RenderResourceContext] currently holds
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RenderResourceUsage {
+    pub buffers: usize,
+    /// The total size of the buffers, in bytes
+    pub buffer_memory: usize,
+    pub textures: usize,
+}
+
 pub trait RenderResourceContext: Downcast + Send + Sync + 'static {
     fn create_swap_chain(&self, window: &Window);
-    fn next_swap_chain_texture(&self, window: &Window) -> TextureId;
+    /// The texture to draw the next frame of the `window` to, or `None` if the window can't be
+    /// drawn to right now, like when it is minimized or its surface was lost
+    fn next_swap_chain_texture(&self, window: &Window) -> Option<TextureId>;
     fn drop_swap_chain_texture(&self, resource: TextureId);
     fn drop_all_swap_chain_textures(&self);
     fn create_sampler(&self, sampler_descriptor: &SamplerDescriptor) -> SamplerId;
@@ -26,7 +37,21 @@ pub trait RenderResourceContext: Downcast + Send + Sync + 'static {
     );
     fn map_buffer(&self, id: BufferId);
     fn unmap_buffer(&self, id: BufferId);
+    /// Starts mapping a buffer with [BufferUsage::MAP_READ](crate::renderer::BufferUsage::MAP_READ)
+    /// for reading, without waiting for the GPU. Call it once the commands that write to the
+    /// buffer are submitted, and read the buffer with
+    /// [RenderResourceContext::try_read_mapped_buffer] in a later frame.
+    fn map_buffer_async(&self, id: BufferId);
+    /// Calls `read` with the data of a buffer mapped with
+    /// [RenderResourceContext::map_buffer_async], and unmaps it. Returns false if the buffer isn't
+    /// mapped yet. A buffer that fails to map is unmapped without calling `read`.
+    fn try_read_mapped_buffer(&self, id: BufferId, read: &mut dyn FnMut(&[u8])) -> bool;
     fn create_buffer_with_data(&self, buffer_info: BufferInfo, data: &[u8]) -> BufferId;
+    /// Writes `data` to a buffer at `offset` before the commands of the current frame are
+    /// submitted, without needing a [RenderContext](crate::renderer::RenderContext). The buffer
+    /// needs [BufferUsage::COPY_DST](crate::renderer::BufferUsage::COPY_DST), and `offset` has to
+    /// be a multiple of 4.
+    fn queue_write_buffer(&self, id: BufferId, offset: u64, data: &[u8]);
     fn create_shader_module(&self, shader_handle: &Handle<Shader>, shaders: &Assets<Shader>);
     fn create_shader_module_from_source(&self, shader_handle: &Handle<Shader>, shader: &Shader);
     fn get_specialized_shader(
@@ -38,6 +63,7 @@ pub trait RenderResourceContext: Downcast + Send + Sync + 'static {
     fn remove_texture(&self, texture: TextureId);
     fn remove_sampler(&self, sampler: SamplerId);
     fn get_buffer_info(&self, buffer: BufferId) -> Option<BufferInfo>;
+    fn resource_usage(&self) -> RenderResourceUsage;
     fn get_aligned_uniform_size(&self, size: usize, dynamic: bool) -> usize;
     fn get_aligned_texture_size(&self, data_size: usize) -> usize;
     fn set_asset_resource_untyped(
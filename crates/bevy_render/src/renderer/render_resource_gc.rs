@@ -0,0 +1,187 @@
+use super::{RenderResourceContext, RenderResourceId};
+use bevy_app::prelude::*;
+use bevy_diagnostic::{Diagnostic, DiagnosticId, Diagnostics};
+use bevy_ecs::{IntoSystem, Res, ResMut};
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+
+/// Releases the GPU resources of despawned entities and removed assets, a few at a time.
+///
+/// Render resource nodes queue the resources they created for an entity or an asset when it goes
+/// away, and [RenderResourceGc::render_resource_gc_system] releases at most
+/// `releases_per_frame` of them every frame, so unloading many chunks at once doesn't stall a
+/// frame. Resources are queued through a shared reference, so the render resource node systems
+/// that queue them can still run in parallel.
+#[derive(Debug)]
+pub struct RenderResourceGc {
+    pub releases_per_frame: usize,
+    pending: Mutex<PendingResources>,
+    released: usize,
+}
+
+#[derive(Debug, Default)]
+struct PendingResources {
+    resources: VecDeque<RenderResourceId>,
+    queued: usize,
+}
+
+impl Default for RenderResourceGc {
+    fn default() -> Self {
+        Self::new(256)
+    }
+}
+
+impl RenderResourceGc {
+    pub fn new(releases_per_frame: usize) -> Self {
+        RenderResourceGc {
+            releases_per_frame,
+            pending: Default::default(),
+            released: 0,
+        }
+    }
+
+    /// Queues `resource` to be released in a later frame. Nothing may use it anymore.
+    pub fn queue(&self, resource: impl Into<RenderResourceId>) {
+        let mut pending = self.pending.lock();
+        pending.resources.push_back(resource.into());
+        pending.queued += 1;
+    }
+
+    /// The number of resources waiting to be released
+    pub fn pending(&self) -> usize {
+        self.pending.lock().resources.len()
+    }
+
+    /// The total number of resources queued so far
+    pub fn queued(&self) -> usize {
+        self.pending.lock().queued
+    }
+
+    /// The total number of resources released so far
+    pub fn released(&self) -> usize {
+        self.released
+    }
+
+    /// Releases up to `releases_per_frame` of the oldest queued resources, and returns how many
+    /// were released
+    pub fn release(&mut self, render_resource_context: &dyn RenderResourceContext) -> usize {
+        let pending = &mut self.pending.get_mut().resources;
+        let count = pending.len().min(self.releases_per_frame);
+        for resource in pending.drain(..count) {
+            match resource {
+                RenderResourceId::Buffer(buffer) => render_resource_context.remove_buffer(buffer),
+                RenderResourceId::Texture(texture) => {
+                    render_resource_context.remove_texture(texture)
+                }
+                RenderResourceId::Sampler(sampler) => {
+                    render_resource_context.remove_sampler(sampler)
+                }
+            }
+        }
+        self.released += count;
+        count
+    }
+
+    pub fn render_resource_gc_system(
+        mut gc: ResMut<RenderResourceGc>,
+        render_resource_context: Res<Box<dyn RenderResourceContext>>,
+    ) {
+        gc.release(&**render_resource_context);
+    }
+}
+
+/// Adds diagnostics about GPU resources to an App: the number of resources waiting for the
+/// [RenderResourceGc], the number it released each frame, and the number and size of the live
+/// buffers and textures. When the GC keeps up, the live counts stay flat while entities are
+/// spawned and despawned.
+#[derive(Default)]
+pub struct RenderResourceDiagnosticsPlugin;
+
+#[derive(Default)]
+pub struct RenderResourceDiagnosticsState {
+    released: usize,
+}
+
+impl Plugin for RenderResourceDiagnosticsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_startup_system(Self::setup_system.system())
+            .init_resource::<RenderResourceDiagnosticsState>()
+            .add_system(Self::diagnostic_system.system());
+    }
+}
+
+impl RenderResourceDiagnosticsPlugin {
+    pub const PENDING_RESOURCES: DiagnosticId =
+        DiagnosticId::from_u128(301928374650192837465019283746501928375);
+    pub const RELEASED_RESOURCES: DiagnosticId =
+        DiagnosticId::from_u128(98127364501928374650192837465019283741);
+    pub const BUFFERS: DiagnosticId =
+        DiagnosticId::from_u128(209384756102938475610293847561029384756);
+    pub const BUFFER_MEMORY: DiagnosticId =
+        DiagnosticId::from_u128(140293847561029384756102938475610293847);
+    pub const TEXTURES: DiagnosticId =
+        DiagnosticId::from_u128(65019283746501928374650192837465019283);
+
+    pub fn setup_system(mut diagnostics: ResMut<Diagnostics>) {
+        diagnostics.add(Diagnostic::new(
+            Self::PENDING_RESOURCES,
+            "pending_render_resources",
+            20,
+        ));
+        diagnostics.add(Diagnostic::new(
+            Self::RELEASED_RESOURCES,
+            "released_render_resources",
+            20,
+        ));
+        diagnostics.add(Diagnostic::new(Self::BUFFERS, "buffers", 20));
+        diagnostics.add(Diagnostic::new(Self::BUFFER_MEMORY, "buffer_memory", 20));
+        diagnostics.add(Diagnostic::new(Self::TEXTURES, "textures", 20));
+    }
+
+    pub fn diagnostic_system(
+        mut state: ResMut<RenderResourceDiagnosticsState>,
+        mut diagnostics: ResMut<Diagnostics>,
+        gc: Res<RenderResourceGc>,
+        render_resource_context: Res<Box<dyn RenderResourceContext>>,
+    ) {
+        let usage = render_resource_context.resource_usage();
+        diagnostics.add_measurement(Self::PENDING_RESOURCES, gc.pending() as f64);
+        diagnostics.add_measurement(
+            Self::RELEASED_RESOURCES,
+            (gc.released() - state.released) as f64,
+        );
+        diagnostics.add_measurement(Self::BUFFERS, usage.buffers as f64);
+        diagnostics.add_measurement(Self::BUFFER_MEMORY, usage.buffer_memory as f64);
+        diagnostics.add_measurement(Self::TEXTURES, usage.textures as f64);
+        state.released = gc.released();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::{BufferInfo, HeadlessRenderResourceContext};
+
+    #[test]
+    fn release_over_several_frames() {
+        let context = HeadlessRenderResourceContext::default();
+        let mut gc = RenderResourceGc::new(2);
+        for _ in 0..5 {
+            gc.queue(context.create_buffer(BufferInfo {
+                size: 16,
+                ..Default::default()
+            }));
+        }
+        assert_eq!(context.resource_usage().buffers, 5);
+        assert_eq!(context.resource_usage().buffer_memory, 80);
+
+        assert_eq!(gc.release(&context), 2);
+        assert_eq!(gc.release(&context), 2);
+        assert_eq!(gc.pending(), 1);
+        assert_eq!(context.resource_usage().buffers, 1);
+        assert_eq!(gc.release(&context), 1);
+        assert_eq!(gc.release(&context), 0);
+        assert_eq!(context.resource_usage().buffers, 0);
+        assert_eq!((gc.queued(), gc.released()), (5, 5));
+    }
+}
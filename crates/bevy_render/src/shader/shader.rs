@@ -3,13 +3,16 @@ use crate::{
     renderer::RenderResourceContext,
 };
 
-use super::ShaderLayout;
+use super::{expand_shader_includes, shader_includes, ShaderInclude, ShaderLayout};
 use bevy_app::{EventReader, Events};
-use bevy_asset::{AssetEvent, AssetLoader, Assets, Handle, LoadContext, LoadedAsset};
+use bevy_asset::{AssetEvent, AssetLoader, AssetPath, Assets, Handle, LoadContext, LoadedAsset};
 use bevy_ecs::{Local, Res, ResMut};
 use bevy_reflect::TypeUuid;
-use bevy_utils::{tracing::error, BoxedFuture};
-use std::marker::Copy;
+use bevy_utils::{tracing::error, BoxedFuture, HashMap};
+use std::{
+    marker::Copy,
+    path::{Path, PathBuf},
+};
 use thiserror::Error;
 
 /// The stage of a shader
@@ -27,6 +30,10 @@ pub enum ShaderError {
     #[error("Shader compilation error: {0}")]
     Compilation(String),
 
+    /// A file included with `#include` doesn't exist.
+    #[error("Shader include error: {0}")]
+    Include(String),
+
     #[cfg(any(target_os = "ios", all(target_arch = "aarch64", target_os = "macos")))]
     /// shaderc error.
     #[error("shaderc error")]
@@ -138,20 +145,41 @@ impl ShaderSource {
 pub struct Shader {
     pub source: ShaderSource,
     pub stage: ShaderStage,
+    /// The files the source of a shader loaded from a file includes, which are already expanded
+    /// into its source. The shader is reloaded when one of them changes.
+    includes: Vec<Handle<ShaderInclude>>,
 }
 
 impl Shader {
     pub fn new(stage: ShaderStage, source: ShaderSource) -> Shader {
-        Shader { stage, source }
-    }
-
-    pub fn from_glsl(stage: ShaderStage, glsl: &str) -> Shader {
         Shader {
-            source: ShaderSource::Glsl(glsl.to_string()),
             stage,
+            source,
+            includes: Vec::new(),
         }
     }
 
+    pub fn from_glsl(stage: ShaderStage, glsl: &str) -> Shader {
+        Shader::new(stage, ShaderSource::Glsl(glsl.to_string()))
+    }
+
+    /// A GLSL shader with its `#include "path"` lines replaced by the sources in `includes`, by
+    /// their paths. Shaders built into the app use this to share code, as they aren't loaded
+    /// from files that can include other files.
+    pub fn from_glsl_with_includes(
+        stage: ShaderStage,
+        glsl: &str,
+        includes: &HashMap<PathBuf, String>,
+    ) -> Result<Shader, ShaderError> {
+        let source = expand_shader_includes(glsl, Path::new(""), includes)?;
+        Ok(Shader::from_glsl(stage, &source))
+    }
+
+    /// The files this shader includes, when it was loaded from a file
+    pub fn includes(&self) -> &[Handle<ShaderInclude>] {
+        &self.includes
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     pub fn get_spirv(&self, macros: Option<&[String]>) -> Result<Vec<u32>, ShaderError> {
         match self.source {
@@ -162,10 +190,10 @@ impl Shader {
 
     #[cfg(not(target_arch = "wasm32"))]
     pub fn get_spirv_shader(&self, macros: Option<&[String]>) -> Result<Shader, ShaderError> {
-        Ok(Shader {
-            source: ShaderSource::Spirv(self.get_spirv(macros)?),
-            stage: self.stage,
-        })
+        Ok(Shader::new(
+            self.stage,
+            ShaderSource::Spirv(self.get_spirv(macros)?),
+        ))
     }
 
     #[cfg(not(target_arch = "wasm32"))]
@@ -239,14 +267,37 @@ impl AssetLoader for ShaderLoader {
     ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
         Box::pin(async move {
             let ext = load_context.path().extension().unwrap().to_str().unwrap();
-
-            let shader = match ext {
-                "vert" => Shader::from_glsl(ShaderStage::Vertex, std::str::from_utf8(bytes)?),
-                "frag" => Shader::from_glsl(ShaderStage::Fragment, std::str::from_utf8(bytes)?),
+            let stage = match ext {
+                "vert" => ShaderStage::Vertex,
+                "frag" => ShaderStage::Fragment,
                 _ => panic!("unhandled extension: {}", ext),
             };
 
-            load_context.set_default_asset(LoadedAsset::new(shader));
+            // read the included files, and the files they include
+            let source = std::str::from_utf8(bytes)?;
+            let mut includes = HashMap::default();
+            let mut pending_includes = shader_includes(source, load_context.path());
+            while let Some(path) = pending_includes.pop() {
+                if includes.contains_key(&path) {
+                    continue;
+                }
+                let include = String::from_utf8(load_context.read_asset_bytes(&path).await?)?;
+                pending_includes.extend(shader_includes(&include, &path));
+                includes.insert(path, include);
+            }
+
+            let mut shader = Shader::from_glsl(
+                stage,
+                &expand_shader_includes(source, load_context.path(), &includes)?,
+            );
+            // the includes are loaded as assets too, so they are watched for changes
+            shader.includes = includes
+                .keys()
+                .map(|path| load_context.get_handle(AssetPath::from(path.as_path())))
+                .collect();
+            let dependencies = includes.keys().map(|path| path.clone().into()).collect();
+            load_context
+                .set_default_asset(LoadedAsset::new(shader).with_dependencies(dependencies));
             Ok(())
         })
     }
@@ -0,0 +1,207 @@
+use super::{Shader, ShaderError, ShaderSource, ShaderStage};
+use crate::renderer::RenderResourceContext;
+use bevy_core::AsBytes;
+use bevy_utils::HashMap;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// SPIR-V of GLSL shaders compiled ahead of time, so they don't have to be compiled at startup.
+///
+/// Shaders are cached per source, stage and set of shader defs, like the [PipelineCompiler]
+/// specializes them, and saved to a directory with a `.spv` file per specialization. The
+/// `precompile_shaders` example compiles shader files into a cache, and
+/// [PipelineCompiler::set_shader_cache] makes the pipeline compiler use it.
+///
+/// [PipelineCompiler]: crate::pipeline::PipelineCompiler
+/// [PipelineCompiler::set_shader_cache]: crate::pipeline::PipelineCompiler::set_shader_cache
+#[derive(Debug, Default, Clone)]
+pub struct ShaderCache {
+    shaders: HashMap<u64, Vec<u32>>,
+}
+
+impl ShaderCache {
+    /// Loads the `.spv` files [ShaderCache::save] wrote to `directory`
+    pub fn load<P: AsRef<Path>>(directory: P) -> io::Result<Self> {
+        let mut shaders = HashMap::default();
+        for entry in fs::read_dir(directory)? {
+            let path = entry?.path();
+            if path.extension().and_then(|extension| extension.to_str()) != Some("spv") {
+                continue;
+            }
+            let key = match path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| u64::from_str_radix(stem, 16).ok())
+            {
+                Some(key) => key,
+                None => continue,
+            };
+            if let ShaderSource::Spirv(spirv) = ShaderSource::spirv_from_bytes(&fs::read(&path)?) {
+                shaders.insert(key, spirv);
+            }
+        }
+        Ok(ShaderCache { shaders })
+    }
+
+    /// Writes the cached shaders to `directory`, creating it if needed
+    pub fn save<P: AsRef<Path>>(&self, directory: P) -> io::Result<()> {
+        let directory = directory.as_ref();
+        fs::create_dir_all(directory)?;
+        for (key, spirv) in self.shaders.iter() {
+            fs::write(
+                Self::file_path(directory, *key),
+                spirv.as_slice().as_bytes(),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Compiles a GLSL `shader` with `shader_defs`, and caches its SPIR-V. Shaders loaded from
+    /// files have their `#include`s expanded before they are cached, so `shader` has to be
+    /// expanded too, see [expand_shader_includes](super::expand_shader_includes).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn compile(&mut self, shader: &Shader, shader_defs: &[String]) -> Result<(), ShaderError> {
+        if let Some(key) = Self::key(shader, shader_defs) {
+            let spirv = shader.get_spirv(Some(shader_defs))?;
+            self.shaders.insert(key, spirv);
+        }
+        Ok(())
+    }
+
+    /// The cached SPIR-V of a GLSL `shader` compiled with `shader_defs`
+    pub fn get(&self, shader: &Shader, shader_defs: &[String]) -> Option<Shader> {
+        let spirv = self.shaders.get(&Self::key(shader, shader_defs)?)?;
+        Some(Shader::new(
+            shader.stage,
+            ShaderSource::Spirv(spirv.clone()),
+        ))
+    }
+
+    /// The cached SPIR-V of `shader` compiled with `shader_defs`, or, if it isn't cached, the
+    /// shader compiled by the `render_resource_context`
+    pub fn get_specialized_shader(
+        &self,
+        render_resource_context: &dyn RenderResourceContext,
+        shader: &Shader,
+        shader_defs: &[String],
+    ) -> Result<Shader, ShaderError> {
+        match self.get(shader, shader_defs) {
+            Some(shader) => Ok(shader),
+            None => render_resource_context.get_specialized_shader(shader, Some(shader_defs)),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.shaders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shaders.is_empty()
+    }
+
+    /// A hash of the source, stage and defs of a GLSL shader. It has to be the same in every
+    /// build and on every platform, so it uses FNV-1a instead of the hasher of [HashMap].
+    fn key(shader: &Shader, shader_defs: &[String]) -> Option<u64> {
+        let source = match shader.source {
+            ShaderSource::Glsl(ref source) => source,
+            ShaderSource::Spirv(_) => return None,
+        };
+        // the defs of a specialization are a set, so their order doesn't matter
+        let mut shader_defs = shader_defs.iter().collect::<Vec<_>>();
+        shader_defs.sort();
+        shader_defs.dedup();
+
+        let stage: &[u8] = match shader.stage {
+            ShaderStage::Vertex => b"vert",
+            ShaderStage::Fragment => b"frag",
+            ShaderStage::Compute => b"comp",
+        };
+        let mut hash = 0xcbf2_9ce4_8422_2325u64;
+        let parts = std::iter::once(stage)
+            .chain(std::iter::once(source.as_bytes()))
+            .chain(shader_defs.iter().map(|shader_def| shader_def.as_bytes()));
+        for part in parts {
+            // a terminator after every part keeps ("AB", "C") and ("A", "BC") apart
+            for byte in part.iter().chain(std::iter::once(&0xff)) {
+                hash ^= *byte as u64;
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+        Some(hash)
+    }
+
+    fn file_path(directory: &Path, key: u64) -> PathBuf {
+        directory.join(format!("{:016x}.spv", key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAGMENT_SHADER: &str = r#"
+#version 450
+layout(location = 0) out vec4 o_Target;
+void main() {
+# ifdef RED
+    o_Target = vec4(1.0, 0.0, 0.0, 1.0);
+# else
+    o_Target = vec4(1.0);
+# endif
+}
+"#;
+
+    #[test]
+    fn specialization_keys() {
+        let shader = Shader::from_glsl(ShaderStage::Fragment, FRAGMENT_SHADER);
+        let defs = |defs: &[&str]| defs.iter().map(|def| def.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            ShaderCache::key(&shader, &defs(&["A", "B"])),
+            ShaderCache::key(&shader, &defs(&["B", "A"]))
+        );
+        assert_ne!(
+            ShaderCache::key(&shader, &defs(&["AB"])),
+            ShaderCache::key(&shader, &defs(&["A", "B"]))
+        );
+        assert_ne!(
+            ShaderCache::key(&shader, &[]),
+            ShaderCache::key(
+                &Shader::from_glsl(ShaderStage::Vertex, FRAGMENT_SHADER),
+                &[]
+            )
+        );
+        let spirv = Shader::new(ShaderStage::Fragment, ShaderSource::Spirv(vec![0]));
+        assert_eq!(ShaderCache::key(&spirv, &[]), None);
+    }
+
+    #[test]
+    fn save_and_load() {
+        let shader = Shader::from_glsl(ShaderStage::Fragment, FRAGMENT_SHADER);
+        let red = vec!["RED".to_string()];
+        let mut shader_cache = ShaderCache::default();
+        shader_cache.compile(&shader, &[]).unwrap();
+        shader_cache.compile(&shader, &red).unwrap();
+        assert_eq!(shader_cache.len(), 2);
+
+        let directory = std::env::temp_dir().join("bevy_render_shader_cache_test");
+        let _ = fs::remove_dir_all(&directory);
+        shader_cache.save(&directory).unwrap();
+        let loaded = ShaderCache::load(&directory).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(loaded.len(), 2);
+        let compiled = shader.get_spirv(Some(&red)).unwrap();
+        match loaded.get(&shader, &red).map(|shader| shader.source) {
+            Some(ShaderSource::Spirv(spirv)) => assert_eq!(spirv, compiled),
+            _ => panic!("expected the cached shader"),
+        }
+        assert!(loaded
+            .get(
+                &Shader::from_glsl(ShaderStage::Vertex, FRAGMENT_SHADER),
+                &red
+            )
+            .is_none());
+    }
+}
@@ -0,0 +1,204 @@
+use super::{Shader, ShaderError};
+use bevy_app::{EventReader, Events};
+use bevy_asset::{AssetEvent, AssetLoader, AssetServer, Assets, LoadContext, LoadedAsset};
+use bevy_ecs::{Local, Res};
+use bevy_reflect::TypeUuid;
+use bevy_utils::{BoxedFuture, HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+/// GLSL code shared by shaders, which they include with `#include "path"`. Loaded from `.glsl`
+/// files.
+#[derive(Clone, Debug, TypeUuid)]
+#[uuid = "5f2c3a53-3a3e-4d3e-9b85-0d6a27c0b0d4"]
+pub struct ShaderInclude {
+    pub source: String,
+}
+
+#[derive(Default)]
+pub struct ShaderIncludeLoader;
+
+impl AssetLoader for ShaderIncludeLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let source = std::str::from_utf8(bytes)?.to_string();
+            load_context.set_default_asset(LoadedAsset::new(ShaderInclude { source }));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["glsl"]
+    }
+}
+
+/// The path of a `#include "path"` line, if `line` is one
+fn parse_include(line: &str) -> Option<&str> {
+    let line = line.trim_start().strip_prefix('#')?;
+    let line = line.trim_start().strip_prefix("include")?.trim();
+    line.strip_prefix('"')?.strip_suffix('"')
+}
+
+/// Resolves the path of an include relative to the directory of the file that includes it
+fn resolve_include(path: &Path, include: &str) -> PathBuf {
+    let mut resolved = PathBuf::new();
+    for component in path
+        .parent()
+        .unwrap_or_else(|| Path::new(""))
+        .join(include)
+        .components()
+    {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            component => resolved.push(component),
+        }
+    }
+    resolved
+}
+
+/// The paths of the files `source` includes, resolved relative to its `path`
+pub fn shader_includes(source: &str, path: &Path) -> Vec<PathBuf> {
+    source
+        .lines()
+        .filter_map(parse_include)
+        .map(|include| resolve_include(path, include))
+        .collect()
+}
+
+/// Replaces the `#include "path"` lines of `source` with the sources of the files they include,
+/// looked up in `includes` by their paths relative to the `path` of the including file.
+///
+/// Each file is only included the first time, so files can include what they use without
+/// defining anything twice. `#line` directives keep the line numbers of compilation errors
+/// pointing at the including file. Sources without includes are returned unchanged.
+pub fn expand_shader_includes(
+    source: &str,
+    path: &Path,
+    includes: &HashMap<PathBuf, String>,
+) -> Result<String, ShaderError> {
+    if !source.lines().any(|line| parse_include(line).is_some()) {
+        return Ok(source.to_string());
+    }
+    let mut expanded = String::with_capacity(source.len());
+    let mut included = HashSet::default();
+    expand(source, path, includes, &mut included, &mut expanded)?;
+    Ok(expanded)
+}
+
+fn expand(
+    source: &str,
+    path: &Path,
+    includes: &HashMap<PathBuf, String>,
+    included: &mut HashSet<PathBuf>,
+    expanded: &mut String,
+) -> Result<(), ShaderError> {
+    for (line_index, line) in source.lines().enumerate() {
+        let include = match parse_include(line) {
+            Some(include) => resolve_include(path, include),
+            None => {
+                expanded.push_str(line);
+                expanded.push('\n');
+                continue;
+            }
+        };
+        if !included.insert(include.clone()) {
+            expanded.push('\n');
+            continue;
+        }
+        let include_source = includes.get(&include).ok_or_else(|| {
+            ShaderError::Include(format!(
+                "{} includes {}, which doesn't exist",
+                path.display(),
+                include.display()
+            ))
+        })?;
+        expanded.push_str("#line 1\n");
+        expand(include_source, &include, includes, included, expanded)?;
+        expanded.push_str(&format!("#line {}\n", line_index + 2));
+    }
+    Ok(())
+}
+
+/// Reloads the shaders that include a [ShaderInclude] when it changes, so editing shared code
+/// hot reloads every shader that uses it
+pub fn shader_include_update_system(
+    asset_server: Res<AssetServer>,
+    shaders: Res<Assets<Shader>>,
+    shader_include_events: Res<Events<AssetEvent<ShaderInclude>>>,
+    mut shader_include_event_reader: Local<EventReader<AssetEvent<ShaderInclude>>>,
+) {
+    for event in shader_include_event_reader.iter(&shader_include_events) {
+        if let AssetEvent::Modified { handle } = event {
+            for (shader_handle, shader) in shaders.iter() {
+                if shader.includes().contains(handle) {
+                    if let Some(path) = asset_server.get_handle_path(shader_handle) {
+                        asset_server.reload_asset(path);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_includes() {
+        let mut includes = HashMap::default();
+        includes.insert(
+            PathBuf::from("shaders/common/color.glsl"),
+            "#include \"math.glsl\"\nvec4 tint(vec4 color) { return color; }".to_string(),
+        );
+        includes.insert(
+            PathBuf::from("shaders/common/math.glsl"),
+            "float PI = 3.14;".to_string(),
+        );
+        let source = "#version 450\n#include \"common/color.glsl\"\n# include \"common/math.glsl\"\nvoid main() {}";
+        let path = Path::new("shaders/sprite.frag");
+
+        assert_eq!(
+            shader_includes(source, path),
+            vec![
+                PathBuf::from("shaders/common/color.glsl"),
+                PathBuf::from("shaders/common/math.glsl")
+            ]
+        );
+        // math.glsl is only included by color.glsl, the first file to include it
+        assert_eq!(
+            expand_shader_includes(source, path, &includes).unwrap(),
+            "#version 450\n#line 1\n#line 1\nfloat PI = 3.14;\n#line 2\nvec4 tint(vec4 color) { return color; }\n#line 3\n\nvoid main() {}\n"
+        );
+
+        let source_without_includes = "#version 450\r\nvoid main() {}";
+        assert_eq!(
+            expand_shader_includes(source_without_includes, path, &includes).unwrap(),
+            source_without_includes
+        );
+
+        includes.remove(Path::new("shaders/common/math.glsl"));
+        assert!(expand_shader_includes(source, path, &includes).is_err());
+    }
+
+    #[test]
+    fn resolve_relative_includes() {
+        assert_eq!(
+            resolve_include(
+                Path::new("shaders/tiles/tile.vert"),
+                "../common/./math.glsl"
+            ),
+            PathBuf::from("shaders/common/math.glsl")
+        );
+        assert_eq!(
+            resolve_include(Path::new("tile.vert"), "math.glsl"),
+            PathBuf::from("math.glsl")
+        );
+    }
+}
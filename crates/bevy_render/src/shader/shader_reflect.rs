@@ -3,6 +3,7 @@ use crate::{
         BindGroupDescriptor, BindType, BindingDescriptor, BindingShaderStage, InputStepMode,
         UniformProperty, VertexAttributeDescriptor, VertexBufferDescriptor, VertexFormat,
     },
+    render_graph::GLOBALS_UNIFORM,
     shader::{ShaderLayout, GL_VERTEX_INDEX},
     texture::{TextureComponentType, TextureViewDimension},
 };
@@ -154,7 +155,9 @@ fn reflect_binding(
 
     let name = name.to_string();
 
-    if name == "Camera" {
+    // global uniforms are visible to both stages, so pipelines that only read them in one stage
+    // still share bind group layouts with the rest
+    if name == "Camera" || name == GLOBALS_UNIFORM {
         shader_stage = BindingShaderStage::VERTEX | BindingShaderStage::FRAGMENT;
     }
 
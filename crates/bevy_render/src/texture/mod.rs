@@ -2,9 +2,11 @@
 mod hdr_texture_loader;
 #[cfg(feature = "png")]
 mod image_texture_loader;
+mod residency;
 mod sampler_descriptor;
 #[allow(clippy::module_inception)]
 mod texture;
+mod texture_conversion;
 mod texture_descriptor;
 mod texture_dimension;
 
@@ -12,7 +14,9 @@ mod texture_dimension;
 pub use hdr_texture_loader::*;
 #[cfg(feature = "png")]
 pub use image_texture_loader::*;
+pub use residency::*;
 pub use sampler_descriptor::*;
 pub use texture::*;
+pub use texture_conversion::*;
 pub use texture_descriptor::*;
 pub use texture_dimension::*;
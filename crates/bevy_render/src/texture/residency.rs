@@ -0,0 +1,346 @@
+use super::Texture;
+use crate::renderer::RenderResourceContext;
+use bevy_app::prelude::*;
+use bevy_asset::{AssetEvent, Assets, Handle, HandleId};
+use bevy_diagnostic::{Diagnostic, DiagnosticId, Diagnostics};
+use bevy_ecs::{IntoSystem, Local, Res, ResMut};
+use bevy_utils::{HashMap, HashSet};
+
+/// Keeps the GPU memory used by textures under a budget.
+///
+/// Every texture that is uploaded to the GPU is tracked, but only textures marked with
+/// [TextureResidency::mark_streamable], such as the textures of map chunks or LODs, are evicted.
+/// When the total size of the resident textures goes over the budget, the streamable textures
+/// that were drawn the longest time ago are removed from the GPU. Their data stays in
+/// `Assets<Texture>`, and they are uploaded again the next time they are passed to
+/// [TextureResidency::touch].
+///
+/// Whatever draws a streamable texture must touch it every frame it is drawn. Textures touched
+/// during the current frame are never evicted.
+#[derive(Debug)]
+pub struct TextureResidency {
+    budget: usize,
+    usage: usize,
+    frame: u64,
+    textures: HashMap<HandleId, ResidentTexture>,
+    streamable: HashSet<HandleId>,
+    pending_reloads: Vec<HandleId>,
+    evictions: usize,
+    reloads: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ResidentTexture {
+    size: usize,
+    last_used: u64,
+    resident: bool,
+    reloading: bool,
+}
+
+impl Default for TextureResidency {
+    fn default() -> Self {
+        Self::new(usize::max_value())
+    }
+}
+
+impl TextureResidency {
+    /// Creates a residency manager that keeps at most `budget` bytes of streamable textures on
+    /// the GPU
+    pub fn new(budget: usize) -> Self {
+        TextureResidency {
+            budget,
+            usage: 0,
+            frame: 0,
+            textures: Default::default(),
+            streamable: Default::default(),
+            pending_reloads: Default::default(),
+            evictions: 0,
+            reloads: 0,
+        }
+    }
+
+    /// The maximum number of bytes of texture memory, in bytes
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+    }
+
+    /// The number of bytes of texture memory currently used by resident textures
+    pub fn usage(&self) -> usize {
+        self.usage
+    }
+
+    /// The total number of textures evicted so far
+    pub fn evictions(&self) -> usize {
+        self.evictions
+    }
+
+    /// The total number of evicted textures reloaded so far
+    pub fn reloads(&self) -> usize {
+        self.reloads
+    }
+
+    /// Allows `texture` to be evicted when the budget is exceeded
+    pub fn mark_streamable(&mut self, texture: &Handle<Texture>) {
+        self.streamable.insert(texture.id);
+    }
+
+    pub fn is_resident(&self, texture: &Handle<Texture>) -> bool {
+        self.textures
+            .get(&texture.id)
+            .map_or(false, |texture| texture.resident)
+    }
+
+    /// Records that `texture` is drawn during the current frame. If it was evicted, it is
+    /// uploaded again and this returns `true`. Anything bound to the evicted texture, such as a
+    /// material, must then be updated so it binds the new texture.
+    pub fn touch(&mut self, texture: &Handle<Texture>) -> bool {
+        let frame = self.frame;
+        match self.textures.get_mut(&texture.id) {
+            Some(resident_texture) => {
+                resident_texture.last_used = frame;
+                if resident_texture.resident || resident_texture.reloading {
+                    return false;
+                }
+                resident_texture.reloading = true;
+                self.pending_reloads.push(texture.id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn insert(&mut self, texture: HandleId, size: usize) {
+        let last_used = self.frame;
+        let previous = self.textures.insert(
+            texture,
+            ResidentTexture {
+                size,
+                last_used,
+                resident: true,
+                reloading: false,
+            },
+        );
+        if let Some(previous) = previous {
+            if previous.resident {
+                self.usage -= previous.size;
+            }
+        }
+        self.usage += size;
+    }
+
+    fn remove(&mut self, texture: HandleId) {
+        if let Some(previous) = self.textures.remove(&texture) {
+            if previous.resident {
+                self.usage -= previous.size;
+            }
+        }
+        self.streamable.remove(&texture);
+    }
+
+    /// Picks the least recently drawn streamable textures to evict until the usage fits in the
+    /// budget, and marks them as evicted
+    fn evict(&mut self) -> Vec<HandleId> {
+        if self.usage <= self.budget {
+            return Vec::new();
+        }
+
+        let frame = self.frame;
+        let mut candidates = self
+            .streamable
+            .iter()
+            .filter_map(|id| {
+                let texture = self.textures.get(id)?;
+                if texture.resident && texture.last_used != frame {
+                    Some((texture.last_used, *id))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|(last_used, _)| *last_used);
+
+        let mut evicted = Vec::new();
+        for (_, id) in candidates {
+            if self.usage <= self.budget {
+                break;
+            }
+            let texture = self.textures.get_mut(&id).unwrap();
+            texture.resident = false;
+            self.usage -= texture.size;
+            evicted.push(id);
+        }
+        self.evictions += evicted.len();
+        evicted
+    }
+
+    /// Uploads touched textures that were evicted, evicts textures that are over budget and starts
+    /// a new frame
+    pub fn texture_residency_system(
+        mut state: Local<TextureResidencyState>,
+        mut residency: ResMut<TextureResidency>,
+        mut textures: ResMut<Assets<Texture>>,
+        texture_events: Res<Events<AssetEvent<Texture>>>,
+        render_resource_context: Res<Box<dyn RenderResourceContext>>,
+    ) {
+        for event in state.event_reader.iter(&texture_events) {
+            match event {
+                AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                    if let Some(texture) = textures.get(handle) {
+                        residency.insert(handle.id, texture.memory_size());
+                    }
+                }
+                AssetEvent::Removed { handle } => residency.remove(handle.id),
+            }
+        }
+
+        for id in std::mem::take(&mut residency.pending_reloads) {
+            // marking the texture as modified uploads it again. it is resident again once the
+            // modification event is seen above
+            if textures.get_mut(id).is_some() {
+                residency.reloads += 1;
+            }
+        }
+
+        for id in residency.evict() {
+            Texture::remove_current_texture_resources(
+                &**render_resource_context,
+                &Handle::weak(id),
+            );
+        }
+
+        residency.frame += 1;
+    }
+}
+
+#[derive(Default)]
+pub struct TextureResidencyState {
+    event_reader: EventReader<AssetEvent<Texture>>,
+}
+
+/// Adds diagnostics about the [TextureResidency] budget to an App: the budget, the memory used by
+/// resident textures, and the number of textures evicted and reloaded each frame
+#[derive(Default)]
+pub struct TextureResidencyDiagnosticsPlugin;
+
+#[derive(Default)]
+pub struct TextureResidencyDiagnosticsState {
+    evictions: usize,
+    reloads: usize,
+}
+
+impl Plugin for TextureResidencyDiagnosticsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_startup_system(Self::setup_system.system())
+            .init_resource::<TextureResidencyDiagnosticsState>()
+            .add_system(Self::diagnostic_system.system());
+    }
+}
+
+impl TextureResidencyDiagnosticsPlugin {
+    pub const TEXTURE_BUDGET: DiagnosticId =
+        DiagnosticId::from_u128(183744912653092187364091283746519283740);
+    pub const TEXTURE_USAGE: DiagnosticId =
+        DiagnosticId::from_u128(42918374650918273645019283746501928374);
+    pub const EVICTED_TEXTURES: DiagnosticId =
+        DiagnosticId::from_u128(290192837465019283746501928374650192837);
+    pub const RELOADED_TEXTURES: DiagnosticId =
+        DiagnosticId::from_u128(128374650192837465019283746501928374651);
+
+    pub fn setup_system(mut diagnostics: ResMut<Diagnostics>) {
+        diagnostics.add(Diagnostic::new(Self::TEXTURE_BUDGET, "texture_budget", 1));
+        diagnostics.add(Diagnostic::new(Self::TEXTURE_USAGE, "texture_usage", 20));
+        diagnostics.add(Diagnostic::new(
+            Self::EVICTED_TEXTURES,
+            "evicted_textures",
+            20,
+        ));
+        diagnostics.add(Diagnostic::new(
+            Self::RELOADED_TEXTURES,
+            "reloaded_textures",
+            20,
+        ));
+    }
+
+    pub fn diagnostic_system(
+        mut state: ResMut<TextureResidencyDiagnosticsState>,
+        mut diagnostics: ResMut<Diagnostics>,
+        residency: Res<TextureResidency>,
+    ) {
+        diagnostics.add_measurement(Self::TEXTURE_BUDGET, residency.budget() as f64);
+        diagnostics.add_measurement(Self::TEXTURE_USAGE, residency.usage() as f64);
+        diagnostics.add_measurement(
+            Self::EVICTED_TEXTURES,
+            (residency.evictions() - state.evictions) as f64,
+        );
+        diagnostics.add_measurement(
+            Self::RELOADED_TEXTURES,
+            (residency.reloads() - state.reloads) as f64,
+        );
+        state.evictions = residency.evictions();
+        state.reloads = residency.reloads();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texture() -> Handle<Texture> {
+        Handle::weak(HandleId::random::<Texture>())
+    }
+
+    #[test]
+    fn evict_least_recently_used() {
+        let mut residency = TextureResidency::new(300);
+        let (a, b, c, pinned) = (texture(), texture(), texture(), texture());
+        for handle in [&a, &b, &c].iter() {
+            residency.insert(handle.id, 100);
+            residency.mark_streamable(handle);
+        }
+        // textures that are not streamable count towards the budget but are never evicted
+        residency.insert(pinned.id, 100);
+        assert_eq!(residency.usage(), 400);
+
+        // `b` and `c` are drawn after `a`, and `c` is drawn during the current frame
+        residency.frame = 1;
+        residency.touch(&b);
+        residency.frame = 2;
+        residency.touch(&c);
+        assert_eq!(residency.evict(), vec![a.id]);
+        assert_eq!(residency.usage(), 300);
+        assert!(!residency.is_resident(&a));
+
+        residency.set_budget(100);
+        assert_eq!(residency.evict(), vec![b.id]);
+        assert_eq!(residency.usage(), 200);
+        assert!(residency.is_resident(&c));
+        assert!(residency.is_resident(&pinned));
+        assert_eq!(residency.evictions(), 2);
+    }
+
+    #[test]
+    fn reload_touched_textures() {
+        let mut residency = TextureResidency::new(100);
+        let (a, b) = (texture(), texture());
+        residency.insert(a.id, 100);
+        residency.insert(b.id, 100);
+        residency.mark_streamable(&a);
+        residency.frame = 1;
+        assert_eq!(residency.evict(), vec![a.id]);
+
+        // only the first touch of an evicted texture requests a reload
+        assert!(residency.touch(&a));
+        assert!(!residency.touch(&a));
+        assert!(!residency.touch(&b));
+        assert_eq!(residency.pending_reloads, vec![a.id]);
+
+        // the texture is resident again once it is uploaded
+        residency.insert(a.id, 100);
+        assert!(residency.is_resident(&a));
+        assert_eq!(residency.usage(), 200);
+    }
+}
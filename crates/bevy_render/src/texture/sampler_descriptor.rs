@@ -1,8 +1,11 @@
 use crate::pipeline::CompareFunction;
 use std::num::NonZeroU8;
 
+/// The highest anisotropy level supported by samplers
+pub const MAX_ANISOTROPY: u8 = 16;
+
 /// Describes a sampler
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct SamplerDescriptor {
     pub address_mode_u: AddressMode,
     pub address_mode_v: AddressMode,
@@ -33,6 +36,29 @@ impl Default for SamplerDescriptor {
     }
 }
 
+impl SamplerDescriptor {
+    /// Sets the anisotropic filtering level, which keeps textures sharp when they are viewed at
+    /// steep angles or minified. `level` is rounded down to a power of two no higher than
+    /// [MAX_ANISOTROPY], and levels below 2 disable anisotropic filtering. It only has an effect
+    /// with linear filtering, and is ignored if the GPU doesn't support it.
+    pub fn with_anisotropy(mut self, level: u8) -> Self {
+        self.set_anisotropy(level);
+        self
+    }
+
+    pub fn set_anisotropy(&mut self, level: u8) {
+        self.anisotropy_clamp = match level.min(MAX_ANISOTROPY) {
+            0 | 1 => None,
+            level => NonZeroU8::new(1 << (7 - level.leading_zeros())),
+        };
+    }
+
+    /// The anisotropic filtering level, or 1 if anisotropic filtering is disabled
+    pub fn anisotropy(&self) -> u8 {
+        self.anisotropy_clamp.map_or(1, NonZeroU8::get)
+    }
+}
+
 /// How edges should be handled in texture addressing.
 #[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
 pub enum AddressMode {
@@ -59,3 +85,18 @@ impl Default for FilterMode {
         FilterMode::Nearest
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anisotropy_levels() {
+        let sampler = SamplerDescriptor::default();
+        assert_eq!(sampler.anisotropy(), 1);
+        assert_eq!(sampler.with_anisotropy(1).anisotropy_clamp, None);
+        assert_eq!(sampler.with_anisotropy(4).anisotropy(), 4);
+        assert_eq!(sampler.with_anisotropy(7).anisotropy(), 4);
+        assert_eq!(sampler.with_anisotropy(255).anisotropy(), MAX_ANISOTROPY);
+    }
+}
@@ -1,12 +1,12 @@
 use super::{Extent3d, SamplerDescriptor, TextureDescriptor, TextureDimension, TextureFormat};
 use crate::renderer::{
-    RenderResource, RenderResourceContext, RenderResourceId, RenderResourceType,
+    RenderCapabilities, RenderResource, RenderResourceContext, RenderResourceId, RenderResourceType,
 };
 use bevy_app::prelude::{EventReader, Events};
 use bevy_asset::{AssetEvent, Assets, Handle};
 use bevy_ecs::{Res, ResMut};
 use bevy_reflect::TypeUuid;
-use bevy_utils::HashSet;
+use bevy_utils::{tracing::error, HashSet};
 
 pub const TEXTURE_ASSET_INDEX: u64 = 0;
 pub const SAMPLER_ASSET_INDEX: u64 = 1;
@@ -91,6 +91,11 @@ impl Texture {
         self.size.height as f32 / self.size.width as f32
     }
 
+    /// The number of bytes the texture takes up in GPU memory
+    pub fn memory_size(&self) -> usize {
+        self.size.volume() * self.format.pixel_size()
+    }
+
     pub fn resize(&mut self, size: Extent3d) {
         self.size = size;
         self.data
@@ -125,9 +130,108 @@ impl Texture {
         });
     }
 
+    fn pixel_offset(&self, x: u32, y: u32) -> Option<usize> {
+        if x >= self.size.width || y >= self.size.height {
+            return None;
+        }
+        Some((y as usize * self.size.width as usize + x as usize) * self.format.pixel_size())
+    }
+
+    /// The bytes of the pixel at `x`, `y`, or `None` if it is outside of the texture. For
+    /// texture arrays, this is a pixel of the first layer.
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<&[u8]> {
+        let offset = self.pixel_offset(x, y)?;
+        Some(&self.data[offset..offset + self.format.pixel_size()])
+    }
+
+    pub fn get_pixel_mut(&mut self, x: u32, y: u32) -> Option<&mut [u8]> {
+        let offset = self.pixel_offset(x, y)?;
+        let pixel_size = self.format.pixel_size();
+        Some(&mut self.data[offset..offset + pixel_size])
+    }
+
+    /// Sets the pixel at `x`, `y` to the bytes of `pixel`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pixel is outside of the texture, or if `pixel` is not the size of a pixel of
+    /// the texture format.
+    pub fn set_pixel(&mut self, x: u32, y: u32, pixel: &[u8]) {
+        let size = self.size;
+        self.get_pixel_mut(x, y)
+            .unwrap_or_else(|| panic!("pixel {}, {} is outside of {:?}", x, y, size))
+            .copy_from_slice(pixel);
+    }
+
+    /// Sets every pixel from `min` (inclusive) to `max` (exclusive) to the bytes of `pixel`. The
+    /// parts of the rect outside of the texture are ignored.
+    pub fn fill_rect(&mut self, min: [u32; 2], max: [u32; 2], pixel: &[u8]) {
+        assert_eq!(
+            pixel.len(),
+            self.format.pixel_size(),
+            "Fill data must be one pixel."
+        );
+        let max_x = max[0].min(self.size.width);
+        let max_y = max[1].min(self.size.height);
+        if min[0] >= max_x {
+            return;
+        }
+
+        for y in min[1]..max_y {
+            let begin = self.pixel_offset(min[0], y).unwrap();
+            let end = self.pixel_offset(max_x - 1, y).unwrap() + pixel.len();
+            for current_pixel in self.data[begin..end].chunks_exact_mut(pixel.len()) {
+                current_pixel.copy_from_slice(pixel);
+            }
+        }
+    }
+
+    /// Copies the pixels of `source` from `source_min` (inclusive) to `source_max` (exclusive)
+    /// into this texture, with their top left corner at `target_min`. The parts of the rect that
+    /// are outside of either texture are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the textures have different formats. Use [Texture::convert] to convert the
+    /// source texture first.
+    pub fn blit_from(
+        &mut self,
+        source: &Texture,
+        source_min: [u32; 2],
+        source_max: [u32; 2],
+        target_min: [u32; 2],
+    ) {
+        assert_eq!(
+            self.format, source.format,
+            "Blitted textures must have the same format."
+        );
+        let width = source_max[0]
+            .min(source.size.width)
+            .saturating_sub(source_min[0])
+            .min(self.size.width.saturating_sub(target_min[0]));
+        let height = source_max[1]
+            .min(source.size.height)
+            .saturating_sub(source_min[1])
+            .min(self.size.height.saturating_sub(target_min[1]));
+        if width == 0 {
+            return;
+        }
+
+        let row_size = width as usize * self.format.pixel_size();
+        for y in 0..height {
+            let source_begin = source
+                .pixel_offset(source_min[0], source_min[1] + y)
+                .unwrap();
+            let target_begin = self.pixel_offset(target_min[0], target_min[1] + y).unwrap();
+            self.data[target_begin..target_begin + row_size]
+                .copy_from_slice(&source.data[source_begin..source_begin + row_size]);
+        }
+    }
+
     pub fn texture_resource_system(
         mut state: ResMut<TextureResourceSystemState>,
         render_resource_context: Res<Box<dyn RenderResourceContext>>,
+        capabilities: Res<RenderCapabilities>,
         textures: Res<Assets<Texture>>,
         texture_events: Res<Events<AssetEvent<Texture>>>,
     ) {
@@ -153,6 +257,10 @@ impl Texture {
 
         for texture_handle in changed_textures.iter() {
             if let Some(texture) = textures.get(*texture_handle) {
+                if let Err(err) = capabilities.validate_texture(texture.size, texture.dimension) {
+                    error!("Texture {:?} can't be created: {}", texture_handle.id, err);
+                    continue;
+                }
                 let texture_descriptor: TextureDescriptor = texture.into();
                 let texture_resource = render_resource_context.create_texture(texture_descriptor);
 
@@ -172,7 +280,7 @@ impl Texture {
         }
     }
 
-    fn remove_current_texture_resources(
+    pub(crate) fn remove_current_texture_resources(
         render_resource_context: &dyn RenderResourceContext,
         handle: &Handle<Texture>,
     ) {
@@ -227,3 +335,66 @@ impl RenderResource for Handle<Texture> {
         Some(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texture(width: u32, height: u32) -> Texture {
+        Texture::new_fill(
+            Extent3d::new(width, height, 1),
+            TextureDimension::D2,
+            &[0, 0],
+            TextureFormat::Rg8Unorm,
+        )
+    }
+
+    #[test]
+    fn get_set_pixel() {
+        let mut texture = texture(3, 2);
+        texture.set_pixel(2, 1, &[1, 2]);
+        assert_eq!(texture.get_pixel(2, 1), Some(&[1, 2][..]));
+        assert_eq!(&texture.data[10..12], &[1, 2]);
+        assert_eq!(texture.get_pixel(3, 0), None);
+        assert_eq!(texture.get_pixel(0, 2), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_pixel_outside() {
+        texture(3, 2).set_pixel(3, 0, &[1, 2]);
+    }
+
+    #[test]
+    fn fill_rect() {
+        let mut texture = texture(3, 3);
+        // the rect is clipped to the texture
+        texture.fill_rect([1, 1], [5, 5], &[7, 7]);
+        let filled = (0..3)
+            .flat_map(|y| (0..3).map(move |x| (x, y)))
+            .filter(|(x, y)| texture.get_pixel(*x, *y) == Some(&[7, 7][..]))
+            .collect::<Vec<_>>();
+        assert_eq!(filled, vec![(1, 1), (2, 1), (1, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn blit_from() {
+        let mut source = texture(2, 2);
+        source.set_pixel(0, 0, &[1, 1]);
+        source.set_pixel(1, 0, &[2, 2]);
+        source.set_pixel(0, 1, &[3, 3]);
+        source.set_pixel(1, 1, &[4, 4]);
+
+        let mut target = texture(3, 3);
+        target.blit_from(&source, [0, 0], [2, 2], [2, 1]);
+        // only the left column fits in the target
+        assert_eq!(target.get_pixel(2, 1), Some(&[1, 1][..]));
+        assert_eq!(target.get_pixel(2, 2), Some(&[3, 3][..]));
+        assert_eq!(target.get_pixel(1, 1), Some(&[0, 0][..]));
+
+        target.blit_from(&source, [1, 0], [2, 2], [0, 0]);
+        assert_eq!(target.get_pixel(0, 0), Some(&[2, 2][..]));
+        assert_eq!(target.get_pixel(0, 1), Some(&[4, 4][..]));
+        assert_eq!(target.get_pixel(1, 0), Some(&[0, 0][..]));
+    }
+}
@@ -0,0 +1,240 @@
+use super::{Extent3d, Texture, TextureDimension, TextureFormat};
+use crate::{
+    color::{Color, LinearRgba, Srgba},
+    colorspace::SrgbColorSpace,
+};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("cannot convert a texture from {from:?} to {to:?}")]
+pub struct TextureConversionError {
+    pub from: TextureFormat,
+    pub to: TextureFormat,
+}
+
+impl Texture {
+    /// Returns a copy of the texture with its pixels converted to `format`.
+    ///
+    /// Conversions are supported between `R8Unorm`, `Rgba8Unorm`, `Rgba8UnormSrgb`, `Bgra8Unorm`
+    /// and `Bgra8UnormSrgb`. Colors are converted between the sRGB and linear color spaces when
+    /// only one of the formats is sRGB. `R8Unorm` pixels hold linear luminance: converting to it
+    /// keeps the luminance of each pixel, and converting from it gives opaque gray pixels.
+    pub fn convert(&self, format: TextureFormat) -> Result<Texture, TextureConversionError> {
+        let error = TextureConversionError {
+            from: self.format,
+            to: format,
+        };
+        let from = PixelLayout::of(self.format).ok_or(error)?;
+        let to = PixelLayout::of(format).ok_or(error)?;
+
+        let to_linear = from.srgb && !to.srgb;
+        let to_srgb = !from.srgb && to.srgb;
+        let mut srgb_table = [0; 256];
+        if to_linear || to_srgb {
+            for (value, converted) in srgb_table.iter_mut().enumerate() {
+                let value = value as f32 / 255.0;
+                let value = if to_linear {
+                    value.nonlinear_to_linear_srgb()
+                } else {
+                    value.linear_to_nonlinear_srgb()
+                };
+                *converted = (value * 255.0).round() as u8;
+            }
+        }
+
+        let mut data = Vec::with_capacity(self.size.volume() * format.pixel_size());
+        for pixel in self.data.chunks_exact(self.format.pixel_size()) {
+            let mut rgba = from.decode(pixel);
+            if to_linear || to_srgb {
+                for channel in rgba[..3].iter_mut() {
+                    *channel = srgb_table[*channel as usize];
+                }
+            }
+            to.encode(rgba, &mut data);
+        }
+
+        Ok(Texture {
+            data,
+            size: self.size,
+            format,
+            dimension: self.dimension,
+            sampler: self.sampler,
+        })
+    }
+}
+
+impl Texture {
+    /// Creates a texture filled with `color`, stored in the colorspace of `format`.
+    ///
+    /// # Panics
+    /// Panics if `format` is not one of the formats supported by [Texture::convert].
+    pub fn new_fill_color(
+        size: Extent3d,
+        dimension: TextureDimension,
+        color: Color,
+        format: TextureFormat,
+    ) -> Self {
+        let pixel = color_to_pixel(color, format);
+        Texture::new_fill(size, dimension, &pixel, format)
+    }
+
+    /// The color of the pixel at (`x`, `y`), or `None` if it is out of bounds or the format of
+    /// the texture is not one of the formats supported by [Texture::convert]
+    pub fn get_pixel_color(&self, x: u32, y: u32) -> Option<Color> {
+        let layout = PixelLayout::of(self.format)?;
+        let rgba = layout.decode(self.get_pixel(x, y)?);
+        Some(if layout.srgb {
+            Srgba::from_u8(rgba).into()
+        } else {
+            LinearRgba::from_u8(rgba).into()
+        })
+    }
+
+    /// Sets the pixel at (`x`, `y`) to `color`, stored in the colorspace of the texture format.
+    ///
+    /// # Panics
+    /// Panics if the pixel is out of bounds or the format of the texture is not one of the
+    /// formats supported by [Texture::convert].
+    pub fn set_pixel_color(&mut self, x: u32, y: u32, color: Color) {
+        let pixel = color_to_pixel(color, self.format);
+        self.set_pixel(x, y, &pixel);
+    }
+}
+
+fn color_to_pixel(color: Color, format: TextureFormat) -> Vec<u8> {
+    let layout = PixelLayout::of(format)
+        .unwrap_or_else(|| panic!("cannot store colors in {:?} textures", format));
+    let rgba = if layout.srgb {
+        color.as_srgba().to_u8()
+    } else {
+        color.as_linear_rgba().to_u8()
+    };
+    let mut pixel = Vec::with_capacity(format.pixel_size());
+    layout.encode(rgba, &mut pixel);
+    pixel
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Channels {
+    R,
+    Rgba,
+    Bgra,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PixelLayout {
+    channels: Channels,
+    srgb: bool,
+}
+
+impl PixelLayout {
+    fn of(format: TextureFormat) -> Option<Self> {
+        let (channels, srgb) = match format {
+            TextureFormat::R8Unorm => (Channels::R, false),
+            TextureFormat::Rgba8Unorm => (Channels::Rgba, false),
+            TextureFormat::Rgba8UnormSrgb => (Channels::Rgba, true),
+            TextureFormat::Bgra8Unorm => (Channels::Bgra, false),
+            TextureFormat::Bgra8UnormSrgb => (Channels::Bgra, true),
+            _ => return None,
+        };
+        Some(PixelLayout { channels, srgb })
+    }
+
+    fn decode(&self, pixel: &[u8]) -> [u8; 4] {
+        match self.channels {
+            Channels::R => [pixel[0], pixel[0], pixel[0], 255],
+            Channels::Rgba => [pixel[0], pixel[1], pixel[2], pixel[3]],
+            Channels::Bgra => [pixel[2], pixel[1], pixel[0], pixel[3]],
+        }
+    }
+
+    fn encode(&self, [r, g, b, a]: [u8; 4], data: &mut Vec<u8>) {
+        match self.channels {
+            Channels::R => {
+                let luminance = 0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32;
+                data.push(luminance.round() as u8);
+            }
+            Channels::Rgba => data.extend_from_slice(&[r, g, b, a]),
+            Channels::Bgra => data.extend_from_slice(&[b, g, r, a]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texture(data: Vec<u8>, format: TextureFormat) -> Texture {
+        let pixels = (data.len() / format.pixel_size()) as u32;
+        Texture::new(
+            Extent3d::new(pixels, 1, 1),
+            TextureDimension::D2,
+            data,
+            format,
+        )
+    }
+
+    #[test]
+    fn swizzle_channels() {
+        let rgba = texture(vec![1, 2, 3, 4, 5, 6, 7, 8], TextureFormat::Rgba8UnormSrgb);
+        let bgra = rgba.convert(TextureFormat::Bgra8UnormSrgb).unwrap();
+        assert_eq!(bgra.data, vec![3, 2, 1, 4, 7, 6, 5, 8]);
+        assert_eq!(bgra.size, rgba.size);
+        let round_trip = bgra.convert(TextureFormat::Rgba8UnormSrgb).unwrap();
+        assert_eq!(round_trip.data, rgba.data);
+    }
+
+    #[test]
+    fn convert_color_space() {
+        let srgb = texture(vec![0, 188, 255, 128], TextureFormat::Rgba8UnormSrgb);
+        let linear = srgb.convert(TextureFormat::Rgba8Unorm).unwrap();
+        // alpha is always linear
+        assert_eq!(linear.data, vec![0, 128, 255, 128]);
+        let srgb = linear.convert(TextureFormat::Bgra8UnormSrgb).unwrap();
+        assert_eq!(srgb.data, vec![255, 188, 0, 128]);
+    }
+
+    #[test]
+    fn convert_single_channel() {
+        let rgba = texture(
+            vec![255, 255, 255, 0, 0, 0, 255, 255],
+            TextureFormat::Rgba8Unorm,
+        );
+        let r = rgba.convert(TextureFormat::R8Unorm).unwrap();
+        assert_eq!(r.data, vec![255, 18]);
+        let rgba = r.convert(TextureFormat::Rgba8Unorm).unwrap();
+        assert_eq!(rgba.data, vec![255, 255, 255, 255, 18, 18, 18, 255]);
+    }
+
+    #[test]
+    fn unsupported_format() {
+        let rgba = texture(vec![0; 4], TextureFormat::Rgba8Unorm);
+        assert_eq!(
+            rgba.convert(TextureFormat::Rgba32Float).unwrap_err(),
+            TextureConversionError {
+                from: TextureFormat::Rgba8Unorm,
+                to: TextureFormat::Rgba32Float,
+            }
+        );
+    }
+
+    #[test]
+    fn fill_with_color() {
+        let color = Color::rgba_u8(210, 105, 30, 255);
+        let size = Extent3d::new(2, 1, 1);
+        let srgb = Texture::new_fill_color(
+            size,
+            TextureDimension::D2,
+            color,
+            TextureFormat::Bgra8UnormSrgb,
+        );
+        assert_eq!(srgb.get_pixel(1, 0), Some(&[30, 105, 210, 255][..]));
+        assert_eq!(srgb.get_pixel_color(1, 0), Some(color));
+
+        let mut linear =
+            Texture::new_fill_color(size, TextureDimension::D2, color, TextureFormat::Rgba8Unorm);
+        assert_eq!(linear.get_pixel(0, 0), Some(&[164, 36, 3, 255][..]));
+        linear.set_pixel_color(1, 0, Color::WHITE);
+        assert_eq!(linear.get_pixel_color(1, 0), Some(Color::WHITE));
+    }
+}
@@ -1,25 +1,35 @@
 mod command;
 mod dynamic_scene;
+mod prefab;
+mod save_game;
 mod scene;
 mod scene_loader;
 mod scene_spawner;
 pub mod serde;
+mod stable_id;
+mod world_diff;
 
 use bevy_ecs::{IntoSystem, SystemStage};
 pub use command::*;
 pub use dynamic_scene::*;
+pub use prefab::*;
+pub use save_game::*;
 pub use scene::*;
 pub use scene_loader::*;
 pub use scene_spawner::*;
+pub use stable_id::*;
+pub use world_diff::*;
 
 pub mod prelude {
     pub use crate::{
-        DynamicScene, Scene, SceneSpawner, SpawnSceneAsChildCommands, SpawnSceneCommands,
+        DynamicScene, Prefab, RegisterSaveGame, SaveGame, SaveGamePlugin, Scene, SceneSpawner,
+        SpawnPrefabCommands, SpawnSceneAsChildCommands, SpawnSceneCommands, StableId, StableIds,
     };
 }
 
 use bevy_app::prelude::*;
 use bevy_asset::AddAsset;
+use bevy_reflect::RegisterTypeBuilder;
 
 #[derive(Default)]
 pub struct ScenePlugin;
@@ -30,9 +40,18 @@ impl Plugin for ScenePlugin {
     fn build(&self, app: &mut AppBuilder) {
         app.add_asset::<DynamicScene>()
             .add_asset::<Scene>()
+            .add_asset::<Prefab>()
             .init_asset_loader::<SceneLoader>()
+            .init_asset_loader::<PrefabLoader>()
             .init_resource::<SceneSpawner>()
+            .init_resource::<PrefabSpawner>()
             .add_stage_after(stage::EVENT, SCENE_STAGE, SystemStage::parallel())
-            .add_system_to_stage(SCENE_STAGE, scene_spawner_system.system());
+            .add_system_to_stage(SCENE_STAGE, scene_spawner_system.system())
+            .add_system_to_stage(SCENE_STAGE, prefab_spawner_system.system())
+            .register_type::<StableId>()
+            .init_resource::<StableIds>()
+            // after the stages entities are usually spawned and despawned in, before their
+            // trackers are cleared
+            .add_system_to_stage(stage::POST_UPDATE, stable_id_system.system());
     }
 }
@@ -0,0 +1,346 @@
+use crate::{
+    dynamic_scene::serialize_ron,
+    serde::{PrefabDeserializer, PrefabSerializer},
+    SceneSpawnError,
+};
+use anyhow::Result;
+use bevy_asset::{AssetLoader, Assets, Handle, LoadContext, LoadedAsset};
+use bevy_ecs::{Command, Commands, Entity, EntityCommands, FromResources, Resources, World};
+use bevy_reflect::{Reflect, ReflectComponent, TypeRegistry, TypeRegistryArc, TypeUuid};
+use bevy_transform::prelude::Parent;
+use bevy_utils::{tracing::error, BoxedFuture};
+use serde::de::DeserializeSeed;
+
+/// A template for an entity and its children, made of reflected components.
+///
+/// Prefabs are usually loaded from `.prefab` files, which are written in RON:
+///
+/// ```ron
+/// (
+///   components: [
+///     {
+///       "type": "game::Food",
+///       "struct": {
+///         "calories": {
+///           "type": "u32",
+///           "value": 10,
+///         },
+///       },
+///     },
+///   ],
+///   children: [
+///     (components: [/* ... */]),
+///   ],
+/// )
+/// ```
+///
+/// Both fields can be left out. Every component must be registered with `#[reflect(Component)]`.
+#[derive(Debug, Default, TypeUuid)]
+#[uuid = "0b8a3d7e-5c2f-4d1a-9e6b-3f7c8a2d4e19"]
+pub struct Prefab {
+    pub components: Vec<Box<dyn Reflect>>,
+    pub children: Vec<Prefab>,
+}
+
+impl Prefab {
+    /// Adds the components of the prefab to `entity`, and spawns its children. Components that
+    /// `entity` already has are not replaced, so they can be used to override the prefab.
+    pub fn write_to_world(
+        &self,
+        world: &mut World,
+        resources: &Resources,
+        entity: Entity,
+    ) -> Result<(), SceneSpawnError> {
+        let type_registry = resources.get::<TypeRegistryArc>().unwrap();
+        let type_registry = type_registry.read();
+        self.write_entity(world, resources, &type_registry, entity)
+    }
+
+    fn write_entity(
+        &self,
+        world: &mut World,
+        resources: &Resources,
+        type_registry: &TypeRegistry,
+        entity: Entity,
+    ) -> Result<(), SceneSpawnError> {
+        for component in self.components.iter() {
+            let registration = type_registry
+                .get_with_name(component.type_name())
+                .ok_or_else(|| SceneSpawnError::UnregisteredType {
+                    type_name: component.type_name().to_string(),
+                })?;
+            let reflect_component = registration.data::<ReflectComponent>().ok_or_else(|| {
+                SceneSpawnError::UnregisteredComponent {
+                    type_name: component.type_name().to_string(),
+                }
+            })?;
+            if !world.has_component_type(entity, registration.type_id()) {
+                reflect_component.add_component(world, resources, entity, &**component);
+            }
+        }
+
+        for child in self.children.iter() {
+            let child_entity = world.spawn((Parent(entity),));
+            child.write_entity(world, resources, type_registry, child_entity)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn serialize_ron(&self, registry: &TypeRegistryArc) -> Result<String, ron::Error> {
+        serialize_ron(PrefabSerializer::new(self, registry))
+    }
+}
+
+#[derive(Debug)]
+pub struct PrefabLoader {
+    type_registry: TypeRegistryArc,
+}
+
+impl FromResources for PrefabLoader {
+    fn from_resources(resources: &Resources) -> Self {
+        let type_registry = resources.get::<TypeRegistryArc>().unwrap();
+        PrefabLoader {
+            type_registry: (*type_registry).clone(),
+        }
+    }
+}
+
+impl AssetLoader for PrefabLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let mut deserializer = ron::de::Deserializer::from_bytes(bytes)?;
+            let prefab_deserializer = PrefabDeserializer {
+                type_registry: &self.type_registry.read(),
+            };
+            let prefab = prefab_deserializer.deserialize(&mut deserializer)?;
+            load_context.set_default_asset(LoadedAsset::new(prefab));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["prefab"]
+    }
+}
+
+/// Spawns prefabs that were not loaded yet when they were requested
+#[derive(Debug, Default)]
+pub struct PrefabSpawner {
+    pending: Vec<(Entity, Handle<Prefab>)>,
+}
+
+impl PrefabSpawner {
+    /// Writes `prefab` to `entity` as soon as it is loaded
+    pub fn spawn(&mut self, entity: Entity, prefab: Handle<Prefab>) {
+        self.pending.push((entity, prefab));
+    }
+
+    pub fn spawn_queued_prefabs(&mut self, world: &mut World, resources: &Resources) {
+        let prefabs = resources.get::<Assets<Prefab>>().unwrap();
+        self.pending.retain(|(entity, handle)| {
+            if !world.contains(*entity) {
+                return false;
+            }
+
+            match prefabs.get(handle) {
+                Some(prefab) => {
+                    if let Err(err) = prefab.write_to_world(world, resources, *entity) {
+                        error!("Failed to spawn prefab: {}", err);
+                    }
+                    false
+                }
+                None => true,
+            }
+        });
+    }
+}
+
+pub fn prefab_spawner_system(world: &mut World, resources: &mut Resources) {
+    let mut prefab_spawner = resources.get_mut::<PrefabSpawner>().unwrap();
+    prefab_spawner.spawn_queued_prefabs(world, resources);
+}
+
+pub struct SpawnPrefab {
+    entity: Entity,
+    prefab: Handle<Prefab>,
+}
+
+impl Command for SpawnPrefab {
+    fn write(self: Box<Self>, world: &mut World, resources: &mut Resources) {
+        let mut prefab_spawner = resources.get_mut::<PrefabSpawner>().unwrap();
+        prefab_spawner.spawn(self.entity, self.prefab);
+        prefab_spawner.spawn_queued_prefabs(world, resources);
+    }
+}
+
+pub trait SpawnPrefabCommands {
+    /// Spawns an entity from `prefab`. Components added to the returned entity override the
+    /// components of the prefab, even if the prefab has not finished loading yet.
+    fn spawn_prefab(&mut self, prefab: Handle<Prefab>) -> EntityCommands<'_>;
+}
+
+impl SpawnPrefabCommands for Commands {
+    fn spawn_prefab(&mut self, prefab: Handle<Prefab>) -> EntityCommands<'_> {
+        let entity = self.spawn_entity(()).id();
+        self.add_command(SpawnPrefab { entity, prefab });
+        self.entity(entity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_app::App;
+    use bevy_asset::{AddAsset, AssetPlugin, AssetServer, FileAssetIo, HandleId};
+    use bevy_reflect::{Reflect, ReflectPlugin, RegisterTypeBuilder};
+    use bevy_tasks::TaskPool;
+
+    #[derive(Reflect, Default, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Food {
+        calories: u32,
+    }
+
+    #[derive(Reflect, Default, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Sprite {
+        name: String,
+    }
+
+    const FOOD_PREFAB: &str = r#"(
+  components: [
+    {
+      "type": "bevy_scene::prefab::tests::Food",
+      "struct": {
+        "calories": {
+          "type": "u32",
+          "value": 10,
+        },
+      },
+    },
+  ],
+  children: [
+    (
+      components: [
+        {
+          "type": "bevy_scene::prefab::tests::Sprite",
+          "struct": {
+            "name": {
+              "type": "alloc::string::String",
+              "value": "apple",
+            },
+          },
+        },
+      ],
+    ),
+  ],
+)"#;
+
+    fn app() -> App {
+        let asset_server = AssetServer::new(FileAssetIo::new(""), TaskPool::new());
+        let mut app = App::build();
+        app.add_resource(asset_server)
+            .add_plugin(ReflectPlugin)
+            .add_plugin(AssetPlugin)
+            .add_asset::<Prefab>()
+            .init_resource::<PrefabSpawner>()
+            .register_type::<Food>()
+            .register_type::<Sprite>();
+        app.app
+    }
+
+    fn load(resources: &Resources, text: &str) -> Prefab {
+        let type_registry = resources.get::<TypeRegistryArc>().unwrap();
+        let type_registry = type_registry.read();
+        let mut deserializer = ron::de::Deserializer::from_str(text).unwrap();
+        PrefabDeserializer {
+            type_registry: &type_registry,
+        }
+        .deserialize(&mut deserializer)
+        .unwrap()
+    }
+
+    #[test]
+    fn spawn_prefab() {
+        let App {
+            mut world,
+            mut resources,
+            ..
+        } = app();
+        let prefab = load(&resources, FOOD_PREFAB);
+        let handle = resources.get_mut::<Assets<Prefab>>().unwrap().add(prefab);
+
+        let mut commands = Commands::default();
+        commands.set_entity_reserver(world.get_entity_reserver());
+        let plain = commands.spawn_prefab(handle.clone()).id();
+        let overridden = commands
+            .spawn_prefab(handle)
+            .with(Food { calories: 20 })
+            .id();
+        commands.apply(&mut world, &mut resources);
+
+        assert_eq!(*world.get::<Food>(plain).unwrap(), Food { calories: 10 });
+        assert_eq!(
+            *world.get::<Food>(overridden).unwrap(),
+            Food { calories: 20 }
+        );
+        let mut children = world
+            .query::<(&Parent, &Sprite)>()
+            .map(|(parent, sprite)| (parent.0, sprite.name.clone()))
+            .collect::<Vec<_>>();
+        children.sort();
+        let mut expected = vec![
+            (plain, "apple".to_string()),
+            (overridden, "apple".to_string()),
+        ];
+        expected.sort();
+        assert_eq!(children, expected);
+    }
+
+    #[test]
+    fn spawn_when_loaded() {
+        let App {
+            mut world,
+            mut resources,
+            ..
+        } = app();
+        let handle = Handle::<Prefab>::weak(HandleId::random::<Prefab>());
+
+        let mut commands = Commands::default();
+        commands.set_entity_reserver(world.get_entity_reserver());
+        let entity = commands
+            .spawn_prefab(handle.clone())
+            .with(Food { calories: 20 })
+            .id();
+        commands.apply(&mut world, &mut resources);
+        assert_eq!(world.query::<&Sprite>().count(), 0);
+
+        let prefab = load(&resources, FOOD_PREFAB);
+        resources
+            .get_mut::<Assets<Prefab>>()
+            .unwrap()
+            .set(handle, prefab);
+        prefab_spawner_system(&mut world, &mut resources);
+        assert_eq!(*world.get::<Food>(entity).unwrap(), Food { calories: 20 });
+        assert_eq!(world.query::<&Sprite>().count(), 1);
+        assert!(resources.get::<PrefabSpawner>().unwrap().pending.is_empty());
+    }
+
+    #[test]
+    fn ron_round_trip() {
+        let App { resources, .. } = app();
+        let prefab = load(&resources, FOOD_PREFAB);
+        let type_registry = resources.get::<TypeRegistryArc>().unwrap();
+        let text = prefab.serialize_ron(&type_registry).unwrap();
+        let reloaded = load(&resources, &text);
+        assert_eq!(reloaded.components.len(), 1);
+        assert_eq!(reloaded.children.len(), 1);
+        assert_eq!(reloaded.children[0].components.len(), 1);
+        assert!(reloaded.children[0].children.is_empty());
+    }
+}
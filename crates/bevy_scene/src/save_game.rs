@@ -0,0 +1,555 @@
+use crate::{
+    serde::{SceneDeserializer, SceneSerializer},
+    DynamicScene, DynamicSceneToWorldError, Entity,
+};
+use bevy_app::prelude::*;
+use bevy_ecs::{Component, Resource, Resources, World};
+use bevy_reflect::{GetTypeRegistration, ReflectComponent, RegisterTypeBuilder, TypeRegistryArc};
+use bevy_utils::{HashMap, HashSet};
+use serde::{
+    de::{DeserializeOwned, DeserializeSeed, Error, MapAccess, Visitor},
+    ser::{SerializeMap, SerializeSeq, SerializeStruct},
+    Deserialize, Serialize,
+};
+use std::{any::TypeId, collections::BTreeMap};
+use thiserror::Error;
+
+/// Adds a [SaveGameRegistry] to the app. Resources and components are included in save games by
+/// registering them with [RegisterSaveGame].
+#[derive(Default)]
+pub struct SaveGamePlugin {
+    /// The version written to new save games. Bump this (and add a migration with
+    /// [RegisterSaveGame::add_save_migration]) whenever the saved data changes shape.
+    pub version: u32,
+}
+
+impl Plugin for SaveGamePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.resources_mut()
+            .get_or_insert_with(SaveGameRegistry::default)
+            .version = self.version;
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum SaveGameError {
+    #[error("failed to serialize or deserialize save game")]
+    Ron(#[from] ron::Error),
+    #[error("failed to write save game entities to the world")]
+    Scene(#[from] DynamicSceneToWorldError),
+    #[error("save game version {version} is newer than the current version {current}")]
+    UnsupportedVersion { version: u32, current: u32 },
+    #[error("no migration is registered for save game version {version}")]
+    MissingMigration { version: u32 },
+    #[error("SaveGamePlugin has not been added to the app")]
+    MissingRegistry,
+}
+
+/// A migration that upgrades a [SaveGame] from one version to the next
+pub type SaveGameMigration = fn(&mut SaveGame);
+
+#[derive(Clone)]
+struct SaveResourceRegistration {
+    save: fn(&Resources) -> Option<Result<ron::Value, ron::Error>>,
+    load: fn(&mut Resources, ron::Value) -> Result<(), ron::Error>,
+}
+
+/// Tracks which resources and components are included in save games, and how to migrate old
+/// save games to the current version.
+#[derive(Default, Clone)]
+pub struct SaveGameRegistry {
+    version: u32,
+    resources: BTreeMap<String, SaveResourceRegistration>,
+    components: HashSet<TypeId>,
+    migrations: HashMap<u32, SaveGameMigration>,
+}
+
+impl SaveGameRegistry {
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Saves the resource `T` under `name`. The name is written to save games, so it should not
+    /// change when the type is renamed.
+    pub fn register_resource<T>(&mut self, name: &str)
+    where
+        T: Resource + Serialize + DeserializeOwned,
+    {
+        self.resources.insert(
+            name.to_string(),
+            SaveResourceRegistration {
+                save: save_resource::<T>,
+                load: load_resource::<T>,
+            },
+        );
+    }
+
+    /// Saves every entity's `T` component. `T` must be registered in the [TypeRegistryArc] with
+    /// `#[reflect(Component)]`.
+    pub fn register_component<T: Component>(&mut self) {
+        self.components.insert(TypeId::of::<T>());
+    }
+
+    /// Whether the component with this [TypeId] is saved
+    pub fn saves_component(&self, type_id: TypeId) -> bool {
+        self.components.contains(&type_id)
+    }
+
+    /// Adds a migration that upgrades save games from `from_version` to `from_version + 1`
+    pub fn add_migration(&mut self, from_version: u32, migration: SaveGameMigration) {
+        self.migrations.insert(from_version, migration);
+    }
+}
+
+fn save_resource<T>(resources: &Resources) -> Option<Result<ron::Value, ron::Error>>
+where
+    T: Resource + Serialize,
+{
+    let resource = resources.get::<T>()?;
+    Some(ron::to_string(&*resource).and_then(|text| ron::from_str(&text)))
+}
+
+fn load_resource<T>(resources: &mut Resources, value: ron::Value) -> Result<(), ron::Error>
+where
+    T: Resource + DeserializeOwned,
+{
+    resources.insert(value.into_rust::<T>()?);
+    Ok(())
+}
+
+pub trait RegisterSaveGame {
+    fn register_save_resource<T>(&mut self, name: &str) -> &mut Self
+    where
+        T: Resource + Serialize + DeserializeOwned;
+    fn register_save_component<T>(&mut self) -> &mut Self
+    where
+        T: Component + GetTypeRegistration;
+    fn add_save_migration(&mut self, from_version: u32, migration: SaveGameMigration) -> &mut Self;
+}
+
+impl RegisterSaveGame for AppBuilder {
+    fn register_save_resource<T>(&mut self, name: &str) -> &mut Self
+    where
+        T: Resource + Serialize + DeserializeOwned,
+    {
+        self.resources_mut()
+            .get_or_insert_with(SaveGameRegistry::default)
+            .register_resource::<T>(name);
+        self
+    }
+
+    fn register_save_component<T>(&mut self) -> &mut Self
+    where
+        T: Component + GetTypeRegistration,
+    {
+        self.register_type::<T>();
+        {
+            let type_registry = self.resources().get::<TypeRegistryArc>().unwrap();
+            let type_registry = type_registry.read();
+            if type_registry
+                .get(TypeId::of::<T>())
+                .and_then(|registration| registration.data::<ReflectComponent>())
+                .is_none()
+            {
+                panic!(
+                    "{} cannot be saved because it does not reflect Component. Add #[reflect(Component)] to it.",
+                    std::any::type_name::<T>()
+                );
+            }
+        }
+        self.resources_mut()
+            .get_or_insert_with(SaveGameRegistry::default)
+            .register_component::<T>();
+        self
+    }
+
+    fn add_save_migration(&mut self, from_version: u32, migration: SaveGameMigration) -> &mut Self {
+        self.resources_mut()
+            .get_or_insert_with(SaveGameRegistry::default)
+            .add_migration(from_version, migration);
+        self
+    }
+}
+
+/// A snapshot of the registered resources and components in a world, tagged with the
+/// [SaveGameRegistry] version it was written with.
+pub struct SaveGame {
+    pub version: u32,
+    /// Saved resources, keyed by the name they were registered with
+    pub resources: BTreeMap<String, ron::Value>,
+    /// Entities that have at least one saved component, with only their saved components
+    pub scene: DynamicScene,
+}
+
+impl SaveGame {
+    pub fn from_world(world: &World, resources: &Resources) -> Result<Self, SaveGameError> {
+        let registry = resources
+            .get::<SaveGameRegistry>()
+            .ok_or(SaveGameError::MissingRegistry)?;
+        let type_registry = resources.get::<TypeRegistryArc>().unwrap();
+        let type_registry = type_registry.read();
+
+        let mut saved_resources = BTreeMap::new();
+        for (name, registration) in registry.resources.iter() {
+            if let Some(value) = (registration.save)(resources) {
+                saved_resources.insert(name.clone(), value?);
+            }
+        }
+
+        let mut scene = DynamicScene::default();
+        for archetype in world.archetypes() {
+            let saved_types = archetype
+                .types()
+                .iter()
+                .filter(|type_info| registry.components.contains(&type_info.id()))
+                .filter_map(|type_info| type_registry.get(type_info.id()))
+                .filter_map(|registration| registration.data::<ReflectComponent>())
+                .collect::<Vec<_>>();
+            if saved_types.is_empty() {
+                continue;
+            }
+
+            for (index, entity) in archetype.iter_entities().enumerate() {
+                let components = saved_types
+                    .iter()
+                    // SAFE: the index comes directly from a currently live component
+                    .map(|reflect_component| unsafe {
+                        reflect_component
+                            .reflect_component(archetype, index)
+                            .clone_value()
+                    })
+                    .collect();
+                scene.entities.push(Entity {
+                    entity: entity.id(),
+                    components,
+                });
+            }
+        }
+
+        Ok(SaveGame {
+            version: registry.version,
+            resources: saved_resources,
+            scene,
+        })
+    }
+
+    /// Runs the registered migrations until this save game is at the current version
+    pub fn migrate(&mut self, registry: &SaveGameRegistry) -> Result<(), SaveGameError> {
+        if self.version > registry.version {
+            return Err(SaveGameError::UnsupportedVersion {
+                version: self.version,
+                current: registry.version,
+            });
+        }
+
+        while self.version < registry.version {
+            let migration =
+                registry
+                    .migrations
+                    .get(&self.version)
+                    .ok_or(SaveGameError::MissingMigration {
+                        version: self.version,
+                    })?;
+            migration(self);
+            self.version += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Migrates this save game to the current version, inserts its resources and spawns its
+    /// entities. Existing entities are left untouched, so despawn them first when replacing the
+    /// current game. Saved resources that are no longer registered are ignored.
+    pub fn write_to_world(
+        mut self,
+        world: &mut World,
+        resources: &mut Resources,
+    ) -> Result<(), SaveGameError> {
+        let registry = resources
+            .get_cloned::<SaveGameRegistry>()
+            .ok_or(SaveGameError::MissingRegistry)?;
+        self.migrate(&registry)?;
+
+        for (name, value) in self.resources {
+            if let Some(registration) = registry.resources.get(&name) {
+                (registration.load)(resources, value)?;
+            }
+        }
+
+        self.scene.write_to_world(world, resources)?;
+        Ok(())
+    }
+
+    pub fn serialize_ron(&self, registry: &TypeRegistryArc) -> Result<String, ron::Error> {
+        crate::serialize_ron(SaveGameSerializer {
+            save_game: self,
+            registry,
+        })
+    }
+
+    pub fn deserialize_ron(input: &str, registry: &TypeRegistryArc) -> Result<Self, ron::Error> {
+        let mut deserializer = ron::de::Deserializer::from_str(input)?;
+        SaveGameDeserializer { registry }.deserialize(&mut deserializer)
+    }
+}
+
+pub const SAVE_GAME_STRUCT: &str = "SaveGame";
+pub const SAVE_GAME_FIELD_VERSION: &str = "version";
+pub const SAVE_GAME_FIELD_RESOURCES: &str = "resources";
+pub const SAVE_GAME_FIELD_ENTITIES: &str = "entities";
+
+pub struct SaveGameSerializer<'a> {
+    pub save_game: &'a SaveGame,
+    pub registry: &'a TypeRegistryArc,
+}
+
+impl<'a> Serialize for SaveGameSerializer<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct(SAVE_GAME_STRUCT, 3)?;
+        state.serialize_field(SAVE_GAME_FIELD_VERSION, &self.save_game.version)?;
+        state.serialize_field(
+            SAVE_GAME_FIELD_RESOURCES,
+            &ResourcesSerializer {
+                resources: &self.save_game.resources,
+            },
+        )?;
+        state.serialize_field(
+            SAVE_GAME_FIELD_ENTITIES,
+            &SceneSerializer::new(&self.save_game.scene, self.registry),
+        )?;
+        state.end()
+    }
+}
+
+struct ResourcesSerializer<'a> {
+    resources: &'a BTreeMap<String, ron::Value>,
+}
+
+impl<'a> Serialize for ResourcesSerializer<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_map(Some(self.resources.len()))?;
+        for (name, value) in self.resources.iter() {
+            state.serialize_entry(name, &ValueSerializer { value })?;
+        }
+        state.end()
+    }
+}
+
+/// [ron::Value] serializes maps as newtype structs, which it cannot read back, so maps are
+/// written out directly here
+struct ValueSerializer<'a> {
+    value: &'a ron::Value,
+}
+
+impl<'a> Serialize for ValueSerializer<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self.value {
+            ron::Value::Map(map) => {
+                let mut state = serializer.serialize_map(Some(map.len()))?;
+                for (key, value) in map.iter() {
+                    state.serialize_entry(
+                        &ValueSerializer { value: key },
+                        &ValueSerializer { value },
+                    )?;
+                }
+                state.end()
+            }
+            ron::Value::Seq(seq) => {
+                let mut state = serializer.serialize_seq(Some(seq.len()))?;
+                for value in seq.iter() {
+                    state.serialize_element(&ValueSerializer { value })?;
+                }
+                state.end()
+            }
+            ron::Value::Option(Some(value)) => {
+                serializer.serialize_some(&ValueSerializer { value })
+            }
+            value => value.serialize(serializer),
+        }
+    }
+}
+
+pub struct SaveGameDeserializer<'a> {
+    pub registry: &'a TypeRegistryArc,
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for SaveGameDeserializer<'a> {
+    type Value = SaveGame;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_struct(
+            SAVE_GAME_STRUCT,
+            &[
+                SAVE_GAME_FIELD_VERSION,
+                SAVE_GAME_FIELD_RESOURCES,
+                SAVE_GAME_FIELD_ENTITIES,
+            ],
+            SaveGameVisitor {
+                registry: self.registry,
+            },
+        )
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum SaveGameField {
+    Version,
+    Resources,
+    Entities,
+}
+
+struct SaveGameVisitor<'a> {
+    pub registry: &'a TypeRegistryArc,
+}
+
+impl<'a, 'de> Visitor<'de> for SaveGameVisitor<'a> {
+    type Value = SaveGame;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("save game")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut version = None;
+        let mut resources = None;
+        let mut scene = None;
+        while let Some(key) = map.next_key()? {
+            match key {
+                SaveGameField::Version => {
+                    if version.is_some() {
+                        return Err(Error::duplicate_field(SAVE_GAME_FIELD_VERSION));
+                    }
+                    version = Some(map.next_value::<u32>()?);
+                }
+                SaveGameField::Resources => {
+                    if resources.is_some() {
+                        return Err(Error::duplicate_field(SAVE_GAME_FIELD_RESOURCES));
+                    }
+                    resources = Some(map.next_value::<BTreeMap<String, ron::Value>>()?);
+                }
+                SaveGameField::Entities => {
+                    if scene.is_some() {
+                        return Err(Error::duplicate_field(SAVE_GAME_FIELD_ENTITIES));
+                    }
+                    scene = Some(map.next_value_seed(SceneDeserializer {
+                        type_registry: &self.registry.read(),
+                    })?);
+                }
+            }
+        }
+
+        Ok(SaveGame {
+            version: version.ok_or_else(|| Error::missing_field(SAVE_GAME_FIELD_VERSION))?,
+            resources: resources.ok_or_else(|| Error::missing_field(SAVE_GAME_FIELD_RESOURCES))?,
+            scene: scene.ok_or_else(|| Error::missing_field(SAVE_GAME_FIELD_ENTITIES))?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_reflect::{Reflect, ReflectPlugin};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Score(u32);
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Settings {
+        volume: f32,
+        name: String,
+    }
+
+    #[derive(Reflect, Default, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Health {
+        value: u32,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct NotSaved;
+
+    fn app(version: u32) -> App {
+        let mut app = App::build();
+        app.add_plugin(ReflectPlugin)
+            .add_plugin(SaveGamePlugin { version })
+            .register_save_resource::<Score>("score")
+            .register_save_resource::<Settings>("settings")
+            .register_save_component::<Health>()
+            .add_save_migration(0, |save_game| {
+                if let Some(ron::Value::Seq(score)) = save_game.resources.get_mut("score") {
+                    score[0] = ron::Value::Number(ron::Number::new(100));
+                }
+            });
+        app.app
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut app = app(1);
+        app.resources.insert(Score(10));
+        app.resources.insert(Settings {
+            volume: 0.5,
+            name: "player".to_string(),
+        });
+        app.world.spawn((Health { value: 3 }, NotSaved));
+        app.world.spawn((NotSaved,));
+
+        let save_game = SaveGame::from_world(&app.world, &app.resources).unwrap();
+        assert_eq!(save_game.version, 1);
+        assert_eq!(save_game.scene.entities.len(), 1);
+        assert_eq!(save_game.scene.entities[0].components.len(), 1);
+
+        let type_registry = app.resources.get_cloned::<TypeRegistryArc>().unwrap();
+        let text = save_game.serialize_ron(&type_registry).unwrap();
+        let save_game = SaveGame::deserialize_ron(&text, &type_registry).unwrap();
+
+        let mut loaded = self::app(1);
+        save_game
+            .write_to_world(&mut loaded.world, &mut loaded.resources)
+            .unwrap();
+        assert_eq!(*loaded.resources.get::<Score>().unwrap(), Score(10));
+        assert_eq!(loaded.resources.get::<Settings>().unwrap().name, "player");
+        let health = loaded.world.query::<&Health>().collect::<Vec<_>>();
+        assert_eq!(health, vec![&Health { value: 3 }]);
+    }
+
+    #[test]
+    fn migration() {
+        let mut old = app(0);
+        old.resources.insert(Score(10));
+        let save_game = SaveGame::from_world(&old.world, &old.resources).unwrap();
+
+        let mut new = app(1);
+        save_game
+            .write_to_world(&mut new.world, &mut new.resources)
+            .unwrap();
+        assert_eq!(*new.resources.get::<Score>().unwrap(), Score(100));
+
+        let newer = SaveGame::from_world(&new.world, &new.resources).unwrap();
+        let result = newer.write_to_world(&mut old.world, &mut old.resources);
+        assert!(matches!(
+            result,
+            Err(SaveGameError::UnsupportedVersion {
+                version: 1,
+                current: 0
+            })
+        ));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{DynamicScene, Entity};
+use crate::{DynamicScene, Entity, Prefab};
 use anyhow::Result;
 use bevy_reflect::{
     serde::{ReflectDeserializer, ReflectSerializer},
@@ -249,3 +249,177 @@ impl<'a, 'de> Visitor<'de> for ComponentSeqVisitor<'a> {
         Ok(dynamic_properties)
     }
 }
+
+pub struct PrefabSerializer<'a> {
+    pub prefab: &'a Prefab,
+    pub registry: &'a TypeRegistryArc,
+}
+
+impl<'a> PrefabSerializer<'a> {
+    pub fn new(prefab: &'a Prefab, registry: &'a TypeRegistryArc) -> Self {
+        PrefabSerializer { prefab, registry }
+    }
+}
+
+impl<'a> Serialize for PrefabSerializer<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct(PREFAB_STRUCT, 2)?;
+        state.serialize_field(
+            PREFAB_FIELD_COMPONENTS,
+            &ComponentsSerializer {
+                components: &self.prefab.components,
+                registry: self.registry,
+            },
+        )?;
+        state.serialize_field(
+            PREFAB_FIELD_CHILDREN,
+            &PrefabsSerializer {
+                prefabs: &self.prefab.children,
+                registry: self.registry,
+            },
+        )?;
+        state.end()
+    }
+}
+
+struct PrefabsSerializer<'a> {
+    prefabs: &'a [Prefab],
+    registry: &'a TypeRegistryArc,
+}
+
+impl<'a> Serialize for PrefabsSerializer<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_seq(Some(self.prefabs.len()))?;
+        for prefab in self.prefabs.iter() {
+            state.serialize_element(&PrefabSerializer::new(prefab, self.registry))?;
+        }
+        state.end()
+    }
+}
+
+pub struct PrefabDeserializer<'a> {
+    pub type_registry: &'a TypeRegistry,
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for PrefabDeserializer<'a> {
+    type Value = Prefab;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_struct(
+            PREFAB_STRUCT,
+            &[PREFAB_FIELD_COMPONENTS, PREFAB_FIELD_CHILDREN],
+            PrefabVisitor {
+                registry: self.type_registry,
+            },
+        )
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum PrefabField {
+    Components,
+    Children,
+}
+
+pub const PREFAB_STRUCT: &str = "Prefab";
+pub const PREFAB_FIELD_COMPONENTS: &str = "components";
+pub const PREFAB_FIELD_CHILDREN: &str = "children";
+
+struct PrefabVisitor<'a> {
+    pub registry: &'a TypeRegistry,
+}
+
+impl<'a, 'de> Visitor<'de> for PrefabVisitor<'a> {
+    type Value = Prefab;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("prefab")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut components = None;
+        let mut children = None;
+        while let Some(key) = map.next_key()? {
+            match key {
+                PrefabField::Components => {
+                    if components.is_some() {
+                        return Err(Error::duplicate_field(PREFAB_FIELD_COMPONENTS));
+                    }
+                    components = Some(map.next_value_seed(ComponentVecDeserializer {
+                        registry: self.registry,
+                    })?);
+                }
+                PrefabField::Children => {
+                    if children.is_some() {
+                        return Err(Error::duplicate_field(PREFAB_FIELD_CHILDREN));
+                    }
+                    children = Some(map.next_value_seed(PrefabVecDeserializer {
+                        registry: self.registry,
+                    })?);
+                }
+            }
+        }
+
+        // both fields are optional, to keep small prefabs short
+        Ok(Prefab {
+            components: components.unwrap_or_default(),
+            children: children.unwrap_or_default(),
+        })
+    }
+}
+
+struct PrefabVecDeserializer<'a> {
+    registry: &'a TypeRegistry,
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for PrefabVecDeserializer<'a> {
+    type Value = Vec<Prefab>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(PrefabSeqVisitor {
+            registry: self.registry,
+        })
+    }
+}
+
+struct PrefabSeqVisitor<'a> {
+    registry: &'a TypeRegistry,
+}
+
+impl<'a, 'de> Visitor<'de> for PrefabSeqVisitor<'a> {
+    type Value = Vec<Prefab>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("list of prefabs")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut prefabs = Vec::new();
+        while let Some(prefab) = seq.next_element_seed(PrefabDeserializer {
+            type_registry: self.registry,
+        })? {
+            prefabs.push(prefab);
+        }
+
+        Ok(prefabs)
+    }
+}
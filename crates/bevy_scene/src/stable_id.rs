@@ -0,0 +1,195 @@
+use bevy_ecs::{Changed, Entity, Query, ResMut};
+use bevy_reflect::{Reflect, ReflectComponent, ReflectDeserialize};
+use bevy_utils::{tracing::warn, HashMap};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// An id that identifies an entity across saves and loads, unlike [Entity], which is assigned anew
+/// whenever an entity is spawned. Components that refer to other saved entities, like the segments
+/// of a snake or the owner of a chunk, store their [StableId]s and look them up in [StableIds].
+///
+/// [StableId::default] creates a new random id, so spawning an entity with it assigns the entity
+/// its id. Saved and loaded entities keep theirs, as long as [StableId] is saved with
+/// [RegisterSaveGame::register_save_component](crate::RegisterSaveGame::register_save_component).
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, Reflect,
+)]
+#[reflect_value(Component, PartialEq, Hash, Serialize, Deserialize)]
+pub struct StableId(pub Uuid);
+
+impl StableId {
+    pub fn new() -> Self {
+        StableId(Uuid::new_v4())
+    }
+}
+
+impl Default for StableId {
+    fn default() -> Self {
+        StableId::new()
+    }
+}
+
+/// Finds the entity with a given [StableId]. Updated by [stable_id_system] once per frame, in the
+/// POST_UPDATE stage, so entities spawned in UPDATE can be found from the next frame on.
+#[derive(Debug, Default)]
+pub struct StableIds {
+    entities: HashMap<StableId, Entity>,
+    ids: HashMap<Entity, StableId>,
+}
+
+impl StableIds {
+    pub fn get(&self, id: StableId) -> Option<Entity> {
+        self.entities.get(&id).copied()
+    }
+
+    pub fn get_id(&self, entity: Entity) -> Option<StableId> {
+        self.ids.get(&entity).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    fn insert(&mut self, entity: Entity, id: StableId) {
+        self.remove(entity);
+        if let Some(other) = self.entities.insert(id, entity) {
+            // usually caused by spawning the same saved entities twice
+            warn!(
+                "{:?} and {:?} have the same stable id {:?}, only {:?} can be looked up",
+                other, entity, id, entity
+            );
+            self.ids.remove(&other);
+        }
+        self.ids.insert(entity, id);
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        if let Some(id) = self.ids.remove(&entity) {
+            if self.entities.get(&id) == Some(&entity) {
+                self.entities.remove(&id);
+            }
+        }
+    }
+}
+
+pub fn stable_id_system(
+    mut stable_ids: ResMut<StableIds>,
+    query: Query<(Entity, &StableId), Changed<StableId>>,
+) {
+    for entity in query.removed::<StableId>() {
+        stable_ids.remove(*entity);
+    }
+    for (entity, id) in query.iter() {
+        stable_ids.insert(entity, *id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RegisterSaveGame, SaveGame, SaveGamePlugin};
+    use bevy_app::{stage, App};
+    use bevy_ecs::{IntoSystem, Resources, Stage, SystemStage, World};
+    use bevy_reflect::{ReflectPlugin, RegisterTypeBuilder, TypeRegistryArc};
+    use bevy_tasks::{ComputeTaskPool, TaskPool};
+
+    #[derive(Reflect, Default, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Owner {
+        chunk: StableId,
+    }
+
+    fn app() -> App {
+        let mut app = App::build();
+        app.add_plugin(ReflectPlugin)
+            .add_plugin(SaveGamePlugin::default())
+            .register_type::<StableId>()
+            .register_save_component::<StableId>()
+            .register_save_component::<Owner>();
+        app.app
+    }
+
+    fn update_stable_ids(app: &mut App) {
+        let mut stage = SystemStage::serial();
+        stage.add_system(stable_id_system.system());
+        stage.initialize(&mut app.world, &mut app.resources);
+        stage.run(&mut app.world, &mut app.resources);
+    }
+
+    #[test]
+    fn references_survive_save_and_load() {
+        let mut app = app();
+        let chunk_id = StableId::new();
+        app.world.spawn((chunk_id,));
+        app.world
+            .spawn((StableId::new(), Owner { chunk: chunk_id }));
+        let type_registry = app.resources.get_cloned::<TypeRegistryArc>().unwrap();
+        let text = SaveGame::from_world(&app.world, &app.resources)
+            .unwrap()
+            .serialize_ron(&type_registry)
+            .unwrap();
+
+        let mut loaded = self::app();
+        // shift the entity ids of the loaded world
+        loaded.world.spawn(());
+        loaded.resources.insert(StableIds::default());
+        SaveGame::deserialize_ron(&text, &type_registry)
+            .unwrap()
+            .write_to_world(&mut loaded.world, &mut loaded.resources)
+            .unwrap();
+        update_stable_ids(&mut loaded);
+
+        let stable_ids = loaded.resources.get::<StableIds>().unwrap();
+        assert_eq!(stable_ids.len(), 2);
+        let owner = loaded.world.query::<&Owner>().next().unwrap();
+        let chunk = stable_ids.get(owner.chunk).unwrap();
+        assert_eq!(loaded.world.get::<StableId>(chunk).unwrap(), &chunk_id);
+        assert_eq!(stable_ids.get_id(chunk), Some(chunk_id));
+    }
+
+    /// Spawns and despawns entities in UPDATE, in a later stage than stable ids would be updated
+    /// in if they were updated before UPDATE
+    #[derive(Default)]
+    struct Edits {
+        spawn: Option<StableId>,
+        despawn: Option<Entity>,
+        spawned: Option<Entity>,
+    }
+
+    fn edit_system(world: &mut World, resources: &mut Resources) {
+        let mut edits = resources.get_mut::<Edits>().unwrap();
+        if let Some(id) = edits.spawn.take() {
+            edits.spawned = Some(world.spawn((id,)));
+        }
+        if let Some(entity) = edits.despawn.take() {
+            world.despawn(entity).unwrap();
+        }
+    }
+
+    #[test]
+    fn forget_despawned_entities() {
+        let mut app = App::build();
+        app.add_resource(ComputeTaskPool(TaskPool::default()))
+            .init_resource::<StableIds>()
+            .init_resource::<Edits>()
+            .add_system_to_stage(stage::UPDATE, edit_system.system())
+            .add_system_to_stage(stage::POST_UPDATE, stable_id_system.system());
+        let mut app = app.app;
+        let id = StableId::new();
+        app.resources.get_mut::<Edits>().unwrap().spawn = Some(id);
+        app.update();
+        let entity = app.resources.get::<Edits>().unwrap().spawned.unwrap();
+        assert_eq!(
+            app.resources.get::<StableIds>().unwrap().get(id),
+            Some(entity)
+        );
+
+        app.resources.get_mut::<Edits>().unwrap().despawn = Some(entity);
+        app.update();
+        assert!(app.resources.get::<StableIds>().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,204 @@
+use bevy_app::prelude::*;
+use bevy_ecs::{Entity, IntoSystem, Resources, With, World};
+use bevy_reflect::{serde::ReflectSerializer, ReflectComponent, TypeRegistry, TypeRegistryArc};
+use bevy_utils::{tracing::info, HashMap};
+use std::{collections::BTreeMap, fmt};
+
+/// Add this component to an entity to log the changes to its components every frame. Only
+/// components registered with `#[reflect(Component)]` are compared. Requires [WorldDiffPlugin].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WatchChanges;
+
+/// Logs the changes to the components of [WatchChanges] entities at the end of every frame. This
+/// serializes every reflected component of the watched entities each frame, so it is meant for
+/// debugging only.
+#[derive(Default)]
+pub struct WorldDiffPlugin;
+
+impl Plugin for WorldDiffPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<WatchedSnapshots>()
+            .add_system_to_stage(stage::LAST, world_diff_system.system());
+    }
+}
+
+/// The serialized values of an entity's reflected components at one point in time
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct EntitySnapshot {
+    components: BTreeMap<&'static str, String>,
+}
+
+impl EntitySnapshot {
+    /// Serializes the reflected components of `entity`. Returns `None` if the entity does not
+    /// exist.
+    pub fn capture(world: &World, entity: Entity, type_registry: &TypeRegistry) -> Option<Self> {
+        let location = world.get_entity_location(entity)?;
+        let archetype = world.archetypes().nth(location.archetype as usize)?;
+        let mut components = BTreeMap::new();
+        for type_info in archetype.types() {
+            let registration = match type_registry.get(type_info.id()) {
+                Some(registration) => registration,
+                None => continue,
+            };
+            let reflect_component = match registration.data::<ReflectComponent>() {
+                Some(reflect_component) => reflect_component,
+                None => continue,
+            };
+
+            // SAFE: the index comes directly from the entity's current location
+            let component =
+                unsafe { reflect_component.reflect_component(archetype, location.index) };
+            let value = ron::to_string(&ReflectSerializer::new(component, type_registry))
+                .unwrap_or_else(|err| format!("<failed to serialize: {}>", err));
+            components.insert(registration.name(), value);
+        }
+
+        Some(EntitySnapshot { components })
+    }
+
+    /// Returns the serialized value of a component, by its type name
+    pub fn get(&self, type_name: &str) -> Option<&str> {
+        self.components.get(type_name).map(|value| value.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        self.components
+            .iter()
+            .map(|(type_name, value)| (*type_name, value.as_str()))
+    }
+
+    /// Returns the components that were added, removed or changed in `after`
+    pub fn diff(&self, after: &EntitySnapshot) -> Vec<ComponentDiff> {
+        let mut diffs = Vec::new();
+        for (type_name, before) in self.components.iter() {
+            match after.components.get(type_name) {
+                Some(after) if after != before => diffs.push(ComponentDiff::Changed {
+                    type_name,
+                    before: before.clone(),
+                    after: after.clone(),
+                }),
+                Some(_) => {}
+                None => diffs.push(ComponentDiff::Removed {
+                    type_name,
+                    value: before.clone(),
+                }),
+            }
+        }
+
+        for (type_name, value) in after.components.iter() {
+            if !self.components.contains_key(type_name) {
+                diffs.push(ComponentDiff::Added {
+                    type_name,
+                    value: value.clone(),
+                });
+            }
+        }
+
+        diffs
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComponentDiff {
+    Added {
+        type_name: &'static str,
+        value: String,
+    },
+    Removed {
+        type_name: &'static str,
+        value: String,
+    },
+    Changed {
+        type_name: &'static str,
+        before: String,
+        after: String,
+    },
+}
+
+impl fmt::Display for ComponentDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComponentDiff::Added { type_name, value } => write!(f, "+ {}: {}", type_name, value),
+            ComponentDiff::Removed { type_name, value } => write!(f, "- {}: {}", type_name, value),
+            ComponentDiff::Changed {
+                type_name,
+                before,
+                after,
+            } => write!(f, "~ {}: {} -> {}", type_name, before, after),
+        }
+    }
+}
+
+/// The snapshots of [WatchChanges] entities from the previous frame
+#[derive(Default)]
+struct WatchedSnapshots {
+    frame: u64,
+    snapshots: HashMap<Entity, EntitySnapshot>,
+}
+
+fn world_diff_system(world: &mut World, resources: &mut Resources) {
+    let type_registry = resources.get::<TypeRegistryArc>().unwrap();
+    let type_registry = type_registry.read();
+    let mut watched = resources.get_mut::<WatchedSnapshots>().unwrap();
+    watched.frame += 1;
+
+    let mut snapshots = HashMap::default();
+    for entity in world.query_filtered::<Entity, With<WatchChanges>>() {
+        let snapshot = match EntitySnapshot::capture(world, entity, &type_registry) {
+            Some(snapshot) => snapshot,
+            None => continue,
+        };
+        // the first snapshot of an entity is the baseline that later frames are compared to
+        if let Some(previous) = watched.snapshots.get(&entity) {
+            for diff in previous.diff(&snapshot) {
+                info!("frame {} {:?}: {}", watched.frame, entity, diff);
+            }
+        }
+        snapshots.insert(entity, snapshot);
+    }
+
+    // despawned or unwatched entities are forgotten
+    watched.snapshots = snapshots;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_reflect::{GetTypeRegistration, Reflect};
+
+    #[derive(Reflect, Default)]
+    #[reflect(Component)]
+    struct Health(u32);
+
+    #[derive(Reflect, Default)]
+    #[reflect(Component)]
+    struct Name(String);
+
+    #[test]
+    fn entity_snapshot_diff() {
+        let mut registry = TypeRegistry::default();
+        registry.add_registration(Health::get_type_registration());
+        registry.add_registration(Name::get_type_registration());
+
+        let mut world = World::default();
+        let entity = world.spawn((Health(10), WatchChanges));
+        let before = EntitySnapshot::capture(&world, entity, &registry).unwrap();
+        assert_eq!(before.iter().count(), 1);
+
+        world.get_mut::<Health>(entity).unwrap().0 = 5;
+        world.insert_one(entity, Name("ghost".to_string())).unwrap();
+        let after = EntitySnapshot::capture(&world, entity, &registry).unwrap();
+
+        let diffs = before.diff(&after);
+        assert_eq!(diffs.len(), 2);
+        assert!(matches!(
+            diffs[0],
+            ComponentDiff::Changed { type_name, .. } if type_name == std::any::type_name::<Health>()
+        ));
+        assert!(matches!(
+            diffs[1],
+            ComponentDiff::Added { type_name, .. } if type_name == std::any::type_name::<Name>()
+        ));
+        assert!(after.diff(&after).is_empty());
+    }
+}
@@ -0,0 +1,298 @@
+use crate::{Script, ScriptValue, ScriptWorld};
+use bevy_app::{EventReader, Events};
+use bevy_asset::{AssetEvent, Assets, Handle, HandleId};
+use bevy_core::Time;
+use bevy_ecs::{Entity, FromResources, Resources, World};
+use bevy_reflect::TypeRegistryArc;
+use bevy_utils::{tracing::error, HashMap};
+use rhai::{Array, Dynamic, Engine, ImmutableString, RegisterFn, Scope, AST, FLOAT, INT};
+use std::sync::Arc;
+
+/// The scripting engine and the compiled scripts. Scripts are compiled when they are loaded, and
+/// compiled again whenever they change, so edited scripts take effect without restarting the app
+/// when the [AssetServer](bevy_asset::AssetServer) watches for changes.
+pub struct ScriptEngine {
+    engine: Arc<Engine>,
+    compiled: HashMap<HandleId, AST>,
+}
+
+impl Default for ScriptEngine {
+    fn default() -> Self {
+        ScriptEngine {
+            engine: Arc::new(create_engine()),
+            compiled: HashMap::default(),
+        }
+    }
+}
+
+impl ScriptEngine {
+    /// Compiles `script`, logging syntax errors. The previous version of the script keeps running
+    /// if the new one doesn't compile.
+    pub fn compile(&mut self, handle: &Handle<Script>, script: &Script) {
+        match self.engine.compile(&script.source) {
+            Ok(ast) => {
+                self.compiled.insert(handle.id, ast);
+            }
+            Err(err) => error!("failed to compile script {:?}: {}", handle.id, err),
+        }
+    }
+
+    pub fn is_compiled(&self, handle: &Handle<Script>) -> bool {
+        self.compiled.contains_key(&handle.id)
+    }
+}
+
+/// The scripts that run every frame, in order. Each script defines an `update(world, delta)`
+/// function, like:
+///
+/// ```text
+/// fn update(world, delta) {
+///     for entity in world.entities_with("Grass") {
+///         let height = world.get(entity, "Grass", "height");
+///         world.set(entity, "Grass", "height", height + delta);
+///     }
+/// }
+/// ```
+///
+/// Entities are passed to scripts as integers. The `world` provides:
+/// * `entities_with(component)`: the entities that have the component
+/// * `has(entity, component)`
+/// * `get(entity, component, path)`: a bool, number or string field, or `()` if it doesn't exist
+/// * `set(entity, component, path, value)`: returns `false` if the field doesn't exist or can't
+///   hold the value
+/// * `spawn()`: spawns an entity without components
+/// * `insert(entity, component)`: adds a component with its default value
+/// * `despawn(entity)`
+///
+/// Components must be registered with `#[reflect(Component)]`.
+///
+/// With the `bevy_tilemap` feature, which is on by default, the `world` also provides the tiles
+/// of the `WorldTileStore`:
+/// * `tile(x, y)`: the id of the tile, which is 0 for empty tiles
+/// * `tile_name(x, y)`: the name of the tile's kind, or `()` if it is empty
+/// * `tile_id(name)`: the id of a tile kind, or `()` if it isn't registered
+/// * `set_tile(x, y, tile)`: places a tile by id or name, and returns `false` if the tile didn't
+///   change or isn't registered
+/// * `is_solid(x, y)`
+#[derive(Default)]
+pub struct ScriptSystems {
+    scripts: Vec<Handle<Script>>,
+}
+
+impl ScriptSystems {
+    pub fn add(&mut self, script: Handle<Script>) {
+        self.scripts.push(script);
+    }
+
+    pub fn remove(&mut self, script: &Handle<Script>) {
+        self.scripts.retain(|handle| handle != script);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Handle<Script>> {
+        self.scripts.iter()
+    }
+}
+
+pub struct ScriptSystemState {
+    script_event_reader: EventReader<AssetEvent<Script>>,
+}
+
+impl FromResources for ScriptSystemState {
+    fn from_resources(resources: &Resources) -> Self {
+        let events = resources.get::<Events<AssetEvent<Script>>>().unwrap();
+        ScriptSystemState {
+            script_event_reader: events.get_reader(),
+        }
+    }
+}
+
+/// Compiles new and changed scripts, then calls the `update` function of each of the
+/// [ScriptSystems]
+pub fn script_system(world: &mut World, resources: &mut Resources) {
+    let (engine, asts) = {
+        let mut state = resources.get_mut::<ScriptSystemState>().unwrap();
+        let events = resources.get::<Events<AssetEvent<Script>>>().unwrap();
+        let scripts = resources.get::<Assets<Script>>().unwrap();
+        let mut script_engine = resources.get_mut::<ScriptEngine>().unwrap();
+        for event in state.script_event_reader.iter(&events) {
+            match event {
+                AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                    if let Some(script) = scripts.get(handle) {
+                        script_engine.compile(handle, script);
+                    }
+                }
+                AssetEvent::Removed { handle } => {
+                    script_engine.compiled.remove(&handle.id);
+                }
+            }
+        }
+
+        let script_systems = resources.get::<ScriptSystems>().unwrap();
+        let asts = script_systems
+            .iter()
+            .filter_map(|handle| script_engine.compiled.get(&handle.id).cloned())
+            .collect::<Vec<_>>();
+        (script_engine.engine.clone(), asts)
+    };
+    if asts.is_empty() {
+        return;
+    }
+
+    let delta = resources
+        .get::<Time>()
+        .map_or(0.0, |time| time.delta_seconds_f64());
+    let type_registry = resources.get_cloned::<TypeRegistryArc>().unwrap();
+    let script_world = ScriptWorld::new(
+        std::mem::take(world),
+        std::mem::take(resources),
+        type_registry,
+    );
+    for ast in asts.iter() {
+        let result = engine.call_fn::<_, Dynamic>(
+            &mut Scope::new(),
+            ast,
+            "update",
+            (script_world.clone(), delta as FLOAT),
+        );
+        if let Err(err) = result {
+            error!("script failed: {}", err);
+        }
+    }
+    let (script_world, script_resources) = script_world.take();
+    *world = script_world;
+    *resources = script_resources;
+}
+
+fn to_entity(entity: INT) -> Entity {
+    Entity::from_bits(entity as u64)
+}
+
+fn from_entity(entity: Entity) -> INT {
+    entity.to_bits() as INT
+}
+
+fn to_dynamic(value: Option<ScriptValue>) -> Dynamic {
+    match value {
+        Some(ScriptValue::Bool(value)) => Dynamic::from(value),
+        Some(ScriptValue::Int(value)) => Dynamic::from(value),
+        Some(ScriptValue::Float(value)) => Dynamic::from(value),
+        Some(ScriptValue::String(value)) => Dynamic::from(value),
+        None => Dynamic::from(()),
+    }
+}
+
+fn from_dynamic(value: Dynamic) -> Option<ScriptValue> {
+    if let Some(value) = value.clone().try_cast::<bool>() {
+        return Some(ScriptValue::Bool(value));
+    }
+    if let Some(value) = value.clone().try_cast::<INT>() {
+        return Some(ScriptValue::Int(value));
+    }
+    if let Some(value) = value.clone().try_cast::<FLOAT>() {
+        return Some(ScriptValue::Float(value));
+    }
+    value
+        .try_cast::<ImmutableString>()
+        .map(|value| ScriptValue::String(value.to_string()))
+}
+
+fn create_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.register_type_with_name::<ScriptWorld>("World");
+    engine.register_fn(
+        "entities_with",
+        |world: &mut ScriptWorld, component: ImmutableString| -> Array {
+            world
+                .entities_with(&component)
+                .into_iter()
+                .map(|entity| Dynamic::from(from_entity(entity)))
+                .collect()
+        },
+    );
+    engine.register_fn(
+        "has",
+        |world: &mut ScriptWorld, entity: INT, component: ImmutableString| {
+            world.has(to_entity(entity), &component)
+        },
+    );
+    engine.register_fn(
+        "get",
+        |world: &mut ScriptWorld,
+         entity: INT,
+         component: ImmutableString,
+         path: ImmutableString| {
+            to_dynamic(world.get(to_entity(entity), &component, &path))
+        },
+    );
+    engine.register_fn(
+        "set",
+        |world: &mut ScriptWorld,
+         entity: INT,
+         component: ImmutableString,
+         path: ImmutableString,
+         value: Dynamic| {
+            from_dynamic(value).map_or(false, |value| {
+                world.set(to_entity(entity), &component, &path, &value)
+            })
+        },
+    );
+    engine.register_fn("spawn", |world: &mut ScriptWorld| {
+        from_entity(world.spawn())
+    });
+    engine.register_fn(
+        "insert",
+        |world: &mut ScriptWorld, entity: INT, component: ImmutableString| {
+            world.insert(to_entity(entity), &component)
+        },
+    );
+    engine.register_fn("despawn", |world: &mut ScriptWorld, entity: INT| {
+        world.despawn(to_entity(entity))
+    });
+    #[cfg(feature = "bevy_tilemap")]
+    register_tile_fns(&mut engine);
+    engine
+}
+
+#[cfg(feature = "bevy_tilemap")]
+fn register_tile_fns(engine: &mut Engine) {
+    use bevy_tilemap::TileId;
+    use std::convert::TryFrom;
+
+    engine.register_fn("tile", |world: &mut ScriptWorld, x: INT, y: INT| {
+        world
+            .tile(x as i32, y as i32)
+            .map_or(0, |tile| tile.0 as INT)
+    });
+    engine.register_fn("tile_name", |world: &mut ScriptWorld, x: INT, y: INT| {
+        to_dynamic(world.tile_name(x as i32, y as i32).map(ScriptValue::String))
+    });
+    engine.register_fn(
+        "tile_id",
+        |world: &mut ScriptWorld, name: ImmutableString| {
+            to_dynamic(
+                world
+                    .tile_id(&name)
+                    .map(|tile| ScriptValue::Int(tile.0 as INT)),
+            )
+        },
+    );
+    engine.register_fn(
+        "set_tile",
+        |world: &mut ScriptWorld, x: INT, y: INT, tile: INT| {
+            u16::try_from(tile).map_or(false, |tile| {
+                world.set_tile(x as i32, y as i32, TileId(tile))
+            })
+        },
+    );
+    engine.register_fn(
+        "set_tile",
+        |world: &mut ScriptWorld, x: INT, y: INT, name: ImmutableString| {
+            world
+                .tile_id(&name)
+                .map_or(false, |tile| world.set_tile(x as i32, y as i32, tile))
+        },
+    );
+    engine.register_fn("is_solid", |world: &mut ScriptWorld, x: INT, y: INT| {
+        world.is_solid(x as i32, y as i32)
+    });
+}
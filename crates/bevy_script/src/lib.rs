@@ -0,0 +1,33 @@
+mod engine;
+mod script;
+mod value;
+mod world;
+
+pub use engine::*;
+pub use script::*;
+pub use value::*;
+pub use world::*;
+
+pub mod prelude {
+    pub use crate::{Script, ScriptPlugin, ScriptSystems};
+}
+
+use bevy_app::prelude::*;
+use bevy_asset::AddAsset;
+use bevy_ecs::IntoSystem;
+
+/// Runs gameplay systems written in [Rhai](https://rhai.rs) scripts. Scripts are loaded as
+/// [Script] assets and added to [ScriptSystems] to run once per frame.
+#[derive(Default)]
+pub struct ScriptPlugin;
+
+impl Plugin for ScriptPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_asset::<Script>()
+            .init_asset_loader::<ScriptLoader>()
+            .init_resource::<ScriptEngine>()
+            .init_resource::<ScriptSystems>()
+            .init_resource::<ScriptSystemState>()
+            .add_system(script_system.system());
+    }
+}
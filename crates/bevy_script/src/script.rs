@@ -0,0 +1,33 @@
+use anyhow::Result;
+use bevy_asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy_reflect::TypeUuid;
+use bevy_utils::BoxedFuture;
+
+/// The source code of a script
+#[derive(Debug, TypeUuid)]
+#[uuid = "6a1b3f0e-94c2-4d1f-8e57-0b7d7a2c9e43"]
+pub struct Script {
+    pub source: String,
+}
+
+/// Loads [Script]s from `.rhai` files
+#[derive(Default)]
+pub struct ScriptLoader;
+
+impl AssetLoader for ScriptLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let source = std::str::from_utf8(bytes)?.to_string();
+            load_context.set_default_asset(LoadedAsset::new(Script { source }));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["rhai"]
+    }
+}
@@ -0,0 +1,121 @@
+use bevy_reflect::Reflect;
+
+/// A value passed between scripts and reflected component fields. Scripts only see numbers,
+/// booleans and strings, so fields of other types can't be read or written from scripts, but
+/// their numeric fields can, like `translation.x`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+macro_rules! downcast_ints {
+    ($value:expr, $($ty:ty),*) => {
+        $(
+            if let Some(value) = $value.downcast_ref::<$ty>() {
+                return Some(ScriptValue::Int(*value as i64));
+            }
+        )*
+    };
+}
+
+macro_rules! apply_ints {
+    ($target:expr, $value:expr, $($ty:ty),*) => {
+        $(
+            if let Some(target) = $target.downcast_mut::<$ty>() {
+                *target = $value as $ty;
+                return true;
+            }
+        )*
+    };
+}
+
+impl ScriptValue {
+    /// Converts a reflected bool, number or string
+    pub fn from_reflect(value: &dyn Reflect) -> Option<ScriptValue> {
+        if let Some(value) = value.downcast_ref::<f32>() {
+            return Some(ScriptValue::Float(*value as f64));
+        }
+        if let Some(value) = value.downcast_ref::<f64>() {
+            return Some(ScriptValue::Float(*value));
+        }
+        downcast_ints!(value, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+        if let Some(value) = value.downcast_ref::<bool>() {
+            return Some(ScriptValue::Bool(*value));
+        }
+        if let Some(value) = value.downcast_ref::<String>() {
+            return Some(ScriptValue::String(value.clone()));
+        }
+        None
+    }
+
+    /// Writes this value to a reflected field, converting between integers and floats. Returns
+    /// `false` if the field has another type.
+    pub fn apply_to(&self, target: &mut dyn Reflect) -> bool {
+        match self {
+            ScriptValue::Int(value) => {
+                if let Some(target) = target.downcast_mut::<f32>() {
+                    *target = *value as f32;
+                    return true;
+                }
+                if let Some(target) = target.downcast_mut::<f64>() {
+                    *target = *value as f64;
+                    return true;
+                }
+                apply_ints!(target, *value, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+                false
+            }
+            ScriptValue::Float(value) => {
+                if let Some(target) = target.downcast_mut::<f32>() {
+                    *target = *value as f32;
+                    return true;
+                }
+                if let Some(target) = target.downcast_mut::<f64>() {
+                    *target = *value;
+                    return true;
+                }
+                apply_ints!(target, *value, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+                false
+            }
+            ScriptValue::Bool(value) => match target.downcast_mut::<bool>() {
+                Some(target) => {
+                    *target = *value;
+                    true
+                }
+                None => false,
+            },
+            ScriptValue::String(value) => match target.downcast_mut::<String>() {
+                Some(target) => {
+                    *target = value.clone();
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_numbers() {
+        assert_eq!(
+            ScriptValue::from_reflect(&2.5f32),
+            Some(ScriptValue::Float(2.5))
+        );
+        assert_eq!(ScriptValue::from_reflect(&7u8), Some(ScriptValue::Int(7)));
+        assert_eq!(ScriptValue::from_reflect(&vec![1u8]), None);
+
+        let mut target = 0u32;
+        assert!(ScriptValue::Float(3.9).apply_to(&mut target));
+        assert_eq!(target, 3);
+        let mut target = 0.0f32;
+        assert!(ScriptValue::Int(4).apply_to(&mut target));
+        assert_eq!(target, 4.0);
+        assert!(!ScriptValue::Bool(true).apply_to(&mut target));
+    }
+}
@@ -0,0 +1,276 @@
+use crate::ScriptValue;
+use bevy_ecs::{Entity, Resources, World};
+#[cfg(feature = "bevy_tilemap")]
+use bevy_math::IVec2;
+use bevy_reflect::{GetPath, ReflectComponent, TypeRegistryArc};
+#[cfg(feature = "bevy_tilemap")]
+use bevy_tilemap::{TileId, TileRegistry, WorldTileStore};
+use parking_lot::Mutex;
+use std::{any::TypeId, sync::Arc};
+
+/// The world as scripts see it. Components are named by their full type name (or short name, if
+/// it is unique) and must be registered with `#[reflect(Component)]`.
+///
+/// Scripts run with the [World] and [Resources] moved into the [ScriptWorld], so scripts can't
+/// run in parallel with other systems. [ScriptWorld::take] moves them back out.
+#[derive(Clone)]
+pub struct ScriptWorld {
+    inner: Arc<Mutex<(World, Resources)>>,
+    type_registry: TypeRegistryArc,
+}
+
+impl ScriptWorld {
+    pub fn new(world: World, resources: Resources, type_registry: TypeRegistryArc) -> Self {
+        ScriptWorld {
+            inner: Arc::new(Mutex::new((world, resources))),
+            type_registry,
+        }
+    }
+
+    /// Moves the world and resources out again, leaving empty ones for any clones scripts kept
+    pub fn take(&self) -> (World, Resources) {
+        std::mem::take(&mut *self.inner.lock())
+    }
+
+    /// Looks up a registered component type by its full or short name
+    fn component_type(&self, component: &str) -> Option<(TypeId, ReflectComponent)> {
+        let type_registry = self.type_registry.read();
+        let registration = type_registry
+            .get_with_name(component)
+            .or_else(|| type_registry.get_with_short_name(component))?;
+        let reflect_component = registration.data::<ReflectComponent>()?.clone();
+        Some((registration.type_id(), reflect_component))
+    }
+
+    /// The entities that have the component
+    pub fn entities_with(&self, component: &str) -> Vec<Entity> {
+        let type_id = match self.component_type(component) {
+            Some((type_id, _)) => type_id,
+            None => return Vec::new(),
+        };
+        let inner = self.inner.lock();
+        let mut entities = Vec::new();
+        for archetype in inner.0.archetypes() {
+            if archetype.has_type(type_id) {
+                entities.extend(archetype.iter_entities().copied());
+            }
+        }
+        entities
+    }
+
+    pub fn has(&self, entity: Entity, component: &str) -> bool {
+        self.component_type(component)
+            .map_or(false, |(type_id, _)| {
+                self.inner.lock().0.has_component_type(entity, type_id)
+            })
+    }
+
+    /// Reads a field of a component, like `"translation.x"`, or the whole component if `path` is
+    /// empty. Returns `None` if the entity doesn't have the component, or the field isn't a bool,
+    /// number or string.
+    pub fn get(&self, entity: Entity, component: &str, path: &str) -> Option<ScriptValue> {
+        let (type_id, reflect_component) = self.component_type(component)?;
+        let inner = self.inner.lock();
+        let location = inner.0.get_entity_location(entity)?;
+        let archetype = inner.0.archetypes().nth(location.archetype as usize)?;
+        if !archetype.has_type(type_id) {
+            return None;
+        }
+        // SAFE: the archetype has the component, and the index comes from the entity's location
+        let component = unsafe { reflect_component.reflect_component(archetype, location.index) };
+        if path.is_empty() {
+            ScriptValue::from_reflect(component)
+        } else {
+            ScriptValue::from_reflect(component.path(path).ok()?)
+        }
+    }
+
+    /// Writes a field of a component. Returns `false` if the entity doesn't have the component, or
+    /// the field can't hold the value.
+    pub fn set(&self, entity: Entity, component: &str, path: &str, value: &ScriptValue) -> bool {
+        let reflect_component = match self.component_type(component) {
+            Some((_, reflect_component)) => reflect_component,
+            None => return false,
+        };
+        let mut applied = false;
+        reflect_component.modify_component(&mut self.inner.lock().0, entity, &mut |component| {
+            applied = if path.is_empty() {
+                value.apply_to(component)
+            } else {
+                component
+                    .path_mut(path)
+                    .map_or(false, |field| value.apply_to(field))
+            };
+        });
+        applied
+    }
+
+    pub fn spawn(&self) -> Entity {
+        self.inner.lock().0.spawn(())
+    }
+
+    /// Adds a component with its default value, replacing the existing component
+    pub fn insert(&self, entity: Entity, component: &str) -> bool {
+        let reflect_component = match self.component_type(component) {
+            Some((_, reflect_component)) => reflect_component,
+            None => return false,
+        };
+        let mut inner = self.inner.lock();
+        let (world, resources) = &mut *inner;
+        if !world.contains(entity) {
+            return false;
+        }
+        reflect_component.add_default_component(world, resources, entity);
+        true
+    }
+
+    pub fn despawn(&self, entity: Entity) -> bool {
+        self.inner.lock().0.despawn(entity).is_ok()
+    }
+
+    /// The id of the tile at `x`, `y` in the [WorldTileStore], which is 0 for empty tiles
+    #[cfg(feature = "bevy_tilemap")]
+    pub fn tile(&self, x: i32, y: i32) -> Option<TileId> {
+        let inner = self.inner.lock();
+        let store = inner.1.get::<WorldTileStore>()?;
+        Some(store.get(IVec2::new(x, y)))
+    }
+
+    /// The name of the kind of the tile at `x`, `y`, or `None` for empty tiles
+    #[cfg(feature = "bevy_tilemap")]
+    pub fn tile_name(&self, x: i32, y: i32) -> Option<String> {
+        let tile = self.tile(x, y)?;
+        let inner = self.inner.lock();
+        let registry = inner.1.get::<TileRegistry>()?;
+        registry.get(tile).map(|kind| kind.name.clone())
+    }
+
+    /// The id of the tile kind called `name`
+    #[cfg(feature = "bevy_tilemap")]
+    pub fn tile_id(&self, name: &str) -> Option<TileId> {
+        let inner = self.inner.lock();
+        let registry = inner.1.get::<TileRegistry>()?;
+        registry.id(name)
+    }
+
+    /// Places a tile at `x`, `y` in the [WorldTileStore]. Returns `false` if the tile didn't
+    /// change, or `tile` isn't empty or registered.
+    #[cfg(feature = "bevy_tilemap")]
+    pub fn set_tile(&self, x: i32, y: i32, tile: TileId) -> bool {
+        let inner = self.inner.lock();
+        let registered = tile == TileId::EMPTY
+            || inner
+                .1
+                .get::<TileRegistry>()
+                .map_or(false, |registry| registry.get(tile).is_some());
+        if !registered {
+            return false;
+        }
+        inner
+            .1
+            .get_mut::<WorldTileStore>()
+            .map_or(false, |mut store| store.set(IVec2::new(x, y), tile))
+    }
+
+    /// Whether the tile at `x`, `y` blocks movement
+    #[cfg(feature = "bevy_tilemap")]
+    pub fn is_solid(&self, x: i32, y: i32) -> bool {
+        let inner = self.inner.lock();
+        let solid = match (
+            inner.1.get::<WorldTileStore>(),
+            inner.1.get::<TileRegistry>(),
+        ) {
+            (Some(store), Some(registry)) => store.is_solid(IVec2::new(x, y), &registry),
+            _ => false,
+        };
+        solid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_reflect::{Reflect, TypeRegistryArc};
+
+    #[derive(Reflect, Default)]
+    #[reflect(Component)]
+    struct Grass {
+        height: f32,
+        spreads: bool,
+    }
+
+    #[derive(Reflect, Default)]
+    #[reflect(Component)]
+    struct Dirt;
+
+    fn world() -> ScriptWorld {
+        let type_registry = TypeRegistryArc::default();
+        type_registry.write().register::<Grass>();
+        type_registry.write().register::<Dirt>();
+        ScriptWorld::new(World::default(), Resources::default(), type_registry)
+    }
+
+    #[test]
+    fn read_and_write_fields() {
+        let world = world();
+        let grass = world.spawn();
+        assert!(world.insert(grass, "Grass"));
+        let dirt = world.spawn();
+        assert!(world.insert(dirt, "Dirt"));
+
+        assert_eq!(world.entities_with("Grass"), vec![grass]);
+        assert!(world.has(grass, "Grass"));
+        assert!(!world.has(dirt, "Grass"));
+        assert!(world.set(grass, "Grass", "height", &ScriptValue::Int(2)));
+        assert_eq!(
+            world.get(grass, "Grass", "height"),
+            Some(ScriptValue::Float(2.0))
+        );
+        assert!(!world.set(grass, "Grass", "spreads", &ScriptValue::Int(1)));
+        assert_eq!(world.get(dirt, "Grass", "height"), None);
+        assert_eq!(world.get(grass, "Grass", "width"), None);
+
+        // grass spreads to the dirt
+        assert!(world.insert(dirt, "Grass"));
+        assert!(world.despawn(grass));
+        assert_eq!(world.entities_with("Grass"), vec![dirt]);
+
+        let (world, _) = world.take();
+        assert_eq!(world.get::<Grass>(dirt).unwrap().height, 0.0);
+    }
+
+    #[cfg(feature = "bevy_tilemap")]
+    #[test]
+    fn read_and_write_tiles() {
+        use bevy_tilemap::TileKind;
+
+        let world = world();
+        assert_eq!(world.tile(0, 0), None);
+        assert!(!world.set_tile(0, 0, TileId::EMPTY));
+
+        let mut registry = TileRegistry::default();
+        let mut stone = TileKind::new(TileId(1), "stone");
+        stone.solid = true;
+        registry.register(stone).unwrap();
+        {
+            let mut inner = world.inner.lock();
+            inner.1.insert(registry);
+            inner.1.insert(WorldTileStore::new(4));
+        }
+
+        let stone = world.tile_id("stone").unwrap();
+        assert_eq!(world.tile_id("lava"), None);
+        assert!(world.set_tile(-5, 2, stone));
+        assert!(!world.set_tile(-5, 2, stone));
+        assert!(!world.set_tile(0, 0, TileId(2)));
+        assert_eq!(world.tile(-5, 2), Some(stone));
+        assert_eq!(world.tile_name(-5, 2), Some("stone".to_string()));
+        assert_eq!(world.tile_name(0, 0), None);
+        assert!(world.is_solid(-5, 2));
+        assert!(!world.is_solid(0, 0));
+
+        let (_, resources) = world.take();
+        let store = resources.get::<WorldTileStore>().unwrap();
+        assert_eq!(store.get(IVec2::new(-5, 2)), stone);
+    }
+}
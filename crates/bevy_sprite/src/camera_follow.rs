@@ -0,0 +1,230 @@
+use crate::Rect;
+use bevy_core::Time;
+use bevy_ecs::{Entity, Query, QuerySet, Res};
+use bevy_math::{clamp, Vec2};
+use bevy_render::camera::OrthographicProjection;
+use bevy_transform::components::{GlobalTransform, Transform};
+
+/// Makes a 2d camera follow a target entity, such as the player, with smoothing, a deadzone and
+/// optional world bounds.
+///
+/// The camera is moved after transforms are propagated, so it follows the position the target is
+/// drawn at during the current frame. Both its [Transform] and [GlobalTransform] are written, so
+/// the camera must not have a parent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraFollow {
+    /// The entity to follow
+    pub target: Entity,
+    /// The time in seconds the camera takes to cover about two thirds of the distance to its
+    /// destination. With `0.0`, the camera moves to its destination immediately.
+    pub smoothing: f32,
+    /// The half extents of the area around the center of the camera the target can move in
+    /// without moving the camera
+    pub deadzone: Vec2,
+    /// The area of the world the camera can show. When the visible area is larger than the
+    /// bounds, the camera is centered on them.
+    pub bounds: Option<Rect>,
+}
+
+impl CameraFollow {
+    /// Follows `target` without smoothing, deadzone or bounds
+    pub fn new(target: Entity) -> Self {
+        CameraFollow {
+            target,
+            smoothing: 0.0,
+            deadzone: Vec2::zero(),
+            bounds: None,
+        }
+    }
+
+    pub fn with_smoothing(mut self, smoothing: f32) -> Self {
+        self.smoothing = smoothing;
+        self
+    }
+
+    pub fn with_deadzone(mut self, deadzone: Vec2) -> Self {
+        self.deadzone = deadzone;
+        self
+    }
+
+    pub fn with_bounds(mut self, bounds: Rect) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    /// The position of the camera after following `target` from `camera` for `delta_seconds`,
+    /// before it is clamped to the bounds
+    pub fn step(&self, camera: Vec2, target: Vec2, delta_seconds: f32) -> Vec2 {
+        let offset = target - camera;
+        let outside_deadzone = |offset: f32, deadzone: f32| {
+            if offset > deadzone {
+                offset - deadzone
+            } else if offset < -deadzone {
+                offset + deadzone
+            } else {
+                0.0
+            }
+        };
+        let offset = Vec2::new(
+            outside_deadzone(offset.x, self.deadzone.x),
+            outside_deadzone(offset.y, self.deadzone.y),
+        );
+        let factor = if self.smoothing > 0.0 {
+            1.0 - (-delta_seconds / self.smoothing).exp()
+        } else {
+            1.0
+        };
+        camera + offset * factor
+    }
+
+    /// Clamps the position of the camera so the area it shows, from `visible_min` to
+    /// `visible_max` relative to its position, stays inside the bounds
+    pub fn clamp_to_bounds(&self, camera: Vec2, visible_min: Vec2, visible_max: Vec2) -> Vec2 {
+        let bounds = match self.bounds {
+            Some(bounds) => bounds,
+            None => return camera,
+        };
+        let clamp_axis = |position: f32, min: f32, max: f32| {
+            if min > max {
+                (min + max) / 2.0
+            } else {
+                clamp(position, min, max)
+            }
+        };
+        Vec2::new(
+            clamp_axis(
+                camera.x,
+                bounds.min.x - visible_min.x,
+                bounds.max.x - visible_max.x,
+            ),
+            clamp_axis(
+                camera.y,
+                bounds.min.y - visible_min.y,
+                bounds.max.y - visible_max.y,
+            ),
+        )
+    }
+}
+
+/// Moves [CameraFollow] cameras towards their targets
+pub fn camera_follow_system(
+    time: Res<Time>,
+    cameras: Query<(Entity, &CameraFollow)>,
+    mut transforms: QuerySet<(
+        Query<&GlobalTransform>,
+        Query<(&mut Transform, &mut GlobalTransform)>,
+    )>,
+    projections: Query<&OrthographicProjection>,
+) {
+    for (entity, follow) in cameras.iter() {
+        let target = match transforms.q0().get(follow.target) {
+            Ok(target) => target.translation.truncate(),
+            Err(_) => continue,
+        };
+        let (mut transform, mut global_transform) = match transforms.q1_mut().get_mut(entity) {
+            Ok(camera) => camera,
+            Err(_) => continue,
+        };
+        let camera = transform.translation.truncate();
+        let mut position = follow.step(camera, target, time.delta_seconds());
+        if let Ok(projection) = projections.get(entity) {
+            let scale = transform.scale.truncate();
+            position = follow.clamp_to_bounds(
+                position,
+                Vec2::new(projection.left, projection.bottom) * scale,
+                Vec2::new(projection.right, projection.top) * scale,
+            );
+        }
+        // only write changes, so a camera that stays still isn't marked as changed every frame
+        if position != camera {
+            transform.translation = position.extend(transform.translation.z);
+            global_transform.translation = position.extend(global_transform.translation.z);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::{IntoSystem, Resources, Stage, SystemStage, World};
+    use bevy_math::Vec3;
+
+    #[test]
+    fn deadzone_and_smoothing() {
+        let follow = CameraFollow::new(Entity::new(0)).with_deadzone(Vec2::new(10.0, 5.0));
+        // the target is inside the deadzone horizontally and outside it vertically
+        assert_eq!(
+            follow.step(Vec2::zero(), Vec2::new(8.0, -20.0), 0.1),
+            Vec2::new(0.0, -15.0)
+        );
+
+        let follow = follow.with_smoothing(0.5);
+        let position = follow.step(Vec2::zero(), Vec2::new(0.0, -20.0), 0.5);
+        assert!((position.y - -15.0 * (1.0 - (-1.0f32).exp())).abs() < 1e-5);
+        // nothing moves when no time passes
+        assert_eq!(
+            follow.step(Vec2::zero(), Vec2::new(0.0, -20.0), 0.0),
+            Vec2::zero()
+        );
+    }
+
+    #[test]
+    fn clamp_to_world_bounds() {
+        let follow = CameraFollow::new(Entity::new(0)).with_bounds(Rect {
+            min: Vec2::new(0.0, 0.0),
+            max: Vec2::new(100.0, 40.0),
+        });
+        let visible_min = Vec2::new(-20.0, -30.0);
+        let visible_max = Vec2::new(20.0, 30.0);
+        // the visible area is narrower than the bounds but taller
+        assert_eq!(
+            follow.clamp_to_bounds(Vec2::new(-50.0, 0.0), visible_min, visible_max),
+            Vec2::new(20.0, 20.0)
+        );
+        assert_eq!(
+            follow.clamp_to_bounds(Vec2::new(150.0, 100.0), visible_min, visible_max),
+            Vec2::new(80.0, 20.0)
+        );
+    }
+
+    #[test]
+    fn follow_target() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(Time::default());
+        let player = world.spawn((GlobalTransform::from_translation(Vec3::new(
+            300.0, 40.0, 1.0,
+        )),));
+        let projection = OrthographicProjection {
+            left: -50.0,
+            right: 50.0,
+            bottom: -50.0,
+            top: 50.0,
+            ..Default::default()
+        };
+        let camera = world.spawn((
+            CameraFollow::new(player).with_bounds(Rect {
+                min: Vec2::new(-200.0, -200.0),
+                max: Vec2::new(200.0, 200.0),
+            }),
+            Transform::from_translation(Vec3::new(0.0, 0.0, 10.0)),
+            GlobalTransform::from_translation(Vec3::new(0.0, 0.0, 10.0)),
+            projection,
+        ));
+
+        let mut stage = SystemStage::serial();
+        stage.add_system(camera_follow_system.system());
+        stage.initialize(&mut world, &mut resources);
+        stage.run(&mut world, &mut resources);
+
+        let expected = Vec3::new(150.0, 40.0, 10.0);
+        assert_eq!(
+            world.get::<Transform>(camera).unwrap().translation,
+            expected
+        );
+        assert_eq!(
+            world.get::<GlobalTransform>(camera).unwrap().translation,
+            expected
+        );
+    }
+}
@@ -1,6 +1,14 @@
-use bevy_asset::{self, Handle};
+use bevy_asset::{self, Assets, Handle};
+use bevy_ecs::{Query, Res};
 use bevy_reflect::TypeUuid;
-use bevy_render::{color::Color, renderer::RenderResources, shader::ShaderDefs, texture::Texture};
+use bevy_render::{
+    color::Color,
+    draw::Visible,
+    pipeline::{BlendMode, RenderPipelines},
+    renderer::RenderResources,
+    shader::ShaderDefs,
+    texture::{SamplerDescriptor, Texture},
+};
 
 #[derive(Debug, RenderResources, ShaderDefs, TypeUuid)]
 #[uuid = "506cff92-a9f3-4543-862d-6851c7fdfc99"]
@@ -8,6 +16,14 @@ pub struct ColorMaterial {
     pub color: Color,
     #[shader_def]
     pub texture: Option<Handle<Texture>>,
+    /// Overrides the sampler of `texture`, for example to change its filtering or anisotropy
+    /// level for this material only
+    #[render_resources(sampler)]
+    pub sampler: Option<SamplerDescriptor>,
+    /// Overrides how the pipelines of entities drawn with this material blend with what was drawn
+    /// before them. Sprites alpha blend without one.
+    #[render_resources(ignore)]
+    pub blend_mode: Option<BlendMode>,
 }
 
 impl ColorMaterial {
@@ -15,6 +31,8 @@ impl ColorMaterial {
         ColorMaterial {
             color,
             texture: None,
+            sampler: None,
+            blend_mode: None,
         }
     }
 
@@ -22,6 +40,8 @@ impl ColorMaterial {
         ColorMaterial {
             color: Color::WHITE,
             texture: Some(texture),
+            sampler: None,
+            blend_mode: None,
         }
     }
 
@@ -29,8 +49,23 @@ impl ColorMaterial {
         ColorMaterial {
             color,
             texture: Some(texture),
+            sampler: None,
+            blend_mode: None,
         }
     }
+
+    /// Samples the texture of this material with `sampler` instead of the sampler of the texture
+    pub fn with_sampler(mut self, sampler: SamplerDescriptor) -> Self {
+        self.sampler = Some(sampler);
+        self
+    }
+
+    /// Draws entities with this material with `blend_mode`, like [BlendMode::Additive] for lights
+    /// and particles
+    pub fn with_blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.blend_mode = Some(blend_mode);
+        self
+    }
 }
 
 impl Default for ColorMaterial {
@@ -38,6 +73,8 @@ impl Default for ColorMaterial {
         ColorMaterial {
             color: Color::rgb(1.0, 1.0, 1.0),
             texture: None,
+            sampler: None,
+            blend_mode: None,
         }
     }
 }
@@ -53,3 +90,98 @@ impl From<Handle<Texture>> for ColorMaterial {
         ColorMaterial::texture(texture)
     }
 }
+
+/// Specializes the pipelines of entities drawn with a [ColorMaterial] for its [BlendMode], and
+/// sorts them with the transparent entities unless they are opaque, so they are drawn after what
+/// they blend with. Entities whose material loses its blend mode are sorted with the transparent
+/// entities again, like sprites are by default.
+pub fn color_material_blend_system(
+    materials: Res<Assets<ColorMaterial>>,
+    mut query: Query<(&Handle<ColorMaterial>, &mut RenderPipelines, &mut Visible)>,
+) {
+    for (material_handle, mut render_pipelines, mut visible) in query.iter_mut() {
+        let blend_mode = match materials.get(material_handle) {
+            Some(material) => material.blend_mode,
+            None => continue,
+        };
+        let changed = render_pipelines
+            .pipelines
+            .iter()
+            .any(|render_pipeline| render_pipeline.specialization.blend_mode != blend_mode);
+        if changed {
+            for render_pipeline in render_pipelines.pipelines.iter_mut() {
+                render_pipeline.specialization.blend_mode = blend_mode;
+            }
+        }
+        let is_transparent = match blend_mode {
+            Some(blend_mode) => blend_mode.is_transparent(),
+            // sprites alpha blend without a blend mode, so they go back to being transparent
+            None if changed => true,
+            None => continue,
+        };
+        if visible.is_transparent != is_transparent {
+            visible.is_transparent = is_transparent;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_app::App;
+    use bevy_asset::{AddAsset, AssetPlugin, AssetServer, FileAssetIo};
+    use bevy_ecs::{Entity, IntoSystem, Resources, Stage, SystemStage, World};
+    use bevy_reflect::ReflectPlugin;
+    use bevy_render::pipeline::RenderPipeline;
+    use bevy_tasks::TaskPool;
+
+    fn run(world: &mut World, resources: &mut Resources) {
+        let mut stage = SystemStage::serial();
+        stage.add_system(color_material_blend_system.system());
+        stage.initialize(world, resources);
+        stage.run(world, resources);
+    }
+
+    fn blend_mode(world: &World, entity: Entity) -> Option<BlendMode> {
+        let render_pipelines = world.get::<RenderPipelines>(entity).unwrap();
+        render_pipelines.pipelines[0].specialization.blend_mode
+    }
+
+    #[test]
+    fn reset_transparency_without_blend_mode() {
+        let asset_server = AssetServer::new(FileAssetIo::new(""), TaskPool::new());
+        let mut app = App::build();
+        app.add_resource(asset_server)
+            .add_plugin(ReflectPlugin)
+            .add_plugin(AssetPlugin)
+            .add_asset::<ColorMaterial>();
+        let mut resources = app.app.resources;
+        let material = resources
+            .get_mut::<Assets<ColorMaterial>>()
+            .unwrap()
+            .add(ColorMaterial::default().with_blend_mode(BlendMode::Opaque));
+
+        let mut world = World::new();
+        let entity = world.spawn((
+            material.clone(),
+            RenderPipelines::from_pipelines(vec![RenderPipeline::new(Handle::default())]),
+            Visible {
+                is_transparent: true,
+                ..Default::default()
+            },
+        ));
+        run(&mut world, &mut resources);
+        assert_eq!(blend_mode(&world, entity), Some(BlendMode::Opaque));
+        assert!(!world.get::<Visible>(entity).unwrap().is_transparent);
+
+        resources
+            .get_mut::<Assets<ColorMaterial>>()
+            .unwrap()
+            .get_mut(&material)
+            .unwrap()
+            .blend_mode = None;
+        run(&mut world, &mut resources);
+        assert_eq!(blend_mode(&world, entity), None);
+        assert!(world.get::<Visible>(entity).unwrap().is_transparent);
+    }
+}
@@ -1,12 +1,47 @@
-use crate::{Rect, TextureAtlas};
-use bevy_asset::Assets;
+use crate::{AtlasTextureLayout, Rect, TextureAtlas};
+use bevy_app::Events;
+use bevy_asset::{Assets, Handle};
 use bevy_math::Vec2;
-use bevy_render::texture::Texture;
-use guillotiere::{size2, Allocation, AtlasAllocator};
+use bevy_render::texture::{Extent3d, Texture, TextureDimension};
+use bevy_utils::HashMap;
+use guillotiere::{size2, AllocId, Allocation, AtlasAllocator};
 
+/// Packs textures into an existing [TextureAtlas] at runtime.
+///
+/// Textures are added to spare space in the atlas texture. When there is no room left, the atlas
+/// texture grows up to its max size, and textures that were removed are packed away by moving the
+/// remaining textures around. The indices of textures never change, but their rects and UVs may,
+/// so each change is recorded and can be sent as a [TextureAtlasRemapped] event with
+/// [DynamicTextureAtlasBuilder::send_changes].
 pub struct DynamicTextureAtlasBuilder {
     pub atlas_allocator: AtlasAllocator,
     pub padding: i32,
+    max_size: Vec2,
+    allocations: HashMap<u32, AllocId>,
+    free_indices: Vec<u32>,
+    fragmented: bool,
+    changes: Vec<TextureAtlasChange>,
+}
+
+/// A change to the textures of a [TextureAtlas] made by a [DynamicTextureAtlasBuilder]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextureAtlasChange {
+    /// A texture was added at `index`
+    Added { index: u32, rect: Rect },
+    /// The texture at `index` was moved to `rect`
+    Moved { index: u32, rect: Rect },
+    /// The texture at `index` was removed. The index may be reused by a texture added later.
+    Removed { index: u32 },
+    /// The atlas texture was resized, which changes the UVs of every texture
+    Resized { size: Vec2 },
+}
+
+/// Sent when the textures of a [TextureAtlas] are added, moved or removed at runtime, so anything
+/// that baked their rects or UVs can update them
+#[derive(Debug, Clone)]
+pub struct TextureAtlasRemapped {
+    pub texture_atlas: Handle<TextureAtlas>,
+    pub changes: Vec<TextureAtlasChange>,
 }
 
 impl DynamicTextureAtlasBuilder {
@@ -14,29 +49,191 @@ impl DynamicTextureAtlasBuilder {
         Self {
             atlas_allocator: AtlasAllocator::new(to_size2(size)),
             padding,
+            max_size: size,
+            allocations: Default::default(),
+            free_indices: Default::default(),
+            fragmented: false,
+            changes: Default::default(),
         }
     }
 
+    /// Allows the atlas texture to grow up to `max_size` when it runs out of room
+    pub fn with_max_size(mut self, max_size: Vec2) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Adds `texture` to the atlas, converting it to the format of the atlas texture if needed.
+    /// Returns `None` if there is no room left for the texture, or if its format can't be
+    /// converted.
     pub fn add_texture(
         &mut self,
         texture_atlas: &mut TextureAtlas,
         textures: &mut Assets<Texture>,
         texture: &Texture,
     ) -> Option<u32> {
-        let allocation = self.atlas_allocator.allocate(size2(
+        let atlas_format = textures.get(&texture_atlas.texture).unwrap().format;
+        let converted;
+        let texture = if texture.format == atlas_format {
+            texture
+        } else {
+            converted = texture.convert(atlas_format).ok()?;
+            &converted
+        };
+        let size = size2(
             texture.size.width as i32 + self.padding,
             texture.size.height as i32 + self.padding,
-        ));
-        if let Some(allocation) = allocation {
-            let atlas_texture = textures.get_mut(&texture_atlas.texture).unwrap();
-            self.place_texture(atlas_texture, allocation, texture);
-            let mut rect: Rect = allocation.rectangle.into();
-            rect.max.x -= self.padding as f32;
-            rect.max.y -= self.padding as f32;
-            texture_atlas.add_texture(rect);
-            Some((texture_atlas.len() - 1) as u32)
-        } else {
-            None
+        );
+        let mut allocation = self.atlas_allocator.allocate(size);
+        if allocation.is_none() && self.grow(texture_atlas, textures, size) {
+            allocation = self.atlas_allocator.allocate(size);
+        }
+        if allocation.is_none() && self.fragmented {
+            self.repack(texture_atlas, textures);
+            allocation = self.atlas_allocator.allocate(size);
+        }
+
+        let allocation = allocation?;
+        let atlas_texture = textures.get_mut(&texture_atlas.texture).unwrap();
+        self.place_texture(atlas_texture, allocation, texture);
+        let rect = self.texture_rect(allocation);
+        let index = match self.free_indices.pop() {
+            Some(index) => {
+                texture_atlas.textures[index as usize] = rect;
+                texture_atlas.layouts[index as usize] = AtlasTextureLayout::default();
+                index
+            }
+            None => {
+                texture_atlas.add_texture(rect);
+                (texture_atlas.len() - 1) as u32
+            }
+        };
+        self.allocations.insert(index, allocation.id);
+        self.changes.push(TextureAtlasChange::Added { index, rect });
+        Some(index)
+    }
+
+    /// Removes the texture at `index`, which must have been added by this builder, freeing its
+    /// space in the atlas. Its rect becomes empty until the index is reused.
+    pub fn remove_texture(&mut self, texture_atlas: &mut TextureAtlas, index: u32) -> bool {
+        let id = match self.allocations.remove(&index) {
+            Some(id) => id,
+            None => return false,
+        };
+        self.atlas_allocator.deallocate(id);
+        texture_atlas.textures[index as usize] = Rect::default();
+        if let Some(texture_handles) = texture_atlas.texture_handles.as_mut() {
+            texture_handles.retain(|_, texture_index| *texture_index != index as usize);
+        }
+        self.free_indices.push(index);
+        self.fragmented = true;
+        self.changes.push(TextureAtlasChange::Removed { index });
+        true
+    }
+
+    /// Takes the changes made to the atlas since the last call
+    pub fn take_changes(&mut self) -> Vec<TextureAtlasChange> {
+        std::mem::take(&mut self.changes)
+    }
+
+    /// Sends the changes made to the atlas since the last call as a [TextureAtlasRemapped] event
+    pub fn send_changes(
+        &mut self,
+        texture_atlas: &Handle<TextureAtlas>,
+        events: &mut Events<TextureAtlasRemapped>,
+    ) {
+        if !self.changes.is_empty() {
+            events.send(TextureAtlasRemapped {
+                texture_atlas: texture_atlas.clone_weak(),
+                changes: self.take_changes(),
+            });
+        }
+    }
+
+    fn texture_rect(&self, allocation: Allocation) -> Rect {
+        let mut rect = to_rect(allocation.rectangle);
+        rect.max.x -= self.padding as f32;
+        rect.max.y -= self.padding as f32;
+        rect
+    }
+
+    /// Doubles the size of the atlas texture, without going over the max size, until an
+    /// allocation of `size` could fit. Existing textures keep their rects.
+    fn grow(
+        &mut self,
+        texture_atlas: &mut TextureAtlas,
+        textures: &mut Assets<Texture>,
+        size: guillotiere::Size,
+    ) -> bool {
+        let current = self.atlas_allocator.size();
+        let max = to_size2(self.max_size);
+        let mut new_size = current;
+        while new_size.width < size.width || new_size.height < size.height || new_size == current {
+            let grown = size2(
+                (new_size.width * 2).min(max.width),
+                (new_size.height * 2).min(max.height),
+            );
+            if grown == new_size {
+                break;
+            }
+            new_size = grown;
+        }
+        if new_size == current {
+            return false;
+        }
+
+        self.atlas_allocator.grow(new_size);
+        let atlas_texture = textures.get_mut(&texture_atlas.texture).unwrap();
+        let mut grown_texture = Texture::new_fill(
+            Extent3d::new(new_size.width as u32, new_size.height as u32, 1),
+            TextureDimension::D2,
+            &vec![0; atlas_texture.format.pixel_size()],
+            atlas_texture.format,
+        );
+        grown_texture.sampler = atlas_texture.sampler;
+        grown_texture.blit_from(
+            atlas_texture,
+            [0, 0],
+            [atlas_texture.size.width, atlas_texture.size.height],
+            [0, 0],
+        );
+        *atlas_texture = grown_texture;
+        texture_atlas.size = Vec2::new(new_size.width as f32, new_size.height as f32);
+        self.changes.push(TextureAtlasChange::Resized {
+            size: texture_atlas.size,
+        });
+        true
+    }
+
+    /// Packs the textures of the atlas again to reclaim the space fragmented by removed textures
+    fn repack(&mut self, texture_atlas: &mut TextureAtlas, textures: &mut Assets<Texture>) {
+        self.fragmented = false;
+        let indices = self
+            .allocations
+            .iter()
+            .map(|(index, id)| (*id, *index))
+            .collect::<HashMap<_, _>>();
+        let change_list = self.atlas_allocator.rearrange();
+        // packing the same allocations into the same size only fails if the padding can't fit
+        debug_assert!(change_list.failures.is_empty());
+
+        let atlas_texture = textures.get_mut(&texture_atlas.texture).unwrap();
+        let old_texture = atlas_texture.clone();
+        for change in change_list.changes {
+            let index = indices[&change.old.id];
+            let old_rect = self.texture_rect(change.old);
+            let rect = self.texture_rect(change.new);
+            atlas_texture.blit_from(
+                &old_texture,
+                [old_rect.min.x as u32, old_rect.min.y as u32],
+                [old_rect.max.x as u32, old_rect.max.y as u32],
+                [rect.min.x as u32, rect.min.y as u32],
+            );
+            self.allocations.insert(index, change.new.id);
+            texture_atlas.textures[index as usize] = rect;
+            if rect.min != old_rect.min {
+                self.changes.push(TextureAtlasChange::Moved { index, rect });
+            }
         }
     }
 
@@ -72,30 +269,128 @@ impl DynamicTextureAtlasBuilder {
         let mut rect = allocation.rectangle;
         rect.max.x -= self.padding;
         rect.max.y -= self.padding;
-        let atlas_width = atlas_texture.size.width as usize;
-        let rect_width = rect.width() as usize;
-        let format_size = atlas_texture.format.pixel_size();
-
-        for (texture_y, bound_y) in (rect.min.y..rect.max.y).map(|i| i as usize).enumerate() {
-            let begin = (bound_y * atlas_width + rect.min.x as usize) * format_size;
-            let end = begin + rect_width * format_size;
-            let texture_begin = texture_y * rect_width * format_size;
-            let texture_end = texture_begin + rect_width * format_size;
-            atlas_texture.data[begin..end]
-                .copy_from_slice(&texture.data[texture_begin..texture_end]);
-        }
+        atlas_texture.blit_from(
+            texture,
+            [0, 0],
+            [rect.width() as u32, rect.height() as u32],
+            [rect.min.x as u32, rect.min.y as u32],
+        );
     }
 }
 
-impl From<guillotiere::Rectangle> for Rect {
-    fn from(rectangle: guillotiere::Rectangle) -> Self {
-        Rect {
-            min: Vec2::new(rectangle.min.x as f32, rectangle.min.y as f32),
-            max: Vec2::new(rectangle.max.x as f32, rectangle.max.y as f32),
-        }
+fn to_rect(rectangle: guillotiere::Rectangle) -> Rect {
+    Rect {
+        min: Vec2::new(rectangle.min.x as f32, rectangle.min.y as f32),
+        max: Vec2::new(rectangle.max.x as f32, rectangle.max.y as f32),
     }
 }
 
 fn to_size2(vec2: Vec2) -> guillotiere::Size {
     guillotiere::Size::new(vec2.x as i32, vec2.y as i32)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_app::App;
+    use bevy_asset::{AddAsset, AssetPlugin, AssetServer, FileAssetIo};
+    use bevy_reflect::ReflectPlugin;
+    use bevy_render::texture::TextureFormat;
+    use bevy_tasks::TaskPool;
+
+    fn texture(width: u32, height: u32, value: u8) -> Texture {
+        Texture::new_fill(
+            Extent3d::new(width, height, 1),
+            TextureDimension::D2,
+            &[value; 4],
+            TextureFormat::Rgba8UnormSrgb,
+        )
+    }
+
+    fn assert_textures(texture_atlas: &TextureAtlas, textures: &Assets<Texture>, values: &[u8]) {
+        let atlas_texture = textures.get(&texture_atlas.texture).unwrap();
+        for (index, value) in values.iter().enumerate() {
+            let rect = texture_atlas.textures[index];
+            if *value == 0 {
+                assert_eq!(rect, Rect::default());
+                continue;
+            }
+            for y in rect.min.y as u32..rect.max.y as u32 {
+                for x in rect.min.x as u32..rect.max.x as u32 {
+                    assert_eq!(atlas_texture.get_pixel(x, y), Some(&[*value; 4][..]));
+                }
+            }
+        }
+    }
+
+    fn setup(size: f32) -> (App, TextureAtlas) {
+        let asset_server = AssetServer::new(FileAssetIo::new(""), TaskPool::new());
+        let mut app = App::build();
+        app.add_resource(asset_server)
+            .add_plugin(ReflectPlugin)
+            .add_plugin(AssetPlugin)
+            .add_asset::<Texture>();
+        let atlas_texture = app
+            .resources_mut()
+            .get_mut::<Assets<Texture>>()
+            .unwrap()
+            .add(texture(size as u32, size as u32, 0));
+        let texture_atlas = TextureAtlas::new_empty(atlas_texture, Vec2::new(size, size));
+        (app.app, texture_atlas)
+    }
+
+    #[test]
+    fn grow_atlas() {
+        let (app, mut texture_atlas) = setup(8.0);
+        let mut textures = app.resources.get_mut::<Assets<Texture>>().unwrap();
+        let mut builder = DynamicTextureAtlasBuilder::new(Vec2::new(8.0, 8.0), 0)
+            .with_max_size(Vec2::new(16.0, 16.0));
+
+        for value in 1..=4 {
+            let index =
+                builder.add_texture(&mut texture_atlas, &mut textures, &texture(8, 8, value));
+            assert_eq!(index, Some(value as u32 - 1));
+        }
+        // the atlas grew once, to its max size
+        assert_eq!(texture_atlas.size, Vec2::new(16.0, 16.0));
+        assert!(builder
+            .add_texture(&mut texture_atlas, &mut textures, &texture(8, 8, 5))
+            .is_none());
+        assert_textures(&texture_atlas, &textures, &[1, 2, 3, 4]);
+        assert_eq!(
+            builder.take_changes()[1],
+            TextureAtlasChange::Resized {
+                size: Vec2::new(16.0, 16.0)
+            }
+        );
+    }
+
+    #[test]
+    fn remove_and_repack() {
+        let (app, mut texture_atlas) = setup(16.0);
+        let mut textures = app.resources.get_mut::<Assets<Texture>>().unwrap();
+        let mut builder = DynamicTextureAtlasBuilder::new(Vec2::new(16.0, 16.0), 0);
+        builder.add_texture(&mut texture_atlas, &mut textures, &texture(4, 4, 1));
+        builder.add_texture(&mut texture_atlas, &mut textures, &texture(4, 4, 2));
+        builder.add_texture(&mut texture_atlas, &mut textures, &texture(4, 8, 3));
+
+        assert!(builder.remove_texture(&mut texture_atlas, 0));
+        assert!(!builder.remove_texture(&mut texture_atlas, 0));
+        assert_textures(&texture_atlas, &textures, &[0, 2, 3]);
+
+        // the space left by the removed texture is packed away to make room, and the removed
+        // index is reused
+        let index = builder.add_texture(&mut texture_atlas, &mut textures, &texture(12, 16, 4));
+        assert_eq!(index, Some(0));
+        assert_textures(&texture_atlas, &textures, &[4, 2, 3]);
+        let changes = builder.take_changes();
+        assert_eq!(changes[3], TextureAtlasChange::Removed { index: 0 });
+        assert!(changes[4..]
+            .iter()
+            .any(|change| matches!(change, TextureAtlasChange::Moved { .. })));
+        assert!(matches!(
+            changes.last(),
+            Some(TextureAtlasChange::Added { index: 0, .. })
+        ));
+    }
+}
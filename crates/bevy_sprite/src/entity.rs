@@ -4,6 +4,7 @@ use crate::{
 };
 use bevy_asset::Handle;
 use bevy_ecs::Bundle;
+use bevy_math::Vec2;
 use bevy_render::{
     mesh::Mesh,
     pipeline::{RenderPipeline, RenderPipelines},
@@ -46,6 +47,19 @@ impl Default for SpriteBundle {
     }
 }
 
+impl SpriteBundle {
+    /// A sprite drawn with `mesh` in world units, like one of the 2d shapes of
+    /// [shape](bevy_render::mesh::shape), instead of with a quad scaled to the size of the sprite
+    pub fn from_mesh(mesh: Handle<Mesh>, material: Handle<ColorMaterial>) -> Self {
+        SpriteBundle {
+            sprite: Sprite::new(Vec2::one()),
+            mesh,
+            material,
+            ..Default::default()
+        }
+    }
+}
+
 /// A Bundle of components for drawing a single sprite from a sprite sheet (also referred
 /// to as a `TextureAtlas`)
 #[derive(Bundle)]
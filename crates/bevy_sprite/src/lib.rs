@@ -1,27 +1,45 @@
 pub mod collide_aabb;
 pub mod entity;
+pub mod raycast;
 
+mod camera_follow;
 mod color_material;
 mod dynamic_texture_atlas_builder;
-mod rect;
+mod paged_texture_atlas;
+mod picking;
+mod pixel_snap;
 mod render;
 mod sprite;
 mod texture_atlas;
 mod texture_atlas_builder;
+mod tint;
+mod weather_overlay;
+mod y_sort;
 
-use bevy_ecs::IntoSystem;
+use bevy_ecs::{IntoSystem, SystemStage};
+pub use camera_follow::*;
 pub use color_material::*;
 pub use dynamic_texture_atlas_builder::*;
-pub use rect::*;
+pub use paged_texture_atlas::*;
+pub use picking::*;
+pub use pixel_snap::*;
 pub use render::*;
 pub use sprite::*;
 pub use texture_atlas::*;
 pub use texture_atlas_builder::*;
+pub use tint::*;
+pub use weather_overlay::*;
+pub use y_sort::*;
+
+/// The rect type sprites and texture atlases use, which is [bevy_math::Bounds]
+pub use bevy_math::Bounds as Rect;
 
 pub mod prelude {
     pub use crate::{
         entity::{SpriteBundle, SpriteSheetBundle},
-        ColorMaterial, Sprite, SpriteResizeMode, TextureAtlas, TextureAtlasSprite,
+        CameraFollow, ColorMaterial, DayNightCycle, DayNightCyclePlugin, GlobalTint, Picking,
+        PixelSnap, Sprite, SpritePickingPlugin, SpriteResizeMode, TextureAtlas, TextureAtlasSprite,
+        WeatherKind, WeatherOverlay, WeatherOverlayBundle, YSort,
     };
 }
 
@@ -32,13 +50,20 @@ use bevy_reflect::{RegisterTypeBuilder, TypeUuid};
 use bevy_render::{
     mesh::{shape, Mesh},
     render_graph::RenderGraph,
-    shader::asset_shader_defs_system,
+    shader::{asset_shader_defs_system, shader_defs_system},
 };
 use sprite::sprite_system;
 
 #[derive(Default)]
 pub struct SpritePlugin;
 
+pub mod stage {
+    /// Name of the app stage where cameras follow their targets, and the rendered positions of
+    /// entities are snapped to the pixel grid and sorted by y. Runs after POST_UPDATE, once
+    /// transforms are propagated.
+    pub const SPRITE_TRANSFORM: &str = "sprite_transform";
+}
+
 pub const QUAD_HANDLE: HandleUntyped =
     HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 14240461981130137526);
 
@@ -46,11 +71,47 @@ impl Plugin for SpritePlugin {
     fn build(&self, app: &mut AppBuilder) {
         app.add_asset::<ColorMaterial>()
             .add_asset::<TextureAtlas>()
+            .add_event::<TextureAtlasRemapped>()
+            .init_resource::<PagedTextureAtlases>()
+            .init_resource::<GlobalTint>()
             .register_type::<Sprite>()
-            .add_system_to_stage(stage::POST_UPDATE, sprite_system.system())
+            .register_type::<TextureAtlasSprite>()
+            .register_type::<PixelSnap>()
+            .register_type::<YSort>()
+            .add_stage_after(
+                bevy_app::stage::POST_UPDATE,
+                stage::SPRITE_TRANSFORM,
+                SystemStage::parallel(),
+            )
+            .add_system_to_stage(bevy_app::stage::POST_UPDATE, sprite_system.system())
+            .add_system_to_stage(
+                bevy_app::stage::POST_UPDATE,
+                sprite_texture_residency_system.system(),
+            )
             .add_system_to_stage(
-                stage::POST_UPDATE,
+                bevy_app::stage::POST_UPDATE,
+                paged_texture_atlas_system.system(),
+            )
+            // cameras are snapped to the pixel grid after following their target, and entities
+            // are sorted by the y they are snapped to
+            .add_system_to_stage(stage::SPRITE_TRANSFORM, camera_follow_system.system())
+            .add_system_to_stage(stage::SPRITE_TRANSFORM, pixel_snap_system.system())
+            .add_system_to_stage(stage::SPRITE_TRANSFORM, y_sort_system.system())
+            .add_system_to_stage(
+                bevy_app::stage::POST_UPDATE,
+                weather_overlay_system.system(),
+            )
+            .add_system_to_stage(
+                bevy_app::stage::POST_UPDATE,
+                shader_defs_system::<WeatherOverlay>.system(),
+            )
+            .add_system_to_stage(
+                bevy_app::stage::POST_UPDATE,
                 asset_shader_defs_system::<ColorMaterial>.system(),
+            )
+            .add_system_to_stage(
+                bevy_app::stage::POST_UPDATE,
+                color_material_blend_system.system(),
             );
 
         let resources = app.resources_mut();
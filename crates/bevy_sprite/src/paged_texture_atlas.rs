@@ -0,0 +1,448 @@
+use crate::{AtlasTextureLayout, Rect, TextureAtlas, TextureAtlasSprite};
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::{Query, ResMut};
+use bevy_math::Vec2;
+use bevy_render::{
+    draw::Visible,
+    texture::{Extent3d, Texture, TextureDimension},
+};
+use bevy_utils::{HashMap, HashSet};
+
+/// A [TextureAtlas] for tile sets that are too large to keep on the GPU.
+///
+/// The tile set is a grid of equally sized tiles in a source texture that is only kept on the CPU.
+/// The tiles are grouped into square pages of `page_tiles` x `page_tiles` tiles, and a fixed
+/// number of pages are copied into a smaller physical texture when their tiles are requested.
+/// When all page slots are in use, the page that was requested the longest time ago is replaced.
+///
+/// The rects of the atlas are remapped whenever pages move, so a [TextureAtlasSprite] keeps using
+/// the index of its tile in the source grid. Tiles that are not paged in have an empty rect, so
+/// they are not drawn until their page is loaded.
+#[derive(Debug)]
+pub struct PagedTextureAtlas {
+    source: Handle<Texture>,
+    tile_size: Vec2,
+    columns: usize,
+    rows: usize,
+    page_tiles: usize,
+    slot_columns: usize,
+    slot_rows: usize,
+    slots: Vec<Option<PageSlot>>,
+    resident_pages: HashMap<usize, usize>,
+    requested_pages: HashSet<usize>,
+    rects: Vec<Rect>,
+    frame: u64,
+    missing_pages: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PageSlot {
+    page: usize,
+    last_used: u64,
+}
+
+/// The pixels of a page that was paged in, to be copied into the physical texture
+#[derive(Debug)]
+pub struct PageUpload {
+    pub slot: usize,
+    pub texture: Texture,
+}
+
+impl PagedTextureAtlas {
+    /// Creates a paged atlas for the tile set in `source`, which is split into a grid of
+    /// `columns` x `rows` tiles of `tile_size`. The physical texture has room for
+    /// `slot_columns` x `slot_rows` pages.
+    pub fn new(
+        source: Handle<Texture>,
+        tile_size: Vec2,
+        columns: usize,
+        rows: usize,
+        page_tiles: usize,
+        slot_columns: usize,
+        slot_rows: usize,
+    ) -> Self {
+        assert!(page_tiles > 0, "pages must contain at least one tile");
+        PagedTextureAtlas {
+            source,
+            tile_size,
+            columns,
+            rows,
+            page_tiles,
+            slot_columns,
+            slot_rows,
+            slots: vec![None; slot_columns * slot_rows],
+            resident_pages: Default::default(),
+            requested_pages: Default::default(),
+            rects: vec![Rect::default(); columns * rows],
+            frame: 0,
+            missing_pages: 0,
+        }
+    }
+
+    /// The size of the physical texture, in pixels
+    pub fn physical_size(&self) -> Vec2 {
+        Vec2::new(
+            (self.slot_columns * self.page_tiles) as f32 * self.tile_size.x,
+            (self.slot_rows * self.page_tiles) as f32 * self.tile_size.y,
+        )
+    }
+
+    /// The current rects of the tiles in the physical texture
+    pub fn rects(&self) -> &[Rect] {
+        &self.rects
+    }
+
+    /// The number of pages that were requested during the last update but did not fit in the
+    /// physical texture
+    pub fn missing_pages(&self) -> usize {
+        self.missing_pages
+    }
+
+    fn page_columns(&self) -> usize {
+        (0..self.columns).step_by(self.page_tiles).len()
+    }
+
+    fn page(&self, tile: usize) -> usize {
+        let page_columns = self.page_columns();
+        let x = tile % self.columns / self.page_tiles;
+        let y = tile / self.columns / self.page_tiles;
+        y * page_columns + x
+    }
+
+    fn page_tiles(&self, page: usize) -> impl Iterator<Item = usize> {
+        let page_columns = self.page_columns();
+        let min_x = page % page_columns * self.page_tiles;
+        let min_y = page / page_columns * self.page_tiles;
+        let max_x = (min_x + self.page_tiles).min(self.columns);
+        let max_y = (min_y + self.page_tiles).min(self.rows);
+        let columns = self.columns;
+        (min_y..max_y).flat_map(move |y| (min_x..max_x).map(move |x| y * columns + x))
+    }
+
+    /// Requests the page containing `tile` for the next update
+    pub fn request(&mut self, tile: usize) {
+        if tile < self.rects.len() {
+            let page = self.page(tile);
+            self.requested_pages.insert(page);
+        }
+    }
+
+    pub fn is_resident(&self, tile: usize) -> bool {
+        tile < self.rects.len() && self.resident_pages.contains_key(&self.page(tile))
+    }
+
+    /// Pages in the requested pages that are not resident yet, copying their pixels out of
+    /// `source` and remapping the rects of their tiles. Returns the pixels to write to the physical
+    /// texture.
+    pub fn page_in(&mut self, source: &Texture) -> Vec<PageUpload> {
+        let frame = self.frame;
+        self.frame += 1;
+
+        let mut pages = std::mem::take(&mut self.requested_pages)
+            .into_iter()
+            .collect::<Vec<_>>();
+        pages.sort_unstable();
+        let mut missing = Vec::new();
+        for page in pages {
+            match self.resident_pages.get(&page) {
+                Some(slot) => self.slots[*slot].as_mut().unwrap().last_used = frame,
+                None => missing.push(page),
+            }
+        }
+
+        self.missing_pages = 0;
+        let mut uploads = Vec::new();
+        for page in missing {
+            let slot = match self.free_slot(frame) {
+                Some(slot) => slot,
+                None => {
+                    self.missing_pages += 1;
+                    continue;
+                }
+            };
+            if let Some(evicted) = self.slots[slot].take() {
+                self.resident_pages.remove(&evicted.page);
+                for tile in self.page_tiles(evicted.page).collect::<Vec<_>>() {
+                    self.rects[tile] = Rect::default();
+                }
+            }
+
+            self.slots[slot] = Some(PageSlot {
+                page,
+                last_used: frame,
+            });
+            self.resident_pages.insert(page, slot);
+            uploads.push(PageUpload {
+                slot,
+                texture: self.copy_page(source, page),
+            });
+
+            let slot_min = self.slot_min(slot);
+            for tile in self.page_tiles(page).collect::<Vec<_>>() {
+                let x = (tile % self.columns % self.page_tiles) as f32;
+                let y = (tile / self.columns % self.page_tiles) as f32;
+                let min = slot_min + Vec2::new(x * self.tile_size.x, y * self.tile_size.y);
+                self.rects[tile] = Rect {
+                    min,
+                    max: min + self.tile_size,
+                };
+            }
+        }
+
+        uploads
+    }
+
+    /// An empty slot, or else the slot of the least recently requested page that was not
+    /// requested during `frame`
+    fn free_slot(&self, frame: u64) -> Option<usize> {
+        if let Some(slot) = self.slots.iter().position(|slot| slot.is_none()) {
+            return Some(slot);
+        }
+
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| slot.map(|slot| (index, slot.last_used)))
+            .filter(|(_, last_used)| *last_used != frame)
+            .min_by_key(|(_, last_used)| *last_used)
+            .map(|(index, _)| index)
+    }
+
+    fn slot_min(&self, slot: usize) -> Vec2 {
+        let page_size = self.tile_size * self.page_tiles as f32;
+        Vec2::new(
+            (slot % self.slot_columns) as f32 * page_size.x,
+            (slot / self.slot_columns) as f32 * page_size.y,
+        )
+    }
+
+    /// The pixels of `page` in the source texture, padded to the full size of a page
+    fn copy_page(&self, source: &Texture, page: usize) -> Texture {
+        let page_width = self.page_tiles as u32 * self.tile_size.x as u32;
+        let page_height = self.page_tiles as u32 * self.tile_size.y as u32;
+        let page_columns = self.page_columns() as u32;
+        let min = [
+            page as u32 % page_columns * page_width,
+            page as u32 / page_columns * page_height,
+        ];
+        let mut texture = Texture::new_fill(
+            Extent3d::new(page_width, page_height, 1),
+            TextureDimension::D2,
+            &vec![0; source.format.pixel_size()],
+            source.format,
+        );
+        texture.blit_from(
+            source,
+            min,
+            [min[0] + page_width, min[1] + page_height],
+            [0, 0],
+        );
+        texture
+    }
+
+    /// Writes pages copied by [PagedTextureAtlas::page_in] into the physical texture
+    pub fn write_uploads(&self, physical: &mut Texture, uploads: &[PageUpload]) {
+        for upload in uploads {
+            let slot_min = self.slot_min(upload.slot);
+            physical.blit_from(
+                &upload.texture,
+                [0, 0],
+                [upload.texture.size.width, upload.texture.size.height],
+                [slot_min.x as u32, slot_min.y as u32],
+            );
+        }
+    }
+}
+
+/// The [PagedTextureAtlas] of every paged [TextureAtlas]
+#[derive(Debug, Default)]
+pub struct PagedTextureAtlases {
+    atlases: HashMap<Handle<TextureAtlas>, PagedTextureAtlas>,
+}
+
+impl PagedTextureAtlases {
+    /// Creates the [TextureAtlas] and physical texture of `paged_atlas`. The source texture of
+    /// the paged atlas must already be loaded.
+    pub fn add(
+        &mut self,
+        paged_atlas: PagedTextureAtlas,
+        textures: &mut Assets<Texture>,
+        texture_atlases: &mut Assets<TextureAtlas>,
+    ) -> Handle<TextureAtlas> {
+        let source = textures
+            .get(&paged_atlas.source)
+            .expect("the source texture of a paged atlas must be loaded");
+        let size = paged_atlas.physical_size();
+        let mut physical = Texture::new_fill(
+            Extent3d::new(size.x as u32, size.y as u32, 1),
+            TextureDimension::D2,
+            &vec![0; source.format.pixel_size()],
+            source.format,
+        );
+        physical.sampler = source.sampler;
+
+        let texture_atlas = TextureAtlas {
+            texture: textures.add(physical),
+            size,
+            textures: paged_atlas.rects.clone(),
+            layouts: vec![AtlasTextureLayout::default(); paged_atlas.rects.len()],
+            texture_handles: None,
+        };
+        let handle = texture_atlases.add(texture_atlas);
+        self.atlases.insert(handle.clone_weak(), paged_atlas);
+        handle
+    }
+
+    pub fn get(&self, texture_atlas: &Handle<TextureAtlas>) -> Option<&PagedTextureAtlas> {
+        self.atlases.get(texture_atlas)
+    }
+
+    pub fn get_mut(
+        &mut self,
+        texture_atlas: &Handle<TextureAtlas>,
+    ) -> Option<&mut PagedTextureAtlas> {
+        self.atlases.get_mut(texture_atlas)
+    }
+
+    pub fn remove(&mut self, texture_atlas: &Handle<TextureAtlas>) -> Option<PagedTextureAtlas> {
+        self.atlases.remove(texture_atlas)
+    }
+}
+
+/// Requests the tiles of visible sprite sheets that use a paged atlas, and pages them in
+pub fn paged_texture_atlas_system(
+    mut paged_atlases: ResMut<PagedTextureAtlases>,
+    mut textures: ResMut<Assets<Texture>>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    sprites: Query<(&Visible, &Handle<TextureAtlas>, &TextureAtlasSprite)>,
+) {
+    if paged_atlases.atlases.is_empty() {
+        return;
+    }
+
+    for (visible, handle, sprite) in sprites.iter() {
+        if !visible.is_visible {
+            continue;
+        }
+        if let Some(paged_atlas) = paged_atlases.atlases.get_mut(handle) {
+            paged_atlas.request(sprite.index as usize);
+        }
+    }
+
+    paged_atlases
+        .atlases
+        .retain(|handle, _| texture_atlases.get(handle).is_some());
+    for (handle, paged_atlas) in paged_atlases.atlases.iter_mut() {
+        let uploads = match textures.get(&paged_atlas.source) {
+            Some(source) => paged_atlas.page_in(source),
+            None => continue,
+        };
+        if uploads.is_empty() {
+            continue;
+        }
+
+        let texture_atlas = texture_atlases.get_mut(handle).unwrap();
+        texture_atlas.textures.clone_from(&paged_atlas.rects);
+        if let Some(physical) = textures.get_mut(&texture_atlas.texture) {
+            paged_atlas.write_uploads(physical, &uploads);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_asset::HandleId;
+    use bevy_render::texture::TextureFormat;
+
+    /// A 4x4 grid of 2x2 pixel tiles where every pixel holds the index of its tile
+    fn source() -> Texture {
+        let mut data = Vec::new();
+        for y in 0..8 {
+            for x in 0..8 {
+                data.push((y / 2 * 4 + x / 2) as u8);
+            }
+        }
+        Texture::new(
+            Extent3d::new(8, 8, 1),
+            TextureDimension::D2,
+            data,
+            TextureFormat::R8Unorm,
+        )
+    }
+
+    fn paged_atlas() -> PagedTextureAtlas {
+        // pages of 2x2 tiles, and room for two pages side by side
+        PagedTextureAtlas::new(
+            Handle::weak(HandleId::random::<Texture>()),
+            Vec2::new(2.0, 2.0),
+            4,
+            4,
+            2,
+            2,
+            1,
+        )
+    }
+
+    #[test]
+    fn page_in_requested_tiles() {
+        let source = source();
+        let mut paged_atlas = paged_atlas();
+        let mut physical = Texture::new_fill(
+            Extent3d::new(8, 4, 1),
+            TextureDimension::D2,
+            &[255],
+            TextureFormat::R8Unorm,
+        );
+
+        // tile 10 is in the bottom right page, which holds tiles 10, 11, 14 and 15
+        paged_atlas.request(10);
+        let uploads = paged_atlas.page_in(&source);
+        assert_eq!(uploads.len(), 1);
+        paged_atlas.write_uploads(&mut physical, &uploads);
+        assert!(paged_atlas.is_resident(15));
+        assert!(!paged_atlas.is_resident(0));
+
+        let rect = paged_atlas.rects()[15];
+        assert_eq!(rect.min, Vec2::new(2.0, 2.0));
+        assert_eq!(rect.max, Vec2::new(4.0, 4.0));
+        assert_eq!(paged_atlas.rects()[0].width(), 0.0);
+        let pixel = |x: usize, y: usize| physical.data[y * 8 + x];
+        assert_eq!(pixel(0, 0), 10);
+        assert_eq!(pixel(3, 3), 15);
+        assert_eq!(pixel(4, 0), 255);
+
+        // resident pages are not uploaded again
+        paged_atlas.request(11);
+        assert!(paged_atlas.page_in(&source).is_empty());
+    }
+
+    #[test]
+    fn replace_least_recently_requested_page() {
+        let source = source();
+        let mut paged_atlas = paged_atlas();
+        paged_atlas.request(0);
+        paged_atlas.request(2);
+        assert_eq!(paged_atlas.page_in(&source).len(), 2);
+        paged_atlas.request(2);
+        paged_atlas.page_in(&source);
+
+        // both slots are in use, so the page of tile 0 is replaced
+        paged_atlas.request(2);
+        paged_atlas.request(8);
+        let uploads = paged_atlas.page_in(&source);
+        assert_eq!(uploads.len(), 1);
+        assert_eq!(uploads[0].slot, 0);
+        assert!(!paged_atlas.is_resident(0));
+        assert!(paged_atlas.is_resident(8));
+        assert_eq!(paged_atlas.rects()[0].width(), 0.0);
+        assert_eq!(paged_atlas.rects()[8].min, Vec2::new(0.0, 0.0));
+
+        // pages requested during the same update are never replaced
+        paged_atlas.request(0);
+        paged_atlas.request(2);
+        paged_atlas.request(8);
+        assert_eq!(paged_atlas.page_in(&source).len(), 0);
+        assert_eq!(paged_atlas.missing_pages(), 1);
+    }
+}
@@ -0,0 +1,67 @@
+use crate::render::SpriteRenderGraphBuilder;
+use bevy_app::prelude::*;
+use bevy_ecs::Entity;
+use bevy_math::Vec2;
+use bevy_render::render_graph::RenderGraph;
+
+/// The sprite under the cursor, found by the picking pass of the [SpritePickingPlugin]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PickHit {
+    pub entity: Entity,
+    /// The cursor position the entity was picked at. Reading the pick back from the GPU takes a
+    /// few frames, so this can lag behind the current cursor position.
+    pub cursor_position: Vec2,
+    /// The world position under the cursor, for example to find the tile of a chunk that was
+    /// picked
+    pub world_position: Vec2,
+}
+
+/// Which sprite is under the cursor of the 2d camera's window.
+///
+/// Sprites are picked by their drawn pixels, so rotated and scaled sprites are picked precisely,
+/// and clicks go through their transparent parts.
+#[derive(Debug, Clone)]
+pub struct Picking {
+    /// Whether the picking pass is drawn. It draws the visible sprites again, so it can be turned
+    /// off while no tool needs to pick.
+    pub enabled: bool,
+    pub(crate) hovered: Option<PickHit>,
+}
+
+impl Default for Picking {
+    fn default() -> Self {
+        Picking {
+            enabled: true,
+            hovered: None,
+        }
+    }
+}
+
+impl Picking {
+    /// The sprite that was under the cursor when it was last picked, or `None` if there was no
+    /// sprite or the cursor was outside of the window
+    pub fn hovered(&self) -> Option<&PickHit> {
+        self.hovered.as_ref()
+    }
+
+    pub fn hovered_entity(&self) -> Option<Entity> {
+        self.hovered.map(|hit| hit.entity)
+    }
+}
+
+/// Adds a picking pass to the render graph, that draws the ids of the sprites the 2d camera sees
+/// to an offscreen target, and reads back the one under the cursor into the [Picking] resource.
+/// Chunks are drawn as sprites, so they are picked too. Add it after the
+/// [SpritePlugin](crate::SpritePlugin).
+#[derive(Debug, Default)]
+pub struct SpritePickingPlugin;
+
+impl Plugin for SpritePickingPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<Picking>();
+
+        let resources = app.resources_mut();
+        let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
+        render_graph.add_sprite_picking_graph(resources);
+    }
+}
@@ -0,0 +1,101 @@
+use bevy_ecs::{Changed, Query};
+use bevy_math::Vec3;
+use bevy_reflect::{Reflect, ReflectComponent};
+use bevy_transform::components::GlobalTransform;
+
+/// Snaps the rendered position of an entity, such as a sprite, a tile chunk or a 2d camera, to a
+/// pixel grid, which removes the seams and shimmering that appear when tiles are drawn at
+/// fractional pixel positions.
+///
+/// Only the [GlobalTransform] the entity is drawn with is snapped, after transforms are
+/// propagated. Its [Transform](bevy_transform::components::Transform) keeps its exact position,
+/// so movement stays smooth, and children are positioned relative to the exact position of their
+/// parent.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct PixelSnap {
+    /// The number of grid cells in a world unit. With the default orthographic projection, one
+    /// world unit is one pixel. With a pixel perfect scaling mode, one world unit is one pixel of
+    /// the pixel art.
+    pub pixels_per_unit: f32,
+}
+
+impl Default for PixelSnap {
+    fn default() -> Self {
+        PixelSnap {
+            pixels_per_unit: 1.0,
+        }
+    }
+}
+
+impl PixelSnap {
+    /// `translation` with its x and y rounded to the pixel grid
+    pub fn snap(&self, translation: Vec3) -> Vec3 {
+        let pixels_per_unit = self.pixels_per_unit;
+        Vec3::new(
+            (translation.x * pixels_per_unit).round() / pixels_per_unit,
+            (translation.y * pixels_per_unit).round() / pixels_per_unit,
+            translation.z,
+        )
+    }
+}
+
+/// Snaps the global transforms of [PixelSnap] entities that moved
+pub fn pixel_snap_system(
+    mut query: Query<(&PixelSnap, &mut GlobalTransform), Changed<GlobalTransform>>,
+) {
+    for (pixel_snap, mut global_transform) in query.iter_mut() {
+        let translation = pixel_snap.snap(global_transform.translation);
+        // only write changes, so snapping doesn't mark the transform as changed every frame
+        if translation != global_transform.translation {
+            global_transform.translation = translation;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::{IntoSystem, Resources, Stage, SystemStage, World};
+    use bevy_transform::components::Transform;
+
+    #[test]
+    fn snap_rendered_translation() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        let translation = Vec3::new(10.3, -4.6, 2.5);
+        let sprite = world.spawn((
+            PixelSnap::default(),
+            Transform::from_translation(translation),
+            GlobalTransform::from_translation(translation),
+        ));
+        let half_pixels = world.spawn((
+            PixelSnap {
+                pixels_per_unit: 2.0,
+            },
+            GlobalTransform::from_translation(translation),
+        ));
+
+        let mut stage = SystemStage::serial();
+        stage.add_system(pixel_snap_system.system());
+        stage.initialize(&mut world, &mut resources);
+        stage.run(&mut world, &mut resources);
+
+        assert_eq!(
+            world.get::<GlobalTransform>(sprite).unwrap().translation,
+            Vec3::new(10.0, -5.0, 2.5)
+        );
+        assert_eq!(
+            world
+                .get::<GlobalTransform>(half_pixels)
+                .unwrap()
+                .translation,
+            Vec3::new(10.5, -4.5, 2.5)
+        );
+        // the logical position is unchanged
+        assert_eq!(
+            world.get::<Transform>(sprite).unwrap().translation,
+            translation
+        );
+    }
+}
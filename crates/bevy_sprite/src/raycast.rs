@@ -0,0 +1,283 @@
+use crate::Sprite;
+use bevy_ecs::Entity;
+use bevy_math::{Vec2, Vec3};
+use bevy_transform::components::GlobalTransform;
+
+/// A ray in 2d space, starting at `origin` and extending along `direction`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Raycast2d {
+    pub origin: Vec2,
+    /// The direction of the ray. This is always normalized, so hit distances are in world units.
+    pub direction: Vec2,
+}
+
+/// The result of casting a [Raycast2d] against a grid of tiles
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridHit {
+    /// The coordinates of the tile that was hit
+    pub tile: (i32, i32),
+    /// The distance along the ray to the hit point
+    pub distance: f32,
+    /// The world position where the ray entered the tile
+    pub point: Vec2,
+    /// The normal of the tile edge that was hit. This is zero if the ray started inside the tile.
+    pub normal: Vec2,
+}
+
+/// The result of casting a [Raycast2d] against an axis-aligned box
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AabbHit {
+    /// The distance along the ray to the hit point
+    pub distance: f32,
+    /// The world position where the ray entered the box
+    pub point: Vec2,
+    /// The normal of the box edge that was hit. This is zero if the ray started inside the box.
+    pub normal: Vec2,
+}
+
+/// The result of casting a [Raycast2d] against sprites
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpriteHit {
+    pub entity: Entity,
+    pub hit: AabbHit,
+}
+
+impl Raycast2d {
+    /// Creates a new ray. `direction` does not need to be normalized, but it must not be zero.
+    pub fn new(origin: Vec2, direction: Vec2) -> Self {
+        debug_assert!(
+            direction.length_squared() > 0.0,
+            "ray direction must not be zero"
+        );
+        Raycast2d {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    /// Creates a ray starting at `from` and pointing at `to`
+    pub fn between(from: Vec2, to: Vec2) -> Self {
+        Self::new(from, to - from)
+    }
+
+    /// Returns the point `distance` units along the ray
+    pub fn point_at(&self, distance: f32) -> Vec2 {
+        self.origin + self.direction * distance
+    }
+
+    /// Walks the tiles of an infinite grid the ray passes through (in order), using DDA traversal.
+    /// Tile `(x, y)` covers `[x * tile_size.x, (x + 1) * tile_size.x)` horizontally (and likewise
+    /// vertically), so negative coordinates are handled the same way as positive ones.
+    ///
+    /// `is_solid` is called for each visited tile, starting with the tile containing the origin.
+    /// Returns the first tile for which it returns true, or `None` if no solid tile is found
+    /// within `max_distance`.
+    pub fn cast_grid(
+        &self,
+        tile_size: Vec2,
+        max_distance: f32,
+        mut is_solid: impl FnMut(i32, i32) -> bool,
+    ) -> Option<GridHit> {
+        let mut tile = (
+            (self.origin.x / tile_size.x).floor() as i32,
+            (self.origin.y / tile_size.y).floor() as i32,
+        );
+        if is_solid(tile.0, tile.1) {
+            return Some(GridHit {
+                tile,
+                distance: 0.0,
+                point: self.origin,
+                normal: Vec2::zero(),
+            });
+        }
+
+        let step_x = axis_step(self.direction.x);
+        let step_y = axis_step(self.direction.y);
+        let delta_x = axis_delta(self.direction.x, tile_size.x);
+        let delta_y = axis_delta(self.direction.y, tile_size.y);
+        let mut next_x = axis_first_boundary(self.origin.x, self.direction.x, tile_size.x, tile.0);
+        let mut next_y = axis_first_boundary(self.origin.y, self.direction.y, tile_size.y, tile.1);
+
+        loop {
+            let (distance, normal) = if next_x < next_y {
+                let distance = next_x;
+                tile.0 += step_x;
+                next_x += delta_x;
+                (distance, Vec2::new(-step_x as f32, 0.0))
+            } else {
+                let distance = next_y;
+                tile.1 += step_y;
+                next_y += delta_y;
+                (distance, Vec2::new(0.0, -step_y as f32))
+            };
+
+            if !distance.is_finite() || distance > max_distance {
+                return None;
+            }
+
+            if is_solid(tile.0, tile.1) {
+                return Some(GridHit {
+                    tile,
+                    distance,
+                    point: self.point_at(distance),
+                    normal,
+                });
+            }
+        }
+    }
+
+    /// Intersects the ray with an axis-aligned box. `center` and `size` follow the same convention
+    /// as [collide](crate::collide_aabb::collide).
+    pub fn cast_aabb(&self, center: Vec3, size: Vec2, max_distance: f32) -> Option<AabbHit> {
+        let min = center.truncate() - size / 2.0;
+        let max = center.truncate() + size / 2.0;
+
+        let (enter_x, exit_x) = axis_slab(self.origin.x, self.direction.x, min.x, max.x)?;
+        let (enter_y, exit_y) = axis_slab(self.origin.y, self.direction.y, min.y, max.y)?;
+        let enter = enter_x.max(enter_y);
+        let exit = exit_x.min(exit_y);
+        if exit < 0.0 || enter > exit || enter > max_distance {
+            return None;
+        }
+
+        if enter <= 0.0 {
+            // the ray starts inside the box
+            return Some(AabbHit {
+                distance: 0.0,
+                point: self.origin,
+                normal: Vec2::zero(),
+            });
+        }
+
+        let normal = if enter_x > enter_y {
+            Vec2::new(-self.direction.x.signum(), 0.0)
+        } else {
+            Vec2::new(0.0, -self.direction.y.signum())
+        };
+        Some(AabbHit {
+            distance: enter,
+            point: self.point_at(enter),
+            normal,
+        })
+    }
+
+    /// Returns the closest sprite hit by the ray. Sprite bounds are derived from [Sprite::size]
+    /// and the [GlobalTransform] translation and scale. Rotation is ignored.
+    pub fn cast_sprites<'a>(
+        &self,
+        sprites: impl IntoIterator<Item = (Entity, &'a Sprite, &'a GlobalTransform)>,
+        max_distance: f32,
+    ) -> Option<SpriteHit> {
+        let mut closest: Option<SpriteHit> = None;
+        for (entity, sprite, transform) in sprites {
+            let size = sprite.size * transform.scale.truncate();
+            let max_distance = closest.map_or(max_distance, |closest| closest.hit.distance);
+            if let Some(hit) = self.cast_aabb(transform.translation, size, max_distance) {
+                if closest.map_or(true, |closest| hit.distance < closest.hit.distance) {
+                    closest = Some(SpriteHit { entity, hit });
+                }
+            }
+        }
+
+        closest
+    }
+}
+
+fn axis_step(direction: f32) -> i32 {
+    if direction > 0.0 {
+        1
+    } else if direction < 0.0 {
+        -1
+    } else {
+        0
+    }
+}
+
+/// The distance along the ray between two tile boundaries on this axis
+fn axis_delta(direction: f32, tile_size: f32) -> f32 {
+    if direction == 0.0 {
+        f32::INFINITY
+    } else {
+        (tile_size / direction).abs()
+    }
+}
+
+/// The distance along the ray to the first tile boundary on this axis
+fn axis_first_boundary(origin: f32, direction: f32, tile_size: f32, tile: i32) -> f32 {
+    if direction > 0.0 {
+        ((tile + 1) as f32 * tile_size - origin) / direction
+    } else if direction < 0.0 {
+        (tile as f32 * tile_size - origin) / direction
+    } else {
+        f32::INFINITY
+    }
+}
+
+/// The distances along the ray at which it enters and exits the `[min, max]` slab on this axis
+fn axis_slab(origin: f32, direction: f32, min: f32, max: f32) -> Option<(f32, f32)> {
+    if direction == 0.0 {
+        if origin < min || origin > max {
+            None
+        } else {
+            Some((f32::NEG_INFINITY, f32::INFINITY))
+        }
+    } else {
+        let t1 = (min - origin) / direction;
+        let t2 = (max - origin) / direction;
+        Some((t1.min(t2), t1.max(t2)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_hit() {
+        let ray = Raycast2d::new(Vec2::new(0.5, 0.5), Vec2::new(1.0, 0.0));
+        let hit = ray
+            .cast_grid(Vec2::new(1.0, 1.0), 100.0, |x, y| x == 3 && y == 0)
+            .unwrap();
+        assert_eq!(hit.tile, (3, 0));
+        assert_eq!(hit.distance, 2.5);
+        assert_eq!(hit.normal, Vec2::new(-1.0, 0.0));
+    }
+
+    #[test]
+    fn grid_negative_coordinates() {
+        let ray = Raycast2d::new(Vec2::new(0.5, 0.5), Vec2::new(-1.0, -1.0));
+        let mut visited = Vec::new();
+        let hit = ray.cast_grid(Vec2::new(1.0, 1.0), 100.0, |x, y| {
+            visited.push((x, y));
+            x == -2 && y == -2
+        });
+        assert_eq!(hit.unwrap().tile, (-2, -2));
+        assert_eq!(visited[0], (0, 0));
+    }
+
+    #[test]
+    fn grid_max_distance() {
+        let ray = Raycast2d::new(Vec2::new(0.5, 0.5), Vec2::new(0.0, 1.0));
+        assert!(ray
+            .cast_grid(Vec2::new(1.0, 1.0), 5.0, |_, y| y == 10)
+            .is_none());
+    }
+
+    #[test]
+    fn aabb_hit() {
+        let ray = Raycast2d::new(Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0));
+        let hit = ray
+            .cast_aabb(Vec3::new(10.0, 0.0, 0.0), Vec2::new(4.0, 4.0), 100.0)
+            .unwrap();
+        assert_eq!(hit.distance, 8.0);
+        assert_eq!(hit.point, Vec2::new(8.0, 0.0));
+        assert_eq!(hit.normal, Vec2::new(-1.0, 0.0));
+
+        assert!(ray
+            .cast_aabb(Vec3::new(10.0, 5.0, 0.0), Vec2::new(4.0, 4.0), 100.0)
+            .is_none());
+        assert!(ray
+            .cast_aabb(Vec3::new(-10.0, 0.0, 0.0), Vec2::new(4.0, 4.0), 100.0)
+            .is_none());
+    }
+}
@@ -0,0 +1,3 @@
+layout(set = 3, binding = 0) uniform GlobalTint {
+    vec4 Tint;
+};
@@ -0,0 +1,112 @@
+use crate::GlobalTint;
+use bevy_core::AsBytes;
+use bevy_ecs::{Commands, IntoSystem, Local, Res, ResMut, Resources, System, World};
+use bevy_render::{
+    render_graph::{CommandQueue, Node, ResourceSlots, SystemNode},
+    renderer::{
+        BufferId, BufferInfo, BufferUsage, RenderContext, RenderResourceBinding,
+        RenderResourceBindings, RenderResourceContext,
+    },
+};
+
+/// The name of the uniform that sprite shaders read the [GlobalTint] from
+pub const GLOBAL_TINT: &str = "GlobalTint";
+
+/// A Render Graph [Node] that writes the [GlobalTint] to a GPU buffer
+#[derive(Debug, Default)]
+pub struct GlobalTintNode {
+    command_queue: CommandQueue,
+}
+
+impl Node for GlobalTintNode {
+    fn update(
+        &mut self,
+        _world: &World,
+        _resources: &Resources,
+        render_context: &mut dyn RenderContext,
+        _input: &ResourceSlots,
+        _output: &mut ResourceSlots,
+    ) {
+        self.command_queue.execute(render_context);
+    }
+}
+
+impl SystemNode for GlobalTintNode {
+    fn get_system(&self, commands: &mut Commands) -> Box<dyn System<In = (), Out = ()>> {
+        let system = global_tint_node_system.system();
+        commands.insert_local_resource(
+            system.id(),
+            GlobalTintNodeState {
+                command_queue: self.command_queue.clone(),
+                ..Default::default()
+            },
+        );
+        Box::new(system)
+    }
+}
+
+/// Local "global tint node system" state
+#[derive(Debug, Default)]
+pub struct GlobalTintNodeState {
+    command_queue: CommandQueue,
+    tint_buffer: Option<BufferId>,
+    staging_buffer: Option<BufferId>,
+    written_tint: Option<GlobalTint>,
+}
+
+pub fn global_tint_node_system(
+    mut state: Local<GlobalTintNodeState>,
+    render_resource_context: Res<Box<dyn RenderResourceContext>>,
+    global_tint: Res<GlobalTint>,
+    mut render_resource_bindings: ResMut<RenderResourceBindings>,
+) {
+    if state.written_tint == Some(*global_tint) {
+        return;
+    }
+
+    let render_resource_context = &**render_resource_context;
+    let size = std::mem::size_of::<[f32; 4]>();
+    let staging_buffer = if let Some(staging_buffer) = state.staging_buffer {
+        render_resource_context.map_buffer(staging_buffer);
+        staging_buffer
+    } else {
+        let buffer = render_resource_context.create_buffer(BufferInfo {
+            size,
+            buffer_usage: BufferUsage::UNIFORM | BufferUsage::COPY_DST,
+            ..Default::default()
+        });
+        render_resource_bindings.set(
+            GLOBAL_TINT,
+            RenderResourceBinding::Buffer {
+                buffer,
+                range: 0..size as u64,
+                dynamic_index: None,
+            },
+        );
+        state.tint_buffer = Some(buffer);
+
+        let staging_buffer = render_resource_context.create_buffer(BufferInfo {
+            size,
+            buffer_usage: BufferUsage::COPY_SRC | BufferUsage::MAP_WRITE,
+            mapped_at_creation: true,
+        });
+        state.staging_buffer = Some(staging_buffer);
+        staging_buffer
+    };
+
+    // shaders multiply colors in linear space
+    let tint = global_tint.color.as_linear_rgba();
+    render_resource_context.write_mapped_buffer(
+        staging_buffer,
+        0..size as u64,
+        &mut |data, _renderer| {
+            data[0..size].copy_from_slice(tint.as_bytes());
+        },
+    );
+    render_resource_context.unmap_buffer(staging_buffer);
+    let tint_buffer = state.tint_buffer.unwrap();
+    state
+        .command_queue
+        .copy_buffer_to_buffer(staging_buffer, 0, tint_buffer, 0, size as u64);
+    state.written_tint = Some(*global_tint);
+}
@@ -1,4 +1,10 @@
-use crate::{ColorMaterial, Sprite, TextureAtlas, TextureAtlasSprite};
+mod global_tint_node;
+mod picking_node;
+
+pub use global_tint_node::*;
+pub use picking_node::*;
+
+use crate::{ColorMaterial, Sprite, TextureAtlas, TextureAtlasSprite, WeatherOverlay};
 use bevy_asset::{Assets, HandleUntyped};
 use bevy_ecs::Resources;
 use bevy_reflect::TypeUuid;
@@ -12,6 +18,8 @@ use bevy_render::{
     shader::{Shader, ShaderStage, ShaderStages},
     texture::TextureFormat,
 };
+use bevy_utils::HashMap;
+use std::path::PathBuf;
 
 pub const SPRITE_PIPELINE_HANDLE: HandleUntyped =
     HandleUntyped::weak_from_u64(PipelineDescriptor::TYPE_UUID, 2785347840338765446);
@@ -19,6 +27,31 @@ pub const SPRITE_PIPELINE_HANDLE: HandleUntyped =
 pub const SPRITE_SHEET_PIPELINE_HANDLE: HandleUntyped =
     HandleUntyped::weak_from_u64(PipelineDescriptor::TYPE_UUID, 9016885805180281612);
 
+pub const WEATHER_OVERLAY_PIPELINE_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(PipelineDescriptor::TYPE_UUID, 4917325086731468292);
+
+pub const SPRITE_PICKING_PIPELINE_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(PipelineDescriptor::TYPE_UUID, 13390627391573506157);
+
+/// The GLSL the sprite shaders share, by the path they include it with
+fn shader_includes() -> HashMap<PathBuf, String> {
+    let mut includes = HashMap::default();
+    includes.insert(
+        PathBuf::from("color_material.glsl"),
+        include_str!("color_material.glsl").to_string(),
+    );
+    includes.insert(
+        PathBuf::from("global_tint.glsl"),
+        include_str!("global_tint.glsl").to_string(),
+    );
+    includes
+}
+
+fn sprite_shader(stage: ShaderStage, glsl: &str) -> Shader {
+    Shader::from_glsl_with_includes(stage, glsl, &shader_includes())
+        .expect("Sprite shaders only include files in `shader_includes`.")
+}
+
 pub fn build_sprite_sheet_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
     PipelineDescriptor {
         rasterization_state: Some(RasterizationStateDescriptor {
@@ -30,7 +63,7 @@ pub fn build_sprite_sheet_pipeline(shaders: &mut Assets<Shader>) -> PipelineDesc
             clamp_depth: false,
         }),
         depth_stencil_state: Some(DepthStencilStateDescriptor {
-            format: TextureFormat::Depth32Float,
+            format: TextureFormat::Depth24PlusStencil8,
             depth_write_enabled: true,
             depth_compare: CompareFunction::LessEqual,
             stencil: StencilStateDescriptor {
@@ -55,11 +88,11 @@ pub fn build_sprite_sheet_pipeline(shaders: &mut Assets<Shader>) -> PipelineDesc
             write_mask: ColorWrite::ALL,
         }],
         ..PipelineDescriptor::new(ShaderStages {
-            vertex: shaders.add(Shader::from_glsl(
+            vertex: shaders.add(sprite_shader(
                 ShaderStage::Vertex,
                 include_str!("sprite_sheet.vert"),
             )),
-            fragment: Some(shaders.add(Shader::from_glsl(
+            fragment: Some(shaders.add(sprite_shader(
                 ShaderStage::Fragment,
                 include_str!("sprite_sheet.frag"),
             ))),
@@ -78,7 +111,7 @@ pub fn build_sprite_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor
             clamp_depth: false,
         }),
         depth_stencil_state: Some(DepthStencilStateDescriptor {
-            format: TextureFormat::Depth32Float,
+            format: TextureFormat::Depth24PlusStencil8,
             depth_write_enabled: true,
             depth_compare: CompareFunction::LessEqual,
             stencil: StencilStateDescriptor {
@@ -103,11 +136,11 @@ pub fn build_sprite_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor
             write_mask: ColorWrite::ALL,
         }],
         ..PipelineDescriptor::new(ShaderStages {
-            vertex: shaders.add(Shader::from_glsl(
+            vertex: shaders.add(sprite_shader(
                 ShaderStage::Vertex,
                 include_str!("sprite.vert"),
             )),
-            fragment: Some(shaders.add(Shader::from_glsl(
+            fragment: Some(shaders.add(sprite_shader(
                 ShaderStage::Fragment,
                 include_str!("sprite.frag"),
             ))),
@@ -115,15 +148,111 @@ pub fn build_sprite_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor
     }
 }
 
+/// Weather is drawn over everything else, so it neither writes nor tests depth
+pub fn build_weather_overlay_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
+    PipelineDescriptor {
+        rasterization_state: Some(RasterizationStateDescriptor {
+            front_face: FrontFace::Ccw,
+            cull_mode: CullMode::None,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+            clamp_depth: false,
+        }),
+        depth_stencil_state: Some(DepthStencilStateDescriptor {
+            format: TextureFormat::Depth24PlusStencil8,
+            depth_write_enabled: false,
+            depth_compare: CompareFunction::Always,
+            stencil: StencilStateDescriptor {
+                front: StencilStateFaceDescriptor::IGNORE,
+                back: StencilStateFaceDescriptor::IGNORE,
+                read_mask: 0,
+                write_mask: 0,
+            },
+        }),
+        color_states: vec![ColorStateDescriptor {
+            format: TextureFormat::default(),
+            color_blend: BlendDescriptor {
+                src_factor: BlendFactor::SrcAlpha,
+                dst_factor: BlendFactor::OneMinusSrcAlpha,
+                operation: BlendOperation::Add,
+            },
+            alpha_blend: BlendDescriptor {
+                src_factor: BlendFactor::One,
+                dst_factor: BlendFactor::One,
+                operation: BlendOperation::Add,
+            },
+            write_mask: ColorWrite::ALL,
+        }],
+        ..PipelineDescriptor::new(ShaderStages {
+            vertex: shaders.add(sprite_shader(
+                ShaderStage::Vertex,
+                include_str!("weather_overlay.vert"),
+            )),
+            fragment: Some(shaders.add(sprite_shader(
+                ShaderStage::Fragment,
+                include_str!("weather_overlay.frag"),
+            ))),
+        })
+    }
+}
+
+/// Draws the [PICKING_ID](picking_node::PICKING_ID) of sprites instead of their color. It uses the
+/// same bind groups as the sprite pipeline, so it can draw with the bind groups of sprites.
+pub fn build_sprite_picking_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
+    PipelineDescriptor {
+        rasterization_state: Some(RasterizationStateDescriptor {
+            front_face: FrontFace::Ccw,
+            cull_mode: CullMode::None,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+            clamp_depth: false,
+        }),
+        depth_stencil_state: Some(DepthStencilStateDescriptor {
+            format: TextureFormat::Depth32Float,
+            depth_write_enabled: true,
+            depth_compare: CompareFunction::LessEqual,
+            stencil: StencilStateDescriptor {
+                front: StencilStateFaceDescriptor::IGNORE,
+                back: StencilStateFaceDescriptor::IGNORE,
+                read_mask: 0,
+                write_mask: 0,
+            },
+        }),
+        color_states: vec![ColorStateDescriptor {
+            format: TextureFormat::R32Uint,
+            color_blend: BlendDescriptor::REPLACE,
+            alpha_blend: BlendDescriptor::REPLACE,
+            write_mask: ColorWrite::ALL,
+        }],
+        ..PipelineDescriptor::new(ShaderStages {
+            vertex: shaders.add(sprite_shader(
+                ShaderStage::Vertex,
+                include_str!("sprite_picking.vert"),
+            )),
+            fragment: Some(shaders.add(sprite_shader(
+                ShaderStage::Fragment,
+                include_str!("sprite_picking.frag"),
+            ))),
+        })
+    }
+}
+
 pub mod node {
     pub const COLOR_MATERIAL: &str = "color_material";
     pub const SPRITE: &str = "sprite";
     pub const SPRITE_SHEET: &str = "sprite_sheet";
     pub const SPRITE_SHEET_SPRITE: &str = "sprite_sheet_sprite";
+    pub const GLOBAL_TINT: &str = "global_tint";
+    pub const WEATHER_OVERLAY: &str = "weather_overlay";
+    pub const PICKING: &str = "picking";
 }
 
 pub trait SpriteRenderGraphBuilder {
     fn add_sprite_graph(&mut self, resources: &Resources) -> &mut Self;
+    /// Adds a [PickingNode] for the 2d camera, that runs after the main pass
+    fn add_sprite_picking_graph(&mut self, resources: &Resources) -> &mut Self;
 }
 
 impl SpriteRenderGraphBuilder for RenderGraph {
@@ -149,6 +278,17 @@ impl SpriteRenderGraphBuilder for RenderGraph {
             RenderResourcesNode::<TextureAtlasSprite>::new(true),
         );
 
+        self.add_system_node(node::GLOBAL_TINT, GlobalTintNode::default());
+        self.add_node_edge(node::GLOBAL_TINT, base::node::MAIN_PASS)
+            .unwrap();
+
+        self.add_system_node(
+            node::WEATHER_OVERLAY,
+            RenderResourcesNode::<WeatherOverlay>::new(true),
+        );
+        self.add_node_edge(node::WEATHER_OVERLAY, base::node::MAIN_PASS)
+            .unwrap();
+
         let mut pipelines = resources.get_mut::<Assets<PipelineDescriptor>>().unwrap();
         let mut shaders = resources.get_mut::<Assets<Shader>>().unwrap();
         pipelines.set_untracked(SPRITE_PIPELINE_HANDLE, build_sprite_pipeline(&mut shaders));
@@ -156,6 +296,53 @@ impl SpriteRenderGraphBuilder for RenderGraph {
             SPRITE_SHEET_PIPELINE_HANDLE,
             build_sprite_sheet_pipeline(&mut shaders),
         );
+        pipelines.set_untracked(
+            WEATHER_OVERLAY_PIPELINE_HANDLE,
+            build_weather_overlay_pipeline(&mut shaders),
+        );
+        self
+    }
+
+    fn add_sprite_picking_graph(&mut self, resources: &Resources) -> &mut Self {
+        let mut picking_node = PickingNode::new(base::camera::CAMERA_2D);
+        picking_node.add_pipeline(
+            SPRITE_PIPELINE_HANDLE.typed(),
+            SPRITE_PICKING_PIPELINE_HANDLE.typed(),
+        );
+        self.add_node(node::PICKING, picking_node);
+        self.add_node_edge(base::node::MAIN_PASS, node::PICKING)
+            .unwrap();
+
+        let mut pipelines = resources.get_mut::<Assets<PipelineDescriptor>>().unwrap();
+        let mut shaders = resources.get_mut::<Assets<Shader>>().unwrap();
+        pipelines.set_untracked(
+            SPRITE_PICKING_PIPELINE_HANDLE,
+            build_sprite_picking_pipeline(&mut shaders),
+        );
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sprite_shaders_compile() {
+        let shaders = [
+            (ShaderStage::Vertex, include_str!("sprite.vert")),
+            (ShaderStage::Fragment, include_str!("sprite.frag")),
+            (ShaderStage::Vertex, include_str!("sprite_sheet.vert")),
+            (ShaderStage::Fragment, include_str!("sprite_sheet.frag")),
+            (ShaderStage::Vertex, include_str!("sprite_picking.vert")),
+            (ShaderStage::Fragment, include_str!("sprite_picking.frag")),
+        ];
+        // the included color material declares the texture only with this def
+        let texture = vec!["COLORMATERIAL_TEXTURE".to_string()];
+        for (stage, glsl) in shaders.iter() {
+            let shader = sprite_shader(*stage, glsl);
+            shader.get_spirv(None).unwrap();
+            shader.get_spirv(Some(&texture)).unwrap();
+        }
+    }
+}
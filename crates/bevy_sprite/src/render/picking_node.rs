@@ -0,0 +1,595 @@
+use crate::{PickHit, Picking};
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::{Entity, Resources, World};
+use bevy_math::Vec2;
+use bevy_render::{
+    camera::{ActiveCameras, Camera, VisibleEntities},
+    color::Color,
+    draw::{Draw, RenderCommand},
+    pass::{
+        LoadOp, Operations, PassDescriptor, RenderPassColorAttachmentDescriptor,
+        RenderPassDepthStencilAttachmentDescriptor, TextureAttachment,
+    },
+    pipeline::{BindGroupDescriptorId, PipelineCompiler, PipelineDescriptor, RenderPipelines},
+    prelude::Visible,
+    render_graph::{Node, ResourceSlots},
+    renderer::{
+        BindGroup, BindGroupId, BufferId, BufferInfo, BufferUsage, DynamicBuffer, RenderContext,
+        RenderResourceBindings, RenderResourceContext, TextureId,
+    },
+    shader::Shader,
+    texture::{Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsage},
+};
+use bevy_transform::components::GlobalTransform;
+use bevy_utils::HashMap;
+use bevy_window::Windows;
+use std::ops::Deref;
+
+/// The name of the uniform that picking shaders read the id of the drawn entity from. Ids start
+/// at 1, because 0 is left where nothing was drawn.
+pub const PICKING_ID: &str = "PickingId";
+
+/// The size of the id in the [PICKING_ID] uniform
+const PICKING_ID_SIZE: u64 = 4;
+
+/// Copies to buffers need rows that are a multiple of 256 bytes, even for a single texel
+const READBACK_BYTES_PER_ROW: u32 = 256;
+
+/// A picking pipeline, compiled with the specialization of a pipeline that entities are drawn
+/// with
+#[derive(Debug)]
+struct PickingPipeline {
+    pipeline: Handle<PipelineDescriptor>,
+    /// For each bind group of the entity pipeline, the layout to set it with if the picking
+    /// pipeline uses the same one
+    shared_bind_groups: Vec<Option<BindGroupDescriptorId>>,
+    camera_bind_group: Option<BindGroupDescriptorId>,
+    id_bind_group_index: u32,
+    id_bind_group: BindGroupDescriptorId,
+}
+
+#[derive(Debug)]
+struct PickingTarget {
+    width: u32,
+    height: u32,
+    id_texture: TextureId,
+    depth_texture: TextureId,
+}
+
+/// A pick whose id is being read back from the GPU
+#[derive(Debug)]
+struct PendingPick {
+    /// The entities that were drawn, where the entity with id `n` is at `n - 1`
+    entities: Vec<Entity>,
+    cursor_position: Vec2,
+    world_position: Vec2,
+}
+
+#[derive(Debug)]
+enum Readback {
+    Idle,
+    /// The id under the cursor is copied to the readback buffer by the commands of this frame
+    Copied(PendingPick),
+    /// The readback buffer is being mapped
+    Mapping(PendingPick),
+}
+
+/// A Render Graph [Node] that draws the entities a camera sees with picking pipelines, which
+/// write the ids of the entities instead of their colors, and reads back the id under the cursor
+/// into the [Picking] resource.
+///
+/// Entities are drawn with the bind groups of their own pipelines, so each picking pipeline must
+/// use the bind groups of the pipeline it is added for, and read the id from a [PICKING_ID]
+/// uniform in a bind group of its own. The readback never waits for the GPU: a new pick is only
+/// drawn once the last one was read, a few frames later.
+#[derive(Debug)]
+pub struct PickingNode {
+    camera_name: String,
+    pipelines: HashMap<Handle<PipelineDescriptor>, Handle<PipelineDescriptor>>,
+    /// The compiled picking pipelines, by the compiled pipeline they draw entities of
+    compiled_pipelines: HashMap<Handle<PipelineDescriptor>, PickingPipeline>,
+    id_buffer: DynamicBuffer,
+    target: Option<PickingTarget>,
+    readback_buffer: Option<BufferId>,
+    readback: Readback,
+}
+
+impl PickingNode {
+    pub fn new(camera_name: &str) -> Self {
+        PickingNode {
+            camera_name: camera_name.to_string(),
+            pipelines: HashMap::default(),
+            compiled_pipelines: HashMap::default(),
+            id_buffer: DynamicBuffer::new(BufferUsage::UNIFORM, 64 * 256),
+            target: None,
+            readback_buffer: None,
+            readback: Readback::Idle,
+        }
+    }
+
+    /// Picks the entities drawn with `pipeline`, by drawing them with `picking_pipeline`
+    pub fn add_pipeline(
+        &mut self,
+        pipeline: Handle<PipelineDescriptor>,
+        picking_pipeline: Handle<PipelineDescriptor>,
+    ) {
+        self.pipelines.insert(pipeline, picking_pipeline);
+    }
+
+    fn read_back(
+        &mut self,
+        world: &World,
+        render_resource_context: &dyn RenderResourceContext,
+        picking: &mut Picking,
+    ) {
+        let readback_buffer = match self.readback_buffer {
+            Some(readback_buffer) => readback_buffer,
+            None => return,
+        };
+        match std::mem::replace(&mut self.readback, Readback::Idle) {
+            Readback::Idle => {}
+            // the commands that copy the id were submitted at the end of the last frame
+            Readback::Copied(pick) => {
+                render_resource_context.map_buffer_async(readback_buffer);
+                self.readback = Readback::Mapping(pick);
+            }
+            Readback::Mapping(pick) => {
+                let mut id = 0;
+                let read = render_resource_context
+                    .try_read_mapped_buffer(readback_buffer, &mut |data| {
+                        id = u32::from_le_bytes([data[0], data[1], data[2], data[3]])
+                    });
+                if !read {
+                    self.readback = Readback::Mapping(pick);
+                    return;
+                }
+                picking.hovered = id
+                    .checked_sub(1)
+                    .and_then(|index| pick.entities.get(index as usize))
+                    .filter(|entity| world.contains(**entity))
+                    .map(|entity| PickHit {
+                        entity: *entity,
+                        cursor_position: pick.cursor_position,
+                        world_position: pick.world_position,
+                    });
+            }
+        }
+    }
+
+    fn update_target(
+        &mut self,
+        render_resource_context: &dyn RenderResourceContext,
+        width: u32,
+        height: u32,
+    ) -> &PickingTarget {
+        if let Some(target) = &self.target {
+            if target.width != width || target.height != height {
+                render_resource_context.remove_texture(target.id_texture);
+                render_resource_context.remove_texture(target.depth_texture);
+                self.target = None;
+            }
+        }
+        self.target.get_or_insert_with(|| {
+            let descriptor = TextureDescriptor {
+                size: Extent3d::new(width, height, 1),
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::R32Uint,
+                usage: TextureUsage::OUTPUT_ATTACHMENT | TextureUsage::COPY_SRC,
+            };
+            PickingTarget {
+                width,
+                height,
+                id_texture: render_resource_context.create_texture(descriptor),
+                depth_texture: render_resource_context.create_texture(TextureDescriptor {
+                    format: TextureFormat::Depth32Float,
+                    usage: TextureUsage::OUTPUT_ATTACHMENT,
+                    ..descriptor
+                }),
+            }
+        })
+    }
+
+    /// Compiles the picking pipelines for the pipelines of an entity. Returns false if the entity
+    /// isn't drawn with any pipeline that can be picked.
+    fn compile_pipelines(
+        &mut self,
+        render_resource_context: &dyn RenderResourceContext,
+        resources: &Resources,
+        render_pipelines: &RenderPipelines,
+    ) -> bool {
+        let mut pipeline_compiler = resources.get_mut::<PipelineCompiler>().unwrap();
+        let mut pipelines = resources.get_mut::<Assets<PipelineDescriptor>>().unwrap();
+        let mut shaders = resources.get_mut::<Assets<Shader>>().unwrap();
+        let mut pickable = false;
+        for render_pipeline in render_pipelines.pipelines.iter() {
+            let picking_pipeline = match self.pipelines.get(&render_pipeline.pipeline) {
+                Some(picking_pipeline) => picking_pipeline,
+                None => continue,
+            };
+            let compiled_pipeline = match pipeline_compiler.get_specialized_pipeline(
+                &render_pipeline.pipeline,
+                &render_pipeline.specialization,
+            ) {
+                Some(compiled_pipeline) => compiled_pipeline,
+                None => continue,
+            };
+            pickable = true;
+            if self.compiled_pipelines.contains_key(&compiled_pipeline) {
+                continue;
+            }
+
+            // the picking target isn't multisampled or blended, and each entity gets its id at an
+            // offset
+            let mut specialization = render_pipeline.specialization.clone();
+            specialization.sample_count = 1;
+            specialization.blend_mode = None;
+            specialization
+                .dynamic_bindings
+                .insert(PICKING_ID.to_string());
+            let compiled_picking_pipeline = pipeline_compiler
+                .get_specialized_pipeline(picking_pipeline, &specialization)
+                .unwrap_or_else(|| {
+                    pipeline_compiler.compile_pipeline(
+                        render_resource_context,
+                        &mut pipelines,
+                        &mut shaders,
+                        picking_pipeline,
+                        &specialization,
+                    )
+                });
+
+            let layout = pipelines
+                .get(&compiled_pipeline)
+                .unwrap()
+                .get_layout()
+                .unwrap();
+            let picking_layout = pipelines
+                .get(&compiled_picking_pipeline)
+                .unwrap()
+                .get_layout()
+                .unwrap();
+            let id_bind_group = picking_layout
+                .bind_groups
+                .iter()
+                .find(|bind_group| {
+                    bind_group
+                        .bindings
+                        .iter()
+                        .any(|binding| binding.name == PICKING_ID)
+                })
+                .unwrap_or_else(|| panic!("Picking pipelines need a {} uniform", PICKING_ID));
+            let shared_bind_groups = (0..layout.bind_groups.len() as u32)
+                .map(|index| {
+                    let bind_group = layout.get_bind_group(index)?;
+                    let picking_bind_group = picking_layout.get_bind_group(index)?;
+                    if bind_group == picking_bind_group {
+                        Some(bind_group.id)
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            let camera_bind_group = picking_layout.get_bind_group(0).and_then(|bind_group| {
+                if bind_group
+                    .bindings
+                    .iter()
+                    .any(|binding| binding.name == "Camera")
+                {
+                    Some(bind_group.id)
+                } else {
+                    None
+                }
+            });
+
+            self.compiled_pipelines.insert(
+                compiled_pipeline,
+                PickingPipeline {
+                    pipeline: compiled_picking_pipeline,
+                    shared_bind_groups,
+                    camera_bind_group,
+                    id_bind_group_index: id_bind_group.index,
+                    id_bind_group: id_bind_group.id,
+                },
+            );
+        }
+        pickable
+    }
+}
+
+impl Node for PickingNode {
+    fn update(
+        &mut self,
+        world: &World,
+        resources: &Resources,
+        render_context: &mut dyn RenderContext,
+        _input: &ResourceSlots,
+        _output: &mut ResourceSlots,
+    ) {
+        let mut picking = resources.get_mut::<Picking>().unwrap();
+        self.read_back(world, render_context.resources(), &mut picking);
+        if !picking.enabled {
+            picking.hovered = None;
+            return;
+        }
+        if !matches!(self.readback, Readback::Idle) {
+            return;
+        }
+
+        let active_cameras = resources.get::<ActiveCameras>().unwrap();
+        let camera_entity = match active_cameras.get(&self.camera_name) {
+            Some(camera_entity) => camera_entity,
+            None => return,
+        };
+        let (camera, camera_transform, visible_entities) = match (
+            world.get::<Camera>(camera_entity),
+            world.get::<GlobalTransform>(camera_entity),
+            world.get::<VisibleEntities>(camera_entity),
+        ) {
+            (Ok(camera), Ok(camera_transform), Ok(visible_entities)) => {
+                (camera, camera_transform, visible_entities)
+            }
+            _ => return,
+        };
+        let windows = resources.get::<Windows>().unwrap();
+        let window = match windows.get(camera.window) {
+            Some(window) => window,
+            None => return,
+        };
+        let (width, height) = (window.physical_width(), window.physical_height());
+        let cursor_position = match window.cursor_position() {
+            Some(cursor_position) if width > 0 && height > 0 => cursor_position,
+            _ => {
+                picking.hovered = None;
+                return;
+            }
+        };
+
+        // the cursor position is in logical pixels from the bottom left corner of the window,
+        // while the target is addressed in physical pixels from its top left corner
+        let scale_factor = window.scale_factor() as f32;
+        let pixel = Vec2::new(
+            cursor_position.x * scale_factor,
+            height as f32 - cursor_position.y * scale_factor,
+        );
+        let [x, y, viewport_width, viewport_height] = camera
+            .viewport
+            .unwrap_or_default()
+            .physical_rect(width, height);
+        if pixel.x < x
+            || pixel.y < y
+            || pixel.x >= (x + viewport_width).min(width as f32)
+            || pixel.y >= (y + viewport_height).min(height as f32)
+        {
+            picking.hovered = None;
+            return;
+        }
+        let ndc = Vec2::new(
+            (pixel.x - x) / viewport_width * 2.0 - 1.0,
+            1.0 - (pixel.y - y) / viewport_height * 2.0,
+        );
+        let world_position = camera_transform.compute_matrix()
+            * camera.projection_matrix.inverse()
+            * ndc.extend(0.0).extend(1.0);
+        let world_position = world_position.truncate().truncate() / world_position.w;
+
+        // give each entity an id, and compile the pipelines to draw it with
+        let render_resource_context = render_context.resources();
+        let id_size =
+            render_resource_context.get_aligned_uniform_size(PICKING_ID_SIZE as usize, true);
+        self.id_buffer.clear(render_resource_context);
+        let mut entities = Vec::new();
+        let mut draws = Vec::new();
+        for visible_entity in visible_entities.iter() {
+            let entity = visible_entity.entity;
+            if let Ok(visible) = world.get::<Visible>(entity) {
+                if !visible.is_visible {
+                    continue;
+                }
+            }
+            let render_pipelines = match world.get::<RenderPipelines>(entity) {
+                Ok(render_pipelines) if world.get::<Draw>(entity).is_ok() => render_pipelines,
+                _ => continue,
+            };
+            if !self.compile_pipelines(render_resource_context, resources, render_pipelines) {
+                continue;
+            }
+
+            entities.push(entity);
+            let id = entities.len() as u32;
+            let (buffer, range) = self
+                .id_buffer
+                .push(render_resource_context, id_size, |data| {
+                    data[0..PICKING_ID_SIZE as usize].copy_from_slice(&id.to_le_bytes())
+                });
+            draws.push((entity, buffer, range.start as u32));
+        }
+
+        let target = self.update_target(render_resource_context, width, height);
+        let (id_texture, depth_texture) = (target.id_texture, target.depth_texture);
+        let readback_buffer = *self.readback_buffer.get_or_insert_with(|| {
+            render_resource_context.create_buffer(BufferInfo {
+                size: READBACK_BYTES_PER_ROW as usize,
+                buffer_usage: BufferUsage::MAP_READ | BufferUsage::COPY_DST,
+                mapped_at_creation: false,
+            })
+        });
+
+        // the ids of a frame can end up in several buffers if the id buffer grows
+        let mut id_bind_groups: HashMap<(BindGroupDescriptorId, BufferId), BindGroupId> =
+            HashMap::default();
+        for (_, buffer, _) in draws.iter() {
+            for pipeline in self.compiled_pipelines.values() {
+                id_bind_groups
+                    .entry((pipeline.id_bind_group, *buffer))
+                    .or_insert_with(|| {
+                        let bind_group = BindGroup::build()
+                            .add_buffer(0, *buffer, 0..PICKING_ID_SIZE)
+                            .finish();
+                        render_resource_context
+                            .create_bind_group(pipeline.id_bind_group, &bind_group);
+                        bind_group.id
+                    });
+            }
+        }
+        let render_resource_bindings = resources.get::<RenderResourceBindings>().unwrap();
+        let mut camera_bind_group = None;
+        if let Some(camera_binding) = render_resource_bindings.get(&self.camera_name) {
+            let bind_group = BindGroup::build()
+                .add_binding(0, camera_binding.clone())
+                .finish();
+            for descriptor_id in self
+                .compiled_pipelines
+                .values()
+                .filter_map(|pipeline| pipeline.camera_bind_group)
+            {
+                render_resource_context.create_bind_group(descriptor_id, &bind_group);
+            }
+            camera_bind_group = Some(bind_group.id);
+        }
+        self.id_buffer.upload(render_context);
+
+        let pass_descriptor = PassDescriptor {
+            color_attachments: vec![RenderPassColorAttachmentDescriptor {
+                attachment: TextureAttachment::Id(id_texture),
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(Color::NONE),
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: Some(RenderPassDepthStencilAttachmentDescriptor {
+                attachment: TextureAttachment::Id(depth_texture),
+                depth_ops: Some(Operations {
+                    load: LoadOp::Clear(1.0),
+                    store: true,
+                }),
+                stencil_ops: None,
+            }),
+            sample_count: 1,
+        };
+        let compiled_pipelines = &self.compiled_pipelines;
+        render_context.begin_pass(
+            &pass_descriptor,
+            &render_resource_bindings,
+            &mut |render_pass| {
+                render_pass.set_viewport(x, y, viewport_width, viewport_height, 0.0, 1.0);
+                // only the pixel under the cursor is read back
+                render_pass.set_scissor_rect(pixel.x as u32, pixel.y as u32, 1, 1);
+                for (entity, buffer, id_offset) in draws.iter() {
+                    let draw = world.get::<Draw>(*entity).unwrap();
+                    // the commands of pipelines without a picking pipeline are skipped
+                    let mut pipeline = None;
+                    // entities clipped by a scissor rect can't be picked outside of it
+                    let mut clipped = false;
+                    for render_command in draw.render_commands.iter() {
+                        match render_command {
+                            RenderCommand::SetPipeline {
+                                pipeline: entity_pipeline,
+                            } => {
+                                pipeline = compiled_pipelines.get(entity_pipeline);
+                                let pipeline = match pipeline {
+                                    Some(pipeline) => pipeline,
+                                    None => continue,
+                                };
+                                render_pass.set_pipeline(&pipeline.pipeline);
+                                if let (Some(descriptor_id), Some(bind_group)) =
+                                    (pipeline.camera_bind_group, camera_bind_group)
+                                {
+                                    render_pass.set_bind_group(0, descriptor_id, bind_group, None);
+                                }
+                                render_pass.set_bind_group(
+                                    pipeline.id_bind_group_index,
+                                    pipeline.id_bind_group,
+                                    id_bind_groups[&(pipeline.id_bind_group, *buffer)],
+                                    Some(&[*id_offset][..]),
+                                );
+                            }
+                            RenderCommand::SetBindGroup {
+                                index,
+                                bind_group,
+                                dynamic_uniform_indices,
+                            } => {
+                                let descriptor_id = pipeline.and_then(|pipeline| {
+                                    pipeline
+                                        .shared_bind_groups
+                                        .get(*index as usize)
+                                        .copied()
+                                        .flatten()
+                                });
+                                if let Some(descriptor_id) = descriptor_id {
+                                    render_pass.set_bind_group(
+                                        *index,
+                                        descriptor_id,
+                                        *bind_group,
+                                        dynamic_uniform_indices
+                                            .as_ref()
+                                            .map(|indices| indices.deref()),
+                                    );
+                                }
+                            }
+                            RenderCommand::SetVertexBuffer {
+                                buffer,
+                                offset,
+                                slot,
+                            } if pipeline.is_some() => {
+                                render_pass.set_vertex_buffer(*slot, *buffer, *offset);
+                            }
+                            RenderCommand::SetIndexBuffer { buffer, offset }
+                                if pipeline.is_some() =>
+                            {
+                                render_pass.set_index_buffer(*buffer, *offset);
+                            }
+                            RenderCommand::DrawIndexed {
+                                base_vertex,
+                                indices,
+                                instances,
+                            } if pipeline.is_some() && !clipped => {
+                                render_pass.draw_indexed(
+                                    indices.clone(),
+                                    *base_vertex,
+                                    instances.clone(),
+                                );
+                            }
+                            RenderCommand::Draw {
+                                vertices,
+                                instances,
+                            } if pipeline.is_some() && !clipped => {
+                                render_pass.draw(vertices.clone(), instances.clone());
+                            }
+                            RenderCommand::SetScissorRect {
+                                x,
+                                y,
+                                width,
+                                height,
+                            } => {
+                                let (x, y) = (*x as f32, *y as f32);
+                                clipped = pixel.x < x
+                                    || pixel.y < y
+                                    || pixel.x >= x + *width as f32
+                                    || pixel.y >= y + *height as f32;
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            },
+        );
+
+        render_context.copy_texture_to_buffer(
+            id_texture,
+            [pixel.x as u32, pixel.y as u32, 0],
+            0,
+            readback_buffer,
+            0,
+            READBACK_BYTES_PER_ROW,
+            Extent3d::new(1, 1, 1),
+        );
+        self.readback = Readback::Copied(PendingPick {
+            entities,
+            cursor_position,
+            world_position,
+        });
+    }
+}
@@ -1,24 +1,19 @@
 #version 450
 
 layout(location = 0) in vec2 v_Uv;
+layout(location = 1) in vec4 v_Color;
 
 layout(location = 0) out vec4 o_Target;
 
-layout(set = 1, binding = 0) uniform ColorMaterial_color {
-    vec4 Color;
-};
-
-# ifdef COLORMATERIAL_TEXTURE 
-layout(set = 1, binding = 1) uniform texture2D ColorMaterial_texture;
-layout(set = 1, binding = 2) uniform sampler ColorMaterial_texture_sampler;
-# endif
+#include "color_material.glsl"
+#include "global_tint.glsl"
 
 void main() {
-    vec4 color = Color;
+    vec4 color = Color * v_Color;
 # ifdef COLORMATERIAL_TEXTURE
     color *= texture(
         sampler2D(ColorMaterial_texture, ColorMaterial_texture_sampler),
         v_Uv);
 # endif
-    o_Target = color;
+    o_Target = color * Tint;
 }
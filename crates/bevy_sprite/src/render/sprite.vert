@@ -3,8 +3,10 @@
 layout(location = 0) in vec3 Vertex_Position;
 layout(location = 1) in vec3 Vertex_Normal;
 layout(location = 2) in vec2 Vertex_Uv;
+layout(location = 3) in vec4 Vertex_Color;
 
 layout(location = 0) out vec2 v_Uv;
+layout(location = 1) out vec4 v_Color;
 
 layout(set = 0, binding = 0) uniform Camera {
     mat4 ViewProj;
@@ -19,6 +21,7 @@ layout(set = 2, binding = 1) uniform Sprite_size {
 
 void main() {
     v_Uv = Vertex_Uv;
+    v_Color = Vertex_Color;
     vec3 position = Vertex_Position * vec3(size, 1.0);
     gl_Position = ViewProj * Model * vec4(position, 1.0);
 }
\ No newline at end of file
@@ -0,0 +1,25 @@
+#version 450
+
+layout(location = 0) in vec2 v_Uv;
+
+layout(location = 0) out uint o_Target;
+
+#include "color_material.glsl"
+
+layout(set = 3, binding = 0) uniform PickingId {
+    uint Id;
+};
+
+void main() {
+    float alpha = Color.a;
+# ifdef COLORMATERIAL_TEXTURE
+    alpha *= texture(
+        sampler2D(ColorMaterial_texture, ColorMaterial_texture_sampler),
+        v_Uv).a;
+# endif
+    // clicks go through the transparent parts of sprites
+    if (alpha < 0.5) {
+        discard;
+    }
+    o_Target = Id;
+}
@@ -8,8 +8,10 @@ layout(location = 0) out vec4 o_Target;
 layout(set = 1, binding = 2) uniform texture2D TextureAtlas_texture;
 layout(set = 1, binding = 3) uniform sampler TextureAtlas_texture_sampler;
 
+#include "global_tint.glsl"
+
 void main() {
-    o_Target = v_Color * texture(
+    o_Target = v_Color * Tint * texture(
         sampler2D(TextureAtlas_texture, TextureAtlas_texture_sampler),
         v_Uv);
 }
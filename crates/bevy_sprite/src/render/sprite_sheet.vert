@@ -3,6 +3,7 @@
 layout(location = 0) in vec3 Vertex_Position;
 layout(location = 1) in vec3 Vertex_Normal;
 layout(location = 2) in vec2 Vertex_Uv;
+layout(location = 3) in vec4 Vertex_Color;
 
 layout(location = 0) out vec2 v_Uv;
 layout(location = 1) out vec4 v_Color;
@@ -25,6 +26,16 @@ layout(set = 1, binding = 1) buffer TextureAtlas_textures {
     Rect[] Textures;
 };
 
+struct TextureLayout {
+    vec2 offset;
+    vec2 untrimmed_size;
+    uint rotated;
+};
+
+layout(set = 1, binding = 4) buffer TextureAtlas_layouts {
+    TextureLayout[] Layouts;
+};
+
 
 layout(set = 2, binding = 0) uniform Transform {
     mat4 SpriteTransform;
@@ -37,15 +48,21 @@ layout(set = 2, binding = 1) uniform TextureAtlasSprite {
 
 void main() {
     Rect sprite_rect = Textures[TextureAtlasSprite_index];
+    TextureLayout sprite_layout = Layouts[TextureAtlasSprite_index];
     vec2 sprite_dimensions = sprite_rect.end - sprite_rect.begin;
-    vec3 vertex_position = vec3(Vertex_Position.xy * sprite_dimensions, 0.0);
+    if (sprite_layout.rotated != 0) {
+        sprite_dimensions = sprite_dimensions.yx;
+    }
+    vec3 vertex_position = vec3(Vertex_Position.xy * sprite_dimensions + sprite_layout.offset, 0.0);
     vec2 atlas_positions[4] = vec2[](
         vec2(sprite_rect.begin.x, sprite_rect.end.y),
         sprite_rect.begin,
         vec2(sprite_rect.end.x, sprite_rect.begin.y), 
         sprite_rect.end
     );
-    v_Uv = (atlas_positions[gl_VertexIndex] + vec2(0.01, 0.01)) / AtlasSize;
-    v_Color = TextureAtlasSprite_color;
+    // the corners of textures stored rotated 90° clockwise are shifted by one
+    int corner = (gl_VertexIndex + int(sprite_layout.rotated)) % 4;
+    v_Uv = (atlas_positions[corner] + vec2(0.01, 0.01)) / AtlasSize;
+    v_Color = TextureAtlasSprite_color * Vertex_Color;
     gl_Position = ViewProj * SpriteTransform * vec4(ceil(vertex_position), 1.0);
 }
\ No newline at end of file
@@ -0,0 +1,59 @@
+#version 450
+
+layout(location = 0) in vec2 v_WorldPosition;
+
+layout(location = 0) out vec4 o_Target;
+
+layout(set = 1, binding = 1) uniform WeatherOverlay_color {
+    vec4 Color;
+};
+layout(set = 1, binding = 2) uniform WeatherOverlay_cell_size {
+    float CellSize;
+};
+layout(set = 1, binding = 3) uniform WeatherOverlay_particle_size {
+    float ParticleSize;
+};
+layout(set = 1, binding = 4) uniform WeatherOverlay_density {
+    float Density;
+};
+layout(set = 1, binding = 5) uniform WeatherOverlay_fall_speed {
+    float FallSpeed;
+};
+layout(set = 1, binding = 6) uniform WeatherOverlay_wind {
+    float Wind;
+};
+layout(set = 1, binding = 7) uniform WeatherOverlay_time {
+    float Time;
+};
+
+float hash(vec2 p) {
+    return fract(sin(dot(p, vec2(127.1, 311.7))) * 43758.5453);
+}
+
+void main() {
+    // the effect is laid out in world space, so it stays put as the camera moves. each cell of
+    // the grid holds at most one particle, and the grid moves with the wind and the fall speed
+    vec2 position = v_WorldPosition + vec2(-Wind, FallSpeed) * Time;
+    vec2 cell = floor(position / CellSize);
+    float seed = hash(cell);
+    if (seed >= Density) {
+        discard;
+    }
+    vec2 particle = (cell + 0.2 + 0.6 * vec2(hash(cell + 17.0), hash(cell + 31.0))) * CellSize;
+# ifdef WEATHEROVERLAY_SNOW
+    // flakes sway from side to side as they fall
+    particle.x += 0.15 * CellSize * sin(Time * 2.0 + seed * 6.2832);
+    float distance = length(position - particle);
+    float alpha = 1.0 - smoothstep(ParticleSize * 0.5, ParticleSize, distance);
+# else
+    // drops are thin streaks along the direction they fall in
+    vec2 offset = position - particle;
+    float across = abs(offset.x + offset.y * Wind / max(FallSpeed, 0.001));
+    float along = abs(offset.y);
+    float alpha = (1.0 - smoothstep(0.5, 1.0, across)) * (1.0 - smoothstep(0.0, ParticleSize, along));
+# endif
+    if (alpha <= 0.0) {
+        discard;
+    }
+    o_Target = vec4(Color.rgb, Color.a * alpha);
+}
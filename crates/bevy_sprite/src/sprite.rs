@@ -1,9 +1,13 @@
-use crate::ColorMaterial;
+use crate::{ColorMaterial, TextureAtlas};
 use bevy_asset::{Assets, Handle};
-use bevy_ecs::{Query, Res};
+use bevy_ecs::{Query, Res, ResMut};
 use bevy_math::Vec2;
 use bevy_reflect::{Reflect, ReflectDeserialize, TypeUuid};
-use bevy_render::{renderer::RenderResources, texture::Texture};
+use bevy_render::{
+    draw::Visible,
+    renderer::RenderResources,
+    texture::{Texture, TextureResidency},
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Default, RenderResources, TypeUuid, Reflect)]
@@ -61,3 +65,44 @@ pub fn sprite_system(
         }
     }
 }
+
+/// Touches the textures of visible sprites and sprite sheets in the [TextureResidency], so that
+/// streamable textures are only evicted while they are not drawn. The materials and atlases of
+/// evicted textures are marked as modified when the textures are reloaded, so they bind the new
+/// textures.
+pub fn sprite_texture_residency_system(
+    mut residency: ResMut<TextureResidency>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    sprites: Query<(&Visible, &Handle<ColorMaterial>)>,
+    sprite_sheets: Query<(&Visible, &Handle<TextureAtlas>)>,
+) {
+    for (visible, handle) in sprites.iter() {
+        if !visible.is_visible {
+            continue;
+        }
+        let reloaded = match materials
+            .get(handle)
+            .and_then(|material| material.texture.as_ref())
+        {
+            Some(texture) => residency.touch(texture),
+            None => false,
+        };
+        if reloaded {
+            materials.get_mut(handle);
+        }
+    }
+
+    for (visible, handle) in sprite_sheets.iter() {
+        if !visible.is_visible {
+            continue;
+        }
+        let reloaded = match texture_atlases.get(handle) {
+            Some(texture_atlas) => residency.touch(&texture_atlas.texture),
+            None => false,
+        };
+        if reloaded {
+            texture_atlases.get_mut(handle);
+        }
+    }
+}
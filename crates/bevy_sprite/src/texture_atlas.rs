@@ -2,7 +2,7 @@ use crate::Rect;
 use bevy_asset::Handle;
 use bevy_core::Byteable;
 use bevy_math::Vec2;
-use bevy_reflect::TypeUuid;
+use bevy_reflect::{Reflect, ReflectComponent, TypeUuid};
 use bevy_render::{
     color::Color,
     renderer::{RenderResource, RenderResources},
@@ -21,12 +21,47 @@ pub struct TextureAtlas {
     /// The specific areas of the atlas where each texture can be found
     #[render_resources(buffer)]
     pub textures: Vec<Rect>,
+    /// How each texture is stored in its area of the atlas, if it was rotated or trimmed
+    #[render_resources(buffer)]
+    pub layouts: Vec<AtlasTextureLayout>,
     #[render_resources(ignore)]
     pub texture_handles: Option<HashMap<Handle<Texture>, usize>>,
 }
 
-#[derive(Debug, RenderResources, RenderResource)]
+/// How a texture is stored in its rect of a [TextureAtlas]
+#[repr(C)]
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct AtlasTextureLayout {
+    /// The offset from the center of the texture to the center of its trimmed area, in pixels with
+    /// y pointing up. Sprites are drawn at this offset so trimming doesn't move them.
+    pub offset: Vec2,
+    /// The size of the texture before it was trimmed, or zero if it wasn't trimmed
+    pub untrimmed_size: Vec2,
+    /// 1 if the texture is stored rotated 90° clockwise, 0 otherwise
+    pub rotated: u32,
+    _padding: u32,
+}
+
+unsafe impl Byteable for AtlasTextureLayout {}
+
+impl AtlasTextureLayout {
+    pub fn new(offset: Vec2, untrimmed_size: Vec2, rotated: bool) -> Self {
+        AtlasTextureLayout {
+            offset,
+            untrimmed_size,
+            rotated: rotated as u32,
+            _padding: 0,
+        }
+    }
+
+    pub fn is_rotated(&self) -> bool {
+        self.rotated != 0
+    }
+}
+
+#[derive(Debug, RenderResources, RenderResource, Reflect)]
 #[render_resources(from_self)]
+#[reflect(Component)]
 pub struct TextureAtlasSprite {
     pub color: Color,
     pub index: u32,
@@ -61,6 +96,7 @@ impl TextureAtlas {
             size: dimensions,
             texture_handles: None,
             textures: Vec::new(),
+            layouts: Vec::new(),
         }
     }
 
@@ -115,6 +151,7 @@ impl TextureAtlas {
                 ((tile_size.x + x_padding) * columns as f32) - x_padding,
                 ((tile_size.y + y_padding) * rows as f32) - y_padding,
             ),
+            layouts: vec![AtlasTextureLayout::default(); sprites.len()],
             textures: sprites,
             texture,
             texture_handles: None,
@@ -129,6 +166,7 @@ impl TextureAtlas {
     /// from the top-left corner of the texture to the bottom-right corner
     pub fn add_texture(&mut self, rect: Rect) {
         self.textures.push(rect);
+        self.layouts.push(AtlasTextureLayout::default());
     }
 
     /// How many textures are in the `TextureAtlas`
@@ -1,11 +1,13 @@
-use crate::{Rect, TextureAtlas};
+use crate::{AtlasTextureLayout, Rect, TextureAtlas};
 use bevy_asset::{Assets, Handle};
 use bevy_math::Vec2;
-use bevy_render::texture::{Extent3d, Texture, TextureDimension, TextureFormat};
+use bevy_render::texture::{
+    Extent3d, Texture, TextureConversionError, TextureDimension, TextureFormat,
+};
 use bevy_utils::HashMap;
 use rectangle_pack::{
-    contains_smallest_box, pack_rects, volume_heuristic, GroupedRectsToPlace, PackedLocation,
-    RectToInsert, TargetBin,
+    contains_smallest_box, pack_rects, volume_heuristic, GroupedRectsToPlace, RectToInsert,
+    TargetBin,
 };
 use thiserror::Error;
 
@@ -13,6 +15,8 @@ use thiserror::Error;
 pub enum TextureAtlasBuilderError {
     #[error("could not pack textures into an atlas within the given bounds")]
     NotEnoughSpace,
+    #[error("could not convert a texture to the format of the atlas")]
+    WrongFormat(#[from] TextureConversionError),
 }
 
 #[derive(Debug)]
@@ -26,6 +30,25 @@ pub struct TextureAtlasBuilder {
     initial_size: Vec2,
     /// The absolute maximum size of the texture atlas in pixels.
     max_size: Vec2,
+    /// The format of the atlas texture.
+    format: TextureFormat,
+    /// Whether textures taller than they are wide are stored rotated.
+    allow_rotation: bool,
+    /// Whether the transparent borders of textures are trimmed.
+    trim: bool,
+    /// The area of each texture that is copied to the atlas, and whether it is rotated.
+    placements: HashMap<Handle<Texture>, TexturePlacement>,
+    /// The number of transparent pixels between textures.
+    padding: u32,
+    /// The number of times the edge pixels of textures are duplicated around them.
+    extrusion: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TexturePlacement {
+    min: [u32; 2],
+    max: [u32; 2],
+    rotated: bool,
 }
 
 impl Default for TextureAtlasBuilder {
@@ -34,6 +57,12 @@ impl Default for TextureAtlasBuilder {
             rects_to_place: GroupedRectsToPlace::new(),
             initial_size: Vec2::new(256., 256.),
             max_size: Vec2::new(2048., 2048.),
+            format: TextureFormat::Rgba8UnormSrgb,
+            allow_rotation: false,
+            trim: false,
+            placements: HashMap::default(),
+            padding: 0,
+            extrusion: 0,
         }
     }
 }
@@ -53,35 +82,87 @@ impl TextureAtlasBuilder {
         self
     }
 
+    /// Sets the format of the atlas texture. Textures in other formats are converted to it.
+    pub fn format(mut self, format: TextureFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Allows textures that are taller than they are wide to be stored rotated by 90°, which
+    /// packs irregular sprites more tightly. The sprite sheet shader rotates them back.
+    pub fn allow_rotation(mut self, allow_rotation: bool) -> Self {
+        self.allow_rotation = allow_rotation;
+        self
+    }
+
+    /// Trims the fully transparent borders of textures with an alpha channel before packing
+    /// them. The offsets of the trimmed areas are stored in the [AtlasTextureLayout] of each
+    /// texture, so sprites are drawn where they would be without trimming.
+    pub fn trim(mut self, trim: bool) -> Self {
+        self.trim = trim;
+        self
+    }
+
+    /// Leaves `padding` transparent pixels between the textures of the atlas.
+    pub fn padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Surrounds each texture with `extrusion` copies of its edge pixels, so linear filtering
+    /// and zooming out don't blend neighboring textures, such as tiles, into its edges. The rects
+    /// of the atlas only cover the textures themselves.
+    pub fn extrusion(mut self, extrusion: u32) -> Self {
+        self.extrusion = extrusion;
+        self
+    }
+
     /// Adds a texture to be copied to the texture atlas.
     pub fn add_texture(&mut self, texture_handle: Handle<Texture>, texture: &Texture) {
-        self.rects_to_place.push_rect(
-            texture_handle,
-            None,
-            RectToInsert::new(texture.size.width, texture.size.height, 1),
-        )
+        let (min, max) = if self.trim {
+            opaque_bounds(texture).unwrap_or(([0, 0], [texture.size.width, texture.size.height]))
+        } else {
+            ([0, 0], [texture.size.width, texture.size.height])
+        };
+        let width = max[0] - min[0];
+        let height = max[1] - min[1];
+        let rotated = self.allow_rotation && height > width;
+        self.placements.insert(
+            texture_handle.clone_weak(),
+            TexturePlacement { min, max, rotated },
+        );
+        let border = 2 * self.extrusion + self.padding;
+        let rect = if rotated {
+            RectToInsert::new(height + border, width + border, 1)
+        } else {
+            RectToInsert::new(width + border, height + border, 1)
+        };
+        self.rects_to_place.push_rect(texture_handle, None, rect)
     }
 
     fn copy_texture(
         &mut self,
         atlas_texture: &mut Texture,
         texture: &Texture,
-        packed_location: &PackedLocation,
+        placement: &TexturePlacement,
+        target: [u32; 2],
     ) {
-        let rect_width = packed_location.width() as usize;
-        let rect_height = packed_location.height() as usize;
-        let rect_x = packed_location.x() as usize;
-        let rect_y = packed_location.y() as usize;
-        let atlas_width = atlas_texture.size.width as usize;
-        let format_size = atlas_texture.format.pixel_size();
-
-        for (texture_y, bound_y) in (rect_y..rect_y + rect_height).enumerate() {
-            let begin = (bound_y * atlas_width + rect_x) * format_size;
-            let end = begin + rect_width * format_size;
-            let texture_begin = texture_y * rect_width * format_size;
-            let texture_end = texture_begin + rect_width * format_size;
-            atlas_texture.data[begin..end]
-                .copy_from_slice(&texture.data[texture_begin..texture_end]);
+        if !placement.rotated {
+            atlas_texture.blit_from(texture, placement.min, placement.max, target);
+            return;
+        }
+
+        // stored pixel (x, y) of a texture rotated clockwise is pixel (y, height - 1 - x) of
+        // the texture
+        let width = placement.max[0] - placement.min[0];
+        let height = placement.max[1] - placement.min[1];
+        for y in 0..width {
+            for x in 0..height {
+                let pixel = texture
+                    .get_pixel(placement.min[0] + y, placement.min[1] + height - 1 - x)
+                    .unwrap();
+                atlas_texture.set_pixel(target[0] + x, target[1] + y, pixel);
+            }
         }
     }
 
@@ -96,6 +177,8 @@ impl TextureAtlasBuilder {
     ///
     /// If there is not enough space in the atlas texture, an error will
     /// be returned. It is then recommended to make a larger sprite sheet.
+    /// An error is also returned if a texture can't be converted to the
+    /// format of the atlas.
     pub fn finish(
         mut self,
         textures: &mut Assets<Texture>,
@@ -129,8 +212,8 @@ impl TextureAtlasBuilder {
                     atlas_texture = Texture::new_fill(
                         Extent3d::new(current_width, current_height, 1),
                         TextureDimension::D2,
-                        &[0, 0, 0, 0],
-                        TextureFormat::Rgba8UnormSrgb,
+                        &vec![0; self.format.pixel_size()],
+                        self.format,
                     );
                     Some(rect_placements)
                 }
@@ -149,24 +232,255 @@ impl TextureAtlasBuilder {
         let rect_placements = rect_placements.ok_or(TextureAtlasBuilderError::NotEnoughSpace)?;
 
         let mut texture_rects = Vec::with_capacity(rect_placements.packed_locations().len());
+        let mut texture_layouts = Vec::with_capacity(texture_rects.capacity());
         let mut texture_handles = HashMap::default();
         for (texture_handle, (_, packed_location)) in rect_placements.packed_locations().iter() {
             let texture = textures.get(texture_handle).unwrap();
-            let min = Vec2::new(packed_location.x() as f32, packed_location.y() as f32);
-            let max = min
-                + Vec2::new(
-                    packed_location.width() as f32,
-                    packed_location.height() as f32,
-                );
+            let border = 2 * self.extrusion + self.padding;
+            let target = [
+                packed_location.x() + self.extrusion,
+                packed_location.y() + self.extrusion,
+            ];
+            let target_max = [
+                target[0] + packed_location.width() - border,
+                target[1] + packed_location.height() - border,
+            ];
+            let min = Vec2::new(target[0] as f32, target[1] as f32);
+            let max = Vec2::new(target_max[0] as f32, target_max[1] as f32);
+            let placement = self.placements[texture_handle];
             texture_handles.insert(texture_handle.clone_weak(), texture_rects.len());
             texture_rects.push(Rect { min, max });
-            self.copy_texture(&mut atlas_texture, texture, packed_location);
+            texture_layouts.push(placement.layout(texture));
+            if texture.format == self.format {
+                self.copy_texture(&mut atlas_texture, texture, &placement, target);
+            } else {
+                let texture = texture.convert(self.format)?;
+                self.copy_texture(&mut atlas_texture, &texture, &placement, target);
+            }
+            extrude_edges(&mut atlas_texture, target, target_max, self.extrusion);
         }
         Ok(TextureAtlas {
             size: atlas_texture.size.as_vec3().truncate(),
             texture: textures.add(atlas_texture),
             textures: texture_rects,
+            layouts: texture_layouts,
             texture_handles: Some(texture_handles),
         })
     }
 }
+
+impl TexturePlacement {
+    fn layout(&self, texture: &Texture) -> AtlasTextureLayout {
+        let size = Vec2::new(texture.size.width as f32, texture.size.height as f32);
+        let min = Vec2::new(self.min[0] as f32, self.min[1] as f32);
+        let max = Vec2::new(self.max[0] as f32, self.max[1] as f32);
+        if min == Vec2::zero() && max == size {
+            return AtlasTextureLayout::new(Vec2::zero(), Vec2::zero(), self.rotated);
+        }
+
+        let center = (min + max) / 2.0 - size / 2.0;
+        AtlasTextureLayout::new(Vec2::new(center.x, -center.y), size, self.rotated)
+    }
+}
+
+/// Copies the edge pixels of the area from `min` to `max` of `texture` `extrusion` times around it
+fn extrude_edges(texture: &mut Texture, min: [u32; 2], max: [u32; 2], extrusion: u32) {
+    if extrusion == 0 || min[0] == max[0] || min[1] == max[1] {
+        return;
+    }
+
+    for y in min[1]..max[1] {
+        let left = texture.get_pixel(min[0], y).unwrap().to_vec();
+        let right = texture.get_pixel(max[0] - 1, y).unwrap().to_vec();
+        for offset in 1..=extrusion {
+            texture.set_pixel(min[0] - offset, y, &left);
+            texture.set_pixel(max[0] - 1 + offset, y, &right);
+        }
+    }
+    // rows are extruded after columns so the corners are filled too
+    for x in min[0] - extrusion..max[0] + extrusion {
+        let top = texture.get_pixel(x, min[1]).unwrap().to_vec();
+        let bottom = texture.get_pixel(x, max[1] - 1).unwrap().to_vec();
+        for offset in 1..=extrusion {
+            texture.set_pixel(x, min[1] - offset, &top);
+            texture.set_pixel(x, max[1] - 1 + offset, &bottom);
+        }
+    }
+}
+
+/// The smallest area of `texture` that contains all of its pixels that are not fully transparent,
+/// or `None` if the texture has no alpha channel or is fully transparent
+fn opaque_bounds(texture: &Texture) -> Option<([u32; 2], [u32; 2])> {
+    match texture.format {
+        TextureFormat::Rgba8Unorm
+        | TextureFormat::Rgba8UnormSrgb
+        | TextureFormat::Bgra8Unorm
+        | TextureFormat::Bgra8UnormSrgb => {}
+        _ => return None,
+    }
+
+    let mut bounds: Option<([u32; 2], [u32; 2])> = None;
+    for y in 0..texture.size.height {
+        for x in 0..texture.size.width {
+            if texture.get_pixel(x, y).unwrap()[3] == 0 {
+                continue;
+            }
+            bounds = Some(match bounds {
+                Some((min, max)) => (
+                    [min[0].min(x), min[1].min(y)],
+                    [max[0].max(x + 1), max[1].max(y + 1)],
+                ),
+                None => ([x, y], [x + 1, y + 1]),
+            });
+        }
+    }
+    bounds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_app::App;
+    use bevy_asset::{AddAsset, AssetPlugin, AssetServer, FileAssetIo};
+    use bevy_reflect::ReflectPlugin;
+    use bevy_tasks::TaskPool;
+
+    fn setup() -> App {
+        let asset_server = AssetServer::new(FileAssetIo::new(""), TaskPool::new());
+        let mut app = App::build();
+        app.add_resource(asset_server)
+            .add_plugin(ReflectPlugin)
+            .add_plugin(AssetPlugin)
+            .add_asset::<Texture>();
+        app.app
+    }
+
+    /// A `width` x `height` texture where the pixel at (x, y) holds `[x, y, 1, 255]`, surrounded
+    /// by `border` transparent pixels
+    fn texture(width: u32, height: u32, border: u32) -> Texture {
+        let mut texture = Texture::new_fill(
+            Extent3d::new(width + 2 * border, height + 2 * border, 1),
+            TextureDimension::D2,
+            &[0; 4],
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        for y in 0..height {
+            for x in 0..width {
+                texture.set_pixel(x + border, y + border, &[x as u8, y as u8, 1, 255]);
+            }
+        }
+        texture
+    }
+
+    #[test]
+    fn trim_transparent_borders() {
+        let app = setup();
+        let mut textures = app.resources.get_mut::<Assets<Texture>>().unwrap();
+        let mut source = texture(3, 2, 2);
+        // an opaque pixel moves the trimmed area off center
+        source.set_pixel(5, 0, &[9, 9, 9, 255]);
+        let handle = textures.add(source.clone());
+
+        let mut builder = TextureAtlasBuilder::default().trim(true);
+        builder.add_texture(handle.clone(), &source);
+        let texture_atlas = builder.finish(&mut textures).unwrap();
+
+        let index = texture_atlas.get_texture_index(&handle).unwrap();
+        let rect = texture_atlas.textures[index];
+        assert_eq!(rect.max - rect.min, Vec2::new(4.0, 4.0));
+        assert_eq!(
+            texture_atlas.layouts[index],
+            AtlasTextureLayout::new(Vec2::new(0.5, 1.0), Vec2::new(7.0, 6.0), false)
+        );
+        let atlas_texture = textures.get(&texture_atlas.texture).unwrap();
+        let (x, y) = (rect.min.x as u32, rect.min.y as u32);
+        assert_eq!(atlas_texture.get_pixel(x + 3, y), Some(&[9, 9, 9, 255][..]));
+        assert_eq!(atlas_texture.get_pixel(x, y + 2), Some(&[0, 0, 1, 255][..]));
+        assert_eq!(
+            atlas_texture.get_pixel(x + 2, y + 3),
+            Some(&[2, 1, 1, 255][..])
+        );
+    }
+
+    #[test]
+    fn rotate_tall_textures() {
+        let app = setup();
+        let mut textures = app.resources.get_mut::<Assets<Texture>>().unwrap();
+        let tall = texture(2, 3, 0);
+        let wide = texture(3, 2, 0);
+        let tall_handle = textures.add(tall.clone());
+        let wide_handle = textures.add(wide.clone());
+
+        let mut builder = TextureAtlasBuilder::default().allow_rotation(true);
+        builder.add_texture(tall_handle.clone(), &tall);
+        builder.add_texture(wide_handle.clone(), &wide);
+        let texture_atlas = builder.finish(&mut textures).unwrap();
+        let atlas_texture = textures.get(&texture_atlas.texture).unwrap();
+
+        let index = texture_atlas.get_texture_index(&wide_handle).unwrap();
+        assert!(!texture_atlas.layouts[index].is_rotated());
+
+        let index = texture_atlas.get_texture_index(&tall_handle).unwrap();
+        let rect = texture_atlas.textures[index];
+        assert_eq!(rect.max - rect.min, Vec2::new(3.0, 2.0));
+        assert_eq!(
+            texture_atlas.layouts[index],
+            AtlasTextureLayout::new(Vec2::zero(), Vec2::zero(), true)
+        );
+        // the top left corner of the texture is stored in the top right corner of its rect
+        let (x, y) = (rect.min.x as u32, rect.min.y as u32);
+        assert_eq!(atlas_texture.get_pixel(x + 2, y), Some(&[0, 0, 1, 255][..]));
+        assert_eq!(atlas_texture.get_pixel(x, y), Some(&[0, 2, 1, 255][..]));
+        assert_eq!(atlas_texture.get_pixel(x, y + 1), Some(&[1, 2, 1, 255][..]));
+    }
+
+    #[test]
+    fn extrude_edges_and_pad() {
+        let app = setup();
+        let mut textures = app.resources.get_mut::<Assets<Texture>>().unwrap();
+        let (first, second) = (texture(2, 2, 0), texture(3, 1, 0));
+        let first_handle = textures.add(first.clone());
+        let second_handle = textures.add(second.clone());
+
+        let mut builder = TextureAtlasBuilder::default().extrusion(2).padding(1);
+        builder.add_texture(first_handle.clone(), &first);
+        builder.add_texture(second_handle.clone(), &second);
+        let texture_atlas = builder.finish(&mut textures).unwrap();
+        let atlas_texture = textures.get(&texture_atlas.texture).unwrap();
+
+        let index = texture_atlas.get_texture_index(&first_handle).unwrap();
+        let rect = texture_atlas.textures[index];
+        assert_eq!(rect.max - rect.min, Vec2::new(2.0, 2.0));
+        let (x, y) = (rect.min.x as u32, rect.min.y as u32);
+        // edges are duplicated, and corners fill the corners of the extrusion
+        assert_eq!(
+            atlas_texture.get_pixel(x - 2, y + 1),
+            Some(&[0, 1, 1, 255][..])
+        );
+        assert_eq!(
+            atlas_texture.get_pixel(x + 3, y - 1),
+            Some(&[1, 0, 1, 255][..])
+        );
+        assert_eq!(
+            atlas_texture.get_pixel(x - 2, y - 2),
+            Some(&[0, 0, 1, 255][..])
+        );
+        assert_eq!(
+            atlas_texture.get_pixel(x + 3, y + 3),
+            Some(&[1, 1, 1, 255][..])
+        );
+
+        // textures are 2 * 2 + 1 pixels apart
+        let other_rect = texture_atlas.textures[1 - index];
+        let gap = if other_rect.min.x >= rect.max.x {
+            other_rect.min.x - rect.max.x
+        } else if rect.min.x >= other_rect.max.x {
+            rect.min.x - other_rect.max.x
+        } else if other_rect.min.y >= rect.max.y {
+            other_rect.min.y - rect.max.y
+        } else {
+            rect.min.y - other_rect.max.y
+        };
+        assert!(gap >= 5.0);
+    }
+}
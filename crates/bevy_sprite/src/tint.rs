@@ -0,0 +1,132 @@
+use bevy_app::prelude::*;
+use bevy_core::Time;
+use bevy_ecs::{IntoSystem, Res, ResMut};
+use bevy_render::color::Color;
+
+/// A color that every sprite is multiplied with when it is drawn, so the lighting mood of a scene
+/// can change without touching its sprites or re-baking tile textures
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlobalTint {
+    pub color: Color,
+}
+
+impl Default for GlobalTint {
+    fn default() -> Self {
+        GlobalTint {
+            color: Color::WHITE,
+        }
+    }
+}
+
+/// Drives the [GlobalTint] through a day, blending between tints at times of day
+#[derive(Debug, Clone)]
+pub struct DayNightCycle {
+    /// The time of day, from 0.0 at midnight to 1.0 at the next midnight
+    pub time_of_day: f32,
+    /// How many seconds a day lasts
+    pub day_length: f32,
+    pub paused: bool,
+    /// The tints at times of day, sorted by time. The tint is blended between them, wrapping
+    /// around midnight.
+    pub keyframes: Vec<(f32, Color)>,
+}
+
+impl Default for DayNightCycle {
+    fn default() -> Self {
+        DayNightCycle {
+            time_of_day: 0.5,
+            day_length: 600.0,
+            paused: false,
+            keyframes: vec![
+                (0.0, Color::rgb_linear(0.15, 0.18, 0.35)),
+                (0.25, Color::rgb_linear(0.9, 0.65, 0.55)),
+                (0.5, Color::WHITE),
+                (0.75, Color::rgb_linear(0.85, 0.55, 0.45)),
+            ],
+        }
+    }
+}
+
+impl DayNightCycle {
+    /// The tint at a time of day. Returns white if there are no keyframes.
+    pub fn tint_at(&self, time_of_day: f32) -> Color {
+        let (first, last) = match (self.keyframes.first(), self.keyframes.last()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => return Color::WHITE,
+        };
+        let time_of_day = time_of_day.rem_euclid(1.0);
+        let next = self
+            .keyframes
+            .iter()
+            .position(|(time, _)| *time > time_of_day);
+        let (from, to) = match next {
+            // before the first keyframe, blend from the last keyframe of the previous day
+            Some(0) => ((last.0 - 1.0, last.1), first),
+            // after the last keyframe, blend to the first keyframe of the next day
+            None => (last, (first.0 + 1.0, first.1)),
+            Some(next) => (self.keyframes[next - 1], self.keyframes[next]),
+        };
+        if to.0 <= from.0 {
+            return from.1;
+        }
+        from.1.lerp(to.1, (time_of_day - from.0) / (to.0 - from.0))
+    }
+}
+
+/// Advances the [DayNightCycle] and sets the [GlobalTint] to the tint at the time of day
+pub fn day_night_cycle_system(
+    time: Res<Time>,
+    mut cycle: ResMut<DayNightCycle>,
+    mut global_tint: ResMut<GlobalTint>,
+) {
+    if !cycle.paused && cycle.day_length > 0.0 {
+        cycle.time_of_day = (cycle.time_of_day + time.delta_seconds() / cycle.day_length) % 1.0;
+    }
+    let color = cycle.tint_at(cycle.time_of_day);
+    // only write changes, so a paused cycle doesn't re-upload the tint every frame
+    if global_tint.color != color {
+        global_tint.color = color;
+    }
+}
+
+/// Adds a [DayNightCycle] that tints sprites through the day
+#[derive(Default)]
+pub struct DayNightCyclePlugin;
+
+impl Plugin for DayNightCyclePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<DayNightCycle>()
+            .add_system(day_night_cycle_system.system());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blend_keyframes() {
+        let cycle = DayNightCycle {
+            keyframes: vec![(0.25, Color::BLACK), (0.75, Color::WHITE)],
+            ..Default::default()
+        };
+        assert_eq!(cycle.tint_at(0.25), Color::BLACK);
+        assert_eq!(cycle.tint_at(0.5), Color::rgb_linear(0.5, 0.5, 0.5));
+        assert_eq!(cycle.tint_at(0.75), Color::WHITE);
+        // wraps around midnight
+        assert_eq!(cycle.tint_at(0.0), Color::rgb_linear(0.5, 0.5, 0.5));
+        assert_eq!(cycle.tint_at(1.0), Color::rgb_linear(0.5, 0.5, 0.5));
+        assert_eq!(cycle.tint_at(0.875), Color::rgb_linear(0.75, 0.75, 0.75));
+
+        let constant = DayNightCycle {
+            keyframes: vec![(0.5, Color::RED)],
+            ..Default::default()
+        };
+        assert_eq!(constant.tint_at(0.1), Color::RED);
+        let empty = DayNightCycle {
+            keyframes: Vec::new(),
+            ..Default::default()
+        };
+        assert_eq!(empty.tint_at(0.1), Color::WHITE);
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{render::WEATHER_OVERLAY_PIPELINE_HANDLE, QUAD_HANDLE};
+use bevy_asset::Handle;
+use bevy_core::Time;
+use bevy_ecs::{Bundle, Entity, Query, QuerySet, Res};
+use bevy_math::Vec2;
+use bevy_render::{
+    camera::OrthographicProjection,
+    color::Color,
+    mesh::Mesh,
+    pipeline::{RenderPipeline, RenderPipelines},
+    prelude::{Draw, Visible},
+    render_graph::base::MainPass,
+    renderer::RenderResources,
+    shader::{ShaderDefIterator, ShaderDefs},
+};
+use bevy_transform::components::{GlobalTransform, Transform};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeatherKind {
+    Rain,
+    Snow,
+}
+
+/// Draws animated weather, like rain or snow, over everything a 2d camera shows.
+///
+/// The overlay is a single quad that covers the view of its camera. The effect is generated in
+/// its shader from world positions, so it needs no entities per tile or particle, and it stays
+/// in place in the world as the camera moves.
+#[derive(Debug, Clone, RenderResources)]
+pub struct WeatherOverlay {
+    /// The camera whose view the overlay covers. The camera must not be rotated.
+    #[render_resources(ignore)]
+    pub camera: Entity,
+    #[render_resources(ignore)]
+    pub kind: WeatherKind,
+    pub color: Color,
+    /// The size in world units of the grid cells that each hold at most one drop or flake
+    pub cell_size: f32,
+    /// The length of drops or the diameter of flakes in world units. Should be well below
+    /// `cell_size`.
+    pub particle_size: f32,
+    /// The fraction of cells that hold a drop or flake, from 0.0 to 1.0
+    pub density: f32,
+    /// How fast drops or flakes fall, in world units per second
+    pub fall_speed: f32,
+    /// How fast drops or flakes drift to the right, in world units per second
+    pub wind: f32,
+    /// How long the effect has been running. Advanced by [weather_overlay_system].
+    pub time: f32,
+}
+
+impl WeatherOverlay {
+    pub fn rain(camera: Entity) -> Self {
+        WeatherOverlay {
+            camera,
+            kind: WeatherKind::Rain,
+            color: Color::rgba_linear(0.6, 0.65, 0.8, 0.6),
+            cell_size: 24.0,
+            particle_size: 10.0,
+            density: 0.3,
+            fall_speed: 600.0,
+            wind: 60.0,
+            time: 0.0,
+        }
+    }
+
+    pub fn snow(camera: Entity) -> Self {
+        WeatherOverlay {
+            camera,
+            kind: WeatherKind::Snow,
+            color: Color::rgba_linear(1.0, 1.0, 1.0, 0.9),
+            cell_size: 32.0,
+            particle_size: 4.0,
+            density: 0.4,
+            fall_speed: 40.0,
+            wind: 10.0,
+            time: 0.0,
+        }
+    }
+}
+
+impl ShaderDefs for WeatherOverlay {
+    fn shader_defs_len(&self) -> usize {
+        1
+    }
+
+    fn get_shader_def(&self, index: usize) -> Option<&str> {
+        match (index, self.kind) {
+            (0, WeatherKind::Snow) => Some("WEATHEROVERLAY_SNOW"),
+            _ => None,
+        }
+    }
+
+    fn iter_shader_defs(&self) -> ShaderDefIterator<'_> {
+        ShaderDefIterator::new(self)
+    }
+}
+
+#[derive(Bundle)]
+pub struct WeatherOverlayBundle {
+    pub overlay: WeatherOverlay,
+    pub mesh: Handle<Mesh>,
+    pub main_pass: MainPass,
+    pub draw: Draw,
+    pub visible: Visible,
+    pub render_pipelines: RenderPipelines,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+}
+
+impl WeatherOverlayBundle {
+    pub fn new(overlay: WeatherOverlay) -> Self {
+        WeatherOverlayBundle {
+            overlay,
+            mesh: QUAD_HANDLE.typed(),
+            main_pass: MainPass,
+            draw: Default::default(),
+            visible: Visible {
+                is_transparent: true,
+                ..Default::default()
+            },
+            render_pipelines: RenderPipelines::from_pipelines(vec![RenderPipeline::new(
+                WEATHER_OVERLAY_PIPELINE_HANDLE.typed(),
+            )]),
+            transform: Default::default(),
+            global_transform: Default::default(),
+        }
+    }
+}
+
+/// Advances the animation of [WeatherOverlay]s and moves them to cover the view of their cameras
+pub fn weather_overlay_system(
+    time: Res<Time>,
+    mut overlays: Query<(Entity, &mut WeatherOverlay)>,
+    mut transforms: QuerySet<(
+        Query<&GlobalTransform>,
+        Query<(&mut Transform, &mut GlobalTransform)>,
+    )>,
+    projections: Query<&OrthographicProjection>,
+) {
+    for (entity, mut overlay) in overlays.iter_mut() {
+        overlay.time += time.delta_seconds();
+        let (camera, projection) = match (
+            transforms.q0().get(overlay.camera),
+            projections.get(overlay.camera),
+        ) {
+            (Ok(camera), Ok(projection)) => (*camera, projection),
+            _ => continue,
+        };
+        let scale = camera.scale.truncate();
+        let min = Vec2::new(projection.left, projection.bottom) * scale;
+        let max = Vec2::new(projection.right, projection.top) * scale;
+        let center = camera.translation.truncate() + (min + max) / 2.0;
+        // just in front of the near plane, so the overlay is drawn after everything else
+        let translation = center.extend(camera.translation.z - projection.near - 0.01);
+        let size = (max - min).extend(1.0);
+
+        if let Ok((mut transform, mut global_transform)) = transforms.q1_mut().get_mut(entity) {
+            if transform.translation != translation || transform.scale != size {
+                transform.translation = translation;
+                transform.scale = size;
+                global_transform.translation = translation;
+                global_transform.scale = size;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::{IntoSystem, Resources, Stage, SystemStage, World};
+    use bevy_math::Vec3;
+
+    #[test]
+    fn cover_camera_view() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(Time::default());
+        let projection = OrthographicProjection {
+            left: -50.0,
+            right: 50.0,
+            bottom: -30.0,
+            top: 30.0,
+            ..Default::default()
+        };
+        let camera_transform = GlobalTransform {
+            translation: Vec3::new(100.0, -20.0, 999.9),
+            scale: Vec3::new(2.0, 2.0, 1.0),
+            ..Default::default()
+        };
+        let camera = world.spawn((camera_transform, projection));
+        let overlay = world.spawn(WeatherOverlayBundle::new(WeatherOverlay::snow(camera)));
+
+        let mut stage = SystemStage::serial();
+        stage.add_system(weather_overlay_system.system());
+        stage.initialize(&mut world, &mut resources);
+        stage.run(&mut world, &mut resources);
+
+        let transform = *world.get::<GlobalTransform>(overlay).unwrap();
+        assert_eq!(transform.translation.truncate(), Vec2::new(100.0, -20.0));
+        assert!(transform.translation.z < 999.9);
+        assert_eq!(transform.scale, Vec3::new(200.0, 120.0, 1.0));
+        assert_eq!(
+            world.get::<Transform>(overlay).unwrap().scale,
+            transform.scale
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use bevy_ecs::{Changed, Query};
+use bevy_reflect::{Reflect, ReflectComponent};
+use bevy_transform::components::GlobalTransform;
+
+/// Draws an entity in front of the entities above it and behind the entities below it, like
+/// characters and props of a top-down world, by deriving its rendered z from its y.
+///
+/// Like [PixelSnap](crate::PixelSnap), only the [GlobalTransform] the entity is drawn with is
+/// changed, after transforms are propagated. The z of its
+/// [Transform](bevy_transform::components::Transform) stays the layer it is sorted in, so entities
+/// on a higher layer, like roofs, are still drawn over every entity of a lower one, as long as the
+/// layers are further apart than the z range the height of the world is sorted into.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct YSort {
+    /// How much the z of the entity drops for each world unit it is further up. The default
+    /// sorts 10000 units of height into one unit of z.
+    pub z_per_unit: f32,
+    /// The offset from the position of the entity to the point it is sorted by, like the feet of
+    /// a character whose sprite is centered on its position
+    pub y_offset: f32,
+    /// The y and z the entity was last sorted to, so sorting again without moving does nothing
+    #[reflect(ignore)]
+    sorted: Option<(f32, f32)>,
+}
+
+impl Default for YSort {
+    fn default() -> Self {
+        YSort {
+            z_per_unit: 0.0001,
+            y_offset: 0.0,
+            sorted: None,
+        }
+    }
+}
+
+impl YSort {
+    /// Sorts by the point `y_offset` away from the position of the entity
+    pub fn with_y_offset(y_offset: f32) -> Self {
+        YSort {
+            y_offset,
+            ..Default::default()
+        }
+    }
+
+    /// The z of an entity at `y` on layer `z`
+    pub fn sorted_z(&self, y: f32, z: f32) -> f32 {
+        z - (y + self.y_offset) * self.z_per_unit
+    }
+}
+
+/// Sorts the global transforms of [YSort] entities that moved
+pub fn y_sort_system(
+    mut query: Query<(&mut YSort, &mut GlobalTransform), Changed<GlobalTransform>>,
+) {
+    for (mut y_sort, mut global_transform) in query.iter_mut() {
+        let translation = global_transform.translation;
+        // the global transform changes without being propagated again when other systems write
+        // to it, in which case it is already sorted
+        if y_sort.sorted == Some((translation.y, translation.z)) {
+            continue;
+        }
+        let z = y_sort.sorted_z(translation.y, translation.z);
+        y_sort.sorted = Some((translation.y, z));
+        if z != translation.z {
+            global_transform.translation.z = z;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::{IntoSystem, Resources, Stage, SystemStage, World};
+    use bevy_math::Vec3;
+
+    #[test]
+    fn sort_by_y() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        let character = world.spawn((
+            YSort::default(),
+            GlobalTransform::from_translation(Vec3::new(5.0, 100.0, 1.0)),
+        ));
+        let tree = world.spawn((
+            YSort::with_y_offset(-50.0),
+            GlobalTransform::from_translation(Vec3::new(0.0, 160.0, 1.0)),
+        ));
+
+        let mut stage = SystemStage::serial();
+        stage.add_system(y_sort_system.system());
+        stage.initialize(&mut world, &mut resources);
+        stage.run(&mut world, &mut resources);
+
+        let z = |world: &World, entity| world.get::<GlobalTransform>(entity).unwrap().translation.z;
+        assert_eq!(z(&world, character), 1.0 - 100.0 * 0.0001);
+        assert_eq!(z(&world, tree), 1.0 - 110.0 * 0.0001);
+        // the character is lower than the base of the tree, so it's in front
+        assert!(z(&world, character) > z(&world, tree));
+
+        // sorting an entity that didn't move again doesn't change its z
+        world.clear_trackers();
+        world
+            .get_mut::<GlobalTransform>(character)
+            .unwrap()
+            .translation
+            .x = 6.0;
+        stage.run(&mut world, &mut resources);
+        assert_eq!(z(&world, character), 1.0 - 100.0 * 0.0001);
+    }
+}
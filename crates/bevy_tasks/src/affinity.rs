@@ -0,0 +1,16 @@
+/// Pins the current thread to the given logical core. Returns false if the thread could not be
+/// pinned, or if pinning is not supported on this platform.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn pin_current_thread(core_id: usize) -> bool {
+    // SAFE: cpu_set_t is a plain bit set, and sched_setaffinity only reads it
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_SET(core_id, &mut set);
+        libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) == 0
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub(crate) fn pin_current_thread(_core_id: usize) -> bool {
+    false
+}
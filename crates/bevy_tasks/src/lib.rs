@@ -4,6 +4,11 @@ pub use slice::{ParallelSlice, ParallelSliceMut};
 mod task;
 pub use task::Task;
 
+mod stats;
+pub use stats::{TaskPoolStats, TASK_DURATION_BUCKETS};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod affinity;
 #[cfg(not(target_arch = "wasm32"))]
 mod task_pool;
 #[cfg(not(target_arch = "wasm32"))]
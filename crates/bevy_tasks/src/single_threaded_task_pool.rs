@@ -26,6 +26,10 @@ impl TaskPoolBuilder {
         self
     }
 
+    pub fn core_ids(self, _core_ids: Vec<usize>) -> Self {
+        self
+    }
+
     pub fn build(self) -> TaskPool {
         TaskPool::new_internal()
     }
@@ -52,6 +56,16 @@ impl TaskPool {
         1
     }
 
+    /// Tasks are not tracked on the main thread, so this always returns empty statistics
+    pub fn stats(&self) -> crate::TaskPoolStats {
+        crate::TaskPoolStats::default()
+    }
+
+    /// Spawned tasks are driven by the browser, so there are never any tasks to poll here
+    pub fn try_tick(&self) -> bool {
+        false
+    }
+
     /// Allows spawning non-`static futures on the thread pool. The function takes a callback,
     /// passing a scope object into it. The scope object provided to the callback can be used
     /// to spawn tasks. This function will await the completion of all tasks before returning.
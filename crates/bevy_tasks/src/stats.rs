@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+/// The number of buckets in [TaskPoolStats::duration_histogram]
+pub const TASK_DURATION_BUCKETS: usize = 24;
+
+/// A snapshot of the tasks of a [TaskPool](crate::TaskPool), returned by
+/// [TaskPool::stats](crate::TaskPool::stats)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaskPoolStats {
+    /// The number of tasks that have been spawned but not polled yet
+    pub queued: usize,
+    /// The number of tasks that have been polled at least once but have not completed yet
+    pub running: usize,
+    /// The number of tasks that have completed since the pool was created
+    pub completed: u64,
+    /// The sum of the durations of all completed tasks, from their first poll to their completion
+    pub total_duration: Duration,
+    /// The number of completed tasks in each duration range. See
+    /// [TaskPoolStats::duration_bucket].
+    pub duration_histogram: [u64; TASK_DURATION_BUCKETS],
+}
+
+impl TaskPoolStats {
+    /// The index of the [TaskPoolStats::duration_histogram] bucket for tasks that took `duration`.
+    /// Bucket 0 holds tasks that took less than a microsecond, and bucket `i` holds tasks that took
+    /// between `2^(i-1)` and `2^i` microseconds. The last bucket also holds all longer tasks.
+    pub fn duration_bucket(duration: Duration) -> usize {
+        let micros = duration.as_micros().min(u64::MAX as u128) as u64;
+        let bucket = (64 - micros.leading_zeros()) as usize;
+        bucket.min(TASK_DURATION_BUCKETS - 1)
+    }
+
+    /// The average duration of the tasks that completed since `previous` was taken, or `None` if
+    /// none did
+    pub fn average_duration_since(&self, previous: &TaskPoolStats) -> Option<Duration> {
+        let completed = self.completed.checked_sub(previous.completed)?;
+        if completed == 0 {
+            return None;
+        }
+
+        let total = self.total_duration.checked_sub(previous.total_duration)?;
+        Some(total / completed as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duration_bucket() {
+        assert_eq!(TaskPoolStats::duration_bucket(Duration::from_nanos(500)), 0);
+        assert_eq!(TaskPoolStats::duration_bucket(Duration::from_micros(1)), 1);
+        assert_eq!(TaskPoolStats::duration_bucket(Duration::from_micros(3)), 2);
+        assert_eq!(TaskPoolStats::duration_bucket(Duration::from_micros(4)), 3);
+        assert_eq!(
+            TaskPoolStats::duration_bucket(Duration::from_secs(3600)),
+            TASK_DURATION_BUCKETS - 1
+        );
+    }
+}
@@ -2,13 +2,18 @@ use std::{
     future::Future,
     mem,
     pin::Pin,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
     thread::{self, JoinHandle},
+    time::Duration,
 };
 
 use futures_lite::{future, pin};
+use instant::Instant;
 
-use crate::Task;
+use crate::{Task, TaskPoolStats, TASK_DURATION_BUCKETS};
 
 /// Used to create a TaskPool
 #[derive(Debug, Default, Clone)]
@@ -21,6 +26,8 @@ pub struct TaskPoolBuilder {
     /// Allows customizing the name of the threads - helpful for debugging. If set, threads will
     /// be named <thread_name> (<thread_index>), i.e. "MyThreadPool (2)"
     thread_name: Option<String>,
+    /// If set, each thread will be pinned to one of these logical cores
+    core_ids: Option<Vec<usize>>,
 }
 
 impl TaskPoolBuilder {
@@ -30,7 +37,8 @@ impl TaskPoolBuilder {
     }
 
     /// Override the number of threads created for the pool. If unset, we default to the number
-    /// of logical cores of the system
+    /// of logical cores of the system. If set to 0, tasks only run on threads that call
+    /// [TaskPool::scope] or [TaskPool::try_tick].
     pub fn num_threads(mut self, num_threads: usize) -> Self {
         self.num_threads = Some(num_threads);
         self
@@ -49,12 +57,21 @@ impl TaskPoolBuilder {
         self
     }
 
+    /// Pin the threads created for the pool to the given logical cores. Thread `i` is pinned to
+    /// `core_ids[i % core_ids.len()]`. This is only supported on Linux and Android, and is
+    /// ignored on other platforms.
+    pub fn core_ids(mut self, core_ids: Vec<usize>) -> Self {
+        self.core_ids = Some(core_ids);
+        self
+    }
+
     /// Creates a new ThreadPoolBuilder based on the current options.
     pub fn build(self) -> TaskPool {
         TaskPool::new_internal(
             self.num_threads,
             self.stack_size,
             self.thread_name.as_deref(),
+            self.core_ids.as_deref(),
         )
     }
 }
@@ -77,6 +94,93 @@ impl Drop for TaskPoolInner {
     }
 }
 
+/// Counts the tasks of a pool as they move from queued to running to completed
+#[derive(Debug, Default)]
+struct TaskCounters {
+    queued: AtomicUsize,
+    running: AtomicUsize,
+    completed: AtomicU64,
+    total_duration_nanos: AtomicU64,
+    duration_histogram: [AtomicU64; TASK_DURATION_BUCKETS],
+}
+
+impl TaskCounters {
+    fn stats(&self) -> TaskPoolStats {
+        let mut duration_histogram = [0; TASK_DURATION_BUCKETS];
+        for (count, counter) in duration_histogram
+            .iter_mut()
+            .zip(self.duration_histogram.iter())
+        {
+            *count = counter.load(Ordering::Relaxed);
+        }
+
+        TaskPoolStats {
+            queued: self.queued.load(Ordering::Relaxed),
+            running: self.running.load(Ordering::Relaxed),
+            completed: self.completed.load(Ordering::Relaxed),
+            total_duration: Duration::from_nanos(self.total_duration_nanos.load(Ordering::Relaxed)),
+            duration_histogram,
+        }
+    }
+
+    /// Wraps `future` so it updates the counters when it is first polled, when it completes, and
+    /// when it is dropped early
+    fn instrument<T>(self: &Arc<Self>, future: impl Future<Output = T>) -> impl Future<Output = T> {
+        self.queued.fetch_add(1, Ordering::Relaxed);
+        // created outside of the async block so the task is counted even if it's never polled
+        let mut guard = TaskGuard {
+            counters: self.clone(),
+            started: None,
+            finished: false,
+        };
+        async move {
+            guard.counters.queued.fetch_sub(1, Ordering::Relaxed);
+            guard.counters.running.fetch_add(1, Ordering::Relaxed);
+            guard.started = Some(Instant::now());
+            let output = future.await;
+            guard.finish();
+            output
+        }
+    }
+}
+
+struct TaskGuard {
+    counters: Arc<TaskCounters>,
+    started: Option<Instant>,
+    finished: bool,
+}
+
+impl TaskGuard {
+    fn finish(&mut self) {
+        let duration = self
+            .started
+            .map(|started| started.elapsed())
+            .unwrap_or_default();
+        self.counters.running.fetch_sub(1, Ordering::Relaxed);
+        self.counters.completed.fetch_add(1, Ordering::Relaxed);
+        self.counters
+            .total_duration_nanos
+            .fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+        self.counters.duration_histogram[TaskPoolStats::duration_bucket(duration)]
+            .fetch_add(1, Ordering::Relaxed);
+        self.finished = true;
+    }
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        // cancelled tasks are not counted as completed
+        if self.finished {
+            return;
+        }
+        if self.started.is_some() {
+            self.counters.running.fetch_sub(1, Ordering::Relaxed);
+        } else {
+            self.counters.queued.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+}
+
 /// A thread pool for executing tasks. Tasks are futures that are being automatically driven by
 /// the pool on threads owned by the pool.
 #[derive(Debug, Clone)]
@@ -90,6 +194,8 @@ pub struct TaskPool {
 
     /// Inner state of the pool
     inner: Arc<TaskPoolInner>,
+
+    counters: Arc<TaskCounters>,
 }
 
 impl TaskPool {
@@ -102,6 +208,7 @@ impl TaskPool {
         num_threads: Option<usize>,
         stack_size: Option<usize>,
         thread_name: Option<&str>,
+        core_ids: Option<&[usize]>,
     ) -> Self {
         let (shutdown_tx, shutdown_rx) = async_channel::unbounded::<()>();
 
@@ -120,6 +227,10 @@ impl TaskPool {
                     format!("TaskPool ({})", i)
                 };
 
+                let core_id = core_ids
+                    .filter(|core_ids| !core_ids.is_empty())
+                    .map(|core_ids| core_ids[i % core_ids.len()]);
+
                 let mut thread_builder = thread::Builder::new().name(thread_name);
 
                 if let Some(stack_size) = stack_size {
@@ -128,6 +239,10 @@ impl TaskPool {
 
                 thread_builder
                     .spawn(move || {
+                        if let Some(core_id) = core_id {
+                            // pinning is best effort, so the thread keeps running if it fails
+                            crate::affinity::pin_current_thread(core_id);
+                        }
                         let shutdown_future = ex.run(shutdown_rx.recv());
                         // Use unwrap_err because we expect a Closed error
                         future::block_on(shutdown_future).unwrap_err();
@@ -142,6 +257,7 @@ impl TaskPool {
                 threads,
                 shutdown_tx,
             }),
+            counters: Default::default(),
         }
     }
 
@@ -150,6 +266,12 @@ impl TaskPool {
         self.inner.threads.len()
     }
 
+    /// Returns the number of queued, running and completed tasks, and how long completed tasks
+    /// took. Tasks spawned by [TaskPool::scope] are included.
+    pub fn stats(&self) -> TaskPoolStats {
+        self.counters.stats()
+    }
+
     /// Allows spawning non-`static futures on the thread pool. The function takes a callback,
     /// passing a scope object into it. The scope object provided to the callback can be used
     /// to spawn tasks. This function will await the completion of all tasks before returning.
@@ -169,6 +291,7 @@ impl TaskPool {
 
         let mut scope = Scope {
             executor,
+            counters: self.counters.clone(),
             spawned: Vec::new(),
         };
 
@@ -176,7 +299,7 @@ impl TaskPool {
 
         if scope.spawned.is_empty() {
             Vec::default()
-        } else if scope.spawned.len() == 1 {
+        } else if scope.spawned.len() == 1 && self.thread_num() > 0 {
             vec![future::block_on(&mut scope.spawned[0])]
         } else {
             let fut = async move {
@@ -214,6 +337,13 @@ impl TaskPool {
         }
     }
 
+    /// Polls one task of the pool on the calling thread, if any are ready to make progress. Returns
+    /// true if a task was polled. Pools with no threads only make progress when this or
+    /// [TaskPool::scope] is called.
+    pub fn try_tick(&self) -> bool {
+        self.executor.try_tick()
+    }
+
     /// Spawns a static future onto the thread pool. The returned Task is a future. It can also be
     /// cancelled and "detached" allowing it to continue running without having to be polled by the
     /// end-user.
@@ -221,7 +351,7 @@ impl TaskPool {
     where
         T: Send + 'static,
     {
-        Task::new(self.executor.spawn(future))
+        Task::new(self.executor.spawn(self.counters.instrument(future)))
     }
 }
 
@@ -234,12 +364,13 @@ impl Default for TaskPool {
 #[derive(Debug)]
 pub struct Scope<'scope, T> {
     executor: &'scope async_executor::Executor<'scope>,
+    counters: Arc<TaskCounters>,
     spawned: Vec<async_executor::Task<T>>,
 }
 
 impl<'scope, T: Send + 'scope> Scope<'scope, T> {
     pub fn spawn<Fut: Future<Output = T> + 'scope + Send>(&mut self, f: Fut) {
-        let task = self.executor.spawn(f);
+        let task = self.executor.spawn(self.counters.instrument(f));
         self.spawned.push(task);
     }
 }
@@ -279,4 +410,52 @@ mod tests {
         assert_eq!(outputs.len(), 100);
         assert_eq!(count.load(Ordering::Relaxed), 100);
     }
+
+    #[test]
+    pub fn test_no_threads() {
+        let pool = TaskPoolBuilder::new().num_threads(0).build();
+        assert_eq!(pool.thread_num(), 0);
+
+        let outputs = pool.scope(|scope| {
+            scope.spawn(async { 1 });
+        });
+        assert_eq!(outputs, vec![1]);
+
+        let count = Arc::new(AtomicI32::new(0));
+        let task_count = count.clone();
+        pool.spawn(async move {
+            task_count.fetch_add(1, Ordering::Relaxed);
+        })
+        .detach();
+        // detached tasks only run when the pool is ticked
+        assert_eq!(count.load(Ordering::Relaxed), 0);
+        while pool.try_tick() {}
+        assert_eq!(count.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    pub fn test_stats() {
+        let pool = TaskPoolBuilder::new().num_threads(0).build();
+        pool.scope(|scope| {
+            for _ in 0..10 {
+                scope.spawn(async {});
+            }
+        });
+
+        let stats = pool.stats();
+        assert_eq!(stats.completed, 10);
+        assert_eq!(stats.duration_histogram.iter().sum::<u64>(), 10);
+
+        let detached = pool.spawn(async {});
+        let cancelled = pool.spawn(async {});
+        detached.detach();
+        assert_eq!(pool.stats().queued, 2);
+        drop(cancelled);
+        while pool.try_tick() {}
+
+        let stats = pool.stats();
+        assert_eq!(stats.queued, 0);
+        assert_eq!(stats.running, 0);
+        assert_eq!(stats.completed, 11);
+    }
 }
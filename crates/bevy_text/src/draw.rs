@@ -78,6 +78,8 @@ impl<'a> Drawable for DrawableText<'a> {
         } else {
             println!("Could not find vertex buffer for `bevy_sprite::QUAD_HANDLE`.")
         }
+        // the sprite sheet shader reads vertex colors, which the quad doesn't have
+        context.set_vertex_buffers_from_bindings(draw, &[self.render_resource_bindings])?;
 
         let mut indices = 0..0;
         if let Some(RenderResourceId::Buffer(quad_index_buffer)) = render_resource_context
@@ -121,3 +123,158 @@ impl<'a> Drawable for DrawableText<'a> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GlyphAtlasInfo, PositionedGlyph};
+    use bevy_app::App;
+    use bevy_asset::{AddAsset, AssetPlugin, AssetServer, Assets, FileAssetIo, Handle};
+    use bevy_ecs::{IntoSystem, Query, Res, ResMut, Stage, SystemStage};
+    use bevy_math::Vec2;
+    use bevy_reflect::ReflectPlugin;
+    use bevy_render::{
+        draw::RenderCommand,
+        mesh::shape,
+        pipeline::{PipelineCompiler, PipelineDescriptor},
+        renderer::{
+            AssetRenderResourceBindings, BufferInfo, BufferUsage, HeadlessRenderResourceContext,
+            RenderResourceContext, SharedBuffers,
+        },
+        shader::Shader,
+    };
+    use bevy_sprite::{build_sprite_sheet_pipeline, TextureAtlas};
+    use bevy_tasks::TaskPool;
+
+    fn draw_text(
+        mut context: DrawContext,
+        mut render_resource_bindings: ResMut<RenderResourceBindings>,
+        msaa: Res<Msaa>,
+        mut query: Query<(&mut Draw, &Vec<PositionedGlyph>)>,
+    ) {
+        let font_quad_vertex_descriptor =
+            Mesh::from(shape::Quad::new(Vec2::new(1.0, 1.0))).get_vertex_buffer_descriptor();
+        for (mut draw, text_glyphs) in query.iter_mut() {
+            DrawableText {
+                render_resource_bindings: &mut render_resource_bindings,
+                position: Vec3::zero(),
+                style: &TextStyle::default(),
+                text_glyphs,
+                msaa: &msaa,
+                font_quad_vertex_descriptor: &font_quad_vertex_descriptor,
+            }
+            .draw(&mut draw, &mut context)
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn draw_text_with_every_vertex_buffer() {
+        let asset_server = AssetServer::new(FileAssetIo::new(""), TaskPool::new());
+        let mut app = App::build();
+        app.add_resource(asset_server)
+            .add_plugin(ReflectPlugin)
+            .add_plugin(AssetPlugin)
+            .add_asset::<Shader>()
+            .add_asset::<PipelineDescriptor>()
+            .add_asset::<TextureAtlas>();
+        let mut app = app.app;
+        let resources = &mut app.resources;
+
+        let render_resource_context: Box<dyn RenderResourceContext> =
+            Box::new(HeadlessRenderResourceContext::default());
+        let quad = bevy_sprite::QUAD_HANDLE.typed::<Mesh>();
+        let vertex_buffer = render_resource_context.create_buffer(BufferInfo {
+            size: 4 * 32,
+            buffer_usage: BufferUsage::VERTEX,
+            ..Default::default()
+        });
+        let index_buffer = render_resource_context.create_buffer(BufferInfo {
+            size: 6 * 4,
+            buffer_usage: BufferUsage::INDEX,
+            ..Default::default()
+        });
+        render_resource_context.set_asset_resource(
+            &quad,
+            RenderResourceId::Buffer(vertex_buffer),
+            mesh::VERTEX_ATTRIBUTE_BUFFER_ID,
+        );
+        render_resource_context.set_asset_resource(
+            &quad,
+            RenderResourceId::Buffer(index_buffer),
+            mesh::INDEX_BUFFER_ASSET_INDEX,
+        );
+        // like mesh_resource_provider_system does
+        let mut render_resource_bindings = RenderResourceBindings::default();
+        render_resource_bindings.vertex_fallback_buffer =
+            Some(render_resource_context.create_buffer(BufferInfo {
+                size: 32,
+                buffer_usage: BufferUsage::VERTEX,
+                ..Default::default()
+            }));
+        resources.insert(render_resource_bindings);
+        resources.insert(render_resource_context);
+
+        let pipeline = {
+            let mut shaders = resources.get_mut::<Assets<Shader>>().unwrap();
+            let pipeline = build_sprite_sheet_pipeline(&mut shaders);
+            // the headless context doesn't compile shaders, which reflecting their layout needs
+            let stages = &pipeline.shader_stages;
+            for handle in std::iter::once(&stages.vertex).chain(stages.fragment.as_ref()) {
+                let shader = shaders.get(handle).unwrap().get_spirv_shader(None).unwrap();
+                shaders.set_untracked(handle, shader);
+            }
+            pipeline
+        };
+        resources
+            .get_mut::<Assets<PipelineDescriptor>>()
+            .unwrap()
+            .set_untracked(bevy_sprite::SPRITE_SHEET_PIPELINE_HANDLE, pipeline);
+        resources.insert(PipelineCompiler::default());
+        resources.insert(SharedBuffers::new(4096));
+        resources.insert(Msaa::default());
+        let texture_atlas = Handle::<TextureAtlas>::default();
+        let mut asset_render_resource_bindings = AssetRenderResourceBindings::default();
+        asset_render_resource_bindings.get_or_insert_mut(&texture_atlas);
+        resources.insert(asset_render_resource_bindings);
+
+        let text = app.world.spawn((
+            Draw::default(),
+            vec![PositionedGlyph {
+                position: Vec2::zero(),
+                atlas_info: GlyphAtlasInfo {
+                    texture_atlas,
+                    glyph_index: 0,
+                },
+            }],
+        ));
+        let mut stage = SystemStage::serial();
+        stage.add_system(draw_text.system());
+        stage.initialize(&mut app.world, &mut app.resources);
+        stage.run(&mut app.world, &mut app.resources);
+
+        let draw = app.world.get::<Draw>(text).unwrap();
+        let pipeline = draw
+            .render_commands
+            .iter()
+            .find_map(|command| match command {
+                RenderCommand::SetPipeline { pipeline } => Some(pipeline.clone_weak()),
+                _ => None,
+            })
+            .unwrap();
+        let pipelines = app.resources.get::<Assets<PipelineDescriptor>>().unwrap();
+        let layout = pipelines.get(&pipeline).unwrap().get_layout().unwrap();
+        // the quad has no vertex colors, so they come from the fallback buffer
+        assert_eq!(layout.vertex_buffer_descriptors.len(), 2);
+        for slot in 0..layout.vertex_buffer_descriptors.len() as u32 {
+            assert!(draw.render_commands.iter().any(|command| matches!(
+                command,
+                RenderCommand::SetVertexBuffer { slot: set, .. } if *set == slot
+            )));
+        }
+        assert!(draw
+            .render_commands
+            .iter()
+            .any(|command| matches!(command, RenderCommand::DrawIndexed { .. })));
+    }
+}
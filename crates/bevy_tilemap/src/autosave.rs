@@ -0,0 +1,397 @@
+use crate::{ChunkIndex, PackedChunk, RegionError, RegionStorage, WorldTileStore};
+use bevy_core::Time;
+use bevy_ecs::{Resources, World};
+use bevy_reflect::TypeRegistryArc;
+use bevy_scene::{SaveGame, SaveGameError, SaveGameRegistry};
+use bevy_tasks::IoTaskPool;
+use bevy_utils::tracing::warn;
+use parking_lot::{Condvar, Mutex};
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// The file in the autosave directory that holds the [SaveGame]
+pub const AUTOSAVE_SAVE_GAME: &str = "save_game.ron";
+
+/// Saves the world in the background every [Autosave::interval]. Insert it as a resource to turn
+/// autosaving on.
+///
+/// Every save writes the chunks of the [WorldTileStore] that changed to [RegionStorage] in the
+/// autosave directory, and when the app has a [SaveGameRegistry], the [SaveGame] to
+/// [AUTOSAVE_SAVE_GAME]. The writes run on the [IoTaskPool]. Chunks are handed to it a few at a
+/// time, at most [Autosave::max_bytes_per_frame] each frame, so saving a large world is spread
+/// over several frames instead of causing a hitch.
+pub struct Autosave {
+    pub interval: Duration,
+    /// How many bytes of chunks are handed to the [IoTaskPool] per frame. A chunk larger than
+    /// this is still saved, on a frame of its own.
+    pub max_bytes_per_frame: usize,
+    /// How long [autosave_shutdown_system] waits for the save in progress to finish
+    pub shutdown_timeout: Duration,
+    directory: PathBuf,
+    since_save: Duration,
+    save_requested: bool,
+    pending: VecDeque<ChunkIndex>,
+    storage: Arc<Mutex<RegionStorage>>,
+    /// Chunks that failed to save, which are marked dirty again so the next save retries them
+    failed: Arc<Mutex<Vec<ChunkIndex>>>,
+    tasks: Arc<RunningTasks>,
+}
+
+/// Counts the writes on the [IoTaskPool], so the app can wait for them when it exits
+#[derive(Default)]
+struct RunningTasks {
+    count: Mutex<usize>,
+    finished: Condvar,
+}
+
+impl RunningTasks {
+    fn start(&self) {
+        *self.count.lock() += 1;
+    }
+
+    fn finish(&self) {
+        let mut count = self.count.lock();
+        *count -= 1;
+        if *count == 0 {
+            self.finished.notify_all();
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        *self.count.lock() > 0
+    }
+
+    /// Blocks until all tasks finished, or `timeout` passed. Returns `false` on timeout.
+    fn wait(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut count = self.count.lock();
+        while *count > 0 {
+            if self.finished.wait_until(&mut count, deadline).timed_out() {
+                return *count == 0;
+            }
+        }
+        true
+    }
+}
+
+impl Autosave {
+    /// Autosaves to `directory` every minute
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        let directory = directory.into();
+        Autosave {
+            interval: Duration::from_secs(60),
+            max_bytes_per_frame: 256 * 1024,
+            shutdown_timeout: Duration::from_secs(10),
+            storage: Arc::new(Mutex::new(RegionStorage::new(directory.clone()))),
+            directory,
+            since_save: Duration::default(),
+            save_requested: false,
+            pending: VecDeque::new(),
+            failed: Default::default(),
+            tasks: Default::default(),
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn with_max_bytes_per_frame(mut self, max_bytes_per_frame: usize) -> Self {
+        self.max_bytes_per_frame = max_bytes_per_frame;
+        self
+    }
+
+    pub fn with_shutdown_timeout(mut self, shutdown_timeout: Duration) -> Self {
+        self.shutdown_timeout = shutdown_timeout;
+        self
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// The region files chunks are saved to, which can also load them back
+    pub fn storage(&self) -> &Arc<Mutex<RegionStorage>> {
+        &self.storage
+    }
+
+    /// Starts a save on the next frame, unless one is still in progress
+    pub fn save_now(&mut self) {
+        self.save_requested = true;
+    }
+
+    /// Whether a save has chunks left to hand over, or writes left to finish
+    pub fn is_saving(&self) -> bool {
+        !self.pending.is_empty() || self.tasks.is_running()
+    }
+
+    /// Takes the next chunks to save this frame, within [Autosave::max_bytes_per_frame]
+    fn next_batch(
+        &mut self,
+        store: &WorldTileStore,
+    ) -> Vec<(ChunkIndex, Option<Arc<PackedChunk>>)> {
+        let mut budget = self.max_bytes_per_frame;
+        let mut batch = Vec::new();
+        while let Some(index) = self.pending.pop_front() {
+            let chunk = store.shared_chunk(index);
+            // removed chunks only rewrite a header entry
+            let bytes = chunk.as_ref().map_or(8, |chunk| chunk.heap_size());
+            if bytes > budget && !batch.is_empty() {
+                self.pending.push_front(index);
+                break;
+            }
+            budget = budget.saturating_sub(bytes);
+            batch.push((index, chunk));
+        }
+        batch
+    }
+
+    fn spawn_task(&self, pool: &IoTaskPool, task: impl FnOnce() + Send + 'static) {
+        self.tasks.start();
+        let tasks = self.tasks.clone();
+        pool.spawn(async move {
+            task();
+            tasks.finish();
+        })
+        .detach();
+    }
+}
+
+/// Writes `bytes` to a temporary file next to `path`, then renames it to `path`, so `path` holds
+/// either the old or the new contents even if the app stops while writing
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+    let mut file = File::create(&temp_path)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    std::fs::rename(&temp_path, path)
+}
+
+/// Starts a save when the [Autosave] interval passed, and hands the chunks of the current save to
+/// the [IoTaskPool]
+pub fn autosave_system(world: &mut World, resources: &mut Resources) {
+    let mut autosave = match resources.get_mut::<Autosave>() {
+        Some(autosave) => autosave,
+        None => return,
+    };
+    let mut store = resources.get_mut::<WorldTileStore>().unwrap();
+    let pool = resources
+        .get::<IoTaskPool>()
+        .expect("`IoTaskPool` resource not found.");
+    for index in autosave.failed.lock().drain(..) {
+        store.mark_dirty(index);
+    }
+
+    if let Some(time) = resources.get::<Time>() {
+        autosave.since_save += time.delta();
+    }
+    // a save starts after the last one has been written, so writes of a chunk never race
+    let due = autosave.save_requested || autosave.since_save >= autosave.interval;
+    if due && !autosave.is_saving() {
+        autosave.since_save = Duration::default();
+        autosave.save_requested = false;
+        let dirty = store.take_dirty();
+        autosave.pending.extend(dirty);
+        if let Some(save_game) = serialize_save_game(world, resources) {
+            let path = autosave.directory.join(AUTOSAVE_SAVE_GAME);
+            autosave.spawn_task(&pool, move || write_save_game(&path, &save_game));
+        }
+    }
+
+    let batch = autosave.next_batch(&store);
+    if batch.is_empty() {
+        return;
+    }
+    let storage = autosave.storage.clone();
+    let failed = autosave.failed.clone();
+    autosave.spawn_task(&pool, move || {
+        let mut storage = storage.lock();
+        for (index, chunk) in batch {
+            let result: Result<(), RegionError> = match chunk {
+                Some(chunk) => storage.save_chunk(index, &chunk),
+                None => storage.remove_chunk(index).map(|_| ()),
+            };
+            if let Err(err) = result {
+                warn!("Failed to autosave chunk {:?}: {}", index, err);
+                failed.lock().push(index);
+            }
+        }
+    });
+}
+
+/// Waits up to [Autosave::shutdown_timeout] for the save in progress, then saves all the chunks
+/// that changed since on the calling thread, so no changes are lost when the app exits. When the
+/// save in progress still writes chunks after the timeout, the chunks that changed since are not
+/// saved, rather than holding up the exit.
+pub fn autosave_shutdown_system(world: &mut World, resources: &mut Resources) {
+    let mut autosave = match resources.get_mut::<Autosave>() {
+        Some(autosave) => autosave,
+        None => return,
+    };
+    let autosave = &mut *autosave;
+    let deadline = Instant::now() + autosave.shutdown_timeout;
+    let finished = autosave.tasks.wait(autosave.shutdown_timeout);
+    if !finished {
+        warn!(
+            "The autosave in progress did not finish within {:?}",
+            autosave.shutdown_timeout
+        );
+    }
+    // the task in progress holds the storage until it wrote its whole batch
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    let mut storage = match autosave.storage.try_lock_for(remaining) {
+        Some(storage) => storage,
+        None => {
+            warn!("The chunks that changed since the last autosave were not saved");
+            return;
+        }
+    };
+    // chunks the task failed to save are only known once it released the storage
+    let mut store = resources.get_mut::<WorldTileStore>().unwrap();
+    for index in autosave.failed.lock().drain(..) {
+        store.mark_dirty(index);
+    }
+    let dirty = store.take_dirty();
+    autosave.pending.extend(dirty);
+
+    for index in autosave.pending.drain(..) {
+        let result = match store.shared_chunk(index) {
+            Some(chunk) => storage.save_chunk(index, &chunk),
+            None => storage.remove_chunk(index).map(|_| ()),
+        };
+        if let Err(err) = result {
+            warn!("Failed to save chunk {:?}: {}", index, err);
+        }
+    }
+    storage.close();
+    drop(storage);
+    // a save game still being written would race with this one
+    if !finished {
+        return;
+    }
+    if let Some(save_game) = serialize_save_game(world, resources) {
+        write_save_game(&autosave.directory.join(AUTOSAVE_SAVE_GAME), &save_game);
+    }
+}
+
+/// The [SaveGame] of the world in RON, when the app has a [SaveGameRegistry]
+fn serialize_save_game(world: &World, resources: &Resources) -> Option<String> {
+    if !resources.contains::<SaveGameRegistry>() {
+        return None;
+    }
+    let type_registry = resources.get::<TypeRegistryArc>().unwrap();
+    let save_game = SaveGame::from_world(world, resources).and_then(|save_game| {
+        save_game
+            .serialize_ron(&type_registry)
+            .map_err(SaveGameError::from)
+    });
+    match save_game {
+        Ok(save_game) => Some(save_game),
+        Err(err) => {
+            warn!("Failed to autosave the save game: {}", err);
+            None
+        }
+    }
+}
+
+fn write_save_game(path: &Path, save_game: &str) {
+    let result = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| write_atomic(path, save_game.as_bytes()));
+    if let Err(err) = result {
+        warn!("Failed to autosave {}: {}", path.display(), err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TileId;
+    use bevy_ecs::{IntoSystem, Stage, SystemStage};
+    use bevy_math::IVec2;
+    use bevy_tasks::TaskPoolBuilder;
+
+    #[test]
+    fn autosave_spreads_chunks_over_frames() {
+        let directory =
+            std::env::temp_dir().join(format!("bevy_tilemap_autosave_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(IoTaskPool(TaskPoolBuilder::new().num_threads(1).build()));
+        let mut store = WorldTileStore::new(8);
+        for x in 0..4 {
+            store.set(IVec2::new(x * 8, 0), TileId(1));
+        }
+        let chunk_bytes = store
+            .get_chunk(ChunkIndex(IVec2::zero()))
+            .unwrap()
+            .heap_size();
+        resources.insert(store);
+        let mut autosave = Autosave::new(&directory).with_max_bytes_per_frame(chunk_bytes * 3);
+        autosave.save_now();
+        resources.insert(autosave);
+
+        let mut stage = SystemStage::serial();
+        stage.add_system(autosave_system.system());
+        stage.initialize(&mut world, &mut resources);
+        stage.run(&mut world, &mut resources);
+        assert_eq!(resources.get::<Autosave>().unwrap().pending.len(), 1);
+        stage.run(&mut world, &mut resources);
+        assert!(resources.get::<Autosave>().unwrap().pending.is_empty());
+
+        let autosave = resources.get::<Autosave>().unwrap();
+        assert!(autosave.tasks.wait(Duration::from_secs(10)));
+        drop(autosave);
+        let mut storage = RegionStorage::new(&directory);
+        for x in 0..4 {
+            let chunk = storage.load_chunk(ChunkIndex(IVec2::new(x, 0))).unwrap();
+            assert_eq!(chunk.unwrap().get(0, 0), Some(TileId(1)));
+        }
+        assert!(resources
+            .get_mut::<WorldTileStore>()
+            .unwrap()
+            .take_dirty()
+            .is_empty());
+
+        // the chunks that changed since are saved when the app exits
+        resources
+            .get_mut::<WorldTileStore>()
+            .unwrap()
+            .set(IVec2::new(40, 0), TileId(2));
+
+        // unless a stalled save holds the storage past the timeout, which doesn't hold up the exit
+        let storage = resources.get::<Autosave>().unwrap().storage.clone();
+        let stalled = storage.lock();
+        resources.get_mut::<Autosave>().unwrap().shutdown_timeout = Duration::from_millis(50);
+        let start = Instant::now();
+        autosave_shutdown_system(&mut world, &mut resources);
+        assert!(start.elapsed() < Duration::from_secs(5));
+        let store = resources.get::<WorldTileStore>().unwrap();
+        assert!(store.is_dirty(ChunkIndex(IVec2::new(5, 0))));
+        drop(store);
+        drop(stalled);
+
+        autosave_shutdown_system(&mut world, &mut resources);
+        let chunk = RegionStorage::new(&directory)
+            .load_chunk(ChunkIndex(IVec2::new(5, 0)))
+            .unwrap();
+        assert_eq!(chunk.unwrap().get(0, 0), Some(TileId(2)));
+
+        write_atomic(&directory.join("file"), b"saved").unwrap();
+        assert_eq!(std::fs::read(directory.join("file")).unwrap(), b"saved");
+        assert!(!directory.join("file.tmp").exists());
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,321 @@
+use crate::TileId;
+use std::{convert::TryInto, sync::Arc};
+
+/// A square grid of tiles.
+///
+/// The tiles are shared with the [WorldTileStore](crate::WorldTileStore) and with clones of the
+/// chunk, so spawning a stored chunk doesn't copy its tiles. Setting a tile copies them, if they
+/// are shared, before changing them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    tiles: Arc<PackedChunk>,
+}
+
+impl Chunk {
+    /// Creates a chunk of `size` by `size` empty tiles
+    pub fn new(size: u32) -> Self {
+        Chunk::from_packed(Arc::new(PackedChunk::new(size)))
+    }
+
+    /// Creates a chunk of `size` by `size` tiles from its tiles in row-major order
+    pub fn from_tiles(size: u32, tiles: &[TileId]) -> Self {
+        Chunk::from_packed(Arc::new(PackedChunk::from_tiles(size, tiles)))
+    }
+
+    /// Creates a chunk that shares `tiles`
+    pub fn from_packed(tiles: Arc<PackedChunk>) -> Self {
+        Chunk { tiles }
+    }
+
+    /// The tiles of the chunk, which may be shared
+    pub fn packed(&self) -> &Arc<PackedChunk> {
+        &self.tiles
+    }
+
+    /// The number of tiles along each side of the chunk
+    pub fn size(&self) -> u32 {
+        self.tiles.size()
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Option<TileId> {
+        self.tiles.get(x, y)
+    }
+
+    /// Sets the tile at `(x, y)`. Returns `false` if the position is outside the chunk.
+    pub fn set(&mut self, x: u32, y: u32, tile: TileId) -> bool {
+        match self.tiles.get(x, y) {
+            // only copy shared tiles if something changes
+            Some(current) if current != tile => Arc::make_mut(&mut self.tiles).set(x, y, tile),
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    /// The tiles in row-major order
+    pub fn iter(&self) -> impl Iterator<Item = TileId> + '_ {
+        self.tiles.iter()
+    }
+}
+
+/// The tiles of a chunk, packed to use as few bits per tile as the number of different tiles in
+/// the chunk allows. A chunk of a single kind of tile takes no space beyond its palette.
+#[derive(Debug, Clone)]
+pub struct PackedChunk {
+    size: u32,
+    /// The different tiles in the chunk. Tiles are stored as indices into the palette.
+    palette: Vec<TileId>,
+    bits_per_tile: u32,
+    words: Vec<u64>,
+}
+
+impl PackedChunk {
+    /// Creates a chunk of `size` by `size` empty tiles
+    pub fn new(size: u32) -> Self {
+        PackedChunk {
+            size,
+            palette: vec![TileId::EMPTY],
+            bits_per_tile: 0,
+            words: Vec::new(),
+        }
+    }
+
+    /// Packs `size` by `size` tiles, given in row-major order
+    pub fn from_tiles(size: u32, tiles: &[TileId]) -> Self {
+        assert_eq!(tiles.len(), (size * size) as usize);
+        let mut palette = Vec::new();
+        for tile in tiles {
+            if !palette.contains(tile) {
+                palette.push(*tile);
+            }
+        }
+        if palette.is_empty() {
+            palette.push(TileId::EMPTY);
+        }
+        let mut packed = PackedChunk {
+            size,
+            palette,
+            bits_per_tile: 0,
+            words: Vec::new(),
+        };
+        packed.resize_words(bits_for(packed.palette.len()));
+        for (index, tile) in tiles.iter().enumerate() {
+            let entry = packed
+                .palette
+                .iter()
+                .position(|entry| entry == tile)
+                .unwrap();
+            packed.write(index, entry);
+        }
+        packed
+    }
+
+    /// The tiles in row-major order
+    pub fn iter(&self) -> impl Iterator<Item = TileId> + '_ {
+        (0..self.len()).map(move |index| self.palette[self.read(index)])
+    }
+
+    /// The number of tiles along each side of the chunk
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Option<TileId> {
+        self.index(x, y).map(|index| self.palette[self.read(index)])
+    }
+
+    /// Sets the tile at `(x, y)`. Returns `false` if the position is outside the chunk or the
+    /// tile was already set.
+    pub fn set(&mut self, x: u32, y: u32, tile: TileId) -> bool {
+        let index = match self.index(x, y) {
+            Some(index) => index,
+            None => return false,
+        };
+        if self.palette[self.read(index)] == tile {
+            return false;
+        }
+        let entry = match self.palette.iter().position(|entry| *entry == tile) {
+            Some(entry) => entry,
+            None => {
+                self.palette.push(tile);
+                let bits = bits_for(self.palette.len());
+                if bits > self.bits_per_tile {
+                    self.repack(bits);
+                }
+                self.palette.len() - 1
+            }
+        };
+        self.write(index, entry);
+        true
+    }
+
+    /// Drops tiles from the palette that are no longer used, which can lower the bits per tile
+    pub fn compact(&mut self) {
+        let tiles = self.iter().collect::<Vec<_>>();
+        *self = PackedChunk::from_tiles(self.size, &tiles);
+    }
+
+    /// Whether every tile of the chunk is empty
+    pub fn is_empty(&self) -> bool {
+        self.iter().all(|tile| tile == TileId::EMPTY)
+    }
+
+    /// The number of bits each tile takes, which grows with the number of different tiles
+    pub fn bits_per_tile(&self) -> u32 {
+        self.bits_per_tile
+    }
+
+    /// Appends the chunk in a compact binary form, which [PackedChunk::from_bytes] reads back
+    pub fn write_bytes(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.size.to_le_bytes());
+        bytes.extend_from_slice(&(self.palette.len() as u32).to_le_bytes());
+        for tile in self.palette.iter() {
+            bytes.extend_from_slice(&tile.0.to_le_bytes());
+        }
+        bytes.extend_from_slice(&self.bits_per_tile.to_le_bytes());
+        for word in self.words.iter() {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+    }
+
+    /// Reads a chunk written by [PackedChunk::write_bytes]. Returns `None` if `bytes` don't hold
+    /// a valid chunk.
+    pub fn from_bytes(mut bytes: &[u8]) -> Option<Self> {
+        let size = read_u32(&mut bytes)?;
+        let palette_len = read_u32(&mut bytes)? as usize;
+        let palette = (0..palette_len)
+            .map(|_| read_u16(&mut bytes).map(TileId))
+            .collect::<Option<Vec<_>>>()?;
+        let bits_per_tile = read_u32(&mut bytes)?;
+        size.checked_mul(size)?;
+        if palette.is_empty() || bits_per_tile != bits_for(palette.len()) {
+            return None;
+        }
+        let mut chunk = PackedChunk {
+            size,
+            palette,
+            bits_per_tile: 0,
+            words: Vec::new(),
+        };
+        // check the length before allocating, so a corrupt size can't allocate too much
+        let words_len = match bits_per_tile {
+            0 => 0,
+            _ if size == 0 => return None,
+            bits => (chunk.len() - 1) / (64 / bits) as usize + 1,
+        };
+        if bytes.len() != words_len * 8 {
+            return None;
+        }
+        chunk.resize_words(bits_per_tile);
+        for word in chunk.words.iter_mut() {
+            *word = read_u64(&mut bytes)?;
+        }
+        if (0..chunk.len()).all(|index| chunk.read(index) < chunk.palette.len()) {
+            Some(chunk)
+        } else {
+            None
+        }
+    }
+
+    /// The number of bytes allocated for the chunk's tiles
+    pub fn heap_size(&self) -> usize {
+        self.palette.capacity() * std::mem::size_of::<TileId>()
+            + self.words.capacity() * std::mem::size_of::<u64>()
+    }
+
+    fn len(&self) -> usize {
+        (self.size * self.size) as usize
+    }
+
+    fn index(&self, x: u32, y: u32) -> Option<usize> {
+        if x < self.size && y < self.size {
+            Some((y * self.size + x) as usize)
+        } else {
+            None
+        }
+    }
+
+    // tiles don't cross word boundaries, which wastes a few bits per word for some sizes but
+    // keeps every read to a single word
+    fn tiles_per_word(&self) -> usize {
+        (64 / self.bits_per_tile) as usize
+    }
+
+    fn read(&self, index: usize) -> usize {
+        if self.bits_per_tile == 0 {
+            return 0;
+        }
+        let tiles_per_word = self.tiles_per_word();
+        let word = self.words[index / tiles_per_word];
+        let shift = (index % tiles_per_word) as u32 * self.bits_per_tile;
+        ((word >> shift) & ((1 << self.bits_per_tile) - 1)) as usize
+    }
+
+    fn write(&mut self, index: usize, entry: usize) {
+        if self.bits_per_tile == 0 {
+            return;
+        }
+        let tiles_per_word = self.tiles_per_word();
+        let shift = (index % tiles_per_word) as u32 * self.bits_per_tile;
+        let mask = ((1 << self.bits_per_tile) - 1) << shift;
+        let word = &mut self.words[index / tiles_per_word];
+        *word = (*word & !mask) | ((entry as u64) << shift);
+    }
+
+    fn resize_words(&mut self, bits_per_tile: u32) {
+        self.bits_per_tile = bits_per_tile;
+        self.words = if bits_per_tile == 0 {
+            Vec::new()
+        } else {
+            // a chunk with more than one kind of tile has at least one tile
+            vec![0; (self.len() - 1) / self.tiles_per_word() + 1]
+        };
+    }
+
+    fn repack(&mut self, bits_per_tile: u32) {
+        let entries = (0..self.len())
+            .map(|index| self.read(index))
+            .collect::<Vec<_>>();
+        self.resize_words(bits_per_tile);
+        for (index, entry) in entries.into_iter().enumerate() {
+            self.write(index, entry);
+        }
+    }
+}
+
+// chunks are equal when they hold the same tiles, however they are packed
+impl PartialEq for PackedChunk {
+    fn eq(&self, other: &Self) -> bool {
+        self.size == other.size && self.iter().eq(other.iter())
+    }
+}
+
+/// Splits the first `len` bytes off `bytes`
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if bytes.len() < len {
+        return None;
+    }
+    let (head, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Some(head)
+}
+
+fn read_u16(bytes: &mut &[u8]) -> Option<u16> {
+    take(bytes, 2).map(|head| u16::from_le_bytes(head.try_into().unwrap()))
+}
+
+fn read_u32(bytes: &mut &[u8]) -> Option<u32> {
+    take(bytes, 4).map(|head| u32::from_le_bytes(head.try_into().unwrap()))
+}
+
+fn read_u64(bytes: &mut &[u8]) -> Option<u64> {
+    take(bytes, 8).map(|head| u64::from_le_bytes(head.try_into().unwrap()))
+}
+
+/// The number of bits per tile needed to tell `palette_len` tiles apart
+fn bits_for(palette_len: usize) -> u32 {
+    if palette_len <= 1 {
+        0
+    } else {
+        32 - ((palette_len - 1) as u32).leading_zeros()
+    }
+}
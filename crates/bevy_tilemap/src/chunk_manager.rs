@@ -0,0 +1,354 @@
+use crate::{ChunkBundle, ChunkGenerator, ChunkIndex, TileAtlas, WorldTileStore};
+use bevy_ecs::{Commands, Entity, Query, Res, ResMut, With};
+use bevy_math::{IRect, Vec2};
+use bevy_render::camera::OrthographicProjection;
+use bevy_transform::components::GlobalTransform;
+use bevy_utils::{HashMap, HashSet};
+
+/// Marks entities, usually cameras, that the [ChunkManager] keeps chunks spawned around. Loaders
+/// with an [OrthographicProjection] keep the chunks they show spawned, at any zoom.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ChunkLoader;
+
+/// The order the [ChunkManager] spawns, despawns and iterates chunks in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkOrder {
+    /// Whatever order the chunk indices come out of a hash set in, which changes from run to
+    /// run. Skips sorting, for worlds that don't need reproducible frames.
+    Unordered,
+    /// The bottom row first, each row from left to right
+    RowMajor,
+    /// The chunks closest to a [ChunkLoader] first, with ties in [ChunkOrder::RowMajor] order
+    NearestFirst,
+}
+
+impl Default for ChunkOrder {
+    fn default() -> Self {
+        ChunkOrder::NearestFirst
+    }
+}
+
+/// Spawns the chunks within `load_radius` of every [ChunkLoader] as [ChunkBundle]s, with their
+/// tiles from the [WorldTileStore], and despawns the others. Chunks that are not in the store are
+/// spawned once the [ChunkGenerator] generated them, if it is enabled.
+#[derive(Debug)]
+pub struct ChunkManager {
+    /// How many chunks in each direction from the chunk of a loader, or from the chunks a camera
+    /// loader shows, are kept spawned
+    pub load_radius: u32,
+    pub order: ChunkOrder,
+    spawned: HashMap<ChunkIndex, Entity>,
+    loader_chunks: Vec<ChunkIndex>,
+}
+
+impl Default for ChunkManager {
+    fn default() -> Self {
+        ChunkManager {
+            load_radius: 2,
+            order: Default::default(),
+            spawned: Default::default(),
+            loader_chunks: Default::default(),
+        }
+    }
+}
+
+impl ChunkManager {
+    /// The entity of a spawned chunk
+    pub fn get_entity(&self, index: ChunkIndex) -> Option<Entity> {
+        self.spawned.get(&index).copied()
+    }
+
+    /// Whether every chunk that overlaps the area from `min` to `max` in world units is spawned,
+    /// for chunks of `chunk_size` tiles of `tile_size` world units
+    pub fn is_area_spawned(&self, min: Vec2, max: Vec2, chunk_size: u32, tile_size: u32) -> bool {
+        ChunkIndex::overlapping(min, max, chunk_size, tile_size)
+            .iter()
+            .all(|index| self.spawned.contains_key(&ChunkIndex(index)))
+    }
+
+    /// The spawned chunks, in the manager's [ChunkOrder]
+    pub fn iter_spawned(&self) -> impl Iterator<Item = (ChunkIndex, Entity)> + '_ {
+        let mut indices = self.spawned.keys().copied().collect::<Vec<_>>();
+        self.sort(&mut indices);
+        let spawned = &self.spawned;
+        indices
+            .into_iter()
+            .map(move |index| (index, spawned[&index]))
+    }
+
+    fn sort(&self, indices: &mut [ChunkIndex]) {
+        let row_major = |index: &ChunkIndex| (index.0.y, index.0.x);
+        match self.order {
+            ChunkOrder::Unordered => {}
+            ChunkOrder::RowMajor => indices.sort_unstable_by_key(row_major),
+            ChunkOrder::NearestFirst => {
+                let loader_chunks = &self.loader_chunks;
+                let distance = |index: &ChunkIndex| {
+                    loader_chunks
+                        .iter()
+                        .map(|loader| index.0.distance_squared(loader.0))
+                        .min()
+                        .unwrap_or(0)
+                };
+                indices.sort_unstable_by_key(|index| (distance(index), row_major(index)));
+            }
+        }
+    }
+}
+
+/// Spawns and despawns chunks as [ChunkLoader]s move, in the order of [ChunkManager::order]
+pub fn chunk_manager_system(
+    commands: &mut Commands,
+    mut manager: ResMut<ChunkManager>,
+    mut generator: ResMut<ChunkGenerator>,
+    tile_atlas: Res<TileAtlas>,
+    mut store: ResMut<WorldTileStore>,
+    loaders: Query<(&GlobalTransform, Option<&OrthographicProjection>), With<ChunkLoader>>,
+) {
+    let manager = &mut *manager;
+    generator.store_generated(&mut store);
+    let tile_size = tile_atlas.tile_size;
+    let chunk_size = store.chunk_size();
+    let radius = manager.load_radius as i32;
+    let mut loader_chunks = Vec::new();
+    let mut wanted = HashSet::default();
+    for (transform, projection) in loaders.iter() {
+        let position = transform.translation.truncate();
+        let loader_chunk = ChunkIndex::containing(position, chunk_size, tile_size);
+        loader_chunks.push(loader_chunk);
+        let area = match projection {
+            // the projection is scaled by the transform when zooming
+            Some(projection) => {
+                let scale = transform.scale.truncate();
+                ChunkIndex::overlapping(
+                    position + Vec2::new(projection.left, projection.bottom) * scale,
+                    position + Vec2::new(projection.right, projection.top) * scale,
+                    chunk_size,
+                    tile_size,
+                )
+            }
+            None => IRect::from_point(loader_chunk.0),
+        };
+        wanted.extend(area.expand(radius).iter().map(ChunkIndex));
+    }
+    loader_chunks.sort_unstable();
+    loader_chunks.dedup();
+    manager.loader_chunks = loader_chunks;
+
+    let mut despawned = manager
+        .spawned
+        .keys()
+        .filter(|index| !wanted.contains(index))
+        .copied()
+        .collect::<Vec<_>>();
+    manager.sort(&mut despawned);
+    for index in despawned {
+        let entity = manager.spawned.remove(&index).unwrap();
+        commands.despawn(entity);
+    }
+
+    let mut spawned = wanted
+        .into_iter()
+        .filter(|index| !manager.spawned.contains_key(index))
+        .collect::<Vec<_>>();
+    manager.sort(&mut spawned);
+    for index in spawned {
+        // chunks edited while they are generated are only spawned once they are generated
+        if generator.is_generating(index)
+            || generator.is_enabled() && store.get_chunk(index).is_none()
+        {
+            generator.request(index, &mut store);
+            continue;
+        }
+        commands.spawn(ChunkBundle::new(index.0, store.chunk(index), tile_size));
+        manager
+            .spawned
+            .insert(index, commands.current_entity().unwrap());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chunk_store_system, Chunk, TileId};
+    use bevy_ecs::{IntoSystem, Resources, Stage, SystemStage, World};
+    use bevy_math::{IVec2, Vec3};
+
+    fn spawn_order(order: ChunkOrder) -> Vec<(i32, i32)> {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(TileAtlas {
+            tile_size: 1,
+            ..Default::default()
+        });
+        resources.insert(WorldTileStore::new(10));
+        resources.insert(ChunkGenerator::default());
+        resources.insert(ChunkManager {
+            load_radius: 1,
+            order,
+            ..Default::default()
+        });
+        world.spawn((
+            ChunkLoader,
+            GlobalTransform::from_translation(Vec3::new(5.0, 5.0, 0.0)),
+        ));
+
+        let mut stage = SystemStage::serial();
+        stage.add_system(chunk_manager_system.system());
+        stage.initialize(&mut world, &mut resources);
+        stage.run(&mut world, &mut resources);
+
+        let mut chunks = world
+            .query::<(Entity, &ChunkIndex)>()
+            .map(|(entity, index)| (entity.id(), index.0.into()))
+            .collect::<Vec<(u32, (i32, i32))>>();
+        chunks.sort_unstable();
+        chunks.into_iter().map(|(_, index)| index).collect()
+    }
+
+    #[test]
+    fn deterministic_spawn_order() {
+        assert_eq!(
+            spawn_order(ChunkOrder::NearestFirst),
+            vec![
+                (0, 0),
+                (0, -1),
+                (-1, 0),
+                (1, 0),
+                (0, 1),
+                (-1, -1),
+                (1, -1),
+                (-1, 1),
+                (1, 1)
+            ]
+        );
+        assert_eq!(
+            spawn_order(ChunkOrder::RowMajor),
+            vec![
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (-1, 0),
+                (0, 0),
+                (1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn despawned_chunks_keep_tiles() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(TileAtlas {
+            tile_size: 1,
+            ..Default::default()
+        });
+        resources.insert(WorldTileStore::new(2));
+        resources.insert(ChunkGenerator::default());
+        resources.insert(ChunkManager {
+            load_radius: 0,
+            ..Default::default()
+        });
+        let loader = world.spawn((ChunkLoader, GlobalTransform::default()));
+        // the store system runs in a later stage than the manager, so it runs first in the
+        // next frame
+        let mut stage = SystemStage::serial();
+        stage.add_system(chunk_store_system.system());
+        stage.add_system(chunk_manager_system.system());
+        stage.initialize(&mut world, &mut resources);
+        stage.run(&mut world, &mut resources);
+
+        let index = ChunkIndex(IVec2::zero());
+        let entity = resources.get::<ChunkManager>().unwrap().get_entity(index);
+        world
+            .get_mut::<Chunk>(entity.unwrap())
+            .unwrap()
+            .set(1, 1, TileId(3));
+
+        let move_loader = |world: &mut World, x: f32| {
+            world
+                .get_mut::<GlobalTransform>(loader)
+                .unwrap()
+                .translation
+                .x = x;
+        };
+        move_loader(&mut world, 10.0);
+        stage.run(&mut world, &mut resources);
+        assert!(world.get::<Chunk>(entity.unwrap()).is_err());
+        move_loader(&mut world, 0.0);
+        stage.run(&mut world, &mut resources);
+
+        let entity = resources.get::<ChunkManager>().unwrap().get_entity(index);
+        let chunk = world.get::<Chunk>(entity.unwrap()).unwrap();
+        assert_eq!(chunk.get(1, 1), Some(TileId(3)));
+        assert_eq!(
+            resources
+                .get::<WorldTileStore>()
+                .unwrap()
+                .get(IVec2::new(1, 1)),
+            TileId(3)
+        );
+
+        // tiles set in the store show up in the spawned chunk
+        world.clear_trackers();
+        resources
+            .get_mut::<WorldTileStore>()
+            .unwrap()
+            .set(IVec2::new(0, 1), TileId(4));
+        stage.run(&mut world, &mut resources);
+        let chunk = world.get::<Chunk>(entity.unwrap()).unwrap();
+        assert_eq!(chunk.get(0, 1), Some(TileId(4)));
+    }
+
+    #[test]
+    fn cameras_load_what_they_show() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(TileAtlas {
+            tile_size: 1,
+            ..Default::default()
+        });
+        resources.insert(WorldTileStore::new(10));
+        resources.insert(ChunkGenerator::default());
+        resources.insert(ChunkManager {
+            load_radius: 0,
+            ..Default::default()
+        });
+        let camera = world.spawn((
+            ChunkLoader,
+            OrthographicProjection {
+                left: -15.0,
+                right: 15.0,
+                bottom: -5.0,
+                top: 5.0,
+                ..Default::default()
+            },
+            GlobalTransform::default(),
+        ));
+        let mut stage = SystemStage::serial();
+        stage.add_system(chunk_manager_system.system());
+        stage.initialize(&mut world, &mut resources);
+
+        let mut zoom = |world: &mut World, scale: f32| {
+            world.get_mut::<GlobalTransform>(camera).unwrap().scale = Vec3::splat(scale);
+            stage.run(world, &mut resources);
+            let manager = resources.get::<ChunkManager>().unwrap();
+            let mut spawned = manager
+                .iter_spawned()
+                .map(|(index, _)| index.0)
+                .collect::<Vec<_>>();
+            spawned.sort_unstable_by_key(|index| (index.y, index.x));
+            (spawned[0], spawned.len())
+        };
+        // from x -15 to 15 and y -5 to 5
+        assert_eq!(zoom(&mut world, 1.0), (IVec2::new(-2, -1), 8));
+        // zoomed out, from x -30 to 30
+        assert_eq!(zoom(&mut world, 2.0), (IVec2::new(-3, -1), 12));
+        // zoomed in, from x -7.5 to 7.5, and the chunks that went out of view are despawned
+        assert_eq!(zoom(&mut world, 0.5), (IVec2::new(-1, -1), 4));
+        assert_eq!(world.query::<&ChunkIndex>().count(), 4);
+    }
+}
@@ -0,0 +1,470 @@
+use crate::{Chunk, PackedChunk, TileRegistry};
+use bevy_app::{EventReader, Events};
+use bevy_asset::{AssetEvent, Assets, Handle};
+use bevy_ecs::{Bundle, Entity, Local, Query, QuerySet, Res, ResMut};
+use bevy_math::{IRect, IVec2, Vec2};
+use bevy_render::{
+    mesh::Mesh,
+    pipeline::{RenderPipeline, RenderPipelines},
+    prelude::{Draw, Visible},
+    render_graph::base::MainPass,
+    renderer::RenderCapabilities,
+    texture::{Extent3d, FilterMode, SamplerDescriptor, Texture, TextureDimension},
+};
+use bevy_sprite::{ColorMaterial, Sprite, TextureAtlas, QUAD_HANDLE, SPRITE_PIPELINE_HANDLE};
+use bevy_transform::components::{GlobalTransform, Transform};
+use bevy_utils::tracing::error;
+use std::sync::Arc;
+
+/// The atlas that tiles are drawn from. The `sprite` of a [TileKind](crate::TileKind) is the
+/// index of its texture in the atlas. Tile textures must be `tile_size` pixels square, and must
+/// not be rotated or trimmed.
+#[derive(Debug, Clone)]
+pub struct TileAtlas {
+    pub atlas: Handle<TextureAtlas>,
+    /// The size of a tile in pixels, which is also its size in world units
+    pub tile_size: u32,
+}
+
+impl Default for TileAtlas {
+    fn default() -> Self {
+        TileAtlas {
+            atlas: Default::default(),
+            tile_size: 16,
+        }
+    }
+}
+
+/// What the tilemap did in the last frame
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct TilemapStats {
+    /// The number of chunk textures baked from scratch
+    pub chunks_baked: usize,
+    /// The number of tiles baked again into chunk textures, because they changed
+    pub tiles_rebaked: usize,
+    /// The number of bytes of chunk textures that have to be uploaded to the GPU
+    pub bytes_uploaded: usize,
+}
+
+/// The position of a chunk in the grid of chunks
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ChunkIndex(pub IVec2);
+
+impl ChunkIndex {
+    /// The center of the chunk in world units, for chunks of `chunk_size` tiles of `tile_size`
+    /// world units
+    pub fn center(&self, chunk_size: u32, tile_size: u32) -> Vec2 {
+        let size = (chunk_size * tile_size) as f32;
+        (self.0.as_vec2() + Vec2::splat(0.5)) * size
+    }
+
+    /// The chunk that contains `position` in world units
+    pub fn containing(position: Vec2, chunk_size: u32, tile_size: u32) -> Self {
+        let size = (chunk_size * tile_size) as f32;
+        ChunkIndex(IVec2::from_grid_position(position, size))
+    }
+
+    /// The chunks that overlap the area from `min` to `max` in world units
+    pub fn overlapping(min: Vec2, max: Vec2, chunk_size: u32, tile_size: u32) -> IRect {
+        let size = (chunk_size * tile_size) as f32;
+        let max = (max / size).ceil();
+        IRect::new(
+            ChunkIndex::containing(min, chunk_size, tile_size).0,
+            IVec2::new(max.x as i32, max.y as i32),
+        )
+    }
+}
+
+/// The texture a chunk's tiles are baked into, on the CPU. When the chunk changes, only the tiles
+/// that changed are baked again.
+#[derive(Debug, Clone)]
+pub struct ChunkTexture {
+    pub texture: Option<Handle<Texture>>,
+    dirty: bool,
+    /// The tiles the texture shows. Sharing them makes the chunk copy its tiles when they are
+    /// edited, so the tiles that changed can be found by comparing the two.
+    baked: Option<Arc<PackedChunk>>,
+}
+
+impl Default for ChunkTexture {
+    fn default() -> Self {
+        ChunkTexture {
+            texture: None,
+            dirty: true,
+            baked: None,
+        }
+    }
+}
+
+impl ChunkTexture {
+    /// Bakes the whole chunk again in the next frame
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+}
+
+/// A chunk drawn as a sprite with its baked [ChunkTexture]
+#[derive(Bundle)]
+pub struct ChunkBundle {
+    pub chunk: Chunk,
+    pub index: ChunkIndex,
+    pub chunk_texture: ChunkTexture,
+    pub sprite: Sprite,
+    pub mesh: Handle<Mesh>,
+    pub material: Handle<ColorMaterial>,
+    pub main_pass: MainPass,
+    pub draw: Draw,
+    pub visible: Visible,
+    pub render_pipelines: RenderPipelines,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+}
+
+impl ChunkBundle {
+    pub fn new(index: IVec2, chunk: Chunk, tile_size: u32) -> Self {
+        let index = ChunkIndex(index);
+        let size = (chunk.size() * tile_size) as f32;
+        let translation = index.center(chunk.size(), tile_size).extend(0.0);
+        ChunkBundle {
+            chunk,
+            index,
+            chunk_texture: Default::default(),
+            sprite: Sprite::new(Vec2::new(size, size)),
+            mesh: QUAD_HANDLE.typed(),
+            material: Default::default(),
+            main_pass: MainPass,
+            draw: Default::default(),
+            visible: Visible {
+                is_transparent: true,
+                ..Default::default()
+            },
+            render_pipelines: RenderPipelines::from_pipelines(vec![RenderPipeline::new(
+                SPRITE_PIPELINE_HANDLE.typed(),
+            )]),
+            transform: Transform::from_translation(translation),
+            global_transform: GlobalTransform::from_translation(translation),
+        }
+    }
+
+    pub fn with_z(mut self, z: f32) -> Self {
+        self.transform.translation.z = z;
+        self.global_transform.translation.z = z;
+        self
+    }
+}
+
+/// Draws the tiles of `chunk` into a texture, with the tile at `(0, 0)` in the bottom left corner.
+/// Empty tiles, tiles without a sprite and multi-tile objects, which are drawn by their
+/// [TileEntity](crate::TileEntity), are transparent.
+pub fn bake_chunk(
+    chunk: &Chunk,
+    registry: &TileRegistry,
+    atlas: &TextureAtlas,
+    atlas_texture: &Texture,
+    tile_size: u32,
+) -> Texture {
+    let size = chunk.size();
+    let pixels = size * tile_size;
+    let mut texture = Texture::new_fill(
+        Extent3d::new(pixels, pixels, 1),
+        TextureDimension::D2,
+        &vec![0; atlas_texture.format.pixel_size()],
+        atlas_texture.format,
+    );
+    texture.sampler = SamplerDescriptor {
+        mag_filter: FilterMode::Nearest,
+        min_filter: FilterMode::Nearest,
+        ..Default::default()
+    };
+    for y in 0..size {
+        for x in 0..size {
+            blit_tile(
+                &mut texture,
+                chunk,
+                (x, y),
+                registry,
+                atlas,
+                atlas_texture,
+                tile_size,
+            );
+        }
+    }
+    texture
+}
+
+/// Draws the tiles at `tiles` of `chunk` again, into the `texture` [bake_chunk] baked it into
+pub fn rebake_tiles(
+    texture: &mut Texture,
+    chunk: &Chunk,
+    tiles: &[(u32, u32)],
+    registry: &TileRegistry,
+    atlas: &TextureAtlas,
+    atlas_texture: &Texture,
+    tile_size: u32,
+) {
+    let size = chunk.size();
+    let transparent = vec![0; texture.format.pixel_size()];
+    for &(x, y) in tiles {
+        let min = [x * tile_size, (size - 1 - y) * tile_size];
+        texture.fill_rect(min, [min[0] + tile_size, min[1] + tile_size], &transparent);
+        blit_tile(
+            texture,
+            chunk,
+            (x, y),
+            registry,
+            atlas,
+            atlas_texture,
+            tile_size,
+        );
+    }
+}
+
+fn blit_tile(
+    texture: &mut Texture,
+    chunk: &Chunk,
+    (x, y): (u32, u32),
+    registry: &TileRegistry,
+    atlas: &TextureAtlas,
+    atlas_texture: &Texture,
+    tile_size: u32,
+) {
+    let rect = match chunk
+        .get(x, y)
+        .and_then(|tile| registry.get(tile))
+        .filter(|kind| !kind.is_multi_tile())
+        .and_then(|kind| kind.sprite)
+        .and_then(|sprite| atlas.textures.get(sprite as usize))
+    {
+        Some(rect) => rect,
+        None => return,
+    };
+    // texture rows go from top to bottom
+    texture.blit_from(
+        atlas_texture,
+        [rect.min.x as u32, rect.min.y as u32],
+        [rect.max.x as u32, rect.max.y as u32],
+        [x * tile_size, (chunk.size() - 1 - y) * tile_size],
+    );
+}
+
+/// The tiles of `chunk` that are not the same in `baked`
+fn changed_tiles(baked: &PackedChunk, chunk: &Chunk) -> Vec<(u32, u32)> {
+    let size = chunk.size();
+    (0..size)
+        .flat_map(|y| (0..size).map(move |x| (x, y)))
+        .filter(|&(x, y)| baked.get(x, y) != chunk.get(x, y))
+        .collect()
+}
+
+/// Bakes the textures of chunks that were spawned, and of all chunks when the tile atlas changes.
+/// Chunks whose tiles changed only have the tiles that changed baked again.
+#[allow(clippy::too_many_arguments)]
+pub fn chunk_texture_system(
+    mut texture_event_reader: Local<EventReader<AssetEvent<Texture>>>,
+    mut reported_too_large: Local<bool>,
+    texture_events: Res<Events<AssetEvent<Texture>>>,
+    capabilities: Res<RenderCapabilities>,
+    tile_atlas: Res<TileAtlas>,
+    registry: Res<TileRegistry>,
+    atlases: Res<Assets<TextureAtlas>>,
+    mut textures: ResMut<Assets<Texture>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut stats: ResMut<TilemapStats>,
+    mut chunks: QuerySet<(
+        Query<(Entity, &Chunk, &mut ChunkTexture)>,
+        Query<(&mut ChunkTexture, &mut Handle<ColorMaterial>)>,
+    )>,
+) {
+    *stats = TilemapStats::default();
+    let atlas = match atlases.get(&tile_atlas.atlas) {
+        Some(atlas) => atlas,
+        None => return,
+    };
+    let atlas_changed = texture_event_reader
+        .iter(&texture_events)
+        .any(|event| match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                *handle == atlas.texture
+            }
+            AssetEvent::Removed { .. } => false,
+        });
+    let atlas_texture = match textures.get(&atlas.texture) {
+        Some(atlas_texture) => atlas_texture,
+        None => return,
+    };
+
+    let mut baked = Vec::new();
+    let mut rebaked = Vec::new();
+    for (entity, chunk, mut chunk_texture) in chunks.q0_mut().iter_mut() {
+        if !chunk_texture.dirty && !atlas_changed {
+            let changed = match (&chunk_texture.baked, &chunk_texture.texture) {
+                (Some(tiles), _) if Arc::ptr_eq(tiles, chunk.packed()) => continue,
+                (Some(tiles), Some(texture)) if tiles.size() == chunk.size() => {
+                    Some((texture.clone(), changed_tiles(tiles, chunk)))
+                }
+                _ => None,
+            };
+            if let Some((texture, tiles)) = changed {
+                chunk_texture.baked = Some(chunk.packed().clone());
+                if !tiles.is_empty() {
+                    rebaked.push((texture, chunk.clone(), tiles));
+                }
+                continue;
+            }
+        }
+
+        chunk_texture.dirty = false;
+        chunk_texture.baked = Some(chunk.packed().clone());
+        let pixels = chunk.size() * tile_atlas.tile_size;
+        let size = Extent3d::new(pixels, pixels, 1);
+        if let Err(err) = capabilities.validate_texture(size, TextureDimension::D2) {
+            if !*reported_too_large {
+                error!(
+                    "Chunks of {} tiles of {} pixels can't be drawn, make them smaller: {}",
+                    chunk.size(),
+                    tile_atlas.tile_size,
+                    err
+                );
+                *reported_too_large = true;
+            }
+            continue;
+        }
+        let texture = bake_chunk(chunk, &registry, atlas, atlas_texture, tile_atlas.tile_size);
+        baked.push((entity, texture));
+    }
+
+    for (handle, chunk, tiles) in rebaked {
+        // taken out of the assets, so it can be drawn into while the atlas is borrowed
+        let mut texture = match textures.get_mut_untracked(&handle) {
+            Some(texture) => std::mem::take(texture),
+            None => continue,
+        };
+        let atlas_texture = textures.get(&atlas.texture).unwrap();
+        rebake_tiles(
+            &mut texture,
+            &chunk,
+            &tiles,
+            &registry,
+            atlas,
+            atlas_texture,
+            tile_atlas.tile_size,
+        );
+        stats.tiles_rebaked += tiles.len();
+        stats.bytes_uploaded += texture.data.len();
+        *textures.get_mut(&handle).unwrap() = texture;
+    }
+
+    for (entity, texture) in baked {
+        stats.chunks_baked += 1;
+        stats.bytes_uploaded += texture.data.len();
+        let (mut chunk_texture, mut material) = chunks.q1_mut().get_mut(entity).unwrap();
+        match chunk_texture
+            .texture
+            .as_ref()
+            .and_then(|handle| textures.get_mut(handle))
+        {
+            Some(chunk_texture) => *chunk_texture = texture,
+            None => {
+                let handle = textures.add(texture);
+                *material = materials.add(ColorMaterial::texture(handle.clone()));
+                chunk_texture.texture = Some(handle);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TileId, TileKind};
+    use bevy_render::texture::TextureFormat;
+    use bevy_sprite::Rect;
+
+    /// A 2x1 atlas of 1 pixel tiles, red and green, and tile kinds drawn with them
+    fn red_green_atlas() -> (TextureAtlas, Texture, TileRegistry) {
+        let atlas_texture = Texture::new(
+            Extent3d::new(2, 1, 1),
+            TextureDimension::D2,
+            vec![255, 0, 0, 255, 0, 255, 0, 255],
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        let mut atlas = TextureAtlas::new_empty(Default::default(), Vec2::new(2.0, 1.0));
+        atlas.add_texture(Rect {
+            min: Vec2::new(0.0, 0.0),
+            max: Vec2::new(1.0, 1.0),
+        });
+        atlas.add_texture(Rect {
+            min: Vec2::new(1.0, 0.0),
+            max: Vec2::new(2.0, 1.0),
+        });
+        let mut registry = TileRegistry::default();
+        let red = TileKind {
+            sprite: Some(0),
+            ..TileKind::new(TileId(1), "red")
+        };
+        let green = TileKind {
+            sprite: Some(1),
+            ..TileKind::new(TileId(2), "green")
+        };
+        let big_red = TileKind {
+            sprite: Some(0),
+            footprint: (2, 1),
+            ..TileKind::new(TileId(3), "big red")
+        };
+        registry.register(red).unwrap();
+        registry.register(green).unwrap();
+        registry.register(big_red).unwrap();
+        (atlas, atlas_texture, registry)
+    }
+
+    #[test]
+    fn bake_tiles() {
+        let (atlas, atlas_texture, registry) = red_green_atlas();
+        let mut chunk = Chunk::new(2);
+        chunk.set(0, 0, TileId(1));
+        chunk.set(1, 1, TileId(2));
+        chunk.set(0, 1, TileId(3));
+        let texture = bake_chunk(&chunk, &registry, &atlas, &atlas_texture, 1);
+        // the bottom left tile is in the last row of the texture
+        assert_eq!(texture.get_pixel(0, 1), Some(&[255, 0, 0, 255][..]));
+        assert_eq!(texture.get_pixel(1, 0), Some(&[0, 255, 0, 255][..]));
+        // multi-tile objects are drawn by their tile entities
+        assert_eq!(texture.get_pixel(0, 0), Some(&[0, 0, 0, 0][..]));
+        assert_eq!(texture.get_pixel(1, 1), Some(&[0, 0, 0, 0][..]));
+    }
+
+    #[test]
+    fn rebake_changed_tiles() {
+        let (atlas, atlas_texture, registry) = red_green_atlas();
+        let mut chunk = Chunk::new(3);
+        chunk.set(0, 0, TileId(1));
+        chunk.set(2, 1, TileId(2));
+        let mut texture = bake_chunk(&chunk, &registry, &atlas, &atlas_texture, 1);
+
+        let baked = chunk.packed().clone();
+        chunk.set(0, 0, TileId::EMPTY);
+        chunk.set(1, 2, TileId(2));
+        chunk.set(2, 1, TileId(2));
+        // the baked tiles are shared, so they aren't changed along with the chunk
+        let tiles = changed_tiles(&baked, &chunk);
+        assert_eq!(tiles, vec![(0, 0), (1, 2)]);
+
+        rebake_tiles(
+            &mut texture,
+            &chunk,
+            &tiles,
+            &registry,
+            &atlas,
+            &atlas_texture,
+            1,
+        );
+        assert_eq!(
+            texture.data,
+            bake_chunk(&chunk, &registry, &atlas, &atlas_texture, 1).data
+        );
+    }
+}
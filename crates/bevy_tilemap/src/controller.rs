@@ -0,0 +1,340 @@
+use crate::{TileAtlas, TileRegistry, WorldTileStore};
+use bevy_core::Time;
+use bevy_ecs::{Query, Res};
+use bevy_math::{IVec2, Vec2};
+use bevy_transform::components::Transform;
+
+/// How far, in tiles, a box has to reach into a tile to overlap it. Keeps boxes that stopped at
+/// the edge of a tile from overlapping it through rounding errors.
+const EPSILON: f32 = 1e-4;
+
+/// Moves an entity over the tiles of the world like the character of a platformer. Its box is
+/// swept against solid tiles one axis at a time, so it slides along walls and floors instead of
+/// sticking to them, and can't tunnel through thin walls however fast it moves.
+///
+/// Tiles of chunks that are not in the [WorldTileStore] are empty, so characters should be
+/// [ChunkLoader](crate::ChunkLoader)s to keep the chunks around them loaded.
+///
+/// ```ignore
+/// fn walk(input: Res<Input<KeyCode>>, mut characters: Query<&mut CharacterController>) {
+///     for mut character in characters.iter_mut() {
+///         character.velocity.x = 0.0;
+///         if input.pressed(KeyCode::Left) {
+///             character.velocity.x -= 100.0;
+///         }
+///         if input.pressed(KeyCode::Right) {
+///             character.velocity.x += 100.0;
+///         }
+///         if input.just_pressed(KeyCode::Space) {
+///             character.jump(300.0);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CharacterController {
+    /// The size of the character's box in world units, centered on its translation
+    pub size: Vec2,
+    /// The velocity in world units per second. It is set to zero along the axes the character
+    /// hits a tile on.
+    pub velocity: Vec2,
+    /// The downward acceleration in world units per second squared
+    pub gravity: f32,
+    /// The fastest the character falls, in world units per second
+    pub max_fall_speed: f32,
+    /// The highest ledge, in world units, that the character walks up onto without jumping
+    pub step_height: f32,
+    /// Whether the character landed on a solid tile in its last move
+    pub grounded: bool,
+}
+
+impl CharacterController {
+    /// A character with a box of `size` world units that falls at up to 600 world units per
+    /// second, and doesn't step up ledges
+    pub fn new(size: Vec2) -> Self {
+        CharacterController {
+            size,
+            velocity: Vec2::zero(),
+            gravity: 980.0,
+            max_fall_speed: 600.0,
+            step_height: 0.0,
+            grounded: false,
+        }
+    }
+
+    pub fn with_gravity(mut self, gravity: f32) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    pub fn with_max_fall_speed(mut self, max_fall_speed: f32) -> Self {
+        self.max_fall_speed = max_fall_speed;
+        self
+    }
+
+    pub fn with_step_height(mut self, step_height: f32) -> Self {
+        self.step_height = step_height;
+        self
+    }
+
+    /// Jumps up at `speed` world units per second if the character stands on the ground, and
+    /// returns whether it did
+    pub fn jump(&mut self, speed: f32) -> bool {
+        if !self.grounded {
+            return false;
+        }
+        self.velocity.y = speed;
+        self.grounded = false;
+        true
+    }
+}
+
+/// Applies gravity to [CharacterController]s and moves them by their velocity, without moving
+/// them into solid tiles
+pub fn character_controller_system(
+    time: Res<Time>,
+    tile_atlas: Res<TileAtlas>,
+    registry: Res<TileRegistry>,
+    store: Res<WorldTileStore>,
+    mut characters: Query<(&mut CharacterController, &mut Transform)>,
+) {
+    let delta_seconds = time.delta_seconds();
+    if delta_seconds == 0.0 {
+        return;
+    }
+    let collision = TileCollision {
+        store: &store,
+        registry: &registry,
+        tile_size: tile_atlas.tile_size as f32,
+    };
+    for (mut controller, mut transform) in characters.iter_mut() {
+        controller.velocity.y = (controller.velocity.y - controller.gravity * delta_seconds)
+            .max(-controller.max_fall_speed);
+        let movement = collision.move_box(
+            transform.translation.truncate(),
+            controller.size / 2.0,
+            controller.velocity * delta_seconds,
+            controller.step_height,
+            controller.grounded,
+        );
+        controller.grounded = movement.hit_y && controller.velocity.y < 0.0;
+        if movement.hit_x {
+            controller.velocity.x = 0.0;
+        }
+        if movement.hit_y {
+            controller.velocity.y = 0.0;
+        }
+        transform.translation = movement.center.extend(transform.translation.z);
+    }
+}
+
+/// Where a box ended up after moving, and the axes it hit a solid tile on
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Movement {
+    center: Vec2,
+    hit_x: bool,
+    hit_y: bool,
+}
+
+/// The solid tiles of the world, as boxes of `tile_size` world units
+struct TileCollision<'a> {
+    store: &'a WorldTileStore,
+    registry: &'a TileRegistry,
+    tile_size: f32,
+}
+
+impl<'a> TileCollision<'a> {
+    /// Moves the box of `half_size` at `center` by `delta`, first horizontally and then
+    /// vertically. A grounded box that is stopped horizontally steps up ledges of up to
+    /// `step_height`.
+    fn move_box(
+        &self,
+        center: Vec2,
+        half_size: Vec2,
+        delta: Vec2,
+        step_height: f32,
+        grounded: bool,
+    ) -> Movement {
+        let (dx, mut hit_x) = self.sweep(center, half_size, delta.x, true);
+        let mut moved = center + Vec2::new(dx, 0.0);
+        if hit_x && grounded && step_height > 0.0 {
+            // move the box up, across and back down, and keep that if it got further
+            let (up, _) = self.sweep(center, half_size, step_height, false);
+            let raised = center + Vec2::new(0.0, up);
+            let (raised_dx, raised_hit_x) = self.sweep(raised, half_size, delta.x, true);
+            if raised_dx.abs() > dx.abs() {
+                let across = raised + Vec2::new(raised_dx, 0.0);
+                let (down, _) = self.sweep(across, half_size, -up, false);
+                moved = across + Vec2::new(0.0, down);
+                hit_x = raised_hit_x;
+            }
+        }
+        let (dy, hit_y) = self.sweep(moved, half_size, delta.y, false);
+        Movement {
+            center: moved + Vec2::new(0.0, dy),
+            hit_x,
+            hit_y,
+        }
+    }
+
+    /// How far the box of `half_size` at `center` moves by `distance` along one axis before it
+    /// hits a solid tile, and whether it hit one. Tiles the box already overlaps don't stop it,
+    /// so boxes that are stuck in tiles can move out of them.
+    fn sweep(&self, center: Vec2, half_size: Vec2, distance: f32, horizontal: bool) -> (f32, bool) {
+        let (along, across, half_along, half_across) = if horizontal {
+            (center.x, center.y, half_size.x, half_size.y)
+        } else {
+            (center.y, center.x, half_size.y, half_size.x)
+        };
+        let tile_size = self.tile_size;
+        // the rows or columns of tiles the box covers across the axis it moves along
+        let across_min = ((across - half_across) / tile_size + EPSILON).floor() as i32;
+        let across_max = ((across + half_across) / tile_size - EPSILON).ceil() as i32 - 1;
+        let is_solid = |along: i32| {
+            (across_min..=across_max).any(|across| {
+                let tile = if horizontal {
+                    IVec2::new(along, across)
+                } else {
+                    IVec2::new(across, along)
+                };
+                self.store.is_solid(tile, self.registry)
+            })
+        };
+
+        if distance > 0.0 {
+            let edge = along + half_along;
+            let first = (edge / tile_size - EPSILON).ceil() as i32;
+            let last = ((edge + distance) / tile_size - EPSILON).ceil() as i32 - 1;
+            for tile in first..=last {
+                if is_solid(tile) {
+                    return ((tile as f32 * tile_size - edge).max(0.0), true);
+                }
+            }
+        } else if distance < 0.0 {
+            let edge = along - half_along;
+            let first = (edge / tile_size + EPSILON).floor() as i32 - 1;
+            let last = ((edge + distance) / tile_size + EPSILON).floor() as i32;
+            for tile in (last..=first).rev() {
+                if is_solid(tile) {
+                    return (((tile + 1) as f32 * tile_size - edge).min(0.0), true);
+                }
+            }
+        }
+        (distance, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TileId, TileKind};
+
+    /// A floor of stone along y = 0, a ledge one tile high on x = 4, and a wall three tiles high
+    /// on x = 8, in tiles of 16 world units
+    fn world() -> (WorldTileStore, TileRegistry) {
+        let stone = TileId(1);
+        let mut registry = TileRegistry::default();
+        let mut kind = TileKind::new(stone, "stone");
+        kind.solid = true;
+        registry.register(kind).unwrap();
+        let mut store = WorldTileStore::new(4);
+        for x in -8..16 {
+            store.set(IVec2::new(x, 0), stone);
+        }
+        for x in 4..8 {
+            store.set(IVec2::new(x, 1), stone);
+        }
+        for y in 1..4 {
+            store.set(IVec2::new(8, y), stone);
+        }
+        (store, registry)
+    }
+
+    #[test]
+    fn sweep_against_solid_tiles() {
+        let (store, registry) = world();
+        let collision = TileCollision {
+            store: &store,
+            registry: &registry,
+            tile_size: 16.0,
+        };
+        let half_size = Vec2::new(6.0, 6.0);
+
+        // falls onto the floor, and slides along it
+        let movement = collision.move_box(
+            Vec2::new(8.0, 40.0),
+            half_size,
+            Vec2::new(10.0, -100.0),
+            0.0,
+            false,
+        );
+        assert_eq!(movement.center, Vec2::new(18.0, 22.0));
+        assert!(!movement.hit_x);
+        assert!(movement.hit_y);
+
+        // is stopped by the side of the ledge, even when moving further than a tile
+        let movement = collision.move_box(
+            Vec2::new(50.0, 22.0),
+            half_size,
+            Vec2::new(40.0, 0.0),
+            0.0,
+            true,
+        );
+        assert_eq!(movement.center, Vec2::new(58.0, 22.0));
+        assert!(movement.hit_x);
+
+        // falling from the edge of the ledge doesn't catch on it
+        let movement = collision.move_box(
+            Vec2::new(58.0, 40.0),
+            half_size,
+            Vec2::new(0.0, -30.0),
+            0.0,
+            false,
+        );
+        assert_eq!(movement.center, Vec2::new(58.0, 22.0));
+    }
+
+    #[test]
+    fn step_up_ledges() {
+        let (store, registry) = world();
+        let collision = TileCollision {
+            store: &store,
+            registry: &registry,
+            tile_size: 16.0,
+        };
+        let half_size = Vec2::new(6.0, 6.0);
+
+        // grounded boxes walk up onto the ledge
+        let movement = collision.move_box(
+            Vec2::new(50.0, 22.0),
+            half_size,
+            Vec2::new(20.0, -1.0),
+            16.0,
+            true,
+        );
+        assert_eq!(movement.center, Vec2::new(70.0, 38.0));
+        assert!(!movement.hit_x);
+        assert!(movement.hit_y);
+
+        // boxes in the air don't
+        let movement = collision.move_box(
+            Vec2::new(50.0, 22.0),
+            half_size,
+            Vec2::new(20.0, 0.0),
+            16.0,
+            false,
+        );
+        assert_eq!(movement.center, Vec2::new(58.0, 22.0));
+
+        // walls higher than the step height stop them
+        let movement = collision.move_box(
+            Vec2::new(110.0, 38.0),
+            half_size,
+            Vec2::new(20.0, -1.0),
+            16.0,
+            true,
+        );
+        assert_eq!(movement.center, Vec2::new(122.0, 38.0));
+        assert!(movement.hit_x);
+    }
+}
@@ -0,0 +1,517 @@
+use crate::{
+    ChunkIndex, ChunkRng, GeneratedChunk, TileId, TileRegistry, WorldGenerator, WorldTileStore,
+};
+use bevy_math::{IRect, IVec2};
+
+/// Salt of the seeds of the areas a [DungeonGenerator] generates a dungeon for
+const AREA_SALT: u64 = 0x7a1c_53e9_d24b_086f;
+
+/// A tile of a [DungeonMap]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DungeonCell {
+    Wall,
+    Floor,
+    Door,
+}
+
+/// The tile kinds a dungeon is built from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DungeonTiles {
+    pub wall: TileId,
+    pub floor: TileId,
+    pub door: TileId,
+}
+
+impl DungeonTiles {
+    /// The tile kinds with the given names, if they are all registered
+    pub fn from_registry(
+        registry: &TileRegistry,
+        wall: &str,
+        floor: &str,
+        door: &str,
+    ) -> Option<Self> {
+        Some(DungeonTiles {
+            wall: registry.id(wall)?,
+            floor: registry.id(floor)?,
+            door: registry.id(door)?,
+        })
+    }
+
+    pub fn tile(&self, cell: DungeonCell) -> TileId {
+        match cell {
+            DungeonCell::Wall => self.wall,
+            DungeonCell::Floor => self.floor,
+            DungeonCell::Door => self.door,
+        }
+    }
+}
+
+/// A dungeon of walls, floors and doors, with `(0, 0)` in the bottom left corner. Its edges are
+/// always walls.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DungeonMap {
+    width: u32,
+    height: u32,
+    cells: Vec<DungeonCell>,
+    rooms: Vec<IRect>,
+}
+
+impl DungeonMap {
+    /// A dungeon that is all walls
+    pub fn new(width: u32, height: u32) -> Self {
+        DungeonMap {
+            width,
+            height,
+            cells: vec![DungeonCell::Wall; (width * height) as usize],
+            rooms: Vec::new(),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Option<DungeonCell> {
+        if x < self.width && y < self.height {
+            Some(self.cells[(y * self.width + x) as usize])
+        } else {
+            None
+        }
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, cell: DungeonCell) {
+        if x < self.width && y < self.height {
+            self.cells[(y * self.width + x) as usize] = cell;
+        }
+    }
+
+    /// Whether the tile at `position` can be walked on. Tiles outside the dungeon can't.
+    pub fn is_open(&self, position: IVec2) -> bool {
+        position.x >= 0
+            && position.y >= 0
+            && self
+                .get(position.x as u32, position.y as u32)
+                .map_or(false, |cell| cell != DungeonCell::Wall)
+    }
+
+    /// The floors of the rooms of the dungeon, which caves don't have
+    pub fn rooms(&self) -> &[IRect] {
+        &self.rooms
+    }
+
+    /// The groups of open tiles that can be walked between, the largest first
+    pub fn regions(&self) -> Vec<Vec<IVec2>> {
+        let mut seen = vec![false; self.cells.len()];
+        let mut regions = Vec::new();
+        let bounds = IRect::new(
+            IVec2::zero(),
+            IVec2::new(self.width as i32, self.height as i32),
+        );
+        for start in bounds.iter() {
+            let index = (start.y as u32 * self.width + start.x as u32) as usize;
+            if seen[index] || !self.is_open(start) {
+                continue;
+            }
+            seen[index] = true;
+            let mut region = Vec::new();
+            let mut stack = vec![start];
+            while let Some(position) = stack.pop() {
+                region.push(position);
+                for &offset in [
+                    IVec2::unit_x(),
+                    IVec2::unit_y(),
+                    -IVec2::unit_x(),
+                    -IVec2::unit_y(),
+                ]
+                .iter()
+                {
+                    let next = position + offset;
+                    if self.is_open(next) {
+                        let index = (next.y as u32 * self.width + next.x as u32) as usize;
+                        if !seen[index] {
+                            seen[index] = true;
+                            stack.push(next);
+                        }
+                    }
+                }
+            }
+            regions.push(region);
+        }
+        regions.sort_by_key(|region| std::cmp::Reverse(region.len()));
+        regions
+    }
+
+    /// Writes the dungeon into the store, with its bottom left corner on `origin`
+    pub fn write(&self, store: &mut WorldTileStore, origin: IVec2, tiles: &DungeonTiles) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let cell = self.cells[(y * self.width + x) as usize];
+                store.set(origin + IVec2::new(x as i32, y as i32), tiles.tile(cell));
+            }
+        }
+    }
+
+    fn carve(&mut self, position: IVec2) {
+        // the edges stay walls
+        if position.x > 0
+            && position.y > 0
+            && position.x < self.width as i32 - 1
+            && position.y < self.height as i32 - 1
+        {
+            self.set(position.x as u32, position.y as u32, DungeonCell::Floor);
+        }
+    }
+}
+
+/// Lays out dungeons for [DungeonGenerator]s
+pub trait DungeonLayout: Send + Sync + 'static {
+    fn generate(&self, width: u32, height: u32, rng: &mut ChunkRng) -> DungeonMap;
+}
+
+/// Rooms connected by corridors, laid out by binary space partitioning. The dungeon is split in
+/// two again and again, a room is placed in each part, and the rooms of parts that were split
+/// apart are connected by a corridor, so every room can be reached. Doors are placed where
+/// corridors enter rooms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BspRooms {
+    /// The smallest width or height of a part the dungeon is split into, walls around its room
+    /// included
+    pub min_split_size: u32,
+    /// The smallest width or height of the floor of a room
+    pub min_room_size: u32,
+}
+
+impl Default for BspRooms {
+    fn default() -> Self {
+        BspRooms {
+            min_split_size: 10,
+            min_room_size: 4,
+        }
+    }
+}
+
+impl BspRooms {
+    /// Places the rooms of `area` and connects them, and returns a tile in one of them
+    fn split(&self, area: IRect, rng: &mut ChunkRng, map: &mut DungeonMap) -> Option<IVec2> {
+        let size = area.size();
+        let min_split = self.min_split_size.max(3);
+        let split_x = size.x >= min_split * 2;
+        let split_y = size.y >= min_split * 2;
+        let vertical = match (split_x, split_y) {
+            (false, false) => return self.place_room(area, rng, map),
+            (true, false) => true,
+            (false, true) => false,
+            // split across the longer side, so parts don't get too narrow
+            (true, true) if size.x * 4 > size.y * 5 => true,
+            (true, true) if size.y * 4 > size.x * 5 => false,
+            (true, true) => rng.chance(0.5),
+        };
+        let length = if vertical { size.x } else { size.y };
+        let at = (min_split + rng.below(length - min_split * 2 + 1)) as i32;
+        let (first, second) = if vertical {
+            (
+                IRect::new(area.min, IVec2::new(area.min.x + at, area.max.y)),
+                IRect::new(IVec2::new(area.min.x + at, area.min.y), area.max),
+            )
+        } else {
+            (
+                IRect::new(area.min, IVec2::new(area.max.x, area.min.y + at)),
+                IRect::new(IVec2::new(area.min.x, area.min.y + at), area.max),
+            )
+        };
+        let first = self.split(first, rng, map);
+        let second = self.split(second, rng, map);
+        if let (Some(from), Some(to)) = (first, second) {
+            connect(map, from, to, rng.chance(0.5));
+        }
+        first.or(second)
+    }
+
+    /// Places a room in `area`, with walls around it, and returns its center
+    fn place_room(&self, area: IRect, rng: &mut ChunkRng, map: &mut DungeonMap) -> Option<IVec2> {
+        let inside = area.expand(-1);
+        let space = inside.size();
+        let min_size = self.min_room_size.max(1);
+        if inside.is_empty() || space.x < min_size || space.y < min_size {
+            return None;
+        }
+        let width = min_size + rng.below(space.x - min_size + 1);
+        let height = min_size + rng.below(space.y - min_size + 1);
+        let min = inside.min
+            + IVec2::new(
+                rng.below(space.x - width + 1) as i32,
+                rng.below(space.y - height + 1) as i32,
+            );
+        let room = IRect::new(min, min + IVec2::new(width as i32, height as i32));
+        for position in room.iter() {
+            map.carve(position);
+        }
+        map.rooms.push(room);
+        Some(room.min + (room.max - room.min) / 2)
+    }
+
+    /// Turns the tiles where corridors enter rooms into doors
+    fn place_doors(map: &mut DungeonMap) {
+        let is_wall = |map: &DungeonMap, position: IVec2| !map.is_open(position);
+        let mut doors = Vec::new();
+        for room in map.rooms.iter() {
+            let sides = (room.min.y..room.max.y)
+                .flat_map(|y| {
+                    vec![
+                        (IVec2::new(room.min.x - 1, y), IVec2::unit_y()),
+                        (IVec2::new(room.max.x, y), IVec2::unit_y()),
+                    ]
+                })
+                .chain((room.min.x..room.max.x).flat_map(|x| {
+                    vec![
+                        (IVec2::new(x, room.min.y - 1), IVec2::unit_x()),
+                        (IVec2::new(x, room.max.y), IVec2::unit_x()),
+                    ]
+                }));
+            for (position, along) in sides {
+                // a corridor enters here if the tile is open between walls along the room
+                if map.is_open(position)
+                    && is_wall(map, position + along)
+                    && is_wall(map, position - along)
+                {
+                    doors.push(position);
+                }
+            }
+        }
+        for door in doors {
+            map.set(door.x as u32, door.y as u32, DungeonCell::Door);
+        }
+    }
+}
+
+impl DungeonLayout for BspRooms {
+    fn generate(&self, width: u32, height: u32, rng: &mut ChunkRng) -> DungeonMap {
+        let mut map = DungeonMap::new(width, height);
+        let area = IRect::new(IVec2::zero(), IVec2::new(width as i32, height as i32));
+        self.split(area, rng, &mut map);
+        BspRooms::place_doors(&mut map);
+        map
+    }
+}
+
+/// Carves an L shaped corridor from `from` to `to`, horizontally first if `horizontal_first`
+fn connect(map: &mut DungeonMap, from: IVec2, to: IVec2, horizontal_first: bool) {
+    let corner = if horizontal_first {
+        IVec2::new(to.x, from.y)
+    } else {
+        IVec2::new(from.x, to.y)
+    };
+    for &(start, end) in [(from, corner), (corner, to)].iter() {
+        let min = start.min(end);
+        let max = start.max(end);
+        for position in IRect::new(min, max + IVec2::one()).iter() {
+            map.carve(position);
+        }
+    }
+}
+
+/// Caves grown with a cellular automaton. Tiles start out as walls at random, and become walls
+/// when most tiles around them are walls, and floors when most are floors, which smooths the
+/// noise into caves.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellularCaves {
+    /// The share of tiles that start out as walls, from 0 to 1
+    pub fill: f32,
+    /// How often the caves are smoothed
+    pub steps: u32,
+    /// Fills in all caves but the largest, so every floor can be reached
+    pub connected: bool,
+}
+
+impl Default for CellularCaves {
+    fn default() -> Self {
+        CellularCaves {
+            fill: 0.45,
+            steps: 4,
+            connected: true,
+        }
+    }
+}
+
+impl DungeonLayout for CellularCaves {
+    fn generate(&self, width: u32, height: u32, rng: &mut ChunkRng) -> DungeonMap {
+        let mut map = DungeonMap::new(width, height);
+        let area = IRect::new(IVec2::zero(), IVec2::new(width as i32, height as i32));
+        for position in area.iter() {
+            if !rng.chance(self.fill) {
+                map.carve(position);
+            }
+        }
+
+        for _ in 0..self.steps {
+            let previous = map.clone();
+            for position in area.expand(-1).iter() {
+                // tiles outside the dungeon count as walls
+                let walls = IRect::new(position - IVec2::one(), position + IVec2::splat(2))
+                    .iter()
+                    .filter(|&around| around != position && !previous.is_open(around))
+                    .count();
+                let cell = if walls > 4 {
+                    DungeonCell::Wall
+                } else if walls < 4 {
+                    DungeonCell::Floor
+                } else {
+                    continue;
+                };
+                map.set(position.x as u32, position.y as u32, cell);
+            }
+        }
+
+        if self.connected {
+            for region in map.regions().into_iter().skip(1) {
+                for position in region {
+                    map.set(position.x as u32, position.y as u32, DungeonCell::Wall);
+                }
+            }
+        }
+        map
+    }
+}
+
+/// Generates chunks from a dungeon [DungeonLayout], like [BspRooms] or [CellularCaves].
+///
+/// A dungeon spans an area of `chunks` by `chunks` chunks, so rooms and caves can be larger
+/// than a chunk. Each chunk lays out the dungeon of its area from the seed of the world and the
+/// position of the area, and keeps its part of it. Neighboring areas are separate dungeons, with
+/// walls between them.
+///
+/// ```ignore
+/// let tiles = DungeonTiles::from_registry(&registry, "wall", "floor", "door").unwrap();
+/// app.add_resource(ChunkGenerator::new(DungeonGenerator::new(BspRooms::default(), tiles)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct DungeonGenerator<L> {
+    layout: L,
+    tiles: DungeonTiles,
+    chunks: u32,
+}
+
+impl<L: DungeonLayout> DungeonGenerator<L> {
+    /// Generates dungeons that span 4 by 4 chunks
+    pub fn new(layout: L, tiles: DungeonTiles) -> Self {
+        DungeonGenerator {
+            layout,
+            tiles,
+            chunks: 4,
+        }
+    }
+
+    pub fn with_chunks(mut self, chunks: u32) -> Self {
+        self.chunks = chunks.max(1);
+        self
+    }
+
+    /// The dungeon of the area at `area`, for chunks of `chunk_size` tiles
+    pub fn area(&self, seed: u64, area: IVec2, chunk_size: u32) -> DungeonMap {
+        let size = chunk_size * self.chunks;
+        let mut rng = ChunkRng::new(seed ^ AREA_SALT, ChunkIndex(area));
+        self.layout.generate(size, size, &mut rng)
+    }
+}
+
+impl<L: DungeonLayout> WorldGenerator for DungeonGenerator<L> {
+    fn generate(&self, chunk: &mut GeneratedChunk) {
+        let chunks = self.chunks as i32;
+        let size = chunk.size();
+        let index = chunk.index.0;
+        let area = IVec2::new(index.x.div_euclid(chunks), index.y.div_euclid(chunks));
+        let map = self.area(chunk.seed, area, size);
+        let offset = (index - area * chunks) * size as i32;
+        for y in 0..size {
+            for x in 0..size {
+                let cell = map
+                    .get(offset.x as u32 + x, offset.y as u32 + y)
+                    .unwrap_or(DungeonCell::Wall);
+                chunk.set(x, y, self.tiles.tile(cell));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_walled(map: &DungeonMap) {
+        for x in 0..map.width() {
+            assert_eq!(map.get(x, 0), Some(DungeonCell::Wall));
+            assert_eq!(map.get(x, map.height() - 1), Some(DungeonCell::Wall));
+        }
+        for y in 0..map.height() {
+            assert_eq!(map.get(0, y), Some(DungeonCell::Wall));
+            assert_eq!(map.get(map.width() - 1, y), Some(DungeonCell::Wall));
+        }
+    }
+
+    #[test]
+    fn bsp_rooms() {
+        let mut rng = ChunkRng::from_seed(3);
+        let map = BspRooms::default().generate(64, 40, &mut rng);
+        assert_walled(&map);
+        assert!(map.rooms().len() >= 4);
+        for room in map.rooms() {
+            assert!(room.size().x >= 4 && room.size().y >= 4);
+            assert!(room.iter().all(|position| map.is_open(position)));
+        }
+        // every room can be reached from every other
+        assert_eq!(map.regions().len(), 1);
+
+        // doors are between walls
+        let mut doors = 0;
+        for y in 0..map.height() {
+            for x in 0..map.width() {
+                if map.get(x, y) != Some(DungeonCell::Door) {
+                    continue;
+                }
+                doors += 1;
+                let position = IVec2::new(x as i32, y as i32);
+                let between =
+                    |along: IVec2| !map.is_open(position + along) && !map.is_open(position - along);
+                assert!(between(IVec2::unit_x()) || between(IVec2::unit_y()));
+            }
+        }
+        assert!(doors >= map.rooms().len() - 1);
+    }
+
+    #[test]
+    fn connected_caves() {
+        let mut rng = ChunkRng::from_seed(5);
+        let map = CellularCaves::default().generate(48, 48, &mut rng);
+        assert_walled(&map);
+        let regions = map.regions();
+        assert_eq!(regions.len(), 1);
+        assert!(regions[0].len() > 48 * 48 / 10);
+        assert!(map.rooms().is_empty());
+    }
+
+    #[test]
+    fn dungeons_span_chunks() {
+        let tiles = DungeonTiles {
+            wall: TileId(1),
+            floor: TileId(2),
+            door: TileId(3),
+        };
+        let generator = DungeonGenerator::new(BspRooms::default(), tiles).with_chunks(2);
+        let map = generator.area(9, IVec2::new(-1, 0), 16);
+        for &index in [IVec2::new(-2, 0), IVec2::new(-1, 1)].iter() {
+            let mut chunk = GeneratedChunk::new(ChunkIndex(index), 16, 9);
+            generator.generate(&mut chunk);
+            let offset = (index - IVec2::new(-2, 0)) * 16;
+            for y in 0..16 {
+                for x in 0..16 {
+                    let cell = map.get(offset.x as u32 + x, offset.y as u32 + y).unwrap();
+                    assert_eq!(chunk.get(x, y), Some(tiles.tile(cell)));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use crate::{ChunkManager, TileAtlas, WorldTileStore};
+use bevy_core::Time;
+use bevy_ecs::{Query, Res};
+use bevy_math::Vec2;
+use bevy_render::camera::{Camera, OrthographicProjection};
+use bevy_transform::components::{GlobalTransform, Transform};
+use bevy_window::Windows;
+
+/// Pans a 2d camera while the cursor is near the edges of its window, like the camera of a
+/// strategy game.
+///
+/// The camera only pans over spawned chunks, so it never shows the world past them. Make the
+/// camera a [ChunkLoader](crate::ChunkLoader) so chunks keep spawning ahead of it, and raise
+/// [ChunkManager::load_radius] if the visible area is larger than the spawned chunks around it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgePan {
+    /// The speed of the camera in world units per second
+    pub speed: f32,
+    /// How close to an edge of the window, in logical pixels, the cursor pans the camera
+    pub edge: f32,
+}
+
+impl EdgePan {
+    /// Pans at `speed` world units per second while the cursor is within 16 pixels of an edge
+    pub fn new(speed: f32) -> Self {
+        EdgePan { speed, edge: 16.0 }
+    }
+
+    pub fn with_edge(mut self, edge: f32) -> Self {
+        self.edge = edge;
+        self
+    }
+
+    /// The direction to pan in, from the `cursor` position in a window of `size`, with each axis
+    /// `-1.0`, `0.0` or `1.0`
+    pub fn direction(&self, cursor: Vec2, size: Vec2) -> Vec2 {
+        let axis = |position: f32, size: f32| {
+            if position < self.edge {
+                -1.0
+            } else if position > size - self.edge {
+                1.0
+            } else {
+                0.0
+            }
+        };
+        Vec2::new(axis(cursor.x, size.x), axis(cursor.y, size.y))
+    }
+}
+
+/// Pans [EdgePan] cameras, without showing chunks that are not spawned
+pub fn edge_pan_system(
+    time: Res<Time>,
+    windows: Res<Windows>,
+    manager: Res<ChunkManager>,
+    tile_atlas: Res<TileAtlas>,
+    store: Res<WorldTileStore>,
+    mut cameras: Query<(
+        &EdgePan,
+        &Camera,
+        &OrthographicProjection,
+        &mut Transform,
+        &GlobalTransform,
+    )>,
+) {
+    let chunk_size = store.chunk_size();
+    let tile_size = tile_atlas.tile_size;
+    for (edge_pan, camera, projection, mut transform, global_transform) in cameras.iter_mut() {
+        let window = match windows.get(camera.window) {
+            Some(window) => window,
+            None => continue,
+        };
+        let cursor = match window.cursor_position() {
+            Some(cursor) => cursor,
+            None => continue,
+        };
+        let direction = edge_pan.direction(cursor, Vec2::new(window.width(), window.height()));
+        if direction == Vec2::zero() {
+            continue;
+        }
+
+        let scale = global_transform.scale.truncate();
+        let visible_min = Vec2::new(projection.left, projection.bottom) * scale;
+        let visible_max = Vec2::new(projection.right, projection.top) * scale;
+        let step = direction * edge_pan.speed * time.delta_seconds();
+        let position = transform.translation.truncate();
+        let panned = pan_over_spawned_chunks(
+            &manager,
+            position,
+            step,
+            (visible_min, visible_max),
+            chunk_size,
+            tile_size,
+        );
+        if panned != position {
+            transform.translation = panned.extend(transform.translation.z);
+        }
+    }
+}
+
+/// The position of a camera at `position` after panning by `step`, but only as far as it shows
+/// spawned chunks. The camera shows the area from `visible.0` to `visible.1` relative to its
+/// position.
+fn pan_over_spawned_chunks(
+    manager: &ChunkManager,
+    mut position: Vec2,
+    step: Vec2,
+    visible: (Vec2, Vec2),
+    chunk_size: u32,
+    tile_size: u32,
+) -> Vec2 {
+    let chunk_world_size = (chunk_size * tile_size) as f32;
+    let (visible_min, visible_max) = visible;
+    let is_spawned = |position: Vec2| {
+        manager.is_area_spawned(
+            position + visible_min,
+            position + visible_max,
+            chunk_size,
+            tile_size,
+        )
+    };
+    // each axis is panned on its own, so the camera still slides along the spawned chunks when
+    // it can't pan diagonally
+    for &axis in &[Vec2::unit_x(), Vec2::unit_y()] {
+        let distance = step.dot(axis);
+        if distance == 0.0 {
+            continue;
+        }
+        let mut panned = position + axis * distance;
+        if !is_spawned(panned) {
+            // pan up to the edge of the chunks the camera shows now
+            let to_edge = if distance > 0.0 {
+                let max = (position + visible_max).dot(axis);
+                (max / chunk_world_size).ceil() * chunk_world_size - max
+            } else {
+                let min = (position + visible_min).dot(axis);
+                (min / chunk_world_size).floor() * chunk_world_size - min
+            };
+            panned = position + axis * to_edge;
+            if !is_spawned(panned) {
+                continue;
+            }
+        }
+        position = panned;
+    }
+    position
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chunk_manager_system, ChunkGenerator, ChunkLoader};
+    use bevy_ecs::{IntoSystem, Resources, Stage, SystemStage, World};
+    use bevy_math::Vec3;
+
+    #[test]
+    fn pan_direction() {
+        let edge_pan = EdgePan::new(100.0).with_edge(10.0);
+        let size = Vec2::new(200.0, 100.0);
+        assert_eq!(
+            edge_pan.direction(Vec2::new(195.0, 50.0), size),
+            Vec2::new(1.0, 0.0)
+        );
+        assert_eq!(
+            edge_pan.direction(Vec2::new(5.0, 2.0), size),
+            Vec2::new(-1.0, -1.0)
+        );
+        assert_eq!(
+            edge_pan.direction(Vec2::new(100.0, 50.0), size),
+            Vec2::zero()
+        );
+    }
+
+    #[test]
+    fn pan_only_over_spawned_chunks() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(TileAtlas {
+            tile_size: 1,
+            ..Default::default()
+        });
+        resources.insert(WorldTileStore::new(10));
+        let mut manager = ChunkManager::default();
+        manager.load_radius = 1;
+        resources.insert(manager);
+        resources.insert(ChunkGenerator::default());
+        world.spawn((
+            ChunkLoader,
+            GlobalTransform::from_translation(Vec3::new(5.0, 5.0, 0.0)),
+        ));
+        let mut stage = SystemStage::serial();
+        stage.add_system(chunk_manager_system.system());
+        stage.initialize(&mut world, &mut resources);
+        stage.run(&mut world, &mut resources);
+
+        // the chunks from -10 to 20 are spawned, and the camera shows 4 units on every side
+        let manager = resources.get::<ChunkManager>().unwrap();
+        let visible = (Vec2::splat(-4.0), Vec2::splat(4.0));
+        let pan = |position: Vec2, step: Vec2| {
+            pan_over_spawned_chunks(&manager, position, step, visible, 10, 1)
+        };
+        assert_eq!(
+            pan(Vec2::new(5.0, 5.0), Vec2::new(3.0, -3.0)),
+            Vec2::new(8.0, 2.0)
+        );
+        // the camera stops at the edge of the spawned chunks, and keeps panning along it
+        assert_eq!(
+            pan(Vec2::new(14.0, 5.0), Vec2::new(3.0, 3.0)),
+            Vec2::new(16.0, 8.0)
+        );
+        assert_eq!(
+            pan(Vec2::new(16.0, -5.0), Vec2::new(3.0, -3.0)),
+            Vec2::new(16.0, -6.0)
+        );
+    }
+}
@@ -0,0 +1,482 @@
+use crate::{ChunkIndex, ChunkManager, TileAtlas, TileRegistry, WorldTileStore};
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::{Commands, Entity, Query, Res, ResMut};
+use bevy_math::{IVec2, Vec2};
+use bevy_render::{
+    prelude::Visible,
+    texture::{Extent3d, FilterMode, SamplerDescriptor, Texture, TextureDimension, TextureFormat},
+};
+use bevy_sprite::{entity::SpriteBundle, ColorMaterial, Sprite};
+use bevy_transform::components::{GlobalTransform, Transform};
+use bevy_utils::{HashMap, HashSet};
+
+/// Sees the tiles up to `radius` tiles around it through the [FogOfWar]. Solid tiles are seen,
+/// but hide the tiles behind them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FogViewer {
+    pub radius: u32,
+}
+
+impl FogViewer {
+    pub fn new(radius: u32) -> Self {
+        FogViewer { radius }
+    }
+}
+
+/// The tiles [FogViewer]s see, and the tiles they have seen before. Set `enabled` to draw the
+/// fog over the spawned chunks, as an overlay that hides the tiles that were never seen and
+/// darkens the tiles that aren't seen anymore.
+///
+/// What a viewer sees is only computed again when it moves to another tile or its radius
+/// changes, and only the tiles that were hidden or revealed are drawn again. Call
+/// [FogOfWar::refresh] after changing which tiles are solid.
+#[derive(Debug)]
+pub struct FogOfWar {
+    pub enabled: bool,
+    /// How dark explored tiles that no viewer sees are, from 0 to 1
+    pub explored_darkness: f32,
+    /// The z of the overlays, which has to be above everything the fog hides
+    pub overlay_z: f32,
+    /// The number of viewers that see each visible tile
+    visible: HashMap<IVec2, u32>,
+    explored: HashSet<IVec2>,
+    sights: HashMap<Entity, Sight>,
+    /// The tiles that were hidden or revealed since the overlays were last drawn
+    changed: HashSet<IVec2>,
+    overlays: HashMap<ChunkIndex, FogOverlay>,
+    refresh: bool,
+}
+
+impl Default for FogOfWar {
+    fn default() -> Self {
+        FogOfWar {
+            enabled: false,
+            explored_darkness: 0.6,
+            overlay_z: 100.0,
+            visible: Default::default(),
+            explored: Default::default(),
+            sights: Default::default(),
+            changed: Default::default(),
+            overlays: Default::default(),
+            refresh: false,
+        }
+    }
+}
+
+/// What a viewer saw from `tile`
+#[derive(Debug)]
+struct Sight {
+    tile: IVec2,
+    radius: u32,
+    tiles: Vec<IVec2>,
+}
+
+/// The sprite the fog over a chunk is drawn with, with a pixel for every tile
+#[derive(Debug)]
+struct FogOverlay {
+    entity: Entity,
+    texture: Handle<Texture>,
+}
+
+impl FogOfWar {
+    /// Whether a viewer sees `tile`
+    pub fn is_visible(&self, tile: IVec2) -> bool {
+        self.visible.contains_key(&tile)
+    }
+
+    /// Whether a viewer has ever seen `tile`
+    pub fn is_explored(&self, tile: IVec2) -> bool {
+        self.explored.contains(&tile)
+    }
+
+    /// How dark the fog over `tile` is, from 0 for visible tiles to 1 for tiles that were never
+    /// seen
+    pub fn darkness(&self, tile: IVec2) -> f32 {
+        if self.is_visible(tile) {
+            0.0
+        } else if self.is_explored(tile) {
+            self.explored_darkness
+        } else {
+            1.0
+        }
+    }
+
+    /// Computes what all viewers see again in the next frame
+    pub fn refresh(&mut self) {
+        self.refresh = true;
+    }
+
+    /// Forgets the explored tiles, so only the tiles viewers see now are revealed
+    pub fn clear_explored(&mut self) {
+        let visible = &self.visible;
+        self.changed.extend(
+            self.explored
+                .drain()
+                .filter(|tile| !visible.contains_key(tile)),
+        );
+        self.explored.extend(self.visible.keys().copied());
+    }
+
+    /// Replaces what `viewer` sees with the tiles seen from `tile`. Tiles it sees from both
+    /// tiles stay visible, so they aren't drawn again.
+    fn see(&mut self, viewer: Entity, tile: IVec2, radius: u32, is_opaque: impl Fn(IVec2) -> bool) {
+        let mut tiles = HashSet::default();
+        shadowcast(tile, radius, is_opaque, |tile| {
+            tiles.insert(tile);
+        });
+        for &tile in tiles.iter() {
+            let count = self.visible.entry(tile).or_insert(0);
+            if *count == 0 {
+                self.changed.insert(tile);
+            }
+            *count += 1;
+            self.explored.insert(tile);
+        }
+        let sight = Sight {
+            tile,
+            radius,
+            tiles: tiles.into_iter().collect(),
+        };
+        if let Some(previous) = self.sights.insert(viewer, sight) {
+            self.unsee(previous);
+        }
+    }
+
+    /// Removes what `viewer` saw from the visible tiles
+    fn forget(&mut self, viewer: Entity) {
+        if let Some(sight) = self.sights.remove(&viewer) {
+            self.unsee(sight);
+        }
+    }
+
+    fn unsee(&mut self, sight: Sight) {
+        for tile in sight.tiles {
+            let count = self.visible.get_mut(&tile).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                self.visible.remove(&tile);
+                self.changed.insert(tile);
+            }
+        }
+    }
+
+    /// Draws the fog over the tiles of the chunk at `index` into `texture`
+    fn paint(&self, texture: &mut Texture, index: ChunkIndex, tiles: &[(u32, u32)]) {
+        let size = texture.size.width;
+        for &(x, y) in tiles {
+            let tile = index.0 * size as i32 + IVec2::new(x as i32, y as i32);
+            let alpha = (self.darkness(tile) * 255.0).round() as u8;
+            // texture rows go from top to bottom
+            let min = [x, size - 1 - y];
+            texture.fill_rect(min, [min[0] + 1, min[1] + 1], &[0, 0, 0, alpha]);
+        }
+    }
+}
+
+/// Calls `visit` with the tiles up to `radius` tiles from `origin` that can be seen from it, with
+/// recursive shadowcasting over the eight octants around it. Opaque tiles are visited, but hide
+/// the tiles behind them. Tiles on the edges between octants are visited more than once.
+pub fn shadowcast(
+    origin: IVec2,
+    radius: u32,
+    is_opaque: impl Fn(IVec2) -> bool,
+    mut visit: impl FnMut(IVec2),
+) {
+    // how the rows and columns of each octant map to x and y
+    const OCTANTS: [(i32, i32, i32, i32); 8] = [
+        (1, 0, 0, 1),
+        (0, 1, 1, 0),
+        (0, -1, 1, 0),
+        (-1, 0, 0, 1),
+        (-1, 0, 0, -1),
+        (0, -1, -1, 0),
+        (0, 1, -1, 0),
+        (1, 0, 0, -1),
+    ];
+    visit(origin);
+    for &octant in OCTANTS.iter() {
+        let mut caster = Shadowcaster {
+            origin,
+            radius: radius as i32,
+            octant,
+            is_opaque: &is_opaque,
+            visit: &mut visit,
+        };
+        caster.cast(1, 1.0, 0.0);
+    }
+}
+
+struct Shadowcaster<'a, O, V> {
+    origin: IVec2,
+    radius: i32,
+    octant: (i32, i32, i32, i32),
+    is_opaque: &'a O,
+    visit: &'a mut V,
+}
+
+impl<'a, O: Fn(IVec2) -> bool, V: FnMut(IVec2)> Shadowcaster<'a, O, V> {
+    /// Visits the tiles of the octant from `row` on that are lit between the slopes `start` and
+    /// `end`, and casts the light that passes between opaque tiles further on its own
+    fn cast(&mut self, row: i32, mut start: f32, end: f32) {
+        if start < end {
+            return;
+        }
+        let (xx, xy, yx, yy) = self.octant;
+        let mut next_start = start;
+        for distance in row..=self.radius {
+            let dy = -distance;
+            let mut blocked = false;
+            for dx in -distance..=0 {
+                let left_slope = (dx as f32 - 0.5) / (dy as f32 + 0.5);
+                let right_slope = (dx as f32 + 0.5) / (dy as f32 - 0.5);
+                if start < right_slope {
+                    continue;
+                }
+                if end > left_slope {
+                    break;
+                }
+                let tile = self.origin + IVec2::new(dx * xx + dy * xy, dx * yx + dy * yy);
+                if dx * dx + dy * dy <= self.radius * self.radius {
+                    (self.visit)(tile);
+                }
+                let opaque = (self.is_opaque)(tile);
+                if blocked {
+                    if opaque {
+                        next_start = right_slope;
+                    } else {
+                        blocked = false;
+                        start = next_start;
+                    }
+                } else if opaque && distance < self.radius {
+                    blocked = true;
+                    self.cast(distance + 1, start, left_slope);
+                    next_start = right_slope;
+                }
+            }
+            if blocked {
+                break;
+            }
+        }
+    }
+}
+
+/// Computes what [FogViewer]s see when they move to another tile
+pub fn fog_of_war_system(
+    mut fog: ResMut<FogOfWar>,
+    tile_atlas: Res<TileAtlas>,
+    registry: Res<TileRegistry>,
+    store: Res<WorldTileStore>,
+    viewers: Query<(Entity, &FogViewer, &GlobalTransform)>,
+) {
+    let refresh = std::mem::replace(&mut fog.refresh, false);
+    let is_opaque = |tile: IVec2| store.is_solid(tile, &registry);
+    let mut seen = HashSet::default();
+    for (entity, viewer, transform) in viewers.iter() {
+        seen.insert(entity);
+        let tile = IVec2::from_grid_position(
+            transform.translation.truncate(),
+            tile_atlas.tile_size as f32,
+        );
+        let moved = fog.sights.get(&entity).map_or(true, |sight| {
+            sight.tile != tile || sight.radius != viewer.radius
+        });
+        if moved || refresh {
+            fog.see(entity, tile, viewer.radius, is_opaque);
+        }
+    }
+    let removed = fog
+        .sights
+        .keys()
+        .filter(|entity| !seen.contains(*entity))
+        .copied()
+        .collect::<Vec<_>>();
+    for entity in removed {
+        fog.forget(entity);
+    }
+}
+
+/// Spawns and despawns the overlays of the [FogOfWar] with the chunks they cover, and draws the
+/// tiles that were hidden or revealed into them
+pub fn fog_overlay_system(
+    commands: &mut Commands,
+    mut fog: ResMut<FogOfWar>,
+    manager: Res<ChunkManager>,
+    tile_atlas: Res<TileAtlas>,
+    store: Res<WorldTileStore>,
+    mut textures: ResMut<Assets<Texture>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let fog = &mut *fog;
+    let enabled = fog.enabled;
+    fog.overlays.retain(|index, overlay| {
+        let keep = enabled && manager.get_entity(*index).is_some();
+        if !keep {
+            commands.despawn(overlay.entity);
+        }
+        keep
+    });
+    if !enabled {
+        fog.changed.clear();
+        return;
+    }
+
+    let chunk_size = store.chunk_size();
+    let mut changed: HashMap<ChunkIndex, Vec<(u32, u32)>> = HashMap::default();
+    for tile in fog.changed.drain() {
+        let (index, position) = store.locate(tile);
+        changed.entry(index).or_default().push(position);
+    }
+    for (index, tiles) in changed {
+        if let Some(texture) = fog
+            .overlays
+            .get(&index)
+            .and_then(|overlay| textures.get_mut(&overlay.texture))
+        {
+            fog.paint(texture, index, &tiles);
+        }
+    }
+
+    let all_tiles = (0..chunk_size)
+        .flat_map(|y| (0..chunk_size).map(move |x| (x, y)))
+        .collect::<Vec<_>>();
+    for (index, _) in manager.iter_spawned() {
+        if fog.overlays.contains_key(&index) {
+            continue;
+        }
+        let mut texture = Texture::new_fill(
+            Extent3d::new(chunk_size, chunk_size, 1),
+            TextureDimension::D2,
+            &[0, 0, 0, 255],
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        texture.sampler = SamplerDescriptor {
+            mag_filter: FilterMode::Nearest,
+            min_filter: FilterMode::Nearest,
+            ..Default::default()
+        };
+        fog.paint(&mut texture, index, &all_tiles);
+        let texture = textures.add(texture);
+        let size = (chunk_size * tile_atlas.tile_size) as f32;
+        let translation = index
+            .center(chunk_size, tile_atlas.tile_size)
+            .extend(fog.overlay_z);
+        commands.spawn(SpriteBundle {
+            sprite: Sprite::new(Vec2::new(size, size)),
+            material: materials.add(ColorMaterial::texture(texture.clone())),
+            visible: Visible {
+                is_transparent: true,
+                ..Default::default()
+            },
+            transform: Transform::from_translation(translation),
+            global_transform: GlobalTransform::from_translation(translation),
+            ..Default::default()
+        });
+        fog.overlays.insert(
+            index,
+            FogOverlay {
+                entity: commands.current_entity().unwrap(),
+                texture,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TileId, TileKind};
+    use bevy_ecs::{IntoSystem, Resources, Stage, SystemStage, World};
+    use bevy_math::Vec3;
+
+    #[test]
+    fn shadowcast_behind_walls() {
+        // a wall from (2, -1) to (2, 1)
+        let is_opaque = |tile: IVec2| tile.x == 2 && tile.y.abs() <= 1;
+        let mut seen = HashSet::default();
+        shadowcast(IVec2::zero(), 5, is_opaque, |tile| {
+            seen.insert(tile);
+        });
+        assert!(seen.contains(&IVec2::zero()));
+        // the wall is seen, but not what is behind it
+        assert!(seen.contains(&IVec2::new(2, 0)));
+        assert!(!seen.contains(&IVec2::new(3, 0)));
+        assert!(!seen.contains(&IVec2::new(5, 1)));
+        // the other directions are open up to the radius
+        assert!(seen.contains(&IVec2::new(-5, 0)));
+        assert!(seen.contains(&IVec2::new(0, 5)));
+        assert!(seen.contains(&IVec2::new(3, 4)));
+        assert!(!seen.contains(&IVec2::new(4, 4)));
+        assert!(!seen.contains(&IVec2::new(-6, 0)));
+    }
+
+    #[test]
+    fn update_when_viewers_move() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        let mut registry = TileRegistry::default();
+        let mut stone = TileKind::new(TileId(1), "stone");
+        stone.solid = true;
+        registry.register(stone).unwrap();
+        let mut store = WorldTileStore::new(8);
+        for y in -3..=3 {
+            store.set(IVec2::new(2, y), TileId(1));
+        }
+        resources.insert(registry);
+        resources.insert(store);
+        resources.insert(TileAtlas {
+            tile_size: 10,
+            ..Default::default()
+        });
+        resources.insert(FogOfWar::default());
+        let viewer = world.spawn((
+            FogViewer::new(4),
+            GlobalTransform::from_translation(Vec3::new(5.0, 5.0, 0.0)),
+        ));
+        let mut stage = SystemStage::serial();
+        stage.add_system(fog_of_war_system.system());
+        stage.initialize(&mut world, &mut resources);
+        stage.run(&mut world, &mut resources);
+
+        {
+            let fog = resources.get::<FogOfWar>().unwrap();
+            assert!(fog.is_visible(IVec2::new(0, 0)));
+            assert!(fog.is_visible(IVec2::new(-4, 0)));
+            assert!(fog.is_visible(IVec2::new(2, 0)));
+            assert!(!fog.is_visible(IVec2::new(3, 0)));
+            assert_eq!(fog.darkness(IVec2::new(3, 0)), 1.0);
+        }
+
+        // moving within the tile doesn't compute the sight again
+        resources.get_mut::<FogOfWar>().unwrap().changed.clear();
+        world
+            .get_mut::<GlobalTransform>(viewer)
+            .unwrap()
+            .translation = Vec3::new(8.0, 2.0, 0.0);
+        stage.run(&mut world, &mut resources);
+        assert!(resources.get::<FogOfWar>().unwrap().changed.is_empty());
+
+        // moving to another tile hides the tiles that are out of sight, but they stay explored
+        world
+            .get_mut::<GlobalTransform>(viewer)
+            .unwrap()
+            .translation = Vec3::new(-25.0, 5.0, 0.0);
+        stage.run(&mut world, &mut resources);
+        {
+            let fog = resources.get::<FogOfWar>().unwrap();
+            assert!(fog.is_visible(IVec2::new(-7, 0)));
+            assert!(!fog.is_visible(IVec2::new(2, 0)));
+            assert!(fog.is_explored(IVec2::new(2, 0)));
+            assert_eq!(fog.darkness(IVec2::new(2, 0)), fog.explored_darkness);
+            assert!(fog.changed.contains(&IVec2::new(2, 0)));
+            assert!(fog.changed.contains(&IVec2::new(-7, 0)));
+            assert!(!fog.changed.contains(&IVec2::new(0, 0)));
+        }
+
+        // despawned viewers don't see anything
+        world.despawn(viewer).unwrap();
+        stage.run(&mut world, &mut resources);
+        let fog = resources.get::<FogOfWar>().unwrap();
+        assert!(!fog.is_visible(IVec2::new(-2, 0)));
+        assert!(fog.is_explored(IVec2::new(-2, 0)));
+    }
+}
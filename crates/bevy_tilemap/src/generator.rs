@@ -0,0 +1,378 @@
+use crate::{ChunkIndex, PackedChunk, TileId, WorldTileStore};
+use bevy_ecs::{Res, ResMut};
+use bevy_math::IVec2;
+use bevy_tasks::AsyncComputeTaskPool;
+use bevy_utils::HashSet;
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+/// Generates the tiles of chunks, like terrain from noise or rooms from rules. Set one with
+/// [ChunkGenerator::new] to generate the chunks that are not in the [WorldTileStore] yet.
+///
+/// Closures that take a [GeneratedChunk] are world generators too:
+///
+/// ```ignore
+/// app.add_resource(
+///     ChunkGenerator::new(|chunk: &mut GeneratedChunk| {
+///         for y in 0..chunk.size() {
+///             for x in 0..chunk.size() {
+///                 if chunk.rng.chance(0.1) {
+///                     chunk.set(x, y, rock);
+///                 }
+///             }
+///         }
+///     })
+///     .with_seed(42),
+/// );
+/// ```
+pub trait WorldGenerator: Send + Sync + 'static {
+    /// Fills in the tiles of a chunk, which start out empty. Chunks are generated in any order,
+    /// on any thread, so the tiles must only depend on the chunk, its seed and its
+    /// [ChunkRng], for a world to come out the same every time it is generated.
+    fn generate(&self, chunk: &mut GeneratedChunk);
+}
+
+impl<F: Fn(&mut GeneratedChunk) + Send + Sync + 'static> WorldGenerator for F {
+    fn generate(&self, chunk: &mut GeneratedChunk) {
+        self(chunk)
+    }
+}
+
+/// A chunk being filled in by a [WorldGenerator]
+#[derive(Debug)]
+pub struct GeneratedChunk {
+    pub index: ChunkIndex,
+    /// The seed of the world, for noise that has to line up across chunks
+    pub seed: u64,
+    /// Random numbers for this chunk, seeded from the seed of the world and the chunk index
+    pub rng: ChunkRng,
+    tiles: PackedChunk,
+}
+
+impl GeneratedChunk {
+    pub fn new(index: ChunkIndex, size: u32, seed: u64) -> Self {
+        GeneratedChunk {
+            index,
+            seed,
+            rng: ChunkRng::new(seed, index),
+            tiles: PackedChunk::new(size),
+        }
+    }
+
+    /// The number of tiles along the sides of the chunk
+    pub fn size(&self) -> u32 {
+        self.tiles.size()
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Option<TileId> {
+        self.tiles.get(x, y)
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, tile: TileId) {
+        self.tiles.set(x, y, tile);
+    }
+
+    /// The position in the world of the tile at `x`, `y` in the chunk
+    pub fn world_tile(&self, x: u32, y: u32) -> IVec2 {
+        self.index.0 * self.size() as i32 + IVec2::new(x as i32, y as i32)
+    }
+
+    pub fn into_tiles(self) -> PackedChunk {
+        self.tiles
+    }
+}
+
+/// A small, fast random number generator (SplitMix64) that gives the same numbers on every
+/// platform. It is not suitable for anything that has to be unpredictable.
+#[derive(Debug, Clone)]
+pub struct ChunkRng {
+    state: u64,
+}
+
+impl ChunkRng {
+    /// A generator for the chunk at `index` of the world with `seed`. Every chunk gets different
+    /// numbers, even neighboring ones.
+    pub fn new(seed: u64, index: ChunkIndex) -> Self {
+        let position = ((index.0.x as u32 as u64) << 32) | index.0.y as u32 as u64;
+        let mut rng = ChunkRng::from_seed(seed);
+        ChunkRng::from_seed(rng.next_u64() ^ position)
+    }
+
+    pub fn from_seed(seed: u64) -> Self {
+        let mut rng = ChunkRng { state: seed };
+        // mixes seeds that differ in few bits apart
+        rng.next_u64();
+        rng
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// A number from 0 up to but not including 1
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    /// A number from 0 up to but not including `bound`
+    pub fn below(&mut self, bound: u32) -> u32 {
+        ((self.next_u32() as u64 * bound as u64) >> 32) as u32
+    }
+
+    /// `true` with a `probability` from 0 to 1
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+}
+
+/// Generates the tiles of chunks that are about to be spawned, but are not in the
+/// [WorldTileStore] yet, like the parts of a procedural world that were never visited.
+///
+/// Chunks are generated by a [WorldGenerator] on the [AsyncComputeTaskPool], so generating many
+/// chunks at once doesn't cause a hitch. The [ChunkManager](crate::ChunkManager) only spawns a
+/// chunk once its tiles are generated, and generated chunks are kept in the store like loaded
+/// ones. Without a world generator, which is the default, chunks missing from the store are
+/// spawned empty.
+#[derive(Default)]
+pub struct ChunkGenerator {
+    generator: Option<Arc<dyn WorldGenerator>>,
+    seed: u64,
+    requested: Vec<ChunkIndex>,
+    pending: HashSet<ChunkIndex>,
+    generated: Arc<Mutex<Vec<(ChunkIndex, PackedChunk)>>>,
+}
+
+impl ChunkGenerator {
+    /// Generates chunks with `generator`, with a seed of 0
+    pub fn new(generator: impl WorldGenerator) -> Self {
+        ChunkGenerator {
+            generator: Some(Arc::new(generator)),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.generator.is_some()
+    }
+
+    /// Generates the chunk at `index` right away, on this thread
+    pub fn generate(&self, index: ChunkIndex, chunk_size: u32) -> Option<PackedChunk> {
+        let generator = self.generator.as_ref()?;
+        Some(generate_chunk(&**generator, index, chunk_size, self.seed))
+    }
+
+    /// Whether the chunk is being generated
+    pub fn is_generating(&self, index: ChunkIndex) -> bool {
+        self.pending.contains(&index)
+    }
+
+    /// The number of chunks being generated
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Generates a chunk, unless it is already being generated
+    pub(crate) fn request(&mut self, index: ChunkIndex, store: &mut WorldTileStore) {
+        if self.pending.insert(index) {
+            store.start_generating(index);
+            self.requested.push(index);
+        }
+    }
+
+    /// Stores the chunks that finished generating. Tiles set while they were generated are kept,
+    /// and chunks that were loaded in the meantime aren't replaced.
+    pub(crate) fn store_generated(&mut self, store: &mut WorldTileStore) {
+        for (index, chunk) in self.generated.lock().drain(..) {
+            self.pending.remove(&index);
+            store.store_generated(index, chunk);
+        }
+    }
+}
+
+fn generate_chunk(
+    generator: &dyn WorldGenerator,
+    index: ChunkIndex,
+    chunk_size: u32,
+    seed: u64,
+) -> PackedChunk {
+    let mut chunk = GeneratedChunk::new(index, chunk_size, seed);
+    generator.generate(&mut chunk);
+    chunk.into_tiles()
+}
+
+/// Generates the chunks the [ChunkManager](crate::ChunkManager) asked the [ChunkGenerator] for
+/// on the [AsyncComputeTaskPool]
+pub fn chunk_generation_system(
+    pool: Res<AsyncComputeTaskPool>,
+    store: Res<WorldTileStore>,
+    mut generator: ResMut<ChunkGenerator>,
+) {
+    let world_generator = match &generator.generator {
+        Some(world_generator) => world_generator.clone(),
+        None => return,
+    };
+    let chunk_size = store.chunk_size();
+    let seed = generator.seed;
+    for index in std::mem::take(&mut generator.requested) {
+        let world_generator = world_generator.clone();
+        let generated = generator.generated.clone();
+        pool.spawn(async move {
+            let chunk = generate_chunk(&*world_generator, index, chunk_size, seed);
+            generated.lock().push((index, chunk));
+        })
+        .detach();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chunk_manager_system, Chunk, ChunkLoader, ChunkManager, TileAtlas};
+    use bevy_ecs::{IntoSystem, Resources, Stage, SystemStage, World};
+    use bevy_math::Vec3;
+    use bevy_tasks::TaskPoolBuilder;
+    use bevy_transform::components::GlobalTransform;
+
+    #[test]
+    fn generate_chunks_in_the_background() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        // without threads, tasks only run when the pool is ticked
+        let pool = TaskPoolBuilder::new().num_threads(0).build();
+        resources.insert(AsyncComputeTaskPool(pool.clone()));
+        resources.insert(TileAtlas {
+            tile_size: 1,
+            ..Default::default()
+        });
+        let mut store = WorldTileStore::new(4);
+        store.set(IVec2::new(5, 0), TileId(9));
+        resources.insert(store);
+        let mut manager = ChunkManager::default();
+        manager.load_radius = 1;
+        resources.insert(manager);
+        resources.insert(ChunkGenerator::new(|chunk: &mut GeneratedChunk| {
+            chunk.set(0, 0, TileId(1 + chunk.index.0.y as u16));
+        }));
+        world.spawn((
+            ChunkLoader,
+            GlobalTransform::from_translation(Vec3::new(2.0, 6.0, 0.0)),
+        ));
+
+        let mut stage = SystemStage::serial();
+        stage.add_system(chunk_manager_system.system());
+        stage.add_system(chunk_generation_system.system());
+        stage.initialize(&mut world, &mut resources);
+        stage.run(&mut world, &mut resources);
+
+        // the stored chunk is spawned right away, the others once they are generated
+        let spawned = |world: &World| world.query::<&ChunkIndex>().count();
+        assert_eq!(spawned(&world), 1);
+        assert_eq!(resources.get::<ChunkGenerator>().unwrap().pending(), 8);
+        // a chunk edited while it is generated keeps the edit, and its generated tiles, and
+        // isn't spawned before it is generated
+        resources
+            .get_mut::<WorldTileStore>()
+            .unwrap()
+            .set(IVec2::new(-3, 8), TileId(7));
+        stage.run(&mut world, &mut resources);
+        assert_eq!(spawned(&world), 1);
+
+        while pool.try_tick() {}
+        stage.run(&mut world, &mut resources);
+        assert_eq!(spawned(&world), 9);
+
+        let generator = resources.get::<ChunkGenerator>().unwrap();
+        assert_eq!(generator.pending(), 0);
+        let store = resources.get::<WorldTileStore>().unwrap();
+        assert_eq!(store.get(IVec2::new(4, 8)), TileId(3));
+        assert_eq!(store.get(IVec2::new(5, 0)), TileId(9));
+        assert_eq!(store.get(IVec2::new(-3, 8)), TileId(7));
+        assert_eq!(store.get(IVec2::new(-4, 8)), TileId(3));
+        // generated chunks can be generated again, so they aren't saved
+        assert!(!store.is_dirty(ChunkIndex(IVec2::new(1, 2))));
+        assert!(store.is_dirty(ChunkIndex(IVec2::new(-1, 2))));
+        for (index, chunk) in world.query::<(&ChunkIndex, &Chunk)>() {
+            match (index.0.x, index.0.y) {
+                (1, 0) => assert_eq!(chunk.get(1, 0), Some(TileId(9))),
+                (-1, 2) => {
+                    assert_eq!(chunk.get(0, 0), Some(TileId(3)));
+                    assert_eq!(chunk.get(1, 0), Some(TileId(7)));
+                }
+                _ => assert_eq!(chunk.get(0, 0), Some(TileId(1 + index.0.y as u16))),
+            }
+        }
+    }
+
+    /// Scatters rocks over grass, and a wall along the row at y 0 that has to line up across
+    /// chunks
+    struct Meadow;
+
+    impl WorldGenerator for Meadow {
+        fn generate(&self, chunk: &mut GeneratedChunk) {
+            for y in 0..chunk.size() {
+                for x in 0..chunk.size() {
+                    let tile = if chunk.world_tile(x, y).y == 0 {
+                        TileId(3)
+                    } else if chunk.rng.chance(0.2) {
+                        TileId(2)
+                    } else {
+                        TileId(1)
+                    };
+                    chunk.set(x, y, tile);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn deterministic_generation() {
+        let generator = ChunkGenerator::new(Meadow).with_seed(7);
+        let a = ChunkIndex(IVec2::new(-1, 0));
+        let b = ChunkIndex(IVec2::new(0, 0));
+        // the same in any order
+        let b_first = generator.generate(b, 8).unwrap();
+        let a_second = generator.generate(a, 8).unwrap();
+        assert_eq!(generator.generate(a, 8).unwrap(), a_second);
+        assert_eq!(generator.generate(b, 8).unwrap(), b_first);
+        // but different for other chunks and other seeds
+        assert_ne!(a_second, b_first);
+        let reseeded = ChunkGenerator::new(Meadow).with_seed(8);
+        assert_ne!(reseeded.generate(b, 8).unwrap(), b_first);
+        assert!(ChunkGenerator::default().generate(b, 8).is_none());
+
+        for x in 0..8 {
+            assert_eq!(a_second.get(x, 0), Some(TileId(3)));
+            assert_eq!(b_first.get(x, 0), Some(TileId(3)));
+        }
+        let rocks = b_first.iter().filter(|tile| *tile == TileId(2)).count();
+        assert!(rocks > 0 && rocks < 56);
+
+        let mut rng = ChunkRng::new(1, a);
+        for _ in 0..100 {
+            assert!(rng.below(6) < 6);
+            let value = rng.next_f32();
+            assert!((0.0..1.0).contains(&value));
+        }
+        assert_ne!(
+            ChunkRng::new(1, a).next_u64(),
+            ChunkRng::new(1, b).next_u64()
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use crate::{saved_components, ChunkIndex, ChunkManager, TileAtlas, WorldTileStore};
+use bevy_app::Events;
+use bevy_ecs::{Added, Changed, Entity, Query, QuerySet, Res, ResMut, Resources, World};
+use bevy_reflect::{Reflect, ReflectComponent, TypeRegistryArc};
+use bevy_scene::SaveGameRegistry;
+use bevy_transform::{
+    components::{GlobalTransform, Transform},
+    hierarchy::despawn_with_children_recursive,
+};
+use bevy_utils::HashMap;
+
+/// The chunk an entity is in, kept up to date from its [GlobalTransform] by [in_chunk_system].
+/// Add it to the entities that should be tracked, like players and monsters.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct InChunk(pub ChunkIndex);
+
+/// Sent when an entity with [InChunk] moves from one chunk into another
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkCrossing {
+    pub entity: Entity,
+    pub from: ChunkIndex,
+    pub to: ChunkIndex,
+}
+
+/// What happens to an entity with [InChunk] while its chunk is not spawned by the
+/// [ChunkManager], either because the chunk was despawned or because the entity moved out of the
+/// spawned chunks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnChunkUnload {
+    /// The entity stays, and keeps running its systems
+    Keep,
+    Despawn,
+    /// The entity is despawned, and spawned again when its chunk is spawned. Only the components
+    /// registered with [SaveGameRegistry](bevy_scene::SaveGameRegistry) are kept, so register
+    /// [Transform] to keep its position.
+    Persist,
+}
+
+impl Default for OnChunkUnload {
+    fn default() -> Self {
+        OnChunkUnload::Keep
+    }
+}
+
+/// The components of the entities that were despawned with [OnChunkUnload::Persist], by the chunk
+/// they were in
+#[derive(Debug, Default)]
+pub struct PersistedEntities {
+    chunks: HashMap<ChunkIndex, Vec<Vec<Box<dyn Reflect>>>>,
+}
+
+impl PersistedEntities {
+    /// The number of entities persisted in a chunk
+    pub fn len(&self, index: ChunkIndex) -> usize {
+        self.chunks.get(&index).map_or(0, |entities| entities.len())
+    }
+}
+
+/// Updates [InChunk] from [GlobalTransform], and sends a [ChunkCrossing] when it changes. Entities
+/// that just got [InChunk] are placed in their chunk without one.
+pub fn in_chunk_system(
+    mut crossings: ResMut<Events<ChunkCrossing>>,
+    tile_atlas: Res<TileAtlas>,
+    store: Res<WorldTileStore>,
+    mut queries: QuerySet<(
+        Query<(&GlobalTransform, &mut InChunk), Added<InChunk>>,
+        Query<(Entity, &GlobalTransform, &mut InChunk), Changed<GlobalTransform>>,
+    )>,
+) {
+    let chunk_size = store.chunk_size();
+    let containing = |transform: &GlobalTransform| {
+        ChunkIndex::containing(
+            transform.translation.truncate(),
+            chunk_size,
+            tile_atlas.tile_size,
+        )
+    };
+    for (transform, mut in_chunk) in queries.q0_mut().iter_mut() {
+        in_chunk.0 = containing(transform);
+    }
+    for (entity, transform, mut in_chunk) in queries.q1_mut().iter_mut() {
+        let index = containing(transform);
+        if in_chunk.0 != index {
+            crossings.send(ChunkCrossing {
+                entity,
+                from: in_chunk.0,
+                to: index,
+            });
+            in_chunk.0 = index;
+        }
+    }
+}
+
+/// Despawns or persists the entities in chunks that are not spawned, as set by their
+/// [OnChunkUnload], and spawns the persisted entities of chunks that were spawned again
+pub fn chunk_unload_system(world: &mut World, resources: &mut Resources) {
+    let manager = resources.get::<ChunkManager>().unwrap();
+    let mut persisted = resources.get_mut::<PersistedEntities>().unwrap();
+    let unloaded = world
+        .query::<(Entity, &InChunk, &OnChunkUnload)>()
+        .filter(|(_, in_chunk, on_unload)| {
+            **on_unload != OnChunkUnload::Keep && manager.get_entity(in_chunk.0).is_none()
+        })
+        .map(|(entity, in_chunk, on_unload)| (entity, in_chunk.0, *on_unload))
+        .collect::<Vec<_>>();
+    let save_registry = resources.get::<SaveGameRegistry>();
+    let type_registry = resources.get::<TypeRegistryArc>();
+    for (entity, index, on_unload) in unloaded {
+        if on_unload == OnChunkUnload::Persist {
+            if let (Some(save_registry), Some(type_registry)) = (&save_registry, &type_registry) {
+                let components =
+                    saved_components(world, save_registry, &type_registry.read(), entity);
+                persisted.chunks.entry(index).or_default().push(components);
+            }
+        }
+        despawn_with_children_recursive(world, entity);
+    }
+
+    let respawned = persisted
+        .chunks
+        .keys()
+        .filter(|index| manager.get_entity(**index).is_some())
+        .copied()
+        .collect::<Vec<_>>();
+    if respawned.is_empty() {
+        return;
+    }
+    let type_registry = type_registry.unwrap();
+    let type_registry = type_registry.read();
+    for index in respawned {
+        for components in persisted.chunks.remove(&index).unwrap() {
+            let entity = world.spawn((InChunk(index), OnChunkUnload::Persist));
+            for component in components.iter() {
+                if let Some(reflect_component) = type_registry
+                    .get_with_name(component.type_name())
+                    .and_then(|registration| registration.data::<ReflectComponent>())
+                {
+                    reflect_component.add_component(world, resources, entity, &**component);
+                }
+            }
+            // without a matching global transform, the entity would seem to cross chunks once
+            // its transform is propagated
+            let global_transform = world
+                .get::<Transform>(entity)
+                .ok()
+                .map(|transform| GlobalTransform::from(*transform));
+            if let Some(global_transform) = global_transform {
+                if world.get::<GlobalTransform>(entity).is_err() {
+                    world.insert_one(entity, global_transform).unwrap();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chunk_manager_system, ChunkGenerator, ChunkLoader};
+    use bevy_app::App;
+    use bevy_ecs::{IntoSystem, Stage, SystemStage};
+    use bevy_math::{IVec2, Vec3};
+    use bevy_reflect::ReflectPlugin;
+    use bevy_scene::{RegisterSaveGame, SaveGamePlugin};
+
+    #[derive(Reflect, Default, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Health {
+        value: u32,
+    }
+
+    #[test]
+    fn entities_follow_chunks() {
+        let mut app = App::build();
+        app.add_plugin(ReflectPlugin)
+            .add_plugin(SaveGamePlugin::default())
+            .register_save_component::<Transform>()
+            .register_save_component::<Health>()
+            .add_event::<ChunkCrossing>()
+            .add_resource(TileAtlas {
+                tile_size: 1,
+                ..Default::default()
+            })
+            .add_resource(WorldTileStore::new(10))
+            .init_resource::<ChunkManager>()
+            .init_resource::<ChunkGenerator>()
+            .init_resource::<PersistedEntities>();
+        let App {
+            mut world,
+            mut resources,
+            ..
+        } = app.app;
+
+        resources.get_mut::<ChunkManager>().unwrap().load_radius = 0;
+        let loader = world.spawn((ChunkLoader, GlobalTransform::default()));
+        let position = Vec3::new(5.0, 5.0, 0.0);
+        let monster = world.spawn((
+            Health { value: 3 },
+            Transform::from_translation(position),
+            GlobalTransform::from_translation(position),
+            InChunk::default(),
+            OnChunkUnload::Persist,
+        ));
+        let player = world.spawn((
+            GlobalTransform::from_translation(Vec3::new(-5.0, 5.0, 0.0)),
+            InChunk::default(),
+        ));
+
+        let mut stage = SystemStage::serial();
+        stage.add_system(chunk_manager_system.system());
+        stage.add_system(in_chunk_system.system());
+        stage.add_system(chunk_unload_system.system());
+        stage.initialize(&mut world, &mut resources);
+        stage.run(&mut world, &mut resources);
+        world.clear_trackers();
+        assert_eq!(
+            world.get::<InChunk>(player).unwrap().0,
+            ChunkIndex(IVec2::new(-1, 0))
+        );
+        assert!(world.contains(monster));
+        let crossings = resources.get::<Events<ChunkCrossing>>().unwrap();
+        assert_eq!(crossings.get_reader().iter(&crossings).count(), 0);
+        drop(crossings);
+
+        world
+            .get_mut::<GlobalTransform>(player)
+            .unwrap()
+            .translation
+            .x = 5.0;
+        stage.run(&mut world, &mut resources);
+        world.clear_trackers();
+        let crossings = resources.get::<Events<ChunkCrossing>>().unwrap();
+        let crossing = crossings.get_reader().iter(&crossings).next().copied();
+        assert_eq!(
+            crossing,
+            Some(ChunkCrossing {
+                entity: player,
+                from: ChunkIndex(IVec2::new(-1, 0)),
+                to: ChunkIndex(IVec2::new(0, 0)),
+            })
+        );
+        drop(crossings);
+
+        // the monster is persisted while the loader is away, and the player stays
+        world
+            .get_mut::<GlobalTransform>(loader)
+            .unwrap()
+            .translation
+            .x = 50.0;
+        stage.run(&mut world, &mut resources);
+        world.clear_trackers();
+        assert!(!world.contains(monster));
+        assert!(world.contains(player));
+        let index = ChunkIndex(IVec2::zero());
+        assert_eq!(resources.get::<PersistedEntities>().unwrap().len(index), 1);
+
+        world
+            .get_mut::<GlobalTransform>(loader)
+            .unwrap()
+            .translation
+            .x = 0.0;
+        stage.run(&mut world, &mut resources);
+        let (health, in_chunk, transform) = world
+            .query::<(&Health, &InChunk, &GlobalTransform)>()
+            .next()
+            .unwrap();
+        assert_eq!(health.value, 3);
+        assert_eq!(in_chunk.0, index);
+        assert_eq!(transform.translation, position);
+    }
+}
@@ -0,0 +1,103 @@
+mod autosave;
+mod chunk;
+mod chunk_manager;
+mod chunk_texture;
+mod controller;
+mod dungeon;
+mod edge_pan;
+mod fog;
+mod generator;
+mod in_chunk;
+mod region;
+mod seam;
+mod store;
+mod tile;
+mod tile_entity;
+mod wfc;
+
+pub use autosave::*;
+pub use chunk::*;
+pub use chunk_manager::*;
+pub use chunk_texture::*;
+pub use controller::*;
+pub use dungeon::*;
+pub use edge_pan::*;
+pub use fog::*;
+pub use generator::*;
+pub use in_chunk::*;
+pub use region::*;
+pub use seam::*;
+pub use store::*;
+pub use tile::*;
+pub use tile_entity::*;
+pub use wfc::*;
+
+pub mod prelude {
+    pub use crate::{
+        CharacterController, Chunk, ChunkBundle, ChunkCrossing, ChunkGenerator, ChunkIndex,
+        ChunkLoader, ChunkManager, DungeonGenerator, DungeonTiles, EdgePan, FogOfWar, FogViewer,
+        GeneratedChunk, InChunk, OnChunkUnload, TileAtlas, TileId, TileKind, TileKinds,
+        TileRegistry, TilemapPlugin, WfcGenerator, WfcRules, WorldGenerator, WorldTileStore,
+    };
+}
+
+use bevy_app::prelude::*;
+use bevy_asset::AddAsset;
+use bevy_ecs::IntoSystem;
+
+/// Adds the [TileRegistry], which is filled from the [TileKinds] files loaded with the
+/// [AssetServer](bevy_asset::AssetServer), and bakes the textures of [ChunkBundle]s from the
+/// [TileAtlas]. The [ChunkManager] streams chunks in and out around [ChunkLoader]s,
+/// along with the [TileEntities] of their tiles, [EdgePan] cameras pan over them and
+/// [CharacterController]s move through them without entering solid tiles. The [FogOfWar] tracks
+/// what [FogViewer]s see of them. Chunks that were never stored are generated in the background
+/// by the [ChunkGenerator].
+/// Inserting an [Autosave] resource saves the world in the background, and once more when the
+/// app exits, and the [TilemapDebug] resource turns on checks for rendering bugs.
+///
+/// Chunks are 32 tiles square and tiles 16 world units square by default. Other sizes are
+/// configured by inserting the resources before adding the plugin:
+///
+/// ```ignore
+/// app.add_resource(WorldTileStore::new(16))
+///     .add_resource(TileAtlas {
+///         atlas: atlas_handle,
+///         tile_size: 8,
+///     })
+///     .add_plugin(TilemapPlugin);
+/// ```
+#[derive(Default)]
+pub struct TilemapPlugin;
+
+impl Plugin for TilemapPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_asset::<TileKinds>()
+            .init_asset_loader::<TileKindsLoader>()
+            .init_resource::<TileRegistry>()
+            .init_resource::<TileAtlas>()
+            .init_resource::<TilemapStats>()
+            .init_resource::<ChunkManager>()
+            .init_resource::<ChunkGenerator>()
+            .init_resource::<WorldTileStore>()
+            .init_resource::<TileEntities>()
+            .init_resource::<PersistedEntities>()
+            .init_resource::<TilemapDebug>()
+            .init_resource::<FogOfWar>()
+            .add_event::<ChunkCrossing>()
+            .add_system_to_stage(stage::PRE_UPDATE, tile_registry_system.system())
+            .add_system_to_stage(stage::PRE_UPDATE, chunk_manager_system.system())
+            .add_system_to_stage(stage::PRE_UPDATE, chunk_generation_system.system())
+            .add_system(edge_pan_system.system())
+            .add_system(character_controller_system.system())
+            .add_system_to_stage(stage::POST_UPDATE, chunk_store_system.system())
+            .add_system_to_stage(stage::POST_UPDATE, tile_entity_system.system())
+            .add_system_to_stage(stage::POST_UPDATE, in_chunk_system.system())
+            .add_system_to_stage(stage::POST_UPDATE, chunk_unload_system.system())
+            .add_system_to_stage(stage::POST_UPDATE, chunk_texture_system.system())
+            .add_system_to_stage(stage::POST_UPDATE, chunk_seam_system.system())
+            .add_system_to_stage(stage::POST_UPDATE, fog_of_war_system.system())
+            .add_system_to_stage(stage::POST_UPDATE, fog_overlay_system.system())
+            .add_system_to_stage(stage::LAST, autosave_system.system())
+            .add_shutdown_system(autosave_shutdown_system.system());
+    }
+}
@@ -0,0 +1,496 @@
+use crate::{ChunkIndex, PackedChunk, WorldTileStore};
+use bevy_math::{IRect, IVec2};
+use bevy_utils::HashMap;
+use std::{
+    convert::TryInto,
+    fs::{File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+/// The number of chunks along each side of the square of chunks stored in one region file
+pub const REGION_SIZE: i32 = 32;
+
+const CHUNKS_PER_REGION: usize = (REGION_SIZE * REGION_SIZE) as usize;
+const MAGIC: &[u8; 4] = b"BTRG";
+const VERSION: u32 = 1;
+/// Chunks are stored in whole sectors, so the sectors of chunks that were rewritten elsewhere can
+/// be reused
+const SECTOR_SIZE: u64 = 4096;
+/// The magic and version, then the location of every chunk
+const HEADER_LEN: u64 = 8 + CHUNKS_PER_REGION as u64 * 8;
+const HEADER_SECTORS: u32 = ((HEADER_LEN - 1) / SECTOR_SIZE + 1) as u32;
+const ZSTD_LEVEL: i32 = 3;
+
+/// How the tiles of a chunk are compressed in a region file
+const UNCOMPRESSED: u8 = 0;
+const ZSTD: u8 = 1;
+
+#[derive(Error, Debug)]
+pub enum RegionError {
+    #[error("Region file IO failed: {0}")]
+    Io(#[from] io::Error),
+    #[error("`{0}` is not a region file, or its header is corrupt.")]
+    InvalidHeader(PathBuf),
+    #[error("Chunk {0:?} in its region file is corrupt.")]
+    CorruptChunk(ChunkIndex),
+    #[error("Chunk {index:?} has {found} tiles per side, but the store has {expected}.")]
+    WrongChunkSize {
+        index: ChunkIndex,
+        expected: u32,
+        found: u32,
+    },
+}
+
+/// Where a chunk is stored in a region file, in sectors. A chunk that isn't stored is at sector 0,
+/// which is part of the header.
+#[derive(Debug, Default, Clone, Copy)]
+struct Location {
+    sector: u32,
+    sectors: u32,
+}
+
+/// A file that stores the chunks of a [REGION_SIZE] by [REGION_SIZE] square of chunks, each
+/// compressed on its own, so single chunks can be read and rewritten without touching the rest of
+/// the file.
+///
+/// The file starts with a header that has the location of every chunk, in whole sectors. A stored
+/// chunk is its length, the compression it uses and its compressed tiles.
+///
+/// Chunks are never overwritten in place. A chunk is written to free sectors and synced to disk
+/// before the header points to it, and its old sectors are only reused after that, so a region
+/// file holds either the old or the new chunk even if the app stops while saving.
+#[derive(Debug)]
+pub struct RegionFile {
+    path: PathBuf,
+    file: File,
+    locations: Vec<Location>,
+    used_sectors: Vec<bool>,
+}
+
+impl RegionFile {
+    /// Opens a region file, creating it if it doesn't exist
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, RegionError> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        let len = file.metadata()?.len();
+        let mut header = vec![0; (HEADER_SECTORS as u64 * SECTOR_SIZE) as usize];
+        if len == 0 {
+            header[0..4].copy_from_slice(MAGIC);
+            header[4..8].copy_from_slice(&VERSION.to_le_bytes());
+            file.write_all(&header)?;
+            file.sync_data()?;
+        } else {
+            if len < header.len() as u64 {
+                return Err(RegionError::InvalidHeader(path));
+            }
+            file.read_exact(&mut header)?;
+            if &header[0..4] != MAGIC || header[4..8] != VERSION.to_le_bytes() {
+                return Err(RegionError::InvalidHeader(path));
+            }
+        }
+
+        let file_sectors = sectors_for(len).max(HEADER_SECTORS as u64);
+        let mut used_sectors = vec![false; file_sectors as usize];
+        for used in used_sectors.iter_mut().take(HEADER_SECTORS as usize) {
+            *used = true;
+        }
+        let mut locations = Vec::with_capacity(CHUNKS_PER_REGION);
+        for entry in header[8..HEADER_LEN as usize].chunks_exact(8) {
+            let location = Location {
+                sector: u32::from_le_bytes(entry[0..4].try_into().unwrap()),
+                sectors: u32::from_le_bytes(entry[4..8].try_into().unwrap()),
+            };
+            if location.sector != 0 {
+                let start = location.sector as usize;
+                let end = start + location.sectors as usize;
+                if start < HEADER_SECTORS as usize || end > used_sectors.len() {
+                    return Err(RegionError::InvalidHeader(path));
+                }
+                for used in used_sectors[start..end].iter_mut() {
+                    *used = true;
+                }
+            }
+            locations.push(location);
+        }
+        Ok(RegionFile {
+            path,
+            file,
+            locations,
+            used_sectors,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the chunk at `index` is stored. Chunks outside the region are never stored.
+    pub fn contains(&self, index: ChunkIndex) -> bool {
+        self.locations[slot(index)].sector != 0
+    }
+
+    pub fn read_chunk(&mut self, index: ChunkIndex) -> Result<Option<PackedChunk>, RegionError> {
+        let location = self.locations[slot(index)];
+        if location.sector == 0 {
+            return Ok(None);
+        }
+        self.file
+            .seek(SeekFrom::Start(location.sector as u64 * SECTOR_SIZE))?;
+        let mut len = [0; 4];
+        self.file.read_exact(&mut len)?;
+        let len = u32::from_le_bytes(len) as u64;
+        if len == 0 || 4 + len > location.sectors as u64 * SECTOR_SIZE {
+            return Err(RegionError::CorruptChunk(index));
+        }
+        let mut record = vec![0; len as usize];
+        self.file.read_exact(&mut record)?;
+        let bytes = match record[0] {
+            UNCOMPRESSED => record.split_off(1),
+            ZSTD => zstd::decode_all(&record[1..]).map_err(|_| RegionError::CorruptChunk(index))?,
+            _ => return Err(RegionError::CorruptChunk(index)),
+        };
+        PackedChunk::from_bytes(&bytes)
+            .map(Some)
+            .ok_or(RegionError::CorruptChunk(index))
+    }
+
+    /// Stores a chunk in free sectors, then points the header to it
+    pub fn write_chunk(
+        &mut self,
+        index: ChunkIndex,
+        chunk: &PackedChunk,
+    ) -> Result<(), RegionError> {
+        let mut bytes = Vec::new();
+        chunk.write_bytes(&mut bytes);
+        let compressed = zstd::encode_all(&bytes[..], ZSTD_LEVEL)?;
+        let mut record = Vec::with_capacity(5 + compressed.len());
+        record.extend_from_slice(&(compressed.len() as u32 + 1).to_le_bytes());
+        record.push(ZSTD);
+        record.extend_from_slice(&compressed);
+        let sectors = sectors_for(record.len() as u64) as u32;
+        record.resize((sectors as u64 * SECTOR_SIZE) as usize, 0);
+
+        let slot = slot(index);
+        let old = self.locations[slot];
+        let sector = self.allocate(sectors);
+        let written = self
+            .file
+            .seek(SeekFrom::Start(sector as u64 * SECTOR_SIZE))
+            .and_then(|_| self.file.write_all(&record))
+            .and_then(|_| self.file.sync_data());
+        if let Err(err) = written {
+            self.free(sector, sectors);
+            return Err(err.into());
+        }
+        self.set_location(slot, Location { sector, sectors })?;
+        self.free(old.sector, old.sectors);
+        Ok(())
+    }
+
+    /// Removes a chunk. Returns `false` if it wasn't stored.
+    pub fn remove_chunk(&mut self, index: ChunkIndex) -> Result<bool, RegionError> {
+        let slot = slot(index);
+        let old = self.locations[slot];
+        if old.sector == 0 {
+            return Ok(false);
+        }
+        self.set_location(slot, Location::default())?;
+        self.free(old.sector, old.sectors);
+        Ok(true)
+    }
+
+    /// Finds the first run of free sectors that is long enough, or makes room at the end
+    fn allocate(&mut self, sectors: u32) -> u32 {
+        let sectors = sectors as usize;
+        let mut run = 0;
+        for (sector, used) in self.used_sectors.iter().enumerate() {
+            run = if *used { 0 } else { run + 1 };
+            if run == sectors {
+                let start = sector + 1 - sectors;
+                self.mark(start, sectors, true);
+                return start as u32;
+            }
+        }
+        let start = self.used_sectors.len() - run;
+        self.used_sectors.resize(start + sectors, true);
+        self.mark(start, sectors, true);
+        start as u32
+    }
+
+    fn free(&mut self, sector: u32, sectors: u32) {
+        if sector != 0 {
+            self.mark(sector as usize, sectors as usize, false);
+        }
+    }
+
+    fn mark(&mut self, start: usize, len: usize, used: bool) {
+        for sector in self.used_sectors[start..start + len].iter_mut() {
+            *sector = used;
+        }
+    }
+
+    /// Updates the header entry of a chunk and syncs it, so the sectors it pointed to before can
+    /// be reused
+    fn set_location(&mut self, slot: usize, location: Location) -> Result<(), RegionError> {
+        let mut entry = [0; 8];
+        entry[0..4].copy_from_slice(&location.sector.to_le_bytes());
+        entry[4..8].copy_from_slice(&location.sectors.to_le_bytes());
+        self.file.seek(SeekFrom::Start(8 + slot as u64 * 8))?;
+        self.file.write_all(&entry)?;
+        self.file.sync_data()?;
+        self.locations[slot] = location;
+        Ok(())
+    }
+}
+
+/// The number of sectors `len` bytes take up
+fn sectors_for(len: u64) -> u64 {
+    if len == 0 {
+        0
+    } else {
+        (len - 1) / SECTOR_SIZE + 1
+    }
+}
+
+/// The position of a chunk in the header of its region
+fn slot(index: ChunkIndex) -> usize {
+    let local = index.0.rem_euclid(REGION_SIZE);
+    (local.y * REGION_SIZE + local.x) as usize
+}
+
+/// Saves and loads the chunks of a [WorldTileStore] to and from the [RegionFile]s in a directory,
+/// so large worlds are kept in a few files rather than a file per chunk
+#[derive(Debug)]
+pub struct RegionStorage {
+    directory: PathBuf,
+    regions: HashMap<IVec2, RegionFile>,
+}
+
+impl RegionStorage {
+    /// Stores regions in `directory`, which is created when the first chunk is saved
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        RegionStorage {
+            directory: directory.into(),
+            regions: Default::default(),
+        }
+    }
+
+    /// The region that holds the chunk at `index`
+    pub fn region(index: ChunkIndex) -> IVec2 {
+        index.0.div_euclid(REGION_SIZE)
+    }
+
+    pub fn region_path(&self, region: IVec2) -> PathBuf {
+        self.directory
+            .join(format!("r.{}.{}.region", region.x, region.y))
+    }
+
+    pub fn load_chunk(&mut self, index: ChunkIndex) -> Result<Option<PackedChunk>, RegionError> {
+        match self.open(Self::region(index), false)? {
+            Some(region) => region.read_chunk(index),
+            None => Ok(None),
+        }
+    }
+
+    pub fn save_chunk(
+        &mut self,
+        index: ChunkIndex,
+        chunk: &PackedChunk,
+    ) -> Result<(), RegionError> {
+        let region = self.open(Self::region(index), true)?.unwrap();
+        region.write_chunk(index, chunk)
+    }
+
+    pub fn remove_chunk(&mut self, index: ChunkIndex) -> Result<bool, RegionError> {
+        match self.open(Self::region(index), false)? {
+            Some(region) => region.remove_chunk(index),
+            None => Ok(false),
+        }
+    }
+
+    /// Saves the chunks of `store` that changed since they were last saved, and removes the
+    /// chunks that were removed from it. Returns the number of chunks saved or removed. Chunks
+    /// that could not be saved stay dirty.
+    pub fn save_dirty(&mut self, store: &mut WorldTileStore) -> Result<usize, RegionError> {
+        let dirty = store.take_dirty();
+        for (saved, index) in dirty.iter().enumerate() {
+            let result = match store.get_chunk(*index) {
+                Some(chunk) => self.save_chunk(*index, chunk),
+                None => self.remove_chunk(*index).map(|_| ()),
+            };
+            if let Err(err) = result {
+                for index in dirty[saved..].iter() {
+                    store.mark_dirty(*index);
+                }
+                return Err(err);
+            }
+        }
+        Ok(dirty.len())
+    }
+
+    /// Loads the saved chunks in `area` that `store` doesn't hold yet. Returns the number of
+    /// chunks loaded.
+    pub fn load_area(
+        &mut self,
+        store: &mut WorldTileStore,
+        area: IRect,
+    ) -> Result<usize, RegionError> {
+        let mut loaded = 0;
+        for index in area.iter().map(ChunkIndex) {
+            if store.get_chunk(index).is_some() {
+                continue;
+            }
+            if let Some(chunk) = self.load_chunk(index)? {
+                if chunk.size() != store.chunk_size() {
+                    return Err(RegionError::WrongChunkSize {
+                        index,
+                        expected: store.chunk_size(),
+                        found: chunk.size(),
+                    });
+                }
+                store.load_chunk(index, chunk);
+                loaded += 1;
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// Closes the open region files
+    pub fn close(&mut self) {
+        self.regions.clear();
+    }
+
+    fn open(
+        &mut self,
+        region: IVec2,
+        create: bool,
+    ) -> Result<Option<&mut RegionFile>, RegionError> {
+        if !self.regions.contains_key(&region) {
+            let path = self.region_path(region);
+            if !create && !path.exists() {
+                return Ok(None);
+            }
+            std::fs::create_dir_all(&self.directory)?;
+            self.regions.insert(region, RegionFile::open(path)?);
+        }
+        Ok(self.regions.get_mut(&region))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TileId;
+
+    #[test]
+    fn save_and_load_regions() {
+        let directory =
+            std::env::temp_dir().join(format!("bevy_tilemap_region_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+
+        let mut store = WorldTileStore::new(16);
+        store.set(IVec2::new(3, 4), TileId(1));
+        store.set(IVec2::new(-40, 600), TileId(2));
+        let mut storage = RegionStorage::new(&directory);
+        assert_eq!(storage.save_dirty(&mut store).unwrap(), 2);
+        assert!(storage.region_path(IVec2::new(0, 0)).exists());
+        assert!(storage.region_path(IVec2::new(-1, 1)).exists());
+
+        // a chunk with many different tiles no longer fits in its sector, and moves
+        let index = ChunkIndex(IVec2::zero());
+        for id in 0..256 {
+            store.set(IVec2::new(id % 16, id / 16), TileId(id as u16 + 10));
+        }
+        store.set(IVec2::new(1, 1), TileId(1));
+        storage.save_dirty(&mut store).unwrap();
+        storage.close();
+
+        let mut loaded = WorldTileStore::new(16);
+        let area = IRect::new(IVec2::new(-4, -4), IVec2::new(4, 40));
+        assert_eq!(storage.load_area(&mut loaded, area).unwrap(), 2);
+        assert_eq!(loaded.get_chunk(index), store.get_chunk(index));
+        assert_eq!(loaded.get(IVec2::new(1, 1)), TileId(1));
+        assert_eq!(loaded.get(IVec2::new(-40, 600)), TileId(2));
+        assert!(loaded.take_dirty().is_empty());
+
+        assert!(storage.remove_chunk(index).unwrap());
+        assert!(storage.load_chunk(index).unwrap().is_none());
+        assert!(storage
+            .load_chunk(ChunkIndex(IVec2::new(-3, 37)))
+            .unwrap()
+            .is_some());
+
+        std::fs::write(storage.region_path(IVec2::new(5, 5)), b"not a region").unwrap();
+        storage.close();
+        assert!(matches!(
+            storage.load_chunk(ChunkIndex(IVec2::new(160, 160))),
+            Err(RegionError::InvalidHeader(_))
+        ));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn rewrite_chunks_elsewhere() {
+        let path = std::env::temp_dir().join(format!(
+            "bevy_tilemap_region_rewrite_test_{}.region",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let index = ChunkIndex(IVec2::new(2, 3));
+        let mut chunk = PackedChunk::new(16);
+        chunk.set(0, 0, TileId(1));
+        let mut region = RegionFile::open(&path).unwrap();
+        region.write_chunk(index, &chunk).unwrap();
+        let first = region.locations[slot(index)];
+
+        // the old sectors stay untouched until the header points to the new ones
+        chunk.set(0, 0, TileId(2));
+        region.write_chunk(index, &chunk).unwrap();
+        let second = region.locations[slot(index)];
+        assert_ne!(first.sector, second.sector);
+        chunk.set(0, 0, TileId(3));
+        region.write_chunk(index, &chunk).unwrap();
+        assert_eq!(region.locations[slot(index)].sector, first.sector);
+
+        let mut reopened = RegionFile::open(&path).unwrap();
+        let loaded = reopened.read_chunk(index).unwrap().unwrap();
+        assert_eq!(loaded.get(0, 0), Some(TileId(3)));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn remove_saved_chunks() {
+        let directory = std::env::temp_dir().join(format!(
+            "bevy_tilemap_region_remove_test_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&directory);
+
+        let mut store = WorldTileStore::new(16);
+        store.set(IVec2::new(3, 4), TileId(1));
+        store.set(IVec2::new(20, 4), TileId(2));
+        let mut storage = RegionStorage::new(&directory);
+        assert_eq!(storage.save_dirty(&mut store).unwrap(), 2);
+
+        let removed = ChunkIndex(IVec2::zero());
+        store.remove_chunk(removed);
+        assert!(store.is_dirty(removed));
+        assert_eq!(storage.save_dirty(&mut store).unwrap(), 1);
+        storage.close();
+
+        let mut loaded = WorldTileStore::new(16);
+        let area = IRect::new(IVec2::new(-1, -1), IVec2::new(2, 2));
+        assert_eq!(storage.load_area(&mut loaded, area).unwrap(), 1);
+        assert!(loaded.get_chunk(removed).is_none());
+        assert_eq!(loaded.get(IVec2::new(20, 4)), TileId(2));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,339 @@
+use crate::{Chunk, ChunkIndex, ChunkTexture, TileAtlas, TileRegistry, WorldTileStore};
+use bevy_asset::Assets;
+use bevy_ecs::{Changed, Query, Res};
+use bevy_math::{IVec2, Vec2};
+use bevy_render::texture::Texture;
+use bevy_sprite::TextureAtlas;
+use bevy_transform::components::Transform;
+use thiserror::Error;
+
+/// Checks the tilemap does what it should, at a cost, to track down rendering bugs
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TilemapDebug {
+    /// Panics when a baked chunk doesn't line up with its neighbors, see [validate_chunk_edges]
+    pub assert_seams: bool,
+}
+
+/// Why a chunk doesn't line up with its neighbors
+#[derive(Error, Debug, PartialEq)]
+pub enum SeamError {
+    #[error("Chunk {index:?} is at {translation}, but should be at {expected}.")]
+    Misplaced {
+        index: ChunkIndex,
+        translation: Vec2,
+        expected: Vec2,
+    },
+    #[error("Chunk {index:?} is baked {actual} pixels square, but should be {expected}.")]
+    WrongSize {
+        index: ChunkIndex,
+        actual: u32,
+        expected: u32,
+    },
+    #[error("Pixel {pixel:?} on the edge of chunk {index:?} doesn't show tile {tile:?}.")]
+    EdgePixel {
+        index: ChunkIndex,
+        pixel: [u32; 2],
+        tile: IVec2,
+    },
+}
+
+/// Checks that the edges of the baked `texture` of the chunk at `index` show the tiles the
+/// `store` holds on either side of the seams to its neighbors.
+///
+/// Every edge pixel is looked up from its position in world pixels, independently of how chunks
+/// are baked, so chunks that are baked upside down, from the tiles of another chunk, or with
+/// their tiles off by one on the negative side of the world all fail, even when each chunk looks
+/// fine on its own.
+pub fn validate_chunk_edges(
+    index: ChunkIndex,
+    texture: &Texture,
+    store: &WorldTileStore,
+    registry: &TileRegistry,
+    atlas: &TextureAtlas,
+    atlas_texture: &Texture,
+    tile_size: u32,
+) -> Result<(), SeamError> {
+    let pixels = store.chunk_size() * tile_size;
+    if texture.size.width != pixels || texture.size.height != pixels {
+        return Err(SeamError::WrongSize {
+            index,
+            actual: texture.size.width,
+            expected: pixels,
+        });
+    }
+    let transparent = vec![0; texture.format.pixel_size()];
+    let edges = (0..pixels).flat_map(|i| {
+        let last = pixels - 1;
+        vec![[0, i], [last, i], [i, 0], [i, last]]
+    });
+    for pixel in edges {
+        // texture rows go from top to bottom, world pixels from bottom to top
+        let local = IVec2::new(pixel[0] as i32, (pixels - 1 - pixel[1]) as i32);
+        let world = index.0 * pixels as i32 + local;
+        let tile = world.div_euclid(tile_size as i32);
+        let offset = world.rem_euclid(tile_size as i32);
+        let expected = registry
+            .get(store.get(tile))
+            .filter(|kind| !kind.is_multi_tile())
+            .and_then(|kind| kind.sprite)
+            .and_then(|sprite| atlas.textures.get(sprite as usize))
+            .and_then(|rect| {
+                atlas_texture.get_pixel(
+                    rect.min.x as u32 + offset.x as u32,
+                    rect.min.y as u32 + tile_size - 1 - offset.y as u32,
+                )
+            })
+            .unwrap_or(&transparent);
+        if texture.get_pixel(pixel[0], pixel[1]) != Some(expected) {
+            return Err(SeamError::EdgePixel { index, pixel, tile });
+        }
+    }
+    Ok(())
+}
+
+/// Validates the chunks baked this frame when [TilemapDebug::assert_seams] is set, and panics
+/// if one doesn't line up with its neighbors. Their tiles are compared with the
+/// [WorldTileStore], so tiles have to be edited before
+/// [chunk_store_system](crate::chunk_store_system) runs.
+pub fn chunk_seam_system(
+    debug: Res<TilemapDebug>,
+    store: Res<WorldTileStore>,
+    registry: Res<TileRegistry>,
+    tile_atlas: Res<TileAtlas>,
+    atlases: Res<Assets<TextureAtlas>>,
+    textures: Res<Assets<Texture>>,
+    chunks: Query<(&ChunkIndex, &Chunk, &ChunkTexture, &Transform), Changed<ChunkTexture>>,
+) {
+    if !debug.assert_seams {
+        return;
+    }
+    let atlas = match atlases.get(&tile_atlas.atlas) {
+        Some(atlas) => atlas,
+        None => return,
+    };
+    let atlas_texture = match textures.get(&atlas.texture) {
+        Some(atlas_texture) => atlas_texture,
+        None => return,
+    };
+    for (index, chunk, chunk_texture, transform) in chunks.iter() {
+        let texture = match chunk_texture
+            .texture
+            .as_ref()
+            .and_then(|handle| textures.get(handle))
+        {
+            Some(texture) => texture,
+            None => continue,
+        };
+        let translation = transform.translation.truncate();
+        let expected = index.center(chunk.size(), tile_atlas.tile_size);
+        let result = if translation != expected {
+            Err(SeamError::Misplaced {
+                index: *index,
+                translation,
+                expected,
+            })
+        } else {
+            validate_chunk_edges(
+                *index,
+                texture,
+                &store,
+                &registry,
+                atlas,
+                atlas_texture,
+                tile_atlas.tile_size,
+            )
+        };
+        if let Err(err) = result {
+            panic!("{}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bake_chunk, TileId, TileKind};
+    use bevy_render::texture::{Extent3d, TextureDimension, TextureFormat};
+    use bevy_sprite::Rect;
+
+    /// An atlas of two 2x2 pixel tiles with four different pixels each, so tiles baked upside
+    /// down or mirrored don't match
+    fn atlas() -> (TextureAtlas, Texture) {
+        let mut data = Vec::new();
+        for row in 0..2u8 {
+            for column in 0..4u8 {
+                data.extend_from_slice(&[row * 4 + column, 0, 0, 255]);
+            }
+        }
+        let atlas_texture = Texture::new(
+            Extent3d::new(4, 2, 1),
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        let mut atlas = TextureAtlas::new_empty(Default::default(), Vec2::new(4.0, 2.0));
+        for x in 0..2 {
+            atlas.add_texture(Rect {
+                min: Vec2::new(x as f32 * 2.0, 0.0),
+                max: Vec2::new(x as f32 * 2.0 + 2.0, 2.0),
+            });
+        }
+        (atlas, atlas_texture)
+    }
+
+    fn registry() -> TileRegistry {
+        let mut registry = TileRegistry::default();
+        for (id, sprite) in [(1, 0), (2, 1)].iter() {
+            registry
+                .register(TileKind {
+                    sprite: Some(*sprite),
+                    ..TileKind::new(TileId(*id), format!("tile {}", id))
+                })
+                .unwrap();
+        }
+        registry
+    }
+
+    #[test]
+    fn seamless_chunks() {
+        let (atlas, atlas_texture) = atlas();
+        let registry = registry();
+        let mut store = WorldTileStore::new(3);
+        // a checkerboard across the chunks on both sides of zero, with a gap to leave empty edge
+        // tiles too
+        for x in -4..4 {
+            for y in -4..4 {
+                if (x, y) != (2, -1) {
+                    store.set(IVec2::new(x, y), TileId(1 + (x + y).rem_euclid(2) as u16));
+                }
+            }
+        }
+
+        for x in -2..2 {
+            for y in -2..2 {
+                let index = ChunkIndex(IVec2::new(x, y));
+                let texture = bake_chunk(&store.chunk(index), &registry, &atlas, &atlas_texture, 2);
+                assert_eq!(
+                    validate_chunk_edges(
+                        index,
+                        &texture,
+                        &store,
+                        &registry,
+                        &atlas,
+                        &atlas_texture,
+                        2
+                    ),
+                    Ok(())
+                );
+            }
+        }
+
+        // the pixels on either side of the seam between the chunks at x -1 and 0 are the two
+        // halves of neighboring tiles
+        let left = bake_chunk(
+            &store.chunk(ChunkIndex(IVec2::new(-1, 0))),
+            &registry,
+            &atlas,
+            &atlas_texture,
+            2,
+        );
+        let right = bake_chunk(
+            &store.chunk(ChunkIndex(IVec2::new(0, 0))),
+            &registry,
+            &atlas,
+            &atlas_texture,
+            2,
+        );
+        for y in 0..6 {
+            let row = (y % 2) as u8;
+            // tile (-1, 2 - y / 2) and tile (0, 2 - y / 2) are different kinds
+            let left_kind = ((-1 + 2 - y as i32 / 2).rem_euclid(2)) as u8;
+            let right_kind = 1 - left_kind;
+            assert_eq!(
+                left.get_pixel(5, y).unwrap()[0],
+                row * 4 + left_kind * 2 + 1
+            );
+            assert_eq!(right.get_pixel(0, y).unwrap()[0], row * 4 + right_kind * 2);
+        }
+    }
+
+    #[test]
+    fn detect_broken_seams() {
+        let (atlas, atlas_texture) = atlas();
+        let registry = registry();
+        let mut store = WorldTileStore::new(2);
+        store.set(IVec2::new(-1, -1), TileId(1));
+        store.set(IVec2::new(0, -1), TileId(2));
+        let index = ChunkIndex(IVec2::new(-1, -1));
+        let validate = |texture: &Texture| {
+            validate_chunk_edges(index, texture, &store, &registry, &atlas, &atlas_texture, 2)
+        };
+
+        // the tiles of the neighboring chunk, as if the index was off by one
+        let texture = bake_chunk(
+            &store.chunk(ChunkIndex(IVec2::new(0, -1))),
+            &registry,
+            &atlas,
+            &atlas_texture,
+            2,
+        );
+        assert_eq!(
+            validate(&texture),
+            Err(SeamError::EdgePixel {
+                index,
+                pixel: [0, 0],
+                tile: IVec2::new(-2, -1),
+            })
+        );
+
+        // the right tiles upside down
+        let mut texture = bake_chunk(&store.chunk(index), &registry, &atlas, &atlas_texture, 2);
+        assert_eq!(validate(&texture), Ok(()));
+        let flipped = texture.data.chunks(4 * 4).rev().flatten().copied();
+        texture.data = flipped.collect();
+        assert!(matches!(
+            validate(&texture),
+            Err(SeamError::EdgePixel { .. })
+        ));
+
+        let texture = bake_chunk(&Chunk::new(3), &registry, &atlas, &atlas_texture, 2);
+        assert_eq!(
+            validate(&texture),
+            Err(SeamError::WrongSize {
+                index,
+                actual: 6,
+                expected: 4,
+            })
+        );
+    }
+
+    #[test]
+    fn continuous_tile_coordinates() {
+        let store = WorldTileStore::new(4);
+        let tile_size = 16;
+        let mut previous: Option<(ChunkIndex, (u32, u32))> = None;
+        for x in -9..9 {
+            let tile = IVec2::new(x, -x);
+            let (index, local) = store.locate(tile);
+            assert_eq!(
+                index.0 * 4 + IVec2::new(local.0 as i32, local.1 as i32),
+                tile
+            );
+            // the center of the tile is in the chunk that holds it
+            let center = (tile.as_vec2() + Vec2::splat(0.5)) * tile_size as f32;
+            assert_eq!(ChunkIndex::containing(center, 4, tile_size), index);
+            // stepping one tile right either moves one tile along the chunk, or wraps into the
+            // next chunk
+            if let Some((previous_index, previous_local)) = previous {
+                if previous_local.0 == 3 {
+                    assert_eq!(index.0.x, previous_index.0.x + 1);
+                    assert_eq!(local.0, 0);
+                } else {
+                    assert_eq!(index.0.x, previous_index.0.x);
+                    assert_eq!(local.0, previous_local.0 + 1);
+                }
+            }
+            previous = Some((index, local));
+        }
+    }
+}
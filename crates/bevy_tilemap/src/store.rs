@@ -0,0 +1,377 @@
+use crate::{Chunk, ChunkIndex, PackedChunk, TileId, TileRegistry};
+use bevy_ecs::{Changed, Query, QuerySet, ResMut};
+use bevy_math::{IRect, IVec2, MortonCode};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+
+/// How much memory a [WorldTileStore] uses
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TileStoreMemory {
+    pub chunks: usize,
+    pub tiles: usize,
+    /// The bytes allocated for packed tiles
+    pub packed_bytes: usize,
+    /// The bytes the tiles would take unpacked, as in a [Chunk]
+    pub unpacked_bytes: usize,
+}
+
+/// The tiles of the whole world, kept apart from the chunk entities that show them, so a world
+/// can be much larger than what is spawned. Tiles are addressed by their position in tiles, which
+/// may be negative. Positions outside any stored chunk hold [TileId::EMPTY].
+///
+/// The [ChunkManager](crate::ChunkManager) spawns chunks that share their tiles with the store.
+/// After the update, [chunk_store_system] shares the tiles of [Chunk]s that changed with the
+/// store, and the tiles changed in the store with the spawned [Chunk]s, so either can be edited.
+#[derive(Debug)]
+pub struct WorldTileStore {
+    chunk_size: u32,
+    chunks: BTreeMap<MortonCode, Arc<PackedChunk>>,
+    dirty: BTreeSet<MortonCode>,
+    /// The tiles set in chunks that are being generated, which are kept over the generated tiles
+    generating: BTreeMap<MortonCode, BTreeSet<(u32, u32)>>,
+    empty: Arc<PackedChunk>,
+}
+
+impl Default for WorldTileStore {
+    fn default() -> Self {
+        WorldTileStore::new(32)
+    }
+}
+
+impl WorldTileStore {
+    /// Creates a store of chunks of `chunk_size` by `chunk_size` tiles
+    pub fn new(chunk_size: u32) -> Self {
+        WorldTileStore {
+            chunk_size,
+            chunks: Default::default(),
+            dirty: Default::default(),
+            generating: Default::default(),
+            empty: Arc::new(PackedChunk::new(chunk_size)),
+        }
+    }
+
+    pub fn chunk_size(&self) -> u32 {
+        self.chunk_size
+    }
+
+    /// The chunk that holds `tile`, and the position of the tile in it
+    pub fn locate(&self, tile: IVec2) -> (ChunkIndex, (u32, u32)) {
+        let size = self.chunk_size as i32;
+        let local = tile.rem_euclid(size);
+        (
+            ChunkIndex(tile.div_euclid(size)),
+            (local.x as u32, local.y as u32),
+        )
+    }
+
+    pub fn get(&self, tile: IVec2) -> TileId {
+        let (index, (x, y)) = self.locate(tile);
+        self.get_chunk(index)
+            .and_then(|chunk| chunk.get(x, y))
+            .unwrap_or(TileId::EMPTY)
+    }
+
+    /// Sets a tile, storing its chunk if it was not stored yet. Returns `false` if the tile was
+    /// already set.
+    pub fn set(&mut self, tile: IVec2, id: TileId) -> bool {
+        let (index, (x, y)) = self.locate(tile);
+        let key = MortonCode::encode(index.0);
+        if let Some(edited) = self.generating.get_mut(&key) {
+            edited.insert((x, y));
+        }
+        let empty = &self.empty;
+        let chunk = self.chunks.entry(key).or_insert_with(|| empty.clone());
+        if chunk.get(x, y) == Some(id) {
+            return false;
+        }
+        let changed = Arc::make_mut(chunk).set(x, y, id);
+        if changed {
+            self.dirty.insert(key);
+        }
+        changed
+    }
+
+    /// The tile of the multi-tile object whose footprint covers `tile`, and its kind. Objects
+    /// placed on other chunks are found too, so objects can straddle chunk boundaries.
+    pub fn object_at(&self, tile: IVec2, registry: &TileRegistry) -> Option<(IVec2, TileId)> {
+        let (width, height) = registry.max_footprint();
+        for dy in 0..height as i32 {
+            for dx in 0..width as i32 {
+                let anchor = tile - IVec2::new(dx, dy);
+                let id = self.get(anchor);
+                let covers = registry.get(id).map_or(false, |kind| {
+                    kind.is_multi_tile()
+                        && dx < kind.footprint.0 as i32
+                        && dy < kind.footprint.1 as i32
+                });
+                if covers {
+                    return Some((anchor, id));
+                }
+            }
+        }
+        None
+    }
+
+    /// Whether `tile` blocks movement, because it is solid or covered by a solid multi-tile
+    /// object
+    pub fn is_solid(&self, tile: IVec2, registry: &TileRegistry) -> bool {
+        registry.is_solid(self.get(tile))
+            || self
+                .object_at(tile, registry)
+                .map_or(false, |(_, id)| registry.is_solid(id))
+    }
+
+    /// Whether an object with `footprint` can be placed on `anchor` without covering solid tiles
+    /// or overlapping other multi-tile objects
+    pub fn is_area_clear(
+        &self,
+        anchor: IVec2,
+        footprint: (u32, u32),
+        registry: &TileRegistry,
+    ) -> bool {
+        (0..footprint.1 as i32).all(|dy| {
+            (0..footprint.0 as i32).all(|dx| {
+                let tile = anchor + IVec2::new(dx, dy);
+                !registry.is_solid(self.get(tile)) && self.object_at(tile, registry).is_none()
+            })
+        })
+    }
+
+    pub fn get_chunk(&self, index: ChunkIndex) -> Option<&PackedChunk> {
+        self.chunks
+            .get(&MortonCode::encode(index.0))
+            .map(|chunk| &**chunk)
+    }
+
+    /// The stored tiles of a chunk, shared rather than copied
+    pub fn shared_chunk(&self, index: ChunkIndex) -> Option<Arc<PackedChunk>> {
+        self.chunks.get(&MortonCode::encode(index.0)).cloned()
+    }
+
+    /// A chunk that shares the stored tiles, or is empty if none are stored
+    pub fn chunk(&self, index: ChunkIndex) -> Chunk {
+        let tiles = self.chunks.get(&MortonCode::encode(index.0));
+        Chunk::from_packed(tiles.unwrap_or(&self.empty).clone())
+    }
+
+    /// Stores the tiles of a chunk, sharing them with the chunk. The chunk is only marked dirty
+    /// if its tiles changed, and empty chunks are only stored to replace a chunk that was not
+    /// empty.
+    pub fn insert_chunk(&mut self, index: ChunkIndex, chunk: &Chunk) {
+        let key = MortonCode::encode(index.0);
+        let tiles = chunk.packed();
+        let changed = match self.chunks.get(&key) {
+            Some(stored) => !Arc::ptr_eq(stored, tiles) && stored != tiles,
+            None => !tiles.is_empty(),
+        };
+        if changed {
+            self.dirty.insert(key);
+        }
+        if self.chunks.contains_key(&key) || changed {
+            self.generating.remove(&key);
+            self.chunks.insert(key, tiles.clone());
+        }
+    }
+
+    /// Stores a chunk that was loaded, without marking it dirty
+    pub fn load_chunk(&mut self, index: ChunkIndex, chunk: PackedChunk) {
+        let key = MortonCode::encode(index.0);
+        self.generating.remove(&key);
+        self.chunks.insert(key, Arc::new(chunk));
+    }
+
+    /// Starts keeping track of the tiles set in a chunk that is being generated, so
+    /// [WorldTileStore::store_generated] keeps them
+    pub(crate) fn start_generating(&mut self, index: ChunkIndex) {
+        self.generating
+            .entry(MortonCode::encode(index.0))
+            .or_default();
+    }
+
+    /// Stores a chunk that finished generating, with the tiles set since it started generating
+    /// over the generated ones. Chunks that were loaded, inserted or removed in the meantime are
+    /// kept as they are.
+    pub(crate) fn store_generated(&mut self, index: ChunkIndex, mut chunk: PackedChunk) {
+        let key = MortonCode::encode(index.0);
+        let edited = self.generating.remove(&key);
+        match (self.chunks.get(&key), edited) {
+            (Some(stored), Some(edited)) => {
+                for (x, y) in edited {
+                    chunk.set(x, y, stored.get(x, y).unwrap());
+                }
+            }
+            (Some(_), None) => return,
+            (None, _) => {}
+        }
+        self.chunks.insert(key, Arc::new(chunk));
+    }
+
+    /// Removes the tiles of a chunk. The chunk stays dirty, so saving it removes it from disk too.
+    pub fn remove_chunk(&mut self, index: ChunkIndex) -> Option<Arc<PackedChunk>> {
+        let key = MortonCode::encode(index.0);
+        self.generating.remove(&key);
+        self.dirty.insert(key);
+        self.chunks.remove(&key)
+    }
+
+    /// The stored chunks in `area`, in Z-order
+    pub fn iter_chunks(&self, area: IRect) -> impl Iterator<Item = (ChunkIndex, &PackedChunk)> {
+        MortonCode::range(&self.chunks, area).map(|(index, chunk)| (ChunkIndex(index), &**chunk))
+    }
+
+    pub fn is_dirty(&self, index: ChunkIndex) -> bool {
+        self.dirty.contains(&MortonCode::encode(index.0))
+    }
+
+    /// Marks a chunk as changed, so it is saved again
+    pub fn mark_dirty(&mut self, index: ChunkIndex) {
+        self.dirty.insert(MortonCode::encode(index.0));
+    }
+
+    /// The chunks that changed since the last call, in Z-order. Clears their dirty flags.
+    pub fn take_dirty(&mut self) -> Vec<ChunkIndex> {
+        let dirty = std::mem::take(&mut self.dirty);
+        dirty
+            .into_iter()
+            .map(|key| ChunkIndex(key.decode()))
+            .collect()
+    }
+
+    pub fn memory_usage(&self) -> TileStoreMemory {
+        let tiles_per_chunk = (self.chunk_size * self.chunk_size) as usize;
+        let chunks = self.chunks.len();
+        TileStoreMemory {
+            chunks,
+            tiles: chunks * tiles_per_chunk,
+            packed_bytes: self.chunks.values().map(|chunk| chunk.heap_size()).sum(),
+            unpacked_bytes: chunks * tiles_per_chunk * std::mem::size_of::<TileId>(),
+        }
+    }
+}
+
+/// Shares the tiles of [Chunk]s that changed with the [WorldTileStore], then the tiles that
+/// changed in the store with their spawned [Chunk]s. When both change in the same frame, the
+/// tiles of the [Chunk] are kept.
+pub fn chunk_store_system(
+    mut store: ResMut<WorldTileStore>,
+    mut chunks: QuerySet<(
+        Query<(&ChunkIndex, &Chunk), Changed<Chunk>>,
+        Query<(&ChunkIndex, &mut Chunk)>,
+    )>,
+) {
+    for (index, chunk) in chunks.q0().iter() {
+        store.insert_chunk(*index, chunk);
+    }
+    for (index, mut chunk) in chunks.q1_mut().iter_mut() {
+        let stored = store.chunks.get(&MortonCode::encode(index.0));
+        if let Some(stored) = stored {
+            if !Arc::ptr_eq(stored, chunk.packed()) {
+                *chunk = Chunk::from_packed(stored.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TileKind;
+
+    #[test]
+    fn packed_tiles() {
+        let mut store = WorldTileStore::new(16);
+        assert_eq!(store.get(IVec2::new(-1, 40)), TileId::EMPTY);
+        assert!(store.set(IVec2::new(-1, 40), TileId(7)));
+        assert!(!store.set(IVec2::new(-1, 40), TileId(7)));
+        assert_eq!(store.get(IVec2::new(-1, 40)), TileId(7));
+        assert_eq!(store.take_dirty(), vec![ChunkIndex(IVec2::new(-1, 2))]);
+        assert!(store.take_dirty().is_empty());
+
+        // growing the palette repacks the chunk without losing tiles
+        let index = ChunkIndex(IVec2::new(-1, 2));
+        for id in 1..20 {
+            store.set(
+                IVec2::new(-16 + id as i32 % 16, 32 + id as i32 / 16),
+                TileId(id),
+            );
+        }
+        let chunk = store.get_chunk(index).unwrap();
+        assert_eq!(chunk.bits_per_tile(), 5);
+        assert_eq!(store.get(IVec2::new(-1, 40)), TileId(7));
+        assert_eq!(store.get(IVec2::new(-13, 33)), TileId(19));
+
+        let tiles = chunk.iter().collect::<Vec<_>>();
+        assert_eq!(&PackedChunk::from_tiles(16, &tiles), chunk);
+        let copy = Chunk::from_tiles(16, &tiles);
+        store.remove_chunk(index);
+        store.insert_chunk(index, &copy);
+        assert!(store.is_dirty(index));
+        store.take_dirty();
+
+        // chunks from the store share its tiles until they are changed
+        let mut shared = store.chunk(index);
+        assert!(Arc::ptr_eq(shared.packed(), copy.packed()));
+        store.insert_chunk(index, &shared);
+        assert!(!store.is_dirty(index));
+        shared.set(0, 0, TileId(2));
+        assert!(!Arc::ptr_eq(shared.packed(), copy.packed()));
+        assert_eq!(store.get(IVec2::new(-16, 32)), TileId::EMPTY);
+
+        let memory = store.memory_usage();
+        assert_eq!(memory.tiles, 256);
+        assert_eq!(memory.unpacked_bytes, 512);
+        assert!(memory.packed_bytes < memory.unpacked_bytes);
+    }
+
+    #[test]
+    fn multi_tile_objects() {
+        let mut registry = TileRegistry::default();
+        registry
+            .register(TileKind {
+                solid: true,
+                ..TileKind::new(TileId(1), "rock")
+            })
+            .unwrap();
+        registry
+            .register(TileKind {
+                solid: true,
+                footprint: (3, 2),
+                ..TileKind::new(TileId(2), "house")
+            })
+            .unwrap();
+        registry
+            .register(TileKind {
+                footprint: (2, 2),
+                ..TileKind::new(TileId(3), "rug")
+            })
+            .unwrap();
+
+        // a house across the boundary of the chunks at x -1 and 0
+        let mut store = WorldTileStore::new(4);
+        let house = IVec2::new(-2, 3);
+        store.set(house, TileId(2));
+        for x in -2..1 {
+            for y in 3..5 {
+                let tile = IVec2::new(x, y);
+                assert_eq!(store.object_at(tile, &registry), Some((house, TileId(2))));
+                assert!(store.is_solid(tile, &registry));
+            }
+        }
+        assert!(!store.is_solid(IVec2::new(1, 3), &registry));
+        assert!(!store.is_solid(IVec2::new(-2, 5), &registry));
+        assert!(!store.is_solid(IVec2::new(-3, 3), &registry));
+        assert_eq!(store.object_at(IVec2::new(-2, 2), &registry), None);
+
+        // objects that aren't solid don't block, but still can't be overlapped
+        store.set(IVec2::new(5, -1), TileId(3));
+        assert!(!store.is_solid(IVec2::new(6, 0), &registry));
+        assert!(!store.is_area_clear(IVec2::new(6, 0), (2, 2), &registry));
+        assert!(store.is_area_clear(IVec2::new(7, 0), (2, 2), &registry));
+        store.set(IVec2::new(8, 1), TileId(1));
+        assert!(!store.is_area_clear(IVec2::new(7, 0), (2, 2), &registry));
+        assert!(!store.is_area_clear(IVec2::new(-1, 2), (1, 2), &registry));
+        assert!(store.is_area_clear(IVec2::new(-1, 1), (1, 2), &registry));
+    }
+}
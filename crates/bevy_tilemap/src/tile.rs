@@ -0,0 +1,316 @@
+use anyhow::Result;
+use bevy_app::{EventReader, Events};
+use bevy_asset::{AssetEvent, AssetLoader, Assets, LoadContext, LoadedAsset};
+use bevy_ecs::{Local, Res, ResMut};
+use bevy_reflect::TypeUuid;
+use bevy_utils::{tracing::error, BoxedFuture, HashMap};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The stable numeric id of a [TileKind]. Chunks store tiles as ids, so the id of a kind must not
+/// change once worlds have been saved with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TileId(pub u16);
+
+impl TileId {
+    /// The id of empty tiles. It can't be registered.
+    pub const EMPTY: TileId = TileId(0);
+}
+
+impl Default for TileId {
+    fn default() -> Self {
+        TileId::EMPTY
+    }
+}
+
+/// A custom property of a [TileKind], like how slippery it is
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TileProperty {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+/// A kind of tile, like dirt or grass
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TileKind {
+    pub id: TileId,
+    pub name: String,
+    /// The index of the tile's sprite in the tile atlas
+    #[serde(default)]
+    pub sprite: Option<u32>,
+    /// Whether the tile blocks movement
+    #[serde(default)]
+    pub solid: bool,
+    /// The asset path of a [Prefab](bevy_scene::Prefab) that is spawned on every tile of this
+    /// kind while its chunk is spawned, like a tree with a collider on a tree tile
+    #[serde(default)]
+    pub prefab: Option<String>,
+    /// The tiles an object of this kind covers, in tiles to the right of and above the tile it is
+    /// placed on, like `(3, 2)` for a house. The object blocks movement on all of them if it is
+    /// solid, and is drawn as a single sprite over all of them by its [TileEntity], rather than
+    /// baked into the chunk, so its sprite must be as large as its footprint.
+    ///
+    /// [TileEntity]: crate::TileEntity
+    #[serde(default = "single_tile")]
+    pub footprint: (u32, u32),
+    #[serde(default)]
+    pub properties: HashMap<String, TileProperty>,
+}
+
+impl TileKind {
+    pub fn new(id: TileId, name: impl Into<String>) -> Self {
+        TileKind {
+            id,
+            name: name.into(),
+            sprite: None,
+            solid: false,
+            prefab: None,
+            footprint: single_tile(),
+            properties: HashMap::default(),
+        }
+    }
+
+    /// Whether objects of this kind cover more than the tile they are placed on
+    pub fn is_multi_tile(&self) -> bool {
+        self.footprint != single_tile()
+    }
+
+    pub fn property(&self, name: &str) -> Option<&TileProperty> {
+        self.properties.get(name)
+    }
+}
+
+fn single_tile() -> (u32, u32) {
+    (1, 1)
+}
+
+/// Tile kinds loaded from a `.tiles` file, which lists them in RON:
+///
+/// ```text
+/// [
+///     (id: 1, name: "dirt", sprite: Some(0), solid: true),
+///     (id: 2, name: "grass", sprite: Some(1), solid: true, properties: {
+///         "spreads": Bool(true),
+///     }),
+///     (id: 3, name: "water", sprite: Some(2)),
+///     (id: 4, name: "tree", sprite: Some(1), prefab: Some("prefabs/tree.prefab")),
+///     (id: 5, name: "house", sprite: Some(3), solid: true, footprint: (3, 2)),
+/// ]
+/// ```
+///
+/// Loaded kinds are added to the [TileRegistry], so mods can add tiles by shipping their own
+/// files.
+#[derive(Debug, Clone, TypeUuid)]
+#[uuid = "3c0c4f4e-8f5d-4b8e-9d55-2f1c0a7b6e21"]
+pub struct TileKinds {
+    pub kinds: Vec<TileKind>,
+}
+
+#[derive(Default)]
+pub struct TileKindsLoader;
+
+impl AssetLoader for TileKindsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let kinds = ron::de::from_bytes::<Vec<TileKind>>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(TileKinds { kinds }));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tiles"]
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum TileRegistryError {
+    #[error("Tile id {0:?} is reserved for empty tiles.")]
+    ReservedId(TileId),
+    #[error("Tile id {id:?} of `{name}` is already used by `{existing}`.")]
+    IdTaken {
+        id: TileId,
+        name: String,
+        existing: String,
+    },
+    #[error("Tile `{name}` is already registered with id {existing:?}.")]
+    NameTaken { name: String, existing: TileId },
+}
+
+/// The kinds of tiles, by id and by name
+#[derive(Debug, Default)]
+pub struct TileRegistry {
+    kinds: HashMap<TileId, TileKind>,
+    ids: HashMap<String, TileId>,
+    max_footprint: (u32, u32),
+}
+
+impl TileRegistry {
+    /// Adds a tile kind, or replaces the kind with the same id and name. Ids and names can't be
+    /// reassigned to other kinds.
+    pub fn register(&mut self, kind: TileKind) -> Result<(), TileRegistryError> {
+        if kind.id == TileId::EMPTY {
+            return Err(TileRegistryError::ReservedId(kind.id));
+        }
+        if let Some(existing) = self.kinds.get(&kind.id) {
+            if existing.name != kind.name {
+                return Err(TileRegistryError::IdTaken {
+                    id: kind.id,
+                    name: kind.name,
+                    existing: existing.name.clone(),
+                });
+            }
+        }
+        if let Some(existing) = self.ids.get(&kind.name) {
+            if *existing != kind.id {
+                return Err(TileRegistryError::NameTaken {
+                    name: kind.name,
+                    existing: *existing,
+                });
+            }
+        }
+        self.max_footprint = (
+            self.max_footprint.0.max(kind.footprint.0),
+            self.max_footprint.1.max(kind.footprint.1),
+        );
+        self.ids.insert(kind.name.clone(), kind.id);
+        self.kinds.insert(kind.id, kind);
+        Ok(())
+    }
+
+    pub fn get(&self, id: TileId) -> Option<&TileKind> {
+        self.kinds.get(&id)
+    }
+
+    pub fn get_with_name(&self, name: &str) -> Option<&TileKind> {
+        self.id(name).and_then(|id| self.get(id))
+    }
+
+    pub fn id(&self, name: &str) -> Option<TileId> {
+        self.ids.get(name).copied()
+    }
+
+    /// Whether tiles with this id block movement. Empty and unknown tiles don't.
+    pub fn is_solid(&self, id: TileId) -> bool {
+        self.get(id).map_or(false, |kind| kind.solid)
+    }
+
+    /// The largest width and height of the footprints of the registered kinds, which bounds how
+    /// far away the object covering a tile can be placed
+    pub fn max_footprint(&self) -> (u32, u32) {
+        self.max_footprint
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &TileKind> {
+        self.kinds.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.kinds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.kinds.is_empty()
+    }
+}
+
+/// Registers the tile kinds of loaded and changed [TileKinds]. Kinds stay registered when their
+/// file is unloaded, because chunks may still contain them.
+pub fn tile_registry_system(
+    mut event_reader: Local<EventReader<AssetEvent<TileKinds>>>,
+    events: Res<Events<AssetEvent<TileKinds>>>,
+    tile_kinds: Res<Assets<TileKinds>>,
+    mut registry: ResMut<TileRegistry>,
+) {
+    for event in event_reader.iter(&events) {
+        match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                if let Some(tile_kinds) = tile_kinds.get(handle) {
+                    for kind in tile_kinds.kinds.iter() {
+                        if let Err(err) = registry.register(kind.clone()) {
+                            error!("failed to register tile kind: {}", err);
+                        }
+                    }
+                }
+            }
+            AssetEvent::Removed { .. } => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn register_kinds() {
+        let mut registry = TileRegistry::default();
+        registry.register(TileKind::new(TileId(1), "dirt")).unwrap();
+        let grass = TileKind {
+            solid: true,
+            ..TileKind::new(TileId(2), "grass")
+        };
+        registry.register(grass).unwrap();
+
+        assert_eq!(registry.id("grass"), Some(TileId(2)));
+        assert_eq!(registry.max_footprint(), (1, 1));
+        assert!(registry.is_solid(TileId(2)));
+        assert!(!registry.is_solid(TileId(1)));
+        assert!(!registry.is_solid(TileId::EMPTY));
+        assert_eq!(
+            registry.register(TileKind::new(TileId::EMPTY, "air")),
+            Err(TileRegistryError::ReservedId(TileId::EMPTY))
+        );
+        assert_eq!(
+            registry.register(TileKind::new(TileId(1), "sand")),
+            Err(TileRegistryError::IdTaken {
+                id: TileId(1),
+                name: "sand".to_string(),
+                existing: "dirt".to_string(),
+            })
+        );
+        assert_eq!(
+            registry.register(TileKind::new(TileId(3), "dirt")),
+            Err(TileRegistryError::NameTaken {
+                name: "dirt".to_string(),
+                existing: TileId(1),
+            })
+        );
+
+        // reloading a kind replaces it
+        registry
+            .register(TileKind::new(TileId(2), "grass"))
+            .unwrap();
+        assert!(!registry.is_solid(TileId(2)));
+        assert_eq!(registry.len(), 2);
+    }
+
+    #[test]
+    fn parse_tile_kinds() {
+        let kinds = ron::de::from_str::<Vec<TileKind>>(
+            r#"[
+                (id: 1, name: "dirt", sprite: Some(0), solid: true),
+                (id: 2, name: "ice", properties: { "friction": Float(0.1) }),
+                (id: 3, name: "house", footprint: (3, 2)),
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(kinds[0].sprite, Some(0));
+        assert!(kinds[0].solid);
+        assert_eq!(kinds[1].sprite, None);
+        assert!(!kinds[1].is_multi_tile());
+        assert_eq!(kinds[2].footprint, (3, 2));
+        assert!(kinds[2].is_multi_tile());
+        assert_eq!(
+            kinds[1].property("friction"),
+            Some(&TileProperty::Float(0.1))
+        );
+    }
+}
@@ -0,0 +1,434 @@
+use crate::{Chunk, ChunkIndex, TileAtlas, TileId, TileRegistry, WorldTileStore};
+use bevy_asset::{AssetServer, Handle};
+use bevy_ecs::{Entity, Resources, World};
+use bevy_math::{IVec2, Vec2, Vec3};
+use bevy_reflect::{Reflect, ReflectComponent, TypeRegistry, TypeRegistryArc};
+use bevy_scene::{Prefab, PrefabSpawner, SaveGameRegistry};
+use bevy_sprite::{entity::SpriteSheetBundle, TextureAtlasSprite};
+use bevy_transform::{
+    components::{GlobalTransform, Transform},
+    hierarchy::despawn_with_children_recursive,
+};
+use bevy_utils::HashMap;
+
+/// An entity spawned from the [Prefab] of the [TileKind](crate::TileKind) of the tile at `tile`.
+/// Multi-tile objects also get their sprite, drawn over their whole footprint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileEntity {
+    pub tile: IVec2,
+    pub kind: TileId,
+}
+
+#[derive(Debug)]
+struct HydratedChunk {
+    entity: Entity,
+    /// The tiles of `tile_entities`, in the same order
+    tiles: Vec<IVec2>,
+    tile_entities: Vec<Entity>,
+}
+
+/// A spawned tile entity, and how many spawned chunks it is in
+#[derive(Debug)]
+struct SpawnedEntity {
+    entity: Entity,
+    chunks: usize,
+}
+
+#[derive(Debug)]
+struct SavedTileEntity {
+    kind: TileId,
+    components: Vec<Box<dyn Reflect>>,
+}
+
+/// The [TileEntity]s of the spawned chunks, and the saved state of those in despawned chunks.
+///
+/// When a chunk is spawned, [tile_entity_system] spawns an entity from the prefab of every tile
+/// in it whose kind has one, and for every multi-tile object whose footprint covers part of it.
+/// An object that straddles the boundary between chunks is spawned once, while any of them is
+/// spawned. When the last of those chunks is despawned, the components of the entity that are
+/// registered with [SaveGameRegistry](bevy_scene::SaveGameRegistry) are saved and the entity
+/// despawned. The saved components are added back when the entity is spawned again, replacing
+/// the components of the prefab, as long as the tile has not changed kind in between.
+#[derive(Debug, Default)]
+pub struct TileEntities {
+    hydrated: HashMap<ChunkIndex, HydratedChunk>,
+    spawned: HashMap<IVec2, SpawnedEntity>,
+    saved: HashMap<IVec2, SavedTileEntity>,
+    prefabs: HashMap<String, Handle<Prefab>>,
+}
+
+impl TileEntities {
+    /// The tile entities of a chunk, including the objects of other chunks that reach into it
+    pub fn get(&self, index: ChunkIndex) -> &[Entity] {
+        self.hydrated
+            .get(&index)
+            .map_or(&[], |chunk| &chunk.tile_entities[..])
+    }
+
+    /// The saved components of the tile entity at `tile`, if its chunk is despawned
+    pub fn saved_components(&self, tile: IVec2) -> Option<&[Box<dyn Reflect>]> {
+        self.saved.get(&tile).map(|saved| &saved.components[..])
+    }
+}
+
+/// Spawns the [TileEntity]s of chunks that were spawned, and saves and despawns those of chunks
+/// that were despawned
+pub fn tile_entity_system(world: &mut World, resources: &mut Resources) {
+    let mut prefab_spawner = resources.get_mut::<PrefabSpawner>();
+    let mut tile_entities = resources.get_mut::<TileEntities>().unwrap();
+    let tile_entities = &mut *tile_entities;
+    let type_registry = resources.get::<TypeRegistryArc>().unwrap();
+    let type_registry = type_registry.read();
+
+    let despawned = tile_entities
+        .hydrated
+        .iter()
+        .filter(|(_, chunk)| !world.contains(chunk.entity))
+        .map(|(index, _)| *index)
+        .collect::<Vec<_>>();
+    let save_registry = resources.get::<SaveGameRegistry>();
+    for index in despawned {
+        let chunk = tile_entities.hydrated.remove(&index).unwrap();
+        for (tile, entity) in chunk.tiles.into_iter().zip(chunk.tile_entities) {
+            // objects stay while another chunk they reach into is spawned
+            if let Some(spawned) = tile_entities.spawned.get_mut(&tile) {
+                spawned.chunks -= 1;
+                if spawned.chunks > 0 {
+                    continue;
+                }
+                tile_entities.spawned.remove(&tile);
+            }
+            let tile_entity = match world.get::<TileEntity>(entity) {
+                Ok(tile_entity) => *tile_entity,
+                Err(_) => continue,
+            };
+            if let Some(save_registry) = &save_registry {
+                let components = saved_components(world, save_registry, &type_registry, entity);
+                tile_entities.saved.insert(
+                    tile_entity.tile,
+                    SavedTileEntity {
+                        kind: tile_entity.kind,
+                        components,
+                    },
+                );
+            }
+            despawn_with_children_recursive(world, entity);
+        }
+    }
+
+    let tile_registry = resources.get::<TileRegistry>().unwrap();
+    let store = resources.get::<WorldTileStore>().unwrap();
+    let (max_width, max_height) = tile_registry.max_footprint();
+    let mut spawned = Vec::new();
+    for (entity, index, chunk) in world.query::<(Entity, &ChunkIndex, &Chunk)>() {
+        if tile_entities.hydrated.contains_key(index) {
+            continue;
+        }
+        let size = chunk.size() as i32;
+        let origin = index.0 * size;
+        let mut tiles = Vec::new();
+        // objects placed on the chunks below and left of this one can reach into it
+        for y in origin.y - max_height.saturating_sub(1) as i32..origin.y + size {
+            for x in origin.x - max_width.saturating_sub(1) as i32..origin.x + size {
+                let tile = IVec2::new(x, y);
+                let local = tile - origin;
+                let in_chunk = local.x >= 0 && local.y >= 0;
+                let id = if in_chunk {
+                    chunk.get(local.x as u32, local.y as u32).unwrap()
+                } else {
+                    store.get(tile)
+                };
+                let kind = match tile_registry.get(id) {
+                    Some(kind) => kind,
+                    None => continue,
+                };
+                let reaches_chunk = kind.is_multi_tile()
+                    && x + kind.footprint.0 as i32 > origin.x
+                    && y + kind.footprint.1 as i32 > origin.y;
+                if !in_chunk && !reaches_chunk {
+                    continue;
+                }
+                // prefabs are only spawned when there is a prefab spawner to spawn them
+                let prefab = kind.prefab.clone().filter(|_| prefab_spawner.is_some());
+                let sprite = kind.sprite.filter(|_| kind.is_multi_tile());
+                if prefab.is_none() && sprite.is_none() {
+                    continue;
+                }
+                tiles.push(SpawnedTileEntity {
+                    tile,
+                    kind: id,
+                    prefab,
+                    sprite,
+                    footprint: kind.footprint,
+                });
+            }
+        }
+        spawned.push((entity, *index, tiles));
+    }
+    if spawned.is_empty() {
+        return;
+    }
+
+    let asset_server = resources.get::<AssetServer>().unwrap();
+    let tile_atlas = resources.get::<TileAtlas>().unwrap();
+    let tile_size = tile_atlas.tile_size as f32;
+    for (chunk_entity, index, tiles) in spawned {
+        let mut chunk = HydratedChunk {
+            entity: chunk_entity,
+            tiles: Vec::with_capacity(tiles.len()),
+            tile_entities: Vec::with_capacity(tiles.len()),
+        };
+        for spawned in tiles {
+            // objects already spawned by another chunk they reach into are shared with it
+            if let Some(spawned_entity) = tile_entities.spawned.get_mut(&spawned.tile) {
+                spawned_entity.chunks += 1;
+                chunk.tiles.push(spawned.tile);
+                chunk.tile_entities.push(spawned_entity.entity);
+                continue;
+            }
+            let SpawnedTileEntity {
+                tile,
+                kind,
+                prefab,
+                sprite,
+                footprint,
+            } = spawned;
+            // objects are centered on their footprint
+            let footprint = Vec2::new(footprint.0 as f32, footprint.1 as f32);
+            let translation = ((tile.as_vec2() + footprint / 2.0) * tile_size).extend(0.0);
+            let entity = match sprite {
+                Some(sprite) => {
+                    // drawn over the chunks, which are at a z of 0
+                    let translation = translation + Vec3::unit_z();
+                    let entity = world.spawn(SpriteSheetBundle {
+                        sprite: TextureAtlasSprite::new(sprite),
+                        texture_atlas: tile_atlas.atlas.clone(),
+                        transform: Transform::from_translation(translation),
+                        global_transform: GlobalTransform::from_translation(translation),
+                        ..Default::default()
+                    });
+                    world.insert_one(entity, TileEntity { tile, kind }).unwrap();
+                    entity
+                }
+                None => world.spawn((
+                    TileEntity { tile, kind },
+                    Transform::from_translation(translation),
+                    GlobalTransform::from_translation(translation),
+                )),
+            };
+            if let Some(saved) = tile_entities.saved.remove(&tile) {
+                if saved.kind == kind {
+                    for component in saved.components.iter() {
+                        if let Some(reflect_component) = type_registry
+                            .get_with_name(component.type_name())
+                            .and_then(|registration| registration.data::<ReflectComponent>())
+                        {
+                            reflect_component.add_component(world, resources, entity, &**component);
+                        }
+                    }
+                }
+            }
+            if let (Some(prefab), Some(prefab_spawner)) = (prefab, &mut prefab_spawner) {
+                let handle = match tile_entities.prefabs.get(&prefab) {
+                    Some(handle) => handle.clone(),
+                    None => {
+                        let handle = asset_server.load(prefab.as_str());
+                        tile_entities.prefabs.insert(prefab, handle.clone());
+                        handle
+                    }
+                };
+                prefab_spawner.spawn(entity, handle);
+            }
+            tile_entities
+                .spawned
+                .insert(tile, SpawnedEntity { entity, chunks: 1 });
+            chunk.tiles.push(tile);
+            chunk.tile_entities.push(entity);
+        }
+        tile_entities.hydrated.insert(index, chunk);
+    }
+    if let Some(prefab_spawner) = &mut prefab_spawner {
+        prefab_spawner.spawn_queued_prefabs(world, resources);
+    }
+}
+
+/// A tile entity [tile_entity_system] is about to spawn
+struct SpawnedTileEntity {
+    tile: IVec2,
+    kind: TileId,
+    prefab: Option<String>,
+    sprite: Option<u32>,
+    footprint: (u32, u32),
+}
+
+/// The components of `entity` that save games include
+pub(crate) fn saved_components(
+    world: &World,
+    save_registry: &SaveGameRegistry,
+    type_registry: &TypeRegistry,
+    entity: Entity,
+) -> Vec<Box<dyn Reflect>> {
+    let location = match world.get_entity_location(entity) {
+        Some(location) => location,
+        None => return Vec::new(),
+    };
+    let archetype = world.archetypes().nth(location.archetype as usize).unwrap();
+    archetype
+        .types()
+        .iter()
+        .filter(|type_info| save_registry.saves_component(type_info.id()))
+        .filter_map(|type_info| type_registry.get(type_info.id()))
+        .filter_map(|registration| registration.data::<ReflectComponent>())
+        // SAFE: the index comes directly from the location of a live entity
+        .map(|reflect_component| unsafe {
+            reflect_component
+                .reflect_component(archetype, location.index)
+                .clone_value()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TileKind;
+    use bevy_app::App;
+    use bevy_asset::{AddAsset, AssetPlugin, Assets, FileAssetIo};
+    use bevy_reflect::ReflectPlugin;
+    use bevy_scene::RegisterSaveGame;
+    use bevy_tasks::TaskPool;
+
+    #[derive(Reflect, Default, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Health {
+        value: u32,
+    }
+
+    #[test]
+    fn hydrate_tile_entities() {
+        let asset_server = AssetServer::new(FileAssetIo::new(""), TaskPool::new());
+        let mut app = App::build();
+        app.add_resource(asset_server)
+            .add_plugin(ReflectPlugin)
+            .add_plugin(AssetPlugin)
+            .add_asset::<Prefab>()
+            .init_resource::<PrefabSpawner>()
+            .init_resource::<TileEntities>()
+            .init_resource::<TileAtlas>()
+            .add_resource(WorldTileStore::new(4))
+            .register_save_component::<Health>();
+        let App {
+            mut world,
+            mut resources,
+            ..
+        } = app.app;
+
+        let mut registry = TileRegistry::default();
+        registry
+            .register(TileKind {
+                prefab: Some("tree.prefab".to_string()),
+                ..TileKind::new(TileId(1), "tree")
+            })
+            .unwrap();
+        registry
+            .register(TileKind {
+                sprite: Some(3),
+                footprint: (2, 2),
+                ..TileKind::new(TileId(2), "house")
+            })
+            .unwrap();
+        resources.insert(registry);
+        let handle: Handle<Prefab> = resources
+            .get::<AssetServer>()
+            .unwrap()
+            .get_handle("tree.prefab");
+        resources
+            .get_mut::<Assets<Prefab>>()
+            .unwrap()
+            .set_untracked(
+                handle,
+                Prefab {
+                    components: vec![Box::new(Health { value: 10 })],
+                    children: Vec::new(),
+                },
+            );
+
+        let mut chunk = Chunk::new(4);
+        chunk.set(1, 2, TileId(1));
+        chunk.set(3, 2, TileId(2));
+        let index = ChunkIndex(IVec2::new(-1, 0));
+        let neighbor = ChunkIndex(IVec2::new(0, 0));
+        // the chunks are spawned apart from the store, which only needs the house
+        resources
+            .get_mut::<WorldTileStore>()
+            .unwrap()
+            .set(IVec2::new(-1, 2), TileId(2));
+        let chunk_entity = world.spawn((index, chunk.clone()));
+        tile_entity_system(&mut world, &mut resources);
+
+        let tree = resources.get::<TileEntities>().unwrap().get(index)[0];
+        assert_eq!(world.get::<Health>(tree).unwrap().value, 10);
+        assert_eq!(
+            *world.get::<TileEntity>(tree).unwrap(),
+            TileEntity {
+                tile: IVec2::new(-3, 2),
+                kind: TileId(1)
+            }
+        );
+        assert_eq!(
+            world.get::<Transform>(tree).unwrap().translation,
+            Vec2::new(-2.5 * 16.0, 2.5 * 16.0).extend(0.0)
+        );
+
+        // the house is drawn over its footprint, which straddles the boundary to the next chunk
+        let house = resources.get::<TileEntities>().unwrap().get(index)[1];
+        assert_eq!(world.get::<TextureAtlasSprite>(house).unwrap().index, 3);
+        assert_eq!(
+            world.get::<Transform>(house).unwrap().translation,
+            Vec2::new(0.0, 3.0 * 16.0).extend(1.0)
+        );
+
+        // the next chunk shares the house instead of spawning it again
+        let neighbor_entity = world.spawn((neighbor, Chunk::new(4)));
+        tile_entity_system(&mut world, &mut resources);
+        assert_eq!(
+            resources.get::<TileEntities>().unwrap().get(neighbor),
+            &[house]
+        );
+
+        // the state of the tree is kept while its chunk is despawned, and the house stays while
+        // the next chunk is spawned
+        world.get_mut::<Health>(tree).unwrap().value = 3;
+        world.despawn(chunk_entity).unwrap();
+        tile_entity_system(&mut world, &mut resources);
+        assert!(!world.contains(tree));
+        assert!(world.contains(house));
+        assert!(resources
+            .get::<TileEntities>()
+            .unwrap()
+            .saved_components(IVec2::new(-3, 2))
+            .is_some());
+
+        // the house is spawned from the next chunk alone
+        world.despawn(neighbor_entity).unwrap();
+        tile_entity_system(&mut world, &mut resources);
+        assert!(!world.contains(house));
+        world.spawn((neighbor, Chunk::new(4)));
+        tile_entity_system(&mut world, &mut resources);
+        let house = resources.get::<TileEntities>().unwrap().get(neighbor)[0];
+        assert_eq!(
+            *world.get::<TileEntity>(house).unwrap(),
+            TileEntity {
+                tile: IVec2::new(-1, 2),
+                kind: TileId(2)
+            }
+        );
+
+        world.spawn((index, chunk));
+        tile_entity_system(&mut world, &mut resources);
+        let tile_entities = resources.get::<TileEntities>().unwrap();
+        let tree = tile_entities.get(index)[0];
+        assert_eq!(world.get::<Health>(tree).unwrap().value, 3);
+        assert_eq!(tile_entities.get(index)[1], house);
+    }
+}
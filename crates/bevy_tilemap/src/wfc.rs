@@ -0,0 +1,536 @@
+use crate::{ChunkIndex, ChunkRng, GeneratedChunk, TileId, WorldGenerator};
+use bevy_math::IVec2;
+use bevy_utils::{tracing::warn, FixedBitSet};
+
+/// The offsets of the sides of a tile, right, up, left and down. The opposite of a side is two
+/// sides further.
+const SIDES: [(i32, i32); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+const RIGHT: usize = 0;
+const UP: usize = 1;
+const LEFT: usize = 2;
+const DOWN: usize = 3;
+
+/// Salts of the seeds of the tiles [WfcGenerator] places on the edges of chunks
+const CORNER_SALT: u64 = 0x2f6b_1a3c_84d5_e901;
+const COLUMN_SALT: u64 = 0x5c3e_97a1_0b2d_f468;
+const ROW_SALT: u64 = 0x91d4_26f8_e37a_0c5b;
+
+fn opposite(side: usize) -> usize {
+    (side + 2) % 4
+}
+
+/// Which tiles a [WfcGenerator] may place next to each other, and how often it picks each tile
+///
+/// ```ignore
+/// let rules = WfcRules::new()
+///     .with_tile(water, 2.0)
+///     .with_tile(sand, 1.0)
+///     .with_horizontal(water, sand)
+///     .with_horizontal(sand, water)
+///     .with_vertical(water, sand)
+///     .with_vertical(sand, water);
+/// ```
+///
+/// Tiles are not allowed next to anything, not even themselves, until they are allowed with
+/// [WfcRules::with_horizontal] and [WfcRules::with_vertical].
+#[derive(Debug, Clone, Default)]
+pub struct WfcRules {
+    tiles: Vec<TileId>,
+    weights: Vec<f32>,
+    /// The tiles allowed on each side of each tile, by their index in `tiles`
+    neighbors: Vec<[FixedBitSet; 4]>,
+}
+
+impl WfcRules {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Learns the rules from an example map, given as rows of `width` tiles from the bottom row
+    /// up. Tiles are allowed next to each other if they are next to each other in the example,
+    /// and are picked as often as they appear in it.
+    pub fn from_example(tiles: &[TileId], width: u32) -> Self {
+        let mut rules = WfcRules::new();
+        if width == 0 {
+            return rules;
+        }
+        let width = width as usize;
+        for &tile in tiles {
+            rules.index(tile);
+        }
+        for weight in rules.weights.iter_mut() {
+            *weight = 0.0;
+        }
+        for (i, &tile) in tiles.iter().enumerate() {
+            let index = rules.index(tile);
+            rules.weights[index] += 1.0;
+            if (i + 1) % width != 0 && i + 1 < tiles.len() {
+                rules.allow(tile, tiles[i + 1], RIGHT);
+            }
+            if i + width < tiles.len() {
+                rules.allow(tile, tiles[i + width], UP);
+            }
+        }
+        rules
+    }
+
+    /// Picks `tile` with `weight` relative to the other tiles
+    pub fn with_tile(mut self, tile: TileId, weight: f32) -> Self {
+        let index = self.index(tile);
+        self.weights[index] = weight;
+        self
+    }
+
+    /// Allows `right` on the right of `left`
+    pub fn with_horizontal(mut self, left: TileId, right: TileId) -> Self {
+        self.allow(left, right, RIGHT);
+        self
+    }
+
+    /// Allows `above` above `below`
+    pub fn with_vertical(mut self, below: TileId, above: TileId) -> Self {
+        self.allow(below, above, UP);
+        self
+    }
+
+    /// Whether `right` is allowed on the right of `left`
+    pub fn allows_horizontal(&self, left: TileId, right: TileId) -> bool {
+        self.allows(left, right, RIGHT)
+    }
+
+    /// Whether `above` is allowed above `below`
+    pub fn allows_vertical(&self, below: TileId, above: TileId) -> bool {
+        self.allows(below, above, UP)
+    }
+
+    /// The tiles the rules place
+    pub fn tiles(&self) -> &[TileId] {
+        &self.tiles
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// The index of `tile`, which is added with a weight of 1 if it is new
+    fn index(&mut self, tile: TileId) -> usize {
+        if let Some(index) = self.tiles.iter().position(|&other| other == tile) {
+            return index;
+        }
+        self.tiles.push(tile);
+        self.weights.push(1.0);
+        let len = self.tiles.len();
+        self.neighbors.push(Default::default());
+        for sides in self.neighbors.iter_mut() {
+            for allowed in sides.iter_mut() {
+                allowed.grow(len);
+            }
+        }
+        len - 1
+    }
+
+    fn allow(&mut self, tile: TileId, neighbor: TileId, side: usize) {
+        let tile = self.index(tile);
+        let neighbor = self.index(neighbor);
+        self.neighbors[tile][side].insert(neighbor);
+        self.neighbors[neighbor][opposite(side)].insert(tile);
+    }
+
+    fn allows(&self, tile: TileId, neighbor: TileId, side: usize) -> bool {
+        let position = |tile: TileId| self.tiles.iter().position(|&other| other == tile);
+        match (position(tile), position(neighbor)) {
+            (Some(tile), Some(neighbor)) => self.neighbors[tile][side].contains(neighbor),
+            _ => false,
+        }
+    }
+
+    /// Picks one of the `tiles` by weight
+    fn pick(&self, tiles: &FixedBitSet, rng: &mut ChunkRng) -> usize {
+        let total: f32 = tiles.ones().map(|tile| self.weights[tile]).sum();
+        let mut target = rng.next_f32() * total;
+        for tile in tiles.ones() {
+            target -= self.weights[tile];
+            if target < 0.0 {
+                return tile;
+            }
+        }
+        tiles.ones().last().unwrap_or(0)
+    }
+
+    fn all(&self) -> FixedBitSet {
+        let mut all = FixedBitSet::with_capacity(self.len());
+        all.insert_range(..);
+        all
+    }
+
+    fn only(&self, tile: usize) -> FixedBitSet {
+        let mut only = FixedBitSet::with_capacity(self.len());
+        only.insert(tile);
+        only
+    }
+}
+
+/// Generates chunks with wave function collapse, so every tile is next to tiles the
+/// [WfcRules] allow next to it, like coasts that always have sand between water and grass.
+///
+/// Chunks are generated on their own, but their edges still line up. The tiles on the left and
+/// bottom edges of every chunk are picked first, from the seed of the world and the position of
+/// the edge, so the chunks on either side of an edge agree on them. Rules that can't get from
+/// every tile to every other tile within a chunk, like checkerboards, can't always be followed
+/// on the edges. Tiles that break the rules are picked at random, and the chunk is generated
+/// again up to [WfcGenerator::with_attempts] times to get rid of them.
+///
+/// ```ignore
+/// app.add_resource(ChunkGenerator::new(WfcGenerator::new(rules)).with_seed(42));
+/// ```
+#[derive(Debug, Clone)]
+pub struct WfcGenerator {
+    rules: WfcRules,
+    attempts: u32,
+}
+
+impl WfcGenerator {
+    /// Generates chunks with `rules`, generating a chunk up to 10 times until it follows them
+    pub fn new(rules: WfcRules) -> Self {
+        WfcGenerator {
+            rules,
+            attempts: 10,
+        }
+    }
+
+    pub fn with_attempts(mut self, attempts: u32) -> Self {
+        self.attempts = attempts;
+        self
+    }
+
+    pub fn rules(&self) -> &WfcRules {
+        &self.rules
+    }
+
+    /// The tile on the bottom left corner of the chunk at `chunk`
+    fn corner(&self, seed: u64, chunk: IVec2) -> usize {
+        let mut rng = ChunkRng::new(seed ^ CORNER_SALT, ChunkIndex(chunk));
+        self.rules.pick(&self.rules.all(), &mut rng)
+    }
+
+    /// The tiles on the left edge of the chunk at `chunk` from the bottom up, or on its bottom
+    /// edge from the left if not `vertical`. They start at the corner of the chunk, and lead to
+    /// the corner of the next chunk along the edge.
+    fn edge(&self, seed: u64, chunk: IVec2, vertical: bool, size: u32) -> Vec<usize> {
+        let (next, side, salt, width, height) = if vertical {
+            (IVec2::new(0, 1), UP, COLUMN_SALT, 1, size)
+        } else {
+            (IVec2::new(1, 0), RIGHT, ROW_SALT, size, 1)
+        };
+        let start = self.corner(seed, chunk);
+        let end = self.corner(seed, chunk + next);
+        let (last_x, last_y) = (width - 1, height - 1);
+        let mut wave = Wave::new(&self.rules, width, height);
+        let restrictions = [
+            (0, 0, self.rules.only(start)),
+            (
+                last_x,
+                last_y,
+                self.rules.neighbors[end][opposite(side)].clone(),
+            ),
+        ];
+        let mut rng = ChunkRng::new(seed ^ salt, ChunkIndex(chunk));
+        wave.solve(&restrictions, &mut rng, self.attempts)
+    }
+}
+
+impl WorldGenerator for WfcGenerator {
+    fn generate(&self, chunk: &mut GeneratedChunk) {
+        if self.rules.is_empty() {
+            return;
+        }
+        let size = chunk.size();
+        let index = chunk.index.0;
+        let last = size - 1;
+        let left = self.edge(chunk.seed, index, true, size);
+        let bottom = self.edge(chunk.seed, index, false, size);
+        // the edges of the neighbors on the right and above
+        let right = self.edge(chunk.seed, index + IVec2::new(1, 0), true, size);
+        let top = self.edge(chunk.seed, index + IVec2::new(0, 1), false, size);
+
+        let mut restrictions = Vec::with_capacity(size as usize * 4);
+        for i in 0..size {
+            let rules = &self.rules;
+            restrictions.push((0, i, rules.only(left[i as usize])));
+            restrictions.push((i, 0, rules.only(bottom[i as usize])));
+            restrictions.push((last, i, rules.neighbors[right[i as usize]][LEFT].clone()));
+            restrictions.push((i, last, rules.neighbors[top[i as usize]][DOWN].clone()));
+        }
+        let mut wave = Wave::new(&self.rules, size, size);
+        let tiles = wave.solve(&restrictions, &mut chunk.rng, self.attempts);
+        if wave.contradictions > 0 {
+            warn!(
+                "Chunk {:?} breaks the wave function collapse rules on {} tiles.",
+                chunk.index, wave.contradictions
+            );
+        }
+        for y in 0..size {
+            for x in 0..size {
+                let tile = self.rules.tiles[tiles[(y * size + x) as usize]];
+                chunk.set(x, y, tile);
+            }
+        }
+    }
+}
+
+/// A grid of cells that can each still be any of a set of tiles, which is collapsed to a tile
+/// per cell
+struct Wave<'a> {
+    rules: &'a WfcRules,
+    width: u32,
+    height: u32,
+    cells: Vec<FixedBitSet>,
+    /// Cells whose neighbors left them no tiles. They don't restrict their neighbors, and are
+    /// picked at random.
+    broken: FixedBitSet,
+    /// Cells that lost tiles, whose neighbors may have to lose tiles too
+    changed: Vec<usize>,
+    contradictions: usize,
+}
+
+impl<'a> Wave<'a> {
+    fn new(rules: &'a WfcRules, width: u32, height: u32) -> Self {
+        let len = (width * height) as usize;
+        Wave {
+            rules,
+            width,
+            height,
+            cells: Vec::new(),
+            broken: FixedBitSet::with_capacity(len),
+            changed: Vec::new(),
+            contradictions: 0,
+        }
+    }
+
+    /// The tiles of the cells, row by row from the bottom, restricted to the tiles given for some
+    /// of them. Keeps the tiles with the fewest contradictions out of `attempts` attempts.
+    fn solve(
+        &mut self,
+        restrictions: &[(u32, u32, FixedBitSet)],
+        rng: &mut ChunkRng,
+        attempts: u32,
+    ) -> Vec<usize> {
+        let mut best: Option<(usize, Vec<usize>)> = None;
+        for _ in 0..attempts.max(1) {
+            self.reset();
+            for (x, y, tiles) in restrictions {
+                self.restrict((y * self.width + x) as usize, tiles);
+            }
+            self.propagate();
+            let tiles = self.collapse(rng);
+            if best.as_ref().map_or(true, |(contradictions, _)| {
+                self.contradictions < *contradictions
+            }) {
+                best = Some((self.contradictions, tiles));
+            }
+            if self.contradictions == 0 {
+                break;
+            }
+        }
+        let (contradictions, tiles) = best.unwrap();
+        self.contradictions = contradictions;
+        tiles
+    }
+
+    fn reset(&mut self) {
+        let len = (self.width * self.height) as usize;
+        self.cells = vec![self.rules.all(); len];
+        self.broken.clear();
+        self.changed.clear();
+        self.contradictions = 0;
+    }
+
+    /// Removes the tiles that aren't in `tiles` from a cell
+    fn restrict(&mut self, cell: usize, tiles: &FixedBitSet) {
+        if self.broken.contains(cell) {
+            return;
+        }
+        let before = self.cells[cell].count_ones(..);
+        self.cells[cell].intersect_with(tiles);
+        let after = self.cells[cell].count_ones(..);
+        if after == 0 {
+            self.contradictions += 1;
+            self.broken.insert(cell);
+            self.cells[cell] = self.rules.all();
+        } else if after < before {
+            self.changed.push(cell);
+        }
+    }
+
+    /// Removes the tiles from the neighbors of changed cells that aren't allowed next to the
+    /// tiles the cells have left, until no cell changes
+    fn propagate(&mut self) {
+        while let Some(cell) = self.changed.pop() {
+            if self.broken.contains(cell) {
+                continue;
+            }
+            let x = (cell as u32 % self.width) as i32;
+            let y = (cell as u32 / self.width) as i32;
+            for (side, &(dx, dy)) in SIDES.iter().enumerate() {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || ny < 0 || nx >= self.width as i32 || ny >= self.height as i32 {
+                    continue;
+                }
+                let mut allowed = FixedBitSet::with_capacity(self.rules.len());
+                for tile in self.cells[cell].ones() {
+                    allowed.union_with(&self.rules.neighbors[tile][side]);
+                }
+                self.restrict((ny as u32 * self.width + nx as u32) as usize, &allowed);
+            }
+        }
+    }
+
+    /// Picks a tile for the cell with the fewest tiles left until every cell has one tile left
+    fn collapse(&mut self, rng: &mut ChunkRng) -> Vec<usize> {
+        loop {
+            let mut lowest = None;
+            let mut fewest = usize::MAX;
+            let mut ties = 0;
+            for (cell, tiles) in self.cells.iter().enumerate() {
+                if self.broken.contains(cell) {
+                    continue;
+                }
+                let count = tiles.count_ones(..);
+                if count <= 1 || count > fewest {
+                    continue;
+                }
+                if count < fewest {
+                    fewest = count;
+                    ties = 0;
+                }
+                // every cell with the fewest tiles is as likely to be picked
+                ties += 1;
+                if rng.below(ties) == 0 {
+                    lowest = Some(cell);
+                }
+            }
+            let cell = match lowest {
+                Some(cell) => cell,
+                None => break,
+            };
+            let tile = self.rules.pick(&self.cells[cell], rng);
+            self.cells[cell] = self.rules.only(tile);
+            self.changed.push(cell);
+            self.propagate();
+        }
+
+        let rules = self.rules;
+        let broken = &self.broken;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(cell, tiles)| {
+                if broken.contains(cell) {
+                    rules.pick(tiles, rng)
+                } else {
+                    tiles.ones().next().unwrap()
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WATER: TileId = TileId(1);
+    const SAND: TileId = TileId(2);
+    const GRASS: TileId = TileId(3);
+
+    /// Water and grass with sand between them
+    fn coast() -> WfcRules {
+        let pairs = [
+            (WATER, WATER),
+            (WATER, SAND),
+            (SAND, SAND),
+            (SAND, GRASS),
+            (GRASS, GRASS),
+        ];
+        let mut rules = WfcRules::new();
+        for &(a, b) in pairs.iter() {
+            rules = rules
+                .with_horizontal(a, b)
+                .with_horizontal(b, a)
+                .with_vertical(a, b)
+                .with_vertical(b, a);
+        }
+        rules
+    }
+
+    #[test]
+    fn learn_rules_from_example() {
+        #[rustfmt::skip]
+        let example = [
+            WATER, WATER, SAND, GRASS, GRASS,
+            WATER, SAND, SAND, SAND, GRASS,
+            SAND, SAND, GRASS, GRASS, GRASS,
+        ];
+        let rules = WfcRules::from_example(&example, 5);
+        assert_eq!(rules.tiles(), &[WATER, SAND, GRASS]);
+        assert!(rules.allows_horizontal(WATER, SAND));
+        assert!(rules.allows_horizontal(WATER, WATER));
+        assert!(rules.allows_vertical(WATER, SAND));
+        assert!(rules.allows_vertical(GRASS, GRASS));
+        assert!(!rules.allows_horizontal(WATER, GRASS));
+        assert!(!rules.allows_vertical(GRASS, WATER));
+        // sand is never below water in the example
+        assert!(!rules.allows_vertical(SAND, WATER));
+        // the end of a row isn't next to the start of the next one
+        assert!(!rules.allows_horizontal(GRASS, WATER));
+        assert_eq!(rules.weights, vec![3.0, 6.0, 6.0]);
+    }
+
+    #[test]
+    fn generate_chunks_that_follow_the_rules() {
+        let generator = WfcGenerator::new(coast());
+        let size = 8;
+        let generate = |x: i32, y: i32| {
+            let mut chunk = GeneratedChunk::new(ChunkIndex(IVec2::new(x, y)), size, 7);
+            generator.generate(&mut chunk);
+            chunk
+        };
+        let world = [
+            generate(0, 0),
+            generate(1, 0),
+            generate(0, 1),
+            generate(1, 1),
+        ];
+        let tile = |x: u32, y: u32| {
+            world[((y / size) * 2 + x / size) as usize]
+                .get(x % size, y % size)
+                .unwrap()
+        };
+
+        // the rules hold within chunks and across their edges
+        let rules = generator.rules();
+        for y in 0..size * 2 {
+            for x in 0..size * 2 {
+                assert!(rules.tiles().contains(&tile(x, y)));
+                if x + 1 < size * 2 {
+                    assert!(rules.allows_horizontal(tile(x, y), tile(x + 1, y)));
+                }
+                if y + 1 < size * 2 {
+                    assert!(rules.allows_vertical(tile(x, y), tile(x, y + 1)));
+                }
+            }
+        }
+
+        // chunks come out the same every time
+        let again = generate(1, 1);
+        for y in 0..size {
+            for x in 0..size {
+                assert_eq!(again.get(x, y), world[3].get(x, y));
+            }
+        }
+    }
+}
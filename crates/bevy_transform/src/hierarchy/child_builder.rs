@@ -70,10 +70,8 @@ impl Command for PushChildren {
 
 impl<'a> ChildBuilder<'a> {
     pub fn spawn(&mut self, components: impl DynamicBundle + Send + Sync + 'static) -> &mut Self {
-        self.commands.spawn(components);
-        self.push_children
-            .children
-            .push(self.commands.current_entity().unwrap());
+        let entity = self.commands.spawn_entity(components).id();
+        self.push_children.children.push(entity);
         self
     }
 
@@ -1,6 +1,7 @@
 use crate::components::{Children, Parent};
-use bevy_ecs::{Command, Commands, Entity, Resources, World};
+use bevy_ecs::{Command, Commands, Component, Entity, Resources, With, World};
 use bevy_utils::tracing::debug;
+use std::marker::PhantomData;
 
 #[derive(Debug)]
 pub struct DespawnRecursive {
@@ -38,9 +39,31 @@ impl Command for DespawnRecursive {
     }
 }
 
+#[derive(Debug)]
+pub struct DespawnRecursiveAllWith<T: Component> {
+    phantom: PhantomData<T>,
+}
+
+impl<T: Component> Command for DespawnRecursiveAllWith<T> {
+    fn write(self: Box<Self>, world: &mut World, _resources: &mut Resources) {
+        let entities = world
+            .query_filtered::<Entity, With<T>>()
+            .collect::<Vec<_>>();
+        for entity in entities {
+            // the entity may already be gone if it was a child of a previous entity
+            if world.contains(entity) {
+                despawn_with_children_recursive(world, entity);
+            }
+        }
+    }
+}
+
 pub trait DespawnRecursiveExt {
     /// Despawns the provided entity and its children.
     fn despawn_recursive(&mut self, entity: Entity) -> &mut Self;
+
+    /// Despawns every entity with a `T` component, and their children.
+    fn despawn_recursive_all_with<T: Component>(&mut self) -> &mut Self;
 }
 
 impl DespawnRecursiveExt for Commands {
@@ -48,6 +71,20 @@ impl DespawnRecursiveExt for Commands {
     fn despawn_recursive(&mut self, entity: Entity) -> &mut Self {
         self.add_command(DespawnRecursive { entity })
     }
+
+    /// Despawns every entity with a `T` component, and their children.
+    fn despawn_recursive_all_with<T: Component>(&mut self) -> &mut Self {
+        self.add_command(DespawnRecursiveAllWith::<T> {
+            phantom: PhantomData,
+        })
+    }
+}
+
+/// A system that despawns every entity with a `T` component along with their children. This is
+/// the hierarchy-aware version of [despawn_all_with](bevy_ecs::despawn_all_with), for cleaning
+/// up UI trees and other parented entities when leaving a state.
+pub fn despawn_recursive_all_with<T: Component>(commands: &mut Commands) {
+    commands.despawn_recursive_all_with::<T>();
 }
 
 #[cfg(test)]
@@ -122,4 +159,37 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn despawn_recursive_all_with() {
+        struct Marker;
+
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        let mut command_buffer = Commands::default();
+        command_buffer.set_entity_reserver(world.get_entity_reserver());
+
+        command_buffer
+            .spawn(("Marked parent".to_owned(), 0u32, Marker))
+            .with_children(|parent| {
+                // marked children are despawned along with their parent
+                parent.spawn(("Marked child".to_owned(), 1u32, Marker));
+                parent.spawn(("Child".to_owned(), 2u32));
+            })
+            .spawn(("Parent".to_owned(), 3u32))
+            .with_children(|parent| {
+                parent.spawn(("Marked child".to_owned(), 4u32, Marker));
+                parent.spawn(("Child".to_owned(), 5u32));
+            });
+        let parent_entity = command_buffer.current_entity().unwrap();
+        command_buffer.apply(&mut world, &mut resources);
+
+        command_buffer.despawn_recursive_all_with::<Marker>();
+        command_buffer.apply(&mut world, &mut resources);
+
+        let mut results = world.query::<&u32>().copied().collect::<Vec<_>>();
+        results.sort_unstable();
+        assert_eq!(results, vec![3u32, 5u32]);
+        assert_eq!(world.get::<Children>(parent_entity).unwrap().len(), 1);
+    }
 }
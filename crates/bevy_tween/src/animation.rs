@@ -0,0 +1,437 @@
+use crate::Ease;
+use bevy_asset::{Assets, Handle, HandleId};
+use bevy_core::{EntityLabels, Time};
+use bevy_ecs::{Entity, Resources, World};
+use bevy_math::{Quat, Vec2, Vec3, Vec4};
+use bevy_reflect::{
+    DynamicStruct, GetPath, Reflect, ReflectComponent, ReflectRef, TypeRegistryArc, TypeUuid,
+};
+use serde::{Deserialize, Serialize};
+use std::ops::{Add, Mul, Sub};
+
+/// How the value of a track changes between two keyframes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Interpolation {
+    /// Interpolates between the values of the keyframes, along the easing curve of the first
+    /// keyframe. Values that can't be interpolated step instead.
+    Linear,
+    /// Keeps the value of a keyframe until the next one, which is used for values like the index
+    /// of a sprite in a sprite sheet
+    Step,
+}
+
+impl Default for Interpolation {
+    fn default() -> Self {
+        Interpolation::Linear
+    }
+}
+
+#[derive(Debug)]
+pub struct Keyframe {
+    /// The time of the keyframe in seconds since the start of the clip
+    pub time: f32,
+    /// The easing curve used to interpolate from this keyframe to the next one
+    pub ease: Ease,
+    pub value: Box<dyn Reflect>,
+}
+
+/// Animates one field of a component
+#[derive(Debug)]
+pub struct AnimationTrack {
+    /// The label of the entities to animate, from [EntityLabels]. When `None`, the entity of the
+    /// [AnimationPlayer] is animated.
+    pub target: Option<String>,
+    /// The full type name of the animated component, which must be registered with
+    /// [ReflectComponent] type data
+    pub component: String,
+    /// The path of the animated field in the component, such as `translation.x`. When empty, the
+    /// whole component is animated.
+    pub path: String,
+    pub interpolation: Interpolation,
+    /// The keyframes of the track, sorted by time
+    pub keyframes: Vec<Keyframe>,
+}
+
+impl AnimationTrack {
+    /// The value of the track at `time`. Before the first keyframe and after the last one, the
+    /// value of that keyframe is used.
+    pub fn sample(&self, time: f32) -> Option<Box<dyn Reflect>> {
+        let next = self
+            .keyframes
+            .iter()
+            .position(|keyframe| keyframe.time > time);
+        let (from, to) = match next {
+            Some(0) => return Some(self.keyframes[0].value.clone_value()),
+            Some(next) => (&self.keyframes[next - 1], &self.keyframes[next]),
+            None => return self.keyframes.last().map(|last| last.value.clone_value()),
+        };
+        if self.interpolation == Interpolation::Step {
+            return Some(from.value.clone_value());
+        }
+        let t = from.ease.sample((time - from.time) / (to.time - from.time));
+        Some(lerp_reflect(&*from.value, &*to.value, t).unwrap_or_else(|| from.value.clone_value()))
+    }
+}
+
+/// A keyframe animation of the fields of reflected components, such as the translation of a
+/// [Transform](bevy_transform::components::Transform), the index of a sprite in a sprite sheet or
+/// a color. Clips are loaded from `.anim` files, which contain a list of [AnimationTrack]s:
+///
+/// ```ron
+/// [
+///     (
+///         component: "bevy_transform::components::transform::Transform",
+///         path: "translation",
+///         keyframes: [
+///             (time: 0.0, value: {"type": "glam::f32::vec3::Vec3", "value": (0.0, 0.0, 0.0)}),
+///             (time: 1.5, ease: QuadOut, value: {"type": "glam::f32::vec3::Vec3", "value": (200.0, 0.0, 0.0)}),
+///         ],
+///     ),
+///     (
+///         target: "door",
+///         component: "bevy_sprite::texture_atlas::TextureAtlasSprite",
+///         path: "index",
+///         interpolation: Step,
+///         keyframes: [
+///             (time: 0.0, value: {"type": "u32", "value": 0}),
+///             (time: 0.5, value: {"type": "u32", "value": 1}),
+///         ],
+///     ),
+/// ]
+/// ```
+#[derive(Debug, Default, TypeUuid)]
+#[uuid = "2e9ab1e4-7c4d-4d43-9f0b-43a5f27c8e36"]
+pub struct AnimationClip {
+    tracks: Vec<AnimationTrack>,
+    duration: f32,
+}
+
+impl AnimationClip {
+    /// A clip of `tracks`, with their keyframes sorted by time. Keyframes at times that aren't
+    /// finite, like `NaN`, can't be ordered, so they are dropped.
+    pub fn new(mut tracks: Vec<AnimationTrack>) -> Self {
+        for track in tracks.iter_mut() {
+            track.keyframes.retain(|keyframe| keyframe.time.is_finite());
+            track.keyframes.sort_by(|a, b| {
+                a.time
+                    .partial_cmp(&b.time)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+        }
+        let duration = tracks
+            .iter()
+            .filter_map(|track| track.keyframes.last())
+            .map(|keyframe| keyframe.time)
+            .fold(0.0, f32::max);
+        AnimationClip { tracks, duration }
+    }
+
+    pub fn tracks(&self) -> &[AnimationTrack] {
+        &self.tracks
+    }
+
+    /// The time of the last keyframe of the clip, in seconds
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+}
+
+/// Interpolates between two reflected values. Floats, vectors and quaternions are interpolated,
+/// and so are structs, field by field, which covers types like colors. Returns `None` for other
+/// values.
+pub fn lerp_reflect(a: &dyn Reflect, b: &dyn Reflect, t: f32) -> Option<Box<dyn Reflect>> {
+    fn lerp<T>(a: &dyn Reflect, b: &dyn Reflect, t: f32) -> Option<Box<dyn Reflect>>
+    where
+        T: Reflect + Copy + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>,
+    {
+        let (a, b) = (*a.downcast_ref::<T>()?, *b.downcast_ref::<T>()?);
+        Some(Box::new(a + (b - a) * t))
+    }
+
+    if let (Some(a), Some(b)) = (a.downcast_ref::<Quat>(), b.downcast_ref::<Quat>()) {
+        return Some(Box::new(a.slerp(*b, t)));
+    }
+    if let Some(value) = lerp::<f32>(a, b, t)
+        .or_else(|| lerp::<Vec2>(a, b, t))
+        .or_else(|| lerp::<Vec3>(a, b, t))
+        .or_else(|| lerp::<Vec4>(a, b, t))
+    {
+        return Some(value);
+    }
+
+    match (a.reflect_ref(), b.reflect_ref()) {
+        (ReflectRef::Struct(a), ReflectRef::Struct(b)) => {
+            let mut value = DynamicStruct::default();
+            value.set_name(a.type_name().to_string());
+            for (index, a_field) in a.iter_fields().enumerate() {
+                let name = a.name_at(index)?;
+                let b_field = b.field(name)?;
+                let field = lerp_reflect(a_field, b_field, t).unwrap_or_else(|| {
+                    if t < 1.0 {
+                        a_field.clone_value()
+                    } else {
+                        b_field.clone_value()
+                    }
+                });
+                value.insert_boxed(name, field);
+            }
+            Some(Box::new(value))
+        }
+        _ => None,
+    }
+}
+
+/// Plays an [AnimationClip] on its entity, and on the entities labeled with the targets of the
+/// tracks of the clip
+#[derive(Debug)]
+pub struct AnimationPlayer {
+    pub clip: Handle<AnimationClip>,
+    /// How fast the clip is played, `1.0` being its normal speed
+    pub speed: f32,
+    /// Plays the clip from the start again when it ends, instead of stopping
+    pub looping: bool,
+    pub paused: bool,
+    elapsed: f32,
+    finished: bool,
+}
+
+impl AnimationPlayer {
+    pub fn new(clip: Handle<AnimationClip>) -> Self {
+        AnimationPlayer {
+            clip,
+            speed: 1.0,
+            looping: false,
+            paused: false,
+            elapsed: 0.0,
+            finished: false,
+        }
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    /// The time in the clip, in seconds
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed
+    }
+
+    /// Returns `true` once a clip that isn't looping has reached its end
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Jumps to `time` in the clip
+    pub fn seek(&mut self, time: f32) {
+        self.elapsed = time.max(0.0);
+        self.finished = false;
+    }
+
+    fn advance(&mut self, delta_seconds: f32, duration: f32) {
+        self.elapsed += delta_seconds * self.speed;
+        if self.elapsed >= duration {
+            if self.looping && duration > 0.0 {
+                self.elapsed %= duration;
+            } else {
+                self.elapsed = duration;
+                self.finished = true;
+            }
+        }
+    }
+}
+
+/// Advances the [AnimationPlayer]s and writes the animated values into the fields of their
+/// targets. Finished players stop writing, so the animated components stop being marked as
+/// mutated.
+pub fn animation_player_system(world: &mut World, resources: &mut Resources) {
+    let (time, clips, type_registry) = match (
+        resources.get::<Time>(),
+        resources.get::<Assets<AnimationClip>>(),
+        resources.get::<TypeRegistryArc>(),
+    ) {
+        (Some(time), Some(clips), Some(type_registry)) => (time, clips, type_registry),
+        _ => return,
+    };
+    let entity_labels = resources.get::<EntityLabels>();
+
+    let mut playing: Vec<(Entity, HandleId, f32)> = Vec::new();
+    for (entity, mut player) in world.query_mut::<(Entity, &mut AnimationPlayer)>() {
+        if player.paused || player.finished {
+            continue;
+        }
+        if let Some(clip) = clips.get(&player.clip) {
+            player.advance(time.delta_seconds(), clip.duration());
+            playing.push((entity, player.clip.id, player.elapsed));
+        }
+    }
+
+    let type_registry = type_registry.read();
+    for (entity, clip, elapsed) in playing {
+        let clip = clips.get(clip).unwrap();
+        for track in clip.tracks.iter() {
+            let reflect_component = match type_registry
+                .get_with_name(&track.component)
+                .and_then(|registration| registration.data::<ReflectComponent>())
+            {
+                Some(reflect_component) => reflect_component,
+                None => continue,
+            };
+            let value = match track.sample(elapsed) {
+                Some(value) => value,
+                None => continue,
+            };
+            let targets = match (&track.target, &entity_labels) {
+                (Some(label), Some(entity_labels)) => entity_labels.get(label).unwrap_or(&[]),
+                (Some(_), None) => &[],
+                (None, _) => std::slice::from_ref(&entity),
+            };
+            for target in targets.iter() {
+                reflect_component.modify_component(world, *target, &mut |component| {
+                    if track.path.is_empty() {
+                        component.apply(&*value);
+                    } else if let Ok(field) = component.path_mut(&track.path) {
+                        field.apply(&*value);
+                    }
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_app::App;
+    use bevy_asset::{AddAsset, AssetPlugin, AssetServer, FileAssetIo};
+    use bevy_reflect::{GetField, ReflectPlugin, RegisterTypeBuilder};
+    use bevy_tasks::TaskPool;
+    use bevy_transform::components::Transform;
+
+    fn keyframe(time: f32, value: impl Reflect) -> Keyframe {
+        Keyframe {
+            time,
+            ease: Ease::Linear,
+            value: Box::new(value),
+        }
+    }
+
+    #[test]
+    fn sample_keyframes() {
+        let mut track = AnimationTrack {
+            target: None,
+            component: String::new(),
+            path: String::new(),
+            interpolation: Interpolation::Linear,
+            keyframes: vec![
+                keyframe(1.0, Vec2::new(0.0, 0.0)),
+                keyframe(3.0, Vec2::new(4.0, 2.0)),
+                keyframe(4.0, 7u32),
+            ],
+        };
+        let sample = |track: &AnimationTrack, time: f32| {
+            track.sample(time).map(|value| format!("{:?}", value))
+        };
+        assert_eq!(sample(&track, 0.0), sample(&track, 1.0));
+        assert_eq!(
+            track.sample(2.0).unwrap().downcast_ref::<Vec2>(),
+            Some(&Vec2::new(2.0, 1.0))
+        );
+        assert_eq!(track.sample(5.0).unwrap().downcast_ref::<u32>(), Some(&7));
+
+        track.interpolation = Interpolation::Step;
+        assert_eq!(
+            track.sample(2.9).unwrap().downcast_ref::<Vec2>(),
+            Some(&Vec2::new(0.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn drop_keyframes_at_nan_times() {
+        let clip = AnimationClip::new(vec![AnimationTrack {
+            target: None,
+            component: String::new(),
+            path: String::new(),
+            interpolation: Interpolation::Linear,
+            keyframes: vec![
+                keyframe(2.0, 1.0f32),
+                keyframe(f32::NAN, 5.0f32),
+                keyframe(0.0, 0.0f32),
+                keyframe(f32::INFINITY, 9.0f32),
+            ],
+        }]);
+        let times = clip.tracks()[0]
+            .keyframes
+            .iter()
+            .map(|keyframe| keyframe.time)
+            .collect::<Vec<_>>();
+        assert_eq!(times, vec![0.0, 2.0]);
+        assert_eq!(clip.duration(), 2.0);
+    }
+
+    #[test]
+    fn interpolate_struct_fields() {
+        let mut a = DynamicStruct::default();
+        a.insert("alpha", 0.0f32);
+        a.insert("name", "a".to_string());
+        let mut b = DynamicStruct::default();
+        b.insert("alpha", 1.0f32);
+        b.insert("name", "b".to_string());
+
+        let value = lerp_reflect(&a, &b, 0.25).unwrap();
+        let value = match value.reflect_ref() {
+            ReflectRef::Struct(value) => value,
+            _ => panic!("expected a struct"),
+        };
+        assert_eq!(value.get_field::<f32>("alpha"), Some(&0.25));
+        // values that can't be interpolated step
+        assert_eq!(value.get_field::<String>("name"), Some(&"a".to_string()));
+        assert!(lerp_reflect(&1u32, &2u32, 0.5).is_none());
+    }
+
+    #[test]
+    fn animate_component_fields() {
+        let asset_server = AssetServer::new(FileAssetIo::new(""), TaskPool::new());
+        let mut app = App::build();
+        app.add_resource(asset_server)
+            .add_plugin(ReflectPlugin)
+            .add_plugin(AssetPlugin)
+            .add_asset::<AnimationClip>()
+            .init_resource::<Time>()
+            .register_type::<Transform>();
+        let App {
+            mut world,
+            mut resources,
+            ..
+        } = app.app;
+
+        let clip = resources
+            .get_mut::<Assets<AnimationClip>>()
+            .unwrap()
+            .add(AnimationClip::new(vec![AnimationTrack {
+                target: None,
+                component: std::any::type_name::<Transform>().to_string(),
+                path: "translation".to_string(),
+                interpolation: Interpolation::Linear,
+                keyframes: vec![
+                    keyframe(0.0, Vec3::zero()),
+                    keyframe(2.0, Vec3::new(4.0, 2.0, 0.0)),
+                ],
+            }]));
+        let mut player = AnimationPlayer::new(clip);
+        player.seek(1.0);
+        let entity = world.spawn((Transform::default(), player));
+
+        animation_player_system(&mut world, &mut resources);
+        assert_eq!(
+            world.get::<Transform>(entity).unwrap().translation,
+            Vec3::new(2.0, 1.0, 0.0)
+        );
+        assert!(!world.get::<AnimationPlayer>(entity).unwrap().is_finished());
+    }
+}
@@ -0,0 +1,394 @@
+use crate::{AnimationClip, AnimationTrack, Ease, Interpolation, Keyframe};
+use anyhow::Result;
+use bevy_asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy_ecs::{FromResources, Resources};
+use bevy_reflect::{serde::ReflectDeserializer, ReflectComponent, TypeRegistry, TypeRegistryArc};
+use bevy_utils::BoxedFuture;
+use serde::{
+    de::{DeserializeSeed, Error, MapAccess, SeqAccess, Visitor},
+    Deserialize,
+};
+
+/// Loads [AnimationClip]s from `.anim` files
+#[derive(Debug)]
+pub struct AnimationClipLoader {
+    type_registry: TypeRegistryArc,
+}
+
+impl FromResources for AnimationClipLoader {
+    fn from_resources(resources: &Resources) -> Self {
+        let type_registry = resources.get::<TypeRegistryArc>().unwrap();
+        AnimationClipLoader {
+            type_registry: (*type_registry).clone(),
+        }
+    }
+}
+
+impl AssetLoader for AnimationClipLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let mut deserializer = ron::de::Deserializer::from_bytes(bytes)?;
+            let clip_deserializer = AnimationClipDeserializer {
+                type_registry: &self.type_registry.read(),
+            };
+            let clip = clip_deserializer.deserialize(&mut deserializer)?;
+            load_context.set_default_asset(LoadedAsset::new(clip));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["anim"]
+    }
+}
+
+pub struct AnimationClipDeserializer<'a> {
+    pub type_registry: &'a TypeRegistry,
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for AnimationClipDeserializer<'a> {
+    type Value = AnimationClip;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Ok(AnimationClip::new(deserializer.deserialize_seq(
+            TrackSeqVisitor {
+                type_registry: self.type_registry,
+            },
+        )?))
+    }
+}
+
+struct TrackSeqVisitor<'a> {
+    type_registry: &'a TypeRegistry,
+}
+
+impl<'a, 'de> Visitor<'de> for TrackSeqVisitor<'a> {
+    type Value = Vec<AnimationTrack>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("list of animation tracks")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut tracks = Vec::new();
+        while let Some(track) = seq.next_element_seed(TrackDeserializer {
+            type_registry: self.type_registry,
+        })? {
+            tracks.push(track);
+        }
+        Ok(tracks)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum TrackField {
+    Target,
+    Component,
+    Path,
+    Interpolation,
+    Keyframes,
+}
+
+pub const TRACK_STRUCT: &str = "AnimationTrack";
+pub const TRACK_FIELD_TARGET: &str = "target";
+pub const TRACK_FIELD_COMPONENT: &str = "component";
+pub const TRACK_FIELD_PATH: &str = "path";
+pub const TRACK_FIELD_INTERPOLATION: &str = "interpolation";
+pub const TRACK_FIELD_KEYFRAMES: &str = "keyframes";
+
+struct TrackDeserializer<'a> {
+    type_registry: &'a TypeRegistry,
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for TrackDeserializer<'a> {
+    type Value = AnimationTrack;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_struct(
+            TRACK_STRUCT,
+            &[
+                TRACK_FIELD_TARGET,
+                TRACK_FIELD_COMPONENT,
+                TRACK_FIELD_PATH,
+                TRACK_FIELD_INTERPOLATION,
+                TRACK_FIELD_KEYFRAMES,
+            ],
+            TrackVisitor {
+                type_registry: self.type_registry,
+            },
+        )
+    }
+}
+
+struct TrackVisitor<'a> {
+    type_registry: &'a TypeRegistry,
+}
+
+impl<'a, 'de> Visitor<'de> for TrackVisitor<'a> {
+    type Value = AnimationTrack;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("animation track")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut target = None;
+        let mut component = None;
+        let mut path = None;
+        let mut interpolation = None;
+        let mut keyframes = None;
+        while let Some(key) = map.next_key()? {
+            match key {
+                TrackField::Target => target = Some(map.next_value::<String>()?),
+                TrackField::Component => component = Some(map.next_value::<String>()?),
+                TrackField::Path => path = Some(map.next_value::<String>()?),
+                TrackField::Interpolation => {
+                    interpolation = Some(map.next_value::<Interpolation>()?)
+                }
+                TrackField::Keyframes => {
+                    keyframes = Some(map.next_value_seed(KeyframeVecDeserializer {
+                        type_registry: self.type_registry,
+                    })?)
+                }
+            }
+        }
+
+        let component = component.ok_or_else(|| Error::missing_field(TRACK_FIELD_COMPONENT))?;
+        // catch typos in component names when the clip is loaded instead of when it is played
+        if self
+            .type_registry
+            .get_with_name(&component)
+            .and_then(|registration| registration.data::<ReflectComponent>())
+            .is_none()
+        {
+            return Err(Error::custom(format!(
+                "{} is not registered as a reflected component",
+                component
+            )));
+        }
+        Ok(AnimationTrack {
+            target,
+            component,
+            path: path.unwrap_or_default(),
+            interpolation: interpolation.unwrap_or_default(),
+            keyframes: keyframes.ok_or_else(|| Error::missing_field(TRACK_FIELD_KEYFRAMES))?,
+        })
+    }
+}
+
+struct KeyframeVecDeserializer<'a> {
+    type_registry: &'a TypeRegistry,
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for KeyframeVecDeserializer<'a> {
+    type Value = Vec<Keyframe>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(KeyframeSeqVisitor {
+            type_registry: self.type_registry,
+        })
+    }
+}
+
+struct KeyframeSeqVisitor<'a> {
+    type_registry: &'a TypeRegistry,
+}
+
+impl<'a, 'de> Visitor<'de> for KeyframeSeqVisitor<'a> {
+    type Value = Vec<Keyframe>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("list of keyframes")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut keyframes = Vec::new();
+        while let Some(keyframe) = seq.next_element_seed(KeyframeDeserializer {
+            type_registry: self.type_registry,
+        })? {
+            keyframes.push(keyframe);
+        }
+        Ok(keyframes)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum KeyframeField {
+    Time,
+    Ease,
+    Value,
+}
+
+pub const KEYFRAME_STRUCT: &str = "Keyframe";
+pub const KEYFRAME_FIELD_TIME: &str = "time";
+pub const KEYFRAME_FIELD_EASE: &str = "ease";
+pub const KEYFRAME_FIELD_VALUE: &str = "value";
+
+struct KeyframeDeserializer<'a> {
+    type_registry: &'a TypeRegistry,
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for KeyframeDeserializer<'a> {
+    type Value = Keyframe;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_struct(
+            KEYFRAME_STRUCT,
+            &[
+                KEYFRAME_FIELD_TIME,
+                KEYFRAME_FIELD_EASE,
+                KEYFRAME_FIELD_VALUE,
+            ],
+            KeyframeVisitor {
+                type_registry: self.type_registry,
+            },
+        )
+    }
+}
+
+struct KeyframeVisitor<'a> {
+    type_registry: &'a TypeRegistry,
+}
+
+impl<'a, 'de> Visitor<'de> for KeyframeVisitor<'a> {
+    type Value = Keyframe;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("keyframe")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut time = None;
+        let mut ease = None;
+        let mut value = None;
+        while let Some(key) = map.next_key()? {
+            match key {
+                KeyframeField::Time => {
+                    let seconds = map.next_value::<f32>()?;
+                    if !seconds.is_finite() {
+                        return Err(Error::custom(format!(
+                            "keyframe time {} is not a finite number of seconds",
+                            seconds
+                        )));
+                    }
+                    time = Some(seconds);
+                }
+                KeyframeField::Ease => ease = Some(map.next_value::<Ease>()?),
+                KeyframeField::Value => {
+                    value = Some(map.next_value_seed(ReflectDeserializer::new(self.type_registry))?)
+                }
+            }
+        }
+
+        Ok(Keyframe {
+            time: time.ok_or_else(|| Error::missing_field(KEYFRAME_FIELD_TIME))?,
+            ease: ease.unwrap_or_default(),
+            value: value.ok_or_else(|| Error::missing_field(KEYFRAME_FIELD_VALUE))?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_math::Vec3;
+    use bevy_transform::components::Transform;
+
+    fn deserialize(type_registry: &TypeRegistry, clip: &str) -> ron::Result<AnimationClip> {
+        let mut deserializer = ron::de::Deserializer::from_str(clip)?;
+        AnimationClipDeserializer { type_registry }.deserialize(&mut deserializer)
+    }
+
+    #[test]
+    fn deserialize_clip() {
+        let mut type_registry = TypeRegistry::default();
+        type_registry.register::<Transform>();
+        type_registry.register::<Vec3>();
+        type_registry.register::<f32>();
+
+        let clip = deserialize(
+            &type_registry,
+            r#"[
+                (
+                    component: "bevy_transform::components::transform::Transform",
+                    path: "translation",
+                    keyframes: [
+                        (time: 2.0, value: {"type": "glam::f32::vec3::Vec3", "value": (4.0, 2.0, 0.0)}),
+                        (time: 0.0, ease: QuadOut, value: {"type": "glam::f32::vec3::Vec3", "value": (0.0, 0.0, 0.0)}),
+                    ],
+                ),
+                (
+                    target: "door",
+                    component: "bevy_transform::components::transform::Transform",
+                    path: "scale.x",
+                    interpolation: Step,
+                    keyframes: [(time: 0.5, value: {"type": "f32", "value": 2.0})],
+                ),
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(clip.duration(), 2.0);
+        let tracks = clip.tracks();
+        // keyframes are sorted by time
+        assert_eq!(tracks[0].keyframes[0].ease, Ease::QuadOut);
+        assert_eq!(tracks[0].interpolation, Interpolation::Linear);
+        assert_eq!(
+            tracks[0].keyframes[1].value.downcast_ref::<Vec3>(),
+            Some(&Vec3::new(4.0, 2.0, 0.0))
+        );
+        assert_eq!(tracks[1].target.as_deref(), Some("door"));
+        assert_eq!(tracks[1].interpolation, Interpolation::Step);
+
+        let error = deserialize(
+            &type_registry,
+            r#"[(component: "Transfrom", keyframes: [])]"#,
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Transfrom is not registered as a reflected component"));
+
+        let error = deserialize(
+            &type_registry,
+            r#"[(
+                component: "bevy_transform::components::transform::Transform",
+                keyframes: [(time: NaN, value: {"type": "f32", "value": 2.0})],
+            )]"#,
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("keyframe time NaN is not a finite number"));
+    }
+}
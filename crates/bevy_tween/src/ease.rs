@@ -0,0 +1,135 @@
+use bevy_math::clamp;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+/// An easing curve, which maps the linear progress of a tween to the ratio its lens interpolates
+/// with. Both are `0.0` at the start of the tween and `1.0` at its end, but some curves, like
+/// [Ease::BackOut] and [Ease::ElasticOut], overshoot in between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Ease {
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    BackIn,
+    BackOut,
+    ElasticOut,
+    BounceOut,
+}
+
+impl Default for Ease {
+    fn default() -> Self {
+        Ease::Linear
+    }
+}
+
+impl Ease {
+    /// Samples the curve at `t`, which is clamped to `[0.0, 1.0]`
+    pub fn sample(self, t: f32) -> f32 {
+        let t = clamp(t, 0.0, 1.0);
+        match self {
+            Ease::Linear => t,
+            Ease::QuadIn => t * t,
+            Ease::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Ease::QuadInOut => in_out(t, |t| t * t),
+            Ease::CubicIn => t * t * t,
+            Ease::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Ease::CubicInOut => in_out(t, |t| t * t * t),
+            Ease::SineIn => 1.0 - (t * PI / 2.0).cos(),
+            Ease::SineOut => (t * PI / 2.0).sin(),
+            Ease::SineInOut => (1.0 - (t * PI).cos()) / 2.0,
+            Ease::BackIn => back_in(t),
+            Ease::BackOut => 1.0 - back_in(1.0 - t),
+            Ease::ElasticOut => {
+                if t == 0.0 || t == 1.0 {
+                    t
+                } else {
+                    2.0f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0
+                }
+            }
+            Ease::BounceOut => bounce_out(t),
+        }
+    }
+}
+
+/// Uses the first half of the curve for the first half of the tween, and the same curve mirrored
+/// for the second half
+fn in_out(t: f32, ease_in: impl Fn(f32) -> f32) -> f32 {
+    if t < 0.5 {
+        ease_in(t * 2.0) / 2.0
+    } else {
+        1.0 - ease_in((1.0 - t) * 2.0) / 2.0
+    }
+}
+
+fn back_in(t: f32) -> f32 {
+    const OVERSHOOT: f32 = 1.70158;
+    t * t * ((OVERSHOOT + 1.0) * t - OVERSHOOT)
+}
+
+fn bounce_out(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURVES: [Ease; 14] = [
+        Ease::Linear,
+        Ease::QuadIn,
+        Ease::QuadOut,
+        Ease::QuadInOut,
+        Ease::CubicIn,
+        Ease::CubicOut,
+        Ease::CubicInOut,
+        Ease::SineIn,
+        Ease::SineOut,
+        Ease::SineInOut,
+        Ease::BackIn,
+        Ease::BackOut,
+        Ease::ElasticOut,
+        Ease::BounceOut,
+    ];
+
+    #[test]
+    fn curves_start_at_zero_and_end_at_one() {
+        for ease in CURVES.iter() {
+            assert!(ease.sample(0.0).abs() < 1e-5, "{:?}", ease);
+            assert!((ease.sample(1.0) - 1.0).abs() < 1e-5, "{:?}", ease);
+            // the input is clamped
+            assert_eq!(ease.sample(-1.0), ease.sample(0.0), "{:?}", ease);
+            assert_eq!(ease.sample(2.0), ease.sample(1.0), "{:?}", ease);
+        }
+    }
+
+    #[test]
+    fn in_out_curves_are_symmetric() {
+        for ease in [Ease::QuadInOut, Ease::CubicInOut, Ease::SineInOut].iter() {
+            assert!((ease.sample(0.5) - 0.5).abs() < 1e-5, "{:?}", ease);
+            assert!((ease.sample(0.2) + ease.sample(0.8) - 1.0).abs() < 1e-5);
+        }
+        assert!(Ease::QuadIn.sample(0.5) < 0.5);
+        assert!(Ease::QuadOut.sample(0.5) > 0.5);
+        assert!(Ease::BackOut.sample(0.7) > 1.0);
+    }
+}
@@ -0,0 +1,69 @@
+use bevy_math::{Quat, Vec3};
+use bevy_transform::components::Transform;
+
+/// Writes the interpolated value of a tween into the fields of a component.
+///
+/// Closures taking the component and the ratio are lenses, so any field can be tweened without a
+/// dedicated type:
+/// ```
+/// use bevy_tween::{Ease, Tween};
+///
+/// struct Opacity(f32);
+///
+/// let fade_out = Tween::new(0.5, Ease::QuadOut, |opacity: &mut Opacity, ratio: f32| {
+///     opacity.0 = 1.0 - ratio;
+/// });
+/// ```
+pub trait Lens<T>: Send + Sync + 'static {
+    /// Sets the fields of `target` to their value at `ratio`, which is `0.0` at the start of the
+    /// tween and `1.0` at its end
+    fn lerp(&mut self, target: &mut T, ratio: f32);
+}
+
+impl<T, F> Lens<T> for F
+where
+    F: FnMut(&mut T, f32) + Send + Sync + 'static,
+{
+    fn lerp(&mut self, target: &mut T, ratio: f32) {
+        self(target, ratio)
+    }
+}
+
+/// Moves a [Transform] from `start` to `end`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransformTranslationLens {
+    pub start: Vec3,
+    pub end: Vec3,
+}
+
+impl Lens<Transform> for TransformTranslationLens {
+    fn lerp(&mut self, target: &mut Transform, ratio: f32) {
+        target.translation = self.start + (self.end - self.start) * ratio;
+    }
+}
+
+/// Rotates a [Transform] from `start` to `end` along the shortest path
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransformRotationLens {
+    pub start: Quat,
+    pub end: Quat,
+}
+
+impl Lens<Transform> for TransformRotationLens {
+    fn lerp(&mut self, target: &mut Transform, ratio: f32) {
+        target.rotation = self.start.slerp(self.end, ratio);
+    }
+}
+
+/// Scales a [Transform] from `start` to `end`. Scaling a 2d camera zooms it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransformScaleLens {
+    pub start: Vec3,
+    pub end: Vec3,
+}
+
+impl Lens<Transform> for TransformScaleLens {
+    fn lerp(&mut self, target: &mut Transform, ratio: f32) {
+        target.scale = self.start + (self.end - self.start) * ratio;
+    }
+}
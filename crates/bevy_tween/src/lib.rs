@@ -0,0 +1,104 @@
+mod animation;
+mod animation_loader;
+mod ease;
+mod lens;
+mod tween;
+
+pub use animation::*;
+pub use animation_loader::*;
+pub use ease::*;
+pub use lens::*;
+pub use tween::*;
+
+pub mod prelude {
+    pub use crate::{
+        AddTween, AnimationClip, AnimationPlayer, Ease, Lens, TransformRotationLens,
+        TransformScaleLens, TransformTranslationLens, Tween, TweenCompleted,
+    };
+}
+
+use bevy_app::prelude::*;
+use bevy_asset::AddAsset;
+use bevy_ecs::{Component, IntoSystem};
+use bevy_transform::components::Transform;
+
+/// Plays [Tween]s of [Transform] components and sends [TweenCompleted] events. Tweens of other
+/// component types are registered with [AddTween::add_tween]. Also loads [AnimationClip]s and
+/// plays them with [AnimationPlayer]s.
+#[derive(Default)]
+pub struct TweenPlugin;
+
+impl Plugin for TweenPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_tween::<Transform>()
+            .add_asset::<AnimationClip>()
+            .init_asset_loader::<AnimationClipLoader>()
+            .add_system(animation_player_system.system());
+    }
+}
+
+pub trait AddTween {
+    /// Plays the [Tween]s of `T` components during the UPDATE stage, before transforms are
+    /// propagated
+    fn add_tween<T>(&mut self) -> &mut Self
+    where
+        T: Component;
+}
+
+impl AddTween for AppBuilder {
+    fn add_tween<T>(&mut self) -> &mut Self
+    where
+        T: Component,
+    {
+        if self.resources().get::<Events<TweenCompleted>>().is_none() {
+            self.add_event::<TweenCompleted>();
+        }
+        self.add_system(tween_system::<T>.system())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_core::Time;
+    use bevy_ecs::{Resources, Stage, SystemStage, World};
+    use bevy_math::Vec3;
+
+    #[test]
+    fn send_completion_events() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(Time::default());
+        resources.insert(Events::<TweenCompleted>::default());
+        // with no time passing, only tweens without a duration complete
+        let lens = TransformScaleLens {
+            start: Vec3::one(),
+            end: Vec3::splat(2.0),
+        };
+        let zoomed = world.spawn((
+            Transform::default(),
+            Tween::new(0.0, Ease::Linear, lens).with_completion_id(3),
+        ));
+        let zooming = world.spawn((Transform::default(), Tween::new(1.0, Ease::Linear, lens)));
+
+        let mut stage = SystemStage::serial();
+        stage.add_system(tween_system::<Transform>.system());
+        stage.initialize(&mut world, &mut resources);
+        stage.run(&mut world, &mut resources);
+
+        assert_eq!(
+            world.get::<Transform>(zoomed).unwrap().scale,
+            Vec3::splat(2.0)
+        );
+        assert_eq!(world.get::<Transform>(zooming).unwrap().scale, Vec3::one());
+        let events = resources.get::<Events<TweenCompleted>>().unwrap();
+        let mut reader = events.get_reader();
+        assert_eq!(
+            reader.iter(&events).collect::<Vec<_>>(),
+            vec![&TweenCompleted {
+                entity: zoomed,
+                completion_id: 3,
+            }]
+        );
+    }
+}
@@ -0,0 +1,288 @@
+use crate::{Ease, Lens};
+use bevy_app::Events;
+use bevy_core::Time;
+use bevy_ecs::{Component, Entity, Query, Res, ResMut};
+
+/// Animates the fields of the `T` component of its entity, with a sequence of steps that each
+/// interpolate from a start value to an end value along an easing curve.
+///
+/// Tweens of a component type are only played once the type is registered with
+/// [AddTween::add_tween](crate::AddTween::add_tween), which the [TweenPlugin](crate::TweenPlugin)
+/// does for [Transform](bevy_transform::components::Transform). When a tween completes, a
+/// [TweenCompleted] event is sent. The tween component is kept, so it can be inspected or
+/// replaced.
+///
+/// ```
+/// use bevy_math::Vec3;
+/// use bevy_tween::{Ease, TransformScaleLens, TransformTranslationLens, Tween};
+/// use bevy_transform::components::Transform;
+///
+/// // a tile falls into place after a short delay, then bounces once
+/// let fall_in = Tween::new(
+///     0.4,
+///     Ease::QuadIn,
+///     TransformTranslationLens {
+///         start: Vec3::new(0.0, 200.0, 0.0),
+///         end: Vec3::zero(),
+///     },
+/// )
+/// .with_delay(0.1)
+/// .then(
+///     0.2,
+///     Ease::BounceOut,
+///     TransformScaleLens {
+///         start: Vec3::new(1.2, 0.8, 1.0),
+///         end: Vec3::one(),
+///     },
+/// )
+/// .with_completion_id(7);
+/// # let _: Tween<Transform> = fall_in;
+/// ```
+pub struct Tween<T> {
+    steps: Vec<TweenStep<T>>,
+    index: usize,
+    elapsed: f32,
+    looping: bool,
+    paused: bool,
+    finished: bool,
+    completion_id: u64,
+}
+
+struct TweenStep<T> {
+    delay: f32,
+    duration: f32,
+    ease: Ease,
+    lens: Box<dyn Lens<T>>,
+}
+
+impl<T> TweenStep<T> {
+    fn end(&self) -> f32 {
+        self.delay + self.duration
+    }
+}
+
+impl<T> std::fmt::Debug for Tween<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tween")
+            .field("steps", &self.steps.len())
+            .field("index", &self.index)
+            .field("elapsed", &self.elapsed)
+            .field("looping", &self.looping)
+            .field("paused", &self.paused)
+            .field("finished", &self.finished)
+            .field("completion_id", &self.completion_id)
+            .finish()
+    }
+}
+
+impl<T: 'static> Tween<T> {
+    /// A tween with a single step, which interpolates with `lens` along `ease` for `duration`
+    /// seconds
+    pub fn new(duration: f32, ease: Ease, lens: impl Lens<T>) -> Self {
+        Tween {
+            steps: Vec::new(),
+            index: 0,
+            elapsed: 0.0,
+            looping: false,
+            paused: false,
+            finished: false,
+            completion_id: 0,
+        }
+        .then(duration, ease, lens)
+    }
+
+    /// Adds a step that starts when the previous one ends
+    pub fn then(mut self, duration: f32, ease: Ease, lens: impl Lens<T>) -> Self {
+        self.steps.push(TweenStep {
+            delay: 0.0,
+            duration: duration.max(0.0),
+            ease,
+            lens: Box::new(lens),
+        });
+        self
+    }
+
+    /// Waits `delay` seconds before the last added step starts. The target isn't written during
+    /// the delay.
+    pub fn with_delay(mut self, delay: f32) -> Self {
+        if let Some(step) = self.steps.last_mut() {
+            step.delay = delay.max(0.0);
+        }
+        self
+    }
+
+    /// Restarts the tween from its first step when it completes, instead of stopping. A
+    /// [TweenCompleted] event is sent every time it loops.
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    /// Sets the id of the [TweenCompleted] events sent by this tween, so they can be told apart
+    /// from the events of other tweens of the same entity
+    pub fn with_completion_id(mut self, completion_id: u64) -> Self {
+        self.completion_id = completion_id;
+        self
+    }
+
+    pub fn completion_id(&self) -> u64 {
+        self.completion_id
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn unpause(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Returns `true` once the last step has completed, unless the tween is looping
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// The total length of the tween in seconds, including delays
+    pub fn duration(&self) -> f32 {
+        self.steps.iter().map(TweenStep::end).sum()
+    }
+
+    /// Plays the tween from the start again
+    pub fn rewind(&mut self) {
+        self.index = 0;
+        self.elapsed = 0.0;
+        self.finished = false;
+    }
+
+    /// Advances the tween by `delta_seconds` and writes the interpolated value into `target`.
+    /// Returns the number of times the tween completed during this tick, which is more than one
+    /// only when a looping tween is shorter than the tick.
+    pub fn tick(&mut self, delta_seconds: f32, target: &mut T) -> u32 {
+        if self.paused || self.finished {
+            return 0;
+        }
+        self.elapsed += delta_seconds;
+        let looping = self.looping && self.duration() > 0.0;
+        let mut completions = 0;
+        loop {
+            let step = &mut self.steps[self.index];
+            if self.elapsed < step.end() {
+                if self.elapsed >= step.delay {
+                    let t = (self.elapsed - step.delay) / step.duration;
+                    step.lens.lerp(target, step.ease.sample(t));
+                }
+                return completions;
+            }
+
+            // always end the step on its exact end value, even when the tick skips past it
+            step.lens.lerp(target, step.ease.sample(1.0));
+            self.elapsed -= step.end();
+            self.index += 1;
+            if self.index == self.steps.len() {
+                completions += 1;
+                if looping {
+                    self.index = 0;
+                } else {
+                    self.finished = true;
+                    self.elapsed = 0.0;
+                    return completions;
+                }
+            }
+        }
+    }
+}
+
+/// Sent when a [Tween] completes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TweenCompleted {
+    /// The entity of the tween
+    pub entity: Entity,
+    /// The id set with [Tween::with_completion_id]
+    pub completion_id: u64,
+}
+
+/// Plays the [Tween]s of `T` components
+pub fn tween_system<T: Component>(
+    time: Res<Time>,
+    mut completed: ResMut<Events<TweenCompleted>>,
+    mut query: Query<(Entity, &mut Tween<T>, &mut T)>,
+) {
+    for (entity, mut tween, mut target) in query.iter_mut() {
+        // don't mark the target as changed once the tween stops writing it
+        if tween.is_paused() || tween.is_finished() {
+            continue;
+        }
+        for _ in 0..tween.tick(time.delta_seconds(), &mut target) {
+            completed.send(TweenCompleted {
+                entity,
+                completion_id: tween.completion_id(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Opacity(f32);
+
+    fn fade(start: f32, end: f32) -> impl Lens<Opacity> {
+        move |opacity: &mut Opacity, ratio: f32| opacity.0 = start + (end - start) * ratio
+    }
+
+    #[test]
+    fn delayed_sequence() {
+        let mut tween = Tween::new(1.0, Ease::Linear, fade(0.0, 1.0))
+            .with_delay(0.5)
+            .then(2.0, Ease::Linear, fade(1.0, 0.0));
+        let mut opacity = Opacity(-1.0);
+        assert_eq!(tween.duration(), 3.5);
+
+        // nothing is written during the delay
+        assert_eq!(tween.tick(0.25, &mut opacity), 0);
+        assert_eq!(opacity, Opacity(-1.0));
+        tween.tick(0.5, &mut opacity);
+        assert_eq!(opacity, Opacity(0.25));
+        // the second step starts with the time left over from the first one
+        tween.tick(1.25, &mut opacity);
+        assert_eq!(opacity, Opacity(0.75));
+        assert!(!tween.is_finished());
+
+        assert_eq!(tween.tick(10.0, &mut opacity), 1);
+        assert_eq!(opacity, Opacity(0.0));
+        assert!(tween.is_finished());
+        assert_eq!(tween.tick(1.0, &mut opacity), 0);
+
+        tween.rewind();
+        tween.tick(1.0, &mut opacity);
+        assert_eq!(opacity, Opacity(0.5));
+    }
+
+    #[test]
+    fn loop_and_pause() {
+        let mut tween = Tween::new(1.0, Ease::Linear, fade(0.0, 1.0)).looping(true);
+        let mut opacity = Opacity(0.0);
+        assert_eq!(tween.tick(2.5, &mut opacity), 2);
+        assert_eq!(opacity, Opacity(0.5));
+        assert!(!tween.is_finished());
+
+        tween.pause();
+        tween.tick(0.25, &mut opacity);
+        assert_eq!(opacity, Opacity(0.5));
+        tween.unpause();
+        tween.tick(0.25, &mut opacity);
+        assert_eq!(opacity, Opacity(0.75));
+
+        // a looping tween without a duration completes once instead of looping forever
+        let mut instant = Tween::new(0.0, Ease::Linear, fade(0.0, 1.0)).looping(true);
+        assert_eq!(instant.tick(0.1, &mut opacity), 1);
+        assert!(instant.is_finished());
+        assert_eq!(opacity, Opacity(1.0));
+    }
+}
@@ -0,0 +1,122 @@
+use crate::{entity::ImageBundle, PositionType, Style, Val};
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::{Commands, Entity, Local, Query, Res, ResMut, With};
+use bevy_math::Vec2;
+use bevy_render::{draw::Visible, texture::Texture};
+use bevy_sprite::ColorMaterial;
+use bevy_transform::components::Transform;
+use bevy_window::Windows;
+
+/// The z of the [CursorImage] node, in front of all other UI nodes
+const CURSOR_IMAGE_Z: f32 = 900.0;
+
+/// A texture drawn in place of the system cursor of the primary window, like the crosshair of a
+/// tile editor. The system cursor is hidden while a texture is set. Use
+/// [Window::set_cursor_icon](bevy_window::Window::set_cursor_icon) to pick one of the system
+/// cursors instead.
+///
+/// The texture is drawn as a UI node, so it follows the cursor with the latency of a frame.
+#[derive(Debug, Clone, Default)]
+pub struct CursorImage {
+    pub texture: Option<Handle<Texture>>,
+    /// The point of the texture that is at the cursor position, in pixels from its top left
+    /// corner
+    pub hotspot: Vec2,
+}
+
+/// Marks the UI node that draws the [CursorImage]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CursorImageNode;
+
+#[derive(Debug, Default)]
+pub struct CursorImageState {
+    node: Option<Entity>,
+    material: Option<Handle<ColorMaterial>>,
+    hid_cursor: bool,
+}
+
+/// Spawns, moves and despawns the node that draws the [CursorImage], and hides the system cursor
+/// while it is shown. The window is only borrowed mutably to change the visibility of its cursor,
+/// so [Windows] isn't marked as changed every frame.
+pub fn cursor_image_system(
+    commands: &mut Commands,
+    mut state: Local<CursorImageState>,
+    cursor_image: Res<CursorImage>,
+    mut windows: ResMut<Windows>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    textures: Res<Assets<Texture>>,
+    mut nodes: Query<(&mut Style, &mut Transform, &mut Visible), With<CursorImageNode>>,
+) {
+    let (cursor_visible, cursor_position) = match windows.get_primary() {
+        Some(window) => (window.cursor_visible(), window.cursor_position()),
+        None => return,
+    };
+    let texture = match &cursor_image.texture {
+        Some(texture) => texture,
+        None => {
+            if let Some(node) = state.node.take() {
+                commands.despawn(node);
+            }
+            if let Some(material) = state.material.take() {
+                materials.remove(&material);
+            }
+            if state.hid_cursor {
+                if !cursor_visible {
+                    let window = windows.get_primary_mut().unwrap();
+                    window.set_cursor_visibility(true);
+                }
+                state.hid_cursor = false;
+            }
+            return;
+        }
+    };
+
+    if cursor_visible {
+        let window = windows.get_primary_mut().unwrap();
+        window.set_cursor_visibility(false);
+        state.hid_cursor = true;
+    }
+    match &state.material {
+        Some(material) => {
+            let material = materials.get_mut(material).unwrap();
+            if material.texture.as_ref() != Some(texture) {
+                material.texture = Some(texture.clone());
+            }
+        }
+        None => state.material = Some(materials.add(texture.clone().into())),
+    }
+    let node = match state.node {
+        Some(node) => node,
+        None => {
+            commands
+                .spawn(ImageBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        ..Default::default()
+                    },
+                    material: state.material.clone().unwrap(),
+                    ..Default::default()
+                })
+                .with(CursorImageNode);
+            let node = commands.current_entity().unwrap();
+            state.node = Some(node);
+            return;
+        }
+    };
+
+    let (mut style, mut transform, mut visible) = match nodes.get_mut(node) {
+        Ok(node) => node,
+        Err(_) => return,
+    };
+    let size = textures.get(texture).map(|texture| texture.size);
+    match (cursor_position, size) {
+        (Some(position), Some(size)) => {
+            visible.is_visible = true;
+            style.position.left = Val::Px(position.x - cursor_image.hotspot.x);
+            style.position.bottom =
+                Val::Px(position.y - (size.height as f32 - cursor_image.hotspot.y));
+            transform.translation.z = CURSOR_IMAGE_Z;
+        }
+        _ => visible.is_visible = false,
+    }
+}
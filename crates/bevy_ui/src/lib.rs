@@ -1,4 +1,5 @@
 mod anchors;
+mod cursor;
 pub mod entity;
 mod flex;
 mod focus;
@@ -9,6 +10,7 @@ pub mod update;
 pub mod widget;
 
 pub use anchors::*;
+pub use cursor::*;
 pub use flex::*;
 pub use focus::*;
 pub use margins::*;
@@ -20,7 +22,7 @@ pub mod prelude {
         entity::*,
         node::*,
         widget::{Button, Text},
-        Anchors, Interaction, Margins,
+        Anchors, CursorImage, Interaction, Margins,
     };
 }
 
@@ -39,6 +41,7 @@ pub mod stage {
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut AppBuilder) {
         app.init_resource::<FlexSurface>()
+            .init_resource::<CursorImage>()
             .add_stage_before(
                 bevy_app::stage::POST_UPDATE,
                 stage::UI,
@@ -49,6 +52,8 @@ impl Plugin for UiPlugin {
             .add_system_to_stage(stage::UI, widget::text_system.system())
             .add_system_to_stage(stage::UI, widget::image_node_system.system())
             .add_system_to_stage(stage::UI, ui_z_system.system())
+            // after the z of nodes is set, and before the nodes are laid out
+            .add_system_to_stage(stage::UI, cursor_image_system.system())
             .add_system_to_stage(stage::UI, flex_node_system.system())
             .add_system_to_stage(bevy_render::stage::DRAW, widget::draw_text_system.system());
 
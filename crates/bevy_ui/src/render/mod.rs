@@ -32,7 +32,7 @@ pub fn build_ui_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
             clamp_depth: false,
         }),
         depth_stencil_state: Some(DepthStencilStateDescriptor {
-            format: TextureFormat::Depth32Float,
+            format: TextureFormat::Depth24PlusStencil8,
             depth_write_enabled: true,
             depth_compare: CompareFunction::Less,
             stencil: StencilStateDescriptor {
@@ -105,7 +105,10 @@ impl UiRenderGraphBuilder for RenderGraph {
                     load: LoadOp::Clear(1.0),
                     store: true,
                 }),
-                stencil_ops: None,
+                stencil_ops: Some(Operations {
+                    load: LoadOp::Clear(0),
+                    store: true,
+                }),
             }),
             sample_count: msaa.samples,
         });
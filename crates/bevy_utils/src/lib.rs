@@ -1,6 +1,13 @@
+mod pool;
+mod small_map;
+
 pub use ahash::AHasher;
 use ahash::RandomState;
+pub use fixedbitset::FixedBitSet;
 pub use instant::{Duration, Instant};
+pub use pool::*;
+pub use small_map::*;
+pub use smallvec::{smallvec, SmallVec};
 use std::{future::Future, pin::Pin};
 pub use tracing;
 pub use uuid::Uuid;
@@ -0,0 +1,209 @@
+/// A handle to a value in a [Pool]. Handles to removed values stay invalid after their slot is
+/// reused, because the slot's generation changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PoolHandle {
+    index: u32,
+    generation: u32,
+}
+
+impl PoolHandle {
+    /// The slot of the value in its pool
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// How many times the slot was used before this value
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Slot<T> {
+    Occupied {
+        generation: u32,
+        value: T,
+    },
+    Free {
+        generation: u32,
+        next_free: Option<u32>,
+    },
+}
+
+/// Values addressed by [PoolHandle]s, for engine internals that reuse many short-lived values,
+/// like chunk textures or particle buffers, without spawning entities for them.
+///
+/// The slots of removed values are reused by the next values inserted, so a pool doesn't grow
+/// beyond the most values it held at once. Each reuse of a slot bumps its generation, so a handle
+/// to a removed value never returns the value that replaced it.
+///
+/// # Examples
+///
+/// ```
+/// use bevy_utils::Pool;
+/// let mut pool = Pool::default();
+/// let first = pool.insert("first");
+/// assert_eq!(pool.remove(first), Some("first"));
+///
+/// let second = pool.insert("second");
+/// // the slot is reused, but the old handle doesn't see the new value
+/// assert_eq!(second.index(), first.index());
+/// assert_eq!(pool.get(first), None);
+/// assert_eq!(pool.get(second), Some(&"second"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Pool<T> {
+    slots: Vec<Slot<T>>,
+    free: Option<u32>,
+    len: usize,
+}
+
+impl<T> Default for Pool<T> {
+    fn default() -> Self {
+        Pool {
+            slots: Vec::new(),
+            free: None,
+            len: 0,
+        }
+    }
+}
+
+impl<T> Pool<T> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Pool {
+            slots: Vec::with_capacity(capacity),
+            ..Default::default()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Adds a value, in the slot of a removed value if there is one
+    pub fn insert(&mut self, value: T) -> PoolHandle {
+        self.len += 1;
+        if let Some(index) = self.free {
+            let slot = &mut self.slots[index as usize];
+            let (generation, next_free) = match *slot {
+                Slot::Free {
+                    generation,
+                    next_free,
+                } => (generation, next_free),
+                Slot::Occupied { .. } => unreachable!("occupied slot in the free list"),
+            };
+            self.free = next_free;
+            *slot = Slot::Occupied { generation, value };
+            PoolHandle { index, generation }
+        } else {
+            let index = self.slots.len() as u32;
+            self.slots.push(Slot::Occupied {
+                generation: 0,
+                value,
+            });
+            PoolHandle {
+                index,
+                generation: 0,
+            }
+        }
+    }
+
+    /// Removes a value, leaving its handle and any copies of it invalid
+    pub fn remove(&mut self, handle: PoolHandle) -> Option<T> {
+        self.get(handle)?;
+        let slot = &mut self.slots[handle.index as usize];
+        let free = Slot::Free {
+            generation: handle.generation.wrapping_add(1),
+            next_free: self.free,
+        };
+        self.free = Some(handle.index);
+        self.len -= 1;
+        match std::mem::replace(slot, free) {
+            Slot::Occupied { value, .. } => Some(value),
+            Slot::Free { .. } => unreachable!(),
+        }
+    }
+
+    pub fn contains(&self, handle: PoolHandle) -> bool {
+        self.get(handle).is_some()
+    }
+
+    pub fn get(&self, handle: PoolHandle) -> Option<&T> {
+        match self.slots.get(handle.index as usize)? {
+            Slot::Occupied { generation, value } if *generation == handle.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, handle: PoolHandle) -> Option<&mut T> {
+        match self.slots.get_mut(handle.index as usize)? {
+            Slot::Occupied { generation, value } if *generation == handle.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Removes every value. Their handles stay invalid, as if they were removed one by one.
+    pub fn clear(&mut self) {
+        let handles = self.iter().map(|(handle, _)| handle).collect::<Vec<_>>();
+        for handle in handles {
+            self.remove(handle);
+        }
+    }
+
+    /// The values and their handles, in the order of their slots
+    pub fn iter(&self) -> impl Iterator<Item = (PoolHandle, &T)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| match slot {
+                Slot::Occupied { generation, value } => Some((
+                    PoolHandle {
+                        index: index as u32,
+                        generation: *generation,
+                    },
+                    value,
+                )),
+                Slot::Free { .. } => None,
+            })
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (PoolHandle, &mut T)> {
+        self.slots
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, slot)| match slot {
+                Slot::Occupied { generation, value } => Some((
+                    PoolHandle {
+                        index: index as u32,
+                        generation: *generation,
+                    },
+                    value,
+                )),
+                Slot::Free { .. } => None,
+            })
+    }
+}
+
+impl<T> std::ops::Index<PoolHandle> for Pool<T> {
+    type Output = T;
+
+    fn index(&self, handle: PoolHandle) -> &T {
+        self.get(handle)
+            .unwrap_or_else(|| panic!("{:?} was removed from the pool", handle))
+    }
+}
+
+impl<T> std::ops::IndexMut<PoolHandle> for Pool<T> {
+    fn index_mut(&mut self, handle: PoolHandle) -> &mut T {
+        self.get_mut(handle)
+            .unwrap_or_else(|| panic!("{:?} was removed from the pool", handle))
+    }
+}
@@ -0,0 +1,194 @@
+use crate::HashMap;
+use smallvec::SmallVec;
+use std::{borrow::Borrow, hash::Hash};
+
+/// The number of entries a [SmallMap] keeps inline before it moves them into a [HashMap]
+pub const SMALL_MAP_INLINE_CAPACITY: usize = 8;
+
+/// A map for the many small maps of a game, like the components of a prefab or the properties
+/// of a tile, that doesn't allocate until it holds more than [SMALL_MAP_INLINE_CAPACITY]
+/// entries.
+///
+/// Up to that many entries are kept inline and found by comparing keys, which is faster than
+/// hashing for so few of them. Beyond that, the entries are moved into a [HashMap] with AHash.
+///
+/// # Examples
+///
+/// ```
+/// use bevy_utils::SmallMap;
+/// let mut map = SmallMap::default();
+/// map.insert("speed", 2.0);
+/// assert_eq!(map.insert("speed", 3.0), Some(2.0));
+/// assert_eq!(map.get("speed"), Some(&3.0));
+/// assert!(map.is_inline());
+///
+/// for i in 0..16 {
+///     map.insert(if i % 2 == 0 { "even" } else { "odd" }, i as f32);
+/// }
+/// assert_eq!(map.len(), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SmallMap<K, V> {
+    entries: SmallMapEntries<K, V>,
+}
+
+#[derive(Debug, Clone)]
+enum SmallMapEntries<K, V> {
+    Inline(SmallVec<[(K, V); SMALL_MAP_INLINE_CAPACITY]>),
+    Hashed(HashMap<K, V>),
+}
+
+impl<K, V> Default for SmallMap<K, V> {
+    fn default() -> Self {
+        SmallMap {
+            entries: SmallMapEntries::Inline(SmallVec::new()),
+        }
+    }
+}
+
+impl<K: Hash + Eq, V> SmallMap<K, V> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.entries {
+            SmallMapEntries::Inline(entries) => entries.len(),
+            SmallMapEntries::Hashed(entries) => entries.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether the entries are still kept inline, rather than in a [HashMap]
+    pub fn is_inline(&self) -> bool {
+        matches!(self.entries, SmallMapEntries::Inline(_))
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match &self.entries {
+            SmallMapEntries::Inline(entries) => entries
+                .iter()
+                .find(|(k, _)| k.borrow() == key)
+                .map(|(_, v)| v),
+            SmallMapEntries::Hashed(entries) => entries.get(key),
+        }
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match &mut self.entries {
+            SmallMapEntries::Inline(entries) => entries
+                .iter_mut()
+                .find(|(k, _)| k.borrow() == key)
+                .map(|(_, v)| v),
+            SmallMapEntries::Hashed(entries) => entries.get_mut(key),
+        }
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Inserts a value, returning the value that was replaced if the key was already in the map
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let entries = match &mut self.entries {
+            SmallMapEntries::Inline(entries) => entries,
+            SmallMapEntries::Hashed(entries) => return entries.insert(key, value),
+        };
+        if let Some((_, existing)) = entries.iter_mut().find(|(k, _)| *k == key) {
+            return Some(std::mem::replace(existing, value));
+        }
+        if entries.len() < SMALL_MAP_INLINE_CAPACITY {
+            entries.push((key, value));
+        } else {
+            let mut hashed = HashMap::with_capacity_and_hasher(
+                SMALL_MAP_INLINE_CAPACITY * 2,
+                Default::default(),
+            );
+            hashed.extend(entries.drain(..));
+            hashed.insert(key, value);
+            self.entries = SmallMapEntries::Hashed(hashed);
+        }
+        None
+    }
+
+    /// Removes a value. Maps that moved their entries into a [HashMap] keep them there.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match &mut self.entries {
+            SmallMapEntries::Inline(entries) => {
+                let index = entries.iter().position(|(k, _)| k.borrow() == key)?;
+                Some(entries.swap_remove(index).1)
+            }
+            SmallMapEntries::Hashed(entries) => entries.remove(key),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries = SmallMapEntries::Inline(SmallVec::new());
+    }
+
+    /// The entries, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        let (inline, hashed) = match &self.entries {
+            SmallMapEntries::Inline(entries) => (Some(entries.iter().map(|(k, v)| (k, v))), None),
+            SmallMapEntries::Hashed(entries) => (None, Some(entries.iter())),
+        };
+        inline
+            .into_iter()
+            .flatten()
+            .chain(hashed.into_iter().flatten())
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, v)| v)
+    }
+}
+
+impl<K: Hash + Eq, V> std::iter::FromIterator<(K, V)> for SmallMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = SmallMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Hash + Eq, V> Extend<(K, V)> for SmallMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Hash + Eq, V: PartialEq> PartialEq for SmallMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl<K: Hash + Eq, V: Eq> Eq for SmallMap<K, V> {}
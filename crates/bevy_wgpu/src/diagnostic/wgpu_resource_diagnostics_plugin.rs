@@ -23,6 +23,18 @@ impl WgpuResourceDiagnosticsPlugin {
         DiagnosticId::from_u128(96406067032931216377076410852598331304);
     pub const BUFFERS: DiagnosticId =
         DiagnosticId::from_u128(133146619577893994787249934474491530491);
+    pub const BUFFER_ARENA_BLOCKS: DiagnosticId =
+        DiagnosticId::from_u128(227146542184813930472652096612803577346);
+    pub const BUFFER_ARENA_ALLOCATIONS: DiagnosticId =
+        DiagnosticId::from_u128(59830913446213744305217935428386420977);
+    pub const BUFFER_ARENA_USED_MEMORY: DiagnosticId =
+        DiagnosticId::from_u128(170425226339316858738513651467938046543);
+    pub const BUFFER_ARENA_FRAGMENTATION: DiagnosticId =
+        DiagnosticId::from_u128(298553616446350407361458328712519626818);
+    pub const STAGING_CHUNKS: DiagnosticId =
+        DiagnosticId::from_u128(71923412687154219346028617259014530913);
+    pub const UPLOADED_BYTES: DiagnosticId =
+        DiagnosticId::from_u128(246312875103662980311374852965731046571);
     pub const RENDER_PIPELINES: DiagnosticId =
         DiagnosticId::from_u128(278527620040377353875091478462209885377);
     pub const SAMPLERS: DiagnosticId =
@@ -57,6 +69,30 @@ impl WgpuResourceDiagnosticsPlugin {
 
         diagnostics.add(Diagnostic::new(Self::BUFFERS, "buffers", 10));
 
+        diagnostics.add(Diagnostic::new(
+            Self::BUFFER_ARENA_BLOCKS,
+            "buffer_arena_blocks",
+            10,
+        ));
+        diagnostics.add(Diagnostic::new(
+            Self::BUFFER_ARENA_ALLOCATIONS,
+            "buffer_arena_allocations",
+            10,
+        ));
+        diagnostics.add(Diagnostic::new(
+            Self::BUFFER_ARENA_USED_MEMORY,
+            "buffer_arena_used_memory",
+            10,
+        ));
+        diagnostics.add(Diagnostic::new(
+            Self::BUFFER_ARENA_FRAGMENTATION,
+            "buffer_arena_fragmentation",
+            10,
+        ));
+
+        diagnostics.add(Diagnostic::new(Self::STAGING_CHUNKS, "staging_chunks", 10));
+        diagnostics.add(Diagnostic::new(Self::UPLOADED_BYTES, "uploaded_bytes", 10));
+
         diagnostics.add(Diagnostic::new(Self::TEXTURES, "textures", 10));
 
         diagnostics.add(Diagnostic::new(Self::TEXTURE_VIEWS, "texture_views", 10));
@@ -121,6 +157,33 @@ impl WgpuResourceDiagnosticsPlugin {
             render_resource_context.resources.buffers.read().len() as f64,
         );
 
+        let buffer_arena_stats = render_resource_context
+            .resources
+            .buffer_arena
+            .read()
+            .stats();
+        diagnostics.add_measurement(Self::BUFFER_ARENA_BLOCKS, buffer_arena_stats.blocks as f64);
+        diagnostics.add_measurement(
+            Self::BUFFER_ARENA_ALLOCATIONS,
+            buffer_arena_stats.allocations as f64,
+        );
+        diagnostics.add_measurement(
+            Self::BUFFER_ARENA_USED_MEMORY,
+            buffer_arena_stats.used_memory as f64,
+        );
+        diagnostics.add_measurement(
+            Self::BUFFER_ARENA_FRAGMENTATION,
+            buffer_arena_stats.fragmentation as f64,
+        );
+
+        let staging_belt = render_resource_context.resources.staging_belt.lock();
+        diagnostics.add_measurement(Self::STAGING_CHUNKS, staging_belt.chunks() as f64);
+        diagnostics.add_measurement(
+            Self::UPLOADED_BYTES,
+            staging_belt.uploaded_bytes_last_frame() as f64,
+        );
+        drop(staging_belt);
+
         diagnostics.add_measurement(
             Self::TEXTURES,
             render_resource_context.resources.textures.read().len() as f64,
@@ -1,14 +1,20 @@
 pub mod diagnostic;
 pub mod renderer;
+mod wgpu_buffer_arena;
+mod wgpu_options;
 mod wgpu_render_pass;
 mod wgpu_renderer;
 mod wgpu_resources;
+mod wgpu_staging_belt;
 mod wgpu_type_converter;
 
 use futures_lite::future;
+pub use wgpu_buffer_arena::*;
+pub use wgpu_options::*;
 pub use wgpu_render_pass::*;
 pub use wgpu_renderer::*;
 pub use wgpu_resources::*;
+pub use wgpu_staging_belt::*;
 
 use bevy_app::prelude::*;
 use bevy_ecs::{IntoSystem, Resources, World};
@@ -25,37 +31,40 @@ impl Plugin for WgpuPlugin {
             .add_system_to_stage(
                 bevy_render::stage::POST_RENDER,
                 shared_buffers_update_system.system(),
+            )
+            .add_shutdown_system_to_stage(
+                shutdown_stage::POST_SHUTDOWN,
+                wgpu_shutdown_system.system(),
             );
     }
 }
 
+/// Waits for the GPU to finish the submitted work, then releases the GPU resources, before the
+/// windows they draw to are closed
+pub fn wgpu_shutdown_system(_world: &mut World, resources: &mut Resources) {
+    let render_resource_context = match resources.get::<Box<dyn RenderResourceContext>>() {
+        Some(render_resource_context) => render_resource_context,
+        None => return,
+    };
+    if let Some(render_resource_context) =
+        render_resource_context.downcast_ref::<WgpuRenderResourceContext>()
+    {
+        render_resource_context.device.poll(wgpu::Maintain::Wait);
+        render_resource_context.resources.clear();
+    }
+}
+
 pub fn get_wgpu_render_system(resources: &mut Resources) -> impl FnMut(&mut World, &mut Resources) {
     let options = resources
         .get_cloned::<WgpuOptions>()
-        .unwrap_or_else(WgpuOptions::default);
+        .unwrap_or_else(WgpuOptions::default)
+        .with_env_overrides();
     let mut wgpu_renderer = future::block_on(WgpuRenderer::new(options));
     let resource_context = WgpuRenderResourceContext::new(wgpu_renderer.device.clone());
     resources.insert::<Box<dyn RenderResourceContext>>(Box::new(resource_context));
+    resources.insert(wgpu_renderer.capabilities.clone());
     resources.insert(SharedBuffers::new(4096));
     move |world, resources| {
         wgpu_renderer.update(world, resources);
     }
 }
-
-#[derive(Default, Clone)]
-pub struct WgpuOptions {
-    power_pref: WgpuPowerOptions,
-}
-
-#[derive(Clone)]
-pub enum WgpuPowerOptions {
-    HighPerformance,
-    Adaptive,
-    LowPower,
-}
-
-impl Default for WgpuPowerOptions {
-    fn default() -> Self {
-        WgpuPowerOptions::HighPerformance
-    }
-}
@@ -114,6 +114,87 @@ impl RenderContext for WgpuRenderContext {
         )
     }
 
+    fn copy_texture_to_buffer(
+        &mut self,
+        source_texture: TextureId,
+        source_origin: [u32; 3],
+        source_mip_level: u32,
+        destination_buffer: BufferId,
+        destination_offset: u64,
+        destination_bytes_per_row: u32,
+        size: Extent3d,
+    ) {
+        self.render_resource_context.copy_texture_to_buffer(
+            self.command_encoder.get_or_create(&self.device),
+            source_texture,
+            source_origin,
+            source_mip_level,
+            destination_buffer,
+            destination_offset,
+            destination_bytes_per_row,
+            size,
+        )
+    }
+
+    fn write_texture(
+        &mut self,
+        data: &[u8],
+        bytes_per_row: u32,
+        destination_texture: TextureId,
+        destination_origin: [u32; 3],
+        destination_mip_level: u32,
+        size: Extent3d,
+    ) {
+        let resources = &self.render_resource_context.resources;
+        let (staging_buffer, staging_offset) =
+            resources.staging_belt.lock().stage(&self.device, data);
+        let textures = resources.textures.read();
+        let destination = textures.get(&destination_texture).unwrap();
+        self.command_encoder
+            .get_or_create(&self.device)
+            .copy_buffer_to_texture(
+                wgpu::BufferCopyView {
+                    buffer: &staging_buffer,
+                    layout: wgpu::TextureDataLayout {
+                        offset: staging_offset,
+                        bytes_per_row,
+                        rows_per_image: size.height,
+                    },
+                },
+                wgpu::TextureCopyView {
+                    texture: destination,
+                    mip_level: destination_mip_level,
+                    origin: wgpu::Origin3d {
+                        x: destination_origin[0],
+                        y: destination_origin[1],
+                        z: destination_origin[2],
+                    },
+                },
+                size.wgpu_into(),
+            );
+    }
+
+    fn write_buffer(&mut self, data: &[u8], destination_buffer: BufferId, destination_offset: u64) {
+        assert!(
+            data.len() & 3 == 0,
+            "buffer writes need a size that is a multiple of 4"
+        );
+        let resources = &self.render_resource_context.resources;
+        let (staging_buffer, staging_offset) =
+            resources.staging_belt.lock().stage(&self.device, data);
+        let buffers = resources.buffers.read();
+        let destination = buffers.get(&destination_buffer).unwrap();
+        self.command_encoder
+            .get_or_create(&self.device)
+            .copy_buffer_to_buffer(
+                &staging_buffer,
+                staging_offset,
+                &destination.buffer,
+                destination.offset + destination_offset,
+                data.len() as u64,
+            );
+    }
+
     fn resources(&self) -> &dyn RenderResourceContext {
         &self.render_resource_context
     }
@@ -63,9 +63,9 @@ impl WgpuRenderGraphExecutor {
                                     panic!("Node inputs not set.")
                                 };
 
-                                let output_resource =
-                                    outputs.get(*output_index).expect("Output should be set.");
-                                input_slot.resource = Some(output_resource);
+                                // an output is unset when its node had nothing to produce this
+                                // frame, like the swap chain of a minimized window
+                                input_slot.resource = outputs.get(*output_index);
                             } else {
                                 panic!("No edge connected to input.")
                             }
@@ -97,7 +97,13 @@ impl WgpuRenderGraphExecutor {
                 }
             }
 
+            let mut buffer_arena = render_resource_context.resources.buffer_arena.write();
+            let mut staging_belt = render_resource_context.resources.staging_belt.lock();
+            buffer_arena.write_queued(queue);
+            staging_belt.finish();
             queue.submit(command_buffers.drain(..));
+            buffer_arena.free_removed();
+            staging_belt.recall(&device);
         }
     }
 }
@@ -1,6 +1,6 @@
 use crate::{
     wgpu_type_converter::{OwnedWgpuVertexBufferDescriptor, WgpuInto},
-    WgpuBindGroupInfo, WgpuResources,
+    WgpuBindGroupInfo, WgpuBuffer, WgpuBufferArena, WgpuResources,
 };
 
 use bevy_asset::{Assets, Handle, HandleUntyped};
@@ -10,12 +10,12 @@ use bevy_render::{
     },
     renderer::{
         BindGroup, BufferId, BufferInfo, RenderResourceBinding, RenderResourceContext,
-        RenderResourceId, SamplerId, TextureId,
+        RenderResourceId, RenderResourceUsage, SamplerId, TextureId,
     },
     shader::{glsl_to_spirv, Shader, ShaderError, ShaderSource},
     texture::{Extent3d, SamplerDescriptor, TextureDescriptor},
 };
-use bevy_utils::tracing::trace;
+use bevy_utils::tracing::{debug, trace, warn};
 use bevy_window::{Window, WindowId};
 use futures_lite::future;
 use std::{borrow::Cow, ops::Range, sync::Arc};
@@ -43,6 +43,13 @@ impl WgpuRenderResourceContext {
         window_surfaces.insert(window_id, surface);
     }
 
+    /// Drops the surface and swap chain of every window. Surfaces become invalid when the app is
+    /// suspended on mobile platforms, and must be recreated when it resumes.
+    pub fn remove_window_surfaces(&self) {
+        self.resources.window_swap_chains.write().clear();
+        self.resources.window_surfaces.write().clear();
+    }
+
     pub fn copy_buffer_to_buffer(
         &self,
         command_encoder: &mut wgpu::CommandEncoder,
@@ -57,10 +64,10 @@ impl WgpuRenderResourceContext {
         let source = buffers.get(&source_buffer).unwrap();
         let destination = buffers.get(&destination_buffer).unwrap();
         command_encoder.copy_buffer_to_buffer(
-            source,
-            source_offset,
-            destination,
-            destination_offset,
+            &source.buffer,
+            source.offset + source_offset,
+            &destination.buffer,
+            destination.offset + destination_offset,
             size,
         );
     }
@@ -84,9 +91,9 @@ impl WgpuRenderResourceContext {
         let destination = textures.get(&destination_texture).unwrap();
         command_encoder.copy_buffer_to_texture(
             wgpu::BufferCopyView {
-                buffer: source,
+                buffer: &source.buffer,
                 layout: wgpu::TextureDataLayout {
-                    offset: source_offset,
+                    offset: source.offset + source_offset,
                     bytes_per_row: source_bytes_per_row,
                     rows_per_image: size.height,
                 },
@@ -104,6 +111,45 @@ impl WgpuRenderResourceContext {
         );
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn copy_texture_to_buffer(
+        &self,
+        command_encoder: &mut wgpu::CommandEncoder,
+        source_texture: TextureId,
+        source_origin: [u32; 3],
+        source_mip_level: u32,
+        destination_buffer: BufferId,
+        destination_offset: u64,
+        destination_bytes_per_row: u32,
+        size: Extent3d,
+    ) {
+        let buffers = self.resources.buffers.read();
+        let textures = self.resources.textures.read();
+
+        let source = textures.get(&source_texture).unwrap();
+        let destination = buffers.get(&destination_buffer).unwrap();
+        command_encoder.copy_texture_to_buffer(
+            wgpu::TextureCopyView {
+                texture: source,
+                mip_level: source_mip_level,
+                origin: wgpu::Origin3d {
+                    x: source_origin[0],
+                    y: source_origin[1],
+                    z: source_origin[2],
+                },
+            },
+            wgpu::BufferCopyView {
+                buffer: &destination.buffer,
+                layout: wgpu::TextureDataLayout {
+                    offset: destination.offset + destination_offset,
+                    bytes_per_row: destination_bytes_per_row,
+                    rows_per_image: size.height,
+                },
+            },
+            size.wgpu_into(),
+        );
+    }
+
     pub fn create_bind_group_layout(&self, descriptor: &BindGroupDescriptor) {
         if self
             .resources
@@ -148,15 +194,20 @@ impl WgpuRenderResourceContext {
         bind_group_layouts.insert(descriptor.id, bind_group_layout);
     }
 
-    fn try_next_swap_chain_texture(&self, window_id: bevy_window::WindowId) -> Option<TextureId> {
+    /// The next frame of the swap chain of the window, or `None` if the window has no swap chain
+    fn try_next_swap_chain_texture(
+        &self,
+        window_id: bevy_window::WindowId,
+    ) -> Option<Result<TextureId, wgpu::SwapChainError>> {
         let mut window_swap_chains = self.resources.window_swap_chains.write();
         let mut swap_chain_outputs = self.resources.swap_chain_frames.write();
 
-        let window_swap_chain = window_swap_chains.get_mut(&window_id).unwrap();
-        let next_texture = window_swap_chain.get_current_frame().ok()?;
-        let id = TextureId::new();
-        swap_chain_outputs.insert(id, next_texture);
-        Some(id)
+        let window_swap_chain = window_swap_chains.get_mut(&window_id)?;
+        Some(window_swap_chain.get_current_frame().map(|next_texture| {
+            let id = TextureId::new();
+            swap_chain_outputs.insert(id, next_texture);
+            id
+        }))
     }
 }
 
@@ -193,16 +244,27 @@ impl RenderResourceContext for WgpuRenderResourceContext {
         let mut buffer_infos = self.resources.buffer_infos.write();
         let mut buffers = self.resources.buffers.write();
 
-        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
-            label: None,
-            size: buffer_info.size as u64,
-            usage: buffer_info.buffer_usage.wgpu_into(),
-            mapped_at_creation: buffer_info.mapped_at_creation,
-        });
+        let size = buffer_info.size as u64;
+        let usage = buffer_info.buffer_usage.wgpu_into();
+        let buffer =
+            if !buffer_info.mapped_at_creation && WgpuBufferArena::can_allocate(size, usage) {
+                self.resources
+                    .buffer_arena
+                    .write()
+                    .allocate(&self.device, size, usage)
+            } else {
+                let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+                    label: None,
+                    size,
+                    usage,
+                    mapped_at_creation: buffer_info.mapped_at_creation,
+                });
+                WgpuBuffer::new(buffer, size)
+            };
 
         let id = BufferId::new();
         buffer_infos.insert(id, buffer_info);
-        buffers.insert(id, Arc::new(buffer));
+        buffers.insert(id, buffer);
         id
     }
 
@@ -212,25 +274,49 @@ impl RenderResourceContext for WgpuRenderResourceContext {
         let mut buffers = self.resources.buffers.write();
 
         buffer_info.size = data.len();
-        let buffer = self
-            .device
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                contents: data,
-                label: None,
-                usage: buffer_info.buffer_usage.wgpu_into(),
-            });
+        let size = data.len() as u64;
+        let usage = buffer_info.buffer_usage.wgpu_into();
+        let buffer = if WgpuBufferArena::can_allocate(size, usage) {
+            let mut buffer_arena = self.resources.buffer_arena.write();
+            let buffer = buffer_arena.allocate(&self.device, size, usage);
+            buffer_arena.queue_write(&buffer, 0, data);
+            buffer
+        } else {
+            let buffer = self
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    contents: data,
+                    label: None,
+                    usage,
+                });
+            WgpuBuffer::new(buffer, size)
+        };
 
         let id = BufferId::new();
         buffer_infos.insert(id, buffer_info);
-        buffers.insert(id, Arc::new(buffer));
+        buffers.insert(id, buffer);
         id
     }
 
+    fn queue_write_buffer(&self, id: BufferId, offset: u64, data: &[u8]) {
+        let buffers = self.resources.buffers.read();
+        let buffer = buffers.get(&id).unwrap();
+        self.resources
+            .buffer_arena
+            .write()
+            .queue_write(buffer, offset, data);
+    }
+
     fn remove_buffer(&self, buffer: BufferId) {
         let mut buffers = self.resources.buffers.write();
         let mut buffer_infos = self.resources.buffer_infos.write();
 
-        buffers.remove(&buffer);
+        self.resources.pending_maps.lock().0.remove(&buffer);
+        if let Some(buffer) = buffers.remove(&buffer) {
+            if buffer.sub_allocated {
+                self.resources.buffer_arena.write().remove(buffer);
+            }
+        }
         buffer_infos.remove(&buffer);
     }
 
@@ -275,11 +361,18 @@ impl RenderResourceContext for WgpuRenderResourceContext {
     fn create_swap_chain(&self, window: &Window) {
         let surfaces = self.resources.window_surfaces.read();
         let mut window_swap_chains = self.resources.window_swap_chains.write();
+        window_swap_chains.remove(&window.id());
 
+        // minimized windows have no size, and suspended apps have no surfaces, so there is
+        // nothing to draw to until the window is restored or the app resumed
+        if window.physical_width() == 0 || window.physical_height() == 0 {
+            return;
+        }
+        let surface = match surfaces.get(&window.id()) {
+            Some(surface) => surface,
+            None => return,
+        };
         let swap_chain_descriptor: wgpu::SwapChainDescriptor = window.wgpu_into();
-        let surface = surfaces
-            .get(&window.id())
-            .expect("No surface found for window.");
         let swap_chain = self
             .device
             .create_swap_chain(surface, &swap_chain_descriptor);
@@ -287,17 +380,40 @@ impl RenderResourceContext for WgpuRenderResourceContext {
         window_swap_chains.insert(window.id(), swap_chain);
     }
 
-    fn next_swap_chain_texture(&self, window: &bevy_window::Window) -> TextureId {
-        if let Some(texture_id) = self.try_next_swap_chain_texture(window.id()) {
-            texture_id
-        } else {
-            self.resources
-                .window_swap_chains
-                .write()
-                .remove(&window.id());
+    fn next_swap_chain_texture(&self, window: &bevy_window::Window) -> Option<TextureId> {
+        if !self
+            .resources
+            .window_swap_chains
+            .read()
+            .contains_key(&window.id())
+        {
+            // the swap chain is dropped while the window is minimized or the app suspended
             self.create_swap_chain(window);
-            self.try_next_swap_chain_texture(window.id())
-                .expect("Failed to acquire next swap chain texture!")
+        }
+        match self.try_next_swap_chain_texture(window.id())? {
+            Ok(texture_id) => Some(texture_id),
+            // skip the frame, the next one waits again
+            Err(wgpu::SwapChainError::Timeout) => None,
+            Err(error) => {
+                // an outdated or lost swap chain is recreated with the current size of the window
+                debug!(
+                    "Recreating the swap chain of window {}: {}",
+                    window.id(),
+                    error
+                );
+                self.create_swap_chain(window);
+                match self.try_next_swap_chain_texture(window.id())? {
+                    Ok(texture_id) => Some(texture_id),
+                    Err(error) => {
+                        warn!(
+                            "Skipping a frame of window {}, no swap chain texture: {}",
+                            window.id(),
+                            error
+                        );
+                        None
+                    }
+                }
+            }
         }
     }
 
@@ -530,6 +646,15 @@ impl RenderResourceContext for WgpuRenderResourceContext {
         self.resources.buffer_infos.read().get(&buffer).cloned()
     }
 
+    fn resource_usage(&self) -> RenderResourceUsage {
+        let buffer_infos = self.resources.buffer_infos.read();
+        RenderResourceUsage {
+            buffers: buffer_infos.len(),
+            buffer_memory: buffer_infos.values().map(|info| info.size).sum(),
+            textures: self.resources.textures.read().len(),
+        }
+    }
+
     fn write_mapped_buffer(
         &self,
         id: BufferId,
@@ -548,7 +673,7 @@ impl RenderResourceContext for WgpuRenderResourceContext {
     fn map_buffer(&self, id: BufferId) {
         let buffers = self.resources.buffers.read();
         let buffer = buffers.get(&id).unwrap();
-        let buffer_slice = buffer.slice(..);
+        let buffer_slice = buffer.slice(0..buffer.size);
         let data = buffer_slice.map_async(wgpu::MapMode::Write);
         self.device.poll(wgpu::Maintain::Wait);
         if future::block_on(data).is_err() {
@@ -559,7 +684,41 @@ impl RenderResourceContext for WgpuRenderResourceContext {
     fn unmap_buffer(&self, id: BufferId) {
         let buffers = self.resources.buffers.read();
         let buffer = buffers.get(&id).unwrap();
-        buffer.unmap();
+        buffer.buffer.unmap();
+    }
+
+    fn map_buffer_async(&self, id: BufferId) {
+        let buffers = self.resources.buffers.read();
+        let buffer = buffers.get(&id).unwrap();
+        let map = buffer.slice(0..buffer.size).map_async(wgpu::MapMode::Read);
+        self.resources
+            .pending_maps
+            .lock()
+            .0
+            .insert(id, Box::pin(map));
+    }
+
+    fn try_read_mapped_buffer(&self, id: BufferId, read: &mut dyn FnMut(&[u8])) -> bool {
+        let mut pending_maps = self.resources.pending_maps.lock();
+        let map = match pending_maps.0.get_mut(&id) {
+            Some(map) => map,
+            None => return false,
+        };
+        self.device.poll(wgpu::Maintain::Poll);
+        let result = match future::block_on(future::poll_once(map)) {
+            Some(result) => result,
+            None => return false,
+        };
+        pending_maps.0.remove(&id);
+
+        let buffers = self.resources.buffers.read();
+        let buffer = buffers.get(&id).unwrap();
+        match result {
+            Ok(()) => read(&buffer.slice(0..buffer.size).get_mapped_range()),
+            Err(_) => warn!("Failed to map buffer {:?} for reading", id),
+        }
+        buffer.buffer.unmap();
+        true
     }
 
     fn get_aligned_texture_size(&self, size: usize) -> usize {
@@ -583,9 +742,6 @@ impl RenderResourceContext for WgpuRenderResourceContext {
             ShaderSource::Spirv(ref bytes) => bytes.clone(),
             ShaderSource::Glsl(ref source) => glsl_to_spirv(&source, shader.stage, macros)?,
         };
-        Ok(Shader {
-            source: ShaderSource::Spirv(spirv_data),
-            ..*shader
-        })
+        Ok(Shader::new(shader.stage, ShaderSource::Spirv(spirv_data)))
     }
 }
@@ -0,0 +1,318 @@
+use crate::renderer::BIND_BUFFER_ALIGNMENT;
+use bevy_utils::HashMap;
+use std::{ops::Range, sync::Arc};
+
+/// A wgpu buffer, or the range of a shared one that a small buffer was sub-allocated in
+#[derive(Debug, Clone)]
+pub struct WgpuBuffer {
+    pub buffer: Arc<wgpu::Buffer>,
+    /// Where the buffer starts in `buffer`
+    pub offset: u64,
+    pub size: u64,
+    /// Whether the buffer is a range of a buffer of the [WgpuBufferArena]
+    pub sub_allocated: bool,
+}
+
+impl WgpuBuffer {
+    /// A buffer that owns the whole wgpu buffer
+    pub fn new(buffer: wgpu::Buffer, size: u64) -> Self {
+        WgpuBuffer {
+            buffer: Arc::new(buffer),
+            offset: 0,
+            size,
+            sub_allocated: false,
+        }
+    }
+
+    /// A slice of the buffer, with `range` relative to its start
+    pub fn slice(&self, range: Range<u64>) -> wgpu::BufferSlice<'_> {
+        self.buffer
+            .slice(self.offset + range.start..self.offset + range.end)
+    }
+
+    /// A slice of the buffer from `offset` to its end
+    pub fn slice_from(&self, offset: u64) -> wgpu::BufferSlice<'_> {
+        self.slice(offset..self.size)
+    }
+}
+
+/// Hands out aligned ranges of a fixed size space, like a buffer. The first free range that fits
+/// is used, and freed ranges are merged with their neighbors.
+#[derive(Debug, Clone)]
+pub struct RangeAllocator {
+    size: u64,
+    /// Sorted and never touching each other
+    free_ranges: Vec<Range<u64>>,
+}
+
+impl RangeAllocator {
+    pub fn new(size: u64) -> Self {
+        RangeAllocator {
+            size,
+            free_ranges: std::iter::once(0..size).collect(),
+        }
+    }
+
+    pub fn allocate(&mut self, size: u64, alignment: u64) -> Option<Range<u64>> {
+        let (index, start) = self.free_ranges.iter().enumerate().find_map(|(i, free)| {
+            let start = free.start + (alignment - free.start % alignment) % alignment;
+            if start + size <= free.end {
+                Some((i, start))
+            } else {
+                None
+            }
+        })?;
+        let free = self.free_ranges.remove(index);
+        let end = start + size;
+        if end < free.end {
+            self.free_ranges.insert(index, end..free.end);
+        }
+        // the padding before an aligned start stays free
+        if free.start < start {
+            self.free_ranges.insert(index, free.start..start);
+        }
+        Some(start..end)
+    }
+
+    pub fn free(&mut self, range: Range<u64>) {
+        let index = self
+            .free_ranges
+            .iter()
+            .position(|free| free.start > range.start)
+            .unwrap_or(self.free_ranges.len());
+        let merges_previous = index > 0 && self.free_ranges[index - 1].end == range.start;
+        let merges_next =
+            index < self.free_ranges.len() && self.free_ranges[index].start == range.end;
+        match (merges_previous, merges_next) {
+            (true, true) => {
+                let next = self.free_ranges.remove(index);
+                self.free_ranges[index - 1].end = next.end;
+            }
+            (true, false) => self.free_ranges[index - 1].end = range.end,
+            (false, true) => self.free_ranges[index].start = range.start,
+            (false, false) => self.free_ranges.insert(index, range),
+        }
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn free_size(&self) -> u64 {
+        self.free_ranges
+            .iter()
+            .map(|free| free.end - free.start)
+            .sum()
+    }
+
+    pub fn largest_free_range(&self) -> u64 {
+        self.free_ranges
+            .iter()
+            .map(|free| free.end - free.start)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.free_size() == self.size
+    }
+}
+
+/// How much of the [WgpuBufferArena] is used
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct BufferArenaStats {
+    /// The number of shared buffers
+    pub blocks: usize,
+    /// The number of buffers sub-allocated in the shared buffers
+    pub allocations: usize,
+    /// The total size of the shared buffers, in bytes
+    pub block_memory: u64,
+    /// The bytes used by sub-allocated buffers
+    pub used_memory: u64,
+    /// How scattered the free memory of the shared buffers is, from 0 when each shared buffer has
+    /// a single free range, up to 1 when their free memory is split into many small ranges
+    pub fragmentation: f32,
+}
+
+#[derive(Debug)]
+struct ArenaBlock {
+    buffer: Arc<wgpu::Buffer>,
+    ranges: RangeAllocator,
+    allocations: usize,
+}
+
+/// Sub-allocates small GPU-only buffers in large shared ones, so thousands of per-sprite and
+/// per-chunk uniforms don't need a wgpu buffer each. Buffers that are mapped or larger than
+/// [WgpuBufferArena::MAX_ALLOCATION_SIZE] get their own wgpu buffer.
+///
+/// The ranges of removed buffers are only reused once the commands recorded before their removal
+/// are submitted, and the data of buffers created with data is written right before the next
+/// submission.
+#[derive(Debug, Default)]
+pub struct WgpuBufferArena {
+    blocks: HashMap<wgpu::BufferUsage, Vec<ArenaBlock>>,
+    removed: Vec<WgpuBuffer>,
+    queued_writes: Vec<(Arc<wgpu::Buffer>, u64, Vec<u8>)>,
+}
+
+impl WgpuBufferArena {
+    pub const BLOCK_SIZE: u64 = 1024 * 1024;
+    pub const MAX_ALLOCATION_SIZE: u64 = 64 * 1024;
+
+    /// Whether a buffer of `size` with `usage` is sub-allocated
+    pub fn can_allocate(size: u64, usage: wgpu::BufferUsage) -> bool {
+        size > 0
+            && size <= Self::MAX_ALLOCATION_SIZE
+            && !usage.intersects(wgpu::BufferUsage::MAP_READ | wgpu::BufferUsage::MAP_WRITE)
+    }
+
+    pub fn allocate(
+        &mut self,
+        device: &wgpu::Device,
+        size: u64,
+        usage: wgpu::BufferUsage,
+    ) -> WgpuBuffer {
+        // the data of buffers created with data is copied in
+        let usage = usage | wgpu::BufferUsage::COPY_DST;
+        // copies need sizes that are a multiple of 4
+        let aligned_size = (size + 3) & !3;
+        let blocks = self.blocks.entry(usage).or_default();
+        for block in blocks.iter_mut() {
+            if let Some(range) = block
+                .ranges
+                .allocate(aligned_size, BIND_BUFFER_ALIGNMENT as u64)
+            {
+                block.allocations += 1;
+                return WgpuBuffer {
+                    buffer: block.buffer.clone(),
+                    offset: range.start,
+                    size,
+                    sub_allocated: true,
+                };
+            }
+        }
+
+        let mut block = ArenaBlock {
+            buffer: Arc::new(device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("buffer_arena_block"),
+                size: Self::BLOCK_SIZE,
+                usage,
+                mapped_at_creation: false,
+            })),
+            ranges: RangeAllocator::new(Self::BLOCK_SIZE),
+            allocations: 1,
+        };
+        let range = block
+            .ranges
+            .allocate(aligned_size, BIND_BUFFER_ALIGNMENT as u64)
+            .unwrap();
+        let buffer = WgpuBuffer {
+            buffer: block.buffer.clone(),
+            offset: range.start,
+            size,
+            sub_allocated: true,
+        };
+        blocks.push(block);
+        buffer
+    }
+
+    /// Queues `data` to be written to `buffer` at `offset` before the next submission. Buffers
+    /// that aren't sub-allocated need [wgpu::BufferUsage::COPY_DST].
+    pub fn queue_write(&mut self, buffer: &WgpuBuffer, offset: u64, data: &[u8]) {
+        let mut data = data.to_vec();
+        data.resize((data.len() + 3) & !3, 0);
+        self.queued_writes
+            .push((buffer.buffer.clone(), buffer.offset + offset, data));
+    }
+
+    /// Frees the range of the sub-allocated `buffer` after the next submission
+    pub fn remove(&mut self, buffer: WgpuBuffer) {
+        self.removed.push(buffer);
+    }
+
+    /// Writes the queued data, before submitting commands to `queue`
+    pub fn write_queued(&mut self, queue: &wgpu::Queue) {
+        for (buffer, offset, data) in self.queued_writes.drain(..) {
+            queue.write_buffer(&buffer, offset, &data);
+        }
+    }
+
+    /// Frees the ranges of removed buffers, after submitting the commands that may use them.
+    /// Shared buffers that end up empty are dropped, except for one per usage.
+    pub fn free_removed(&mut self) {
+        for removed in self.removed.drain(..) {
+            let aligned_size = (removed.size + 3) & !3;
+            for blocks in self.blocks.values_mut() {
+                if let Some(block) = blocks
+                    .iter_mut()
+                    .find(|block| Arc::ptr_eq(&block.buffer, &removed.buffer))
+                {
+                    block
+                        .ranges
+                        .free(removed.offset..removed.offset + aligned_size);
+                    block.allocations -= 1;
+                    break;
+                }
+            }
+        }
+        for blocks in self.blocks.values_mut() {
+            let mut kept_empty_block = false;
+            blocks.retain(|block| {
+                if block.allocations > 0 {
+                    true
+                } else {
+                    !std::mem::replace(&mut kept_empty_block, true)
+                }
+            });
+        }
+    }
+
+    pub fn stats(&self) -> BufferArenaStats {
+        let mut stats = BufferArenaStats::default();
+        let mut fragmentation = 0.0;
+        for block in self.blocks.values().flatten() {
+            stats.blocks += 1;
+            stats.allocations += block.allocations;
+            stats.block_memory += block.ranges.size();
+            let free_size = block.ranges.free_size();
+            stats.used_memory += block.ranges.size() - free_size;
+            if free_size > 0 {
+                fragmentation += 1.0 - block.ranges.largest_free_range() as f32 / free_size as f32;
+            }
+        }
+        if stats.blocks > 0 {
+            stats.fragmentation = fragmentation / stats.blocks as f32;
+        }
+        stats
+    }
+
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+        self.removed.clear();
+        self.queued_writes.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocate_and_free_ranges() {
+        let mut ranges = RangeAllocator::new(1024);
+        assert_eq!(ranges.allocate(100, 256), Some(0..100));
+        assert_eq!(ranges.allocate(100, 256), Some(256..356));
+        assert_eq!(ranges.allocate(600, 256), None);
+        assert_eq!(ranges.allocate(300, 256), Some(512..812));
+        assert_eq!(ranges.free_size(), 1024 - 500);
+        assert_eq!(ranges.largest_free_range(), 212);
+
+        ranges.free(256..356);
+        assert_eq!(ranges.largest_free_range(), 412);
+        ranges.free(0..100);
+        ranges.free(512..812);
+        assert!(ranges.is_empty());
+        assert_eq!(ranges.largest_free_range(), 1024);
+        assert_eq!(ranges.allocate(1024, 256), Some(0..1024));
+    }
+}
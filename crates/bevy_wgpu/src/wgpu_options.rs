@@ -0,0 +1,164 @@
+use bevy_utils::tracing::warn;
+
+/// Configures which GPU the [WgpuPlugin](crate::WgpuPlugin) renders with. Insert it as a resource
+/// before adding the plugin.
+///
+/// The `BEVY_WGPU_BACKEND`, `BEVY_WGPU_POWER_PREFERENCE`, `BEVY_WGPU_DEVICE_TYPE` and
+/// `BEVY_WGPU_ADAPTER` environment variables override these options, so the same build can be
+/// run on each GPU of a machine, for example to compare the performance of the chunk renderer on
+/// an integrated and a discrete GPU.
+#[derive(Debug, Default, Clone)]
+pub struct WgpuOptions {
+    pub backend: WgpuBackend,
+    pub power_pref: WgpuPowerOptions,
+    /// Only render with GPUs of this type
+    pub device_type: Option<WgpuDeviceType>,
+    /// Only render with a GPU whose name contains this, ignoring case, like `"nvidia"`
+    pub adapter_name: Option<String>,
+}
+
+impl WgpuOptions {
+    /// These options, with the ones set in the `BEVY_WGPU_*` environment variables replacing them.
+    /// Values that can't be parsed are ignored with a warning.
+    pub fn with_env_overrides(self) -> Self {
+        self.with_overrides(|name| std::env::var(name).ok())
+    }
+
+    fn with_overrides(mut self, var: impl Fn(&str) -> Option<String>) -> Self {
+        fn parse<T>(
+            var: &impl Fn(&str) -> Option<String>,
+            name: &str,
+            parse: impl Fn(&str) -> Option<T>,
+        ) -> Option<T> {
+            let value = var(name)?;
+            let parsed = parse(&value.trim().to_lowercase());
+            if parsed.is_none() {
+                warn!("Ignoring the unknown value {:?} of {}", value, name);
+            }
+            parsed
+        }
+
+        if let Some(backend) = parse(&var, "BEVY_WGPU_BACKEND", WgpuBackend::parse) {
+            self.backend = backend;
+        }
+        if let Some(power_pref) = parse(&var, "BEVY_WGPU_POWER_PREFERENCE", WgpuPowerOptions::parse)
+        {
+            self.power_pref = power_pref;
+        }
+        if let Some(device_type) = parse(&var, "BEVY_WGPU_DEVICE_TYPE", WgpuDeviceType::parse) {
+            self.device_type = Some(device_type);
+        }
+        if let Some(adapter_name) = var("BEVY_WGPU_ADAPTER") {
+            self.adapter_name = Some(adapter_name);
+        }
+        self
+    }
+}
+
+/// The graphics APIs wgpu looks for GPUs with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WgpuBackend {
+    /// Vulkan, Metal, DX12 or WebGPU, whichever the platform supports
+    Primary,
+    /// OpenGL or DX11, for older GPUs
+    Secondary,
+    Vulkan,
+    Metal,
+    Dx12,
+    Dx11,
+    Gl,
+}
+
+impl Default for WgpuBackend {
+    fn default() -> Self {
+        WgpuBackend::Primary
+    }
+}
+
+impl WgpuBackend {
+    fn parse(value: &str) -> Option<Self> {
+        Some(match value {
+            "primary" => WgpuBackend::Primary,
+            "secondary" => WgpuBackend::Secondary,
+            "vulkan" => WgpuBackend::Vulkan,
+            "metal" => WgpuBackend::Metal,
+            "dx12" => WgpuBackend::Dx12,
+            "dx11" => WgpuBackend::Dx11,
+            "gl" => WgpuBackend::Gl,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WgpuPowerOptions {
+    HighPerformance,
+    Adaptive,
+    LowPower,
+}
+
+impl Default for WgpuPowerOptions {
+    fn default() -> Self {
+        WgpuPowerOptions::HighPerformance
+    }
+}
+
+impl WgpuPowerOptions {
+    fn parse(value: &str) -> Option<Self> {
+        Some(match value {
+            "high_performance" | "high-performance" | "high" => WgpuPowerOptions::HighPerformance,
+            "adaptive" | "default" => WgpuPowerOptions::Adaptive,
+            "low_power" | "low-power" | "low" => WgpuPowerOptions::LowPower,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WgpuDeviceType {
+    /// A GPU that shares memory with the CPU
+    Integrated,
+    /// A GPU on its own card
+    Discrete,
+    /// A GPU of a virtual machine
+    Virtual,
+    /// A software renderer
+    Cpu,
+}
+
+impl WgpuDeviceType {
+    fn parse(value: &str) -> Option<Self> {
+        Some(match value {
+            "integrated" => WgpuDeviceType::Integrated,
+            "discrete" => WgpuDeviceType::Discrete,
+            "virtual" => WgpuDeviceType::Virtual,
+            "cpu" => WgpuDeviceType::Cpu,
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_overrides() {
+        let options = WgpuOptions {
+            backend: WgpuBackend::Vulkan,
+            adapter_name: Some("amd".to_string()),
+            ..Default::default()
+        };
+        let options = options.with_overrides(|name| match name {
+            "BEVY_WGPU_POWER_PREFERENCE" => Some("Low_Power".to_string()),
+            "BEVY_WGPU_DEVICE_TYPE" => Some("integrated".to_string()),
+            "BEVY_WGPU_BACKEND" => Some("opengl".to_string()),
+            _ => None,
+        });
+        // the unknown backend keeps the one of the options
+        assert_eq!(options.backend, WgpuBackend::Vulkan);
+        assert_eq!(options.power_pref, WgpuPowerOptions::LowPower);
+        assert_eq!(options.device_type, Some(WgpuDeviceType::Integrated));
+        assert_eq!(options.adapter_name.as_deref(), Some("amd"));
+    }
+}
@@ -24,7 +24,7 @@ impl<'a> RenderPass for WgpuRenderPass<'a> {
     fn set_vertex_buffer(&mut self, start_slot: u32, buffer_id: BufferId, offset: u64) {
         let buffer = self.wgpu_resources.buffers.get(&buffer_id).unwrap();
         self.render_pass
-            .set_vertex_buffer(start_slot, buffer.slice(offset..));
+            .set_vertex_buffer(start_slot, buffer.slice_from(offset));
     }
 
     fn set_viewport(&mut self, x: f32, y: f32, w: f32, h: f32, min_depth: f32, max_depth: f32) {
@@ -42,7 +42,7 @@ impl<'a> RenderPass for WgpuRenderPass<'a> {
 
     fn set_index_buffer(&mut self, buffer_id: BufferId, offset: u64) {
         let buffer = self.wgpu_resources.buffers.get(&buffer_id).unwrap();
-        self.render_pass.set_index_buffer(buffer.slice(offset..));
+        self.render_pass.set_index_buffer(buffer.slice_from(offset));
     }
 
     fn draw_indexed(&mut self, indices: Range<u32>, base_vertex: i32, instances: Range<u32>) {
@@ -1,64 +1,185 @@
 use crate::{
     renderer::{WgpuRenderGraphExecutor, WgpuRenderResourceContext},
-    WgpuOptions, WgpuPowerOptions,
+    WgpuBackend, WgpuOptions, WgpuPowerOptions,
 };
 use bevy_app::prelude::*;
 use bevy_ecs::{Resources, World};
 use bevy_render::{
     render_graph::{DependentNodeStager, RenderGraph, RenderGraphStager},
-    renderer::RenderResourceContext,
+    renderer::{RenderCapabilities, RenderResourceContext},
 };
-use bevy_window::{WindowCreated, WindowResized, Windows};
+use bevy_utils::tracing::info;
+use bevy_window::{AppResumed, AppSuspended, WindowCreated, WindowResized, Windows};
 use std::{ops::Deref, sync::Arc};
 
 pub struct WgpuRenderer {
     pub instance: wgpu::Instance,
     pub device: Arc<wgpu::Device>,
     pub queue: wgpu::Queue,
+    pub capabilities: RenderCapabilities,
     pub window_resized_event_reader: EventReader<WindowResized>,
     pub window_created_event_reader: EventReader<WindowCreated>,
+    pub app_suspended_event_reader: EventReader<AppSuspended>,
+    pub app_resumed_event_reader: EventReader<AppResumed>,
     pub initialized: bool,
 }
 
+/// The adapter that matches the device type and name of the `options`, or `None` to let wgpu pick
+/// one by power preference. Adapters are listed in the same order on every run, so the same one is
+/// picked each time.
+#[cfg(not(target_arch = "wasm32"))]
+fn find_adapter(
+    instance: &wgpu::Instance,
+    backends: wgpu::BackendBit,
+    options: &WgpuOptions,
+) -> Option<wgpu::Adapter> {
+    if options.device_type.is_none() && options.adapter_name.is_none() {
+        return None;
+    }
+    let adapters = instance.enumerate_adapters(backends).collect::<Vec<_>>();
+    let adapter_name = options
+        .adapter_name
+        .as_ref()
+        .map(|name| name.to_lowercase());
+    let matches = |info: &wgpu::AdapterInfo| {
+        let device_type = match info.device_type {
+            wgpu::DeviceType::IntegratedGpu => Some(crate::WgpuDeviceType::Integrated),
+            wgpu::DeviceType::DiscreteGpu => Some(crate::WgpuDeviceType::Discrete),
+            wgpu::DeviceType::VirtualGpu => Some(crate::WgpuDeviceType::Virtual),
+            wgpu::DeviceType::Cpu => Some(crate::WgpuDeviceType::Cpu),
+            wgpu::DeviceType::Other => None,
+        };
+        (options.device_type.is_none() || options.device_type == device_type)
+            && adapter_name
+                .as_ref()
+                .map_or(true, |name| info.name.to_lowercase().contains(name))
+    };
+    // among the matching adapters, prefer the kind of GPU the power preference asks for
+    let rank = |info: &wgpu::AdapterInfo| match (options.power_pref, &info.device_type) {
+        (WgpuPowerOptions::HighPerformance, wgpu::DeviceType::DiscreteGpu)
+        | (WgpuPowerOptions::LowPower, wgpu::DeviceType::IntegratedGpu) => 0,
+        (_, wgpu::DeviceType::Cpu) => 2,
+        _ => 1,
+    };
+    let available = adapters
+        .iter()
+        .map(|adapter| {
+            let info = adapter.get_info();
+            format!("{} ({:?}, {:?})", info.name, info.device_type, info.backend)
+        })
+        .collect::<Vec<_>>();
+    let adapter = adapters
+        .into_iter()
+        .filter(|adapter| matches(&adapter.get_info()))
+        .min_by_key(|adapter| rank(&adapter.get_info()));
+    if adapter.is_none() {
+        panic!(
+            "No GPU matches the device type {:?} and name {:?}. The available GPUs are: {}",
+            options.device_type,
+            options.adapter_name,
+            available.join(", ")
+        );
+    }
+    adapter
+}
+
+#[cfg(target_arch = "wasm32")]
+fn find_adapter(
+    _instance: &wgpu::Instance,
+    _backends: wgpu::BackendBit,
+    options: &WgpuOptions,
+) -> Option<wgpu::Adapter> {
+    if options.device_type.is_some() || options.adapter_name.is_some() {
+        bevy_utils::tracing::warn!(
+            "The browser picks the GPU, so the device type and name of the WgpuOptions are ignored"
+        );
+    }
+    None
+}
+
 impl WgpuRenderer {
     pub async fn new(options: WgpuOptions) -> Self {
-        let instance = wgpu::Instance::new(wgpu::BackendBit::PRIMARY);
+        let backends = match options.backend {
+            WgpuBackend::Primary => wgpu::BackendBit::PRIMARY,
+            WgpuBackend::Secondary => wgpu::BackendBit::SECONDARY,
+            WgpuBackend::Vulkan => wgpu::BackendBit::VULKAN,
+            WgpuBackend::Metal => wgpu::BackendBit::METAL,
+            WgpuBackend::Dx12 => wgpu::BackendBit::DX12,
+            WgpuBackend::Dx11 => wgpu::BackendBit::DX11,
+            WgpuBackend::Gl => wgpu::BackendBit::GL,
+        };
+        let instance = wgpu::Instance::new(backends);
 
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: match options.power_pref {
-                    WgpuPowerOptions::HighPerformance => wgpu::PowerPreference::HighPerformance,
-                    WgpuPowerOptions::Adaptive => wgpu::PowerPreference::Default,
-                    WgpuPowerOptions::LowPower => wgpu::PowerPreference::LowPower,
-                },
-                compatible_surface: None,
-            })
-            .await
-            .expect("Unable to find a GPU! Make sure you have installed required drivers!");
+        let adapter = match find_adapter(&instance, backends, &options) {
+            Some(adapter) => adapter,
+            None => instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: match options.power_pref {
+                        WgpuPowerOptions::HighPerformance => wgpu::PowerPreference::HighPerformance,
+                        WgpuPowerOptions::Adaptive => wgpu::PowerPreference::Default,
+                        WgpuPowerOptions::LowPower => wgpu::PowerPreference::LowPower,
+                    },
+                    compatible_surface: None,
+                })
+                .await
+                .expect("Unable to find a GPU! Make sure you have installed required drivers!"),
+        };
 
         #[cfg(feature = "trace")]
         let trace_path = Some(std::path::Path::new("wgpu_trace"));
         #[cfg(not(feature = "trace"))]
         let trace_path = None;
 
+        // ask for everything the adapter supports that bevy can use
+        let features = adapter.features() & wgpu::Features::TEXTURE_COMPRESSION_BC;
+        let limits = wgpu::Limits {
+            // push constants need a feature bevy does not request
+            max_push_constant_size: 0,
+            ..adapter.limits()
+        };
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
-                    features: wgpu::Features::empty(),
-                    limits: wgpu::Limits::default(),
+                    features,
+                    limits,
                     shader_validation: true,
                 },
                 trace_path,
             )
             .await
             .unwrap();
+        #[cfg(not(target_arch = "wasm32"))]
+        let adapter_name = {
+            let info = adapter.get_info();
+            let adapter_name = format!("{} ({:?})", info.name, info.backend);
+            info!("Rendering with {}", adapter_name);
+            Some(adapter_name)
+        };
+        #[cfg(target_arch = "wasm32")]
+        let adapter_name = None;
+        let limits = device.limits();
+        let capabilities = RenderCapabilities {
+            adapter_name,
+            max_bind_groups: limits.max_bind_groups,
+            max_sampled_textures_per_shader_stage: limits.max_sampled_textures_per_shader_stage,
+            max_uniform_buffer_binding_size: limits.max_uniform_buffer_binding_size,
+            texture_compression_bc: device
+                .features()
+                .contains(wgpu::Features::TEXTURE_COMPRESSION_BC),
+            // this version of wgpu does not report texture size limits, so they stay at the
+            // limits every device supports
+            ..Default::default()
+        };
         let device = Arc::new(device);
         WgpuRenderer {
             instance,
             device,
             queue,
+            capabilities,
             window_resized_event_reader: Default::default(),
             window_created_event_reader: Default::default(),
+            app_suspended_event_reader: Default::default(),
+            app_resumed_event_reader: Default::default(),
             initialized: false,
         }
     }
@@ -89,6 +210,45 @@ impl WgpuRenderer {
         }
     }
 
+    pub fn handle_app_lifecycle_events(&mut self, resources: &Resources) {
+        let suspended = {
+            let app_suspended_events = resources.get::<Events<AppSuspended>>().unwrap();
+            self.app_suspended_event_reader
+                .latest(&app_suspended_events)
+                .is_some()
+        };
+        let resumed = {
+            let app_resumed_events = resources.get::<Events<AppResumed>>().unwrap();
+            self.app_resumed_event_reader
+                .latest(&app_resumed_events)
+                .is_some()
+        };
+        if !suspended {
+            // the first resume (sent on startup by some platforms) does not need new surfaces
+            return;
+        }
+
+        let render_resource_context = resources.get::<Box<dyn RenderResourceContext>>().unwrap();
+        let render_resource_context = render_resource_context
+            .downcast_ref::<WgpuRenderResourceContext>()
+            .unwrap();
+        render_resource_context.remove_window_surfaces();
+        if !resumed {
+            return;
+        }
+
+        #[cfg(feature = "bevy_winit")]
+        {
+            let windows = resources.get::<Windows>().unwrap();
+            let winit_windows = resources.get::<bevy_winit::WinitWindows>().unwrap();
+            for window in windows.iter() {
+                let winit_window = winit_windows.get_window(window.id()).unwrap();
+                let surface = unsafe { self.instance.create_surface(winit_window.deref()) };
+                render_resource_context.set_window_surface(window.id(), surface);
+            }
+        }
+    }
+
     pub fn run_graph(&mut self, world: &mut World, resources: &mut Resources) {
         let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
         // stage nodes
@@ -110,11 +270,21 @@ impl WgpuRenderer {
     }
 
     pub fn update(&mut self, world: &mut World, resources: &mut Resources) {
+        self.handle_app_lifecycle_events(resources);
         self.handle_window_created_events(resources);
         self.run_graph(world, resources);
 
         let render_resource_context = resources.get::<Box<dyn RenderResourceContext>>().unwrap();
         render_resource_context.drop_all_swap_chain_textures();
         render_resource_context.remove_stale_bind_groups();
+        if let Some(render_resource_context) =
+            render_resource_context.downcast_ref::<WgpuRenderResourceContext>()
+        {
+            render_resource_context
+                .resources
+                .staging_belt
+                .lock()
+                .end_frame();
+        }
     }
 }
@@ -1,3 +1,4 @@
+use crate::{WgpuBuffer, WgpuBufferArena, WgpuStagingBelt};
 use bevy_asset::{Handle, HandleUntyped};
 use bevy_render::{
     pipeline::{BindGroupDescriptorId, PipelineDescriptor},
@@ -8,8 +9,22 @@ use bevy_render::{
 use bevy_utils::HashMap;
 use bevy_window::WindowId;
 use crossbeam_channel::{Receiver, Sender, TryRecvError};
-use parking_lot::{RwLock, RwLockReadGuard};
-use std::sync::Arc;
+use parking_lot::{Mutex, RwLock, RwLockReadGuard};
+use std::{future::Future, pin::Pin, sync::Arc};
+
+/// The future of a buffer being mapped with `map_async`, boxed so it can be stored and polled
+/// in later frames
+pub type WgpuMapFuture = Pin<Box<dyn Future<Output = Result<(), wgpu::BufferAsyncError>> + Send>>;
+
+/// Buffers that are being mapped for reading, without blocking on the GPU
+#[derive(Default)]
+pub struct WgpuPendingMaps(pub HashMap<BufferId, WgpuMapFuture>);
+
+impl std::fmt::Debug for WgpuPendingMaps {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.0.keys()).finish()
+    }
+}
 
 #[derive(Debug, Default)]
 pub struct WgpuBindGroupInfo {
@@ -40,7 +55,7 @@ pub struct WgpuBindGroupInfo {
 /// WgpuResources directly. RenderContext already has a lifetime greater than the RenderPass.
 #[derive(Debug)]
 pub struct WgpuResourcesReadLock<'a> {
-    pub buffers: RwLockReadGuard<'a, HashMap<BufferId, Arc<wgpu::Buffer>>>,
+    pub buffers: RwLockReadGuard<'a, HashMap<BufferId, WgpuBuffer>>,
     pub textures: RwLockReadGuard<'a, HashMap<TextureId, wgpu::TextureView>>,
     pub swap_chain_frames: RwLockReadGuard<'a, HashMap<TextureId, wgpu::SwapChainFrame>>,
     pub render_pipelines:
@@ -65,7 +80,7 @@ impl<'a> WgpuResourcesReadLock<'a> {
 /// Stores read only references to WgpuResource collections. See WgpuResourcesReadLock docs for context on why this exists
 #[derive(Debug)]
 pub struct WgpuResourceRefs<'a> {
-    pub buffers: &'a HashMap<BufferId, Arc<wgpu::Buffer>>,
+    pub buffers: &'a HashMap<BufferId, WgpuBuffer>,
     pub textures: &'a HashMap<TextureId, wgpu::TextureView>,
     pub swap_chain_frames: &'a HashMap<TextureId, wgpu::SwapChainFrame>,
     pub render_pipelines: &'a HashMap<Handle<PipelineDescriptor>, wgpu::RenderPipeline>,
@@ -80,7 +95,7 @@ pub struct WgpuResources {
     pub window_surfaces: Arc<RwLock<HashMap<WindowId, wgpu::Surface>>>,
     pub window_swap_chains: Arc<RwLock<HashMap<WindowId, wgpu::SwapChain>>>,
     pub swap_chain_frames: Arc<RwLock<HashMap<TextureId, wgpu::SwapChainFrame>>>,
-    pub buffers: Arc<RwLock<HashMap<BufferId, Arc<wgpu::Buffer>>>>,
+    pub buffers: Arc<RwLock<HashMap<BufferId, WgpuBuffer>>>,
     pub texture_views: Arc<RwLock<HashMap<TextureId, wgpu::TextureView>>>,
     pub textures: Arc<RwLock<HashMap<TextureId, wgpu::Texture>>>,
     pub samplers: Arc<RwLock<HashMap<SamplerId, wgpu::Sampler>>>,
@@ -89,10 +104,34 @@ pub struct WgpuResources {
     pub bind_groups: Arc<RwLock<HashMap<BindGroupDescriptorId, WgpuBindGroupInfo>>>,
     pub bind_group_layouts: Arc<RwLock<HashMap<BindGroupDescriptorId, wgpu::BindGroupLayout>>>,
     pub asset_resources: Arc<RwLock<HashMap<(HandleUntyped, u64), RenderResourceId>>>,
+    pub buffer_arena: Arc<RwLock<WgpuBufferArena>>,
+    pub staging_belt: Arc<Mutex<WgpuStagingBelt>>,
+    pub pending_maps: Arc<Mutex<WgpuPendingMaps>>,
     pub bind_group_counter: BindGroupCounter,
 }
 
 impl WgpuResources {
+    /// Drops every resource, the ones that use others first, and the window surfaces last
+    pub fn clear(&self) {
+        self.bind_groups.write().clear();
+        self.render_pipelines.write().clear();
+        self.bind_group_layouts.write().clear();
+        self.shader_modules.write().clear();
+        self.swap_chain_frames.write().clear();
+        self.texture_views.write().clear();
+        self.textures.write().clear();
+        self.texture_descriptors.write().clear();
+        self.samplers.write().clear();
+        self.buffers.write().clear();
+        self.buffer_infos.write().clear();
+        self.buffer_arena.write().clear();
+        self.staging_belt.lock().clear();
+        self.pending_maps.lock().0.clear();
+        self.asset_resources.write().clear();
+        self.window_swap_chains.write().clear();
+        self.window_surfaces.write().clear();
+    }
+
     pub fn read(&self) -> WgpuResourcesReadLock {
         WgpuResourcesReadLock {
             buffers: self.buffers.read(),
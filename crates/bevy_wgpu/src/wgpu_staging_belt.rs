@@ -0,0 +1,152 @@
+use crate::WgpuMapFuture;
+use futures_lite::future;
+use std::sync::Arc;
+
+#[derive(Debug)]
+struct StagingChunk {
+    buffer: Arc<wgpu::Buffer>,
+    size: u64,
+    offset: u64,
+}
+
+/// A ring of mapped staging buffers that uploads go through, so texture and buffer writes are
+/// copied straight into mapped memory instead of creating a buffer for each upload, or waiting for
+/// a buffer to be mapped.
+///
+/// Data is staged into mapped chunks and copied to its destination by the commands of the current
+/// frame. The chunks are unmapped before the commands are submitted, mapped again once the GPU is
+/// done with them, and then reused. Chunks that are still being mapped are never written to, so a
+/// frame never overwrites data an earlier frame is still copying.
+#[derive(Default)]
+pub struct WgpuStagingBelt {
+    /// Mapped chunks that data is being staged in
+    active: Vec<StagingChunk>,
+    /// Unmapped chunks used by the commands that are about to be submitted
+    closed: Vec<StagingChunk>,
+    /// Chunks waiting for the GPU to finish with them
+    mapping: Vec<(StagingChunk, WgpuMapFuture)>,
+    /// Mapped chunks ready to be reused
+    free: Vec<StagingChunk>,
+    uploaded_bytes: u64,
+    uploaded_bytes_last_frame: u64,
+}
+
+impl std::fmt::Debug for WgpuStagingBelt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WgpuStagingBelt")
+            .field("active", &self.active)
+            .field("closed", &self.closed)
+            .field("mapping", &self.mapping.len())
+            .field("free", &self.free)
+            .field("uploaded_bytes", &self.uploaded_bytes)
+            .field("uploaded_bytes_last_frame", &self.uploaded_bytes_last_frame)
+            .finish()
+    }
+}
+
+impl WgpuStagingBelt {
+    /// The size of a chunk. Larger uploads get a chunk of their own size.
+    pub const CHUNK_SIZE: u64 = 1024 * 1024;
+    /// The alignment of staged data, which suits copies to buffers and to textures of any format
+    pub const ALIGNMENT: u64 = 256;
+
+    /// Copies `data` into staging memory, and returns the staging buffer and the offset of the
+    /// data in it to copy from
+    pub fn stage(&mut self, device: &wgpu::Device, data: &[u8]) -> (Arc<wgpu::Buffer>, u64) {
+        let size = data.len() as u64;
+        let index = match self
+            .active
+            .iter()
+            .position(|chunk| Self::aligned(chunk.offset) + size <= chunk.size)
+        {
+            Some(index) => index,
+            None => {
+                let chunk = match self.free.iter().position(|chunk| chunk.size >= size) {
+                    Some(index) => self.free.swap_remove(index),
+                    None => StagingChunk {
+                        buffer: Arc::new(device.create_buffer(&wgpu::BufferDescriptor {
+                            label: Some("staging_belt_chunk"),
+                            size: Self::CHUNK_SIZE.max(Self::aligned(size)),
+                            usage: wgpu::BufferUsage::MAP_WRITE | wgpu::BufferUsage::COPY_SRC,
+                            mapped_at_creation: true,
+                        })),
+                        size: Self::CHUNK_SIZE.max(Self::aligned(size)),
+                        offset: 0,
+                    },
+                };
+                self.active.push(chunk);
+                self.active.len() - 1
+            }
+        };
+
+        let chunk = &mut self.active[index];
+        let offset = Self::aligned(chunk.offset);
+        chunk
+            .buffer
+            .slice(offset..offset + size)
+            .get_mapped_range_mut()
+            .copy_from_slice(data);
+        chunk.offset = offset + size;
+        self.uploaded_bytes += size;
+        (chunk.buffer.clone(), offset)
+    }
+
+    /// Unmaps the chunks data was staged in, before the commands that copy from them are submitted
+    pub fn finish(&mut self) {
+        for chunk in self.active.drain(..) {
+            chunk.buffer.unmap();
+            self.closed.push(chunk);
+        }
+    }
+
+    /// Maps the chunks again once the submitted commands are done with them, so they can be
+    /// reused. Call it after submitting the commands.
+    pub fn recall(&mut self, device: &wgpu::Device) {
+        for chunk in self.closed.drain(..) {
+            let map = chunk.buffer.slice(..).map_async(wgpu::MapMode::Write);
+            self.mapping.push((chunk, Box::pin(map)));
+        }
+        device.poll(wgpu::Maintain::Poll);
+
+        let mut index = 0;
+        while index < self.mapping.len() {
+            match future::block_on(future::poll_once(&mut self.mapping[index].1)) {
+                Some(result) => {
+                    let (mut chunk, _) = self.mapping.swap_remove(index);
+                    // a chunk that failed to map is dropped, and a new one is created if needed
+                    if result.is_ok() {
+                        chunk.offset = 0;
+                        self.free.push(chunk);
+                    }
+                }
+                None => index += 1,
+            }
+        }
+    }
+
+    /// Starts counting the bytes uploaded in the next frame
+    pub fn end_frame(&mut self) {
+        self.uploaded_bytes_last_frame = std::mem::take(&mut self.uploaded_bytes);
+    }
+
+    /// The number of bytes uploaded through the belt in the last frame
+    pub fn uploaded_bytes_last_frame(&self) -> u64 {
+        self.uploaded_bytes_last_frame
+    }
+
+    /// The number of staging chunks, in use or not
+    pub fn chunks(&self) -> usize {
+        self.active.len() + self.closed.len() + self.mapping.len() + self.free.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.active.clear();
+        self.closed.clear();
+        self.mapping.clear();
+        self.free.clear();
+    }
+
+    fn aligned(offset: u64) -> u64 {
+        (offset + Self::ALIGNMENT - 1) & !(Self::ALIGNMENT - 1)
+    }
+}
@@ -0,0 +1,313 @@
+use bevy_app::Events;
+use bevy_ecs::ResMut;
+use bevy_utils::tracing::warn;
+use std::{
+    io,
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Arc, Mutex,
+    },
+};
+use thiserror::Error;
+
+/// An error from a [ClipboardBackend]
+#[derive(Error, Debug)]
+pub enum ClipboardError {
+    #[error("the clipboard is not available on this platform")]
+    Unavailable,
+    #[error("the clipboard does not hold text")]
+    NotText,
+    #[error("failed to run the clipboard command `{command}`")]
+    Command {
+        command: &'static str,
+        #[source]
+        source: io::Error,
+    },
+}
+
+/// Where a [Clipboard] reads and writes its text. Backends may block, as the [Clipboard] runs
+/// them on a thread of its own.
+pub trait ClipboardBackend: Send + Sync + 'static {
+    fn get_text(&mut self) -> Result<String, ClipboardError>;
+    fn set_text(&mut self, text: &str) -> Result<(), ClipboardError>;
+}
+
+/// The text of the clipboard, sent after [Clipboard::request_text] once it has been read
+#[derive(Debug)]
+pub struct ClipboardText {
+    pub text: Result<String, ClipboardError>,
+}
+
+enum ClipboardRequest {
+    GetText,
+    SetText(String),
+}
+
+/// The clipboard of the platform, so text can be copied out of the app and pasted into it, like
+/// level data or world seeds.
+///
+/// Reading and writing the clipboard of the platform can be slow, so the [ClipboardBackend] runs
+/// on a thread of its own, which handles the requests in order. Text that is read arrives as a
+/// [ClipboardText] event.
+///
+/// By default it uses a [SystemClipboard] on desktop platforms and a [MemoryClipboard], which is
+/// only shared within the app, everywhere else.
+pub struct Clipboard {
+    backend: Arc<Mutex<Box<dyn ClipboardBackend>>>,
+    /// Sends requests to the thread of the backend, which starts with the first request
+    requests: Mutex<Option<Sender<ClipboardRequest>>>,
+    text_sender: Mutex<Sender<Result<String, ClipboardError>>>,
+    texts: Mutex<Receiver<Result<String, ClipboardError>>>,
+}
+
+impl Clipboard {
+    pub fn new(backend: impl ClipboardBackend) -> Self {
+        let (text_sender, texts) = channel();
+        Clipboard {
+            backend: Arc::new(Mutex::new(Box::new(backend))),
+            requests: Mutex::new(None),
+            text_sender: Mutex::new(text_sender),
+            texts: Mutex::new(texts),
+        }
+    }
+
+    /// Reads the text of the clipboard, which is sent as a [ClipboardText] event
+    pub fn request_text(&mut self) {
+        self.request(ClipboardRequest::GetText);
+    }
+
+    /// Copies `text` to the clipboard. Failures are logged.
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.request(ClipboardRequest::SetText(text.into()));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn request(&mut self, request: ClipboardRequest) {
+        let backend = &self.backend;
+        let text_sender = self.text_sender.get_mut().unwrap();
+        let requests = self.requests.get_mut().unwrap();
+        let requests = requests.get_or_insert_with(|| {
+            let (requests, receiver) = channel();
+            let backend = backend.clone();
+            let text_sender = text_sender.clone();
+            std::thread::Builder::new()
+                .name("clipboard".to_string())
+                .spawn(move || {
+                    for request in receiver {
+                        handle_request(&mut **backend.lock().unwrap(), request, &text_sender);
+                    }
+                })
+                .expect("Failed to spawn the clipboard thread.");
+            requests
+        });
+        // the thread only stops when the clipboard is dropped
+        requests.send(request).unwrap();
+    }
+
+    /// There are no threads to run the backend on, so it runs right away
+    #[cfg(target_arch = "wasm32")]
+    fn request(&mut self, request: ClipboardRequest) {
+        let text_sender = self.text_sender.get_mut().unwrap();
+        handle_request(&mut **self.backend.lock().unwrap(), request, text_sender);
+    }
+}
+
+fn handle_request(
+    backend: &mut dyn ClipboardBackend,
+    request: ClipboardRequest,
+    text_sender: &Sender<Result<String, ClipboardError>>,
+) {
+    match request {
+        ClipboardRequest::GetText => {
+            // the receiver is only dropped with the clipboard
+            let _ = text_sender.send(backend.get_text());
+        }
+        ClipboardRequest::SetText(text) => {
+            if let Err(err) = backend.set_text(&text) {
+                warn!("Failed to copy text to the clipboard: {}", err);
+            }
+        }
+    }
+}
+
+/// Sends the text read from the [Clipboard] as [ClipboardText] events
+pub fn clipboard_system(
+    mut clipboard: ResMut<Clipboard>,
+    mut clipboard_text_events: ResMut<Events<ClipboardText>>,
+) {
+    for text in clipboard.texts.get_mut().unwrap().try_iter() {
+        clipboard_text_events.send(ClipboardText { text });
+    }
+}
+
+impl Default for Clipboard {
+    #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+    fn default() -> Self {
+        Clipboard::new(SystemClipboard)
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    fn default() -> Self {
+        Clipboard::new(MemoryClipboard::default())
+    }
+}
+
+impl std::fmt::Debug for Clipboard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Clipboard").finish()
+    }
+}
+
+/// A clipboard that is only shared within the app
+#[derive(Debug, Default, Clone)]
+pub struct MemoryClipboard {
+    text: Option<String>,
+}
+
+impl ClipboardBackend for MemoryClipboard {
+    fn get_text(&mut self) -> Result<String, ClipboardError> {
+        self.text.clone().ok_or(ClipboardError::NotText)
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<(), ClipboardError> {
+        self.text = Some(text.to_string());
+        Ok(())
+    }
+}
+
+/// The clipboard of the operating system, through the command line tools of the platform:
+/// `pbcopy` and `pbpaste` on macOS, PowerShell and `clip` on Windows, and `wl-copy` and `wl-paste`
+/// or `xclip` on Linux, depending on whether the session runs Wayland.
+#[derive(Debug, Default, Clone)]
+pub struct SystemClipboard;
+
+impl SystemClipboard {
+    #[cfg(target_os = "macos")]
+    fn commands() -> (&'static [&'static str], &'static [&'static str]) {
+        (&["pbpaste"], &["pbcopy"])
+    }
+
+    #[cfg(target_os = "windows")]
+    fn commands() -> (&'static [&'static str], &'static [&'static str]) {
+        (
+            &[
+                "powershell",
+                "-NoProfile",
+                "-Command",
+                "[Console]::OutputEncoding = [Text.Encoding]::UTF8; Get-Clipboard -Raw",
+            ],
+            &["clip"],
+        )
+    }
+
+    #[cfg(target_os = "linux")]
+    fn commands() -> (&'static [&'static str], &'static [&'static str]) {
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            (&["wl-paste", "--no-newline"], &["wl-copy"])
+        } else {
+            (
+                &["xclip", "-selection", "clipboard", "-out"],
+                &["xclip", "-selection", "clipboard", "-in"],
+            )
+        }
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    fn commands() -> (&'static [&'static str], &'static [&'static str]) {
+        (&[], &[])
+    }
+}
+
+impl ClipboardBackend for SystemClipboard {
+    fn get_text(&mut self) -> Result<String, ClipboardError> {
+        let command = match SystemClipboard::commands().0 {
+            [] => return Err(ClipboardError::Unavailable),
+            command => command,
+        };
+        let error = |source| ClipboardError::Command {
+            command: command[0],
+            source,
+        };
+        let output = std::process::Command::new(command[0])
+            .args(&command[1..])
+            .stderr(std::process::Stdio::null())
+            .output()
+            .map_err(error)?;
+        if !output.status.success() {
+            // the tools fail when the clipboard is empty or holds something other than text
+            return Err(ClipboardError::NotText);
+        }
+        let mut text = String::from_utf8(output.stdout).map_err(|_| ClipboardError::NotText)?;
+        // PowerShell ends its output with a line break
+        if cfg!(target_os = "windows") && text.ends_with("\r\n") {
+            text.truncate(text.len() - 2);
+        }
+        Ok(text)
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<(), ClipboardError> {
+        use std::io::Write;
+        let command = match SystemClipboard::commands().1 {
+            [] => return Err(ClipboardError::Unavailable),
+            command => command,
+        };
+        let error = |source| ClipboardError::Command {
+            command: command[0],
+            source,
+        };
+        let mut child = std::process::Command::new(command[0])
+            .args(&command[1..])
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .map_err(error)?;
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(&clipboard_input(text))
+            .map_err(error)?;
+        // the Linux tools fork to keep serving the clipboard, and return once they have the text
+        let status = child.wait().map_err(error)?;
+        if !status.success() {
+            return Err(ClipboardError::Unavailable);
+        }
+        Ok(())
+    }
+}
+
+/// The text to write to the input of the copy command. `clip` reads text in the code page of the
+/// console unless it starts with a UTF-16 byte order mark, so it gets UTF-16.
+fn clipboard_input(text: &str) -> Vec<u8> {
+    if cfg!(target_os = "windows") {
+        let mut bytes = vec![0xff, 0xfe];
+        for unit in text.encode_utf16() {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+        bytes
+    } else {
+        text.as_bytes().to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_clipboard() {
+        let mut clipboard = Clipboard::new(MemoryClipboard::default());
+        clipboard.request_text();
+        clipboard.set_text("seed: 1234 ✓");
+        clipboard.request_text();
+
+        // requests are handled in order on the thread of the backend
+        let texts = clipboard.texts.get_mut().unwrap();
+        assert!(matches!(
+            texts.recv().unwrap(),
+            Err(ClipboardError::NotText)
+        ));
+        assert_eq!(texts.recv().unwrap().unwrap(), "seed: 1234 ✓");
+    }
+}
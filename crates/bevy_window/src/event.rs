@@ -28,6 +28,15 @@ pub struct WindowCreated {
     pub id: WindowId,
 }
 
+/// An event that is sent when the app is suspended by the operating system, for example when it is
+/// moved to the background on Android or iOS. Window surfaces are invalid until the app is resumed.
+#[derive(Debug, Clone)]
+pub struct AppSuspended;
+
+/// An event that is sent when the app is resumed after being suspended.
+#[derive(Debug, Clone)]
+pub struct AppResumed;
+
 /// An event that is sent whenever a close was requested for a window. For example: when the "close" button
 /// is pressed on a window.
 #[derive(Debug, Clone)]
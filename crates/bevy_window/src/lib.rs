@@ -1,9 +1,13 @@
+mod clipboard;
+mod cursor;
 mod event;
 mod system;
 mod window;
 mod windows;
 
 use bevy_ecs::IntoSystem;
+pub use clipboard::*;
+pub use cursor::*;
 pub use event::*;
 pub use system::*;
 pub use window::*;
@@ -11,8 +15,8 @@ pub use windows::*;
 
 pub mod prelude {
     pub use crate::{
-        CursorEntered, CursorLeft, CursorMoved, ReceivedCharacter, Window, WindowDescriptor,
-        Windows,
+        Clipboard, ClipboardText, CursorEntered, CursorIcon, CursorLeft, CursorMoved,
+        ReceivedCharacter, Window, WindowDescriptor, Windows,
     };
 }
 
@@ -44,7 +48,12 @@ impl Plugin for WindowPlugin {
             .add_event::<CursorLeft>()
             .add_event::<ReceivedCharacter>()
             .add_event::<WindowFocused>()
-            .init_resource::<Windows>();
+            .add_event::<AppSuspended>()
+            .add_event::<AppResumed>()
+            .add_event::<ClipboardText>()
+            .init_resource::<Windows>()
+            .init_resource::<Clipboard>()
+            .add_system_to_stage(stage::PRE_UPDATE, clipboard_system.system());
 
         if self.add_primary_window {
             let resources = app.resources();
@@ -1,3 +1,4 @@
+use crate::CursorIcon;
 use bevy_math::Vec2;
 use bevy_utils::Uuid;
 
@@ -61,6 +62,7 @@ pub struct Window {
     decorations: bool,
     cursor_visible: bool,
     cursor_locked: bool,
+    cursor_icon: CursorIcon,
     cursor_position: Option<Vec2>,
     mode: WindowMode,
     #[cfg(target_arch = "wasm32")]
@@ -95,6 +97,9 @@ pub enum WindowCommand {
     SetCursorVisibility {
         visible: bool,
     },
+    SetCursorIcon {
+        icon: CursorIcon,
+    },
     SetCursorPosition {
         position: Vec2,
     },
@@ -136,6 +141,7 @@ impl Window {
             decorations: window_descriptor.decorations,
             cursor_visible: window_descriptor.cursor_visible,
             cursor_locked: window_descriptor.cursor_locked,
+            cursor_icon: CursorIcon::Default,
             cursor_position: None,
             mode: window_descriptor.mode,
             #[cfg(target_arch = "wasm32")]
@@ -296,6 +302,17 @@ impl Window {
         });
     }
 
+    #[inline]
+    pub fn cursor_icon(&self) -> CursorIcon {
+        self.cursor_icon
+    }
+
+    pub fn set_cursor_icon(&mut self, icon: CursorIcon) {
+        self.cursor_icon = icon;
+        self.command_queue
+            .push(WindowCommand::SetCursorIcon { icon });
+    }
+
     #[inline]
     pub fn cursor_position(&self) -> Option<Vec2> {
         self.cursor_position
@@ -1,4 +1,5 @@
 mod converters;
+mod panic_handler;
 mod winit_config;
 mod winit_windows;
 use bevy_input::{
@@ -6,6 +7,7 @@ use bevy_input::{
     mouse::{MouseButtonInput, MouseMotion, MouseScrollUnit, MouseWheel},
     touch::TouchInput,
 };
+pub use panic_handler::*;
 pub use winit_config::*;
 pub use winit_windows::*;
 
@@ -14,9 +16,10 @@ use bevy_ecs::{IntoSystem, Resources, World};
 use bevy_math::Vec2;
 use bevy_utils::tracing::{error, trace};
 use bevy_window::{
-    CreateWindow, CursorEntered, CursorLeft, CursorMoved, ReceivedCharacter, WindowCloseRequested,
-    WindowCreated, WindowFocused, WindowResized, Windows,
+    AppResumed, AppSuspended, CreateWindow, CursorEntered, CursorLeft, CursorMoved,
+    ReceivedCharacter, WindowCloseRequested, WindowCreated, WindowFocused, WindowResized, Windows,
 };
+use std::panic::AssertUnwindSafe;
 use winit::{
     event::{self, DeviceEvent, Event, WindowEvent},
     event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
@@ -95,6 +98,10 @@ fn change_window(_: &mut World, resources: &mut Resources) {
                     let window = winit_windows.get_window(id).unwrap();
                     window.set_cursor_visible(visible);
                 }
+                bevy_window::WindowCommand::SetCursorIcon { icon } => {
+                    let window = winit_windows.get_window(id).unwrap();
+                    window.set_cursor_icon(converters::convert_cursor_icon(icon));
+                }
                 bevy_window::WindowCommand::SetCursorPosition { position } => {
                     let window = winit_windows.get_window(id).unwrap();
                     let inner_size = window.inner_size().to_logical::<f32>(window.scale_factor());
@@ -166,6 +173,12 @@ pub fn winit_runner(mut app: App) {
 
     trace!("Entering winit event loop");
 
+    // the app is not updated while suspended, because window surfaces may not exist
+    let mut active = true;
+    // with the panic handler, a panicking update stops the updates but keeps the windows open
+    let panic_handler = app.resources.get_cloned::<PanicHandler>();
+    let mut panicked = false;
+
     let should_return_from_run = app
         .resources
         .get::<WinitConfig>()
@@ -174,6 +187,20 @@ pub fn winit_runner(mut app: App) {
     let event_handler = move |event: Event<()>,
                               event_loop: &EventLoopWindowTarget<()>,
                               control_flow: &mut ControlFlow| {
+        if panicked {
+            *control_flow = ControlFlow::Wait;
+            match event {
+                event::Event::WindowEvent {
+                    event: WindowEvent::CloseRequested,
+                    ..
+                } => *control_flow = ControlFlow::Exit,
+                event::Event::LoopDestroyed if !should_return_from_run => {
+                    std::process::exit(PANIC_EXIT_CODE)
+                }
+                _ => (),
+            }
+            return;
+        }
         *control_flow = ControlFlow::Poll;
 
         if let Some(app_exit_events) = app.resources.get_mut::<Events<AppExit>>() {
@@ -329,8 +356,15 @@ pub fn winit_runner(mut app: App) {
                         new_inner_size.height,
                     );
                     window.update_scale_factor_from_backend(scale_factor);
-                    // should we send a resize event to indicate the change in
-                    // logical size?
+                    // the physical size changed, so the swap chain and the window textures have
+                    // to be recreated
+                    let mut resize_events =
+                        app.resources.get_mut::<Events<WindowResized>>().unwrap();
+                    resize_events.send(WindowResized {
+                        id: window_id,
+                        width: window.width(),
+                        height: window.height(),
+                    });
                 }
                 WindowEvent::Focused(focused) => {
                     let mut focused_events =
@@ -362,13 +396,46 @@ pub fn winit_runner(mut app: App) {
                     delta: Vec2::new(delta.0 as f32, delta.1 as f32),
                 });
             }
+            event::Event::Suspended => {
+                active = false;
+                let mut app_suspended_events =
+                    app.resources.get_mut::<Events<AppSuspended>>().unwrap();
+                app_suspended_events.send(AppSuspended);
+            }
+            event::Event::Resumed => {
+                active = true;
+                let mut app_resumed_events = app.resources.get_mut::<Events<AppResumed>>().unwrap();
+                app_resumed_events.send(AppResumed);
+            }
             event::Event::MainEventsCleared => {
                 handle_create_window_events(
                     &mut app.resources,
                     event_loop,
                     &mut create_window_event_reader,
                 );
-                app.update();
+                if active {
+                    match &panic_handler {
+                        Some(panic_handler) => {
+                            let update =
+                                std::panic::catch_unwind(AssertUnwindSafe(|| app.update()));
+                            if update.is_ok() {
+                                panic_handler.clear();
+                            } else {
+                                panicked = true;
+                                panic_handler.show(&app.resources);
+                            }
+                        }
+                        None => app.update(),
+                    }
+                }
+            }
+            event::Event::LoopDestroyed => {
+                app.shutdown();
+                // the event loop exits the process once this returns, unless it returns from run
+                let exit_code = app.exit_code();
+                if exit_code != 0 && !should_return_from_run {
+                    std::process::exit(exit_code);
+                }
             }
             _ => (),
         }
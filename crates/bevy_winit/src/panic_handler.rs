@@ -0,0 +1,136 @@
+use crate::WinitWindows;
+use bevy_app::prelude::*;
+use bevy_ecs::Resources;
+use bevy_utils::tracing::error;
+use std::{
+    backtrace::Backtrace,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+/// The exit code of an app that panicked, the same as the one of a Rust program that panics
+pub const PANIC_EXIT_CODE: i32 = 101;
+
+/// Shows panics of the app in a message box and in the title of its windows, instead of closing
+/// the windows or leaving them frozen with the message only in the terminal.
+///
+/// Once a system panics, the app stops updating and its windows stay open until one of them is
+/// closed, then the process exits with [PANIC_EXIT_CODE]. The message box shows the panic with its
+/// backtrace, and is drawn by the platform, as the app can't render once it panicked. On Linux it
+/// uses a dialog tool like `zenity`, `kdialog` or `xmessage` when one is installed. The panic is
+/// still logged too.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PanicHandlerPlugin;
+
+impl Plugin for PanicHandlerPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let panic_handler = PanicHandler::default();
+        let reports = panic_handler.reports.clone();
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let message = if let Some(message) = info.payload().downcast_ref::<&str>() {
+                message.to_string()
+            } else if let Some(message) = info.payload().downcast_ref::<String>() {
+                message.clone()
+            } else {
+                "Box<Any>".to_string()
+            };
+            let report = PanicReport {
+                message,
+                location: info.location().map(|location| location.to_string()),
+                thread: std::thread::current().name().map(str::to_string),
+                backtrace: Backtrace::force_capture().to_string(),
+            };
+            reports.lock().unwrap().push(report);
+            default_hook(info);
+        }));
+        app.add_resource(panic_handler);
+    }
+}
+
+/// A panic caught by the [PanicHandlerPlugin]
+#[derive(Debug, Clone)]
+pub struct PanicReport {
+    pub message: String,
+    /// The file, line and column the panic happened at
+    pub location: Option<String>,
+    /// The name of the thread that panicked
+    pub thread: Option<String>,
+    pub backtrace: String,
+}
+
+impl fmt::Display for PanicReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "thread '{}' panicked at '{}'",
+            self.thread.as_deref().unwrap_or("<unnamed>"),
+            self.message
+        )?;
+        if let Some(location) = &self.location {
+            write!(f, ", {}", location)?;
+        }
+        Ok(())
+    }
+}
+
+/// The panics recorded by the [PanicHandlerPlugin] during the current update. A panic in a task
+/// also panics the thread that waits for the task, so a caught panic may come with a few reports,
+/// the first one being the cause.
+#[derive(Debug, Default, Clone)]
+pub struct PanicHandler {
+    reports: Arc<Mutex<Vec<PanicReport>>>,
+}
+
+impl PanicHandler {
+    /// Forgets the panics of tasks that did not stop the app
+    pub(crate) fn clear(&self) {
+        self.reports.lock().unwrap().clear();
+    }
+
+    /// Shows the panics of the update that panicked in the titles of the windows and in a
+    /// message box, and logs them with their backtraces
+    pub(crate) fn show(&self, resources: &Resources) {
+        let reports = std::mem::take(&mut *self.reports.lock().unwrap());
+        let title = match reports.first() {
+            Some(report) => format!("{} - close the window to exit", report),
+            None => "The app panicked - close the window to exit".to_string(),
+        };
+        if let Some(winit_windows) = resources.get::<WinitWindows>() {
+            for window in winit_windows.windows.values() {
+                window.set_title(&title);
+            }
+        }
+        for report in reports.iter() {
+            error!("{}\n{}", report, report.backtrace);
+        }
+
+        let mut text = reports
+            .iter()
+            .map(|report| format!("{}\n\n{}", report, report.backtrace))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        const MAX_TEXT_LEN: usize = 4000;
+        if text.len() > MAX_TEXT_LEN {
+            let mut end = MAX_TEXT_LEN;
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+            text.truncate(end);
+            text.push_str("\n...");
+        }
+        // the message box blocks, so it runs on its own thread to keep the windows responsive
+        #[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+        std::thread::spawn(move || show_message_box("The app panicked", &text));
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+fn show_message_box(title: &str, text: &str) {
+    // the dialogs refuse text with quotes, and C strings can't hold nul characters
+    let text = text
+        .replace('\'', "\u{2019}")
+        .replace('"', "\u{201d}")
+        .replace('\0', "");
+    tinyfiledialogs::message_box_ok(title, &text, tinyfiledialogs::MessageBoxIcon::Error);
+}
@@ -0,0 +1,48 @@
+use bevy::{prelude::*, render::pipeline::BlendMode};
+
+/// Draws the same shapes with each blend mode over a striped background
+fn main() {
+    App::build()
+        .add_plugins(DefaultPlugins)
+        .add_startup_system(setup.system())
+        .run();
+}
+
+fn setup(
+    commands: &mut Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    let stripe = meshes.add(shape::Quad::new(Vec2::new(1000.0, 60.0)).into());
+    for (i, color) in [Color::rgb(0.8, 0.2, 0.2), Color::rgb(0.2, 0.6, 0.3)]
+        .iter()
+        .cycle()
+        .take(6)
+        .enumerate()
+    {
+        commands.spawn(SpriteBundle {
+            transform: Transform::from_translation(Vec3::new(0.0, 150.0 - i as f32 * 60.0, 0.0)),
+            ..SpriteBundle::from_mesh(stripe.clone(), materials.add((*color).into()))
+        });
+    }
+
+    let circle = meshes.add(shape::Circle::new(80.0).into());
+    let color = Color::rgba(0.3, 0.5, 1.0, 0.6);
+    let blend_modes = [
+        BlendMode::Opaque,
+        BlendMode::AlphaBlend,
+        BlendMode::Additive,
+        BlendMode::Multiply,
+    ];
+    for (i, blend_mode) in blend_modes.iter().enumerate() {
+        commands.spawn(SpriteBundle {
+            transform: Transform::from_translation(Vec3::new(-300.0 + i as f32 * 200.0, 0.0, 1.0)),
+            ..SpriteBundle::from_mesh(
+                circle.clone(),
+                materials.add(ColorMaterial::color(color).with_blend_mode(*blend_mode)),
+            )
+        });
+    }
+}
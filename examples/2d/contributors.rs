@@ -77,8 +77,8 @@ fn setup(
         let mut transform = Transform::from_translation(Vec3::new(pos.0, pos.1, 0.0));
         transform.scale.x *= if flipped { -1.0 } else { 1.0 };
 
-        commands
-            .spawn((Contributor { color: col },))
+        let e = commands
+            .spawn_entity((Contributor { color: col },))
             .with(Velocity {
                 translation: velocity,
                 rotation: -dir * 5.0,
@@ -91,12 +91,12 @@ fn setup(
                 material: materials.add(ColorMaterial {
                     color: COL_DESELECTED * col,
                     texture: Some(texture_handle.clone()),
+                    ..Default::default()
                 }),
                 ..Default::default()
             })
-            .with(transform);
-
-        let e = commands.current_entity().unwrap();
+            .with(transform)
+            .id();
 
         sel.order.push((name, e));
     }
@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+
+/// Draws 2d shapes with color materials, without any textures
+fn main() {
+    App::build()
+        .add_plugins(DefaultPlugins)
+        .add_startup_system(setup.system())
+        .run();
+}
+
+fn setup(
+    commands: &mut Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    commands
+        .spawn(Camera2dBundle::default())
+        .spawn(SpriteBundle {
+            transform: Transform::from_translation(Vec3::new(-250.0, 0.0, 0.0)),
+            ..SpriteBundle::from_mesh(
+                meshes.add(shape::RoundedRect::new(Vec2::new(160.0, 100.0), 20.0).into()),
+                materials.add(Color::rgb(0.3, 0.5, 0.9).into()),
+            )
+        })
+        .spawn(SpriteBundle::from_mesh(
+            meshes.add(shape::Capsule::new(40.0, 80.0).into()),
+            materials.add(Color::rgb(0.3, 0.8, 0.4).into()),
+        ))
+        .spawn(SpriteBundle {
+            transform: Transform::from_translation(Vec3::new(250.0, 0.0, 0.0)),
+            ..SpriteBundle::from_mesh(
+                meshes.add(shape::Circle::new(60.0).into()),
+                materials.add(Color::rgb(0.9, 0.6, 0.2).into()),
+            )
+        })
+        // vertex colors blend across the shape, tinting the color of the material
+        .spawn(SpriteBundle {
+            transform: Transform::from_translation(Vec3::new(0.0, -200.0, 0.0)),
+            ..SpriteBundle::from_mesh(
+                meshes.add(gradient_quad()),
+                materials.add(Color::WHITE.into()),
+            )
+        })
+        // a small dot, like a marker for checking alignment
+        .spawn(SpriteBundle {
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 1.0)),
+            ..SpriteBundle::from_mesh(
+                meshes.add(shape::Circle::new(4.0).into()),
+                materials.add(Color::RED.into()),
+            )
+        });
+}
+
+fn gradient_quad() -> Mesh {
+    let mut mesh: Mesh = shape::Quad::new(Vec2::new(400.0, 80.0)).into();
+    mesh.set_attribute(
+        Mesh::ATTRIBUTE_COLOR,
+        vec![
+            [1.0, 0.2, 0.2, 1.0],
+            [1.0, 0.2, 0.2, 1.0],
+            [0.2, 0.2, 1.0, 1.0],
+            [0.2, 0.2, 1.0, 1.0],
+        ],
+    );
+    mesh
+}
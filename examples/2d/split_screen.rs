@@ -0,0 +1,70 @@
+use bevy::{
+    prelude::*,
+    render::{
+        camera::{ActiveCameras, Camera, Viewport},
+        render_graph::{
+            base::{camera::CAMERA_2D, BaseRenderGraphBuilder},
+            RenderGraph,
+        },
+    },
+};
+
+/// This example draws the same scene from two 2d cameras side by side in one window.
+fn main() {
+    App::build()
+        .add_plugins(DefaultPlugins)
+        .add_startup_system(setup.system())
+        .add_system(move_sprite.system())
+        .run();
+}
+
+const RIGHT_CAMERA: &str = "RightCamera";
+
+struct Moving;
+
+fn setup(
+    commands: &mut Commands,
+    asset_server: Res<AssetServer>,
+    mut render_graph: ResMut<RenderGraph>,
+    mut active_cameras: ResMut<ActiveCameras>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    // the second camera draws in the main pass, after the default 2d camera
+    render_graph.add_main_pass_camera(RIGHT_CAMERA);
+    active_cameras.add(RIGHT_CAMERA);
+
+    let texture_handle = asset_server.load("branding/icon.png");
+    commands
+        // the left camera uses the default 2d camera name. its clear color clears the whole
+        // window before both cameras draw
+        .spawn(Camera2dBundle {
+            camera: Camera {
+                name: Some(CAMERA_2D.to_string()),
+                viewport: Some(Viewport::new(0.0, 0.0, 0.5, 1.0)),
+                clear_color: Some(Color::rgb(0.1, 0.1, 0.2)),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        // the right camera looks at the scene from further right
+        .spawn(Camera2dBundle {
+            camera: Camera {
+                name: Some(RIGHT_CAMERA.to_string()),
+                viewport: Some(Viewport::new(0.5, 0.0, 0.5, 1.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(Vec3::new(200.0, 0.0, 999.9)),
+            ..Default::default()
+        })
+        .spawn(SpriteBundle {
+            material: materials.add(texture_handle.into()),
+            ..Default::default()
+        })
+        .with(Moving);
+}
+
+fn move_sprite(time: Res<Time>, mut query: Query<&mut Transform, With<Moving>>) {
+    for mut transform in query.iter_mut() {
+        transform.translation.x = time.seconds_since_startup().sin() as f32 * 200.0;
+    }
+}
@@ -7,7 +7,7 @@ fn main() {
         .add_plugins(DefaultPlugins)
         // Adding a plugin group adds all plugins in the group by default
         .add_plugins(HelloWorldPlugins)
-        // You can also modify a PluginGroup (such as disabling plugins) like this:
+        // You can also modify a PluginGroup (such as disabling or replacing plugins) like this:
         // .add_plugins_with(HelloWorldPlugins, |group| {
         //     group
         //         .disable::<PrintWorldPlugin>()
@@ -5,6 +5,15 @@ use bevy::prelude::*;
 fn main() {
     App::build()
         .add_resource(DefaultTaskPoolOptions::with_num_threads(4))
+        // Threads can also be pinned to their own cores, so the pools don't compete for them.
+        // Pinning only helps when nothing else needs those cores, so it is off by default:
+        // .add_resource(DefaultTaskPoolOptions {
+        //     pin_threads: true,
+        //     ..DefaultTaskPoolOptions::with_num_threads(4)
+        // })
+        // Alternatively, run everything on the main thread, which makes the order of systems and
+        // tasks deterministic:
+        // .add_resource(DefaultTaskPoolOptions::single_threaded())
         .add_plugins(DefaultPlugins)
         .run();
 }
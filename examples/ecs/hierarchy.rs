@@ -23,6 +23,7 @@ fn setup(
             material: materials.add(ColorMaterial {
                 color: Color::WHITE,
                 texture: Some(texture.clone()),
+                ..Default::default()
             }),
             ..Default::default()
         })
@@ -38,6 +39,7 @@ fn setup(
                 material: materials.add(ColorMaterial {
                     color: Color::BLUE,
                     texture: Some(texture.clone()),
+                    ..Default::default()
                 }),
                 ..Default::default()
             });
@@ -59,6 +61,7 @@ fn setup(
             material: materials.add(ColorMaterial {
                 color: Color::RED,
                 texture: Some(texture.clone()),
+                ..Default::default()
             }),
             ..Default::default()
         })
@@ -68,7 +71,7 @@ fn setup(
     // Another way is to use the push_children function to add children after the parent
     // entity has already been spawned.
     let child = commands
-        .spawn(SpriteBundle {
+        .spawn_entity(SpriteBundle {
             transform: Transform {
                 translation: Vec3::new(0.0, 250.0, 0.0),
                 scale: Vec3::splat(0.75),
@@ -77,11 +80,11 @@ fn setup(
             material: materials.add(ColorMaterial {
                 color: Color::GREEN,
                 texture: Some(texture),
+                ..Default::default()
             }),
             ..Default::default()
         })
-        .current_entity()
-        .unwrap();
+        .id();
 
     // Pushing takes a slice of children to add:
     commands.push_children(parent, &[child]);
@@ -9,7 +9,12 @@ fn main() {
         .add_stage_after(stage::UPDATE, STAGE, StateStage::<AppState>::default())
         .on_state_enter(STAGE, AppState::Menu, setup_menu.system())
         .on_state_update(STAGE, AppState::Menu, menu.system())
-        .on_state_exit(STAGE, AppState::Menu, cleanup_menu.system())
+        // everything tagged with `MenuUi` is despawned, along with its children, when the menu closes
+        .on_state_exit(
+            STAGE,
+            AppState::Menu,
+            despawn_recursive_all_with::<MenuUi>.system(),
+        )
         .on_state_enter(STAGE, AppState::InGame, setup_game.system())
         .on_state_update(STAGE, AppState::InGame, movement.system())
         .on_state_update(STAGE, AppState::InGame, change_color.system())
@@ -24,9 +29,8 @@ enum AppState {
     InGame,
 }
 
-struct MenuData {
-    button_entity: Entity,
-}
+/// Marks the root entities of the menu
+struct MenuUi;
 
 fn setup_menu(
     commands: &mut Commands,
@@ -50,6 +54,7 @@ fn setup_menu(
             material: button_materials.normal.clone(),
             ..Default::default()
         })
+        .with(MenuUi)
         .with_children(|parent| {
             parent.spawn(TextBundle {
                 text: Text {
@@ -64,9 +69,6 @@ fn setup_menu(
                 ..Default::default()
             });
         });
-    commands.insert_resource(MenuData {
-        button_entity: commands.current_entity().unwrap(),
-    });
 }
 
 fn menu(
@@ -93,10 +95,6 @@ fn menu(
     }
 }
 
-fn cleanup_menu(commands: &mut Commands, menu_data: Res<MenuData>) {
-    commands.despawn_recursive(menu_data.button_entity);
-}
-
 fn setup_game(
     commands: &mut Commands,
     asset_server: Res<AssetServer>,
@@ -0,0 +1,72 @@
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::shape,
+        pipeline::{PipelineDescriptor, RenderPipeline},
+        shader::{ShaderStage, ShaderStages},
+    },
+};
+
+/// This example shows how to animate a shader with the time of the `Globals` uniform, which render
+/// passes bind next to the camera, so every pipeline can read it without any per-frame work on the
+/// CPU
+fn main() {
+    App::build()
+        .add_plugins(DefaultPlugins)
+        .add_startup_system(setup.system())
+        .run();
+}
+
+const VERTEX_SHADER: &str = r#"
+#version 450
+layout(location = 0) in vec3 Vertex_Position;
+layout(location = 0) out vec2 v_Position;
+layout(set = 0, binding = 0) uniform Camera {
+    mat4 ViewProj;
+};
+layout(set = 1, binding = 0) uniform Transform {
+    mat4 Model;
+};
+void main() {
+    v_Position = Vertex_Position.xy;
+    gl_Position = ViewProj * Model * vec4(Vertex_Position, 1.0);
+}
+"#;
+
+const FRAGMENT_SHADER: &str = r#"
+#version 450
+layout(location = 0) in vec2 v_Position;
+layout(location = 0) out vec4 o_Target;
+layout(set = 0, binding = 1) uniform Globals {
+    float Time;
+    float DeltaTime;
+    uint Frame;
+};
+void main() {
+    // slow waves of glowing lava
+    float wave = sin(v_Position.x * 0.05 + Time * 2.0) + sin(v_Position.y * 0.07 - Time * 1.3);
+    o_Target = mix(vec4(0.6, 0.05, 0.0, 1.0), vec4(1.0, 0.7, 0.1, 1.0), wave * 0.25 + 0.5);
+}
+"#;
+
+fn setup(
+    commands: &mut Commands,
+    mut pipelines: ResMut<Assets<PipelineDescriptor>>,
+    mut shaders: ResMut<Assets<Shader>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let pipeline_handle = pipelines.add(PipelineDescriptor::default_config(ShaderStages {
+        vertex: shaders.add(Shader::from_glsl(ShaderStage::Vertex, VERTEX_SHADER)),
+        fragment: Some(shaders.add(Shader::from_glsl(ShaderStage::Fragment, FRAGMENT_SHADER))),
+    }));
+
+    commands
+        .spawn(MeshBundle {
+            mesh: meshes.add(Mesh::from(shape::Quad::new(Vec2::new(600.0, 400.0)))),
+            render_pipelines: RenderPipelines::from_pipelines(vec![RenderPipeline::new(
+                pipeline_handle,
+            )]),
+            ..Default::default()
+        })
+        .spawn(Camera2dBundle::default());
+}
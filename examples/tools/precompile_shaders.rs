@@ -0,0 +1,114 @@
+use bevy::{
+    render::shader::{expand_shader_includes, shader_includes, Shader, ShaderCache, ShaderStage},
+    utils::HashMap,
+};
+use std::path::{Path, PathBuf};
+
+/// Compiles GLSL shaders to SPIR-V ahead of time, so slow machines don't have to compile them at
+/// startup. Every shader is compiled for each set of shader defs it is used with:
+///
+/// `cargo run --example precompile_shaders -- <cache directory> [--defs A,B]... <shader files>...`
+///
+/// Without `--defs`, shaders are compiled without defs. The defs of a pipeline are the ones its
+/// [ShaderDefs](bevy::render::shader::ShaderDefs) set, like `COLORMATERIAL_TEXTURE`. A game uses
+/// the cache by handing it to the pipeline compiler at startup:
+///
+/// ```ignore
+/// let shader_cache = ShaderCache::load("shader_cache").unwrap_or_default();
+/// app.resources_mut()
+///     .get_mut::<PipelineCompiler>()
+///     .unwrap()
+///     .set_shader_cache(shader_cache);
+/// ```
+///
+/// Shaders that are not in the cache, or whose source changed since it was built, are still
+/// compiled at runtime.
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let cache_directory = match args.next() {
+        Some(cache_directory) => PathBuf::from(cache_directory),
+        None => {
+            eprintln!(
+                "usage: precompile_shaders <cache directory> [--defs A,B]... <shader files>..."
+            );
+            std::process::exit(1);
+        }
+    };
+    let mut def_sets = Vec::new();
+    let mut shader_paths = Vec::new();
+    while let Some(arg) = args.next() {
+        if arg == "--defs" {
+            let defs = args.next().unwrap_or_default();
+            def_sets.push(
+                defs.split(',')
+                    .filter(|def| !def.is_empty())
+                    .map(|def| def.to_string())
+                    .collect::<Vec<_>>(),
+            );
+        } else {
+            shader_paths.push(PathBuf::from(arg));
+        }
+    }
+    if def_sets.is_empty() {
+        def_sets.push(Vec::new());
+    }
+
+    let mut shader_cache = ShaderCache::load(&cache_directory).unwrap_or_default();
+    let mut failed = false;
+    for path in shader_paths.iter() {
+        let shader = match load_shader(path) {
+            Ok(shader) => shader,
+            Err(error) => {
+                eprintln!("{}: {}", path.display(), error);
+                failed = true;
+                continue;
+            }
+        };
+        for defs in def_sets.iter() {
+            match shader_cache.compile(&shader, defs) {
+                Ok(()) => println!("compiled {} with defs {:?}", path.display(), defs),
+                Err(error) => {
+                    eprintln!("{} with defs {:?}: {}", path.display(), defs, error);
+                    failed = true;
+                }
+            }
+        }
+    }
+
+    if let Err(error) = shader_cache.save(&cache_directory) {
+        eprintln!("failed to save {}: {}", cache_directory.display(), error);
+        failed = true;
+    }
+    println!(
+        "{} shaders cached in {}",
+        shader_cache.len(),
+        cache_directory.display()
+    );
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+/// Reads a shader and the files it includes, like the shader loader does
+fn load_shader(path: &Path) -> Result<Shader, Box<dyn std::error::Error>> {
+    let stage = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("vert") => ShaderStage::Vertex,
+        Some("frag") => ShaderStage::Fragment,
+        _ => return Err("shaders need a .vert or .frag extension".into()),
+    };
+    let source = std::fs::read_to_string(path)?;
+    let mut includes = HashMap::default();
+    let mut pending_includes = shader_includes(&source, path);
+    while let Some(include_path) = pending_includes.pop() {
+        if includes.contains_key(&include_path) {
+            continue;
+        }
+        let include = std::fs::read_to_string(&include_path)?;
+        pending_includes.extend(shader_includes(&include, &include_path));
+        includes.insert(include_path, include);
+    }
+    Ok(Shader::from_glsl(
+        stage,
+        &expand_shader_includes(&source, path, &includes)?,
+    ))
+}
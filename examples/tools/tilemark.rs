@@ -0,0 +1,273 @@
+use bevy::{
+    app::AppExit,
+    prelude::*,
+    render::{
+        camera::Camera,
+        color::Srgba,
+        texture::{Extent3d, TextureDimension, TextureFormat},
+    },
+    tilemap::TilemapStats,
+};
+use std::{fs::OpenOptions, io::Write, path::PathBuf, time::Instant};
+
+/// A tilemap stress test. Spawns a grid of chunks, pans the camera across them along a fixed
+/// path and appends the results to a CSV file, so runs on different machines, or with different
+/// tilemap backends, can be compared.
+///
+/// Usage: `cargo run --release --example tilemark --features bevy_tilemap -- [options]`
+///
+/// * `--chunks N`: spawn N x N chunks (default 32)
+/// * `--chunk-size N`: each chunk has N x N tiles (default 32)
+/// * `--tile-size N`: each tile is N pixels square (default 8)
+/// * `--frames N`: pan the camera for N frames (default 1000)
+/// * `--output PATH`: the CSV file to append to (default `tilemark.csv`)
+fn main() {
+    let config = Config::from_args();
+    App::build()
+        .add_resource(WindowDescriptor {
+            title: "TileMark".to_string(),
+            width: 1280.,
+            height: 720.,
+            vsync: false,
+            resizable: false,
+            ..Default::default()
+        })
+        .add_resource(config)
+        .add_plugins(DefaultPlugins)
+        .add_plugin(TilemapPlugin)
+        .init_resource::<Metrics>()
+        .add_startup_system(setup.system())
+        .add_system(pan_camera.system())
+        .add_system_to_stage(stage::LAST, record_metrics.system())
+        .run();
+}
+
+/// The name of the tilemap backend in the results
+const BACKEND: &str = "cpu_bake";
+const TILE_KINDS: u16 = 8;
+
+#[derive(Debug, Clone)]
+struct Config {
+    chunks: u32,
+    chunk_size: u32,
+    tile_size: u32,
+    frames: usize,
+    output: PathBuf,
+}
+
+impl Config {
+    fn from_args() -> Self {
+        let mut config = Config {
+            chunks: 32,
+            chunk_size: 32,
+            tile_size: 8,
+            frames: 1000,
+            output: PathBuf::from("tilemark.csv"),
+        };
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .unwrap_or_else(|| panic!("missing value for {}", arg));
+            let number = || {
+                value
+                    .parse::<u32>()
+                    .unwrap_or_else(|_| panic!("{} expects a number, got {}", arg, value))
+            };
+            match arg.as_str() {
+                "--chunks" => config.chunks = number(),
+                "--chunk-size" => config.chunk_size = number(),
+                "--tile-size" => config.tile_size = number(),
+                "--frames" => config.frames = number() as usize,
+                "--output" => config.output = PathBuf::from(&value),
+                _ => panic!("unknown option {}", arg),
+            }
+        }
+        config
+    }
+
+    fn world_size(&self) -> f32 {
+        (self.chunks * self.chunk_size * self.tile_size) as f32
+    }
+}
+
+#[derive(Default)]
+struct Metrics {
+    spawned_at: Option<Instant>,
+    chunks_baked: usize,
+    spawn_latency: Option<f64>,
+    frame: usize,
+    frame_times: Vec<f64>,
+    bytes_uploaded: Vec<usize>,
+}
+
+fn setup(
+    commands: &mut Commands,
+    config: Res<Config>,
+    mut metrics: ResMut<Metrics>,
+    mut textures: ResMut<Assets<Texture>>,
+    mut atlases: ResMut<Assets<TextureAtlas>>,
+    mut registry: ResMut<TileRegistry>,
+    mut tile_atlas: ResMut<TileAtlas>,
+) {
+    // a generated atlas with a row of tiles in different colors, with a darker border so the
+    // tiles can be told apart
+    let tile_size = config.tile_size;
+    let mut texture = Texture::new_fill(
+        Extent3d::new(tile_size * TILE_KINDS as u32, tile_size, 1),
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    for kind in 0..TILE_KINDS as u32 {
+        let color = Srgba::from(Color::hsl(
+            kind as f32 * 360.0 / TILE_KINDS as f32,
+            0.6,
+            0.5,
+        ));
+        let pixel = [
+            (color.red * 255.0) as u8,
+            (color.green * 255.0) as u8,
+            (color.blue * 255.0) as u8,
+            255,
+        ];
+        texture.fill_rect(
+            [kind * tile_size + 1, 1],
+            [(kind + 1) * tile_size - 1, tile_size - 1],
+            &pixel,
+        );
+        registry
+            .register(TileKind {
+                sprite: Some(kind),
+                ..TileKind::new(TileId(kind as u16 + 1), format!("tile_{}", kind))
+            })
+            .unwrap();
+    }
+    let texture = textures.add(texture);
+    tile_atlas.atlas = atlases.add(TextureAtlas::from_grid(
+        texture,
+        Vec2::new(tile_size as f32, tile_size as f32),
+        TILE_KINDS as usize,
+        1,
+    ));
+    tile_atlas.tile_size = tile_size;
+
+    commands.spawn(Camera2dBundle {
+        transform: Transform::from_scale(Vec3::new(4.0, 4.0, 1.0)),
+        ..Default::default()
+    });
+    for chunk_y in 0..config.chunks as i32 {
+        for chunk_x in 0..config.chunks as i32 {
+            let mut chunk = Chunk::new(config.chunk_size);
+            for y in 0..config.chunk_size {
+                for x in 0..config.chunk_size {
+                    let kind = rand::random::<u16>() % TILE_KINDS + 1;
+                    chunk.set(x, y, TileId(kind));
+                }
+            }
+            commands.spawn(ChunkBundle::new(
+                IVec2::new(chunk_x, chunk_y),
+                chunk,
+                tile_size,
+            ));
+        }
+    }
+    metrics.spawned_at = Some(Instant::now());
+}
+
+/// Moves the camera back and forth across the world, one step per frame, so every run takes the
+/// same path regardless of its frame rate
+fn pan_camera(
+    config: Res<Config>,
+    metrics: Res<Metrics>,
+    mut cameras: Query<&mut Transform, With<Camera>>,
+) {
+    let progress = metrics.frame as f32 / config.frames as f32;
+    let angle = progress * std::f32::consts::PI * 2.0;
+    let half_world = config.world_size() / 2.0;
+    let position = Vec2::new(
+        half_world + half_world * 0.8 * angle.sin(),
+        half_world + half_world * 0.8 * (angle * 2.0).sin(),
+    );
+    for mut transform in cameras.iter_mut() {
+        transform.translation = position.extend(transform.translation.z);
+    }
+}
+
+fn record_metrics(
+    config: Res<Config>,
+    time: Res<Time>,
+    stats: Res<TilemapStats>,
+    mut metrics: ResMut<Metrics>,
+    mut app_exit_events: ResMut<Events<AppExit>>,
+) {
+    let total_chunks = (config.chunks * config.chunks) as usize;
+    if metrics.spawn_latency.is_none() {
+        metrics.chunks_baked += stats.chunks_baked;
+        if metrics.chunks_baked >= total_chunks {
+            let spawned_at = metrics.spawned_at.unwrap();
+            metrics.spawn_latency = Some(spawned_at.elapsed().as_secs_f64());
+        }
+        return;
+    }
+
+    metrics.frame_times.push(time.delta_seconds_f64());
+    metrics.bytes_uploaded.push(stats.bytes_uploaded);
+    metrics.frame += 1;
+    if metrics.frame >= config.frames {
+        write_results(&config, &metrics);
+        app_exit_events.send(AppExit);
+    }
+}
+
+fn write_results(config: &Config, metrics: &Metrics) {
+    let mut frame_times = metrics.frame_times.clone();
+    frame_times.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    // the fps that this share of frames reached or exceeded
+    let fps_percentile = |percentile: f64| {
+        let index = ((frame_times.len() - 1) as f64 * (1.0 - percentile)).round() as usize;
+        1.0 / frame_times[index]
+    };
+    let mean_frame_time = frame_times.iter().sum::<f64>() / frame_times.len() as f64;
+    let bytes_uploaded = metrics.bytes_uploaded.iter().sum::<usize>();
+    let max_bytes_uploaded = metrics.bytes_uploaded.iter().max().copied().unwrap_or(0);
+    let tiles = (config.chunks * config.chunks * config.chunk_size * config.chunk_size) as usize;
+
+    let write_header = !config.output.exists();
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&config.output)
+        .unwrap();
+    if write_header {
+        writeln!(
+            file,
+            "backend,chunks,tiles,frames,spawn_latency_ms,fps_mean,fps_p50,fps_p5,fps_p1,\
+             upload_bytes_total,upload_bytes_max_frame"
+        )
+        .unwrap();
+    }
+    writeln!(
+        file,
+        "{},{},{},{},{:.2},{:.2},{:.2},{:.2},{:.2},{},{}",
+        BACKEND,
+        config.chunks * config.chunks,
+        tiles,
+        frame_times.len(),
+        metrics.spawn_latency.unwrap() * 1000.0,
+        1.0 / mean_frame_time,
+        fps_percentile(0.5),
+        fps_percentile(0.05),
+        fps_percentile(0.01),
+        bytes_uploaded,
+        max_bytes_uploaded,
+    )
+    .unwrap();
+    println!(
+        "{} tiles: {:.1} fps mean, {:.1} fps 1% low, results appended to {}",
+        tiles,
+        1.0 / mean_frame_time,
+        fps_percentile(0.01),
+        config.output.display()
+    );
+}
@@ -0,0 +1,70 @@
+use bevy::{
+    prelude::*,
+    render::texture::{Extent3d, TextureDimension, TextureFormat},
+};
+
+/// This example shows the ways to change the cursor: pick a system cursor icon, draw a texture in
+/// place of the cursor, or grab the cursor so it can't leave the window
+fn main() {
+    App::build()
+        .add_plugins(DefaultPlugins)
+        .add_startup_system(setup.system())
+        .add_system(change_cursor.system())
+        .run();
+}
+
+struct Crosshair(Handle<Texture>);
+
+fn setup(commands: &mut Commands, mut textures: ResMut<Assets<Texture>>) {
+    // a 15x15 white cross with a transparent background
+    const SIZE: u32 = 15;
+    let mut texture = Texture::new_fill(
+        Extent3d::new(SIZE, SIZE, 1),
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    for i in 0..SIZE {
+        for &(x, y) in &[(i, SIZE / 2), (SIZE / 2, i)] {
+            let offset = ((y * SIZE + x) * 4) as usize;
+            texture.data[offset..offset + 4].copy_from_slice(&[255, 255, 255, 255]);
+        }
+    }
+    commands
+        .spawn(CameraUiBundle::default())
+        .insert_resource(Crosshair(textures.add(texture)));
+}
+
+/// Press 1 to cycle the system cursor icons, 2 to toggle the crosshair texture, and G to grab or
+/// release the cursor
+fn change_cursor(
+    input: Res<Input<KeyCode>>,
+    crosshair: Res<Crosshair>,
+    mut cursor_image: ResMut<CursorImage>,
+    mut windows: ResMut<Windows>,
+) {
+    // the window is only borrowed mutably when the cursor changes, as every change is sent to the
+    // windowing backend
+    if input.just_pressed(KeyCode::Key1) {
+        let window = windows.get_primary_mut().unwrap();
+        let icon = match window.cursor_icon() {
+            CursorIcon::Default => CursorIcon::Crosshair,
+            CursorIcon::Crosshair => CursorIcon::Hand,
+            CursorIcon::Hand => CursorIcon::Move,
+            _ => CursorIcon::Default,
+        };
+        window.set_cursor_icon(icon);
+    }
+    if input.just_pressed(KeyCode::Key2) {
+        cursor_image.texture = match cursor_image.texture {
+            Some(_) => None,
+            None => Some(crosshair.0.clone()),
+        };
+        // the hotspot is the center of the cross
+        cursor_image.hotspot = Vec2::new(7.0, 7.0);
+    }
+    if input.just_pressed(KeyCode::G) {
+        let window = windows.get_primary_mut().unwrap();
+        window.set_cursor_lock_mode(!window.cursor_locked());
+    }
+}
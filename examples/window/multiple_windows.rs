@@ -88,7 +88,7 @@ fn setup_pipeline(
         WindowTextureNode::new(
             window_id,
             TextureDescriptor {
-                format: TextureFormat::Depth32Float,
+                format: TextureFormat::Depth24PlusStencil8,
                 usage: TextureUsage::OUTPUT_ATTACHMENT,
                 sample_count: msaa.samples,
                 ..Default::default()
@@ -4,6 +4,7 @@ crates=(
     bevy_derive
     bevy_math
     bevy_tasks
+    bevy_noise
     bevy_ecs/macros
     bevy_ecs
     bevy_app
@@ -16,6 +17,7 @@ crates=(
     bevy_core
     bevy_diagnostic
     bevy_transform
+    bevy_tween
     bevy_window
     bevy_render
     bevy_input
@@ -23,8 +25,11 @@ crates=(
     bevy_pbr
     bevy_gltf
     bevy_scene
+    bevy_net
+    bevy_script
     bevy_sprite
     bevy_text
+    bevy_tilemap
     bevy_ui
     bevy_winit
     bevy_wgpu